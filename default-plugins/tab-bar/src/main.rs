@@ -32,6 +32,7 @@ struct State {
     mode_info: ModeInfo,
     tab_line: Vec<LinePart>,
     hide_swap_layout_indication: bool,
+    foreground_processes: BTreeMap<usize, String>, // tab position -> program in its focused pane
 }
 
 static ARROW_SEPARATOR: &str = "";
@@ -48,6 +49,7 @@ impl ZellijPlugin for State {
         subscribe(&[
             EventType::TabUpdate,
             EventType::ModeUpdate,
+            EventType::PaneUpdate,
             EventType::Mouse,
         ]);
     }
@@ -75,6 +77,23 @@ impl ZellijPlugin for State {
                     eprintln!("Could not find active tab.");
                 }
             },
+            Event::PaneUpdate(pane_manifest) => {
+                let foreground_processes: BTreeMap<usize, String> = pane_manifest
+                    .panes
+                    .iter()
+                    .filter_map(|(tab_position, panes)| {
+                        panes
+                            .iter()
+                            .find(|p| p.is_focused && !p.is_plugin && !p.is_floating)
+                            .and_then(|p| p.foreground_process.clone())
+                            .map(|foreground_process| (*tab_position, foreground_process))
+                    })
+                    .collect();
+                if self.foreground_processes != foreground_processes {
+                    should_render = true;
+                }
+                self.foreground_processes = foreground_processes;
+            },
             Event::Mouse(me) => match me {
                 Mouse::LeftClick(_, col) => {
                     let tab_to_focus = get_tab_to_focus(&self.tab_line, self.active_tab_idx, col);
@@ -111,6 +130,16 @@ impl ZellijPlugin for State {
         let mut is_alternate_tab = false;
         for t in &mut self.tabs {
             let mut tabname = t.name.clone();
+            let has_default_name = tabname
+                .strip_prefix("Tab #")
+                .map(|index| index.parse::<usize>().is_ok())
+                .unwrap_or(false);
+            if has_default_name {
+                // tabs that were not renamed show the program running in their focused pane
+                if let Some(foreground_process) = self.foreground_processes.get(&t.position) {
+                    tabname = foreground_process.clone();
+                }
+            }
             if t.active && self.mode_info.mode == InputMode::RenameTab {
                 if tabname.is_empty() {
                     tabname = String::from("Enter name...");
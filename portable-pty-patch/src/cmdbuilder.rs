@@ -1,3 +1,5 @@
+#[cfg(windows)]
+use crate::cmdline;
 #[cfg(unix)]
 use anyhow::Context;
 #[cfg(feature = "serde_support")]
@@ -666,8 +668,6 @@ impl CommandBuilder {
     }
 
     pub(crate) fn cmdline(&self) -> anyhow::Result<(Vec<u16>, Vec<u16>)> {
-        let mut cmdline = Vec::<u16>::new();
-
         let exe: OsString = if self.is_default_prog() {
             self.get_env("ComSpec")
                 .unwrap_or(OsStr::new("cmd.exe"))
@@ -675,73 +675,50 @@ impl CommandBuilder {
         } else {
             self.search_path(&self.args[0])
         };
+        let exe: Vec<u16> = exe.encode_wide().collect();
 
-        Self::append_quoted(&exe, &mut cmdline);
-
-        // Ensure that we nul terminate the module name, otherwise we'll
-        // ask CreateProcessW to start something random!
-        let mut exe: Vec<u16> = exe.encode_wide().collect();
-        exe.push(0);
-
+        let mut args = vec![];
         for arg in self.args.iter().skip(1) {
-            cmdline.push(' ' as u16);
             anyhow::ensure!(
                 !arg.encode_wide().any(|c| c == 0),
                 "invalid encoding for command line argument {:?}",
                 arg
             );
-            Self::append_quoted(arg, &mut cmdline);
+            args.push(arg.encode_wide().collect::<Vec<u16>>());
         }
-        // Ensure that the command line is nul terminated too!
-        cmdline.push(0);
-        Ok((exe, cmdline))
-    }
-
-    // Borrowed from https://github.com/hniksic/rust-subprocess/blob/873dfed165173e52907beb87118b2c0c05d8b8a1/src/popen.rs#L1117
-    // which in turn was translated from ArgvQuote at http://tinyurl.com/zmgtnls
-    fn append_quoted(arg: &OsStr, cmdline: &mut Vec<u16>) {
-        if !arg.is_empty()
-            && !arg.encode_wide().any(|c| {
-                c == ' ' as u16
-                    || c == '\t' as u16
-                    || c == '\n' as u16
-                    || c == '\x0b' as u16
-                    || c == '\"' as u16
-            })
-        {
-            cmdline.extend(arg.encode_wide());
-            return;
-        }
-        cmdline.push('"' as u16);
-
-        let arg: Vec<_> = arg.encode_wide().collect();
-        let mut i = 0;
-        while i < arg.len() {
-            let mut num_backslashes = 0;
-            while i < arg.len() && arg[i] == '\\' as u16 {
-                i += 1;
-                num_backslashes += 1;
-            }
 
-            if i == arg.len() {
-                for _ in 0..num_backslashes * 2 {
-                    cmdline.push('\\' as u16);
-                }
-                break;
-            } else if arg[i] == b'"' as u16 {
-                for _ in 0..num_backslashes * 2 + 1 {
-                    cmdline.push('\\' as u16);
-                }
-                cmdline.push(arg[i]);
-            } else {
-                for _ in 0..num_backslashes {
-                    cmdline.push('\\' as u16);
+        let (mut exe, mut cmdline) = if cmdline::is_batch_file(&exe) {
+            // CreateProcessW would run the batch file through cmd.exe itself,
+            // but with the arguments quoted for the C runtime rather than
+            // for cmd.exe
+            let cmd_exe: Vec<u16> = self
+                .get_env("ComSpec")
+                .unwrap_or(OsStr::new("cmd.exe"))
+                .encode_wide()
+                .collect();
+            let cmdline = cmdline::batch_file_command_line(&cmd_exe, &exe, &args)?;
+            (cmd_exe, cmdline)
+        } else {
+            let mut cmdline = Vec::<u16>::new();
+            cmdline::append_program(&exe, &mut cmdline);
+            let is_cmd_exe = cmdline::is_cmd_exe(&exe);
+            for arg in &args {
+                cmdline.push(' ' as u16);
+                if is_cmd_exe {
+                    cmdline::append_cmd_arg(arg, &mut cmdline, false)?;
+                } else {
+                    cmdline::append_arg(arg, &mut cmdline);
                 }
-                cmdline.push(arg[i]);
             }
-            i += 1;
-        }
-        cmdline.push('"' as u16);
+            (exe, cmdline)
+        };
+
+        // Ensure that we nul terminate the module name, otherwise we'll
+        // ask CreateProcessW to start something random!
+        exe.push(0);
+        // Ensure that the command line is nul terminated too!
+        cmdline.push(0);
+        Ok((exe, cmdline))
     }
 }
 
@@ -0,0 +1,533 @@
+//! Building Windows command lines.
+//!
+//! A Windows process receives a single command line string and each program
+//! splits it into arguments itself. Most programs use the rules of the
+//! Microsoft C runtime (which `CommandLineToArgvW` shares), but `cmd.exe`
+//! has its own: it expands `%VAR%` anywhere, `"` can only toggle quoting and
+//! `& | < > ^ ( )` are operators outside of quotes. Batch files are always
+//! run through `cmd.exe`, even when they are started directly.
+//!
+//! The functions here work on UTF-16 so that they can be used on the encoded
+//! `OsStr`s of the command builder, and tested on any platform.
+
+use anyhow::ensure;
+
+const QUOTE: u16 = b'"' as u16;
+const BACKSLASH: u16 = b'\\' as u16;
+const PERCENT: u16 = b'%' as u16;
+const SPACE: u16 = b' ' as u16;
+
+/// Appends the program name (`argv[0]`), which the C runtime parses
+/// differently from the other arguments: it ends at the next quote or,
+/// when unquoted, the next whitespace, and backslashes are never escapes.
+pub fn append_program(program: &[u16], cmdline: &mut Vec<u16>) {
+    let needs_quotes =
+        program.is_empty() || program.iter().any(|c| *c == SPACE || *c == b'\t' as u16);
+    if needs_quotes {
+        cmdline.push(QUOTE);
+    }
+    cmdline.extend(program);
+    if needs_quotes {
+        cmdline.push(QUOTE);
+    }
+}
+
+/// Appends an argument quoted so that the C runtime and `CommandLineToArgvW`
+/// parse it back unchanged.
+// Borrowed from https://github.com/hniksic/rust-subprocess/blob/873dfed165173e52907beb87118b2c0c05d8b8a1/src/popen.rs#L1117
+// which in turn was translated from ArgvQuote at http://tinyurl.com/zmgtnls
+pub fn append_arg(arg: &[u16], cmdline: &mut Vec<u16>) {
+    if !arg.is_empty()
+        && !arg.iter().any(|c| {
+            *c == SPACE
+                || *c == b'\t' as u16
+                || *c == b'\n' as u16
+                || *c == b'\x0b' as u16
+                || *c == QUOTE
+        })
+    {
+        cmdline.extend(arg);
+        return;
+    }
+    cmdline.push(QUOTE);
+
+    let mut i = 0;
+    while i < arg.len() {
+        let mut num_backslashes = 0;
+        while i < arg.len() && arg[i] == BACKSLASH {
+            i += 1;
+            num_backslashes += 1;
+        }
+
+        if i == arg.len() {
+            for _ in 0..num_backslashes * 2 {
+                cmdline.push(BACKSLASH);
+            }
+            break;
+        } else if arg[i] == QUOTE {
+            for _ in 0..num_backslashes * 2 + 1 {
+                cmdline.push(BACKSLASH);
+            }
+            cmdline.push(arg[i]);
+        } else {
+            for _ in 0..num_backslashes {
+                cmdline.push(BACKSLASH);
+            }
+            cmdline.push(arg[i]);
+        }
+        i += 1;
+    }
+    cmdline.push(QUOTE);
+}
+
+/// Appends an argument for a command line that `cmd.exe` will read.
+///
+/// Arguments containing whitespace or characters that `cmd.exe` treats
+/// specially are quoted, with embedded quotes doubled (`""`), which keeps
+/// `cmd.exe` from ever seeing them outside of a quoted section and which the
+/// C runtime reads back as a single quote.
+///
+/// With `escape_variables`, `%` is followed by `%cd:~,%` (an empty substring
+/// of the `cd` variable) so that `cmd.exe` cannot expand `%VAR%`, and every
+/// ASCII symbol not known to be safe is quoted. This is what arguments to
+/// batch files need to arrive verbatim. Without it, `%VAR%` is left for
+/// `cmd.exe` to expand, as it would be when typed at its prompt.
+pub fn append_cmd_arg(
+    arg: &[u16],
+    cmdline: &mut Vec<u16>,
+    escape_variables: bool,
+) -> anyhow::Result<()> {
+    ensure!(
+        !arg.iter().any(|c| *c == b'\r' as u16 || *c == b'\n' as u16),
+        "cmd.exe cannot be passed an argument spanning several lines: {:?}",
+        String::from_utf16_lossy(arg)
+    );
+    // quoting also keeps a trailing backslash from escaping the quote a
+    // batch file might add around it with "%~1"
+    let mut quote = arg.is_empty() || (escape_variables && arg.last() == Some(&BACKSLASH));
+    for c in char::decode_utf16(arg.iter().copied()).filter_map(|c| c.ok()) {
+        let needs_quotes = if escape_variables {
+            // rather than listing the ASCII symbols cmd.exe may act on, we
+            // only leave out the ones known to be safe
+            (c.is_ascii() && !(c.is_ascii_alphanumeric() || r"#$*+-./:?@\_".contains(c)))
+                || c.is_control()
+        } else {
+            c.is_whitespace() || "\"&|<>^(),;=".contains(c)
+        };
+        if needs_quotes {
+            quote = true;
+        }
+    }
+
+    if quote {
+        cmdline.push(QUOTE);
+    }
+    // backslashes are only special before a quote, where the C runtime reads
+    // 2n of them as n
+    let mut backslashes = 0;
+    for c in arg {
+        if *c == BACKSLASH {
+            backslashes += 1;
+        } else {
+            if *c == QUOTE {
+                cmdline.extend(std::iter::repeat_n(BACKSLASH, backslashes));
+                cmdline.push(QUOTE);
+            } else if *c == PERCENT && escape_variables {
+                cmdline.extend("%%cd:~,".encode_utf16());
+            }
+            backslashes = 0;
+        }
+        cmdline.push(*c);
+    }
+    if quote {
+        cmdline.extend(std::iter::repeat_n(BACKSLASH, backslashes));
+        cmdline.push(QUOTE);
+    }
+    Ok(())
+}
+
+/// Whether `program` is a batch file, which Windows runs through `cmd.exe`
+pub fn is_batch_file(program: &[u16]) -> bool {
+    has_extension(program, ".bat") || has_extension(program, ".cmd")
+}
+
+/// Whether `program` is `cmd.exe` itself
+pub fn is_cmd_exe(program: &[u16]) -> bool {
+    let file_name = program
+        .rsplit(|c| *c == BACKSLASH || *c == b'/' as u16)
+        .next()
+        .unwrap_or(program);
+    let file_name = String::from_utf16_lossy(file_name).to_lowercase();
+    file_name == "cmd" || file_name == "cmd.exe"
+}
+
+/// Builds the command line running the batch file `script` with `args`
+/// through `cmd_exe`.
+///
+/// `CreateProcessW` would do this on its own when asked to start a batch
+/// file, but would hand our command line to `cmd.exe` as it is, letting it
+/// expand variables and run operators found in the arguments.
+pub fn batch_file_command_line(
+    cmd_exe: &[u16],
+    script: &[u16],
+    args: &[Vec<u16>],
+) -> anyhow::Result<Vec<u16>> {
+    ensure!(
+        !script.contains(&QUOTE) && script.last() != Some(&BACKSLASH),
+        "invalid batch file name: {:?}",
+        String::from_utf16_lossy(script)
+    );
+    let mut cmdline = vec![];
+    append_program(cmd_exe, &mut cmdline);
+    // command extensions are needed for the %cd:~,% escape, delayed expansion
+    // would expand !VAR! as well and /d skips the AutoRun commands
+    cmdline.extend(" /e:ON /v:OFF /d /c \"".encode_utf16());
+    // with /c, cmd.exe strips the first and the last quote of the command
+    // line, hence the extra pair around the command
+    cmdline.push(QUOTE);
+    cmdline.extend(script);
+    cmdline.push(QUOTE);
+    for arg in args {
+        cmdline.push(SPACE);
+        append_cmd_arg(arg, &mut cmdline, true)?;
+    }
+    cmdline.push(QUOTE);
+    Ok(cmdline)
+}
+
+fn has_extension(program: &[u16], extension: &str) -> bool {
+    let extension: Vec<u16> = extension.encode_utf16().collect();
+    program.len() > extension.len()
+        && program[program.len() - extension.len()..]
+            .iter()
+            .zip(extension.iter())
+            .all(|(a, b)| {
+                char::from_u32(*a as u32).map(|c| c.to_ascii_lowercase() as u32) == Some(*b as u32)
+            })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().collect()
+    }
+
+    fn build(program: &str, args: &[&str]) -> String {
+        let mut cmdline = vec![];
+        append_program(&wide(program), &mut cmdline);
+        for arg in args {
+            cmdline.push(SPACE);
+            append_arg(&wide(arg), &mut cmdline);
+        }
+        String::from_utf16(&cmdline).unwrap()
+    }
+
+    /// Splits a command line the way the C runtime (and `CommandLineToArgvW`) does
+    fn parse(cmdline: &str) -> Vec<String> {
+        let chars: Vec<char> = cmdline.chars().collect();
+        let mut args = vec![];
+        let mut i = 0;
+
+        // the program name
+        let mut program = String::new();
+        if chars.first() == Some(&'"') {
+            i = 1;
+            while i < chars.len() && chars[i] != '"' {
+                program.push(chars[i]);
+                i += 1;
+            }
+            i += 1;
+        } else {
+            while i < chars.len() && chars[i] != ' ' && chars[i] != '\t' {
+                program.push(chars[i]);
+                i += 1;
+            }
+        }
+        args.push(program);
+
+        loop {
+            while i < chars.len() && (chars[i] == ' ' || chars[i] == '\t') {
+                i += 1;
+            }
+            if i >= chars.len() {
+                break;
+            }
+            let mut arg = String::new();
+            let mut in_quotes = false;
+            while i < chars.len() {
+                let c = chars[i];
+                if c == '\\' {
+                    let mut backslashes = 0;
+                    while i < chars.len() && chars[i] == '\\' {
+                        backslashes += 1;
+                        i += 1;
+                    }
+                    if i < chars.len() && chars[i] == '"' {
+                        arg.extend(std::iter::repeat_n('\\', backslashes / 2));
+                        if backslashes % 2 == 1 {
+                            arg.push('"');
+                            i += 1;
+                        }
+                    } else {
+                        arg.extend(std::iter::repeat_n('\\', backslashes));
+                    }
+                    continue;
+                }
+                if c == '"' {
+                    if in_quotes && chars.get(i + 1) == Some(&'"') {
+                        arg.push('"');
+                        i += 2;
+                        continue;
+                    }
+                    in_quotes = !in_quotes;
+                    i += 1;
+                    continue;
+                }
+                if !in_quotes && (c == ' ' || c == '\t') {
+                    break;
+                }
+                arg.push(c);
+                i += 1;
+            }
+            args.push(arg);
+        }
+        args
+    }
+
+    /// Expands `%VAR%` the way `cmd.exe` does on a command line, where
+    /// undefined variables are left as they are
+    fn expand_variables(cmdline: &str, env: &HashMap<&str, &str>) -> String {
+        let mut expanded = String::new();
+        let mut rest = cmdline;
+        while let Some(start) = rest.find('%') {
+            expanded.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            match after.find('%') {
+                Some(end) => {
+                    let name = &after[..end];
+                    if name == "cd:~," {
+                        // an empty substring of the current directory
+                        rest = &after[end + 1..];
+                    } else if let Some(value) = env.get(name.to_uppercase().as_str()) {
+                        expanded.push_str(value);
+                        rest = &after[end + 1..];
+                    } else {
+                        expanded.push('%');
+                        rest = after;
+                    }
+                }
+                None => {
+                    expanded.push('%');
+                    rest = after;
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Whether `cmd.exe` would act on an operator in `cmdline`, ie. one
+    /// outside of a quoted section
+    fn has_unquoted_operator(cmdline: &str) -> bool {
+        let mut in_quotes = false;
+        for c in cmdline.chars() {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            } else if !in_quotes && "&|<>^()".contains(c) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn tricky_args() -> Vec<&'static str> {
+        vec![
+            "",
+            "plain",
+            "with space",
+            "  leading and trailing  ",
+            "tab\there",
+            "\"",
+            "\"\"",
+            "say \"hi\"",
+            "\"quoted at both ends\"",
+            "\\",
+            "\\\\",
+            "C:\\Program Files\\",
+            "C:\\Program Files\\app.exe",
+            "ends with backslash\\",
+            "backslash before quote\\\"",
+            "\\\\\"\\\\",
+            "\\\\server\\share\\dir with spaces\\",
+            "%PATH%",
+            "100%",
+            "%%",
+            "%PATH",
+            "50% of %USERPROFILE%",
+            "!PATH!",
+            "a&b",
+            "a|b",
+            "<in>out",
+            "^caret^",
+            "(parens)",
+            "semi;colon,comma=equals",
+            "ünïcödé and 日本語",
+            "emoji 🦀",
+            "--flag=value with spaces",
+            "-c",
+            "/c",
+        ]
+    }
+
+    #[test]
+    fn program_name_is_quoted_only_when_needed() {
+        assert_eq!(
+            build("C:\\Windows\\notepad.exe", &[]),
+            "C:\\Windows\\notepad.exe"
+        );
+        assert_eq!(
+            build("C:\\Program Files\\app.exe", &[]),
+            "\"C:\\Program Files\\app.exe\""
+        );
+        // backslashes are not escapes in the program name, even before the closing quote
+        assert_eq!(build("C:\\My Apps\\", &[]), "\"C:\\My Apps\\\"");
+        assert_eq!(parse(&build("C:\\My Apps\\", &[]))[0], "C:\\My Apps\\");
+        assert_eq!(build("", &[]), "\"\"");
+    }
+
+    #[test]
+    fn args_are_quoted_only_when_needed() {
+        assert_eq!(build("app", &["a", "b"]), "app a b");
+        assert_eq!(build("app", &["a b"]), "app \"a b\"");
+        assert_eq!(build("app", &[""]), "app \"\"");
+        assert_eq!(build("app", &["C:\\dir\\"]), "app C:\\dir\\");
+        assert_eq!(build("app", &["C:\\a dir\\"]), "app \"C:\\a dir\\\\\"");
+        assert_eq!(build("app", &["say \"hi\""]), "app \"say \\\"hi\\\"\"");
+        assert_eq!(build("app", &["%PATH%"]), "app %PATH%");
+    }
+
+    #[test]
+    fn args_round_trip_through_the_c_runtime_rules() {
+        for arg in tricky_args() {
+            let cmdline = build("C:\\Program Files\\app.exe", &[arg, "next"]);
+            assert_eq!(
+                parse(&cmdline),
+                vec!["C:\\Program Files\\app.exe", arg, "next"],
+                "command line: {}",
+                cmdline
+            );
+        }
+    }
+
+    #[test]
+    fn all_args_round_trip_together() {
+        let args = tricky_args();
+        let cmdline = build("app.exe", &args);
+        let mut expected = vec!["app.exe"];
+        expected.extend(args);
+        assert_eq!(parse(&cmdline), expected);
+    }
+
+    #[test]
+    fn batch_file_args_round_trip_through_cmd_exe() {
+        let env: HashMap<&str, &str> = [
+            ("PATH", "C:\\Windows"),
+            ("USERPROFILE", "C:\\Users\\me"),
+            ("CD", "C:\\here"),
+        ]
+        .iter()
+        .copied()
+        .collect();
+        for arg in tricky_args() {
+            let args = vec![wide(arg), wide("next")];
+            let cmdline = batch_file_command_line(
+                &wide("C:\\Windows\\system32\\cmd.exe"),
+                &wide("C:\\scripts\\my script.bat"),
+                &args,
+            )
+            .unwrap();
+            let cmdline = String::from_utf16(&cmdline).unwrap();
+            let prefix = "C:\\Windows\\system32\\cmd.exe /e:ON /v:OFF /d /c \"";
+            assert!(cmdline.starts_with(prefix), "command line: {}", cmdline);
+            assert!(cmdline.ends_with('"'), "command line: {}", cmdline);
+            // what cmd.exe runs once it strips the outer quotes
+            let command = &cmdline[prefix.len()..cmdline.len() - 1];
+            let expanded = expand_variables(command, &env);
+            assert!(
+                !has_unquoted_operator(&expanded),
+                "cmd.exe would act on an operator in: {}",
+                expanded
+            );
+            assert_eq!(
+                parse(&expanded),
+                vec!["C:\\scripts\\my script.bat", arg, "next"],
+                "command line: {}",
+                cmdline
+            );
+        }
+    }
+
+    #[test]
+    fn batch_file_args_do_not_expand_variables() {
+        let cmdline =
+            batch_file_command_line(&wide("cmd.exe"), &wide("build.cmd"), &[wide("%PATH%")])
+                .unwrap();
+        assert_eq!(
+            String::from_utf16(&cmdline).unwrap(),
+            "cmd.exe /e:ON /v:OFF /d /c \"\"build.cmd\" \"%%cd:~,%PATH%%cd:~,%\"\""
+        );
+    }
+
+    #[test]
+    fn batch_file_args_spanning_lines_are_refused() {
+        for arg in ["line\nbreak", "carriage\rreturn"] {
+            assert!(
+                batch_file_command_line(&wide("cmd.exe"), &wide("a.bat"), &[wide(arg)]).is_err()
+            );
+        }
+        assert!(batch_file_command_line(&wide("cmd.exe"), &wide("a\".bat"), &[]).is_err());
+    }
+
+    #[test]
+    fn cmd_exe_args_keep_variables_but_not_operators() {
+        let env: HashMap<&str, &str> = [("PATH", "C:\\Windows")].iter().copied().collect();
+        let mut cmdline = wide("cmd.exe");
+        for arg in ["/c", "echo", "%PATH%", "a&b", "say \"hi\"", "plain"] {
+            cmdline.push(SPACE);
+            append_cmd_arg(&wide(arg), &mut cmdline, false).unwrap();
+        }
+        let cmdline = String::from_utf16(&cmdline).unwrap();
+        assert_eq!(
+            cmdline,
+            "cmd.exe /c echo %PATH% \"a&b\" \"say \"\"hi\"\"\" plain"
+        );
+        let expanded = expand_variables(&cmdline, &env);
+        assert!(!has_unquoted_operator(&expanded));
+        assert_eq!(
+            parse(&expanded),
+            vec![
+                "cmd.exe",
+                "/c",
+                "echo",
+                "C:\\Windows",
+                "a&b",
+                "say \"hi\"",
+                "plain"
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_files_and_cmd_exe_are_recognized() {
+        assert!(is_batch_file(&wide("C:\\scripts\\build.bat")));
+        assert!(is_batch_file(&wide("C:\\scripts\\BUILD.CMD")));
+        assert!(!is_batch_file(&wide("C:\\scripts\\build.exe")));
+        assert!(!is_batch_file(&wide(".bat")));
+        assert!(is_cmd_exe(&wide("C:\\Windows\\System32\\CMD.EXE")));
+        assert!(is_cmd_exe(&wide("cmd")));
+        assert!(!is_cmd_exe(&wide("C:\\tools\\mycmd.exe")));
+    }
+}
@@ -49,6 +49,7 @@ use std::os::windows::prelude::{AsRawHandle, RawHandle};
 
 pub mod cmdbuilder;
 pub use cmdbuilder::CommandBuilder;
+pub mod cmdline;
 
 #[cfg(unix)]
 pub mod unix;
@@ -170,22 +171,52 @@ impl_downcast!(ChildKiller);
 
 /// Represents the slave side of a pty.
 /// Can be used to spawn processes into the pty.
-pub trait SlavePty {
+pub trait SlavePty: Downcast {
     /// Spawns the command specified by the provided CommandBuilder
     fn spawn_command(&self, cmd: CommandBuilder) -> Result<Box<dyn Child + Send + Sync>, Error>;
 }
+impl_downcast!(SlavePty);
 
 /// Represents the exit status of a child process.
 #[derive(Debug, Clone)]
 pub struct ExitStatus {
     code: u32,
     signal: Option<String>,
+    terminated: bool,
 }
 
+/// Names of the NTSTATUS codes that processes commonly exit with on Windows
+/// when they crash or are interrupted
+const NTSTATUS_NAMES: &[(u32, &str)] = &[
+    (0xC0000005, "STATUS_ACCESS_VIOLATION"),
+    (0xC000001D, "STATUS_ILLEGAL_INSTRUCTION"),
+    (0xC0000094, "STATUS_INTEGER_DIVIDE_BY_ZERO"),
+    (0xC00000FD, "STATUS_STACK_OVERFLOW"),
+    (0xC0000135, "STATUS_DLL_NOT_FOUND"),
+    (0xC000013A, "STATUS_CONTROL_C_EXIT"),
+    (0xC0000142, "STATUS_DLL_INIT_FAILED"),
+    (0xC0000374, "STATUS_HEAP_CORRUPTION"),
+    (0xC0000409, "STATUS_STACK_BUFFER_OVERRUN"),
+];
+
 impl ExitStatus {
     /// Construct an ExitStatus from a process return code
     pub fn with_exit_code(code: u32) -> Self {
-        Self { code, signal: None }
+        Self {
+            code,
+            signal: None,
+            terminated: false,
+        }
+    }
+
+    /// Construct an ExitStatus for a process that was forcibly terminated
+    /// and left with this return code
+    pub fn with_terminated_exit_code(code: u32) -> Self {
+        Self {
+            code,
+            signal: None,
+            terminated: true,
+        }
     }
 
     /// Construct an ExitStatus from a signal name
@@ -193,6 +224,7 @@ impl ExitStatus {
         Self {
             code: 1,
             signal: Some(signal.to_string()),
+            terminated: true,
         }
     }
 
@@ -209,10 +241,37 @@ impl ExitStatus {
         self.code
     }
 
+    /// Returns the exit code reinterpreted as a signed integer, the way
+    /// Windows shells report it (eg. -1073741510 for STATUS_CONTROL_C_EXIT)
+    pub fn exit_code_i32(&self) -> i32 {
+        self.code as i32
+    }
+
     /// Returns the signal if present that this ExitStatus was constructed with
     pub fn signal(&self) -> Option<&str> {
         self.signal.as_deref()
     }
+
+    /// Returns true if the process was terminated by a signal or forcibly
+    /// killed rather than exiting on its own
+    pub fn was_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Returns true if the exit code is an NTSTATUS error code, which is
+    /// what a Windows process exits with when it crashes or is interrupted
+    pub fn is_ntstatus_error(&self) -> bool {
+        self.code & 0xC000_0000 == 0xC000_0000
+    }
+
+    /// Returns the name of the NTSTATUS code that the process exited with,
+    /// if it is a well known one
+    pub fn ntstatus_name(&self) -> Option<&'static str> {
+        NTSTATUS_NAMES
+            .iter()
+            .find(|(code, _)| *code == self.code)
+            .map(|(_, name)| *name)
+    }
 }
 
 impl From<std::process::ExitStatus> for ExitStatus {
@@ -233,6 +292,7 @@ impl From<std::process::ExitStatus> for ExitStatus {
                 return ExitStatus {
                     code: status.code().map(|c| c as u32).unwrap_or(1),
                     signal: Some(signal),
+                    terminated: true,
                 };
             }
         }
@@ -243,7 +303,7 @@ impl From<std::process::ExitStatus> for ExitStatus {
                 .map(|c| c as u32)
                 .unwrap_or_else(|| if status.success() { 0 } else { 1 });
 
-        ExitStatus { code, signal: None }
+        ExitStatus::with_exit_code(code)
     }
 }
 
@@ -254,7 +314,13 @@ impl std::fmt::Display for ExitStatus {
         } else {
             match &self.signal {
                 Some(sig) => write!(fmt, "Terminated by {}", sig),
-                None => write!(fmt, "Exited with code {}", self.code),
+                None => match self.ntstatus_name() {
+                    Some(name) => write!(fmt, "Exited with code 0x{:08X} ({})", self.code, name),
+                    None if self.is_ntstatus_error() => {
+                        write!(fmt, "Exited with code 0x{:08X}", self.code)
+                    },
+                    None => write!(fmt, "Exited with code {}", self.code),
+                },
             }
         }
     }
@@ -415,3 +481,33 @@ pub fn native_pty_system() -> Box<dyn PtySystem + Send> {
 pub type NativePtySystem = unix::UnixPtySystem;
 #[cfg(windows)]
 pub type NativePtySystem = win::conpty::ConPtySystem;
+
+#[cfg(test)]
+mod tests {
+    use super::ExitStatus;
+
+    #[test]
+    fn exit_status_reports_ntstatus_codes() {
+        let status = ExitStatus::with_exit_code(0xC000013A);
+        assert!(!status.success());
+        assert!(!status.was_terminated());
+        assert!(status.is_ntstatus_error());
+        assert_eq!(status.exit_code_i32(), -1073741510);
+        assert_eq!(status.ntstatus_name(), Some("STATUS_CONTROL_C_EXIT"));
+        assert_eq!(
+            status.to_string(),
+            "Exited with code 0xC000013A (STATUS_CONTROL_C_EXIT)"
+        );
+    }
+
+    #[test]
+    fn exit_status_tells_terminated_processes_apart() {
+        let exited = ExitStatus::with_exit_code(1);
+        let terminated = ExitStatus::with_terminated_exit_code(1);
+        assert_eq!(exited.exit_code(), terminated.exit_code());
+        assert!(!exited.was_terminated());
+        assert!(terminated.was_terminated());
+        assert!(!terminated.is_ntstatus_error());
+        assert!(ExitStatus::with_signal("Killed").was_terminated());
+    }
+}
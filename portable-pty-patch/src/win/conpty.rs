@@ -1,32 +1,217 @@
 use crate::cmdbuilder::CommandBuilder;
+use crate::win::error::ConPtyError;
 use crate::win::psuedocon::PsuedoCon;
-use crate::{Child, MasterPty, PtyPair, PtySize, PtySystem, SlavePty};
+pub use crate::win::psuedocon::{set_conpty_host, ConPtyHost};
+use crate::win::SuspendedWinChild;
+use crate::{Child, ChildKiller, ExitStatus, MasterPty, PtyPair, PtySize, PtySystem, SlavePty};
 use anyhow::Error;
 use filedescriptor::{FileDescriptor, Pipe};
-use std::os::windows::io::FromRawHandle;
-use std::sync::{Arc, Mutex};
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, Weak};
+use std::time::{Duration, Instant};
+use std::{iter, mem, ptr, thread};
+use winapi::shared::minwindef::{DWORD, TRUE};
+use winapi::shared::sddl::{ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1};
+use winapi::shared::winerror::ERROR_IO_PENDING;
+use winapi::um::fileapi::{CreateFileW, ReadFile, WriteFile, OPEN_EXISTING};
 use winapi::um::handleapi::INVALID_HANDLE_VALUE;
-use winapi::um::namedpipeapi::CreatePipe;
-use winapi::um::minwinbase::SECURITY_ATTRIBUTES;
+use winapi::um::ioapiset::GetOverlappedResult;
+use winapi::um::minwinbase::{OVERLAPPED, SECURITY_ATTRIBUTES};
+use winapi::um::namedpipeapi::{CreateNamedPipeW, CreatePipe, PeekNamedPipe};
+use winapi::um::synchapi::CreateEventW;
+use winapi::um::winbase::{
+    LocalFree, FILE_FLAG_FIRST_PIPE_INSTANCE, FILE_FLAG_OVERLAPPED, PIPE_ACCESS_INBOUND,
+    PIPE_ACCESS_OUTBOUND, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE,
+    PIPE_WAIT,
+};
 use winapi::um::wincon::COORD;
-use winapi::um::winnt::HANDLE;
+use winapi::um::winnt::{GENERIC_READ, GENERIC_WRITE, HANDLE, PSECURITY_DESCRIPTOR};
+
+/// Buffer size of the pipes created for overlapped I/O, matching the default
+/// size of anonymous pipes
+const OVERLAPPED_PIPE_BUFFER_SIZE: DWORD = 4096;
+
+/// How long closing a pseudoconsole waits for its output pipe to reach EOF
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Resizes requested within this long of each other are coalesced into one
+/// ResizePseudoConsole call, so that dragging a window edge does not make the
+/// shell redraw for every intermediate size
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(40);
+
+/// How long a resize waits for a write to the pseudoconsole in progress to
+/// finish before resizing regardless
+const RESIZE_WRITE_WAIT: Duration = Duration::from_millis(100);
+
+/// How often the exit of helper processes is checked for
+const HELPER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The largest number of rows or columns of a pseudoconsole, since COORD
+/// holds them as SHORTs; anything larger would wrap around to a negative size
+const MAX_CONSOLE_DIMENSION: u16 = i16::MAX as u16;
+
+/// How long setting the codepage of a pseudoconsole waits for chcp to exit
+const SET_CODEPAGE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Makes the names of the pipes created by this process unique
+static PIPE_SERIAL: AtomicUsize = AtomicUsize::new(0);
 
 #[derive(Default)]
-pub struct ConPtySystem {}
+pub struct ConPtySystem {
+    overlapped: bool,
+    passthrough: bool,
+    /// SDDL of the security descriptor of the pipes to the pseudoconsole
+    security_descriptor: Option<String>,
+}
+
+/// A security descriptor converted from SDDL, freed when dropped
+struct SecurityDescriptor(PSECURITY_DESCRIPTOR);
+
+impl SecurityDescriptor {
+    fn from_sddl(sddl: &str) -> io::Result<Self> {
+        let sddl: Vec<u16> = OsStr::new(sddl)
+            .encode_wide()
+            .chain(iter::once(0))
+            .collect();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        let ok = unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1 as DWORD,
+                &mut descriptor,
+                ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(descriptor))
+    }
+}
+
+impl Drop for SecurityDescriptor {
+    fn drop(&mut self) {
+        unsafe { LocalFree(self.0 as _) };
+    }
+}
+
+/// Attributes of a non-inheritable handle with the security descriptor
+/// `descriptor`, or the default one of this process
+fn security_attributes(descriptor: Option<&SecurityDescriptor>) -> SECURITY_ATTRIBUTES {
+    SECURITY_ATTRIBUTES {
+        nLength: mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor.map_or(ptr::null_mut(), |descriptor| descriptor.0),
+        bInheritHandle: 0,
+    }
+}
 
 impl ConPtySystem {
+    /// A `ConPtySystem` whose ptys connect to the pseudoconsole through pipes
+    /// opened for overlapped I/O, so that their output can be read with an
+    /// async runtime (see [`ConPtyMasterPty::try_clone_async_reader`]).
+    pub fn with_overlapped_io() -> Self {
+        Self {
+            overlapped: true,
+            ..Self::default()
+        }
+    }
+
+    /// Create pseudoconsoles in passthrough mode, in which conhost forwards
+    /// the output of programs using VT sequences without rendering it
+    /// itself. Only recent builds of conpty.dll support it.
+    pub fn with_passthrough_mode(mut self) -> Self {
+        self.passthrough = true;
+        self
+    }
+
+    /// Create the pipes connecting to the pseudoconsole with the security
+    /// descriptor described by `sddl`, eg. one whose DACL only grants the
+    /// current user access, rather than with the default one of this
+    /// process.
+    pub fn with_security_descriptor(mut self, sddl: &str) -> Self {
+        self.security_descriptor = Some(sddl.to_owned());
+        self
+    }
+
+    /// Create a named pipe whose end returned first is opened for overlapped
+    /// I/O, and whose end handed to the pseudoconsole is synchronous.
+    /// `inbound` pipes are read from the overlapped end. Like the anonymous
+    /// pipes, neither end is inheritable.
+    fn create_overlapped_pipe(
+        inbound: bool,
+        descriptor: Option<&SecurityDescriptor>,
+    ) -> anyhow::Result<(OwnedHandle, FileDescriptor)> {
+        let name = format!(
+            r"\\.\pipe\portable-pty-{}-{}",
+            std::process::id(),
+            PIPE_SERIAL.fetch_add(1, Ordering::Relaxed)
+        );
+        let name: Vec<u16> = OsStr::new(&name)
+            .encode_wide()
+            .chain(iter::once(0))
+            .collect();
+        let (open_mode, other_end_access) = if inbound {
+            (PIPE_ACCESS_INBOUND, GENERIC_WRITE)
+        } else {
+            (PIPE_ACCESS_OUTBOUND, GENERIC_READ)
+        };
+        let overlapped_end = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                open_mode | FILE_FLAG_OVERLAPPED | FILE_FLAG_FIRST_PIPE_INSTANCE,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                1,
+                OVERLAPPED_PIPE_BUFFER_SIZE,
+                OVERLAPPED_PIPE_BUFFER_SIZE,
+                0,
+                &mut security_attributes(descriptor),
+            )
+        };
+        if overlapped_end == INVALID_HANDLE_VALUE {
+            return Err(ConPtyError::PipeCreation(io::Error::last_os_error()).into());
+        }
+        let overlapped_end = unsafe { OwnedHandle::from_raw_handle(overlapped_end as _) };
+        let other_end = unsafe {
+            CreateFileW(
+                name.as_ptr(),
+                other_end_access,
+                0,
+                ptr::null_mut(),
+                OPEN_EXISTING,
+                0,
+                ptr::null_mut(),
+            )
+        };
+        if other_end == INVALID_HANDLE_VALUE {
+            return Err(ConPtyError::PipeCreation(io::Error::last_os_error()).into());
+        }
+        Ok((overlapped_end, unsafe {
+            FileDescriptor::from_raw_handle(other_end as _)
+        }))
+    }
+
     /// Create an anonymous pipe with a specified buffer size.
-    fn create_pipe_with_buffer(buffer_size: u32) -> anyhow::Result<Pipe> {
+    ///
+    /// Neither end is inheritable: CreatePseudoConsole duplicates the ends it
+    /// is given into conhost itself, and an inheritable handle would leak
+    /// into every process this one spawns with inheritance enabled (such as
+    /// `std::process::Command`), keeping the pipe open and delaying EOF
+    /// after the pane is closed. A `buffer_size` of 0 selects the default
+    /// size.
+    fn create_pipe_with_buffer(
+        buffer_size: u32,
+        descriptor: Option<&SecurityDescriptor>,
+    ) -> anyhow::Result<Pipe> {
         let mut read: HANDLE = INVALID_HANDLE_VALUE;
         let mut write: HANDLE = INVALID_HANDLE_VALUE;
-        let mut sa = SECURITY_ATTRIBUTES {
-            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
-            lpSecurityDescriptor: std::ptr::null_mut(),
-            bInheritHandle: 1,
-        };
+        let mut sa = security_attributes(descriptor);
         let result = unsafe { CreatePipe(&mut read, &mut write, &mut sa, buffer_size) };
         if result == 0 {
-            return Err(std::io::Error::last_os_error().into());
+            return Err(ConPtyError::PipeCreation(io::Error::last_os_error()).into());
         }
         Ok(Pipe {
             read: unsafe { FileDescriptor::from_raw_handle(read as _) },
@@ -37,27 +222,62 @@ impl ConPtySystem {
 
 impl PtySystem for ConPtySystem {
     fn openpty(&self, size: PtySize) -> anyhow::Result<PtyPair> {
-        let stdin = Pipe::new()?;
-        // Use default pipe buffer size (~4KB) to match tmux.
-        // Large buffers (1MB) let conhost batch output lazily;
-        // small buffers force eager flushing, reducing echo latency.
-        let stdout = Pipe::new()?;
+        let size = validate_size(size)?;
+        let descriptor = self
+            .security_descriptor
+            .as_deref()
+            .map(SecurityDescriptor::from_sddl)
+            .transpose()
+            .map_err(ConPtyError::PipeCreation)?;
+        let descriptor = descriptor.as_ref();
+        let (stdin_read, stdin_write, stdout_read, stdout_write) = if self.overlapped {
+            let (stdin_write, stdin_read) = Self::create_overlapped_pipe(false, descriptor)?;
+            let (stdout_read, stdout_write) = Self::create_overlapped_pipe(true, descriptor)?;
+            (
+                stdin_read,
+                PipeEnd::Overlapped(stdin_write),
+                PipeEnd::Overlapped(stdout_read),
+                stdout_write,
+            )
+        } else {
+            let pipe = || Self::create_pipe_with_buffer(0, descriptor);
+            let stdin = pipe()?;
+            // Use default pipe buffer size (~4KB) to match tmux.
+            // Large buffers (1MB) let conhost batch output lazily;
+            // small buffers force eager flushing, reducing echo latency.
+            let stdout = pipe()?;
+            (
+                stdin.read,
+                PipeEnd::Sync(stdin.write),
+                PipeEnd::Sync(stdout.read),
+                stdout.write,
+            )
+        };
 
         let con = PsuedoCon::new(
             COORD {
                 X: size.cols as i16,
                 Y: size.rows as i16,
             },
-            stdin.read,
-            stdout.write,
+            stdin_read,
+            stdout_write,
+            self.passthrough,
         )?;
 
         let master = ConPtyMasterPty {
             inner: Arc::new(Mutex::new(Inner {
                 con,
-                readable: stdout.read,
-                writable: Some(stdin.write),
+                readable: stdout_read,
+                writable: Some(stdin_write),
                 size,
+                shut_down: false,
+                io_lock: Arc::new(Mutex::new(())),
+                last_resize_request: None,
+                resize_scheduled: false,
+                root_child_pid: None,
+                helpers: vec![],
+                async_reader_cloned: false,
+                console_size_check_running: false,
             })),
         };
 
@@ -72,14 +292,258 @@ impl PtySystem for ConPtySystem {
     }
 }
 
+/// Our end of a pipe connected to the pseudoconsole
+enum PipeEnd {
+    Sync(FileDescriptor),
+    Overlapped(OwnedHandle),
+}
+
+/// Synchronous reads and writes on a handle opened for overlapped I/O, for
+/// the `MasterPty` reader and writer of ptys using overlapped pipes.
+pub(crate) struct OverlappedFile {
+    handle: OwnedHandle,
+    event: OwnedHandle,
+}
+
+impl OverlappedFile {
+    pub(crate) fn new(handle: OwnedHandle) -> io::Result<Self> {
+        let event = unsafe { CreateEventW(ptr::null_mut(), TRUE, 0, ptr::null()) };
+        if event.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self {
+            handle,
+            event: unsafe { OwnedHandle::from_raw_handle(event as _) },
+        })
+    }
+
+    /// Wait for an I/O operation started with `overlapped` to complete if
+    /// `started` reports that it is still pending.
+    fn complete(&self, started: i32, overlapped: &mut OVERLAPPED) -> io::Result<usize> {
+        if started == 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(ERROR_IO_PENDING as i32) {
+                return Err(err);
+            }
+        }
+        let mut transferred: DWORD = 0;
+        let ok = unsafe {
+            GetOverlappedResult(
+                self.handle.as_raw_handle() as _,
+                overlapped,
+                &mut transferred,
+                TRUE,
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(transferred as usize)
+    }
+
+    fn overlapped(&self) -> OVERLAPPED {
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        // The pipe may also have been handed to an async runtime, which
+        // associates it with its completion port. Setting the low-order bit
+        // of the event keeps the completion of this operation from being
+        // queued to that port, where it would be taken for one of its own.
+        overlapped.hEvent = (self.event.as_raw_handle() as usize | 1) as _;
+        overlapped
+    }
+}
+
+impl io::Read for OverlappedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut overlapped = self.overlapped();
+        let started = unsafe {
+            ReadFile(
+                self.handle.as_raw_handle() as _,
+                buf.as_mut_ptr() as _,
+                buf.len().min(DWORD::MAX as usize) as DWORD,
+                ptr::null_mut(),
+                &mut overlapped,
+            )
+        };
+        match self.complete(started, &mut overlapped) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(0),
+            result => result,
+        }
+    }
+}
+
+impl io::Write for OverlappedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut overlapped = self.overlapped();
+        let started = unsafe {
+            WriteFile(
+                self.handle.as_raw_handle() as _,
+                buf.as_ptr() as _,
+                buf.len().min(DWORD::MAX as usize) as DWORD,
+                ptr::null_mut(),
+                &mut overlapped,
+            )
+        };
+        self.complete(started, &mut overlapped)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+enum ReaderEnd {
+    Sync(FileDescriptor),
+    Overlapped(OverlappedFile),
+}
+
+/// A reader of the output of the pseudoconsole that can also be polled
+/// without blocking, see [`ConPtyReader::try_read`].
+pub struct ConPtyReader {
+    end: ReaderEnd,
+}
+
+impl ConPtyReader {
+    /// Read whatever output is available without blocking. Returns
+    /// `Ok(None)` if there is none yet, and `Ok(Some(0))` once the
+    /// pseudoconsole was closed.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        let handle = match &self.end {
+            ReaderEnd::Sync(readable) => readable.as_raw_handle(),
+            ReaderEnd::Overlapped(readable) => readable.handle.as_raw_handle(),
+        };
+        let mut available: DWORD = 0;
+        let ok = unsafe {
+            PeekNamedPipe(
+                handle as _,
+                ptr::null_mut(),
+                0,
+                ptr::null_mut(),
+                &mut available,
+                ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::BrokenPipe {
+                return Ok(Some(0));
+            }
+            return Err(err);
+        }
+        if available == 0 || buf.is_empty() {
+            return Ok(None);
+        }
+        // reading no more than is buffered in the pipe does not block
+        let len = buf.len().min(available as usize);
+        io::Read::read(self, &mut buf[..len]).map(Some)
+    }
+}
+
+impl io::Read for ConPtyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.end {
+            ReaderEnd::Sync(readable) => io::Read::read(readable, buf),
+            ReaderEnd::Overlapped(readable) => io::Read::read(readable, buf),
+        }
+    }
+}
+
 struct Inner {
     con: PsuedoCon,
-    readable: FileDescriptor,
-    writable: Option<FileDescriptor>,
+    readable: PipeEnd,
+    writable: Option<PipeEnd>,
     size: PtySize,
+    shut_down: bool,
+    /// Held while writing to the pseudoconsole and while resizing it, so
+    /// that conhost never sees a resize in the middle of a write
+    io_lock: Arc<Mutex<()>>,
+    last_resize_request: Option<Instant>,
+    resize_scheduled: bool,
+    /// The PID of the first process spawned through the slave, usually the
+    /// shell
+    root_child_pid: Option<u32>,
+    /// Helper processes that are still running, terminated on shutdown
+    helpers: Vec<(u32, Box<dyn ChildKiller + Send + Sync>)>,
+    /// Whether the output pipe was handed to an async runtime, which then
+    /// reads it until EOF
+    async_reader_cloned: bool,
+    /// Whether a helper checking the size of the console is running
+    console_size_check_running: bool,
 }
 
 impl Inner {
+    /// Tear the pseudoconsole down in the order that cannot hang: close its
+    /// input, keep draining its output on another thread while
+    /// ClosePseudoConsole waits for conhost to flush and exit, then wait up
+    /// to `timeout` for the output pipe to reach EOF. When an async reader
+    /// was cloned, that reader drains the output instead, since a second
+    /// reader would take bytes meant for it.
+    fn shutdown(&mut self, timeout: Duration) {
+        if self.shut_down {
+            return;
+        }
+        self.shut_down = true;
+        self.writable.take();
+        for (_, mut helper) in self.helpers.drain(..) {
+            let _ = helper.kill();
+        }
+
+        if self.async_reader_cloned {
+            self.con.close();
+            return;
+        }
+
+        let drained = self.clone_reader().ok().and_then(|mut reader| {
+            let (done_tx, done_rx) = mpsc::channel();
+            thread::Builder::new()
+                .name("conpty_drain".to_owned())
+                .spawn(move || {
+                    // Poll rather than block in ReadFile, so that the thread
+                    // is gone by the deadline even if conhost never exits
+                    let deadline = Instant::now() + timeout;
+                    let mut buf = [0u8; 4096];
+                    let reached_eof = loop {
+                        match reader.try_read(&mut buf) {
+                            Ok(Some(0)) | Err(_) => break true,
+                            Ok(Some(_)) => {},
+                            Ok(None) if Instant::now() >= deadline => break false,
+                            Ok(None) => thread::sleep(Duration::from_millis(10)),
+                        }
+                    };
+                    let _ = done_tx.send(reached_eof);
+                })
+                .ok()
+                .map(|_| done_rx)
+        });
+        if drained.is_none() {
+            log::warn!("cannot drain the pseudoconsole output, closing it may block");
+        }
+
+        self.con.close();
+
+        if let Some(done_rx) = drained {
+            if done_rx.recv() != Ok(true) {
+                log::warn!(
+                    "pseudoconsole output did not reach EOF within {:?} of closing it",
+                    timeout
+                );
+            }
+        }
+    }
+
+    fn clone_reader(&self) -> io::Result<ConPtyReader> {
+        let end = match &self.readable {
+            PipeEnd::Sync(readable) => ReaderEnd::Sync(
+                readable
+                    .try_clone()
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?,
+            ),
+            PipeEnd::Overlapped(readable) => {
+                ReaderEnd::Overlapped(OverlappedFile::new(readable.try_clone()?)?)
+            },
+        };
+        Ok(ConPtyReader { end })
+    }
+
     pub fn resize(
         &mut self,
         num_rows: u16,
@@ -87,6 +551,8 @@ impl Inner {
         pixel_width: u16,
         pixel_height: u16,
     ) -> Result<(), Error> {
+        let io_lock = self.io_lock.clone();
+        let _io = lock_for_resize(&io_lock);
         self.con.resize(COORD {
             X: num_cols as i16,
             Y: num_rows as i16,
@@ -99,6 +565,203 @@ impl Inner {
         };
         Ok(())
     }
+
+    /// Bring the pseudoconsole back to the size set last when a program
+    /// attached to it resized its console to `(cols, rows)`, or take that size
+    /// on if resizing it back fails.
+    fn reconcile_size(&mut self, (cols, rows): (u16, u16)) {
+        // A pending resize is going to overwrite whatever the console has now
+        if self.shut_down || self.resize_scheduled {
+            return;
+        }
+        if (cols, rows) == (self.size.cols, self.size.rows) {
+            return;
+        }
+        log::debug!(
+            "pseudoconsole is {}x{} rather than {}x{}, resizing it back",
+            cols,
+            rows,
+            self.size.cols,
+            self.size.rows
+        );
+        let size = self.size;
+        if let Err(e) = self.resize(size.rows, size.cols, size.pixel_width, size.pixel_height) {
+            log::warn!("failed to restore the pseudoconsole size: {:?}", e);
+            self.size.cols = cols;
+            self.size.rows = rows;
+        }
+    }
+}
+
+/// The console size a helper of [`ConPtyMasterPty::reconcile_console_size`]
+/// reported through `exit_code`, as `(cols, rows)`.
+fn console_size_from_exit_code(exit_code: u32) -> Option<(u16, u16)> {
+    let cols = (exit_code >> 16) as u16;
+    let rows = exit_code as u16;
+    if cols == 0 || rows == 0 || cols > MAX_CONSOLE_DIMENSION || rows > MAX_CONSOLE_DIMENSION {
+        return None;
+    }
+    Some((cols, rows))
+}
+
+/// Reject a size without rows or columns, which ResizePseudoConsole either
+/// refuses or wedges conhost with, and clamp the rest to what a console can
+/// hold.
+fn validate_size(size: PtySize) -> Result<PtySize, ConPtyError> {
+    if size.rows == 0 || size.cols == 0 {
+        return Err(ConPtyError::InvalidSize {
+            cols: size.cols,
+            rows: size.rows,
+        });
+    }
+    let clamped = PtySize {
+        rows: size.rows.min(MAX_CONSOLE_DIMENSION),
+        cols: size.cols.min(MAX_CONSOLE_DIMENSION),
+        ..size
+    };
+    if clamped != size {
+        log::warn!(
+            "clamping pseudoconsole size {}x{} to {}x{}",
+            size.cols,
+            size.rows,
+            clamped.cols,
+            clamped.rows
+        );
+    }
+    Ok(clamped)
+}
+
+/// Wait for a write in progress to finish, but give up after
+/// `RESIZE_WRITE_WAIT` since a write blocks for as long as the child does not
+/// read its input.
+fn lock_for_resize(io_lock: &Mutex<()>) -> Option<MutexGuard<'_, ()>> {
+    let started = Instant::now();
+    loop {
+        if let Ok(guard) = io_lock.try_lock() {
+            return Some(guard);
+        }
+        if started.elapsed() >= RESIZE_WRITE_WAIT {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Apply the size requested last once no resize was requested for
+/// `RESIZE_DEBOUNCE`.
+fn apply_debounced_resize(inner: Weak<Mutex<Inner>>) {
+    loop {
+        thread::sleep(RESIZE_DEBOUNCE);
+        let inner = match inner.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+        let mut inner = inner.lock().unwrap();
+        let settled = inner
+            .last_resize_request
+            .map(|requested| requested.elapsed() >= RESIZE_DEBOUNCE)
+            .unwrap_or(true);
+        if settled {
+            inner.resize_scheduled = false;
+            let size = inner.size;
+            if let Err(e) = inner.resize(size.rows, size.cols, size.pixel_width, size.pixel_height)
+            {
+                log::warn!("failed to resize pseudoconsole: {:?}", e);
+            }
+            return;
+        }
+    }
+}
+
+/// A writer to the pseudoconsole that holds `io_lock` while writing.
+struct SerializedWriter {
+    writer: Box<dyn io::Write + Send>,
+    io_lock: Arc<Mutex<()>>,
+}
+
+impl io::Write for SerializedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let _io = self.io_lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _io = self.io_lock.lock().unwrap_or_else(|e| e.into_inner());
+        self.writer.flush()
+    }
+}
+
+/// How to run a helper process, see [`ConPtyMasterPty::spawn_helper`]
+#[derive(Debug, Clone, Default)]
+pub struct HelperOptions {
+    /// Send the stdout and stderr of the helper to `NUL`, so that nothing it
+    /// prints shows up in the output of the pty
+    pub discard_output: bool,
+}
+
+type HelperExitCallback = Box<dyn FnOnce(io::Result<ExitStatus>) + Send>;
+type Helper = (Box<dyn Child + Send + Sync>, HelperExitCallback);
+
+lazy_static::lazy_static! {
+    static ref HELPER_REAPER: Mutex<Option<mpsc::Sender<Helper>>> = Mutex::new(None);
+}
+
+/// Hand `helper` to the thread that waits for every helper process to exit,
+/// starting that thread if it is not running.
+fn reap_helper(
+    helper: Box<dyn Child + Send + Sync>,
+    on_exit: HelperExitCallback,
+) -> io::Result<()> {
+    let mut reaper = HELPER_REAPER.lock().unwrap();
+    let helper = match reaper.as_ref() {
+        Some(sender) => match sender.send((helper, on_exit)) {
+            Ok(()) => return Ok(()),
+            Err(mpsc::SendError(helper)) => helper,
+        },
+        None => (helper, on_exit),
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("conpty_helper_reaper".to_owned())
+        .spawn(move || wait_for_helpers(receiver))?;
+    let _ = sender.send(helper);
+    *reaper = Some(sender);
+    Ok(())
+}
+
+/// Poll the helpers received over `receiver` until they exit and call their
+/// exit callbacks, with one thread for all of them.
+fn wait_for_helpers(receiver: mpsc::Receiver<Helper>) {
+    let mut running = vec![];
+    loop {
+        if running.is_empty() {
+            match receiver.recv() {
+                Ok(helper) => running.push(helper),
+                Err(_) => return,
+            }
+        }
+        running.extend(receiver.try_iter());
+        let mut i = 0;
+        while i < running.len() {
+            let status = match running[i].0.try_wait() {
+                Ok(None) => {
+                    i += 1;
+                    continue;
+                },
+                Ok(Some(status)) => Ok(status),
+                Err(e) => Err(e),
+            };
+            let (_, on_exit) = running.swap_remove(i);
+            on_exit(status);
+        }
+        thread::sleep(HELPER_POLL_INTERVAL);
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.shutdown(SHUTDOWN_DRAIN_TIMEOUT);
+    }
 }
 
 #[derive(Clone)]
@@ -112,28 +775,57 @@ pub struct ConPtySlavePty {
 
 impl MasterPty for ConPtyMasterPty {
     fn resize(&self, size: PtySize) -> anyhow::Result<()> {
+        let size = validate_size(size)?;
         let mut inner = self.inner.lock().unwrap();
-        inner.resize(size.rows, size.cols, size.pixel_width, size.pixel_height)
+        if inner.size == size && !inner.resize_scheduled {
+            return Ok(());
+        }
+        // The new size is reported by get_size right away, but only applied
+        // once resizing settles down
+        inner.size = size;
+        inner.last_resize_request = Some(Instant::now());
+        if !inner.resize_scheduled {
+            let weak = Arc::downgrade(&self.inner);
+            let spawned = thread::Builder::new()
+                .name("conpty_resize".to_owned())
+                .spawn(move || apply_debounced_resize(weak));
+            match spawned {
+                Ok(_) => inner.resize_scheduled = true,
+                Err(_) => {
+                    let size = inner.size;
+                    return inner.resize(size.rows, size.cols, size.pixel_width, size.pixel_height);
+                },
+            }
+        }
+        Ok(())
     }
 
     fn get_size(&self) -> Result<PtySize, Error> {
+        // The size set last, or the size of the console if a program attached
+        // to it changed it and it could not be put back, see
+        // reconcile_console_size
         let inner = self.inner.lock().unwrap();
         Ok(inner.size.clone())
     }
 
     fn try_clone_reader(&self) -> anyhow::Result<Box<dyn std::io::Read + Send>> {
-        Ok(Box::new(self.inner.lock().unwrap().readable.try_clone()?))
+        Ok(Box::new(self.inner.lock().unwrap().clone_reader()?))
     }
 
     fn take_writer(&self) -> anyhow::Result<Box<dyn std::io::Write + Send>> {
-        Ok(Box::new(
-            self.inner
-                .lock()
-                .unwrap()
-                .writable
-                .take()
-                .ok_or_else(|| anyhow::anyhow!("writer already taken"))?,
-        ))
+        let mut inner = self.inner.lock().unwrap();
+        let writer: Box<dyn io::Write + Send> = match inner
+            .writable
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("writer already taken"))?
+        {
+            PipeEnd::Sync(writable) => Box::new(writable),
+            PipeEnd::Overlapped(writable) => Box::new(OverlappedFile::new(writable)?),
+        };
+        Ok(Box::new(SerializedWriter {
+            writer,
+            io_lock: inner.io_lock.clone(),
+        }))
     }
 
     fn spawn_command_in_pty(
@@ -146,10 +838,208 @@ impl MasterPty for ConPtyMasterPty {
     }
 }
 
+impl ConPtyMasterPty {
+    /// Close the pseudoconsole now rather than when the last reference to it
+    /// is dropped, terminating the processes attached to it. Blocks until its
+    /// output pipe reaches EOF, or for at most a couple of seconds, unless an
+    /// async reader was cloned, which must then keep reading. Any writer
+    /// taken from this pty should be dropped first, since conhost may wait
+    /// for its input to close before it exits.
+    pub fn shutdown(&self) {
+        self.inner.lock().unwrap().shutdown(SHUTDOWN_DRAIN_TIMEOUT);
+    }
+
+    /// Clone the reader of the output of the pseudoconsole as a
+    /// [`ConPtyReader`], which unlike [`MasterPty::try_clone_reader`] can be
+    /// polled without blocking. Not to be used together with
+    /// [`Self::try_clone_async_reader`], each would take bytes meant for the
+    /// other.
+    pub fn try_clone_pollable_reader(&self) -> anyhow::Result<ConPtyReader> {
+        Ok(self.inner.lock().unwrap().clone_reader()?)
+    }
+
+    /// Clone the handle of the pipe carrying the output of the pseudoconsole,
+    /// opened for overlapped I/O so that it can be registered with an async
+    /// runtime. Fails unless the pty was opened by
+    /// [`ConPtySystem::with_overlapped_io`].
+    pub fn try_clone_async_reader(&self) -> anyhow::Result<OwnedHandle> {
+        let mut inner = self.inner.lock().unwrap();
+        let reader = match &inner.readable {
+            PipeEnd::Overlapped(readable) => readable.try_clone()?,
+            PipeEnd::Sync(_) => anyhow::bail!("pty was not opened with overlapped I/O"),
+        };
+        inner.async_reader_cloned = true;
+        Ok(reader)
+    }
+
+    /// The PID of the conhost or OpenConsole process hosting the
+    /// pseudoconsole, which is a child of this process rather than of the
+    /// processes attached to the pseudoconsole.
+    pub fn host_process_id(&self) -> Option<u32> {
+        self.inner.lock().unwrap().con.host_process_id()
+    }
+
+    /// The PID of the first process spawned into the pty through its slave.
+    /// Processes spawned with [`MasterPty::spawn_command_in_pty`] do not
+    /// count.
+    pub fn root_child_process_id(&self) -> Option<u32> {
+        self.inner.lock().unwrap().root_child_pid
+    }
+
+    /// Spawn a short-lived helper process attached to the pseudoconsole,
+    /// eg. one inspecting its input buffer. `on_exit` is called with its exit
+    /// status once it exits, from a thread shared by every helper. Helpers
+    /// still running when the pty is shut down are terminated. Returns the
+    /// PID of the helper.
+    pub fn spawn_helper(
+        &self,
+        cmd: CommandBuilder,
+        options: HelperOptions,
+        on_exit: impl FnOnce(io::Result<ExitStatus>) + Send + 'static,
+    ) -> anyhow::Result<u32> {
+        let mut inner = self.inner.lock().unwrap();
+        anyhow::ensure!(!inner.shut_down, "the pseudoconsole was shut down");
+        let child = if options.discard_output {
+            inner.con.spawn_command_discarding_output(cmd)?
+        } else {
+            inner.con.spawn_command(cmd)?
+        };
+        let pid = child
+            .process_id()
+            .ok_or_else(|| anyhow::anyhow!("helper process has no PID"))?;
+        inner.helpers.push((pid, child.clone_killer()));
+        drop(inner);
+
+        let weak = Arc::downgrade(&self.inner);
+        let on_exit = Box::new(move |status| {
+            if let Some(inner) = weak.upgrade() {
+                inner
+                    .lock()
+                    .unwrap()
+                    .helpers
+                    .retain(|(helper_pid, _)| *helper_pid != pid);
+            }
+            on_exit(status)
+        });
+        reap_helper(Box::new(child), on_exit)?;
+        Ok(pid)
+    }
+
+    /// Check the size of the console with `helper`, a process spawned into
+    /// the pseudoconsole that exits with the columns of its console window in
+    /// the upper and the rows in the lower 16 bits of its exit code, or with 0
+    /// if it cannot tell. A program attached to the console may have resized
+    /// it behind our back, in which case the pseudoconsole is resized back to
+    /// the size set last, or [`MasterPty::get_size`] reports the size of the
+    /// console if that fails. Returns right away, and does nothing while an
+    /// earlier check is still running.
+    ///
+    /// Querying the console from a helper keeps this process from attaching
+    /// to it, which would swap the console out from under its other threads.
+    pub fn reconcile_console_size(&self, helper: CommandBuilder) -> anyhow::Result<()> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.console_size_check_running {
+                return Ok(());
+            }
+            inner.console_size_check_running = true;
+        }
+        let weak = Arc::downgrade(&self.inner);
+        let spawned = self.spawn_helper(
+            helper,
+            HelperOptions {
+                discard_output: true,
+            },
+            move |status| {
+                if let Some(inner) = weak.upgrade() {
+                    let mut inner = inner.lock().unwrap();
+                    inner.console_size_check_running = false;
+                    if let Some(console_size) = status
+                        .ok()
+                        .and_then(|status| console_size_from_exit_code(status.exit_code()))
+                    {
+                        inner.reconcile_size(console_size);
+                    }
+                }
+            },
+        );
+        if spawned.is_err() {
+            self.inner.lock().unwrap().console_size_check_running = false;
+        }
+        spawned.map(|_| ())
+    }
+
+    /// Set the input and output codepage of the pseudoconsole, eg. to 65001
+    /// for UTF-8, by running `chcp.com` in it with its output discarded. The
+    /// codepage is console state, so processes spawned into the pseudoconsole
+    /// afterwards start out with it. Blocks until chcp exits, or for at most a
+    /// couple of seconds.
+    pub fn set_console_codepage(&self, codepage: u32) -> anyhow::Result<()> {
+        let mut child = {
+            let inner = self.inner.lock().unwrap();
+            anyhow::ensure!(!inner.shut_down, "the pseudoconsole was shut down");
+            let mut cmd = CommandBuilder::new("chcp.com");
+            cmd.arg(codepage.to_string());
+            inner.con.spawn_command_discarding_output(cmd)?
+        };
+        let deadline = Instant::now() + SET_CODEPAGE_TIMEOUT;
+        loop {
+            match child.try_wait()? {
+                Some(status) if status.success() => return Ok(()),
+                Some(status) => anyhow::bail!("chcp {} failed: {}", codepage, status),
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    anyhow::bail!(
+                        "chcp {} did not exit within {:?}",
+                        codepage,
+                        SET_CODEPAGE_TIMEOUT
+                    );
+                },
+                None => thread::sleep(HELPER_POLL_INTERVAL),
+            }
+        }
+    }
+
+    /// Take the handle of the pipe carrying the input of the pseudoconsole,
+    /// opened for overlapped I/O. Fails unless the pty was opened by
+    /// [`ConPtySystem::with_overlapped_io`], or if the writer was already taken.
+    pub fn take_async_writer(&self) -> anyhow::Result<OwnedHandle> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.writable.take() {
+            Some(PipeEnd::Overlapped(writable)) => Ok(writable),
+            Some(sync) => {
+                inner.writable = Some(sync);
+                anyhow::bail!("pty was not opened with overlapped I/O")
+            },
+            None => anyhow::bail!("writer already taken"),
+        }
+    }
+}
+
+impl ConPtySlavePty {
+    /// Spawn `cmd` with its main thread suspended, so that it can be assigned
+    /// to a job object or have its priority class set before it runs. It only
+    /// starts once [`SuspendedWinChild::resume`] is called.
+    pub fn spawn_command_suspended(
+        &self,
+        cmd: CommandBuilder,
+    ) -> anyhow::Result<SuspendedWinChild> {
+        let mut inner = self.inner.lock().unwrap();
+        let child = inner.con.spawn_command_suspended(cmd)?;
+        if inner.root_child_pid.is_none() {
+            inner.root_child_pid = child.process_id();
+        }
+        Ok(child)
+    }
+}
+
 impl SlavePty for ConPtySlavePty {
     fn spawn_command(&self, cmd: CommandBuilder) -> anyhow::Result<Box<dyn Child + Send + Sync>> {
-        let inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.lock().unwrap();
         let child = inner.con.spawn_command(cmd)?;
+        if inner.root_child_pid.is_none() {
+            inner.root_child_pid = child.process_id();
+        }
         Ok(Box::new(child))
     }
 }
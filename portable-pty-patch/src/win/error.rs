@@ -0,0 +1,143 @@
+use std::fmt;
+use std::io;
+use winapi::shared::winerror::{
+    ERROR_FILE_NOT_FOUND, ERROR_NOT_ENOUGH_MEMORY, ERROR_OUTOFMEMORY, E_OUTOFMEMORY, HRESULT,
+};
+
+/// Why creating or driving a pseudoconsole failed.
+///
+/// The `MasterPty`/`SlavePty` traits return `anyhow::Error`, which wraps
+/// this; use `downcast_ref::<ConPtyError>()` on the error (or on an entry of
+/// its chain) to branch on the cause.
+#[derive(Debug)]
+pub enum ConPtyError {
+    /// Creating the pipes connecting to the pseudoconsole failed
+    PipeCreation(io::Error),
+    /// CreatePseudoConsole failed with this HRESULT
+    PseudoConsoleCreation(HRESULT),
+    /// CreateProcessW failed with this Win32 error code
+    SpawnFailed {
+        command: String,
+        cwd: Option<String>,
+        error: u32,
+    },
+    /// ResizePseudoConsole failed with this HRESULT
+    Resize {
+        cols: i16,
+        rows: i16,
+        hresult: HRESULT,
+    },
+    /// The requested size has no rows or no columns
+    InvalidSize { cols: u16, rows: u16 },
+}
+
+impl ConPtyError {
+    /// Whether the failure was caused by running out of memory, in which
+    /// case trying again a little later may succeed
+    pub fn is_out_of_memory(&self) -> bool {
+        match self {
+            ConPtyError::PipeCreation(e) => matches!(
+                e.raw_os_error().map(|code| code as u32),
+                Some(ERROR_NOT_ENOUGH_MEMORY) | Some(ERROR_OUTOFMEMORY)
+            ),
+            ConPtyError::PseudoConsoleCreation(hresult) => *hresult == E_OUTOFMEMORY,
+            ConPtyError::SpawnFailed { error, .. } => {
+                *error == ERROR_NOT_ENOUGH_MEMORY || *error == ERROR_OUTOFMEMORY
+            },
+            ConPtyError::Resize { hresult, .. } => *hresult == E_OUTOFMEMORY,
+            ConPtyError::InvalidSize { .. } => false,
+        }
+    }
+
+    /// Whether spawning failed because the executable does not exist. A
+    /// missing directory (ERROR_PATH_NOT_FOUND), whether the working
+    /// directory or one on the way to the executable, is not reported here
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            ConPtyError::SpawnFailed { error, .. } if *error == ERROR_FILE_NOT_FOUND
+        )
+    }
+
+    /// Whether the pseudoconsole was asked to take a size it cannot have
+    pub fn is_invalid_size(&self) -> bool {
+        matches!(self, ConPtyError::InvalidSize { .. })
+    }
+}
+
+impl fmt::Display for ConPtyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConPtyError::PipeCreation(e) => {
+                write!(f, "failed to create the pseudoconsole pipes: {}", e)
+            },
+            ConPtyError::PseudoConsoleCreation(hresult) => {
+                write!(f, "failed to create psuedo console: HRESULT {}", hresult)
+            },
+            ConPtyError::SpawnFailed {
+                command,
+                cwd,
+                error,
+            } => write!(
+                f,
+                "CreateProcessW `{}` in cwd `{:?}` failed: {}",
+                command,
+                cwd,
+                io::Error::from_raw_os_error(*error as i32)
+            ),
+            ConPtyError::Resize {
+                cols,
+                rows,
+                hresult,
+            } => write!(
+                f,
+                "failed to resize console to {}x{}: HRESULT: {}",
+                cols, rows, hresult
+            ),
+            ConPtyError::InvalidSize { cols, rows } => {
+                write!(f, "invalid console size {}x{}", cols, rows)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ConPtyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConPtyError::PipeCreation(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winapi::shared::winerror::{ERROR_DIRECTORY, ERROR_PATH_NOT_FOUND};
+
+    fn spawn_failed(error: u32) -> ConPtyError {
+        ConPtyError::SpawnFailed {
+            command: "missing.exe".to_string(),
+            cwd: Some("C:\\missing".to_string()),
+            error,
+        }
+    }
+
+    #[test]
+    fn missing_executable_is_not_found() {
+        assert!(spawn_failed(ERROR_FILE_NOT_FOUND).is_not_found());
+    }
+
+    #[test]
+    fn missing_directory_is_not_a_missing_command() {
+        assert!(!spawn_failed(ERROR_PATH_NOT_FOUND).is_not_found());
+        assert!(!spawn_failed(ERROR_DIRECTORY).is_not_found());
+    }
+
+    #[test]
+    fn other_failures_are_not_reported_as_not_found() {
+        assert!(!ConPtyError::PseudoConsoleCreation(E_OUTOFMEMORY).is_not_found());
+        assert!(!ConPtyError::InvalidSize { cols: 0, rows: 0 }.is_not_found());
+        assert!(!spawn_failed(ERROR_OUTOFMEMORY).is_not_found());
+    }
+}
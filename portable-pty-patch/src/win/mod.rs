@@ -3,7 +3,8 @@ use anyhow::Context as _;
 use std::io::{Error as IoError, Result as IoResult};
 use std::os::windows::io::{AsRawHandle, RawHandle};
 use std::pin::Pin;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use winapi::shared::minwindef::DWORD;
 use winapi::um::minwinbase::STILL_ACTIVE;
@@ -12,17 +13,76 @@ use winapi::um::synchapi::WaitForSingleObject;
 use winapi::um::winbase::INFINITE;
 
 pub mod conpty;
+pub mod error;
 mod procthreadattr;
 mod psuedocon;
+pub mod winpty;
 
 use filedescriptor::OwnedHandle;
 
+/// Which implementation backs the ptys returned by [`pty_system`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PtySystemKind {
+    /// ConPTY, see [`conpty::ConPtySystem`]
+    #[default]
+    ConPty,
+    /// ConPTY in passthrough mode, which leaves rendering VT output to the
+    /// terminal reading it. Requires a recent conpty.dll.
+    ConPtyPassthrough,
+    /// winpty, for systems whose ConPTY is missing or broken, see
+    /// [`winpty::WinPtySystem`]
+    WinPty,
+}
+
+/// A `PtySystem` of the given kind. Its ptys read their output through pipes
+/// opened for overlapped I/O, so that an async runtime can read it directly.
+/// ConPTY pipes are created with the security descriptor described by
+/// `security_descriptor` if given (see
+/// [`conpty::ConPtySystem::with_security_descriptor`]); the winpty agent
+/// creates its pipes itself.
+pub fn pty_system(
+    kind: PtySystemKind,
+    security_descriptor: Option<&str>,
+) -> Box<dyn crate::PtySystem + Send> {
+    let conpty = || {
+        let system = conpty::ConPtySystem::with_overlapped_io();
+        match security_descriptor {
+            Some(sddl) => system.with_security_descriptor(sddl),
+            None => system,
+        }
+    };
+    match kind {
+        PtySystemKind::ConPty => Box::new(conpty()),
+        PtySystemKind::ConPtyPassthrough => Box::new(conpty().with_passthrough_mode()),
+        PtySystemKind::WinPty => Box::new(winpty::WinPtySystem::default()),
+    }
+}
+
 #[derive(Debug)]
 pub struct WinChild {
     proc: Mutex<OwnedHandle>,
+    /// Set once the process is terminated through this child or one of its
+    /// killers, so that its exit status can tell it apart from a process
+    /// that exited on its own with the same code
+    terminated: Arc<AtomicBool>,
 }
 
 impl WinChild {
+    fn new(proc: OwnedHandle) -> Self {
+        Self {
+            proc: Mutex::new(proc),
+            terminated: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn exit_status(&self, code: DWORD) -> ExitStatus {
+        if self.terminated.load(Ordering::SeqCst) {
+            ExitStatus::with_terminated_exit_code(code)
+        } else {
+            ExitStatus::with_exit_code(code)
+        }
+    }
+
     fn is_complete(&mut self) -> IoResult<Option<ExitStatus>> {
         let mut status: DWORD = 0;
         let proc = self.proc.lock().unwrap().try_clone().unwrap();
@@ -31,7 +91,7 @@ impl WinChild {
             if status == STILL_ACTIVE {
                 Ok(None)
             } else {
-                Ok(Some(ExitStatus::with_exit_code(status)))
+                Ok(Some(self.exit_status(status)))
             }
         } else {
             Ok(None)
@@ -45,6 +105,7 @@ impl WinChild {
         if res == 0 {
             Err(err)
         } else {
+            self.terminated.store(true, Ordering::SeqCst);
             Ok(())
         }
     }
@@ -58,13 +119,17 @@ impl ChildKiller for WinChild {
 
     fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
         let proc = self.proc.lock().unwrap().try_clone().unwrap();
-        Box::new(WinChildKiller { proc })
+        Box::new(WinChildKiller {
+            proc,
+            terminated: self.terminated.clone(),
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct WinChildKiller {
     proc: OwnedHandle,
+    terminated: Arc<AtomicBool>,
 }
 
 impl ChildKiller for WinChildKiller {
@@ -74,13 +139,17 @@ impl ChildKiller for WinChildKiller {
         if res == 0 {
             Err(err)
         } else {
+            self.terminated.store(true, Ordering::SeqCst);
             Ok(())
         }
     }
 
     fn clone_killer(&self) -> Box<dyn ChildKiller + Send + Sync> {
         let proc = self.proc.try_clone().unwrap();
-        Box::new(WinChildKiller { proc })
+        Box::new(WinChildKiller {
+            proc,
+            terminated: self.terminated.clone(),
+        })
     }
 }
 
@@ -100,7 +169,7 @@ impl Child for WinChild {
         let mut status: DWORD = 0;
         let res = unsafe { GetExitCodeProcess(proc.as_raw_handle() as _, &mut status) };
         if res != 0 {
-            Ok(ExitStatus::with_exit_code(status))
+            Ok(self.exit_status(status))
         } else {
             Err(IoError::last_os_error())
         }
@@ -121,6 +190,54 @@ impl Child for WinChild {
     }
 }
 
+/// A child whose main thread was created suspended, see
+/// [`conpty::ConPtySlavePty::spawn_command_suspended`]. It runs no code until
+/// it is resumed, and is terminated if it is dropped before that.
+#[derive(Debug)]
+pub struct SuspendedWinChild {
+    child: Option<WinChild>,
+    main_thread: OwnedHandle,
+}
+
+impl SuspendedWinChild {
+    fn new(child: WinChild, main_thread: OwnedHandle) -> Self {
+        Self {
+            child: Some(child),
+            main_thread,
+        }
+    }
+
+    pub fn process_id(&self) -> Option<u32> {
+        self.child.as_ref().and_then(|child| child.process_id())
+    }
+
+    /// The process handle, eg. for `AssignProcessToJobObject` or
+    /// `SetPriorityClass`
+    pub fn as_raw_handle(&self) -> Option<RawHandle> {
+        self.child.as_ref().and_then(|child| child.as_raw_handle())
+    }
+
+    /// Let the child start running
+    pub fn resume(mut self) -> IoResult<WinChild> {
+        let res = unsafe { ResumeThread(self.main_thread.as_raw_handle() as _) };
+        if res == DWORD::MAX {
+            return Err(IoError::last_os_error());
+        }
+        Ok(self
+            .child
+            .take()
+            .expect("a suspended child is only resumed once"))
+    }
+}
+
+impl Drop for SuspendedWinChild {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            child.do_kill().ok();
+        }
+    }
+}
+
 impl std::future::Future for WinChild {
     type Output = anyhow::Result<ExitStatus>;
 
@@ -4,11 +4,20 @@ use std::io::Error as IoError;
 use std::{mem, ptr};
 use winapi::shared::minwindef::DWORD;
 use winapi::um::processthreadsapi::*;
+use winapi::um::winnt::HANDLE;
 
+const PROC_THREAD_ATTRIBUTE_HANDLE_LIST: usize = 0x00020002;
 const PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE: usize = 0x00020016;
 
 pub struct ProcThreadAttributeList {
-    data: Vec<u8>,
+    // Backed by `usize` rather than `u8` so that the list is pointer-aligned.
+    // The attribute list holds pointers and sizes that the kernel accesses
+    // with natural alignment; a byte-aligned buffer happens to work on x64
+    // but can fault on aarch64.
+    data: Vec<usize>,
+    // The attribute list points into this rather than copying it, so it has
+    // to live as long as the list
+    handle_list: Vec<HANDLE>,
 }
 
 impl ProcThreadAttributeList {
@@ -22,11 +31,9 @@ impl ProcThreadAttributeList {
                 &mut bytes_required,
             )
         };
-        let mut data = Vec::with_capacity(bytes_required);
-        // We have the right capacity, so force the vec to consider itself
-        // that length.  The contents of those bytes will be maintained
-        // by the win32 apis used in this impl.
-        unsafe { data.set_len(bytes_required) };
+        let words_required =
+            (bytes_required + mem::size_of::<usize>() - 1) / mem::size_of::<usize>();
+        let mut data: Vec<usize> = vec![0; words_required];
 
         let attr_ptr = data.as_mut_slice().as_mut_ptr() as *mut _;
         let res = unsafe {
@@ -37,7 +44,10 @@ impl ProcThreadAttributeList {
             "InitializeProcThreadAttributeList failed: {}",
             IoError::last_os_error()
         );
-        Ok(Self { data })
+        Ok(Self {
+            data,
+            handle_list: vec![],
+        })
     }
 
     pub fn as_mut_ptr(&mut self) -> LPPROC_THREAD_ATTRIBUTE_LIST {
@@ -63,6 +73,29 @@ impl ProcThreadAttributeList {
         );
         Ok(())
     }
+
+    /// Restrict the handles a process created with inheritance enabled
+    /// inherits to `handles`, which must be inheritable.
+    pub fn set_handle_list(&mut self, handles: &[HANDLE]) -> Result<(), Error> {
+        self.handle_list = handles.to_vec();
+        let res = unsafe {
+            UpdateProcThreadAttribute(
+                self.as_mut_ptr(),
+                0,
+                PROC_THREAD_ATTRIBUTE_HANDLE_LIST,
+                self.handle_list.as_mut_ptr() as _,
+                self.handle_list.len() * mem::size_of::<HANDLE>(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        ensure!(
+            res != 0,
+            "UpdateProcThreadAttribute failed: {}",
+            IoError::last_os_error()
+        );
+        Ok(())
+    }
 }
 
 impl Drop for ProcThreadAttributeList {
@@ -1,7 +1,8 @@
-use super::WinChild;
+use super::error::ConPtyError;
+use super::{SuspendedWinChild, WinChild};
 use crate::cmdbuilder::CommandBuilder;
 use crate::win::procthreadattr::ProcThreadAttributeList;
-use anyhow::{bail, ensure, Error};
+use anyhow::{bail, Error};
 use filedescriptor::{FileDescriptor, OwnedHandle};
 use lazy_static::lazy_static;
 use shared_library::shared_library;
@@ -9,25 +10,28 @@ use std::ffi::OsString;
 use std::io::Error as IoError;
 use std::os::windows::ffi::OsStringExt;
 use std::os::windows::io::{AsRawHandle, FromRawHandle};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::{mem, ptr};
 use winapi::shared::minwindef::DWORD;
 use winapi::shared::winerror::{HRESULT, S_OK};
+use winapi::um::fileapi::{CreateFileW, OPEN_EXISTING};
 use winapi::um::handleapi::*;
+use winapi::um::minwinbase::SECURITY_ATTRIBUTES;
 use winapi::um::processthreadsapi::*;
 use winapi::um::winbase::{
-    CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, STARTF_USESTDHANDLES, STARTUPINFOEXW,
+    CREATE_SUSPENDED, CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT,
+    STARTF_USESTDHANDLES, STARTUPINFOEXW,
 };
 use winapi::um::wincon::COORD;
-use winapi::um::winnt::HANDLE;
+use winapi::um::winnt::{FILE_SHARE_READ, FILE_SHARE_WRITE, GENERIC_WRITE, HANDLE};
 
 pub type HPCON = HANDLE;
 
 pub const PSUEDOCONSOLE_INHERIT_CURSOR: DWORD = 0x1;
 pub const PSEUDOCONSOLE_RESIZE_QUIRK: DWORD = 0x2;
 pub const PSEUDOCONSOLE_WIN32_INPUT_MODE: DWORD = 0x4;
-#[allow(dead_code)]
 pub const PSEUDOCONSOLE_PASSTHROUGH_MODE: DWORD = 0x8;
 
 shared_library!(ConPtyFuncs,
@@ -42,6 +46,34 @@ shared_library!(ConPtyFuncs,
     pub fn ClosePseudoConsole(hpc: HPCON),
 );
 
+/// Where the ConPTY functions are loaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConPtyHost {
+    /// A `conpty.dll` and `OpenConsole.exe` deployed alongside the
+    /// application, falling back to kernel32 if there is none
+    Sideloaded,
+    /// The ConPTY built into kernel32
+    System,
+    /// A `conpty.dll` and `OpenConsole.exe` in this directory, as shipped with
+    /// Windows Terminal, falling back to kernel32 if they are missing
+    Directory(PathBuf),
+}
+
+/// Select where the ConPTY functions are loaded from. They are loaded when
+/// the first pseudoconsole is created, after which the host cannot change.
+pub fn set_conpty_host(host: ConPtyHost) -> Result<(), Error> {
+    let mut selected = CONPTY_HOST.lock().unwrap();
+    if CONPTY_LOADED.load(Ordering::SeqCst) && *selected != host {
+        bail!(
+            "cannot switch to {:?} since {:?} is already loaded",
+            host,
+            *selected
+        );
+    }
+    *selected = host;
+    Ok(())
+}
+
 fn load_conpty() -> ConPtyFuncs {
     // If the kernel doesn't export these functions then their system is
     // too old and we cannot run.
@@ -49,22 +81,141 @@ fn load_conpty() -> ConPtyFuncs {
         "this system does not support conpty.  Windows 10 October 2018 or newer is required",
     );
 
+    let host = CONPTY_HOST.lock().unwrap().clone();
+    CONPTY_LOADED.store(true, Ordering::SeqCst);
+
     // We prefer to use a sideloaded conpty.dll and openconsole.exe host deployed
     // alongside the application.  We check for this after checking for kernel
     // support so that we don't try to proceed and do something crazy.
-    if let Ok(sideloaded) = ConPtyFuncs::open(Path::new("conpty.dll")) {
-        sideloaded
-    } else {
-        kernel
-    }
+    let sideloaded = match host {
+        ConPtyHost::Sideloaded => ConPtyFuncs::open(Path::new("conpty.dll")).ok(),
+        ConPtyHost::System => None,
+        ConPtyHost::Directory(dir) => {
+            // conpty.dll starts the OpenConsole.exe next to it, and cannot
+            // create pseudoconsoles without one
+            if !dir.join("OpenConsole.exe").is_file() {
+                log::warn!(
+                    "no OpenConsole.exe in {}, using the system conpty",
+                    dir.display()
+                );
+                None
+            } else {
+                match ConPtyFuncs::open(&dir.join("conpty.dll")) {
+                    Ok(sideloaded) => Some(sideloaded),
+                    Err(e) => {
+                        log::warn!(
+                            "failed to load conpty.dll from {}, using the system conpty: {:?}",
+                            dir.display(),
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+        }
+    };
+    sideloaded.unwrap_or(kernel)
 }
 
 lazy_static! {
+    static ref CONPTY_HOST: Mutex<ConPtyHost> = Mutex::new(ConPtyHost::Sideloaded);
     static ref CONPTY: ConPtyFuncs = load_conpty();
 }
 
+static CONPTY_LOADED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// Pseudoconsoles are created one at a time, so that the host process
+    /// that appears while creating one is known to be its host
+    static ref CONSOLE_HOSTS: Mutex<ConsoleHosts> = Mutex::new(ConsoleHosts::new());
+}
+
+/// Tells the hosts of open pseudoconsoles apart from the other conhost and
+/// OpenConsole processes this process started, so that the host of a new
+/// pseudoconsole is found with a single process snapshot
+struct ConsoleHosts {
+    /// Hosting a pseudoconsole that is still open
+    open: Vec<u32>,
+    /// Running before the first pseudoconsole was created, or hosting one
+    /// that was closed and may not have exited yet
+    stale: Vec<u32>,
+}
+
+impl ConsoleHosts {
+    fn new() -> Self {
+        Self {
+            open: vec![],
+            stale: console_host_pids(),
+        }
+    }
+
+    /// The host of the pseudoconsole that was just created
+    fn claim_new_host(&mut self) -> Option<u32> {
+        let host_pids = console_host_pids();
+        self.stale.retain(|pid| host_pids.contains(pid));
+        let candidates: Vec<u32> = host_pids
+            .into_iter()
+            .filter(|pid| !self.open.contains(pid))
+            .collect();
+        let host_pid = match candidates.iter().find(|pid| !self.stale.contains(pid)) {
+            Some(pid) => Some(*pid),
+            // the new host reused the PID of a stale one that exited since
+            None if candidates.len() == 1 => Some(candidates[0]),
+            None => None,
+        }?;
+        self.stale.retain(|pid| *pid != host_pid);
+        self.open.push(host_pid);
+        Some(host_pid)
+    }
+
+    fn release(&mut self, host_pid: u32) {
+        self.open.retain(|pid| *pid != host_pid);
+        self.stale.push(host_pid);
+    }
+}
+
+/// The conhost and OpenConsole processes this process started, which host
+/// its pseudoconsoles
+fn console_host_pids() -> Vec<u32> {
+    use winapi::um::tlhelp32::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let own_pid = unsafe { GetCurrentProcessId() };
+    let mut host_pids = vec![];
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            return host_pids;
+        }
+        let mut entry: PROCESSENTRY32W = mem::zeroed();
+        entry.dwSize = mem::size_of::<PROCESSENTRY32W>() as DWORD;
+        let mut has_entry = Process32FirstW(snapshot, &mut entry) != 0;
+        while has_entry {
+            let len = entry
+                .szExeFile
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            let exe_file = OsString::from_wide(&entry.szExeFile[..len]);
+            let exe_file = exe_file.to_string_lossy();
+            if entry.th32ParentProcessID == own_pid
+                && (exe_file.eq_ignore_ascii_case("conhost.exe")
+                    || exe_file.eq_ignore_ascii_case("OpenConsole.exe"))
+            {
+                host_pids.push(entry.th32ProcessID);
+            }
+            has_entry = Process32NextW(snapshot, &mut entry) != 0;
+        }
+        CloseHandle(snapshot);
+    }
+    host_pids
+}
+
 pub struct PsuedoCon {
     con: HPCON,
+    host_pid: Option<u32>,
 }
 
 unsafe impl Send for PsuedoCon {}
@@ -72,46 +223,141 @@ unsafe impl Sync for PsuedoCon {}
 
 impl Drop for PsuedoCon {
     fn drop(&mut self) {
-        unsafe { (CONPTY.ClosePseudoConsole)(self.con) };
+        self.close();
     }
 }
 
 impl PsuedoCon {
-    pub fn new(size: COORD, input: FileDescriptor, output: FileDescriptor) -> Result<Self, Error> {
+    /// Create a pseudoconsole. With `passthrough`, conhost forwards the VT
+    /// sequences written by its clients as they are rather than rendering
+    /// them itself; builds that do not know the flag ignore it.
+    pub fn new(
+        size: COORD,
+        input: FileDescriptor,
+        output: FileDescriptor,
+        passthrough: bool,
+    ) -> Result<Self, Error> {
+        let mut flags = PSUEDOCONSOLE_INHERIT_CURSOR;
+        if passthrough {
+            flags |= PSEUDOCONSOLE_PASSTHROUGH_MODE;
+        }
         let mut con: HPCON = INVALID_HANDLE_VALUE;
+        let mut console_hosts = CONSOLE_HOSTS.lock().unwrap();
         let result = unsafe {
             (CONPTY.CreatePseudoConsole)(
                 size,
                 input.as_raw_handle() as _,
                 output.as_raw_handle() as _,
-                // Use flags=0 to match tmux. INHERIT_CURSOR causes DSR query
-                // that stalls startup. RESIZE_QUIRK triggers full screen
-                // repaints on resize that cause multi-second output stalls.
-                0,
+                // INHERIT_CURSOR makes conhost ask for the cursor position
+                // (ESC[6n) on startup and block until the terminal reading
+                // the output answers, so that it starts drawing where the
+                // cursor actually is. RESIZE_QUIRK is left out since it
+                // triggers full screen repaints on resize that cause
+                // multi-second output stalls.
+                flags,
                 &mut con,
             )
         };
-        ensure!(
-            result == S_OK,
-            "failed to create psuedo console: HRESULT {}",
-            result
-        );
-        Ok(Self { con })
+        if result != S_OK {
+            return Err(ConPtyError::PseudoConsoleCreation(result).into());
+        }
+        // The handle does not tell which process hosts the pseudoconsole, but
+        // it is the one that was started while creating it
+        let host_pid = console_hosts.claim_new_host();
+        Ok(Self { con, host_pid })
+    }
+
+    /// The PID of the conhost or OpenConsole process hosting this
+    /// pseudoconsole
+    pub fn host_process_id(&self) -> Option<u32> {
+        self.host_pid
+    }
+
+    /// Close the pseudoconsole, which terminates the processes attached to
+    /// it. Depending on the Windows build this blocks until conhost has
+    /// written its final output, so the output pipe must be drained while
+    /// this runs. Closing it again does nothing.
+    pub fn close(&mut self) {
+        if self.con != INVALID_HANDLE_VALUE {
+            unsafe { (CONPTY.ClosePseudoConsole)(self.con) };
+            self.con = INVALID_HANDLE_VALUE;
+            if let Some(host_pid) = self.host_pid {
+                CONSOLE_HOSTS.lock().unwrap().release(host_pid);
+            }
+        }
     }
 
     pub fn resize(&self, size: COORD) -> Result<(), Error> {
         let result = unsafe { (CONPTY.ResizePseudoConsole)(self.con, size) };
-        ensure!(
-            result == S_OK,
-            "failed to resize console to {}x{}: HRESULT: {}",
-            size.X,
-            size.Y,
-            result
-        );
+        if result != S_OK {
+            return Err(ConPtyError::Resize {
+                cols: size.X,
+                rows: size.Y,
+                hresult: result,
+            }
+            .into());
+        }
         Ok(())
     }
 
     pub fn spawn_command(&self, cmd: CommandBuilder) -> anyhow::Result<WinChild> {
+        // Make sure we close out the thread handle so we don't leak it;
+        // we do this simply by making it owned
+        let (child, _main_thread) = self.create_process(cmd, 0, None)?;
+        Ok(child)
+    }
+
+    /// Spawn `cmd` with its stdout and stderr going to `NUL` rather than to
+    /// the console, so that nothing it prints shows up in the pane. It is
+    /// still attached to the pseudoconsole and can use its input buffer.
+    pub fn spawn_command_discarding_output(&self, cmd: CommandBuilder) -> anyhow::Result<WinChild> {
+        let name: Vec<u16> = "NUL\0".encode_utf16().collect();
+        let mut sa = SECURITY_ATTRIBUTES {
+            nLength: mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+            lpSecurityDescriptor: ptr::null_mut(),
+            // only ever inherited through the handle list of this one child
+            bInheritHandle: 1,
+        };
+        let null = unsafe {
+            CreateFileW(
+                name.as_ptr(),
+                GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                &mut sa,
+                OPEN_EXISTING,
+                0,
+                ptr::null_mut(),
+            )
+        };
+        if null == INVALID_HANDLE_VALUE {
+            bail!("failed to open NUL: {}", IoError::last_os_error());
+        }
+        let null = unsafe { OwnedHandle::from_raw_handle(null as _) };
+        let (child, _main_thread) =
+            self.create_process(cmd, 0, Some(null.as_raw_handle() as HANDLE))?;
+        Ok(child)
+    }
+
+    /// Spawn `cmd` with its main thread suspended, so that it can be placed
+    /// in a job object or have its priority changed before it runs any code
+    /// or spawns processes of its own.
+    pub fn spawn_command_suspended(
+        &self,
+        cmd: CommandBuilder,
+    ) -> anyhow::Result<SuspendedWinChild> {
+        let (child, main_thread) = self.create_process(cmd, CREATE_SUSPENDED, None)?;
+        Ok(SuspendedWinChild::new(child, main_thread))
+    }
+
+    /// Create a process attached to this pseudoconsole, returning it along
+    /// with its main thread. The process writes its stdout and stderr to
+    /// `output` if given, which has to be inheritable.
+    fn create_process(
+        &self,
+        cmd: CommandBuilder,
+        creation_flags: DWORD,
+        output: Option<HANDLE>,
+    ) -> anyhow::Result<(WinChild, OwnedHandle)> {
         let mut si: STARTUPINFOEXW = unsafe { mem::zeroed() };
         si.StartupInfo.cb = mem::size_of::<STARTUPINFOEXW>() as u32;
         // Explicitly set the stdio handles as invalid handles otherwise
@@ -125,8 +371,13 @@ impl PsuedoCon {
         si.StartupInfo.hStdOutput = INVALID_HANDLE_VALUE;
         si.StartupInfo.hStdError = INVALID_HANDLE_VALUE;
 
-        let mut attrs = ProcThreadAttributeList::with_capacity(1)?;
+        let mut attrs = ProcThreadAttributeList::with_capacity(2)?;
         attrs.set_pty(self.con)?;
+        if let Some(output) = output {
+            si.StartupInfo.hStdOutput = output;
+            si.StartupInfo.hStdError = output;
+            attrs.set_handle_list(&[output])?;
+        }
         si.lpAttributeList = attrs.as_mut_ptr();
 
         let mut pi: PROCESS_INFORMATION = unsafe { mem::zeroed() };
@@ -142,8 +393,13 @@ impl PsuedoCon {
                 cmdline.as_mut_slice().as_mut_ptr(),
                 ptr::null_mut(),
                 ptr::null_mut(),
-                0,
-                EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT,
+                // The child reaches its console through the pseudoconsole
+                // attribute alone, so it inherits no handles other than
+                // `output`, which the handle list restricts inheritance to;
+                // without one it would receive every inheritable handle of
+                // this process, including the pipes of other panes.
+                output.is_some() as i32,
+                EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT | creation_flags,
                 cmd.environment_block().as_mut_slice().as_mut_ptr() as *mut _,
                 cwd.as_ref()
                     .map(|c| c.as_slice().as_ptr())
@@ -153,24 +409,20 @@ impl PsuedoCon {
             )
         };
         if res == 0 {
-            let err = IoError::last_os_error();
-            let msg = format!(
-                "CreateProcessW `{:?}` in cwd `{:?}` failed: {}",
-                cmd_os,
-                cwd.as_ref().map(|c| OsString::from_wide(c)),
-                err
-            );
-            log::error!("{}", msg);
-            bail!("{}", msg);
+            let err = ConPtyError::SpawnFailed {
+                command: cmd_os.to_string_lossy().into_owned(),
+                cwd: cwd
+                    .as_ref()
+                    .map(|c| OsString::from_wide(c).to_string_lossy().into_owned()),
+                error: IoError::last_os_error().raw_os_error().unwrap_or(0) as u32,
+            };
+            log::error!("{}", err);
+            return Err(err.into());
         }
 
-        // Make sure we close out the thread handle so we don't leak it;
-        // we do this simply by making it owned
-        let _main_thread = unsafe { OwnedHandle::from_raw_handle(pi.hThread as _) };
+        let main_thread = unsafe { OwnedHandle::from_raw_handle(pi.hThread as _) };
         let proc = unsafe { OwnedHandle::from_raw_handle(pi.hProcess as _) };
 
-        Ok(WinChild {
-            proc: Mutex::new(proc),
-        })
+        Ok((WinChild::new(proc), main_thread))
     }
 }
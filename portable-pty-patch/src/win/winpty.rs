@@ -0,0 +1,295 @@
+//! A `PtySystem` backed by winpty, for Windows builds whose ConPTY is missing
+//! or broken. winpty runs the console in a hidden window of its own agent
+//! process and scrapes it into VT output, so it needs `winpty.dll` and
+//! `winpty-agent.exe` deployed alongside the application.
+use crate::cmdbuilder::CommandBuilder;
+use crate::win::conpty::OverlappedFile;
+use crate::win::WinChild;
+use crate::{Child, MasterPty, PtyPair, PtySize, PtySystem, SlavePty};
+use anyhow::{anyhow, bail, Error};
+use filedescriptor::FileDescriptor;
+use lazy_static::lazy_static;
+use shared_library::shared_library;
+use std::ffi::OsString;
+use std::io;
+use std::os::windows::ffi::OsStringExt;
+use std::os::windows::io::{FromRawHandle, IntoRawHandle, OwnedHandle};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{ptr, slice};
+use winapi::shared::minwindef::{BOOL, DWORD};
+use winapi::um::fileapi::{CreateFileW, OPEN_EXISTING};
+use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+use winapi::um::winbase::FILE_FLAG_OVERLAPPED;
+use winapi::um::winnt::{GENERIC_READ, GENERIC_WRITE, HANDLE, LPCWSTR};
+
+type WinPtyConfig = *mut std::ffi::c_void;
+type WinPtyHandle = *mut std::ffi::c_void;
+type WinPtySpawnConfig = *mut std::ffi::c_void;
+type WinPtyError = *mut std::ffi::c_void;
+
+/// Translate the console's colors into VT escape sequences
+const WINPTY_FLAG_COLOR_ESCAPES: u64 = 0x4;
+/// Close the agent once the spawned process exits
+const WINPTY_SPAWN_FLAG_AUTO_SHUTDOWN: u64 = 0x1;
+
+shared_library!(WinPtyFuncs,
+    pub fn winpty_error_msg(err: WinPtyError) -> LPCWSTR,
+    pub fn winpty_error_free(err: WinPtyError),
+    pub fn winpty_config_new(agent_flags: u64, err: *mut WinPtyError) -> WinPtyConfig,
+    pub fn winpty_config_free(cfg: WinPtyConfig),
+    pub fn winpty_config_set_initial_size(cfg: WinPtyConfig, cols: i32, rows: i32),
+    pub fn winpty_open(cfg: WinPtyConfig, err: *mut WinPtyError) -> WinPtyHandle,
+    pub fn winpty_conin_name(wp: WinPtyHandle) -> LPCWSTR,
+    pub fn winpty_conout_name(wp: WinPtyHandle) -> LPCWSTR,
+    pub fn winpty_spawn_config_new(
+        spawn_flags: u64,
+        appname: LPCWSTR,
+        cmdline: LPCWSTR,
+        cwd: LPCWSTR,
+        env: LPCWSTR,
+        err: *mut WinPtyError
+    ) -> WinPtySpawnConfig,
+    pub fn winpty_spawn_config_free(cfg: WinPtySpawnConfig),
+    pub fn winpty_spawn(
+        wp: WinPtyHandle,
+        cfg: WinPtySpawnConfig,
+        process_handle: *mut HANDLE,
+        thread_handle: *mut HANDLE,
+        create_process_error: *mut DWORD,
+        err: *mut WinPtyError
+    ) -> BOOL,
+    pub fn winpty_set_size(wp: WinPtyHandle, cols: i32, rows: i32, err: *mut WinPtyError) -> BOOL,
+    pub fn winpty_free(wp: WinPtyHandle),
+);
+
+lazy_static! {
+    static ref WINPTY: Result<WinPtyFuncs, String> =
+        WinPtyFuncs::open(Path::new("winpty.dll")).map_err(|e| format!("{:?}", e));
+}
+
+fn winpty() -> Result<&'static WinPtyFuncs, Error> {
+    WINPTY
+        .as_ref()
+        .map_err(|e| anyhow!("failed to load winpty.dll: {}", e))
+}
+
+/// Read a nul terminated wide string owned by winpty
+unsafe fn wide_string(s: LPCWSTR) -> OsString {
+    if s.is_null() {
+        return OsString::new();
+    }
+    let len = (0..).take_while(|&i| *s.offset(i) != 0).count();
+    OsString::from_wide(slice::from_raw_parts(s, len))
+}
+
+/// Turn the error winpty reported through `err`, if any, into an `Error`
+/// mentioning `what`, and free it.
+fn take_error(funcs: &WinPtyFuncs, err: WinPtyError, what: &str) -> Error {
+    if err.is_null() {
+        return anyhow!("{} failed", what);
+    }
+    let msg = unsafe { wide_string((funcs.winpty_error_msg)(err)) };
+    unsafe { (funcs.winpty_error_free)(err) };
+    anyhow!("{} failed: {}", what, msg.to_string_lossy())
+}
+
+/// Open one of the pipes of the agent by the name winpty reports for it
+fn open_pipe(name: LPCWSTR, access: DWORD, flags: DWORD) -> io::Result<OwnedHandle> {
+    let handle = unsafe {
+        CreateFileW(
+            name,
+            access,
+            0,
+            ptr::null_mut(),
+            OPEN_EXISTING,
+            flags,
+            ptr::null_mut(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedHandle::from_raw_handle(handle as _) })
+}
+
+/// A `PtySystem` running each pty in a winpty agent. The output pipe is
+/// opened for overlapped I/O, like with
+/// [`ConPtySystem::with_overlapped_io`](crate::win::conpty::ConPtySystem::with_overlapped_io).
+#[derive(Default)]
+pub struct WinPtySystem {}
+
+impl PtySystem for WinPtySystem {
+    fn openpty(&self, size: PtySize) -> anyhow::Result<PtyPair> {
+        let funcs = winpty()?;
+        let mut err: WinPtyError = ptr::null_mut();
+        let cfg = unsafe { (funcs.winpty_config_new)(WINPTY_FLAG_COLOR_ESCAPES, &mut err) };
+        if cfg.is_null() {
+            return Err(take_error(funcs, err, "winpty_config_new"));
+        }
+        unsafe {
+            (funcs.winpty_config_set_initial_size)(cfg, size.cols as i32, size.rows as i32);
+        }
+        let wp = unsafe { (funcs.winpty_open)(cfg, &mut err) };
+        unsafe { (funcs.winpty_config_free)(cfg) };
+        if wp.is_null() {
+            return Err(take_error(funcs, err, "winpty_open"));
+        }
+        let winpty = WinPty { wp, funcs };
+
+        let conin = open_pipe(unsafe { (funcs.winpty_conin_name)(wp) }, GENERIC_WRITE, 0)?;
+        let conout = open_pipe(
+            unsafe { (funcs.winpty_conout_name)(wp) },
+            GENERIC_READ,
+            FILE_FLAG_OVERLAPPED,
+        )?;
+
+        let inner = Arc::new(Mutex::new(Inner {
+            winpty,
+            readable: conout,
+            writable: Some(conin),
+            size,
+        }));
+        Ok(PtyPair {
+            master: Box::new(WinPtyMasterPty {
+                inner: inner.clone(),
+            }),
+            slave: Box::new(WinPtySlavePty { inner }),
+        })
+    }
+}
+
+/// A winpty instance, freed along with its agent when dropped
+struct WinPty {
+    wp: WinPtyHandle,
+    funcs: &'static WinPtyFuncs,
+}
+
+unsafe impl Send for WinPty {}
+unsafe impl Sync for WinPty {}
+
+impl Drop for WinPty {
+    fn drop(&mut self) {
+        unsafe { (self.funcs.winpty_free)(self.wp) };
+    }
+}
+
+struct Inner {
+    winpty: WinPty,
+    readable: OwnedHandle,
+    writable: Option<OwnedHandle>,
+    size: PtySize,
+}
+
+pub struct WinPtyMasterPty {
+    inner: Arc<Mutex<Inner>>,
+}
+
+pub struct WinPtySlavePty {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl MasterPty for WinPtyMasterPty {
+    fn resize(&self, size: PtySize) -> anyhow::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        let funcs = inner.winpty.funcs;
+        let mut err: WinPtyError = ptr::null_mut();
+        let ok = unsafe {
+            (funcs.winpty_set_size)(
+                inner.winpty.wp,
+                size.cols as i32,
+                size.rows as i32,
+                &mut err,
+            )
+        };
+        if ok == 0 {
+            return Err(take_error(funcs, err, "winpty_set_size"));
+        }
+        inner.size = size;
+        Ok(())
+    }
+
+    fn get_size(&self) -> Result<PtySize, Error> {
+        Ok(self.inner.lock().unwrap().size)
+    }
+
+    fn try_clone_reader(&self) -> anyhow::Result<Box<dyn io::Read + Send>> {
+        let readable = self.inner.lock().unwrap().readable.try_clone()?;
+        Ok(Box::new(OverlappedFile::new(readable)?))
+    }
+
+    fn take_writer(&self) -> anyhow::Result<Box<dyn io::Write + Send>> {
+        let writable = self
+            .inner
+            .lock()
+            .unwrap()
+            .writable
+            .take()
+            .ok_or_else(|| anyhow!("writer already taken"))?;
+        Ok(Box::new(unsafe {
+            FileDescriptor::from_raw_handle(writable.into_raw_handle())
+        }))
+    }
+}
+
+impl WinPtyMasterPty {
+    /// Clone the handle of the pipe carrying the output of the agent, opened
+    /// for overlapped I/O so that it can be registered with an async runtime.
+    pub fn try_clone_async_reader(&self) -> anyhow::Result<OwnedHandle> {
+        Ok(self.inner.lock().unwrap().readable.try_clone()?)
+    }
+}
+
+impl SlavePty for WinPtySlavePty {
+    fn spawn_command(&self, cmd: CommandBuilder) -> anyhow::Result<Box<dyn Child + Send + Sync>> {
+        let inner = self.inner.lock().unwrap();
+        let funcs = inner.winpty.funcs;
+
+        let (exe, cmdline) = cmd.cmdline()?;
+        let cwd = cmd.current_directory();
+        let env = cmd.environment_block();
+
+        let mut err: WinPtyError = ptr::null_mut();
+        let spawn_cfg = unsafe {
+            (funcs.winpty_spawn_config_new)(
+                WINPTY_SPAWN_FLAG_AUTO_SHUTDOWN,
+                exe.as_ptr(),
+                cmdline.as_ptr(),
+                cwd.as_ref().map(|c| c.as_ptr()).unwrap_or(ptr::null()),
+                env.as_ptr(),
+                &mut err,
+            )
+        };
+        if spawn_cfg.is_null() {
+            return Err(take_error(funcs, err, "winpty_spawn_config_new"));
+        }
+
+        let mut process: HANDLE = ptr::null_mut();
+        let mut create_process_error: DWORD = 0;
+        let ok = unsafe {
+            (funcs.winpty_spawn)(
+                inner.winpty.wp,
+                spawn_cfg,
+                &mut process,
+                ptr::null_mut(),
+                &mut create_process_error,
+                &mut err,
+            )
+        };
+        unsafe { (funcs.winpty_spawn_config_free)(spawn_cfg) };
+        if ok == 0 {
+            let err = take_error(funcs, err, "winpty_spawn");
+            if create_process_error != 0 {
+                bail!(
+                    "{}: {}",
+                    err,
+                    io::Error::from_raw_os_error(create_process_error as i32)
+                );
+            }
+            return Err(err);
+        }
+
+        let proc = unsafe { filedescriptor::OwnedHandle::from_raw_handle(process as _) };
+        Ok(Box::new(WinChild::new(proc)))
+    }
+}
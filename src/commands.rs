@@ -1,6 +1,6 @@
-use dialoguer::Confirm;
+use dialoguer::{Confirm, Password};
 use std::net::IpAddr;
-use std::{fs::File, io::prelude::*, path::PathBuf, process, time::Duration};
+use std::{fs::File, io::prelude::*, path::PathBuf, process, str::FromStr, time::Duration};
 
 #[cfg(feature = "web_server_capability")]
 use isahc::{config::RedirectPolicy, prelude::*, HttpClient, Request};
@@ -9,7 +9,7 @@ use zellij_client::{
     old_config_converter::{
         config_yaml_to_config_kdl, convert_old_yaml_files, layout_yaml_to_layout_kdl,
     },
-    os_input_output::get_client_os_input,
+    os_input_output::{get_client_os_input, get_embedded_client_os_input},
     start_client as start_client_impl, ClientInfo,
 };
 
@@ -18,34 +18,43 @@ use zellij_utils::sessions::{
     generate_unique_session_name, get_active_session, get_resurrectable_sessions, get_sessions,
     get_sessions_sorted_by_mtime, kill_session as kill_session_impl, match_session_name,
     print_sessions, print_sessions_with_index, resurrection_layout, session_exists,
-    validate_session_name, ActiveSession, SessionNameMatch,
+    session_server_is_running, validate_server_socket_path, validate_session_name, ActiveSession,
+    SessionNameMatch,
 };
 
-use zellij_utils::consts::session_layout_cache_file_name;
+use zellij_utils::consts::{session_layout_cache_file_name, session_lock_file_name};
+use zellij_utils::stale_artifacts::remove_stale_artifacts;
 
 #[cfg(feature = "web_server_capability")]
 use zellij_client::web_client::start_web_client as start_web_client_impl;
 
 #[cfg(feature = "web_server_capability")]
-use zellij_utils::web_server_commands::shutdown_all_webserver_instances;
+use zellij_utils::shared::parse_base_url;
+#[cfg(feature = "web_server_capability")]
+use zellij_utils::web_server_commands::{
+    create_webserver_sender, discover_webserver_sockets, find_webserver_socket,
+    query_webserver_with_response, send_webserver_instruction, shutdown_all_webserver_instances,
+    InstructionForWebServer, WebServerResponse,
+};
 
 #[cfg(feature = "web_server_capability")]
 use zellij_utils::web_authentication_tokens::{
-    create_token, list_tokens, revoke_all_tokens, revoke_token,
+    create_share_link_token, create_token, list_tokens, revoke_all_share_links, revoke_all_tokens,
+    revoke_token,
 };
 
 use miette::{Report, Result};
 use zellij_server::{os_input_output::get_server_os_input, start_server as start_server_impl};
 use zellij_utils::{
     cli::{CliArgs, Command, SessionCommand, Sessions},
-    data::ConnectToSession,
+    data::{ConnectToSession, PaneId},
     envs,
     input::{
         actions::Action,
         config::{Config, ConfigError},
         options::Options,
     },
-    setup::Setup,
+    setup::{find_default_config_dir, Setup},
 };
 
 pub(crate) use zellij_utils::sessions::list_sessions;
@@ -157,20 +166,39 @@ fn get_os_input<OsInputOutput>(
     }
 }
 
+/// The client installs its own signal handlers unless asked not to with --no-signal-handlers.
+fn client_os_input_fn(
+    opts: &CliArgs,
+) -> fn() -> Result<zellij_client::os_input_output::ClientOsInputOutput, std::io::Error> {
+    if opts.no_signal_handlers {
+        get_embedded_client_os_input
+    } else {
+        get_client_os_input
+    }
+}
+
 pub(crate) fn start_server(path: PathBuf, debug: bool) {
     // Set instance-wide debug mode
     zellij_utils::consts::DEBUG_MODE.set(debug).unwrap();
 
+    if let Err(e) = validate_server_socket_path(&path) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    if session_server_is_running(&path) {
+        eprintln!(
+            "A server is already running at {:?}, refusing to start another one.",
+            path
+        );
+        process::exit(1);
+    }
+
     #[cfg(windows)]
     {
         // On Windows there is no fork/daemonize. Instead we re-spawn the
         // current executable as a detached, window-less process when we are
         // not already running in server mode.
-        if std::env::var("ZELLIJ_SERVER_MODE").is_err() {
-            use std::os::windows::process::CommandExt;
-            const CREATE_NO_WINDOW: u32 = 0x08000000;
-            const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
-
+        if std::env::var(zellij_utils::envs::SERVER_MODE_ENV_KEY).is_err() {
             let exe = std::env::current_exe().expect("failed to get current exe path");
             // Reconstruct the args that were used to start this server
             let mut args: Vec<String> = std::env::args().skip(1).collect();
@@ -179,12 +207,12 @@ pub(crate) fn start_server(path: PathBuf, debug: bool) {
                 args.push("--debug".to_string());
             }
 
-            std::process::Command::new(exe)
-                .args(&args)
-                .env("ZELLIJ_SERVER_MODE", "1")
-                .creation_flags(CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP)
-                .spawn()
-                .expect("failed to spawn detached server process");
+            zellij_utils::shared::spawn_detached(
+                std::process::Command::new(exe)
+                    .args(&args)
+                    .env(zellij_utils::envs::SERVER_MODE_ENV_KEY, "1"),
+            )
+            .expect("failed to spawn detached server process");
             return;
         }
     }
@@ -267,6 +295,58 @@ pub(crate) fn stop_web_server() -> Result<(), String> {
     std::process::exit(2);
 }
 
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn stop_web_server_for_restart(
+    web_server_base_url: &str,
+) -> Result<(IpAddr, u16), String> {
+    // returns the address the web server was listening on, once it is free for its successor
+    let socket_path = find_web_server_socket(web_server_base_url)?;
+    let socket_path = socket_path.to_str().unwrap_or("");
+    let (ip, port) = match query_webserver_with_response(
+        socket_path,
+        InstructionForWebServer::QueryStatus,
+        DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS,
+    ) {
+        Ok(WebServerResponse::Status(status)) => {
+            let ip = status
+                .ip
+                .parse::<IpAddr>()
+                .map_err(|e| format!("Invalid web server address {}: {}", status.ip, e))?;
+            (ip, status.port)
+        },
+        Ok(response) => return Err(format!("Unexpected response: {:?}", response)),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut sender = create_webserver_sender(socket_path).map_err(|e| e.to_string())?;
+    send_webserver_instruction(&mut sender, InstructionForWebServer::RestartWebServer)
+        .map_err(|e| e.to_string())?;
+
+    let deadline = std::time::Instant::now() + WEB_SERVER_RESTART_TIMEOUT;
+    while std::net::TcpListener::bind((ip, port)).is_err() {
+        if std::time::Instant::now() > deadline {
+            return Err(format!(
+                "Web server did not release port {} on {} in time",
+                port, ip
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    Ok((ip, port))
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn stop_web_server_for_restart(
+    _web_server_base_url: &str,
+) -> Result<(IpAddr, u16), String> {
+    log::error!(
+        "This version of Zellij was compiled without web server support, cannot restart web server!"
+    );
+    eprintln!(
+        "This version of Zellij was compiled without web server support, cannot restart web server!"
+    );
+    std::process::exit(2);
+}
+
 #[cfg(feature = "web_server_capability")]
 pub(crate) fn create_auth_token(name: Option<String>, read_only: bool) -> Result<String, String> {
     // returns the token and it's name
@@ -353,6 +433,11 @@ pub(crate) fn list_auth_tokens() -> Result<Vec<String>, String> {
 
 /// Default timeout for web server status check (in seconds)
 pub const DEFAULT_WEB_SERVER_STATUS_TIMEOUT_SECS: u64 = 30;
+#[cfg(feature = "web_server_capability")]
+const DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS: u64 = 500;
+// the web server closes the websockets of its clients before releasing its port
+#[cfg(feature = "web_server_capability")]
+const WEB_SERVER_RESTART_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[cfg(feature = "web_server_capability")]
 pub(crate) fn web_server_status(
@@ -395,6 +480,210 @@ pub(crate) fn web_server_status(
     std::process::exit(2);
 }
 
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn web_server_activity(web_server_base_url: &str) -> Result<Vec<String>, String> {
+    // returns the state of the web server and the clients it is serving line by line
+    let socket_path = find_web_server_socket(web_server_base_url)?;
+    let socket_path = socket_path.to_str().unwrap_or("");
+    let status = match query_webserver_with_response(
+        socket_path,
+        InstructionForWebServer::QueryStatus,
+        DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS,
+    ) {
+        Ok(WebServerResponse::Status(status)) => status,
+        Ok(response) => return Err(format!("Unexpected response: {:?}", response)),
+        Err(e) => return Err(e.to_string()),
+    };
+    let web_clients = match query_webserver_with_response(
+        socket_path,
+        InstructionForWebServer::ListConnectedWebClients,
+        DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS,
+    ) {
+        Ok(WebServerResponse::ConnectedWebClients(web_clients)) => web_clients,
+        Ok(response) => return Err(format!("Unexpected response: {:?}", response)),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut res = vec![];
+    res.push(format!("Listening on {}:{}", status.ip, status.port));
+    res.push(format!("Uptime: {}", format_uptime(status.uptime_secs)));
+    res.push(format!(
+        "Active websockets: {}",
+        status.active_websocket_count
+    ));
+    if status.session_client_counts.is_empty() {
+        res.push("Serving no sessions".to_owned());
+    } else {
+        let sessions: Vec<String> = status
+            .session_client_counts
+            .iter()
+            .map(|s| {
+                let clients = if s.client_count == 1 {
+                    "client"
+                } else {
+                    "clients"
+                };
+                format!("{} ({} {})", s.session_name, s.client_count, clients)
+            })
+            .collect();
+        res.push(format!("Serving sessions: {}", sessions.join(", ")));
+    }
+    if web_clients.is_empty() {
+        res.push("No web clients connected".to_owned());
+    } else {
+        res.push("Connected web clients:".to_owned());
+        for web_client in web_clients {
+            let access_type = if web_client.is_read_only {
+                " [READ-ONLY]"
+            } else {
+                ""
+            };
+            res.push(format!(
+                "  {}: {}{}",
+                web_client.web_client_id,
+                web_client
+                    .session_name
+                    .unwrap_or_else(|| "not attached to a session yet".to_owned()),
+                access_type
+            ));
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(feature = "web_server_capability")]
+fn format_uptime(uptime_secs: u64) -> String {
+    let (hours, minutes, seconds) = (
+        uptime_secs / 3600,
+        (uptime_secs % 3600) / 60,
+        uptime_secs % 60,
+    );
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn unresponsive_web_server_sockets() -> Vec<PathBuf> {
+    // the sockets left behind by web servers that are no longer running
+    discover_webserver_sockets()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|socket_path| {
+            !matches!(
+                query_webserver_with_response(
+                    socket_path.to_str().unwrap_or(""),
+                    InstructionForWebServer::QueryStatus,
+                    DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS,
+                ),
+                Ok(WebServerResponse::Status(_))
+            )
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn unresponsive_web_server_sockets() -> Vec<PathBuf> {
+    vec![]
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn web_server_activity(_web_server_base_url: &str) -> Result<Vec<String>, String> {
+    log::error!(
+        "This version of Zellij was compiled without web server support, cannot get web server status!"
+    );
+    eprintln!(
+        "This version of Zellij was compiled without web server support, cannot get web server status!"
+    );
+    std::process::exit(2);
+}
+
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn rebind_web_server_port(web_server_base_url: &str, port: u16) -> Result<(), String> {
+    let socket_path = find_web_server_socket(web_server_base_url)?;
+    match query_webserver_with_response(
+        socket_path.to_str().unwrap_or(""),
+        InstructionForWebServer::RebindPort(port),
+        DEFAULT_WEB_SERVER_QUERY_TIMEOUT_MS,
+    ) {
+        Ok(WebServerResponse::ReboundPort(_)) => Ok(()),
+        Ok(WebServerResponse::Error(e)) => Err(e),
+        Ok(response) => Err(format!("Unexpected response: {:?}", response)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn rebind_web_server_port(_web_server_base_url: &str, _port: u16) -> Result<(), String> {
+    log::error!(
+        "This version of Zellij was compiled without web server support, cannot rebind web server port!"
+    );
+    eprintln!(
+        "This version of Zellij was compiled without web server support, cannot rebind web server port!"
+    );
+    std::process::exit(2);
+}
+
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn create_share_link(
+    web_server_base_url: &str,
+    session_name: &str,
+    expires_in_minutes: u64,
+) -> Result<String, String> {
+    // returns the url of the share link
+    match session_exists(session_name) {
+        Ok(true) => {},
+        Ok(false) => return Err(format!("No session named {} found", session_name)),
+        Err(e) => return Err(format!("Failed to look up sessions: {:?}", e)),
+    }
+    create_share_link_token(session_name, expires_in_minutes * 60)
+        .map(|share_token| format!("{}/share/{}", web_server_base_url, share_token))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn create_share_link(
+    _web_server_base_url: &str,
+    _session_name: &str,
+    _expires_in_minutes: u64,
+) -> Result<String, String> {
+    log::error!(
+        "This version of Zellij was compiled without web server support, cannot create share link!"
+    );
+    eprintln!(
+        "This version of Zellij was compiled without web server support, cannot create share link!"
+    );
+    std::process::exit(2);
+}
+
+#[cfg(feature = "web_server_capability")]
+pub(crate) fn revoke_share_links() -> Result<usize, String> {
+    // returns the revoked count
+    revoke_all_share_links().map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "web_server_capability"))]
+pub(crate) fn revoke_share_links() -> Result<usize, String> {
+    log::error!(
+        "This version of Zellij was compiled without web server support, cannot revoke share links!"
+    );
+    eprintln!(
+        "This version of Zellij was compiled without web server support, cannot revoke share links!"
+    );
+    std::process::exit(2);
+}
+
+#[cfg(feature = "web_server_capability")]
+fn find_web_server_socket(web_server_base_url: &str) -> Result<PathBuf, String> {
+    let address = parse_base_url(web_server_base_url).map_err(|e| e.to_string())?;
+    find_webserver_socket(&address.ip, address.port)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No web server found listening on {}", web_server_base_url))
+}
+
 fn find_indexed_session(
     sessions: Vec<String>,
     config_options: Options,
@@ -422,6 +711,7 @@ pub(crate) fn send_action_to_session(
     cli_action: zellij_utils::cli::CliAction,
     requested_session_name: Option<String>,
     config: Option<Config>,
+    session_secret: Option<String>,
 ) {
     match get_active_session() {
         ActiveSession::None => {
@@ -439,7 +729,7 @@ pub(crate) fn send_action_to_session(
                     std::process::exit(1);
                 }
             }
-            attach_with_cli_client(cli_action, &session_name, config);
+            attach_with_cli_client(cli_action, &session_name, config, session_secret);
         },
         ActiveSession::Many => {
             let existing_sessions: Vec<String> = get_sessions()
@@ -449,7 +739,7 @@ pub(crate) fn send_action_to_session(
                 .collect();
             if let Some(session_name) = requested_session_name {
                 if existing_sessions.contains(&session_name) {
-                    attach_with_cli_client(cli_action, &session_name, config);
+                    attach_with_cli_client(cli_action, &session_name, config, session_secret);
                 } else {
                     eprintln!(
                         "Session '{}' not found. The following sessions are active:",
@@ -459,7 +749,7 @@ pub(crate) fn send_action_to_session(
                     std::process::exit(1);
                 }
             } else if let Ok(session_name) = envs::get_session_name() {
-                attach_with_cli_client(cli_action, &session_name, config);
+                attach_with_cli_client(cli_action, &session_name, config, session_secret);
             } else {
                 eprintln!("Please specify the session name to send actions to. The following sessions are active:");
                 list_sessions(false, false, true);
@@ -541,12 +831,18 @@ fn attach_with_cli_client(
     cli_action: zellij_utils::cli::CliAction,
     session_name: &str,
     config: Option<Config>,
+    session_secret: Option<String>,
 ) {
     let os_input = get_os_input(zellij_client::os_input_output::get_cli_client_os_input);
     let get_current_dir = || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     match Action::actions_from_cli(cli_action, Box::new(get_current_dir), config) {
         Ok(actions) => {
-            zellij_client::cli_client::start_cli_client(Box::new(os_input), session_name, actions);
+            zellij_client::cli_client::start_cli_client(
+                Box::new(os_input),
+                session_name,
+                actions,
+                session_secret,
+            );
             std::process::exit(0);
         },
         Err(e) => {
@@ -601,7 +897,7 @@ fn attach_with_session_name(
                 print_sessions(
                     sessions
                         .iter()
-                        .map(|s| (s.clone(), Duration::default(), false))
+                        .map(|s| (s.clone(), Duration::default(), false, None))
                         .collect(),
                     false,
                     false,
@@ -630,6 +926,25 @@ fn attach_with_session_name(
     }
 }
 
+// a locked session turns away clients without its secret, so we ask for it before attaching
+// (at which point the terminal belongs to the client) rather than after being turned away
+fn prompt_for_session_secret_if_locked(opts: &mut CliArgs, client: &ClientInfo) {
+    if let ClientInfo::Attach(session_name, _) = client {
+        if opts.session_secret.is_none() && session_lock_file_name(session_name).exists() {
+            match Password::new()
+                .with_prompt(format!("Secret of session \"{}\"", session_name))
+                .interact()
+            {
+                Ok(session_secret) => opts.session_secret = Some(session_secret),
+                Err(e) => {
+                    eprintln!("Failed to read the session secret: {}", e);
+                    process::exit(1);
+                },
+            }
+        }
+    }
+}
+
 pub(crate) fn start_client(opts: CliArgs) {
     // look for old YAML config/layout/theme files and convert them to KDL
     convert_old_yaml_files(&opts);
@@ -652,8 +967,19 @@ pub(crate) fn start_client(opts: CliArgs) {
         },
     };
 
+    if !matches!(
+        &opts.command,
+        Some(Command::Setup(Setup { clean: true, .. }))
+    ) {
+        // `zellij setup --clean` already cleaned up before getting here
+        std::thread::Builder::new()
+            .name("stale_artifact_cleanup".to_string())
+            .spawn(remove_stale_artifacts)
+            .ok();
+    }
+
     let mut reconnect_to_session: Option<ConnectToSession> = None;
-    let os_input = get_os_input(get_client_os_input);
+    let os_input = get_os_input(client_os_input_fn(&opts));
     loop {
         let os_input = os_input.clone();
         let mut config = config.clone();
@@ -685,6 +1011,10 @@ pub(crate) fn start_client(opts: CliArgs) {
                     token: None,
                     remember: false,
                     forget: false,
+                    // this terminal keeps its own theme in the session it switches to
+                    theme: opts.attach_theme(),
+                    pane_id: None,
+                    force: false,
                 }));
             } else {
                 opts.command = None;
@@ -717,6 +1047,9 @@ pub(crate) fn start_client(opts: CliArgs) {
             token,
             remember,
             forget,
+            theme,
+            pane_id,
+            force: _,
         })) = opts.command.clone()
         {
             if let Some(remote_session_url) = session_name.as_ref().and_then(|s| {
@@ -731,7 +1064,13 @@ pub(crate) fn start_client(opts: CliArgs) {
                     std::process::exit(2);
                 }
 
-                if options.is_some() || create || create_background || force_run_commands {
+                if options.is_some()
+                    || create
+                    || create_background
+                    || force_run_commands
+                    || theme.is_some()
+                    || pane_id.is_some()
+                {
                     eprintln!("Cannot attach to remote session with options.");
                     std::process::exit(2);
                 }
@@ -749,12 +1088,31 @@ pub(crate) fn start_client(opts: CliArgs) {
                     std::process::exit(2);
                 }
             } else {
-                let config_options = match options.as_deref() {
+                let mut config_options = match options.as_deref() {
                     Some(SessionCommand::Options(o)) => {
                         config_options.merge_from_cli(o.to_owned().into())
                     },
                     None => config_options,
                 };
+                if let Some(theme) = theme {
+                    if config.theme_config(Some(&theme)).is_none() {
+                        eprintln!("No theme named \"{}\" was found.", theme);
+                        process::exit(2);
+                    }
+                    config_options.theme = Some(theme);
+                }
+                let attach_pane_id = match pane_id.as_deref().map(PaneId::from_str) {
+                    Some(Ok(PaneId::Terminal(id))) => Some((id, false)),
+                    Some(Ok(PaneId::Plugin(id))) => Some((id, true)),
+                    Some(Err(_)) => {
+                        eprintln!(
+                            "Malformed pane id: {}, expecting either a bare integer (eg. 1), a terminal pane id (eg. terminal_1) or a plugin pane id (eg. plugin_1)",
+                            pane_id.unwrap_or_default()
+                        );
+                        process::exit(2);
+                    },
+                    None => None,
+                };
                 should_create_detached = create_background;
 
                 let mut client = if let Some(idx) = index {
@@ -823,7 +1181,11 @@ pub(crate) fn start_client(opts: CliArgs) {
                     .and_then(|r| r.tab_position.clone());
                 let pane_id_to_focus = reconnect_to_session
                     .as_ref()
-                    .and_then(|r| r.pane_id.clone());
+                    .and_then(|r| r.pane_id.clone())
+                    .or(attach_pane_id);
+                if !is_a_reconnect {
+                    prompt_for_session_secret_if_locked(&mut opts, &client);
+                }
                 reconnect_to_session = start_client_impl(
                     Box::new(os_input),
                     opts,
@@ -867,11 +1229,18 @@ pub(crate) fn start_client(opts: CliArgs) {
                     }
                     match config_options.attach_to_session {
                         Some(true) => {
-                            let client = attach_with_session_name(
+                            let mut client = attach_with_session_name(
                                 Some(session_name.clone()),
                                 config_options.clone(),
                                 true,
                             );
+                            // eg. the layout made of a session template, when it is created
+                            if let Some(layout_info) = layout_info {
+                                client.set_layout_info(layout_info);
+                            }
+                            if !is_a_reconnect {
+                                prompt_for_session_secret_if_locked(&mut opts, &client);
+                            }
                             reconnect_to_session = start_client_impl(
                                 Box::new(os_input),
                                 opts,
@@ -956,6 +1325,29 @@ pub(crate) fn list_aliases(opts: CliArgs) {
     process::exit(0);
 }
 
+pub(crate) fn doctor(opts: CliArgs) {
+    use zellij_client::doctor::{diagnose, Severity};
+
+    let config_options = Setup::from_cli_args(&opts)
+        .map(|(_, _, config_options, _, _)| config_options)
+        .map_err(|e| e.to_string());
+    let config_dir = opts.config_dir.clone().or_else(find_default_config_dir);
+    let findings = diagnose(Config::config_file_path(&opts), config_dir, config_options);
+    for finding in &findings {
+        println!("{}", finding);
+    }
+    let count = |severity| {
+        findings
+            .iter()
+            .filter(|finding| finding.severity == severity)
+            .count()
+    };
+    let (problems, warnings) = (count(Severity::Problem), count(Severity::Warning));
+    println!();
+    println!("{} problem(s), {} warning(s)", problems, warnings);
+    process::exit(if problems > 0 { 1 } else { 0 });
+}
+
 pub(crate) fn watch_session(session_name: Option<String>, opts: CliArgs) {
     let (config, _, config_options, _, _) = match Setup::from_cli_args(&opts) {
         Ok(results) => results,
@@ -984,7 +1376,7 @@ pub(crate) fn watch_session(session_name: Option<String>, opts: CliArgs) {
                 print_sessions(
                     sessions
                         .iter()
-                        .map(|s| (s.clone(), Duration::default(), false))
+                        .map(|s| (s.clone(), Duration::default(), false, None))
                         .collect(),
                     false,
                     false,
@@ -1013,7 +1405,7 @@ pub(crate) fn watch_session(session_name: Option<String>, opts: CliArgs) {
     let mut opts = opts.clone();
     opts.session = Some(client_info.get_session_name().to_string());
 
-    let os_input = get_os_input(get_client_os_input);
+    let os_input = get_os_input(client_os_input_fn(&opts));
 
     // Start the watcher client
     start_client_impl(
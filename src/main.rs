@@ -76,6 +76,48 @@ fn main() {
         std::process::exit(0);
     }
 
+    // ConPTY console size helper: spawned inside a ConPTY to report the size
+    // of its console window, which a program attached to it may have changed.
+    // Exits with the columns in the upper and the rows in the lower 16 bits
+    // of the exit code, or with 0 if the size cannot be read.
+    #[cfg(windows)]
+    if std::env::args_os().any(|a| a == "--conpty-console-size") {
+        unsafe {
+            use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+            use windows_sys::Win32::Storage::FileSystem::{
+                CreateFileW, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+            };
+            use windows_sys::Win32::System::Console::*;
+
+            const GENERIC_READ: u32 = 0x80000000;
+            const GENERIC_WRITE: u32 = 0x40000000;
+            // our stdout goes to NUL, so open the console screen buffer itself
+            let conout_name: Vec<u16> = "CONOUT$\0".encode_utf16().collect();
+            let conout = CreateFileW(
+                conout_name.as_ptr(),
+                GENERIC_READ | GENERIC_WRITE,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                0,
+                std::ptr::null_mut(),
+            );
+            if conout == INVALID_HANDLE_VALUE {
+                std::process::exit(0);
+            }
+            let mut info: CONSOLE_SCREEN_BUFFER_INFO = std::mem::zeroed();
+            let queried = GetConsoleScreenBufferInfo(conout, &mut info) != 0;
+            CloseHandle(conout);
+            let window = info.srWindow;
+            let cols = window.Right as i32 - window.Left as i32 + 1;
+            let rows = window.Bottom as i32 - window.Top as i32 + 1;
+            if !queried || cols <= 0 || rows <= 0 {
+                std::process::exit(0);
+            }
+            std::process::exit((cols << 16) | rows);
+        }
+    }
+
     configure_logger();
     create_config_and_cache_folders();
     let opts = CliArgs::parse();
@@ -83,7 +125,7 @@ fn main() {
     {
         let config = Config::try_from(&opts).ok();
         if let Some(Command::Sessions(Sessions::Action(cli_action))) = opts.command {
-            commands::send_action_to_session(cli_action, opts.session, config);
+            commands::send_action_to_session(cli_action, opts.session, config, opts.session_secret);
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Run {
@@ -107,6 +149,7 @@ fn main() {
             block_until_exit,
             near_current_pane,
             borderless,
+            in_background,
         })) = opts.command
         {
             let cwd = cwd.or_else(|| std::env::current_dir().ok());
@@ -145,8 +188,14 @@ fn main() {
                 unblock_condition,
                 near_current_pane,
                 borderless,
+                in_background,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.session_secret,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Plugin {
@@ -189,8 +238,14 @@ fn main() {
                 unblock_condition,
                 near_current_pane: false,
                 borderless,
+                in_background: false,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.session_secret,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::Edit {
@@ -231,7 +286,12 @@ fn main() {
                 near_current_pane,
                 borderless,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.session_secret,
+            );
             std::process::exit(0);
         }
         if let Some(Command::Sessions(Sessions::ConvertConfig { old_config_file })) = opts.command {
@@ -268,7 +328,12 @@ fn main() {
                 plugin_cwd: None,
                 plugin_title: None,
             };
-            commands::send_action_to_session(command_cli_action, opts.session, config);
+            commands::send_action_to_session(
+                command_cli_action,
+                opts.session,
+                config,
+                opts.session_secret,
+            );
             std::process::exit(0);
         }
     }
@@ -282,6 +347,8 @@ fn main() {
         commands::list_sessions(no_formatting, short, reverse);
     } else if let Some(Command::Sessions(Sessions::ListAliases)) = opts.command {
         commands::list_aliases(opts);
+    } else if let Some(Command::Sessions(Sessions::Doctor)) = opts.command {
+        commands::doctor(opts);
     } else if let Some(Command::Sessions(Sessions::Watch { ref session_name })) = opts.command {
         commands::watch_session(session_name.clone(), opts);
     } else if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
@@ -323,7 +390,12 @@ fn main() {
                 block_until_exit_failure: false,
                 block_until_exit: false,
             };
-            commands::send_action_to_session(new_layout_cli_action, Some(session_name), config);
+            commands::send_action_to_session(
+                new_layout_cli_action,
+                Some(session_name),
+                config,
+                opts.session_secret.clone(),
+            );
         } else {
             commands::start_client(opts);
         }
@@ -333,7 +405,27 @@ fn main() {
         opts.layout = Some(layout_for_new_session.clone());
         commands::start_client(opts);
     } else if let Some(Command::Web(web_opts)) = &opts.command {
-        if web_opts.get_start() {
+        if web_opts.restart {
+            let config_options = commands::get_config_options_from_cli_args(&opts)
+                .expect("Can't find config options");
+            let web_server_base_url = web_server_base_url_from_config(config_options);
+            match commands::stop_web_server_for_restart(&web_server_base_url) {
+                Ok((ip, port)) => {
+                    commands::start_web_server(
+                        opts.clone(),
+                        web_opts.daemonize,
+                        Some(ip),
+                        Some(port),
+                        web_opts.cert.clone(),
+                        web_opts.key.clone(),
+                    );
+                },
+                Err(e) => {
+                    eprintln!("Failed to restart web server: {}", e);
+                    std::process::exit(2)
+                },
+            }
+        } else if web_opts.get_start() {
             let daemonize = web_opts.daemonize;
             commands::start_web_server(
                 opts.clone(),
@@ -373,9 +465,31 @@ fn main() {
                         println!("Consider stopping the server with: zellij web --stop");
                         println!("And then restarting it with: zellij web --start");
                     }
+                    match commands::web_server_activity(&web_server_base_url) {
+                        Ok(activity) => {
+                            for line in activity {
+                                println!("{}", line);
+                            }
+                        },
+                        Err(e) => {
+                            log::error!("Failed to query web server activity: {}", e);
+                            println!(
+                                "Failed to query the web server over its control pipe: {}",
+                                e
+                            );
+                        },
+                    }
                 },
                 Err(_e) => {
                     println!("Web server is offline, checked: {}", web_server_base_url);
+                    let unresponsive_sockets = commands::unresponsive_web_server_sockets();
+                    if let Some(socket_path) = unresponsive_sockets.first() {
+                        println!(
+                            "Found {} socket(s) of web servers that are not responding, left over from web servers that did not shut down cleanly (e.g. {})",
+                            unresponsive_sockets.len(),
+                            socket_path.display()
+                        );
+                    }
                 },
             }
         } else if web_opts.create_token {
@@ -429,6 +543,52 @@ fn main() {
                     std::process::exit(2)
                 },
             }
+        } else if let Some(port) = web_opts.rebind_port {
+            let config_options = commands::get_config_options_from_cli_args(&opts)
+                .expect("Can't find config options");
+            let web_server_base_url = web_server_base_url_from_config(config_options);
+            match commands::rebind_web_server_port(&web_server_base_url, port) {
+                Ok(()) => {
+                    println!("Web server now accepts new connections on port {}.", port);
+                },
+                Err(e) => {
+                    eprintln!("Failed to rebind web server port: {}", e);
+                    std::process::exit(2)
+                },
+            }
+        } else if let Some(session_name) = &web_opts.create_share_link {
+            let config_options = commands::get_config_options_from_cli_args(&opts)
+                .expect("Can't find config options");
+            let web_server_base_url = web_server_base_url_from_config(config_options);
+            let expires_in_minutes = web_opts.share_link_expiry.unwrap_or(60);
+            match commands::create_share_link(
+                &web_server_base_url,
+                session_name,
+                expires_in_minutes,
+            ) {
+                Ok(share_link) => {
+                    println!(
+                        "Created a read-only share link for session {}, expiring in {} minutes",
+                        session_name, expires_in_minutes
+                    );
+                    println!("");
+                    println!("{}", share_link);
+                },
+                Err(e) => {
+                    eprintln!("Failed to create share link: {}", e);
+                    std::process::exit(2)
+                },
+            }
+        } else if web_opts.revoke_share_links {
+            match commands::revoke_share_links() {
+                Ok(revoked_count) => {
+                    println!("Successfully revoked {} share links", revoked_count);
+                },
+                Err(e) => {
+                    eprintln!("Failed to revoke share links: {}", e);
+                    std::process::exit(2)
+                },
+            }
         } else if web_opts.list_tokens {
             match commands::list_auth_tokens() {
                 Ok(token_list) => {
@@ -67,3 +67,12 @@ fn web_cli_status_with_stop_fails() {
     let args = CliArgs::try_parse_from(["zellij", "web", "--status", "--stop"]);
     assert!(args.is_err());
 }
+
+#[test]
+fn no_signal_handlers_flag_works() {
+    let args = CliArgs::try_parse_from(["zellij", "--no-signal-handlers", "attach", "main"]);
+    assert!(args.is_ok());
+    assert!(args.unwrap().no_signal_handlers);
+    let args = CliArgs::try_parse_from(["zellij", "attach", "main"]);
+    assert!(!args.unwrap().no_signal_handlers);
+}
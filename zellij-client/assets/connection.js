@@ -11,6 +11,12 @@ let isDisconnected = false;
 let reconnectionTimeout = null;
 let hasConnectedBefore = false;
 let isPageUnloading = false;
+let isWaitingForRestart = false;
+let sessionToRestartInto = null;
+
+// how long to wait for a restarting web server to come back up before asking the user
+const SERVER_RESTART_TIMEOUT_MS = 30000;
+const SERVER_RESTART_POLL_INTERVAL_MS = 500;
 
 /**
  * Get the delay for reconnection attempts using exponential backoff
@@ -52,6 +58,44 @@ export async function handleDisconnected() {
     isDisconnected = false;
 }
 
+/**
+ * Remember the session to reconnect to once the restarting web server is back up
+ * @param {string|null} sessionName - The session this client is attached to
+ */
+export function setSessionToRestartInto(sessionName) {
+    sessionToRestartInto = sessionName;
+}
+
+/**
+ * Handle the web server restarting (close code 4002) by reconnecting to the same session as soon
+ * as the new web server is up
+ * @returns {Promise<void>}
+ */
+export async function handleServerRestart() {
+    if (isWaitingForRestart || isPageUnloading) {
+        return;
+    }
+    isWaitingForRestart = true;
+
+    const deadline = Date.now() + SERVER_RESTART_TIMEOUT_MS;
+    while (Date.now() < deadline) {
+        await new Promise((resolve) =>
+            setTimeout(resolve, SERVER_RESTART_POLL_INTERVAL_MS)
+        );
+        if (await checkConnection()) {
+            if (sessionToRestartInto) {
+                window.location.href = `${getBaseUrl()}/${encodeURIComponent(sessionToRestartInto)}`;
+            } else {
+                window.location.reload();
+            }
+            return;
+        }
+    }
+
+    isWaitingForRestart = false;
+    handleReconnection();
+}
+
 /**
  * Handle reconnection attempts with exponential backoff
  * @returns {Promise<void>}
@@ -128,4 +172,6 @@ export function resetConnectionState() {
     reconnectionTimeout = null;
     hasConnectedBefore = false;
     isPageUnloading = false;
+    isWaitingForRestart = false;
+    sessionToRestartInto = null;
 }
@@ -0,0 +1,104 @@
+/**
+ * File transfer between the browser and the panes of the session
+ */
+
+import { getBaseUrl } from "./utils.js";
+
+/**
+ * Upload a file into the cwd of the focused pane
+ * @param {string} webClientId - Client ID from authentication
+ * @param {File} file - The file to upload
+ */
+export async function uploadFile(webClientId, file) {
+    const baseUrl = getBaseUrl();
+    const fileName = encodeURIComponent(file.name);
+    const response = await fetch(
+        `${baseUrl}/upload/${encodeURIComponent(webClientId)}?file_name=${fileName}`,
+        {
+            method: "POST",
+            body: file,
+        }
+    );
+    if (!response.ok) {
+        console.error(`Failed to upload ${file.name}: ${await response.text()}`);
+    }
+}
+
+/**
+ * Download a file of the session
+ * @param {string} webClientId - Client ID from authentication
+ * @param {string} file - Either "screen" (dump of the focused pane) or "log"
+ */
+export function downloadFile(webClientId, file) {
+    const baseUrl = getBaseUrl();
+    const link = document.createElement("a");
+    link.href = `${baseUrl}/download/${encodeURIComponent(webClientId)}/${file}`;
+    link.download = "";
+    document.body.appendChild(link);
+    link.click();
+    link.remove();
+}
+
+/**
+ * Upload files dropped on the terminal and offer downloads on shift-right-click
+ * @param {string} webClientId - Client ID from authentication
+ */
+export function initFileTransfer(webClientId) {
+    const terminalElement = document.getElementById("terminal");
+    let downloadMenu;
+
+    function removeDownloadMenu() {
+        if (downloadMenu) {
+            downloadMenu.remove();
+            downloadMenu = undefined;
+        }
+    }
+
+    function showDownloadMenu(event) {
+        removeDownloadMenu();
+        const menu = document.createElement("div");
+        menu.style.position = "absolute";
+        menu.style.top = event.clientY + "px";
+        menu.style.left = event.clientX + "px";
+        menu.style.fontSize = "small";
+        menu.style.padding = "4px";
+        menu.style.border = "thin solid";
+        menu.style.borderRadius = "6px";
+        menu.style.background = "#6c4c4c";
+        menu.style.borderColor = "#150262";
+        menu.style.zIndex = "10";
+        for (const [label, file] of [
+            ["Download screen dump", "screen"],
+            ["Download log", "log"],
+        ]) {
+            const item = document.createElement("div");
+            item.innerText = label;
+            item.style.cursor = "pointer";
+            item.style.padding = "2px 4px";
+            item.addEventListener("click", () => {
+                removeDownloadMenu();
+                downloadFile(webClientId, file);
+            });
+            menu.appendChild(item);
+        }
+        document.body.appendChild(menu);
+        downloadMenu = menu;
+    }
+
+    terminalElement.addEventListener("dragover", (event) => {
+        event.preventDefault();
+    });
+    terminalElement.addEventListener("drop", async (event) => {
+        event.preventDefault();
+        for (const file of event.dataTransfer.files) {
+            await uploadFile(webClientId, file);
+        }
+    });
+    terminalElement.addEventListener("contextmenu", (event) => {
+        if (event.shiftKey) {
+            event.preventDefault();
+            showDownloadMenu(event);
+        }
+    });
+    document.addEventListener("click", removeDownloadMenu);
+}
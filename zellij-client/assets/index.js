@@ -3,6 +3,7 @@ import { initAuthentication } from './auth.js';
 import { initTerminal } from './terminal.js';
 import { setupInputHandlers } from './input.js';
 import { initWebSockets } from './websockets.js';
+import { initFileTransfer } from './file_transfer.js';
 
 document.addEventListener("DOMContentLoaded", async (event) => {
     initConnectionHandlers();
@@ -19,6 +20,7 @@ document.addEventListener("DOMContentLoaded", async (event) => {
     setupInputHandlers(term, sendAnsiKey);
 
     document.title = sessionName;
+    initFileTransfer(webClientId);
     const websockets = initWebSockets(webClientId, sessionName, term, fitAddon, sendAnsiKey);
     
     // Update sendAnsiKey to use the actual WebSocket function returned by initWebSockets
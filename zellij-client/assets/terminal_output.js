@@ -0,0 +1,84 @@
+/**
+ * Decoding of the terminal output frames sent by the web server
+ */
+
+/**
+ * Whether this browser can inflate compressed terminal output
+ * @returns {boolean} true if DecompressionStream supports raw deflate
+ */
+export function supportsCompressedOutput() {
+    if (typeof DecompressionStream === "undefined") {
+        return false;
+    }
+    try {
+        new DecompressionStream("deflate-raw");
+        return true;
+    } catch (e) {
+        return false;
+    }
+}
+
+/**
+ * Parse a TerminalOutputMsg protobuf frame
+ * @param {ArrayBuffer} buffer - The binary websocket frame
+ * @returns {object} Object containing the content bytes and whether they are compressed
+ */
+function parseTerminalOutputMsg(buffer) {
+    const bytes = new Uint8Array(buffer);
+    let offset = 0;
+    let content = new Uint8Array(0);
+    let compressed = false;
+
+    function readVarint() {
+        let value = 0;
+        let multiplier = 1;
+        let byte;
+        do {
+            byte = bytes[offset++];
+            value += (byte & 0x7f) * multiplier;
+            multiplier *= 128;
+        } while (byte & 0x80);
+        return value;
+    }
+
+    while (offset < bytes.length) {
+        const key = readVarint();
+        const fieldNumber = Math.floor(key / 8);
+        const wireType = key & 0x7;
+        if (wireType === 2) {
+            const length = readVarint();
+            const value = bytes.subarray(offset, offset + length);
+            offset += length;
+            if (fieldNumber === 1) {
+                content = value;
+            }
+        } else if (wireType === 0) {
+            const value = readVarint();
+            if (fieldNumber === 2) {
+                compressed = value !== 0;
+            }
+        } else {
+            // not a wire type used by TerminalOutputMsg
+            break;
+        }
+    }
+    return { content, compressed };
+}
+
+const textDecoder = new TextDecoder();
+
+/**
+ * Decode a terminal output frame into the text to write to the terminal
+ * @param {ArrayBuffer} buffer - The binary websocket frame
+ * @returns {Promise<string>} The terminal output
+ */
+export async function decodeTerminalOutput(buffer) {
+    const { content, compressed } = parseTerminalOutputMsg(buffer);
+    if (!compressed) {
+        return textDecoder.decode(content);
+    }
+    const inflated = new Blob([content])
+        .stream()
+        .pipeThrough(new DecompressionStream("deflate-raw"));
+    return textDecoder.decode(await new Response(inflated).arrayBuffer());
+}
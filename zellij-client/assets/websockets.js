@@ -2,8 +2,15 @@
  * WebSocket management for terminal and control connections
  */
 
-import { handleReconnection, handleDisconnected, markConnectionEstablished } from "./connection.js";
+import {
+    handleReconnection,
+    handleDisconnected,
+    handleServerRestart,
+    markConnectionEstablished,
+    setSessionToRestartInto,
+} from "./connection.js";
 import { getWebSocketBaseUrl } from "./utils.js";
+import { decodeTerminalOutput, supportsCompressedOutput } from "./terminal_output.js";
 
 /**
  * Initialize both terminal and control WebSocket connections
@@ -32,10 +39,16 @@ export function initWebSockets(
             ? `${wsBaseUrl}/ws/terminal`
             : `${wsBaseUrl}/ws/terminal/${sessionName}`;
 
-    const queryString = `?web_client_id=${encodeURIComponent(webClientId)}`;
+    let queryString = `?web_client_id=${encodeURIComponent(webClientId)}`;
+    if (supportsCompressedOutput()) {
+        queryString += "&compression=deflate-raw";
+    }
     const wsTerminalUrl = `${url}${queryString}`;
 
     wsTerminal = new WebSocket(wsTerminalUrl);
+    wsTerminal.binaryType = "arraybuffer";
+    // frames are decoded asynchronously, this keeps them in order
+    let terminalOutputQueue = Promise.resolve();
 
     wsTerminal.onopen = function () {
         markConnectionEstablished();
@@ -49,8 +62,18 @@ export function initWebSockets(
             startWsControl(wsControl, term, fitAddon, ownWebClientId, userConfig);
         }
 
-        let data = event.data;
+        if (event.data instanceof ArrayBuffer) {
+            const decodedOutput = decodeTerminalOutput(event.data);
+            terminalOutputQueue = terminalOutputQueue
+                .then(() => decodedOutput)
+                .then(writeTerminalOutput)
+                .catch((e) => console.error("Failed to decode terminal output:", e));
+        } else {
+            writeTerminalOutput(event.data);
+        }
+    };
 
+    function writeTerminalOutput(data) {
         if (typeof data === "string") {
             // Handle ANSI title change sequences
             const titleRegex = /\x1b\]0;([^\x07\x1b]*?)(?:\x07|\x1b\\)/g;
@@ -93,11 +116,13 @@ export function initWebSockets(
         }
 
         term.write(data);
-    };
+    }
 
     wsTerminal.onclose = function (event) {
         if (event.code === 4001) {
             handleDisconnected();
+        } else if (event.code === 4002) {
+            handleServerRestart();
         } else {
             handleReconnection();
         }
@@ -231,23 +256,28 @@ function startWsControl(wsControl, term, fitAddon, ownWebClientId, userConfig) {
             );
         } else if (msg.type === "Log") {
             const { lines } = msg;
-            for (const line in lines) {
+            for (const line of lines) {
                 console.log(line);
             }
         } else if (msg.type === "LogError") {
             const { lines } = msg;
-            for (const line in lines) {
+            for (const line of lines) {
                 console.error(line);
             }
         } else if (msg.type === "SwitchedSession") {
             const { new_session_name } = msg;
             window.location.pathname = `/${new_session_name}`;
+        } else if (msg.type === "ServerRestarting") {
+            const { session_name } = msg;
+            setSessionToRestartInto(session_name);
         }
     };
 
     wsControl.onclose = function (event) {
         if (event.code === 4001) {
             handleDisconnected();
+        } else if (event.code === 4002) {
+            handleServerRestart();
         } else {
             handleReconnection();
         }
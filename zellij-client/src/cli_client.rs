@@ -1,13 +1,15 @@
 //! The `[cli_client]` is used to attach to a running server session
 //! and dispatch actions, that are specified through the command line.
 use std::collections::BTreeMap;
-use std::io::BufRead;
+use std::io::{BufRead, Write};
 use std::process;
 use std::{fs, path::PathBuf};
 
 use crate::os_input_output::ClientOsApi;
+use crate::piped_input::PipedInput;
 use uuid::Uuid;
 use zellij_utils::{
+    data::PaneId,
     errors::prelude::*,
     input::actions::Action,
     ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
@@ -17,6 +19,7 @@ pub fn start_cli_client(
     mut os_input: Box<dyn ClientOsApi>,
     session_name: &str,
     actions: Vec<Action>,
+    session_secret: Option<String>,
 ) {
     let zellij_ipc_pipe: PathBuf = {
         let mut sock_dir = zellij_utils::consts::ZELLIJ_SOCK_DIR.clone();
@@ -26,6 +29,10 @@ pub fn start_cli_client(
         sock_dir
     };
     os_input.connect_to_server(&*zellij_ipc_pipe);
+    if let Some(session_secret) = session_secret {
+        // a locked session turns away connections that did not prove they know its secret
+        os_input.send_to_server(ClientToServerMsg::Authenticate { session_secret });
+    }
     let pane_id = os_input
         .env_variable("ZELLIJ_PANE_ID")
         .and_then(|e| e.trim().parse().ok());
@@ -63,6 +70,13 @@ pub fn start_cli_client(
                     pane_title,
                 );
             },
+            Action::PickFromClipboardHistory {
+                pane_id: target_pane_id,
+            } => {
+                // without a pane to paste into, paste into the one the picker runs in
+                let target_pane_id = target_pane_id.or_else(|| pane_id.map(PaneId::Terminal));
+                clipboard_history_picker(&mut os_input, target_pane_id, pane_id);
+            },
             action => {
                 individual_messages_client(&mut os_input, action, pane_id);
             },
@@ -87,7 +101,8 @@ fn pipe_client(
     cwd: Option<PathBuf>,
     pane_title: Option<String>,
 ) {
-    let mut stdin = os_input.get_stdin_reader();
+    // on Windows piped input usually has CRLF line endings, which plugins don't expect
+    let mut stdin = PipedInput::new(os_input.get_stdin_reader(), cfg!(windows));
     let name = name
         // first we try to take the explicitly supplied message name
         .take()
@@ -137,16 +152,17 @@ fn pipe_client(
         } else {
             // we didn't get payload from the command line, meaning we listen on STDIN because this
             // signifies the user is about to pipe more (eg. cat my-large-file | zellij pipe ...)
-            let mut buffer = String::new();
-            let _ = stdin.read_line(&mut buffer);
-            if buffer.is_empty() {
-                let msg = create_msg(None);
-                os_input.send_to_server(msg);
-                break;
-            } else {
-                // we've got data! send it down the pipe (most common)
-                let msg = create_msg(Some(buffer));
-                os_input.send_to_server(msg);
+            match stdin.next_line() {
+                Some(line) => {
+                    // we've got data! send it down the pipe (most common)
+                    let msg = create_msg(Some(line));
+                    os_input.send_to_server(msg);
+                },
+                None => {
+                    let msg = create_msg(None);
+                    os_input.send_to_server(msg);
+                    break;
+                },
             }
         }
         loop {
@@ -190,6 +206,10 @@ fn pipe_client(
                         eprintln!("{}", e);
                         process::exit(2);
                     },
+                    ExitReason::SessionLocked => {
+                        eprintln!("{}", ExitReason::SessionLocked);
+                        process::exit(2);
+                    },
                     _ => {
                         process::exit(0);
                     },
@@ -200,6 +220,66 @@ fn pipe_client(
     }
 }
 
+fn clipboard_history_picker(
+    os_input: &mut Box<dyn ClientOsApi>,
+    target_pane_id: Option<PaneId>,
+    pane_id: Option<u32>,
+) {
+    os_input.send_to_server(ClientToServerMsg::Action {
+        action: Action::ListClipboardHistory,
+        terminal_id: pane_id,
+        client_id: None,
+        is_cli_client: true,
+    });
+    let entries = loop {
+        match os_input.recv_from_server() {
+            Some((ServerToClientMsg::Log { lines }, _)) => break lines,
+            Some((ServerToClientMsg::LogError { lines }, _)) => {
+                lines.iter().for_each(|line| eprintln!("{line}"));
+                process::exit(2);
+            },
+            Some((ServerToClientMsg::Exit { .. }, _)) | None => process::exit(2),
+            _ => {},
+        }
+    };
+    let mut stdin = os_input.get_stdin_reader();
+    if entries.is_empty() {
+        println!("Nothing was copied in this session yet, press Enter to close");
+        let _ = stdin.read_line(&mut String::new());
+        return;
+    }
+    println!("Recent copies, most recent first:");
+    entries.iter().for_each(|entry| println!("{entry}"));
+    loop {
+        print!("Paste which one? (c and its number to only copy it again, Enter to cancel) ");
+        let _ = std::io::stdout().flush();
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer).unwrap_or(0) == 0 {
+            return;
+        }
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return;
+        }
+        let (copy_only, index) = match answer.strip_prefix('c') {
+            Some(index) => (true, index.trim()),
+            None => (false, answer),
+        };
+        match index.parse::<usize>() {
+            Ok(index) if index < entries.len() => {
+                let action = Action::PasteFromClipboardHistory {
+                    index,
+                    pane_id: target_pane_id,
+                    copy_only,
+                };
+                individual_messages_client(os_input, action, pane_id);
+                return;
+            },
+            _ => println!("Choose a number from 0 to {}", entries.len() - 1),
+        }
+    }
+}
+
 fn individual_messages_client(
     os_input: &mut Box<dyn ClientOsApi>,
     action: Action,
@@ -230,6 +310,10 @@ fn individual_messages_client(
                     eprintln!("{}", e);
                     process::exit(2);
                 },
+                ExitReason::SessionLocked => {
+                    eprintln!("{}", ExitReason::SessionLocked);
+                    process::exit(2);
+                },
                 ExitReason::CustomExitStatus(exit_status) => {
                     process::exit(exit_status);
                 },
@@ -0,0 +1,180 @@
+// The client sends the server a heartbeat every second and the server answers it from its main
+// thread. How long those answers take tells the user whether it is zellij that is slow (eg. a
+// server too busy to keep up) or the connection between them and their terminal (eg. a laggy RDP
+// or SSH session), which the client cannot see and so never warns about.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+pub(crate) const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+const SLOW_ROUND_TRIP: Duration = Duration::from_millis(250);
+const NOT_RESPONDING_AFTER: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ConnectionStatus {
+    Good,
+    Slow(Duration),          // the round trip of the heartbeats
+    NotResponding(Duration), // for how long the server has not answered
+}
+
+impl ConnectionStatus {
+    /// Whether the indicator of `self` covers all of the indicator of `other`, so that it can be
+    /// drawn over it without leaving parts of it behind.
+    fn covers(&self, other: &ConnectionStatus) -> bool {
+        // the banner of a server not responding spans the whole line
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+            || matches!(self, ConnectionStatus::NotResponding(_))
+            || *other == ConnectionStatus::Good
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionQuality {
+    next_heartbeat_id: u64,
+    unanswered_heartbeats: VecDeque<(u64, Instant)>, // (id, sent at)
+    round_trip: Option<Duration>,
+}
+
+impl ConnectionQuality {
+    /// Record a heartbeat sent at `now`, returning the id to send it with.
+    pub fn heartbeat_sent(&mut self, now: Instant) -> u64 {
+        let id = self.next_heartbeat_id;
+        self.next_heartbeat_id += 1;
+        self.unanswered_heartbeats.push_back((id, now));
+        id
+    }
+    pub fn heartbeat_acknowledged(&mut self, id: u64, now: Instant) {
+        // the server answers heartbeats in order, those sent before this one were lost (eg. when
+        // we reattached to it) and will not be answered anymore
+        if let Some(index) = self
+            .unanswered_heartbeats
+            .iter()
+            .position(|(sent_id, _)| *sent_id == id)
+        {
+            let (_, sent_at) = self.unanswered_heartbeats[index];
+            self.unanswered_heartbeats.drain(..=index);
+            self.round_trip = Some(now.saturating_duration_since(sent_at));
+        }
+    }
+    pub fn status(&self, now: Instant) -> ConnectionStatus {
+        let waiting_for = self
+            .unanswered_heartbeats
+            .front()
+            .map(|(_, sent_at)| now.saturating_duration_since(*sent_at))
+            .unwrap_or_default();
+        if waiting_for >= NOT_RESPONDING_AFTER {
+            return ConnectionStatus::NotResponding(waiting_for);
+        }
+        let latency = self.round_trip.unwrap_or_default().max(waiting_for);
+        if latency >= SLOW_ROUND_TRIP {
+            ConnectionStatus::Slow(latency)
+        } else {
+            ConnectionStatus::Good
+        }
+    }
+}
+
+/// What the client shows of the connection quality, and whether the server has to repaint the
+/// screen for it to go away.
+#[derive(Debug)]
+pub(crate) struct ConnectionIndicator {
+    displayed: ConnectionStatus,
+}
+
+impl ConnectionIndicator {
+    pub fn new() -> Self {
+        ConnectionIndicator {
+            displayed: ConnectionStatus::Good,
+        }
+    }
+    /// Show `status` instead of what is displayed, returning whether the screen has to be
+    /// repainted to remove the indicator displayed until now.
+    pub fn update(&mut self, status: ConnectionStatus) -> bool {
+        let needs_repaint = !status.covers(&self.displayed);
+        self.displayed = status;
+        needs_repaint
+    }
+    pub fn is_displayed(&self) -> bool {
+        self.displayed != ConnectionStatus::Good
+    }
+    /// The ANSI sequence drawing the indicator over the top line of a screen `columns` wide,
+    /// leaving the cursor where it was. A slow server gets a small indicator in the top right
+    /// corner, one that does not respond a warning banner across the whole line.
+    pub fn render(&self, columns: usize) -> String {
+        let (text, column, style) = match self.displayed {
+            ConnectionStatus::Good => return String::new(),
+            ConnectionStatus::Slow(round_trip) => {
+                let text = format!(" server lag {:>5}ms ", round_trip.as_millis().min(99999));
+                let column = columns.saturating_sub(text.len()) + 1;
+                (text, column, "30;43")
+            },
+            ConnectionStatus::NotResponding(waiting_for) => {
+                let text = format!(
+                    " The zellij server has not responded for {}s, waiting for it... ",
+                    waiting_for.as_secs()
+                );
+                (format!("{:<1$}", text, columns), 1, "1;97;41")
+            },
+        };
+        let text: String = text.chars().take(columns).collect();
+        format!(
+            "\u{1b}7\u{1b}[1;{}H\u{1b}[0;{}m{}\u{1b}[m\u{1b}8",
+            column, style, text
+        )
+    }
+}
+
+#[test]
+pub fn measures_the_round_trip_of_heartbeats() {
+    let start = Instant::now();
+    let mut connection_quality = ConnectionQuality::default();
+    let id = connection_quality.heartbeat_sent(start);
+    assert_eq!(connection_quality.status(start), ConnectionStatus::Good);
+    connection_quality.heartbeat_acknowledged(id, start + Duration::from_millis(400));
+    assert_eq!(
+        connection_quality.status(start + Duration::from_millis(400)),
+        ConnectionStatus::Slow(Duration::from_millis(400))
+    );
+    let id = connection_quality.heartbeat_sent(start + Duration::from_secs(1));
+    connection_quality.heartbeat_acknowledged(id, start + Duration::from_millis(1010));
+    assert_eq!(
+        connection_quality.status(start + Duration::from_millis(1010)),
+        ConnectionStatus::Good
+    );
+}
+
+#[test]
+pub fn server_not_answering_heartbeats_is_not_responding() {
+    let start = Instant::now();
+    let mut connection_quality = ConnectionQuality::default();
+    let lost_id = connection_quality.heartbeat_sent(start);
+    for second in 1..=4 {
+        connection_quality.heartbeat_sent(start + Duration::from_secs(second));
+    }
+    assert_eq!(
+        connection_quality.status(start + Duration::from_secs(4)),
+        ConnectionStatus::NotResponding(Duration::from_secs(4))
+    );
+    // answering a later heartbeat gives up on the ones sent before it
+    connection_quality.heartbeat_acknowledged(lost_id + 4, start + Duration::from_secs(4));
+    assert_eq!(
+        connection_quality.status(start + Duration::from_secs(4)),
+        ConnectionStatus::Good
+    );
+}
+
+#[test]
+pub fn removing_an_indicator_needs_a_repaint() {
+    let mut indicator = ConnectionIndicator::new();
+    assert_eq!(indicator.render(80), "");
+    assert!(!indicator.update(ConnectionStatus::Slow(Duration::from_millis(300))));
+    assert_eq!(
+        indicator.render(80),
+        "\u{1b}7\u{1b}[1;61H\u{1b}[0;30;43m server lag   300ms \u{1b}[m\u{1b}8"
+    );
+    assert!(!indicator.update(ConnectionStatus::Slow(Duration::from_millis(1200))));
+    assert!(!indicator.update(ConnectionStatus::NotResponding(Duration::from_secs(3))));
+    assert!(indicator.update(ConnectionStatus::Slow(Duration::from_millis(300))));
+    assert!(indicator.update(ConnectionStatus::Good));
+    assert!(!indicator.is_displayed());
+}
@@ -0,0 +1,669 @@
+//! `zellij doctor`: checks what zellij needs from the machine it runs on, suggesting what to do
+//! about whatever is missing.
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use interprocess::local_socket::{
+    prelude::*, Listener, ListenerOptions, Name, Stream as LocalSocketStream,
+};
+use zellij_utils::{
+    consts::{ZELLIJ_CACHE_DIR, ZELLIJ_SOCK_DIR},
+    input::options::Options,
+    ipc::{
+        path_to_ipc_name, ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext,
+        ServerToClientMsg,
+    },
+    shared::create_private_dir,
+};
+
+use crate::stdin_ansi_parser::{AnsiStdinInstruction, StdinAnsiParser};
+
+// a reply taking longer than this means the pipes are wedged
+const ROUND_TRIP_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Problem,
+}
+
+/// The outcome of one of the checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub check: &'static str,
+    pub severity: Severity,
+    pub summary: String,
+    /// What to do about a warning or a problem
+    pub advice: Option<String>,
+}
+
+impl Finding {
+    fn ok(check: &'static str, summary: impl Into<String>) -> Self {
+        Finding {
+            check,
+            severity: Severity::Ok,
+            summary: summary.into(),
+            advice: None,
+        }
+    }
+    fn warning(check: &'static str, summary: impl Into<String>, advice: impl Into<String>) -> Self {
+        Finding {
+            check,
+            severity: Severity::Warning,
+            summary: summary.into(),
+            advice: Some(advice.into()),
+        }
+    }
+    fn problem(check: &'static str, summary: impl Into<String>, advice: impl Into<String>) -> Self {
+        Finding {
+            check,
+            severity: Severity::Problem,
+            summary: summary.into(),
+            advice: Some(advice.into()),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Ok => "OK",
+            Severity::Warning => "WARNING",
+            Severity::Problem => "PROBLEM",
+        };
+        write!(f, "[{}] {}: {}", severity, self.check, self.summary)?;
+        if let Some(advice) = &self.advice {
+            write!(f, "\n    {}", advice)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs all the checks, `config_options` being those of the configuration file at `config_file`
+/// or why it failed to load
+pub fn diagnose(
+    config_file: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+    config_options: Result<Options, String>,
+) -> Vec<Finding> {
+    let mut findings = vec![check_config_file(config_file.as_deref(), &config_options)];
+    let config_options = config_options.unwrap_or_default();
+    findings.push(check_pseudo_terminal(&config_options));
+    findings.push(check_config_dir(config_dir.as_deref()));
+    findings.push(check_dir_is_writable("Cache dir", &ZELLIJ_CACHE_DIR));
+    findings.extend(check_ipc(&ZELLIJ_SOCK_DIR));
+    findings.push(check_terminal());
+    findings.push(check_escape_sequences());
+    findings.push(check_terminal_capabilities());
+    findings
+}
+
+fn check_config_file(
+    config_file: Option<&Path>,
+    config_options: &Result<Options, String>,
+) -> Finding {
+    const CHECK: &str = "Config file";
+    match (config_file, config_options) {
+        (_, Err(e)) => Finding::problem(
+            CHECK,
+            format!("failed to load: {}", e),
+            "Fix the error above, until then zellij starts with the default configuration",
+        ),
+        (Some(config_file), Ok(_)) if config_file.exists() => {
+            Finding::ok(CHECK, format!("{} loads", config_file.display()))
+        },
+        _ => Finding::ok(CHECK, "none, zellij starts with the default configuration"),
+    }
+}
+
+fn check_config_dir(config_dir: Option<&Path>) -> Finding {
+    const CHECK: &str = "Config dir";
+    match config_dir {
+        Some(config_dir) if config_dir.is_dir() => check_dir_is_writable(CHECK, config_dir),
+        _ => Finding::warning(
+            CHECK,
+            "not found",
+            "Run `zellij setup --wizard` to create one with a configuration file",
+        ),
+    }
+}
+
+fn check_dir_is_writable(check: &'static str, dir: &Path) -> Finding {
+    let written = std::fs::create_dir_all(dir).and_then(|_| {
+        let probe = dir.join(format!(".zellij-doctor-{}", std::process::id()));
+        std::fs::write(&probe, b"")?;
+        std::fs::remove_file(&probe)
+    });
+    match written {
+        Ok(()) => Finding::ok(check, format!("{} is writable", dir.display())),
+        Err(e) => Finding::problem(
+            check,
+            format!("{} is not writable: {}", dir.display(), e),
+            format!(
+                "Make sure {} is a directory that belongs to you",
+                dir.display()
+            ),
+        ),
+    }
+}
+
+#[cfg(unix)]
+fn check_pseudo_terminal(_config_options: &Options) -> Finding {
+    const CHECK: &str = "Pseudo terminals";
+    match std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/ptmx")
+    {
+        Ok(_) => Finding::ok(CHECK, "/dev/ptmx opens"),
+        Err(e) => Finding::problem(
+            CHECK,
+            format!("failed to open /dev/ptmx: {}", e),
+            "zellij runs its panes in pseudo terminals, make sure devpts is mounted (eg. in \
+             containers)",
+        ),
+    }
+}
+
+#[cfg(windows)]
+fn check_pseudo_terminal(config_options: &Options) -> Finding {
+    use zellij_utils::input::options::{ConPtyHost, PtySystem};
+
+    let windows = match windows_build() {
+        Some(build) => format!("Windows build {}", build),
+        None => "this Windows".to_owned(),
+    };
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let pty_system = config_options.pty_system.unwrap_or_default();
+    if pty_system == PtySystem::WinPty {
+        let has_winpty = exe_dir.as_ref().map_or(false, |dir| {
+            dir.join("winpty.dll").is_file() && dir.join("winpty-agent.exe").is_file()
+        });
+        return if has_winpty {
+            Finding::ok(
+                "winpty",
+                "winpty.dll and winpty-agent.exe are next to zellij",
+            )
+        } else {
+            Finding::problem(
+                "winpty",
+                "`pty_system` is \"winpty\", but winpty.dll and winpty-agent.exe are not next \
+                 to zellij",
+                "Put them next to the zellij executable, or set `pty_system \"conpty\"`",
+            )
+        };
+    }
+
+    const CHECK: &str = "ConPTY";
+    if !kernel32_has_conpty() {
+        return Finding::problem(
+            CHECK,
+            format!("{} has no ConPTY", windows),
+            "Windows 10 version 1809 (build 17763) or newer is needed, or set `pty_system \
+             \"winpty\"` with winpty.dll and winpty-agent.exe next to zellij",
+        );
+    }
+    let conpty_host = config_options.conpty.clone().unwrap_or_default();
+    let conpty_dir = match &conpty_host {
+        ConPtyHost::Bundled => exe_dir,
+        ConPtyHost::System => None,
+        ConPtyHost::Path(dir) => Some(dir.clone()),
+    };
+    let sideloaded = conpty_dir
+        .filter(|dir| dir.join("conpty.dll").is_file() && dir.join("OpenConsole.exe").is_file());
+    match (sideloaded, &conpty_host) {
+        (Some(dir), _) => {
+            let version = file_version(&dir.join("conpty.dll"))
+                .map(|version| format!(" {}", version))
+                .unwrap_or_default();
+            Finding::ok(
+                CHECK,
+                format!("conpty.dll{} from {}", version, dir.display()),
+            )
+        },
+        (None, _) if pty_system == PtySystem::ConPtyPassthrough => Finding::problem(
+            CHECK,
+            format!(
+                "`pty_system` is \"conpty_passthrough\", which needs a recent conpty.dll, but \
+                 only the ConPTY of {} is there",
+                windows
+            ),
+            "Put conpty.dll and OpenConsole.exe (eg. from Windows Terminal) next to zellij, or \
+             set `pty_system \"conpty\"`",
+        ),
+        (None, ConPtyHost::Path(dir)) => Finding::warning(
+            CHECK,
+            format!(
+                "no conpty.dll and OpenConsole.exe in {}, the ConPTY of {} runs the panes \
+                 instead",
+                dir.display(),
+                windows
+            ),
+            "Point the `conpty` option at a directory with both, eg. that of a Windows Terminal \
+             installation",
+        ),
+        (None, _) => Finding::ok(CHECK, format!("the ConPTY of {}", windows)),
+    }
+}
+
+/// Creates the pipes of a server of our own and sends it a message, whose reply comes back
+/// through them
+fn check_ipc(sock_dir: &Path) -> Vec<Finding> {
+    let socket_path = sock_dir.join(format!("doctor-{}", std::process::id()));
+    let listeners = match create_private_dir(sock_dir).and_then(|_| Listeners::new(&socket_path)) {
+        Ok(listeners) => listeners,
+        Err(e) => {
+            return vec![Finding::problem(
+                "Pipes",
+                format!("failed to create the pipes of a session: {}", e),
+                format!(
+                    "Make sure {} is a directory that belongs to you, or point \
+                     ZELLIJ_SOCKET_DIR at one that does",
+                    sock_dir.display()
+                ),
+            )]
+        },
+    };
+    let pipes = Finding::ok(
+        "Pipes",
+        format!("created those of a session in {}", sock_dir.display()),
+    );
+    let round_trip = match round_trip(&socket_path, listeners) {
+        Ok(elapsed) => Finding::ok(
+            "IPC round trip",
+            format!("a server of our own replied in {} ms", elapsed.as_millis()),
+        ),
+        Err(e) => Finding::problem(
+            "IPC round trip",
+            format!("a server of our own did not reply: {}", e),
+            "Security software may be intercepting the pipes of zellij, try allowing the zellij \
+             executable",
+        ),
+    };
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(&socket_path);
+    vec![pipes, round_trip]
+}
+
+/// The listeners of a server: on Windows replies go through a reverse pipe of their own, see
+/// `path_to_ipc_name_reverse`
+struct Listeners {
+    main: Listener,
+    #[cfg(windows)]
+    reverse: Listener,
+}
+
+impl Listeners {
+    fn new(socket_path: &Path) -> io::Result<Self> {
+        #[cfg(unix)]
+        let _ = std::fs::remove_file(socket_path);
+        let main = listener_options(path_to_ipc_name(socket_path)?)?.create_sync()?;
+        #[cfg(windows)]
+        let reverse = listener_options(zellij_utils::ipc::path_to_ipc_name_reverse(socket_path)?)?
+            .create_sync()?;
+        Ok(Listeners {
+            main,
+            #[cfg(windows)]
+            reverse,
+        })
+    }
+}
+
+/// Options for creating a pipe that, like those of sessions, only the current user can access
+fn listener_options(name: Name<'_>) -> io::Result<ListenerOptions<'_>> {
+    let listener_options = ListenerOptions::new().name(name);
+    #[cfg(windows)]
+    let listener_options = {
+        use interprocess::os::windows::local_socket::ListenerOptionsExt;
+        listener_options
+            .security_descriptor(zellij_utils::ipc::session_pipe_security_descriptor(&[])?)
+    };
+    Ok(listener_options)
+}
+
+fn round_trip(socket_path: &Path, listeners: Listeners) -> anyhow::Result<Duration> {
+    let server = thread::spawn(move || -> anyhow::Result<()> {
+        let stream = listeners.main.accept()?;
+        let mut receiver: IpcReceiverWithContext<ClientToServerMsg> =
+            IpcReceiverWithContext::new(stream);
+        #[cfg(windows)]
+        let mut sender: IpcSenderWithContext<ServerToClientMsg> =
+            IpcSenderWithContext::new(listeners.reverse.accept()?);
+        #[cfg(not(windows))]
+        let mut sender: IpcSenderWithContext<ServerToClientMsg> = receiver.get_sender();
+        match receiver.recv_client_msg() {
+            Some((ClientToServerMsg::ConnStatus, _)) => {
+                sender.send_server_msg(ServerToClientMsg::Connected)
+            },
+            message => Err(anyhow::anyhow!("the server received {:?}", message)),
+        }
+    });
+
+    let started = Instant::now();
+    let (reply_tx, reply_rx) = mpsc::channel();
+    thread::spawn({
+        let socket_path = socket_path.to_path_buf();
+        move || {
+            let _ = reply_tx.send(query_conn_status(&socket_path));
+        }
+    });
+    let reply = reply_rx
+        .recv_timeout(ROUND_TRIP_TIMEOUT)
+        .map_err(|_| anyhow::anyhow!("no reply in {} seconds", ROUND_TRIP_TIMEOUT.as_secs()))??;
+    let elapsed = started.elapsed();
+    match reply {
+        Some(ServerToClientMsg::Connected) => Ok(elapsed),
+        reply => {
+            // what went wrong is best told by the server
+            match server.join() {
+                Ok(Err(e)) => Err(e),
+                _ => Err(anyhow::anyhow!("the client received {:?}", reply)),
+            }
+        },
+    }
+}
+
+fn query_conn_status(socket_path: &Path) -> anyhow::Result<Option<ServerToClientMsg>> {
+    let stream = LocalSocketStream::connect(path_to_ipc_name(socket_path)?)?;
+    let mut sender: IpcSenderWithContext<ClientToServerMsg> = IpcSenderWithContext::new(stream);
+    #[cfg(windows)]
+    let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = IpcReceiverWithContext::new(
+        LocalSocketStream::connect(zellij_utils::ipc::path_to_ipc_name_reverse(socket_path)?)?,
+    );
+    #[cfg(not(windows))]
+    let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+    sender.send_client_msg(ClientToServerMsg::ConnStatus)?;
+    Ok(receiver.recv_server_msg().map(|(reply, _)| reply))
+}
+
+#[cfg(unix)]
+fn check_terminal() -> Finding {
+    const CHECK: &str = "Terminal";
+    match std::env::var("TERM") {
+        Ok(term) if !term.is_empty() && term != "dumb" => {
+            let terminal_program = std::env::var("TERM_PROGRAM")
+                .map(|terminal_program| format!("{}, ", terminal_program))
+                .unwrap_or_default();
+            Finding::ok(CHECK, format!("{}TERM={}", terminal_program, term))
+        },
+        _ => Finding::warning(
+            CHECK,
+            "TERM is not set to a terminal that understands escape sequences",
+            "Run zellij in a terminal emulator, or set TERM (eg. to xterm-256color)",
+        ),
+    }
+}
+
+#[cfg(windows)]
+fn check_terminal() -> Finding {
+    const CHECK: &str = "Terminal";
+    if std::env::var_os("WT_SESSION").is_some() {
+        Finding::ok(CHECK, "Windows Terminal")
+    } else if crate::os_input_output_windows::is_classic_console() {
+        Finding::warning(
+            CHECK,
+            "a classic console window, which zellij draws in with `legacy_console_rendering` \
+             (256 colors, square corners)",
+            "For all colors and fonts, run zellij in Windows Terminal or another terminal \
+             emulator",
+        )
+    } else {
+        let terminal_program = std::env::var("TERM_PROGRAM")
+            .unwrap_or_else(|_| "a terminal emulator hosting a pseudo console".to_owned());
+        Finding::ok(CHECK, terminal_program)
+    }
+}
+
+#[cfg(unix)]
+fn check_escape_sequences() -> Finding {
+    if io::stdout().is_terminal() {
+        Finding::ok("Escape sequences", "the output goes to a terminal")
+    } else {
+        Finding::ok(
+            "Escape sequences",
+            "not checked, the output does not go to a terminal",
+        )
+    }
+}
+
+/// Whether the console of our output processes escape sequences, which zellij turns on when
+/// it starts
+#[cfg(windows)]
+fn check_escape_sequences() -> Finding {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_OUTPUT_HANDLE,
+    };
+
+    const CHECK: &str = "Escape sequences";
+    if !io::stdout().is_terminal() {
+        return Finding::ok(CHECK, "not checked, the output does not go to a console");
+    }
+    let mut mode = 0;
+    let stdout = unsafe { GetStdHandle(STD_OUTPUT_HANDLE) };
+    if unsafe { GetConsoleMode(stdout, &mut mode) } == 0 {
+        return Finding::ok(CHECK, "not checked, the output does not go to a console");
+    }
+    if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+        return Finding::ok(CHECK, "the console processes them");
+    }
+    if unsafe { SetConsoleMode(stdout, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) } != 0 {
+        unsafe { SetConsoleMode(stdout, mode) };
+        Finding::ok(CHECK, "the console processes them once asked to")
+    } else {
+        Finding::problem(
+            CHECK,
+            format!(
+                "the console cannot process them: {}",
+                io::Error::last_os_error()
+            ),
+            "zellij draws with escape sequences, which consoles process since Windows 10, run \
+             it in Windows Terminal or another terminal emulator",
+        )
+    }
+}
+
+/// What zellij detected the last time it queried a terminal, see
+/// `StdinAnsiParser::terminal_emulator_query_string`
+fn check_terminal_capabilities() -> Finding {
+    const CHECK: &str = "Terminal capabilities";
+    let Some(detected) = StdinAnsiParser::new().read_cache() else {
+        return Finding::ok(
+            CHECK,
+            "none detected yet, zellij queries the terminal when it starts",
+        );
+    };
+    let mut synchronized_output = false;
+    let mut kitty_keyboard_protocol = false;
+    let mut sixel = false;
+    let mut cell_size = None;
+    let mut palette_colors = 0;
+    for instruction in detected {
+        match instruction {
+            AnsiStdinInstruction::SynchronizedOutput(sync_output) => {
+                synchronized_output = sync_output.is_some()
+            },
+            AnsiStdinInstruction::KittyKeyboardProtocol(_) => kitty_keyboard_protocol = true,
+            AnsiStdinInstruction::SixelSupport(supports_sixel) => sixel = supports_sixel,
+            AnsiStdinInstruction::PixelDimensions(pixel_dimensions) => {
+                cell_size = pixel_dimensions.character_cell_size.or(cell_size)
+            },
+            AnsiStdinInstruction::ColorRegisters(color_registers) => {
+                palette_colors = color_registers.len()
+            },
+            AnsiStdinInstruction::BackgroundColor(_) | AnsiStdinInstruction::ForegroundColor(_) => {
+            },
+        }
+    }
+    let yes_or_no = |supported: bool| if supported { "yes" } else { "no" };
+    let cell_size = cell_size
+        .map(|size| format!("{}x{} px", size.width, size.height))
+        .unwrap_or_else(|| "unknown".to_owned());
+    Finding::ok(
+        CHECK,
+        format!(
+            "synchronized output {}, kitty keyboard protocol {}, sixel {}, cell size {}, {} \
+             palette colors (as detected when zellij last started)",
+            yes_or_no(synchronized_output),
+            yes_or_no(kitty_keyboard_protocol),
+            yes_or_no(sixel),
+            cell_size,
+            palette_colors
+        ),
+    )
+}
+
+/// The build of Windows we run on, eg. `22631.4317`
+#[cfg(windows)]
+fn windows_build() -> Option<String> {
+    use windows_sys::Win32::System::Registry::{
+        RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD, RRF_RT_REG_SZ,
+    };
+
+    let key = wide(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion");
+    let mut build = [0u16; 32];
+    let mut build_len = std::mem::size_of_val(&build) as u32;
+    if unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            wide("CurrentBuildNumber").as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            build.as_mut_ptr() as _,
+            &mut build_len,
+        )
+    } != 0
+    {
+        return None;
+    }
+    let build = String::from_utf16_lossy(&build[..build_len as usize / 2]);
+    let build = build.trim_end_matches('\0');
+    // the update build revision, the part after the dot that winver shows
+    let mut ubr: u32 = 0;
+    let mut ubr_len = std::mem::size_of::<u32>() as u32;
+    let has_ubr = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            wide("UBR").as_ptr(),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut ubr as *mut u32 as _,
+            &mut ubr_len,
+        )
+    } == 0;
+    Some(if has_ubr {
+        format!("{}.{}", build, ubr)
+    } else {
+        build.to_owned()
+    })
+}
+
+/// Whether kernel32 exports the ConPTY functions, which it does since Windows 10 version 1809
+#[cfg(windows)]
+fn kernel32_has_conpty() -> bool {
+    use windows_sys::Win32::System::LibraryLoader::{GetModuleHandleW, GetProcAddress};
+
+    let kernel32 = wide("kernel32.dll");
+    unsafe {
+        let kernel32 = GetModuleHandleW(kernel32.as_ptr());
+        !kernel32.is_null() && GetProcAddress(kernel32, b"CreatePseudoConsole\0".as_ptr()).is_some()
+    }
+}
+
+/// The file version of the executable or DLL at `path`, eg. `1.22.2502.3001`
+#[cfg(windows)]
+fn file_version(path: &Path) -> Option<String> {
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
+    };
+
+    let path = wide(path);
+    unsafe {
+        let size = GetFileVersionInfoSizeW(path.as_ptr(), std::ptr::null_mut());
+        if size == 0 {
+            return None;
+        }
+        let mut version_info = vec![0u8; size as usize];
+        if GetFileVersionInfoW(path.as_ptr(), 0, size, version_info.as_mut_ptr() as _) == 0 {
+            return None;
+        }
+        let mut fixed_file_info: *mut std::ffi::c_void = std::ptr::null_mut();
+        let mut fixed_file_info_len = 0;
+        if VerQueryValueW(
+            version_info.as_ptr() as _,
+            wide("\\").as_ptr(),
+            &mut fixed_file_info,
+            &mut fixed_file_info_len,
+        ) == 0
+            || fixed_file_info.is_null()
+        {
+            return None;
+        }
+        let fixed_file_info = &*(fixed_file_info as *const VS_FIXEDFILEINFO);
+        Some(format!(
+            "{}.{}.{}.{}",
+            fixed_file_info.dwFileVersionMS >> 16,
+            fixed_file_info.dwFileVersionMS & 0xffff,
+            fixed_file_info.dwFileVersionLS >> 16,
+            fixed_file_info.dwFileVersionLS & 0xffff
+        ))
+    }
+}
+
+#[cfg(windows)]
+fn wide(s: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    s.as_ref().encode_wide().chain(std::iter::once(0)).collect()
+}
+
+#[cfg(test)]
+mod doctor_tests {
+    use super::*;
+
+    #[test]
+    fn findings_print_their_advice_below_them() {
+        let finding = Finding::problem("Cache dir", "not writable", "Fix it");
+        assert_eq!(
+            finding.to_string(),
+            "[PROBLEM] Cache dir: not writable\n    Fix it"
+        );
+        assert_eq!(
+            Finding::ok("Pipes", "created").to_string(),
+            "[OK] Pipes: created"
+        );
+    }
+
+    #[test]
+    fn a_dir_under_a_file_is_not_writable() {
+        let file = std::env::temp_dir().join(format!("zellij-doctor-file-{}", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        let finding = check_dir_is_writable("Cache dir", &file.join("cache"));
+        let _ = std::fs::remove_file(&file);
+        assert_eq!(finding.severity, Severity::Problem);
+    }
+
+    #[test]
+    fn messages_make_it_to_a_server_of_our_own_and_back() {
+        let sock_dir = std::env::temp_dir().join(format!("zellij-doctor-{}", std::process::id()));
+        let findings = check_ipc(&sock_dir);
+        let _ = std::fs::remove_dir_all(&sock_dir);
+        assert_eq!(
+            findings
+                .iter()
+                .map(|finding| (finding.check, finding.severity))
+                .collect::<Vec<_>>(),
+            vec![("Pipes", Severity::Ok), ("IPC round trip", Severity::Ok)],
+            "{:?}",
+            findings
+        );
+    }
+}
@@ -6,19 +6,27 @@ use crate::{
 use termwiz::input::{InputEvent, Modifiers, MouseButtons, MouseEvent as TermwizMouseEvent};
 use zellij_utils::{
     channels::{Receiver, SenderWithContext, OPENCALLS},
-    data::{InputMode, KeyWithModifier},
+    data::{BareKey, InputMode, KeyModifier, KeyWithModifier},
     errors::{ContextType, ErrorContext, FatalError},
     input::{
         actions::Action,
         cast_termwiz_key,
         config::Config,
         mouse::{MouseEvent, MouseEventType},
-        options::Options,
+        options::{ConsoleKeyAction, KeyChord, Options},
     },
-    ipc::{ClientToServerMsg, ExitReason},
+    ipc::{ClientToServerMsg, ExitReason, PixelDimensions},
+    pane_size::SizeInPixels,
     position::Position,
 };
 
+/// The character cell size of the VT340. Terminals that draw sixel images without telling us
+/// their own cell size (eg. Windows Terminal) scale the images as if drawn on one.
+const VT340_CHARACTER_CELL_SIZE: SizeInPixels = SizeInPixels {
+    height: 20,
+    width: 10,
+};
+
 /// Handles the dispatching of [`Action`]s according to the current
 /// [`InputMode`], and keep tracks of the current [`InputMode`].
 struct InputHandler {
@@ -33,6 +41,16 @@ struct InputHandler {
     receive_input_instructions: Receiver<(InputInstruction, ErrorContext)>,
     mouse_old_event: MouseEvent,
     mouse_mode_active: bool,
+    detach_chord: KeyChord,
+    /// The keys of the detach chord pressed so far, held back until it is either completed or
+    /// broken off
+    pending_chord_keys: Vec<(KeyWithModifier, Vec<u8>, bool)>,
+    /// Whether the terminal told us its pixel dimensions when we queried it
+    received_pixel_dimensions: bool,
+    /// Whether the terminal said it can draw sixel images when we queried it
+    host_supports_sixel: bool,
+    /// Whether the terminal has focus, as far as it told us
+    terminal_focused: Option<bool>,
 }
 
 fn termwiz_mouse_convert(original_event: &mut MouseEvent, event: &TermwizMouseEvent) {
@@ -129,6 +147,10 @@ impl InputHandler {
         // server instead
         receive_input_instructions: Receiver<(InputInstruction, ErrorContext)>,
     ) -> Self {
+        let detach_chord = options
+            .detach_chord
+            .clone()
+            .unwrap_or_else(KeyChord::default_detach);
         InputHandler {
             mode,
             os_input,
@@ -140,6 +162,11 @@ impl InputHandler {
             receive_input_instructions,
             mouse_old_event: MouseEvent::new(),
             mouse_mode_active: false,
+            detach_chord,
+            pending_chord_keys: vec![],
+            received_pixel_dimensions: false,
+            host_supports_sixel: false,
+            terminal_focused: None,
         }
     }
 
@@ -174,7 +201,9 @@ impl InputHandler {
                             self.handle_mouse_event(&mouse_event);
                         },
                         InputEvent::Paste(pasted_text) => {
-                            if self.mode == InputMode::Normal || self.mode == InputMode::Locked {
+                            if (self.mode == InputMode::Normal || self.mode == InputMode::Locked)
+                                && self.paste_is_confirmed(&pasted_text)
+                            {
                                 self.dispatch_action(
                                     Action::Write {
                                         key_with_modifier: None,
@@ -248,10 +277,26 @@ impl InputHandler {
                         .unwrap();
                 },
                 Ok((InputInstruction::DoneParsing, _error_context)) => {
+                    if !self.received_pixel_dimensions {
+                        if let Some(pixel_dimensions) = self.fallback_pixel_dimensions() {
+                            self.os_input.send_to_server(
+                                ClientToServerMsg::TerminalPixelDimensions { pixel_dimensions },
+                            );
+                        }
+                    }
                     self.send_client_instructions
                         .send(ClientInstruction::DoneParsingStdinQuery)
                         .unwrap();
                 },
+                Ok((InputInstruction::TerminalFocus(focused), _error_context)) => {
+                    // the Windows console can report a focus change twice, as a focus record and
+                    // as the focus report of the terminal hosting it
+                    if self.terminal_focused != Some(focused) {
+                        self.terminal_focused = Some(focused);
+                        self.os_input
+                            .send_to_server(ClientToServerMsg::TerminalFocus { focused });
+                    }
+                },
                 Ok((InputInstruction::Exit, _error_context)) => {
                     self.should_exit = true;
                 },
@@ -265,6 +310,68 @@ impl InputHandler {
         raw_bytes: Vec<u8>,
         is_kitty_keyboard_protocol: bool,
     ) {
+        if self.key_continues_detach_chord(key, &raw_bytes, is_kitty_keyboard_protocol) {
+            return;
+        }
+        self.forward_key(key, raw_bytes, is_kitty_keyboard_protocol);
+    }
+    /// Tracks the detach chord, returns true if the key was taken by it
+    fn key_continues_detach_chord(
+        &mut self,
+        key: &KeyWithModifier,
+        raw_bytes: &[u8],
+        is_kitty_keyboard_protocol: bool,
+    ) -> bool {
+        let pressed = self.pending_chord_keys.len();
+        match self.detach_chord.0.get(pressed) {
+            Some(next_key) if next_key == key => {
+                if pressed + 1 == self.detach_chord.0.len() {
+                    self.pending_chord_keys.clear();
+                    self.dispatch_action(Action::Detach, None);
+                } else {
+                    self.pending_chord_keys.push((
+                        key.clone(),
+                        raw_bytes.to_vec(),
+                        is_kitty_keyboard_protocol,
+                    ));
+                }
+                true
+            },
+            _ if pressed == 0 => false,
+            _ => {
+                // the chord was broken off, so the keys held back go through as typed, except
+                // that its first key pressed twice is sent on once
+                let first_key_repeated = pressed == 1 && self.detach_chord.0[0] == *key;
+                for (pending_key, pending_raw_bytes, pending_is_kitty_keyboard_protocol) in
+                    std::mem::take(&mut self.pending_chord_keys)
+                {
+                    self.forward_key(
+                        &pending_key,
+                        pending_raw_bytes,
+                        pending_is_kitty_keyboard_protocol,
+                    );
+                }
+                first_key_repeated
+            },
+        }
+    }
+    fn forward_key(
+        &mut self,
+        key: &KeyWithModifier,
+        raw_bytes: Vec<u8>,
+        is_kitty_keyboard_protocol: bool,
+    ) {
+        match self.console_key_action(key) {
+            ConsoleKeyAction::Detach => {
+                self.dispatch_action(Action::Detach, None);
+                return;
+            },
+            ConsoleKeyAction::Quit => {
+                self.dispatch_action(Action::Quit, None);
+                return;
+            },
+            ConsoleKeyAction::Forward => {},
+        }
         // we interpret the keys into actions on the server side so that we can change the
         // keybinds at runtime
         self.os_input.send_to_server(ClientToServerMsg::Key {
@@ -273,9 +380,65 @@ impl InputHandler {
             is_kitty_keyboard_protocol,
         });
     }
+    /// What the user configured Ctrl+C and Ctrl+Z to do, these bypass the keybindings unless
+    /// they are forwarded
+    fn console_key_action(&self, key: &KeyWithModifier) -> ConsoleKeyAction {
+        if !key.has_only_modifiers(&[KeyModifier::Ctrl]) {
+            return ConsoleKeyAction::Forward;
+        }
+        let action = match key.bare_key {
+            BareKey::Char('c') => self.options.ctrl_c_action,
+            BareKey::Char('z') => self.options.ctrl_z_action,
+            _ => None,
+        };
+        action.unwrap_or(ConsoleKeyAction::Forward)
+    }
+    fn paste_is_confirmed(&self, pasted_text: &str) -> bool {
+        let is_too_large = self
+            .options
+            .paste_warning_size
+            .map(|max_size| pasted_text.len() > max_size)
+            .unwrap_or(false);
+        // a single trailing newline is how most single line copies end
+        let is_multi_line = self.options.multi_line_paste_warning.unwrap_or(false)
+            && pasted_text
+                .trim_end_matches(&['\r', '\n'][..])
+                .contains(&['\r', '\n'][..]);
+        let reason = if is_too_large {
+            format!(
+                "You are about to paste {} bytes of text.",
+                pasted_text.len()
+            )
+        } else if is_multi_line {
+            String::from("You are about to paste text spanning multiple lines.")
+        } else {
+            return true;
+        };
+        self.os_input.confirm_paste(&reason)
+    }
+    /// The pixel dimensions to go by when the terminal did not tell us its own: those the
+    /// platform knows of, or those of a VT340 if the terminal draws sixel images regardless, so
+    /// that images drawn in the panes are not dropped
+    fn fallback_pixel_dimensions(&self) -> Option<PixelDimensions> {
+        if let Some(pixel_dimensions) = self.os_input.pixel_dimensions() {
+            return Some(pixel_dimensions);
+        }
+        if !self.host_supports_sixel {
+            return None;
+        }
+        let size = self.os_input.get_terminal_size();
+        Some(PixelDimensions {
+            text_area_size: Some(SizeInPixels {
+                height: VT340_CHARACTER_CELL_SIZE.height * size.rows,
+                width: VT340_CHARACTER_CELL_SIZE.width * size.cols,
+            }),
+            character_cell_size: Some(VT340_CHARACTER_CELL_SIZE),
+        })
+    }
     fn handle_stdin_ansi_instruction(&mut self, ansi_stdin_instructions: AnsiStdinInstruction) {
         match ansi_stdin_instructions {
             AnsiStdinInstruction::PixelDimensions(pixel_dimensions) => {
+                self.received_pixel_dimensions = true;
                 self.os_input
                     .send_to_server(ClientToServerMsg::TerminalPixelDimensions {
                         pixel_dimensions,
@@ -306,6 +469,14 @@ impl InputHandler {
                     .send(ClientInstruction::SetSynchronizedOutput(enabled))
                     .unwrap();
             },
+            AnsiStdinInstruction::SixelSupport(supports_sixel) => {
+                self.host_supports_sixel = supports_sixel;
+            },
+            AnsiStdinInstruction::KittyKeyboardProtocol(_flags) => {
+                self.send_client_instructions
+                    .send(ClientInstruction::KittyKeyboardProtocolSupported)
+                    .unwrap();
+            },
         }
     }
     fn handle_mouse_event(&mut self, mouse_event: &MouseEvent) {
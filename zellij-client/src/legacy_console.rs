@@ -0,0 +1,184 @@
+// The classic Windows console (conhost, eg. a plain cmd window or an RDP session) only speaks
+// part of VT: truecolor is not available everywhere, the rounded box-drawing corners are missing
+// from its default fonts and it knows nothing of styled underlines. Rather than teaching the
+// server about every host, the client rewrites the render output it gets for such a console.
+
+/// Rewrite the render `output` of the server for the classic console: truecolor SGR parameters
+/// become their nearest 256 color palette entry, underline colors and styles are dropped and
+/// rounded pane corners become square ones.
+pub fn simplify_render_output(output: &str) -> String {
+    let mut simplified = String::with_capacity(output.len());
+    let mut rest = output;
+    while let Some(index) = rest.find(|c: char| c == '\u{1b}' || is_rounded_corner(c)) {
+        simplified.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('\u{1b}') if rest[1..].starts_with('[') => {
+                let sequence = &rest[2..];
+                match sequence.find(|c: char| ('\u{40}'..='\u{7e}').contains(&c)) {
+                    Some(end) if sequence[end..].starts_with('m') => {
+                        simplified.push_str(&simplify_sgr(&sequence[..end]));
+                        rest = &sequence[end + 1..];
+                    },
+                    Some(end) => {
+                        simplified.push_str(&rest[..end + 3]);
+                        rest = &sequence[end + 1..];
+                    },
+                    None => {
+                        simplified.push_str(rest);
+                        rest = "";
+                    },
+                }
+            },
+            Some(c) => {
+                simplified.push(square_corner(c));
+                rest = chars.as_str();
+            },
+            None => break,
+        }
+    }
+    simplified.push_str(rest);
+    simplified
+}
+
+fn is_rounded_corner(c: char) -> bool {
+    matches!(c, '╭' | '╮' | '╯' | '╰')
+}
+
+fn square_corner(c: char) -> char {
+    match c {
+        '╭' => '┌',
+        '╮' => '┐',
+        '╯' => '┘',
+        '╰' => '└',
+        c => c,
+    }
+}
+
+/// The SGR sequence for the `parameters` of one, without what the classic console cannot show.
+fn simplify_sgr(sgr_parameters: &str) -> String {
+    let mut simplified: Vec<String> = vec![];
+    let mut parameters = sgr_parameters.split(';');
+    while let Some(parameter) = parameters.next() {
+        // colon separated sub-parameters, eg. "58:2::255:0:0" or "4:3"
+        let mut sub_parameters = parameter.split(':');
+        let code = sub_parameters.next().unwrap_or_default();
+        match code {
+            "38" | "48" if parameter.contains(':') => {
+                let sub_parameters: Vec<&str> = sub_parameters.collect();
+                match sub_parameters.as_slice() {
+                    ["2", "", r, g, b] | ["2", r, g, b] => {
+                        if let Some(index) = palette_index(r, g, b) {
+                            simplified.push(format!("{};5;{}", code, index));
+                        }
+                    },
+                    ["5", index] => simplified.push(format!("{};5;{}", code, index)),
+                    _ => {},
+                }
+            },
+            "38" | "48" => match parameters.next() {
+                Some("2") => {
+                    let (r, g, b) = (
+                        parameters.next().unwrap_or_default(),
+                        parameters.next().unwrap_or_default(),
+                        parameters.next().unwrap_or_default(),
+                    );
+                    if let Some(index) = palette_index(r, g, b) {
+                        simplified.push(format!("{};5;{}", code, index));
+                    }
+                },
+                Some("5") => {
+                    let index = parameters.next().unwrap_or_default();
+                    simplified.push(format!("{};5;{}", code, index));
+                },
+                _ => {},
+            },
+            "58" => {
+                if !parameter.contains(':') {
+                    match parameters.next() {
+                        Some("2") => {
+                            parameters.nth(2);
+                        },
+                        Some("5") => {
+                            parameters.next();
+                        },
+                        _ => {},
+                    }
+                }
+            },
+            "59" => {},
+            "4" if parameter == "4:0" => simplified.push("24".to_owned()),
+            code => simplified.push(code.to_owned()),
+        }
+    }
+    if simplified.is_empty() && !sgr_parameters.is_empty() {
+        // nothing left to set, and an empty SGR sequence would reset all styles instead
+        return String::new();
+    }
+    format!("\u{1b}[{}m", simplified.join(";"))
+}
+
+fn palette_index(r: &str, g: &str, b: &str) -> Option<u8> {
+    Some(rgb_to_palette_index(
+        r.parse().ok()?,
+        g.parse().ok()?,
+        b.parse().ok()?,
+    ))
+}
+
+/// The entry of the 256 color palette closest to a truecolor: one of its 24 grays for a gray,
+/// otherwise one of its 6x6x6 color cube.
+fn rgb_to_palette_index(r: u8, g: u8, b: u8) -> u8 {
+    if r == g && g == b {
+        // the grays go from 8 to 238 in steps of 10
+        return match r {
+            0..=3 => 16,
+            247..=255 => 231,
+            gray => 232 + ((gray.saturating_sub(8) + 5) / 10).min(23),
+        };
+    }
+    let cube = |value: u8| -> u8 {
+        match value {
+            0..=47 => 0,
+            48..=114 => 1,
+            value => (value - 35) / 40,
+        }
+    };
+    16 + 36 * cube(r) + 6 * cube(g) + cube(b)
+}
+
+#[test]
+pub fn maps_truecolor_to_the_256_color_palette() {
+    assert_eq!(
+        simplify_render_output("\u{1b}[38;2;255;0;0mred"),
+        "\u{1b}[38;5;196mred"
+    );
+    assert_eq!(
+        simplify_render_output("\u{1b}[1;48;2;0;0;255;38;5;3mblue"),
+        "\u{1b}[1;48;5;21;38;5;3mblue"
+    );
+    assert_eq!(
+        simplify_render_output("\u{1b}[38;2;128;128;128mgray"),
+        "\u{1b}[38;5;244mgray"
+    );
+}
+
+#[test]
+pub fn drops_underline_colors() {
+    assert_eq!(
+        simplify_render_output("\u{1b}[58:2::255:0:0m\u{1b}[4;59mx"),
+        "\u{1b}[4mx"
+    );
+}
+
+#[test]
+pub fn squares_rounded_corners() {
+    assert_eq!(simplify_render_output("╭─╮\n╰─╯"), "┌─┐\n└─┘");
+}
+
+#[test]
+pub fn leaves_other_sequences_alone() {
+    let output = "\u{1b}[2;3H\u{1b}[?25l\u{1b}[mtext\u{1b}]8;;\u{1b}\\";
+    assert_eq!(simplify_render_output(output), output);
+}
@@ -9,15 +9,20 @@ mod os_input_output_windows;
 
 pub mod cli_client;
 mod command_is_executing;
+mod connection_quality;
+pub mod doctor;
 mod input_handler;
 mod keyboard_parser;
+mod legacy_console;
 pub mod old_config_converter;
+mod piped_input;
 #[cfg(feature = "web_server_capability")]
 pub mod remote_attach;
 mod stdin_ansi_parser;
 mod stdin_handler;
 #[cfg(feature = "web_server_capability")]
 pub mod web_client;
+mod win32_input_parser;
 
 use log::info;
 use std::env::current_exe;
@@ -121,10 +126,13 @@ impl From<std::io::Error> for RemoteClientError {
     }
 }
 
+use crate::connection_quality::{ConnectionIndicator, ConnectionQuality};
 use crate::stdin_ansi_parser::{AnsiStdinInstruction, StdinAnsiParser, SyncOutput};
 use crate::{
-    command_is_executing::CommandIsExecuting, input_handler::input_loop,
-    os_input_output::ClientOsApi, stdin_handler::stdin_loop,
+    command_is_executing::CommandIsExecuting,
+    input_handler::input_loop,
+    os_input_output::{pipe_to_command, ClientOsApi},
+    stdin_handler::stdin_loop,
 };
 use termwiz::input::InputEvent;
 use zellij_utils::cli::CliArgs;
@@ -134,7 +142,12 @@ use zellij_utils::{
     data::{ClientId, ConnectToSession, KeyWithModifier, LayoutInfo, LayoutMetadata},
     envs,
     errors::{ClientContext, ContextType, ErrorInstruction},
-    input::{cli_assets::CliAssets, config::Config, options::Options},
+    input::{
+        actions::Action,
+        cli_assets::CliAssets,
+        config::Config,
+        options::{ConsoleKeyAction, Options, PipeAccess},
+    },
     ipc::{ClientToServerMsg, ExitReason, ServerToClientMsg},
     pane_size::Size,
 };
@@ -153,6 +166,7 @@ pub(crate) enum ClientInstruction {
     LogError(Vec<String>),
     SwitchSession(ConnectToSession),
     SetSynchronizedOutput(Option<SyncOutput>),
+    KittyKeyboardProtocolSupported,
     UnblockCliPipeInput(()), // String -> pipe name
     CliPipeOutput((), ()),   // String -> pipe name, String -> output
     QueryTerminalSize,
@@ -160,6 +174,16 @@ pub(crate) enum ClientInstruction {
     #[allow(dead_code)] // we need the session name here even though we're not currently using it
     RenamedSession(String), // String -> new session name
     ConfigFileUpdated,
+    Bell(String), // String -> name of the pane that rang the bell
+    SetClipboard(String),
+    QueryClipboard(u32), // u32 -> id of the terminal pane asking for the clipboard
+    Notify(String, String), // (title, body)
+    SendHeartbeat,
+    HeartbeatAck(u64), // u64 -> id of the heartbeat
+    OpenLink(String),
+    FileChunk, // files are only downloaded by web clients
+    ShutdownPending,
+    DesktopLocked,
 }
 
 impl From<ServerToClientMsg> for ClientInstruction {
@@ -182,6 +206,17 @@ impl From<ServerToClientMsg> for ClientInstruction {
             ServerToClientMsg::StartWebServer => ClientInstruction::StartWebServer,
             ServerToClientMsg::RenamedSession { name } => ClientInstruction::RenamedSession(name),
             ServerToClientMsg::ConfigFileUpdated => ClientInstruction::ConfigFileUpdated,
+            ServerToClientMsg::Bell { pane_name } => ClientInstruction::Bell(pane_name),
+            ServerToClientMsg::SetClipboard { content } => ClientInstruction::SetClipboard(content),
+            ServerToClientMsg::QueryClipboard { terminal_id } => {
+                ClientInstruction::QueryClipboard(terminal_id)
+            },
+            ServerToClientMsg::Notify { title, body } => ClientInstruction::Notify(title, body),
+            ServerToClientMsg::HeartbeatAck { id } => ClientInstruction::HeartbeatAck(id),
+            ServerToClientMsg::OpenLink { uri } => ClientInstruction::OpenLink(uri),
+            ServerToClientMsg::FileChunk { .. } => ClientInstruction::FileChunk,
+            ServerToClientMsg::ShutdownPending => ClientInstruction::ShutdownPending,
+            ServerToClientMsg::ConnStatusDetailed { .. } => ClientInstruction::Connected,
         }
     }
 }
@@ -200,12 +235,25 @@ impl From<&ClientInstruction> for ClientContext {
             ClientInstruction::DoneParsingStdinQuery => ClientContext::DoneParsingStdinQuery,
             ClientInstruction::SwitchSession(..) => ClientContext::SwitchSession,
             ClientInstruction::SetSynchronizedOutput(..) => ClientContext::SetSynchronisedOutput,
+            ClientInstruction::KittyKeyboardProtocolSupported => {
+                ClientContext::KittyKeyboardProtocolSupported
+            },
             ClientInstruction::UnblockCliPipeInput(..) => ClientContext::UnblockCliPipeInput,
             ClientInstruction::CliPipeOutput(..) => ClientContext::CliPipeOutput,
             ClientInstruction::QueryTerminalSize => ClientContext::QueryTerminalSize,
             ClientInstruction::StartWebServer => ClientContext::StartWebServer,
             ClientInstruction::RenamedSession(..) => ClientContext::RenamedSession,
             ClientInstruction::ConfigFileUpdated => ClientContext::ConfigFileUpdated,
+            ClientInstruction::Bell(..) => ClientContext::Bell,
+            ClientInstruction::SetClipboard(..) => ClientContext::SetClipboard,
+            ClientInstruction::QueryClipboard(..) => ClientContext::QueryClipboard,
+            ClientInstruction::Notify(..) => ClientContext::Notify,
+            ClientInstruction::SendHeartbeat => ClientContext::SendHeartbeat,
+            ClientInstruction::HeartbeatAck(..) => ClientContext::HeartbeatAck,
+            ClientInstruction::OpenLink(..) => ClientContext::OpenLink,
+            ClientInstruction::FileChunk => ClientContext::FileChunk,
+            ClientInstruction::ShutdownPending => ClientContext::ShutdownPending,
+            ClientInstruction::DesktopLocked => ClientContext::DesktopLocked,
         }
     }
 }
@@ -238,7 +286,7 @@ fn spawn_web_server(cli_args: &CliArgs) -> Result<String, String> {
     // On Unix, -d causes the web server to daemonize (double-fork), so cmd.output()
     // returns quickly after the parent exits. On Windows, daemonize is not supported
     // and the web server runs in foreground — cmd.output() would block forever.
-    // Use spawn() with detached process flags instead (same pattern as spawn_server).
+    // Spawn it detached instead (same as spawn_server).
     #[cfg(not(windows))]
     {
         let output = cmd.output();
@@ -255,9 +303,7 @@ fn spawn_web_server(cli_args: &CliArgs) -> Result<String, String> {
     }
     #[cfg(windows)]
     {
-        use std::os::windows::process::CommandExt;
-        cmd.creation_flags(0x200 | 0x08000000); // CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW
-        match cmd.spawn() {
+        match zellij_utils::shared::spawn_detached(&mut cmd) {
             Ok(_child) => Ok(String::new()),
             Err(e) => Err(e.to_string()),
         }
@@ -272,13 +318,160 @@ fn spawn_web_server(_cli_args: &CliArgs) -> Result<String, String> {
     Ok("".to_owned())
 }
 
-pub fn spawn_server(socket_path: &Path, debug: bool) -> io::Result<()> {
+/// Synchronized output for terminals known to support it, until the terminal answers our query
+/// about it.
+fn default_synchronised_output(os_input: &dyn ClientOsApi) -> Option<SyncOutput> {
+    match os_input.env_variable("TERM").as_deref() {
+        Some("alacritty") => Some(SyncOutput::DCS),
+        // Windows Terminal does not set TERM, older versions ignore the mode
+        _ if cfg!(windows) && os_input.env_variable("WT_SESSION").is_some() => {
+            Some(SyncOutput::CSI)
+        },
+        _ => None,
+    }
+}
+
+/// Write the render `output` of the server to the terminal as a single frame.
+fn render_frame(os_input: &dyn ClientOsApi, output: &str, synchronised_output: Option<SyncOutput>) {
+    let mut frame = Vec::with_capacity(output.len() + 16);
+    if let Some(sync) = synchronised_output {
+        frame.extend_from_slice(sync.start_seq());
+    }
+    frame.extend_from_slice(output.as_bytes());
+    if let Some(sync) = synchronised_output {
+        frame.extend_from_slice(sync.end_seq());
+    }
+    os_input
+        .write_frame(&frame)
+        .expect("cannot write to stdout");
+}
+
+/// Show the quality of the connection to the server as it is `now`.
+fn update_connection_indicator(
+    os_input: &dyn ClientOsApi,
+    connection_quality: &ConnectionQuality,
+    connection_indicator: &mut ConnectionIndicator,
+    synchronised_output: Option<SyncOutput>,
+    now: std::time::Instant,
+) {
+    if connection_indicator.update(connection_quality.status(now)) {
+        // the server paints over what is left of the indicator, which is drawn again with its
+        // next render if it is still displayed
+        os_input.send_to_server(ClientToServerMsg::Redraw);
+    } else if connection_indicator.is_displayed() {
+        let indicator = connection_indicator.render(os_input.get_terminal_size().cols);
+        render_frame(os_input, &indicator, synchronised_output);
+    }
+}
+
+// we save and restore the cursor ourselves as well, for terminals that treat 1049 like 1047 and
+// only switch buffers
+#[cfg(not(windows))]
+const ENTER_ALTERNATE_SCREEN: &str = "\u{1b}7\u{1b}[?1049h";
+#[cfg(not(windows))]
+const LEAVE_ALTERNATE_SCREEN: &str = "\u{1b}[?1049l\u{1b}8";
+// on Windows the server titles the window `session: tab`, so we also push the title it is about
+// to replace onto the title stack of the terminal (XTWINOPS 22/23)
+#[cfg(windows)]
+const ENTER_ALTERNATE_SCREEN: &str = "\u{1b}7\u{1b}[22;2t\u{1b}[?1049h";
+#[cfg(windows)]
+const LEAVE_ALTERNATE_SCREEN: &str = "\u{1b}[?1049l\u{1b}[23;2t\u{1b}8";
+
+/// Switch the terminal to the alternate screen, remembering where the user's console was scrolled
+/// to and how its window was titled so that `leave_alternate_screen` can bring them back there.
+fn enter_alternate_screen(os_input: &dyn ClientOsApi) {
+    os_input.save_screen_position();
+    os_input.save_window_title();
+    let mut stdout = os_input.get_stdout_writer();
+    let _ = stdout.write(ENTER_ALTERNATE_SCREEN.as_bytes()).unwrap();
+    stdout.flush().unwrap();
+}
+
+/// Switch the terminal back to the screen the user had before `enter_alternate_screen`, with
+/// their shell history, cursor and window title where they left them.
+fn leave_alternate_screen(os_input: &dyn ClientOsApi) {
+    let mut stdout = os_input.get_stdout_writer();
+    let _ = stdout.write(LEAVE_ALTERNATE_SCREEN.as_bytes()).unwrap();
+    stdout.flush().unwrap();
+    os_input.restore_screen_position();
+    os_input.restore_window_title();
+}
+
+/// Switch the terminal to the alternate screen, clear the attributes its previous program may
+/// have left behind and enter the keyboard modes we read input in.
+fn set_up_client_terminal(
+    os_input: &dyn ClientOsApi,
+    enter_kitty_keyboard_mode: bool,
+    win32_input_mode: bool,
+) {
+    let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
+    enter_alternate_screen(os_input);
+    let _ = os_input
+        .get_stdout_writer()
+        .write(clear_client_terminal_attributes.as_bytes())
+        .unwrap();
+    if enter_kitty_keyboard_mode {
+        let _ = os_input
+            .get_stdout_writer()
+            .write("\u{1b}[>1u".as_bytes())
+            .unwrap();
+    }
+    if win32_input_mode {
+        let _ = os_input
+            .get_stdout_writer()
+            .write(zellij_utils::win32_input::WIN32_INPUT_MODE_ENABLE)
+            .unwrap();
+    }
+}
+
+/// Delays between attempts to attach again to a server whose pipe broke.
+const REATTACH_BACKOFF_MS: [u64; 5] = [100, 200, 400, 800, 1600];
+
+/// Reconnect to the server at `ipc_pipe` and attach to it again with `reattach_msg`, backing off
+/// between attempts. Returns false if the server could not be reached.
+fn reattach_to_server(
+    os_input: &dyn ClientOsApi,
+    ipc_pipe: &Path,
+    reattach_msg: &ClientToServerMsg,
+) -> bool {
+    for (attempt, backoff) in REATTACH_BACKOFF_MS.iter().enumerate() {
+        let backoff = std::time::Duration::from_millis(*backoff);
+        thread::sleep(backoff);
+        if os_input.reconnect_to_server(ipc_pipe, backoff) {
+            log::warn!(
+                "Lost the connection to the server, reattached after {} attempt(s)",
+                attempt + 1
+            );
+            os_input.send_to_server(reattach_msg.clone());
+            os_input.send_to_server(ClientToServerMsg::TerminalResize {
+                new_size: os_input.get_terminal_size(),
+            });
+            return true;
+        }
+    }
+    log::error!("Lost the connection to the server and could not reattach");
+    false
+}
+
+pub fn spawn_server(
+    socket_path: &Path,
+    debug: bool,
+    pipe_access: Option<&PipeAccess>,
+) -> io::Result<()> {
     let mut cmd = Command::new(current_exe()?);
     cmd.arg("--server");
     cmd.arg(socket_path);
     if debug {
         cmd.arg("--debug");
     }
+    // the pipes of the session are created before it receives any configuration
+    #[cfg(windows)]
+    match pipe_access {
+        Some(pipe_access) => cmd.env(envs::PIPE_ACCESS_ENV_KEY, pipe_access.to_string()),
+        None => cmd.env_remove(envs::PIPE_ACCESS_ENV_KEY),
+    };
+    #[cfg(not(windows))]
+    let _ = pipe_access;
 
     // On Unix, the server daemonizes (double-fork) so the parent exits immediately
     // and cmd.status() returns. On Windows there's no fork, so we launch the server
@@ -299,11 +492,9 @@ pub fn spawn_server(socket_path: &Path, debug: bool) -> io::Result<()> {
     }
     #[cfg(windows)]
     {
-        use std::os::windows::process::CommandExt;
-        // CREATE_NEW_PROCESS_GROUP (0x200): server survives if client's console closes
-        // CREATE_NO_WINDOW (0x08000000): server doesn't open a new console window
-        cmd.creation_flags(0x200 | 0x08000000);
-        let _child = cmd.spawn()?;
+        // this is the detached server already, it doesn't need to spawn itself again
+        cmd.env(zellij_utils::envs::SERVER_MODE_ENV_KEY, "1");
+        let _child = zellij_utils::shared::spawn_detached(&mut cmd)?;
         // Drop the Child handle without waiting — the server runs independently.
         // On Windows, dropping Child does NOT kill the process.
         Ok(())
@@ -349,6 +540,7 @@ pub(crate) enum InputInstruction {
     AnsiStdinInstructions(Vec<AnsiStdinInstruction>),
     StartedParsing,
     DoneParsing,
+    TerminalFocus(bool), // bool -> whether the terminal gained focus
     Exit,
 }
 
@@ -359,10 +551,7 @@ pub async fn run_remote_client_terminal_loop(
 ) -> Result<Option<ConnectToSession>, RemoteClientError> {
     use crate::os_input_output::{AsyncSignals, AsyncStdin};
 
-    let synchronised_output = match os_input.env_variable("TERM").as_deref() {
-        Some("alacritty") => Some(SyncOutput::DCS),
-        _ => None,
-    };
+    let synchronised_output = default_synchronised_output(&*os_input);
 
     let mut async_stdin: Box<dyn AsyncStdin> = os_input.get_async_stdin_reader();
     let mut async_signals: Box<dyn AsyncSignals> = os_input
@@ -421,7 +610,8 @@ pub async fn run_remote_client_terminal_loop(
                             break;
                         }
                     }
-                    crate::os_input_output::SignalEvent::Quit => {
+                    crate::os_input_output::SignalEvent::Quit
+                    | crate::os_input_output::SignalEvent::Break => {
                         break;
                     }
                 }
@@ -507,6 +697,9 @@ pub async fn run_remote_client_terminal_loop(
                             Ok(WebServerToWebClientControlMessage::SwitchedSession{ .. }) => {
                                 // no-op
                             }
+                            Ok(WebServerToWebClientControlMessage::ServerRestarting{ .. }) => {
+                                log::info!("Web server is restarting");
+                            }
                             Err(e) => {
                                 log::error!("Failed to deserialize control message: {}", e);
                             }
@@ -555,15 +748,11 @@ pub fn start_remote_client(
 
     let reconnect_to_session = None;
     let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
-    let take_snapshot = "\u{1b}[?1049h";
     let bracketed_paste = "\u{1b}[?2004h";
     let enter_kitty_keyboard_mode = "\u{1b}[>1u";
     os_input.unset_raw_mode().unwrap();
 
-    let _ = os_input
-        .get_stdout_writer()
-        .write(take_snapshot.as_bytes())
-        .unwrap();
+    enter_alternate_screen(&*os_input);
     let _ = os_input
         .get_stdout_writer()
         .write(clear_client_terminal_attributes.as_bytes())
@@ -575,8 +764,6 @@ pub fn start_remote_client(
 
     envs::set_zellij("0".to_string());
 
-    let full_screen_ws = os_input.get_terminal_size();
-
     os_input.set_raw_mode();
     let _ = os_input
         .get_stdout_writer()
@@ -595,21 +782,16 @@ pub fn start_remote_client(
 
     let reset_controlling_terminal_state = |e: String, exit_status: i32| {
         os_input.unset_raw_mode().unwrap();
-        let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
-        let restore_alternate_screen = "\u{1b}[?1049l";
         let exit_kitty_keyboard_mode = "\u{1b}[<1u";
         let reset_style = "\u{1b}[m";
         let show_cursor = "\u{1b}[?25h";
         os_input.disable_mouse().non_fatal();
-        let error = format!(
-            "{}{}{}{}\n{}{}\n",
-            reset_style,
-            show_cursor,
-            restore_alternate_screen,
-            exit_kitty_keyboard_mode,
-            goto_start_of_last_line,
-            e
-        );
+        let _ = os_input
+            .get_stdout_writer()
+            .write(exit_kitty_keyboard_mode.as_bytes())
+            .unwrap();
+        leave_alternate_screen(&*os_input);
+        let error = format!("{}{}\n{}\n", reset_style, show_cursor, e);
         let _ = os_input
             .get_stdout_writer()
             .write(error.as_bytes())
@@ -664,32 +846,26 @@ pub fn start_client(
         .support_kitty_keyboard_protocol
         .map(|e| !e)
         .unwrap_or(false);
+    let mut win32_input_mode = win32_input_parser::win32_input_mode_supported();
+    // the Windows console does not reliably pass the kitty keyboard protocol through to the host
+    // terminal, so there we only enable it once the terminal answered our query for it
+    let negotiate_kitty_keyboard_protocol = cfg!(windows);
     let should_start_web_server = config_options.web_server.map(|w| w).unwrap_or(false);
     let mut reconnect_to_session = None;
-    let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
-    let take_snapshot = "\u{1b}[?1049h";
     let bracketed_paste = "\u{1b}[?2004h";
     let enter_kitty_keyboard_mode = "\u{1b}[>1u";
+    let focus_reporting = "\u{1b}[?1004h";
     os_input.unset_raw_mode().unwrap();
 
     if !is_a_reconnect {
         // we don't do this for a reconnect because our controlling terminal already has the
         // attributes we want from it, and some terminals don't treat these atomically (looking at
         // you Windows Terminal...)
-        let _ = os_input
-            .get_stdout_writer()
-            .write(take_snapshot.as_bytes())
-            .unwrap();
-        let _ = os_input
-            .get_stdout_writer()
-            .write(clear_client_terminal_attributes.as_bytes())
-            .unwrap();
-        if !explicitly_disable_kitty_keyboard_protocol {
-            let _ = os_input
-                .get_stdout_writer()
-                .write(enter_kitty_keyboard_mode.as_bytes())
-                .unwrap();
-        }
+        set_up_client_terminal(
+            &*os_input,
+            !explicitly_disable_kitty_keyboard_protocol && !negotiate_kitty_keyboard_protocol,
+            win32_input_mode,
+        );
     }
     envs::set_zellij("0".to_string());
     config.env.set_vars();
@@ -746,6 +922,8 @@ pub fn start_client(
                 max_panes: cli_args.max_panes,
                 force_run_layout_commands: false,
                 cwd: None,
+                client_theme: cli_args.attach_theme(),
+                session_secret: cli_args.session_secret.clone(),
             };
             (
                 ClientToServerMsg::AttachClient {
@@ -791,12 +969,19 @@ pub fn start_client(
                 max_panes: cli_args.max_panes,
                 force_run_layout_commands: force_run_commands,
                 cwd,
+                client_theme: None,
+                session_secret: cli_args.session_secret.clone(),
             };
 
             os_input.update_session_name(name);
             let ipc_pipe = create_ipc_pipe();
 
-            spawn_server(&*ipc_pipe, cli_args.debug).unwrap();
+            spawn_server(
+                &*ipc_pipe,
+                cli_args.debug,
+                config_options.pipe_access.as_ref(),
+            )
+            .unwrap();
             if should_start_web_server {
                 if let Err(e) = spawn_web_server(&cli_args) {
                     log::error!("Failed to start web server: {}", e);
@@ -845,12 +1030,19 @@ pub fn start_client(
                 max_panes: cli_args.max_panes,
                 force_run_layout_commands: false,
                 cwd: layout_cwd,
+                client_theme: None,
+                session_secret: cli_args.session_secret.clone(),
             };
 
             os_input.update_session_name(name);
             let ipc_pipe = create_ipc_pipe();
 
-            spawn_server(&*ipc_pipe, cli_args.debug).unwrap();
+            spawn_server(
+                &*ipc_pipe,
+                cli_args.debug,
+                config_options.pipe_access.as_ref(),
+            )
+            .unwrap();
             if should_start_web_server {
                 if let Err(e) = spawn_web_server(&cli_args) {
                     log::error!("Failed to start web server: {}", e);
@@ -869,7 +1061,31 @@ pub fn start_client(
         },
     };
 
+    // if the pipe to the server breaks while the server is still alive, we attach to it again
+    // with the same assets rather than starting over
+    let reattach_msg = match first_msg.clone() {
+        ClientToServerMsg::FirstClientConnected {
+            cli_assets,
+            is_web_client,
+        } => ClientToServerMsg::AttachClient {
+            cli_assets,
+            tab_position_to_focus: None,
+            pane_to_focus: None,
+            is_web_client,
+        },
+        msg => msg,
+    };
+
     os_input.connect_to_server(&*ipc_pipe);
+    // with attach --force, the server only goes on to attach us once the other clients of the
+    // session restored their terminals and left (or took too long to)
+    if cli_args.force_attach() && matches!(first_msg, ClientToServerMsg::AttachClient { .. }) {
+        // a locked session only lets those knowing its secret detach the others
+        if let Some(session_secret) = cli_args.session_secret.clone() {
+            os_input.send_to_server(ClientToServerMsg::Authenticate { session_secret });
+        }
+        os_input.send_to_server(ClientToServerMsg::DetachOtherClients);
+    }
     os_input.send_to_server(first_msg);
 
     let mut command_is_executing = CommandIsExecuting::new();
@@ -879,6 +1095,10 @@ pub fn start_client(
         .get_stdout_writer()
         .write(bracketed_paste.as_bytes())
         .unwrap();
+    let _ = os_input
+        .get_stdout_writer()
+        .write(focus_reporting.as_bytes())
+        .unwrap();
 
     let (send_client_instructions, receive_client_instructions): ChannelWithContext<
         ClientInstruction,
@@ -902,6 +1122,25 @@ pub fn start_client(
     });
 
     let on_force_close = config_options.on_force_close.unwrap_or_default();
+    let ctrl_break_action = config_options
+        .ctrl_break_action
+        .unwrap_or_else(|| on_force_close.into());
+    let bell_notification = config_options.bell_notification.unwrap_or_default();
+    let allow_clipboard_read = config_options.allow_clipboard_read.unwrap_or(false);
+    let detach_on_lock = config_options.detach_on_lock.unwrap_or(false);
+    let copy_fallback_command = config_options.copy_fallback_command.clone();
+    let copy_line_endings = config_options.copy_line_endings.unwrap_or_default();
+    let frame_interval = config_options
+        .max_fps
+        .filter(|max_fps| *max_fps > 0)
+        .map(|max_fps| {
+            std::time::Duration::from_secs(1) / u32::try_from(max_fps).unwrap_or(u32::MAX)
+        });
+    let legacy_console_rendering = config_options
+        .legacy_console_rendering
+        .unwrap_or_else(|| os_input.is_classic_console());
+    zellij_utils::logging::apply_log_options(&config_options);
+    let detect_unbracketed_paste = config_options.detect_unbracketed_paste.unwrap_or(false);
     let stdin_ansi_parser = Arc::new(Mutex::new(StdinAnsiParser::new()));
 
     let _stdin_thread = thread::Builder::new()
@@ -916,6 +1155,8 @@ pub fn start_client(
                     send_input_instructions,
                     stdin_ansi_parser,
                     explicitly_disable_kitty_keyboard_protocol,
+                    win32_input_mode,
+                    detect_unbracketed_paste,
                 )
             }
         });
@@ -940,6 +1181,13 @@ pub fn start_client(
             }
         });
 
+    if detach_on_lock {
+        let send_client_instructions = send_client_instructions.clone();
+        os_input.watch_desktop_lock(Box::new(move || {
+            let _ = send_client_instructions.send(ClientInstruction::DesktopLocked);
+        }));
+    }
+
     let _signal_thread = thread::Builder::new()
         .name("signal_listener".to_string())
         .spawn({
@@ -952,6 +1200,12 @@ pub fn start_client(
                             os_api.send_to_server(ClientToServerMsg::TerminalResize {
                                 new_size: os_api.get_terminal_size(),
                             });
+                            // the font may have been resized along with the window
+                            if let Some(pixel_dimensions) = os_api.pixel_dimensions() {
+                                os_api.send_to_server(ClientToServerMsg::TerminalPixelDimensions {
+                                    pixel_dimensions,
+                                });
+                            }
                         }
                     }),
                     Box::new({
@@ -965,15 +1219,55 @@ pub fn start_client(
                             });
                         }
                     }),
+                    Box::new({
+                        let os_api = os_input.clone();
+                        move || {
+                            let action = match ctrl_break_action {
+                                // Ctrl+Break has no byte of its own, ConPTY raises a
+                                // CTRL_BREAK_EVENT in the pane when it reads its key events
+                                ConsoleKeyAction::Forward => Action::Write {
+                                    key_with_modifier: None,
+                                    bytes: zellij_utils::win32_input::Win32KeyEvent::ctrl_break()
+                                        .encode_press(),
+                                    is_kitty_keyboard_protocol: false,
+                                },
+                                ConsoleKeyAction::Detach => Action::Detach,
+                                ConsoleKeyAction::Quit => Action::Quit,
+                            };
+                            os_api.send_to_server(ClientToServerMsg::Action {
+                                action,
+                                terminal_id: None,
+                                client_id: None,
+                                is_cli_client: false,
+                            });
+                        }
+                    }),
                 );
             }
         })
         .unwrap();
 
+    let _heartbeat_thread = thread::Builder::new()
+        .name("heartbeat".to_string())
+        .spawn({
+            let send_client_instructions = send_client_instructions.clone();
+            move || loop {
+                thread::sleep(connection_quality::HEARTBEAT_INTERVAL);
+                if send_client_instructions
+                    .send(ClientInstruction::SendHeartbeat)
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        })
+        .unwrap();
+
     let router_thread = thread::Builder::new()
         .name("router".to_string())
         .spawn({
             let os_input = os_input.clone();
+            let ipc_pipe = ipc_pipe.clone();
             let mut should_break = false;
             let mut consecutive_unknown_messages_received = 0;
             move || loop {
@@ -989,6 +1283,16 @@ pub fn start_client(
                             break;
                         }
                     },
+                    None if os_input.server_disconnected() => {
+                        if reattach_to_server(&*os_input, &ipc_pipe, &reattach_msg) {
+                            consecutive_unknown_messages_received = 0;
+                            continue;
+                        }
+                        send_client_instructions
+                            .send(ClientInstruction::Exit(ExitReason::Disconnect))
+                            .unwrap();
+                        break;
+                    },
                     None => {
                         consecutive_unknown_messages_received += 1;
                         send_client_instructions
@@ -1011,13 +1315,9 @@ pub fn start_client(
 
     let handle_error = |backtrace: String| {
         os_input.unset_raw_mode().unwrap();
-        let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
-        let restore_snapshot = "\u{1b}[?1049l";
         os_input.disable_mouse().non_fatal();
-        let error = format!(
-            "{}\n{}{}\n",
-            restore_snapshot, goto_start_of_last_line, backtrace
-        );
+        leave_alternate_screen(&*os_input);
+        let error = format!("\n{}\n", backtrace);
         let _ = os_input
             .get_stdout_writer()
             .write(error.as_bytes())
@@ -1027,15 +1327,27 @@ pub fn start_client(
     };
 
     let mut exit_msg = String::new();
+    let mut acknowledge_force_detach = false;
     let mut loading = true;
     let mut showed_loading_message = false;
     let loading_start = std::time::Instant::now();
     let loading_delay = std::time::Duration::from_millis(400);
     let mut pending_instructions = vec![];
-    let mut synchronised_output = match os_input.env_variable("TERM").as_deref() {
-        Some("alacritty") => Some(SyncOutput::DCS),
-        _ => None,
+    // the classic console does not know synchronized output, whatever it answers when asked
+    let mut synchronised_output = if legacy_console_rendering {
+        None
+    } else {
+        default_synchronised_output(&*os_input)
     };
+    // render output held back until the next frame is due when the frame rate is capped
+    let mut pending_frame = String::new();
+    let mut last_frame_at = std::time::Instant::now();
+    let mut connection_quality = ConnectionQuality::default();
+    let mut connection_indicator = ConnectionIndicator::new();
+    // the server stops answering heartbeats while it waits for the panes of a killed session
+    let mut shutdown_pending = false;
+    // we offer to attach to the session again once the user is back
+    let mut detached_on_lock = false;
 
     let mut stdout = os_input.get_stdout_writer();
 
@@ -1066,6 +1378,24 @@ pub fn start_client(
                     panic!("client instruction channel disconnected");
                 },
             }
+        } else if let (Some(frame_interval), false) = (frame_interval, pending_frame.is_empty()) {
+            let remaining = frame_interval
+                .checked_sub(last_frame_at.elapsed())
+                .unwrap_or(std::time::Duration::ZERO);
+            match receive_client_instructions.recv_timeout(remaining) {
+                Ok(instruction) => instruction,
+                Err(RecvTimeoutError::Timeout) => {
+                    pending_frame
+                        .push_str(&connection_indicator.render(os_input.get_terminal_size().cols));
+                    render_frame(&*os_input, &pending_frame, synchronised_output);
+                    pending_frame.clear();
+                    last_frame_at = std::time::Instant::now();
+                    continue;
+                },
+                Err(RecvTimeoutError::Disconnected) => {
+                    panic!("client instruction channel disconnected");
+                },
+            }
         } else {
             receive_client_instructions
                 .recv()
@@ -1103,8 +1433,22 @@ pub fn start_client(
 
         match client_instruction {
             ClientInstruction::Exit(reason) => {
-                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                // output held back for the next frame would otherwise be lost
+                if !pending_frame.is_empty() {
+                    render_frame(&*os_input, &pending_frame, synchronised_output);
+                    pending_frame.clear();
+                }
+                // the client that detached us waits for this, so we only send it once the
+                // terminal is restored
+                if reason == ExitReason::ForceDetached {
+                    acknowledge_force_detach = true;
+                } else {
+                    os_input.send_to_server(ClientToServerMsg::ClientExited);
+                }
 
+                if reason != ExitReason::NormalDetached {
+                    detached_on_lock = false;
+                }
                 if let ExitReason::Error(_) = reason {
                     handle_error(reason.to_string());
                 }
@@ -1115,21 +1459,21 @@ pub fn start_client(
                 handle_error(backtrace);
             },
             ClientInstruction::Render(output) => {
-                let mut stdout = os_input.get_stdout_writer();
-                if let Some(sync) = synchronised_output {
-                    stdout
-                        .write_all(sync.start_seq())
-                        .expect("cannot write to stdout");
+                if legacy_console_rendering {
+                    pending_frame.push_str(&legacy_console::simplify_render_output(&output));
+                } else {
+                    pending_frame.push_str(&output);
                 }
-                stdout
-                    .write_all(output.as_bytes())
-                    .expect("cannot write to stdout");
-                if let Some(sync) = synchronised_output {
-                    stdout
-                        .write_all(sync.end_seq())
-                        .expect("cannot write to stdout");
+                let frame_is_due = frame_interval
+                    .map(|frame_interval| last_frame_at.elapsed() >= frame_interval)
+                    .unwrap_or(true);
+                if frame_is_due {
+                    pending_frame
+                        .push_str(&connection_indicator.render(os_input.get_terminal_size().cols));
+                    render_frame(&*os_input, &pending_frame, synchronised_output);
+                    pending_frame.clear();
+                    last_frame_at = std::time::Instant::now();
                 }
-                stdout.flush().expect("could not flush");
             },
             ClientInstruction::UnblockInputThread => {
                 command_is_executing.unblock_input_thread();
@@ -1150,12 +1494,118 @@ pub fn start_client(
                 break;
             },
             ClientInstruction::SetSynchronizedOutput(enabled) => {
-                synchronised_output = enabled;
+                if !legacy_console_rendering {
+                    synchronised_output = enabled;
+                }
+            },
+            ClientInstruction::KittyKeyboardProtocolSupported => {
+                // on a reconnect the terminal already is in the mode we left it in
+                if negotiate_kitty_keyboard_protocol
+                    && !explicitly_disable_kitty_keyboard_protocol
+                    && !is_a_reconnect
+                {
+                    log::info!("Host terminal supports the kitty keyboard protocol, enabling it");
+                    let mut stdout = os_input.get_stdout_writer();
+                    if win32_input_mode {
+                        // kitty keys carry everything we need, and the terminal would otherwise
+                        // keep preferring win32-input-mode
+                        let _ = stdout
+                            .write(zellij_utils::win32_input::WIN32_INPUT_MODE_DISABLE)
+                            .unwrap();
+                        win32_input_mode = false;
+                    }
+                    let _ = stdout.write(enter_kitty_keyboard_mode.as_bytes()).unwrap();
+                    stdout.flush().unwrap();
+                }
             },
             ClientInstruction::QueryTerminalSize => {
                 os_input.send_to_server(ClientToServerMsg::TerminalResize {
                     new_size: os_input.get_terminal_size(),
                 });
+                if let Some(pixel_dimensions) = os_input.pixel_dimensions() {
+                    os_input.send_to_server(ClientToServerMsg::TerminalPixelDimensions {
+                        pixel_dimensions,
+                    });
+                }
+            },
+            ClientInstruction::Bell(pane_name) => {
+                os_input.notify_bell(bell_notification, &pane_name);
+            },
+            ClientInstruction::Notify(title, body) => {
+                os_input.notify(&title, &body);
+            },
+            ClientInstruction::OpenLink(uri) => {
+                os_input.open_link(&uri);
+            },
+            ClientInstruction::SendHeartbeat if shutdown_pending => {},
+            ClientInstruction::SendHeartbeat => {
+                let now = std::time::Instant::now();
+                let id = connection_quality.heartbeat_sent(now);
+                os_input.send_to_server(ClientToServerMsg::Heartbeat { id });
+                update_connection_indicator(
+                    &*os_input,
+                    &connection_quality,
+                    &mut connection_indicator,
+                    synchronised_output,
+                    now,
+                );
+            },
+            ClientInstruction::ShutdownPending => {
+                log::info!("Session is shutting down, waiting for its panes to close");
+                shutdown_pending = true;
+            },
+            ClientInstruction::DesktopLocked => {
+                log::info!("Desktop locked, detaching from the session");
+                detached_on_lock = true;
+                os_input.send_to_server(ClientToServerMsg::Action {
+                    action: Action::Detach,
+                    terminal_id: None,
+                    client_id: None,
+                    is_cli_client: false,
+                });
+            },
+            ClientInstruction::HeartbeatAck(id) => {
+                let now = std::time::Instant::now();
+                connection_quality.heartbeat_acknowledged(id, now);
+                update_connection_indicator(
+                    &*os_input,
+                    &connection_quality,
+                    &mut connection_indicator,
+                    synchronised_output,
+                    now,
+                );
+            },
+            ClientInstruction::SetClipboard(content) => {
+                let content = copy_line_endings.normalize(&content);
+                if let Err(e) = os_input.set_clipboard(&content) {
+                    match &copy_fallback_command {
+                        Some(copy_fallback_command) => {
+                            log::warn!("Failed to set the clipboard, piping to {copy_fallback_command} instead: {:?}", e);
+                            if let Err(e) = pipe_to_command(copy_fallback_command, content) {
+                                log::error!("Failed to copy with {copy_fallback_command}: {:?}", e);
+                            }
+                        },
+                        None => log::error!("Failed to set the clipboard: {:?}", e),
+                    }
+                }
+            },
+            ClientInstruction::QueryClipboard(terminal_id) => {
+                // reading may wait on the user confirming it, so do not block the main loop
+                let os_input = os_input.clone();
+                thread::Builder::new()
+                    .name("clipboard_query".to_string())
+                    .spawn(move || {
+                        let content = if allow_clipboard_read {
+                            os_input.read_clipboard()
+                        } else {
+                            None
+                        };
+                        os_input.send_to_server(ClientToServerMsg::ClipboardContent {
+                            terminal_id,
+                            content,
+                        });
+                    })
+                    .unwrap();
             },
             ClientInstruction::StartWebServer => {
                 let web_server_base_url = web_server_base_url(
@@ -1186,22 +1636,31 @@ pub fn start_client(
     if reconnect_to_session.is_none() {
         let reset_style = "\u{1b}[m";
         let show_cursor = "\u{1b}[?25h";
-        let restore_snapshot = "\u{1b}[?1049l";
         let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
-        let goodbye_message = format!(
-            "{}\n{}{}{}{}\n",
-            goto_start_of_last_line, restore_snapshot, reset_style, show_cursor, exit_msg
-        );
+        let goodbye_message = format!("{}{}{}\n", reset_style, show_cursor, exit_msg);
 
         os_input.disable_mouse().non_fatal();
         info!("{}", exit_msg);
         os_input.unset_raw_mode().unwrap();
         let mut stdout = os_input.get_stdout_writer();
         let exit_kitty_keyboard_mode = "\u{1b}[<1u";
+        let exit_focus_reporting = "\u{1b}[?1004l";
+        let _ = stdout.write(exit_focus_reporting.as_bytes()).unwrap();
         if !explicitly_disable_kitty_keyboard_protocol {
             let _ = stdout.write(exit_kitty_keyboard_mode.as_bytes()).unwrap();
             stdout.flush().unwrap();
         }
+        if win32_input_mode {
+            let _ = stdout
+                .write(zellij_utils::win32_input::WIN32_INPUT_MODE_DISABLE)
+                .unwrap();
+            stdout.flush().unwrap();
+        }
+        // terminals without an alternate screen keep our last frame, so we say goodbye below it
+        let _ = stdout
+            .write(format!("{}\n", goto_start_of_last_line).as_bytes())
+            .unwrap();
+        leave_alternate_screen(&*os_input);
         let _ = stdout.write(goodbye_message.as_bytes()).unwrap();
         stdout.flush().unwrap();
     } else {
@@ -1210,6 +1669,27 @@ pub fn start_client(
         let _ = stdout.write(clear_screen.as_bytes()).unwrap();
         stdout.flush().unwrap();
     }
+    if acknowledge_force_detach {
+        os_input.send_to_server(ClientToServerMsg::ClientExited);
+    }
+
+    if detached_on_lock {
+        if let Ok(session_name) = envs::get_session_name() {
+            if os_input.confirm_reattach(&session_name) {
+                // we restored the terminal above, but reconnects expect to find it set up
+                set_up_client_terminal(
+                    &*os_input,
+                    !explicitly_disable_kitty_keyboard_protocol
+                        && !negotiate_kitty_keyboard_protocol,
+                    win32_input_parser::win32_input_mode_supported(),
+                );
+                reconnect_to_session = Some(ConnectToSession {
+                    name: Some(session_name),
+                    ..Default::default()
+                });
+            }
+        }
+    }
 
     let _ = send_input_instructions.send(InputInstruction::Exit);
 
@@ -1256,12 +1736,19 @@ pub fn start_server_detached(
                 max_panes: cli_args.max_panes,
                 force_run_layout_commands: force_run_commands,
                 cwd,
+                client_theme: None,
+                session_secret: cli_args.session_secret.clone(),
             };
 
             os_input.update_session_name(name);
             let ipc_pipe = create_ipc_pipe();
 
-            spawn_server(&*ipc_pipe, cli_args.debug).unwrap();
+            spawn_server(
+                &*ipc_pipe,
+                cli_args.debug,
+                config_options.pipe_access.as_ref(),
+            )
+            .unwrap();
             if should_start_web_server {
                 if let Err(e) = spawn_web_server(&cli_args) {
                     log::error!("Failed to start web server: {}", e);
@@ -1311,12 +1798,19 @@ pub fn start_server_detached(
                 max_panes: cli_args.max_panes,
                 force_run_layout_commands: false,
                 cwd: layout_cwd,
+                client_theme: None,
+                session_secret: cli_args.session_secret.clone(),
             };
 
             os_input.update_session_name(name);
             let ipc_pipe = create_ipc_pipe();
 
-            spawn_server(&*ipc_pipe, cli_args.debug).unwrap();
+            spawn_server(
+                &*ipc_pipe,
+                cli_args.debug,
+                config_options.pipe_access.as_ref(),
+            )
+            .unwrap();
             if should_start_web_server {
                 if let Err(e) = spawn_web_server(&cli_args) {
                     log::error!("Failed to start web server: {}", e);
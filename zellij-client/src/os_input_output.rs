@@ -8,7 +8,7 @@ use crate::os_input_output_unix::{AsyncSignalListener, BlockingSignalIterator};
 #[cfg(windows)]
 use crate::os_input_output_windows::{AsyncSignalListener, BlockingSignalIterator};
 
-use interprocess::local_socket::{prelude::*, Stream as LocalSocketStream};
+use interprocess::local_socket::{prelude::*, Name, Stream as LocalSocketStream};
 use std::io::prelude::*;
 use std::io::IsTerminal;
 use std::path::Path;
@@ -17,7 +17,11 @@ use std::{io, thread, time};
 use zellij_utils::{
     data::Palette,
     errors::ErrorContext,
-    ipc::{ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, ServerToClientMsg},
+    input::options::BellNotification,
+    ipc::{
+        ClientToServerMsg, IpcReceiverWithContext, IpcSenderWithContext, PixelDimensions,
+        ServerToClientMsg,
+    },
     shared::default_palette,
 };
 
@@ -60,6 +64,8 @@ impl AsyncStdin for AsyncStdinReader {
 pub enum SignalEvent {
     Resize,
     Quit,
+    /// Ctrl+Break was pressed (Windows only)
+    Break,
 }
 
 /// Trait for async signal listening, allowing for testable implementations
@@ -86,6 +92,7 @@ pub struct ClientOsInputOutput {
     receive_instructions_from_server: Arc<Mutex<Option<IpcReceiverWithContext<ServerToClientMsg>>>>,
     reading_from_stdin: Arc<Mutex<Option<Vec<u8>>>>,
     session_name: Arc<Mutex<Option<String>>>,
+    install_signal_handlers: bool,
 }
 
 impl std::fmt::Debug for ClientOsInputOutput {
@@ -125,9 +132,22 @@ pub trait ClientOsApi: Send + Sync + std::fmt::Debug {
     /// Receives a message on client-side IPC channel
     // This should be called from the client-side router thread only.
     fn recv_from_server(&self) -> Option<(ServerToClientMsg, ErrorContext)>;
-    fn handle_signals(&self, sigwinch_cb: Box<dyn Fn()>, quit_cb: Box<dyn Fn()>);
+    fn handle_signals(
+        &self,
+        sigwinch_cb: Box<dyn Fn()>,
+        quit_cb: Box<dyn Fn()>,
+        break_cb: Box<dyn Fn()>,
+    );
     /// Establish a connection with the server socket.
     fn connect_to_server(&self, path: &Path);
+    /// Whether the connection to the server was lost (as opposed to being closed by a message).
+    fn server_disconnected(&self) -> bool {
+        false
+    }
+    /// Re-establish a lost connection with the server socket, giving up after `timeout`.
+    fn reconnect_to_server(&self, _path: &Path, _timeout: time::Duration) -> bool {
+        false
+    }
     fn load_palette(&self) -> Palette;
     fn enable_mouse(&self) -> Result<()>;
     fn disable_mouse(&self) -> Result<()>;
@@ -140,7 +160,102 @@ pub trait ClientOsApi: Send + Sync + std::fmt::Debug {
     }
     /// Returns an async signal listener that can be polled in tokio::select
     fn get_async_signal_listener(&self) -> io::Result<Box<dyn AsyncSignals>> {
-        Ok(Box::new(AsyncSignalListener::new()?))
+        Ok(Box::new(AsyncSignalListener::new(true)?))
+    }
+    /// Write a whole rendered `frame` to stdout at once, so that the terminal does not get to
+    /// draw it half-written
+    fn write_frame(&self, frame: &[u8]) -> io::Result<()> {
+        let mut stdout = self.get_stdout_writer();
+        stdout.write_all(frame)?;
+        stdout.flush()
+    }
+    /// Notify the user about a bell rung in `pane_name`, the terminal emulator is left to
+    /// handle the bell itself if this is not supported on this platform
+    fn notify_bell(&self, _bell_notification: BellNotification, _pane_name: &str) {}
+    /// Show a desktop notification, if this is supported on this platform
+    fn notify(&self, _title: &str, _body: &str) {}
+    /// Open the hyperlink the user ctrl-clicked with the application registered for it
+    fn open_link(&self, _uri: &str) {}
+    /// Call `on_lock` whenever the desktop session we run in is locked, a no-op on platforms
+    /// where we cannot tell
+    fn watch_desktop_lock(&self, _on_lock: Box<dyn Fn() + Send>) {}
+    /// Ask the user whether to attach to `session_name` again after we detached from it because
+    /// their desktop was locked
+    fn confirm_reattach(&self, _session_name: &str) -> bool {
+        false
+    }
+    /// Ask the user whether to go through with a paste we are wary of because of `reason`,
+    /// pastes are always let through if this is not supported on this platform
+    fn confirm_paste(&self, _reason: &str) -> bool {
+        true
+    }
+    /// Put `content` on the system clipboard ourselves, for platforms whose terminals do not
+    /// handle OSC 52 - a no-op elsewhere
+    fn set_clipboard(&self, _content: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Read the system clipboard on behalf of a pane if the user allows it, `None` if they
+    /// declined or this is not supported on this platform
+    fn read_clipboard(&self) -> Option<String> {
+        None
+    }
+    /// The pixel dimensions of the terminal as far as the platform can tell without asking the
+    /// terminal itself, `None` if it cannot
+    fn pixel_dimensions(&self) -> Option<PixelDimensions> {
+        None
+    }
+    /// Whether the terminal is the classic Windows console (conhost), which needs the render
+    /// output simplified
+    fn is_classic_console(&self) -> bool {
+        false
+    }
+    /// Remember where the console is scrolled to and where its cursor is, where the terminal
+    /// does not do this for us when switching to the alternate screen
+    fn save_screen_position(&self) {}
+    /// Scroll the console and move its cursor back to where `save_screen_position` found them
+    fn restore_screen_position(&self) {}
+    /// Remember the title of the terminal window, where the terminal cannot do this for us
+    /// with its title stack
+    fn save_window_title(&self) {}
+    /// Give the terminal window back the title `save_window_title` found
+    fn restore_window_title(&self) {}
+}
+
+impl ClientOsInputOutput {
+    /// Connects to the server at `path`, retrying until it accepts the connection or, if one
+    /// is given, until `deadline` passes. Returns whether a connection was made.
+    fn connect_until(&self, path: &Path, deadline: Option<time::Instant>) -> bool {
+        let fs_name = zellij_utils::ipc::path_to_ipc_name(path)
+            .expect("failed to convert path to socket name");
+        let socket = match connect_with_retry(fs_name, deadline) {
+            Some(socket) => socket,
+            None => return false,
+        };
+        #[cfg(not(windows))]
+        {
+            let sender = IpcSenderWithContext::new(socket);
+            let receiver = sender.get_receiver();
+            *self.send_instructions_to_server.lock().unwrap() = Some(sender);
+            *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
+        }
+        #[cfg(windows)]
+        {
+            // On Windows, use a separate pipe for server→client to avoid deadlock
+            // from concurrent read/write on DuplicateHandle'd pipe handles.
+            let reverse_name = zellij_utils::ipc::path_to_ipc_name_reverse(path)
+                .expect("failed to convert path to reverse socket name");
+            let reverse_socket = match connect_with_retry(reverse_name, deadline) {
+                Some(socket) => socket,
+                None => return false,
+            };
+            // Main pipe: client→server only (no cloning)
+            let sender = IpcSenderWithContext::new(socket);
+            // Reverse pipe: server→client only (no cloning)
+            let receiver = IpcReceiverWithContext::new(reverse_socket);
+            *self.send_instructions_to_server.lock().unwrap() = Some(sender);
+            *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
+        }
+        true
     }
 }
 
@@ -149,6 +264,8 @@ impl ClientOsApi for ClientOsInputOutput {
         get_terminal_size()
     }
     fn set_raw_mode(&mut self) {
+        #[cfg(windows)]
+        crate::os_input_output_windows::save_console_state();
         crossterm::terminal::enable_raw_mode().expect("could not enable raw mode");
         // On Windows, crossterm's enable_raw_mode() does not set
         // ENABLE_VIRTUAL_TERMINAL_INPUT on stdin. Without this flag,
@@ -172,14 +289,17 @@ impl ClientOsApi for ClientOsInputOutput {
         }
     }
     fn unset_raw_mode(&self) -> Result<(), std::io::Error> {
-        crossterm::terminal::disable_raw_mode()?;
+        let disabled_raw_mode = crossterm::terminal::disable_raw_mode();
         // On Windows, crossterm's disable_raw_mode() only adds back the
         // LINE_INPUT|ECHO_INPUT|PROCESSED_INPUT flags — it does NOT clear
-        // ENABLE_VIRTUAL_TERMINAL_INPUT that we set in set_raw_mode().
-        // If left enabled, the parent shell receives ANSI escape sequences
-        // (e.g. ^[[A for arrow keys) instead of native virtual key events.
+        // ENABLE_VIRTUAL_TERMINAL_INPUT that we set in set_raw_mode(), nor
+        // restore the output mode or the codepages. So we put back everything
+        // exactly as we found it, even if crossterm failed. Clearing
+        // ENABLE_VIRTUAL_TERMINAL_INPUT alone is the fallback if nothing was
+        // saved: if left enabled, the parent shell receives ANSI escape
+        // sequences (e.g. ^[[A for arrow keys) instead of native key events.
         #[cfg(windows)]
-        {
+        if !crate::os_input_output_windows::restore_console_state() {
             use std::os::windows::io::AsRawHandle;
             use windows_sys::Win32::System::Console::{
                 GetConsoleMode, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_INPUT,
@@ -195,7 +315,7 @@ impl ClientOsApi for ClientOsInputOutput {
                 }
             }
         }
-        Ok(())
+        disabled_raw_mode
     }
     fn box_clone(&self) -> Box<dyn ClientOsApi> {
         Box::new((*self).clone())
@@ -223,15 +343,30 @@ impl ClientOsApi for ClientOsInputOutput {
             None => {
                 let stdin = std::io::stdin();
                 let mut stdin = stdin.lock();
-                let buffer = match stdin.fill_buf() {
-                    Ok(buf) => buf,
-                    Err(_e) => {
+                // the console input records carry characters that ReadConsoleW (which std reads
+                // the console with) drops
+                #[cfg(windows)]
+                let console_input = crate::os_input_output_windows::read_console_input();
+                #[cfg(not(windows))]
+                let console_input: Option<io::Result<Vec<u8>>> = None;
+                let read_bytes = match console_input {
+                    Some(Ok(read_bytes)) => read_bytes,
+                    Some(Err(_e)) => {
                         return Err("Failed to read from STDIN");
                     },
+                    None => {
+                        let buffer = match stdin.fill_buf() {
+                            Ok(buf) => buf,
+                            Err(_e) => {
+                                return Err("Failed to read from STDIN");
+                            },
+                        };
+                        let length = buffer.len();
+                        let read_bytes = Vec::from(buffer);
+                        stdin.consume(length);
+                        read_bytes
+                    },
                 };
-                let length = buffer.len();
-                let read_bytes = Vec::from(buffer);
-                stdin.consume(length);
 
                 let session_name_after_reading_from_stdin =
                     { self.session_name.lock().unwrap().clone() };
@@ -284,9 +419,14 @@ impl ClientOsApi for ClientOsInputOutput {
             .unwrap()
             .recv_server_msg()
     }
-    fn handle_signals(&self, sigwinch_cb: Box<dyn Fn()>, quit_cb: Box<dyn Fn()>) {
+    fn handle_signals(
+        &self,
+        sigwinch_cb: Box<dyn Fn()>,
+        quit_cb: Box<dyn Fn()>,
+        break_cb: Box<dyn Fn()>,
+    ) {
         let mut sigwinch_cb_timestamp = time::Instant::now();
-        let signals = BlockingSignalIterator::new().unwrap();
+        let signals = BlockingSignalIterator::new(self.install_signal_handlers).unwrap();
         for event in signals {
             match event {
                 SignalEvent::Resize => {
@@ -301,56 +441,29 @@ impl ClientOsApi for ClientOsInputOutput {
                     quit_cb();
                     break;
                 },
+                SignalEvent::Break => {
+                    break_cb();
+                },
             }
         }
     }
+    fn get_async_signal_listener(&self) -> io::Result<Box<dyn AsyncSignals>> {
+        Ok(Box::new(AsyncSignalListener::new(
+            self.install_signal_handlers,
+        )?))
+    }
     fn connect_to_server(&self, path: &Path) {
-        let fs_name = zellij_utils::ipc::path_to_ipc_name(path)
-            .expect("failed to convert path to socket name");
-        let socket;
-        loop {
-            match LocalSocketStream::connect(fs_name.clone()) {
-                Ok(sock) => {
-                    socket = sock;
-                    break;
-                },
-                Err(_) => {
-                    std::thread::sleep(std::time::Duration::from_millis(50));
-                },
-            }
-        }
-        #[cfg(not(windows))]
-        {
-            let sender = IpcSenderWithContext::new(socket);
-            let receiver = sender.get_receiver();
-            *self.send_instructions_to_server.lock().unwrap() = Some(sender);
-            *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
-        }
-        #[cfg(windows)]
-        {
-            // On Windows, use a separate pipe for server→client to avoid deadlock
-            // from concurrent read/write on DuplicateHandle'd pipe handles.
-            let reverse_name = zellij_utils::ipc::path_to_ipc_name_reverse(path)
-                .expect("failed to convert path to reverse socket name");
-            let reverse_socket;
-            loop {
-                match LocalSocketStream::connect(reverse_name.clone()) {
-                    Ok(sock) => {
-                        reverse_socket = sock;
-                        break;
-                    },
-                    Err(_) => {
-                        std::thread::sleep(std::time::Duration::from_millis(50));
-                    },
-                }
-            }
-            // Main pipe: client→server only (no cloning)
-            let sender = IpcSenderWithContext::new(socket);
-            // Reverse pipe: server→client only (no cloning)
-            let receiver = IpcReceiverWithContext::new(reverse_socket);
-            *self.send_instructions_to_server.lock().unwrap() = Some(sender);
-            *self.receive_instructions_from_server.lock().unwrap() = Some(receiver);
-        }
+        self.connect_until(path, None);
+    }
+    fn server_disconnected(&self) -> bool {
+        self.receive_instructions_from_server
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|receiver| receiver.is_disconnected())
+    }
+    fn reconnect_to_server(&self, path: &Path, timeout: time::Duration) -> bool {
+        self.connect_until(path, Some(time::Instant::now() + timeout))
     }
     fn load_palette(&self) -> Palette {
         // this was removed because termbg doesn't release stdin in certain scenarios (we know of
@@ -386,6 +499,66 @@ impl ClientOsApi for ClientOsInputOutput {
         Ok(())
     }
 
+    #[cfg(windows)]
+    fn write_frame(&self, frame: &[u8]) -> io::Result<()> {
+        crate::os_input_output_windows::write_frame(frame)
+    }
+    #[cfg(windows)]
+    fn notify_bell(&self, bell_notification: BellNotification, pane_name: &str) {
+        crate::os_input_output_windows::notify_bell(bell_notification, pane_name);
+    }
+    #[cfg(windows)]
+    fn notify(&self, title: &str, body: &str) {
+        crate::os_input_output_windows::show_toast(title, body);
+    }
+    #[cfg(windows)]
+    fn open_link(&self, uri: &str) {
+        crate::os_input_output_windows::open_link(uri);
+    }
+    #[cfg(windows)]
+    fn watch_desktop_lock(&self, on_lock: Box<dyn Fn() + Send>) {
+        crate::os_input_output_windows::watch_desktop_lock(on_lock);
+    }
+    #[cfg(windows)]
+    fn confirm_reattach(&self, session_name: &str) -> bool {
+        crate::os_input_output_windows::confirm_reattach(session_name)
+    }
+    #[cfg(windows)]
+    fn confirm_paste(&self, reason: &str) -> bool {
+        crate::os_input_output_windows::confirm_paste(reason)
+    }
+    #[cfg(windows)]
+    fn set_clipboard(&self, content: &str) -> Result<()> {
+        crate::os_input_output_windows::set_clipboard(content)
+    }
+    #[cfg(windows)]
+    fn read_clipboard(&self) -> Option<String> {
+        crate::os_input_output_windows::read_clipboard()
+    }
+    #[cfg(windows)]
+    fn pixel_dimensions(&self) -> Option<PixelDimensions> {
+        crate::os_input_output_windows::pixel_dimensions(self.get_terminal_size())
+    }
+    #[cfg(windows)]
+    fn is_classic_console(&self) -> bool {
+        crate::os_input_output_windows::is_classic_console()
+    }
+    #[cfg(windows)]
+    fn save_screen_position(&self) {
+        crate::os_input_output_windows::save_screen_position();
+    }
+    #[cfg(windows)]
+    fn restore_screen_position(&self) {
+        crate::os_input_output_windows::restore_screen_position();
+    }
+    #[cfg(windows)]
+    fn save_window_title(&self) {
+        crate::os_input_output_windows::save_window_title();
+    }
+    #[cfg(windows)]
+    fn restore_window_title(&self) {
+        crate::os_input_output_windows::restore_window_title();
+    }
     fn env_variable(&self, name: &str) -> Option<String> {
         std::env::var(name).ok()
     }
@@ -397,6 +570,23 @@ impl Clone for Box<dyn ClientOsApi> {
     }
 }
 
+fn connect_with_retry(
+    name: Name<'_>,
+    deadline: Option<time::Instant>,
+) -> Option<LocalSocketStream> {
+    loop {
+        match LocalSocketStream::connect(name.clone()) {
+            Ok(sock) => return Some(sock),
+            Err(_) => {
+                if deadline.is_some_and(|deadline| time::Instant::now() >= deadline) {
+                    return None;
+                }
+                thread::sleep(time::Duration::from_millis(50));
+            },
+        }
+    }
+}
+
 pub fn get_client_os_input() -> Result<ClientOsInputOutput, std::io::Error> {
     let reading_from_stdin = Arc::new(Mutex::new(None));
     Ok(ClientOsInputOutput {
@@ -404,9 +594,19 @@ pub fn get_client_os_input() -> Result<ClientOsInputOutput, std::io::Error> {
         receive_instructions_from_server: Arc::new(Mutex::new(None)),
         reading_from_stdin,
         session_name: Arc::new(Mutex::new(None)),
+        install_signal_handlers: true,
     })
 }
 
+/// Like `get_client_os_input`, for a client embedded in another application: it installs no
+/// console control or signal handlers of its own (but for SIGWINCH), so those of the embedding
+/// application keep working and Ctrl-Break or SIGINT do not end its zellij session.
+pub fn get_embedded_client_os_input() -> Result<ClientOsInputOutput, std::io::Error> {
+    let mut os_input = get_client_os_input()?;
+    os_input.install_signal_handlers = false;
+    Ok(os_input)
+}
+
 pub fn get_cli_client_os_input() -> Result<ClientOsInputOutput, std::io::Error> {
     let reading_from_stdin = Arc::new(Mutex::new(None));
     Ok(ClientOsInputOutput {
@@ -414,9 +614,31 @@ pub fn get_cli_client_os_input() -> Result<ClientOsInputOutput, std::io::Error>
         receive_instructions_from_server: Arc::new(Mutex::new(None)),
         reading_from_stdin,
         session_name: Arc::new(Mutex::new(None)),
+        install_signal_handlers: true,
     })
 }
 
+/// Spawn `command` (a program followed by its space separated arguments) and write `text` to
+/// its stdin, without waiting for it to finish
+pub(crate) fn pipe_to_command(command: &str, text: String) -> Result<()> {
+    let mut command_with_args = command.split(' ').filter(|arg| !arg.is_empty());
+    let program = command_with_args.next().context("missing command")?;
+    let mut process = std::process::Command::new(program)
+        .args(command_with_args)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("couldn't spawn {}", program))?;
+    let mut stdin = process.stdin.take().context("could not get stdin")?;
+    thread::spawn(move || {
+        if let Err(e) = stdin.write_all(text.as_bytes()) {
+            log::error!("Failed to write to the stdin of the copy command: {}", e);
+        }
+        drop(stdin);
+        let _ = process.wait();
+    });
+    Ok(())
+}
+
 pub const DEFAULT_STDIN_POLL_TIMEOUT_MS: u64 = 10;
 
 #[cfg(test)]
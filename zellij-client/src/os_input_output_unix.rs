@@ -10,33 +10,49 @@ use std::io;
 /// Async signal listener that maps Unix signals to `SignalEvent` variants.
 pub(crate) struct AsyncSignalListener {
     sigwinch: tokio::signal::unix::Signal,
-    sigterm: tokio::signal::unix::Signal,
-    sigint: tokio::signal::unix::Signal,
-    sigquit: tokio::signal::unix::Signal,
-    sighup: tokio::signal::unix::Signal,
+    sigterm: Option<tokio::signal::unix::Signal>,
+    sigint: Option<tokio::signal::unix::Signal>,
+    sigquit: Option<tokio::signal::unix::Signal>,
+    sighup: Option<tokio::signal::unix::Signal>,
 }
 
 impl AsyncSignalListener {
-    pub fn new() -> io::Result<Self> {
+    /// Without `quit_signals`, only SIGWINCH is listened to and the signals that end a process
+    /// keep whatever disposition the embedding application gave them.
+    pub fn new(quit_signals: bool) -> io::Result<Self> {
+        let quit_signal = |kind: SignalKind| -> io::Result<Option<tokio::signal::unix::Signal>> {
+            if quit_signals {
+                signal(kind).map(Some)
+            } else {
+                Ok(None)
+            }
+        };
         Ok(Self {
             sigwinch: signal(SignalKind::window_change())?,
-            sigterm: signal(SignalKind::terminate())?,
-            sigint: signal(SignalKind::interrupt())?,
-            sigquit: signal(SignalKind::quit())?,
-            sighup: signal(SignalKind::hangup())?,
+            sigterm: quit_signal(SignalKind::terminate())?,
+            sigint: quit_signal(SignalKind::interrupt())?,
+            sigquit: quit_signal(SignalKind::quit())?,
+            sighup: quit_signal(SignalKind::hangup())?,
         })
     }
 }
 
+async fn recv_quit_signal(signal: &mut Option<tokio::signal::unix::Signal>) -> Option<()> {
+    match signal {
+        Some(signal) => signal.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 #[async_trait]
 impl crate::os_input_output::AsyncSignals for AsyncSignalListener {
     async fn recv(&mut self) -> Option<SignalEvent> {
         tokio::select! {
             result = self.sigwinch.recv() => result.map(|_| SignalEvent::Resize),
-            result = self.sigterm.recv() => result.map(|_| SignalEvent::Quit),
-            result = self.sigint.recv() => result.map(|_| SignalEvent::Quit),
-            result = self.sigquit.recv() => result.map(|_| SignalEvent::Quit),
-            result = self.sighup.recv() => result.map(|_| SignalEvent::Quit),
+            result = recv_quit_signal(&mut self.sigterm) => result.map(|_| SignalEvent::Quit),
+            result = recv_quit_signal(&mut self.sigint) => result.map(|_| SignalEvent::Quit),
+            result = recv_quit_signal(&mut self.sigquit) => result.map(|_| SignalEvent::Quit),
+            result = recv_quit_signal(&mut self.sighup) => result.map(|_| SignalEvent::Quit),
         }
     }
 }
@@ -48,8 +64,13 @@ pub(crate) struct BlockingSignalIterator {
 }
 
 impl BlockingSignalIterator {
-    pub fn new() -> io::Result<Self> {
-        let signals = Signals::new([SIGWINCH, SIGTERM, SIGINT, SIGQUIT, SIGHUP])?;
+    /// See `AsyncSignalListener::new` for `quit_signals`.
+    pub fn new(quit_signals: bool) -> io::Result<Self> {
+        let signals = if quit_signals {
+            Signals::new([SIGWINCH, SIGTERM, SIGINT, SIGQUIT, SIGHUP])?
+        } else {
+            Signals::new([SIGWINCH])?
+        };
         Ok(Self { signals })
     }
 }
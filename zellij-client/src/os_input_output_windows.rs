@@ -1,28 +1,58 @@
 use crate::os_input_output::SignalEvent;
+use crate::win32_input_parser::{KeyEventComposer, Win32KeyEvent};
 
+use anyhow::Context;
 use async_trait::async_trait;
 
 use std::io;
 use std::sync::mpsc as std_mpsc;
+use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
+use zellij_utils::input::options::BellNotification;
+use zellij_utils::ipc::PixelDimensions;
+use zellij_utils::pane_size::{Size, SizeInPixels};
+
+/// The application user model id our toasts are shown under.
+///
+/// Windows only shows toasts of applications with an id registered through a start menu
+/// shortcut, which an unpackaged Zellij does not have, so we borrow the one of PowerShell.
+const TOAST_APP_ID: &str =
+    "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+/// The state of the console before we first switched it to raw mode.
+static SAVED_CONSOLE_STATE: Mutex<Option<ConsoleState>> = Mutex::new(None);
+static SAVED_SCREEN_POSITION: Mutex<Option<ScreenPosition>> = Mutex::new(None);
+/// The title of the console window before the server first retitled it, nul terminated.
+static SAVED_WINDOW_TITLE: Mutex<Option<Vec<u16>>> = Mutex::new(None);
+/// Carries half read surrogate pairs over from one read of the console input to the next.
+static KEY_EVENT_COMPOSER: Mutex<KeyEventComposer> = Mutex::new(KeyEventComposer::new());
+/// Called by the window of `watch_desktop_lock` when the desktop is locked.
+static DESKTOP_LOCK_CALLBACK: Mutex<Option<Box<dyn Fn() + Send>>> = Mutex::new(None);
+static DESKTOP_LOCK_WATCHER: std::sync::Once = std::sync::Once::new();
+
 /// Async signal listener for Windows.
 ///
 /// Uses `tokio::signal::windows` for Ctrl-Break, and polls
 /// `crossterm::terminal::size()` for resize detection.
 ///
 /// Ctrl-C is NOT handled here — with ENABLE_PROCESSED_INPUT disabled
-/// (raw console mode), byte 0x03 is delivered directly through ReadFile
-/// to the stdin reader, which forwards it to the active terminal pane.
+/// (raw console mode), it is read by `ReadConsoleInputW` as a key event for
+/// character 0x03 and the stdin reader forwards it to the active terminal pane.
 pub(crate) struct AsyncSignalListener {
-    ctrl_break: tokio::signal::windows::CtrlBreak,
+    ctrl_break: Option<tokio::signal::windows::CtrlBreak>,
     resize_rx: tokio::sync::mpsc::Receiver<()>,
 }
 
 impl AsyncSignalListener {
-    pub fn new() -> io::Result<Self> {
-        let ctrl_break = tokio::signal::windows::ctrl_break()?;
+    /// Without `ctrl_handler`, no console control handler is installed and Ctrl-Break is left
+    /// to whatever handler the embedding application installed.
+    pub fn new(ctrl_handler: bool) -> io::Result<Self> {
+        let ctrl_break = if ctrl_handler {
+            Some(tokio::signal::windows::ctrl_break()?)
+        } else {
+            None
+        };
 
         let (resize_tx, resize_rx) = tokio::sync::mpsc::channel(16);
 
@@ -57,25 +87,33 @@ impl AsyncSignalListener {
 impl crate::os_input_output::AsyncSignals for AsyncSignalListener {
     async fn recv(&mut self) -> Option<SignalEvent> {
         tokio::select! {
-            result = self.ctrl_break.recv() => result.map(|_| SignalEvent::Quit),
+            result = recv_ctrl_break(&mut self.ctrl_break) => result.map(|_| SignalEvent::Break),
             result = self.resize_rx.recv() => result.map(|_| SignalEvent::Resize),
         }
     }
 }
 
+async fn recv_ctrl_break(ctrl_break: &mut Option<tokio::signal::windows::CtrlBreak>) -> Option<()> {
+    match ctrl_break {
+        Some(ctrl_break) => ctrl_break.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Blocking signal iterator for Windows.
 ///
 /// Spawns a thread that uses `SetConsoleCtrlHandler` for Ctrl-Break
-/// (quit signal), and polls `crossterm::terminal::size()` for resize events.
+/// (break signal), and polls `crossterm::terminal::size()` for resize events.
 ///
-/// Ctrl-C is NOT intercepted — it flows through ReadFile as byte 0x03
-/// when ENABLE_PROCESSED_INPUT is disabled (raw console mode).
+/// Ctrl-C is NOT intercepted — it is read by `ReadConsoleInputW` as a key event
+/// for character 0x03 when ENABLE_PROCESSED_INPUT is disabled (raw console mode).
 pub(crate) struct BlockingSignalIterator {
     rx: std_mpsc::Receiver<SignalEvent>,
 }
 
 impl BlockingSignalIterator {
-    pub fn new() -> io::Result<Self> {
+    /// See `AsyncSignalListener::new` for `ctrl_handler`.
+    pub fn new(ctrl_handler: bool) -> io::Result<Self> {
         let (tx, rx) = std_mpsc::channel();
 
         // Thread for resize polling
@@ -99,8 +137,12 @@ impl BlockingSignalIterator {
             })
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
 
-        // Thread for Ctrl-Break handling (quit signal)
-        let quit_tx = tx;
+        if !ctrl_handler {
+            return Ok(Self { rx });
+        }
+
+        // Thread for Ctrl-Break handling (break signal)
+        let break_tx = tx;
         thread::Builder::new()
             .name("blocking_ctrl_handler".to_string())
             .spawn(move || {
@@ -108,18 +150,18 @@ impl BlockingSignalIterator {
                     SetConsoleCtrlHandler, CTRL_BREAK_EVENT,
                 };
 
-                static QUIT_FLAG: std::sync::atomic::AtomicBool =
+                static BREAK_FLAG: std::sync::atomic::AtomicBool =
                     std::sync::atomic::AtomicBool::new(false);
 
                 unsafe extern "system" fn handler(ctrl_type: u32) -> i32 {
                     match ctrl_type {
                         CTRL_BREAK_EVENT => {
-                            QUIT_FLAG.store(true, std::sync::atomic::Ordering::SeqCst);
+                            BREAK_FLAG.store(true, std::sync::atomic::Ordering::SeqCst);
                             1 // handled
                         },
                         // Prevent default termination for CTRL_C_EVENT but don't
-                        // intercept it — with ENABLE_PROCESSED_INPUT disabled,
-                        // byte 0x03 flows through ReadFile directly.
+                        // intercept it — with ENABLE_PROCESSED_INPUT disabled, Ctrl-C
+                        // arrives as a key event carrying 0x03 in `read_console_input`.
                         _ => 1,
                     }
                 }
@@ -130,9 +172,9 @@ impl BlockingSignalIterator {
 
                 loop {
                     thread::sleep(Duration::from_millis(50));
-                    if QUIT_FLAG.load(std::sync::atomic::Ordering::SeqCst) {
-                        QUIT_FLAG.store(false, std::sync::atomic::Ordering::SeqCst);
-                        if quit_tx.send(SignalEvent::Quit).is_err() {
+                    if BREAK_FLAG.load(std::sync::atomic::Ordering::SeqCst) {
+                        BREAK_FLAG.store(false, std::sync::atomic::Ordering::SeqCst);
+                        if break_tx.send(SignalEvent::Break).is_err() {
                             break;
                         }
                     }
@@ -152,6 +194,762 @@ impl Iterator for BlockingSignalIterator {
     }
 }
 
+/// The input and output modes and the codepages of the console we are attached to, `None`
+/// (or 0 for the codepages) where they could not be queried, eg. because stdout is redirected.
+#[derive(Debug, Clone, Copy)]
+struct ConsoleState {
+    input_mode: Option<u32>,
+    output_mode: Option<u32>,
+    input_codepage: u32,
+    output_codepage: u32,
+}
+
+impl ConsoleState {
+    fn capture() -> Self {
+        use windows_sys::Win32::System::Console::{GetConsoleCP, GetConsoleOutputCP};
+        unsafe {
+            ConsoleState {
+                input_mode: console_mode(stdin_handle()),
+                output_mode: console_mode(stdout_handle()),
+                input_codepage: GetConsoleCP(),
+                output_codepage: GetConsoleOutputCP(),
+            }
+        }
+    }
+    fn restore(&self) {
+        use windows_sys::Win32::System::Console::{
+            SetConsoleCP, SetConsoleMode, SetConsoleOutputCP,
+        };
+        unsafe {
+            if let Some(input_mode) = self.input_mode {
+                if SetConsoleMode(stdin_handle(), input_mode) == 0 {
+                    log::error!("Failed to restore the console input mode");
+                }
+            }
+            if let Some(output_mode) = self.output_mode {
+                if SetConsoleMode(stdout_handle(), output_mode) == 0 {
+                    log::error!("Failed to restore the console output mode");
+                }
+            }
+            if self.input_codepage != 0 {
+                SetConsoleCP(self.input_codepage);
+            }
+            if self.output_codepage != 0 {
+                SetConsoleOutputCP(self.output_codepage);
+            }
+        }
+    }
+}
+
+fn stdin_handle() -> windows_sys::Win32::Foundation::HANDLE {
+    use std::os::windows::io::AsRawHandle;
+    io::stdin().as_raw_handle() as windows_sys::Win32::Foundation::HANDLE
+}
+
+fn stdout_handle() -> windows_sys::Win32::Foundation::HANDLE {
+    use std::os::windows::io::AsRawHandle;
+    io::stdout().as_raw_handle() as windows_sys::Win32::Foundation::HANDLE
+}
+
+fn console_mode(handle: windows_sys::Win32::Foundation::HANDLE) -> Option<u32> {
+    use windows_sys::Win32::System::Console::GetConsoleMode;
+    let mut mode: u32 = 0;
+    if unsafe { GetConsoleMode(handle, &mut mode) } != 0 {
+        Some(mode)
+    } else {
+        None
+    }
+}
+
+/// Remember the console modes and codepages as they are before we change them. Only the first
+/// call counts, so that reattaching (eg. when switching sessions) does not save our own raw mode.
+pub(crate) fn save_console_state() {
+    let mut saved_console_state = SAVED_CONSOLE_STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if saved_console_state.is_none() {
+        *saved_console_state = Some(ConsoleState::capture());
+    }
+}
+
+/// Put the console modes and codepages back the way `save_console_state` found them. This
+/// is called on every exit path including the panic hook, so it must not panic itself.
+/// Returns false if nothing was saved.
+pub(crate) fn restore_console_state() -> bool {
+    let saved_console_state = SAVED_CONSOLE_STATE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match *saved_console_state {
+        Some(console_state) => {
+            console_state.restore();
+            true
+        },
+        None => false,
+    }
+}
+
+/// The part of its buffer a console window shows and the position of its cursor.
+#[derive(Clone, Copy)]
+struct ScreenPosition {
+    window: windows_sys::Win32::System::Console::SMALL_RECT,
+    cursor: windows_sys::Win32::System::Console::COORD,
+}
+
+/// Remember which part of its scrollback the console shows and where its cursor is, before we
+/// switch to the alternate screen. Only the first call counts, for the same reason as with
+/// `save_console_state`. Conhost does not scroll back to where the user was when leaving the
+/// alternate screen, and a ConPTY based terminal only sees a pseudo console as large as its
+/// viewport, so there this is harmless.
+pub(crate) fn save_screen_position() {
+    use windows_sys::Win32::System::Console::{
+        GetConsoleScreenBufferInfo, CONSOLE_SCREEN_BUFFER_INFO,
+    };
+    let mut saved_screen_position = SAVED_SCREEN_POSITION
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if saved_screen_position.is_some() {
+        return;
+    }
+    let mut buffer_info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+    if unsafe { GetConsoleScreenBufferInfo(stdout_handle(), &mut buffer_info) } != 0 {
+        *saved_screen_position = Some(ScreenPosition {
+            window: buffer_info.srWindow,
+            cursor: buffer_info.dwCursorPosition,
+        });
+    }
+}
+
+/// Scroll the console back to where `save_screen_position` found it and put its cursor there,
+/// once we left the alternate screen. Like `restore_console_state`, this must not panic.
+pub(crate) fn restore_screen_position() {
+    use windows_sys::Win32::System::Console::{SetConsoleCursorPosition, SetConsoleWindowInfo};
+    let saved_screen_position = SAVED_SCREEN_POSITION
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(screen_position) = *saved_screen_position {
+        unsafe {
+            if SetConsoleWindowInfo(stdout_handle(), 1, &screen_position.window) == 0 {
+                log::warn!("Failed to restore the console window position");
+            }
+            if SetConsoleCursorPosition(stdout_handle(), screen_position.cursor) == 0 {
+                log::warn!("Failed to restore the console cursor position");
+            }
+        }
+    }
+}
+
+/// Remember the title of the console window, before the server retitles it `session: tab`.
+/// Only the first call counts, for the same reason as with `save_console_state`.
+pub(crate) fn save_window_title() {
+    use windows_sys::Win32::System::Console::GetConsoleTitleW;
+    let mut saved_window_title = SAVED_WINDOW_TITLE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if saved_window_title.is_some() {
+        return;
+    }
+    // console titles are limited to 64K, including the terminating nul
+    let mut title = vec![0u16; u16::MAX as usize];
+    let length = unsafe { GetConsoleTitleW(title.as_mut_ptr(), title.len() as u32) };
+    if length > 0 {
+        title.truncate(length as usize);
+        title.push(0);
+        *saved_window_title = Some(title);
+    }
+}
+
+/// Give the console window back the title `save_window_title` found, and clear the progress
+/// the panes reported on the taskbar button of Windows Terminal (OSC 9;4), once we detached.
+/// Like `restore_console_state`, this must not panic.
+pub(crate) fn restore_window_title() {
+    use std::io::Write;
+    use windows_sys::Win32::System::Console::SetConsoleTitleW;
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x1b]9;4;0;0\x1b\\");
+    let _ = stdout.flush();
+    let saved_window_title = SAVED_WINDOW_TITLE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(title) = saved_window_title.as_ref() {
+        if unsafe { SetConsoleTitleW(title.as_ptr()) } == 0 {
+            log::warn!("Failed to restore the console window title");
+        }
+    }
+}
+
+/// Read the text typed into the console from its input records, or `None` if stdin is not a
+/// console.
+///
+/// This is what `ReadConsoleW` does for us too, except that it drops characters entered with
+/// Alt+Numpad, which arrive with the release of Alt. It blocks until some text is typed.
+///
+/// Control characters come in-band with the other key events: raw mode clears
+/// ENABLE_PROCESSED_INPUT, so Ctrl-C is a key event carrying 0x03 rather than a CTRL_C_EVENT
+/// interrupting the read. Console handles cannot be read overlapped, but they need not be.
+///
+/// Focus records are spelled out as the focus reports (mode 1004) terminals send, for the
+/// consoles that cannot send those.
+pub(crate) fn read_console_input() -> Option<io::Result<Vec<u8>>> {
+    use windows_sys::Win32::System::Console::{
+        ReadConsoleInputW, FOCUS_EVENT, INPUT_RECORD, KEY_EVENT,
+    };
+
+    let handle = stdin_handle();
+    console_mode(handle)?;
+    let mut composer = KEY_EVENT_COMPOSER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut records: [INPUT_RECORD; 128] = unsafe { std::mem::zeroed() };
+    loop {
+        let mut read: u32 = 0;
+        let ok = unsafe {
+            ReadConsoleInputW(
+                handle,
+                records.as_mut_ptr(),
+                records.len() as u32,
+                &mut read,
+            )
+        };
+        if ok == 0 {
+            return Some(Err(io::Error::last_os_error()));
+        }
+        let mut text = String::new();
+        // mouse and resize records are of no interest, with ENABLE_VIRTUAL_TERMINAL_INPUT the
+        // mouse arrives as key events spelling out its VT sequences
+        for record in records[..read as usize].iter() {
+            if record.EventType as u32 == FOCUS_EVENT {
+                let focus_event = unsafe { record.Event.FocusEvent };
+                text.push_str(if focus_event.bSetFocus != 0 {
+                    "\u{1b}[I"
+                } else {
+                    "\u{1b}[O"
+                });
+                continue;
+            }
+            if record.EventType as u32 != KEY_EVENT {
+                continue;
+            }
+            let key_event = unsafe { record.Event.KeyEvent };
+            let event = Win32KeyEvent::new(
+                key_event.wVirtualKeyCode as u32,
+                unsafe { key_event.uChar.UnicodeChar } as u32,
+                key_event.bKeyDown != 0,
+                key_event.dwControlKeyState,
+                key_event.wRepeatCount as u32,
+            );
+            if let Some((character, repeat_count)) = composer
+                .compose(event)
+                .and_then(|event| event.typed_character())
+            {
+                text.extend(std::iter::repeat(character).take(repeat_count as usize));
+            }
+        }
+        if !text.is_empty() {
+            return Some(Ok(text.into_bytes()));
+        }
+    }
+}
+
+/// Write a whole rendered `frame` to the console with a single `WriteConsoleW` call where
+/// possible, rather than the many small writes std's line buffered stdout would make, so
+/// that the terminal does not draw a frame half-written. The frame is converted to UTF-16
+/// so that it does not depend on the console output codepage.
+pub(crate) fn write_frame(frame: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+    use windows_sys::Win32::System::Console::WriteConsoleW;
+
+    // anything still buffered by std has to go out first
+    io::stdout().flush()?;
+    let handle = stdout_handle();
+    if console_mode(handle).is_none() {
+        // not a console, eg. redirected to a file
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(frame)?;
+        return stdout.flush();
+    }
+    let wide: Vec<u16> = String::from_utf8_lossy(frame).encode_utf16().collect();
+    let mut remaining = &wide[..];
+    while !remaining.is_empty() {
+        let mut written: u32 = 0;
+        let to_write = remaining.len().min(u32::MAX as usize) as u32;
+        let ok = unsafe {
+            WriteConsoleW(
+                handle,
+                remaining.as_ptr() as *const _,
+                to_write,
+                &mut written,
+                std::ptr::null(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        remaining = &remaining[written as usize..];
+    }
+    Ok(())
+}
+
+/// The pixel dimensions of a console of `size` from its font metrics, or from the size of its
+/// window where the font does not tell. Only a console with a window of its own (conhost) has
+/// meaningful ones: the font of the hidden pseudo console behind a ConPTY based terminal has
+/// nothing to do with what is drawn, those terminals answer the XTWINOPS queries instead.
+pub(crate) fn pixel_dimensions(size: Size) -> Option<PixelDimensions> {
+    use windows_sys::Win32::Foundation::RECT;
+    use windows_sys::Win32::System::Console::{GetCurrentConsoleFontEx, CONSOLE_FONT_INFOEX};
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    if size.cols == 0 || size.rows == 0 {
+        return None;
+    }
+    let console_window = visible_console_window()?;
+    let mut font_info: CONSOLE_FONT_INFOEX = unsafe { std::mem::zeroed() };
+    font_info.cbSize = std::mem::size_of::<CONSOLE_FONT_INFOEX>() as u32;
+    let (mut cell_width, mut cell_height) =
+        if unsafe { GetCurrentConsoleFontEx(stdout_handle(), 0, &mut font_info) } != 0 {
+            (
+                font_info.dwFontSize.X.max(0) as usize,
+                font_info.dwFontSize.Y.max(0) as usize,
+            )
+        } else {
+            (0, 0)
+        };
+    if cell_width == 0 || cell_height == 0 {
+        // eg. TrueType fonts may only report their height
+        let mut client_rect: RECT = unsafe { std::mem::zeroed() };
+        if unsafe { GetClientRect(console_window, &mut client_rect) } == 0 {
+            return None;
+        }
+        if cell_width == 0 {
+            cell_width = (client_rect.right - client_rect.left).max(0) as usize / size.cols;
+        }
+        if cell_height == 0 {
+            cell_height = (client_rect.bottom - client_rect.top).max(0) as usize / size.rows;
+        }
+    }
+    if cell_width == 0 || cell_height == 0 {
+        return None;
+    }
+    Some(PixelDimensions {
+        text_area_size: Some(SizeInPixels {
+            width: cell_width * size.cols,
+            height: cell_height * size.rows,
+        }),
+        character_cell_size: Some(SizeInPixels {
+            width: cell_width,
+            height: cell_height,
+        }),
+    })
+}
+
+/// Whether we draw into a classic console window (conhost) rather than a terminal emulator, eg.
+/// in a plain cmd window or over RDP. Windows Terminal hosts its consoles behind a hidden pseudo
+/// console window and sets `WT_SESSION`.
+pub(crate) fn is_classic_console() -> bool {
+    std::env::var_os("WT_SESSION").is_none() && visible_console_window().is_some()
+}
+
+/// The window of the console we are attached to, if it has one the user can see.
+fn visible_console_window() -> Option<windows_sys::Win32::Foundation::HWND> {
+    use windows_sys::Win32::System::Console::GetConsoleWindow;
+    use windows_sys::Win32::UI::WindowsAndMessaging::IsWindowVisible;
+    let console_window = unsafe { GetConsoleWindow() };
+    if console_window.is_null() || unsafe { IsWindowVisible(console_window) } == 0 {
+        None
+    } else {
+        Some(console_window)
+    }
+}
+
+/// Notify the user about a bell rung in `pane_name` the way they configured in
+/// `bell_notification`.
+pub(crate) fn notify_bell(bell_notification: BellNotification, pane_name: &str) {
+    match bell_notification {
+        BellNotification::None => {},
+        BellNotification::Beep => {
+            use windows_sys::Win32::System::Diagnostics::Debug::MessageBeep;
+            use windows_sys::Win32::UI::WindowsAndMessaging::MB_OK;
+            unsafe {
+                MessageBeep(MB_OK);
+            }
+        },
+        BellNotification::Flash => flash_terminal_window(),
+        BellNotification::Toast => show_toast("Zellij", &format!("Bell in pane: {}", pane_name)),
+    }
+}
+
+/// Ask the user with a message box over the terminal window whether to go through with a
+/// paste we are wary of because of `reason`.
+pub(crate) fn confirm_paste(reason: &str) -> bool {
+    confirm(&format!("{}\n\nPaste it anyway?", reason))
+}
+
+/// Ask the user with a message box whether to attach to `session_name` again after we detached
+/// from it because the desktop was locked. It pops up while the desktop is still locked, so the
+/// user finds it when they unlock it.
+pub(crate) fn confirm_reattach(session_name: &str) -> bool {
+    confirm(&format!(
+        "The desktop was locked, so Zellij detached from session {}.\n\nAttach to it again?",
+        session_name
+    ))
+}
+
+/// Call `on_lock` whenever the desktop session we run in is locked, replacing the callback of an
+/// earlier call.
+///
+/// Windows only sends session change notifications to windows, so a thread of its own creates a
+/// message-only window, registers it with `WTSRegisterSessionNotification` and pumps its
+/// messages.
+pub(crate) fn watch_desktop_lock(on_lock: Box<dyn Fn() + Send>) {
+    use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows_sys::Win32::System::RemoteDesktop::{
+        WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        HWND_MESSAGE, MSG, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_SESSION_LOCK,
+    };
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        message: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if message == WM_WTSSESSION_CHANGE && wparam == WTS_SESSION_LOCK as WPARAM {
+            if let Some(on_lock) = DESKTOP_LOCK_CALLBACK.lock().unwrap().as_ref() {
+                on_lock();
+            }
+            return 0;
+        }
+        DefWindowProcW(hwnd, message, wparam, lparam)
+    }
+
+    *DESKTOP_LOCK_CALLBACK.lock().unwrap() = Some(on_lock);
+    // a client reconnecting to a session keeps the window it registered before
+    DESKTOP_LOCK_WATCHER.call_once(|| {
+        let _ = thread::Builder::new()
+            .name("desktop_lock_watcher".to_string())
+            .spawn(|| unsafe {
+                let class_name: Vec<u16> = "ZellijDesktopLockWatcher"
+                    .encode_utf16()
+                    .chain(Some(0))
+                    .collect();
+                let instance = GetModuleHandleW(std::ptr::null());
+                let mut window_class: WNDCLASSW = std::mem::zeroed();
+                window_class.lpfnWndProc = Some(window_proc);
+                window_class.hInstance = instance;
+                window_class.lpszClassName = class_name.as_ptr();
+                if RegisterClassW(&window_class) == 0 {
+                    log::warn!(
+                        "Cannot watch for desktop locks: {}",
+                        io::Error::last_os_error()
+                    );
+                    return;
+                }
+                let hwnd = CreateWindowExW(
+                    0,
+                    class_name.as_ptr(),
+                    std::ptr::null(),
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                    HWND_MESSAGE,
+                    std::ptr::null_mut(),
+                    instance,
+                    std::ptr::null(),
+                );
+                if hwnd.is_null() {
+                    log::warn!(
+                        "Cannot watch for desktop locks: {}",
+                        io::Error::last_os_error()
+                    );
+                    return;
+                }
+                if WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) == 0 {
+                    log::warn!(
+                        "Cannot watch for desktop locks: {}",
+                        io::Error::last_os_error()
+                    );
+                    return;
+                }
+                let mut message: MSG = std::mem::zeroed();
+                while GetMessageW(&mut message, std::ptr::null_mut(), 0, 0) > 0 {
+                    DispatchMessageW(&message);
+                }
+            });
+    });
+}
+
+/// Open the hyperlink at `uri` with `ShellExecuteW`, like Windows Terminal does on a ctrl-click.
+///
+/// `ShellExecuteW` runs whatever it is given, so only web and mail links are opened right away.
+/// Local files (eg. the `file://` links of `rg --hyperlink`) may be programs or scripts, the user
+/// is asked first. Anything else is refused. This happens on a thread of its own so that neither
+/// the prompt nor a slow handler hold up rendering.
+pub(crate) fn open_link(uri: &str) {
+    let scheme = uri
+        .split_once(':')
+        .map(|(scheme, _)| scheme.to_ascii_lowercase())
+        .unwrap_or_default();
+    let needs_confirmation = match scheme.as_str() {
+        "http" | "https" | "mailto" => false,
+        "file" => true,
+        _ => {
+            log::warn!("Refusing to open link with unsupported scheme: {}", uri);
+            return;
+        },
+    };
+    let uri = uri.to_owned();
+    thread::spawn(move || {
+        use windows_sys::Win32::UI::Shell::ShellExecuteW;
+        use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+        if needs_confirmation && !confirm(&format!("Open {}?", uri)) {
+            return;
+        }
+        let to_wide = |text: &str| -> Vec<u16> { text.encode_utf16().chain(Some(0)).collect() };
+        let operation = to_wide("open");
+        let file = to_wide(&uri);
+        let result = unsafe {
+            ShellExecuteW(
+                std::ptr::null_mut(),
+                operation.as_ptr(),
+                file.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                SW_SHOWNORMAL,
+            )
+        };
+        // values up to 32 are error codes
+        if result as isize <= 32 {
+            log::warn!("Failed to open link {}: error {}", uri, result as isize);
+        }
+    });
+}
+
+/// Put `content` on the Windows clipboard as Unicode text.
+pub(crate) fn set_clipboard(content: &str) -> anyhow::Result<()> {
+    use windows_sys::Win32::Foundation::GlobalFree;
+    use windows_sys::Win32::System::DataExchange::{EmptyClipboard, SetClipboardData};
+    use windows_sys::Win32::System::Memory::{
+        GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE,
+    };
+    use windows_sys::Win32::System::Ole::CF_UNICODETEXT;
+
+    let text: Vec<u16> = content.encode_utf16().chain(Some(0)).collect();
+    let _clipboard = Clipboard::open()?;
+    unsafe {
+        if EmptyClipboard() == 0 {
+            return Err(io::Error::last_os_error()).context("failed to empty the clipboard");
+        }
+        let memory = GlobalAlloc(GMEM_MOVEABLE, text.len() * std::mem::size_of::<u16>());
+        if memory.is_null() {
+            return Err(io::Error::last_os_error()).context("failed to allocate clipboard data");
+        }
+        let destination = GlobalLock(memory) as *mut u16;
+        if destination.is_null() {
+            GlobalFree(memory);
+            return Err(io::Error::last_os_error()).context("failed to lock clipboard data");
+        }
+        std::ptr::copy_nonoverlapping(text.as_ptr(), destination, text.len());
+        GlobalUnlock(memory);
+        // the clipboard owns the memory once this succeeds
+        if SetClipboardData(CF_UNICODETEXT as u32, memory).is_null() {
+            GlobalFree(memory);
+            return Err(io::Error::last_os_error()).context("failed to set clipboard data");
+        }
+    }
+    Ok(())
+}
+
+/// Read the Unicode text on the Windows clipboard for a pane asking for it through OSC 52,
+/// after the user allowed it with a message box over the terminal window.
+pub(crate) fn read_clipboard() -> Option<String> {
+    use windows_sys::Win32::System::DataExchange::GetClipboardData;
+    use windows_sys::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+    use windows_sys::Win32::System::Ole::CF_UNICODETEXT;
+
+    if !confirm("A pane is asking to read your clipboard.\n\nAllow it?") {
+        return None;
+    }
+    let _clipboard = Clipboard::open()
+        .map_err(|e| log::error!("Failed to read the clipboard: {:?}", e))
+        .ok()?;
+    unsafe {
+        let memory = GetClipboardData(CF_UNICODETEXT as u32);
+        if memory.is_null() {
+            return None; // no text on the clipboard
+        }
+        let source = GlobalLock(memory) as *const u16;
+        if source.is_null() {
+            return None;
+        }
+        let max_len = GlobalSize(memory) / std::mem::size_of::<u16>();
+        let wide = std::slice::from_raw_parts(source, max_len);
+        let len = wide.iter().position(|c| *c == 0).unwrap_or(max_len);
+        let content = String::from_utf16_lossy(&wide[..len]);
+        GlobalUnlock(memory);
+        Some(content)
+    }
+}
+
+/// The clipboard, open for as long as this is alive.
+struct Clipboard;
+
+impl Clipboard {
+    fn open() -> anyhow::Result<Self> {
+        use windows_sys::Win32::System::DataExchange::OpenClipboard;
+        // another application may be holding the clipboard for a moment
+        for _ in 0..10 {
+            if unsafe { OpenClipboard(std::ptr::null_mut()) } != 0 {
+                return Ok(Clipboard);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        Err(io::Error::last_os_error()).context("failed to open the clipboard")
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::DataExchange::CloseClipboard;
+        unsafe {
+            CloseClipboard();
+        }
+    }
+}
+
+/// Decode text that is not UTF-8 from the codepage of the console (or the ANSI codepage when
+/// there is no console), which is what `cmd.exe` and older tools write into a pipe.
+pub(crate) fn decode_console_codepage(bytes: &[u8]) -> Option<String> {
+    use windows_sys::Win32::Globalization::{GetACP, MultiByteToWideChar, MB_ERR_INVALID_CHARS};
+    use windows_sys::Win32::System::Console::GetConsoleCP;
+
+    let len = i32::try_from(bytes.len()).ok()?;
+    if len == 0 {
+        return Some(String::new());
+    }
+    let codepage = match unsafe { GetConsoleCP() } {
+        0 => unsafe { GetACP() },
+        codepage => codepage,
+    };
+    unsafe {
+        let wide_len = MultiByteToWideChar(
+            codepage,
+            MB_ERR_INVALID_CHARS,
+            bytes.as_ptr(),
+            len,
+            std::ptr::null_mut(),
+            0,
+        );
+        if wide_len <= 0 {
+            return None;
+        }
+        let mut wide = vec![0u16; wide_len as usize];
+        let written = MultiByteToWideChar(
+            codepage,
+            MB_ERR_INVALID_CHARS,
+            bytes.as_ptr(),
+            len,
+            wide.as_mut_ptr(),
+            wide_len,
+        );
+        if written <= 0 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&wide[..written as usize]))
+    }
+}
+
+/// Ask the user a yes or no `question` with a message box over the terminal window.
+fn confirm(question: &str) -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        MessageBoxW, IDOK, MB_ICONWARNING, MB_OKCANCEL, MB_SETFOREGROUND,
+    };
+    let to_wide = |text: &str| -> Vec<u16> { text.encode_utf16().chain(Some(0)).collect() };
+    let text = to_wide(question);
+    let caption = to_wide("Zellij");
+    let owner = terminal_window().unwrap_or(std::ptr::null_mut());
+    let choice = unsafe {
+        MessageBoxW(
+            owner,
+            text.as_ptr(),
+            caption.as_ptr(),
+            MB_OKCANCEL | MB_ICONWARNING | MB_SETFOREGROUND,
+        )
+    };
+    choice == IDOK
+}
+
+/// The top level window of the terminal emulator we are running in.
+///
+/// `GetConsoleWindow` returns a hidden pseudo window when running under a
+/// ConPTY based terminal (eg. Windows Terminal), which is owned by the real one.
+fn terminal_window() -> Option<windows_sys::Win32::Foundation::HWND> {
+    use windows_sys::Win32::System::Console::GetConsoleWindow;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetAncestor, GA_ROOTOWNER};
+    unsafe {
+        let console_window = GetConsoleWindow();
+        if console_window.is_null() {
+            return None;
+        }
+        let root_owner = GetAncestor(console_window, GA_ROOTOWNER);
+        if root_owner.is_null() {
+            Some(console_window)
+        } else {
+            Some(root_owner)
+        }
+    }
+}
+
+/// Flash the terminal window in the taskbar until it is brought to the foreground.
+fn flash_terminal_window() {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        FlashWindowEx, FLASHWINFO, FLASHW_ALL, FLASHW_TIMERNOFG,
+    };
+    let Some(hwnd) = terminal_window() else {
+        log::warn!("Cannot flash for bell: no console window");
+        return;
+    };
+    let flash_info = FLASHWINFO {
+        cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+        hwnd,
+        dwFlags: FLASHW_ALL | FLASHW_TIMERNOFG,
+        uCount: 0,
+        dwTimeout: 0,
+    };
+    unsafe {
+        FlashWindowEx(&flash_info);
+    }
+}
+
+/// Show a toast notification with `title` and `message` in the Windows notification center.
+pub(crate) fn show_toast(title: &str, message: &str) {
+    use windows::core::HSTRING;
+    use windows::UI::Notifications::{
+        ToastNotification, ToastNotificationManager, ToastTemplateType,
+    };
+
+    let show = || -> windows::core::Result<()> {
+        let toast_xml =
+            ToastNotificationManager::GetTemplateContent(ToastTemplateType::ToastText02)?;
+        let text_elements = toast_xml.GetElementsByTagName(&HSTRING::from("text"))?;
+        for (index, text) in [title, message].into_iter().enumerate() {
+            text_elements
+                .Item(index as u32)?
+                .AppendChild(&toast_xml.CreateTextNode(&HSTRING::from(text))?)?;
+        }
+        let toast = ToastNotification::CreateToastNotification(&toast_xml)?;
+        ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(TOAST_APP_ID))?
+            .Show(&toast)
+    };
+    if let Err(e) = show() {
+        log::warn!("Failed to show notification: {}", e);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,177 @@
+//! Reading what is piped into `zellij pipe`, line by line.
+//!
+//! On Windows, what arrives through a non-console stdin handle depends on the program writing
+//! it: PowerShell sends UTF-8 (or its `$OutputEncoding`) with CRLF line endings, files written by
+//! Windows PowerShell are UTF-16 with a byte order mark and the tools of `cmd.exe` write in the
+//! codepage of the console. Everything is turned into UTF-8 lines here rather than ending the
+//! pipe at the first line that is not valid UTF-8.
+use std::io::{self, BufRead};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+}
+
+pub struct PipedInput<R: BufRead> {
+    reader: R,
+    encoding: Option<Encoding>,
+    normalize_line_endings: bool,
+}
+
+impl<R: BufRead> PipedInput<R> {
+    /// With `normalize_line_endings`, lines ending with CRLF are passed on ending with LF
+    pub fn new(reader: R, normalize_line_endings: bool) -> Self {
+        PipedInput {
+            reader,
+            encoding: None,
+            normalize_line_endings,
+        }
+    }
+    /// Returns the next line including its line ending, or `None` once the input ended
+    pub fn next_line(&mut self) -> Option<String> {
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None => {
+                let encoding = self.detect_encoding();
+                self.encoding = Some(encoding);
+                encoding
+            },
+        };
+        let line = match encoding {
+            Encoding::Utf8 => self.read_utf8_line(),
+            Encoding::Utf16Le => self.read_utf16_line(),
+        };
+        match line {
+            Ok(Some(mut line)) => {
+                if self.normalize_line_endings && line.ends_with("\r\n") {
+                    line.truncate(line.len() - 2);
+                    line.push('\n');
+                }
+                Some(line)
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::error!("Failed to read piped input: {}", e);
+                None
+            },
+        }
+    }
+    fn detect_encoding(&mut self) -> Encoding {
+        let (encoding, bom_len) = match self.reader.fill_buf() {
+            Ok([0xEF, 0xBB, 0xBF, ..]) => (Encoding::Utf8, 3),
+            Ok([0xFF, 0xFE, ..]) => (Encoding::Utf16Le, 2),
+            _ => (Encoding::Utf8, 0),
+        };
+        self.reader.consume(bom_len);
+        encoding
+    }
+    fn read_utf8_line(&mut self) -> io::Result<Option<String>> {
+        let mut bytes = vec![];
+        if self.reader.read_until(b'\n', &mut bytes)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(match String::from_utf8(bytes) {
+            Ok(line) => line,
+            Err(e) => decode_legacy(e.as_bytes()),
+        }))
+    }
+    fn read_utf16_line(&mut self) -> io::Result<Option<String>> {
+        let mut bytes = vec![];
+        loop {
+            if self.reader.read_until(b'\n', &mut bytes)? == 0 {
+                break;
+            }
+            // the newline is the low byte of a code unit, so its high byte comes right after
+            if bytes.len() % 2 == 1 {
+                let mut high_byte = [0u8];
+                if self.reader.read(&mut high_byte)? == 0 {
+                    break;
+                }
+                bytes.push(high_byte[0]);
+            }
+            if bytes.ends_with(&[b'\n', 0]) {
+                break;
+            }
+        }
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let code_units: Vec<u16> = bytes
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair.get(1).copied().unwrap_or(0)]))
+            .collect();
+        Ok(Some(String::from_utf16_lossy(&code_units)))
+    }
+}
+
+/// Decodes a line that is not valid UTF-8, which on Windows was most likely written in the
+/// codepage of the console
+fn decode_legacy(bytes: &[u8]) -> String {
+    #[cfg(windows)]
+    if let Some(decoded) = crate::os_input_output_windows::decode_console_codepage(bytes) {
+        return decoded;
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &[u8], normalize_line_endings: bool) -> Vec<String> {
+        let mut piped_input = PipedInput::new(input, normalize_line_endings);
+        let mut lines = vec![];
+        while let Some(line) = piped_input.next_line() {
+            lines.push(line);
+        }
+        lines
+    }
+
+    #[test]
+    fn reads_utf8_lines() {
+        assert_eq!(
+            lines("first\nsecond\nno newline".as_bytes(), false),
+            vec!["first\n", "second\n", "no newline"]
+        );
+    }
+
+    #[test]
+    fn skips_the_utf8_byte_order_mark() {
+        assert_eq!(lines("\u{feff}línea\n".as_bytes(), false), vec!["línea\n"]);
+    }
+
+    #[test]
+    fn normalizes_crlf_line_endings_only_when_asked() {
+        assert_eq!(
+            lines(b"first\r\nsecond\r\n", true),
+            vec!["first\n", "second\n"]
+        );
+        assert_eq!(
+            lines(b"first\r\nsecond\r\n", false),
+            vec!["first\r\n", "second\r\n"]
+        );
+    }
+
+    #[test]
+    fn does_not_end_the_input_at_a_line_that_is_not_utf8() {
+        let read = lines(b"caf\xe9\nafter\n", false);
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1], "after\n");
+    }
+
+    #[test]
+    fn reads_utf16_lines_with_a_byte_order_mark() {
+        let mut input = vec![0xFF, 0xFE];
+        // U+0A0A has a newline as its low byte and must not split the line
+        for code_unit in "日本\r\n\u{0a0a}ok\n".encode_utf16() {
+            input.extend(code_unit.to_le_bytes());
+        }
+        assert_eq!(lines(&input, true), vec!["日本\n", "\u{0a0a}ok\n"]);
+    }
+
+    #[test]
+    fn empty_input_has_no_lines() {
+        assert!(lines(b"", true).is_empty());
+    }
+}
@@ -268,7 +268,13 @@ impl crate::os_input_output::ClientOsApi for MockClientOsApi {
         None
     }
 
-    fn handle_signals(&self, _sigwinch_cb: Box<dyn Fn()>, _quit_cb: Box<dyn Fn()>) {}
+    fn handle_signals(
+        &self,
+        _sigwinch_cb: Box<dyn Fn()>,
+        _quit_cb: Box<dyn Fn()>,
+        _break_cb: Box<dyn Fn()>,
+    ) {
+    }
 
     fn connect_to_server(&self, _path: &std::path::Path) {}
 
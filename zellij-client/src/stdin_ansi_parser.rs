@@ -65,8 +65,10 @@ impl StdinAnsiParser {
         // <ESC>]11;?<ESC>\ => get background color
         // <ESC>]10;?<ESC>\ => get foreground color
         // <ESC>[?2026$p => get synchronised output mode
+        // <ESC>[?u => get kitty keyboard protocol flags (only answered if supported)
+        // <ESC>[c => get primary device attributes, which include whether sixel is supported
         let mut query_string = String::from(
-            "\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}\u{1b}[?2026$p",
+            "\u{1b}[14t\u{1b}[16t\u{1b}]11;?\u{1b}\u{5c}\u{1b}]10;?\u{1b}\u{5c}\u{1b}[?2026$p\u{1b}[?u\u{1b}[c",
         );
 
         // query colors
@@ -171,6 +173,22 @@ impl StdinAnsiParser {
                 self.pending_events.push(ansi_sequence);
                 self.raw_buffer.clear();
             }
+        } else if byte == b'u' {
+            self.raw_buffer.push(byte);
+            if let Some(ansi_sequence) =
+                AnsiStdinInstruction::kitty_keyboard_protocol_from_bytes(&self.raw_buffer)
+            {
+                self.pending_events.push(ansi_sequence);
+                self.raw_buffer.clear();
+            }
+        } else if byte == b'c' {
+            self.raw_buffer.push(byte);
+            if let Some(ansi_sequence) =
+                AnsiStdinInstruction::sixel_support_from_bytes(&self.raw_buffer)
+            {
+                self.pending_events.push(ansi_sequence);
+                self.raw_buffer.clear();
+            }
         } else {
             self.raw_buffer.push(byte);
         }
@@ -184,6 +202,8 @@ pub enum AnsiStdinInstruction {
     ForegroundColor(String),
     ColorRegisters(Vec<(usize, String)>),
     SynchronizedOutput(Option<SyncOutput>),
+    KittyKeyboardProtocol(u32), // u32 -> the progressive enhancement flags currently set
+    SixelSupport(bool),
 }
 
 impl AnsiStdinInstruction {
@@ -285,6 +305,28 @@ impl AnsiStdinInstruction {
             None
         }
     }
+
+    pub fn kitty_keyboard_protocol_from_bytes(bytes: &[u8]) -> Option<Self> {
+        // eg. <ESC>[?0u
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"\u{1b}\[\?(\d+)u$").unwrap();
+        }
+        let key_string = String::from_utf8_lossy(bytes);
+        let captures = RE.captures_iter(&key_string).next()?;
+        let flags = captures[1].parse::<u32>().ok()?;
+        Some(AnsiStdinInstruction::KittyKeyboardProtocol(flags))
+    }
+
+    pub fn sixel_support_from_bytes(bytes: &[u8]) -> Option<Self> {
+        // eg. <ESC>[?61;4;6;7;14;21;22;23;24;28;32;42c, where the 4 means sixel is supported
+        lazy_static! {
+            static ref RE: Regex = Regex::new(r"\u{1b}\[\?([\d;]*)c$").unwrap();
+        }
+        let key_string = String::from_utf8_lossy(bytes);
+        let captures = RE.captures_iter(&key_string).next()?;
+        let supports_sixel = captures[1].split(';').any(|attribute| attribute == "4");
+        Some(AnsiStdinInstruction::SixelSupport(supports_sixel))
+    }
 }
 
 fn color_sequence_from_bytes(bytes: &[u8]) -> Result<(usize, String), &'static str> {
@@ -317,3 +359,28 @@ fn color_sequence_from_bytes(bytes: &[u8]) -> Result<(usize, String), &'static s
         Err("invalid_instruction")
     }
 }
+
+#[test]
+pub fn can_parse_sixel_support() {
+    let mut stdin_ansi_parser = StdinAnsiParser::new();
+    let events = stdin_ansi_parser.parse(
+        "\u{1b}]11;rgb:0c0c/0c0c/0c0c\u{1b}\\\u{1b}[?61;4;6;7;14;21;22;23;24;28;32;42c"
+            .as_bytes()
+            .to_vec(),
+    );
+    assert!(
+        matches!(
+            events[..],
+            [
+                AnsiStdinInstruction::BackgroundColor(_),
+                AnsiStdinInstruction::SixelSupport(true)
+            ]
+        ),
+        "Sixel support is read from the primary device attributes"
+    );
+    let events = stdin_ansi_parser.parse("\u{1b}[?1;2c".as_bytes().to_vec());
+    assert!(
+        matches!(events[..], [AnsiStdinInstruction::SixelSupport(false)]),
+        "Terminals without the sixel attribute do not support it"
+    );
+}
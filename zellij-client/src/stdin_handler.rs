@@ -1,6 +1,7 @@
 use crate::keyboard_parser::KittyKeyboardParser;
 use crate::os_input_output::ClientOsApi;
 use crate::stdin_ansi_parser::StdinAnsiParser;
+use crate::win32_input_parser::Win32InputParser;
 use crate::InputInstruction;
 use std::sync::{Arc, Mutex};
 use termwiz::input::{InputEvent, InputParser};
@@ -20,13 +21,72 @@ fn send_done_parsing_after_query_timeout(
     });
 }
 
+/// The classic Windows console pastes by typing the text in, without bracketed paste markers. A
+/// single read holding several lines of plain text most likely comes from such a paste, so with
+/// `detect_unbracketed_paste` we treat it as one to have it bracketed for the panes that asked for
+/// it - though lines typed quickly enough look just the same, which is why it is opt-in.
+fn unbracketed_console_paste(buf: &[u8]) -> Option<String> {
+    let line_breaks = &['\r', '\n'][..];
+    let text = std::str::from_utf8(buf).ok()?;
+    if text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\r' | '\n' | '\t'))
+    {
+        // escape sequences and control keys are typed input
+        return None;
+    }
+    let first_line_break = text.find(line_breaks)?;
+    if text[first_line_break..]
+        .trim_start_matches(line_breaks)
+        .is_empty()
+    {
+        return None;
+    }
+    Some(text.to_owned())
+}
+
+const FOCUS_IN: &[u8] = b"\x1b[I";
+const FOCUS_OUT: &[u8] = b"\x1b[O";
+const BRACKETED_PASTE_START: &[u8] = b"\x1b[200~";
+
+/// Take the focus reports (mode 1004) of the terminal out of `buf`, returning whether it gained
+/// or lost focus, in order. A paste is left alone, the pasted text could contain them.
+fn take_focus_reports(buf: &mut Vec<u8>) -> Vec<bool> {
+    let mut focus_reports = vec![];
+    if buf
+        .windows(BRACKETED_PASTE_START.len())
+        .any(|window| window == BRACKETED_PASTE_START)
+    {
+        return focus_reports;
+    }
+    let mut remaining = Vec::with_capacity(buf.len());
+    let mut i = 0;
+    while i < buf.len() {
+        if buf[i..].starts_with(FOCUS_IN) {
+            focus_reports.push(true);
+            i += FOCUS_IN.len();
+        } else if buf[i..].starts_with(FOCUS_OUT) {
+            focus_reports.push(false);
+            i += FOCUS_OUT.len();
+        } else {
+            remaining.push(buf[i]);
+            i += 1;
+        }
+    }
+    *buf = remaining;
+    focus_reports
+}
+
 pub(crate) fn stdin_loop(
     mut os_input: Box<dyn ClientOsApi>,
     send_input_instructions: SenderWithContext<InputInstruction>,
     stdin_ansi_parser: Arc<Mutex<StdinAnsiParser>>,
     explicitly_disable_kitty_keyboard_protocol: bool,
+    win32_input_mode: bool,
+    detect_unbracketed_paste: bool,
 ) {
     let mut input_parser = InputParser::new();
+    let mut win32_input_parser = Win32InputParser::new();
     let mut current_buffer = vec![];
     {
         // on startup we send a query to the terminal emulator for stuff like the pixel size and colors
@@ -61,7 +121,7 @@ pub(crate) fn stdin_loop(
     let mut ansi_stdin_events = vec![];
     loop {
         match os_input.read_from_stdin() {
-            Ok(buf) => {
+            Ok(mut buf) => {
                 {
                     // here we check if we need to parse specialized ANSI instructions sent over STDIN
                     // this happens either on startup (see above) or on SIGWINCH
@@ -85,6 +145,14 @@ pub(crate) fn stdin_loop(
                         .unwrap()
                         .write_cache(ansi_stdin_events.drain(..).collect());
                 }
+                for focused in take_focus_reports(&mut buf) {
+                    send_input_instructions
+                        .send(InputInstruction::TerminalFocus(focused))
+                        .unwrap();
+                }
+                if buf.is_empty() {
+                    continue;
+                }
                 current_buffer.append(&mut buf.to_vec());
 
                 // Handle bare ESC (0x1b alone) explicitly. When the terminal
@@ -106,6 +174,35 @@ pub(crate) fn stdin_loop(
                     continue;
                 }
 
+                if win32_input_mode {
+                    // in win32-input-mode the terminal sends every key as a CSI ... _ sequence,
+                    // which panes do not understand - so we forward the key with its kitty
+                    // serialization instead and let the pane adjust it from there
+                    if let Some(keys) = win32_input_parser.parse(&buf) {
+                        current_buffer.clear();
+                        for key_with_modifier in keys {
+                            let raw_bytes = key_with_modifier
+                                .serialize_kitty()
+                                .map(|s| s.into_bytes())
+                                .unwrap_or_default();
+                            send_input_instructions
+                                .send(InputInstruction::KeyWithModifierEvent(
+                                    key_with_modifier,
+                                    raw_bytes,
+                                ))
+                                .unwrap();
+                        }
+                        continue;
+                    }
+                    // the start of what looked like a key event in an earlier read belongs to
+                    // this input
+                    let held_back = win32_input_parser.take_held_back();
+                    if !held_back.is_empty() {
+                        buf.splice(0..0, held_back.iter().copied());
+                        current_buffer.splice(0..0, held_back);
+                    }
+                }
+
                 if !explicitly_disable_kitty_keyboard_protocol {
                     // first we try to parse with the KittyKeyboardParser
                     // if we fail, we try to parse normally
@@ -123,6 +220,18 @@ pub(crate) fn stdin_loop(
                     }
                 }
 
+                if cfg!(windows) && detect_unbracketed_paste {
+                    if let Some(pasted_text) = unbracketed_console_paste(&buf) {
+                        send_input_instructions
+                            .send(InputInstruction::KeyEvent(
+                                InputEvent::Paste(pasted_text),
+                                current_buffer.drain(..).collect(),
+                            ))
+                            .unwrap();
+                        continue;
+                    }
+                }
+
                 let maybe_more = false; // read_from_stdin should (hopefully) always empty the STDIN buffer completely
                 let mut events = vec![];
                 input_parser.parse(
@@ -154,3 +263,40 @@ pub(crate) fn stdin_loop(
         }
     }
 }
+
+#[test]
+pub fn only_multi_line_plain_text_is_a_console_paste() {
+    assert_eq!(
+        unbracketed_console_paste(b"echo one\recho two\r"),
+        Some(String::from("echo one\recho two\r")),
+        "several lines of text"
+    );
+    assert_eq!(
+        unbracketed_console_paste(b"ls\r"),
+        None,
+        "a single line followed by enter"
+    );
+    assert_eq!(unbracketed_console_paste(b"a"), None, "a single key");
+    assert_eq!(
+        unbracketed_console_paste(b"\x1b[A\r\x1b[B"),
+        None,
+        "escape sequences"
+    );
+}
+
+#[test]
+pub fn focus_reports_are_taken_out_of_the_input() {
+    let mut buf = b"\x1b[I".to_vec();
+    assert_eq!(take_focus_reports(&mut buf), vec![true], "focus in");
+    assert!(buf.is_empty());
+    let mut buf = b"a\x1b[Ob\x1b[I".to_vec();
+    assert_eq!(
+        take_focus_reports(&mut buf),
+        vec![false, true],
+        "focus out and in among keys"
+    );
+    assert_eq!(buf, b"ab".to_vec());
+    let mut buf = b"\x1b[200~\x1b[I\x1b[201~".to_vec();
+    assert!(take_focus_reports(&mut buf).is_empty(), "a paste");
+    assert_eq!(buf, b"\x1b[200~\x1b[I\x1b[201~".to_vec());
+}
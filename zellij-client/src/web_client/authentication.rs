@@ -1,9 +1,13 @@
+use crate::web_client::types::SharedSession;
 use crate::web_client::utils::parse_cookies;
 use axum::body::Body;
-use axum::http::header::SET_COOKIE;
+use axum::http::header::{AUTHORIZATION, SET_COOKIE};
 use axum::{extract::Request, http::StatusCode, middleware::Next, response::Response};
 use axum_extra::extract::cookie::{Cookie, SameSite};
-use zellij_utils::web_authentication_tokens::{is_session_token_read_only, validate_session_token};
+use zellij_utils::web_authentication_tokens::{
+    is_session_token_read_only, is_token_read_only, shared_session_of_session_token,
+    validate_session_token, validate_token,
+};
 
 pub async fn auth_middleware(request: Request, next: Next) -> Result<Response, StatusCode> {
     let cookies = parse_cookies(&request);
@@ -21,6 +25,12 @@ pub async fn auth_middleware(request: Request, next: Next) -> Result<Response, S
             // Store in request extensions for downstream handlers
             let mut request = request;
             request.extensions_mut().insert(is_read_only);
+            if let Ok(Some(session_name)) = shared_session_of_session_token(&session_token) {
+                if !is_allowed_for_shared_session(request.uri().path(), &session_name) {
+                    return Err(StatusCode::FORBIDDEN);
+                }
+                request.extensions_mut().insert(SharedSession(session_name));
+            }
 
             let response = next.run(request).await;
             Ok(response)
@@ -62,3 +72,37 @@ pub async fn auth_middleware(request: Request, next: Next) -> Result<Response, S
         },
     }
 }
+
+/// Whether a login through the share link of `shared_session_name` may request `path`: only what
+/// watching that session takes
+pub fn is_allowed_for_shared_session(path: &str, shared_session_name: &str) -> bool {
+    match path.strip_prefix("/ws/terminal/") {
+        Some(session_name) => urlencoding::decode(session_name)
+            .is_ok_and(|session_name| session_name == shared_session_name),
+        None => matches!(path, "/ws/control" | "/session"),
+    }
+}
+
+/// Authenticates requests to the REST API, which carry a login token in an
+/// `Authorization: Bearer <token>` header rather than a session cookie
+pub async fn api_auth_middleware(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let auth_token = match request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        Some(token) => token.trim().to_owned(),
+        None => return Err(StatusCode::UNAUTHORIZED),
+    };
+
+    match validate_token(&auth_token) {
+        Ok(true) => {
+            let is_read_only = is_token_read_only(&auth_token).unwrap_or(true);
+            let mut request = request;
+            request.extensions_mut().insert(is_read_only);
+            Ok(next.run(request).await)
+        },
+        Ok(false) | Err(_) => Err(StatusCode::UNAUTHORIZED),
+    }
+}
@@ -1,10 +1,15 @@
 use crate::os_input_output::ClientOsApi;
 use crate::web_client::control_message::WebServerToWebClientControlMessage;
-use crate::web_client::types::{ClientChannels, ClientConnectionBus, ConnectionTable};
+use crate::web_client::message_handlers::close_code;
+use crate::web_client::types::{
+    ClientChannels, ClientConnectionBus, ConnectionTable, SERVER_RESTARTING_CLOSE_CODE,
+};
 use axum::extract::ws::{CloseFrame, Message};
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{atomic::AtomicBool, Arc};
 use tokio::sync::mpsc::UnboundedSender;
 use tokio_util::sync::CancellationToken;
+use zellij_utils::web_server_commands::{SessionClientCount, WebClientInfo};
 
 impl ConnectionTable {
     pub fn add_new_client(
@@ -25,6 +30,20 @@ impl ConnectionTable {
             .unwrap_or(false)
     }
 
+    /// Records the login `client_id` was created with, the only one allowed to download its files
+    pub fn set_client_session_token(&mut self, client_id: &str, session_token: String) {
+        if self.client_id_to_channels.contains_key(client_id) {
+            self.client_session_tokens
+                .insert(client_id.to_owned(), session_token);
+        }
+    }
+
+    pub fn is_client_owned_by(&self, client_id: &str, session_token: &str) -> bool {
+        self.client_session_tokens
+            .get(client_id)
+            .is_some_and(|client_session_token| client_session_token == session_token)
+    }
+
     pub fn add_client_control_tx(
         &mut self,
         client_id: &str,
@@ -55,6 +74,103 @@ impl ConnectionTable {
         });
     }
 
+    pub fn set_client_session_name(&mut self, client_id: &str, session_name: String) {
+        if self.client_id_to_channels.contains_key(client_id) {
+            self.client_session_names
+                .insert(client_id.to_owned(), session_name);
+        }
+    }
+
+    pub fn web_clients(&self) -> Vec<WebClientInfo> {
+        self.client_id_to_channels
+            .keys()
+            .map(|client_id| WebClientInfo {
+                web_client_id: client_id.clone(),
+                session_name: self.client_session_names.get(client_id).cloned(),
+                is_read_only: self.is_client_read_only(client_id),
+            })
+            .collect()
+    }
+
+    /// The sessions at least one web client is attached to
+    pub fn session_names(&self) -> Vec<String> {
+        let session_names: BTreeSet<&String> = self.client_session_names.values().collect();
+        session_names.into_iter().cloned().collect()
+    }
+
+    /// How many web clients are attached to each session
+    pub fn session_client_counts(&self) -> Vec<SessionClientCount> {
+        let mut client_counts: BTreeMap<&String, u32> = BTreeMap::new();
+        for session_name in self.client_session_names.values() {
+            *client_counts.entry(session_name).or_insert(0) += 1;
+        }
+        client_counts
+            .into_iter()
+            .map(|(session_name, client_count)| SessionClientCount {
+                session_name: session_name.clone(),
+                client_count,
+            })
+            .collect()
+    }
+
+    /// The control and terminal websockets that are still open
+    pub fn active_websocket_count(&self) -> usize {
+        self.client_id_to_channels
+            .values()
+            .map(|channels| {
+                let control_is_open = channels
+                    .control_channel_tx
+                    .as_ref()
+                    .map(|tx| !tx.is_closed())
+                    .unwrap_or(false);
+                let terminal_is_open = channels
+                    .terminal_channel_tx
+                    .as_ref()
+                    .map(|tx| !tx.is_closed())
+                    .unwrap_or(false);
+                control_is_open as usize + terminal_is_open as usize
+            })
+            .sum()
+    }
+
+    /// Returns false if the client is unknown or is already downloading a file
+    pub fn add_client_file_download_tx(
+        &mut self,
+        client_id: &str,
+        file_download_tx: UnboundedSender<(String, Vec<u8>)>,
+    ) -> bool {
+        if !self.client_id_to_channels.contains_key(client_id)
+            || self.client_file_downloads.contains_key(client_id)
+        {
+            return false;
+        }
+        self.client_file_downloads
+            .insert(client_id.to_owned(), file_download_tx);
+        true
+    }
+
+    pub fn remove_client_file_download_tx(&mut self, client_id: &str) {
+        self.client_file_downloads.remove(client_id);
+    }
+
+    pub fn send_file_chunk(
+        &mut self,
+        client_id: &str,
+        file_name: String,
+        bytes: Vec<u8>,
+        is_last: bool,
+    ) {
+        // dropping the sender after the last chunk lets the download know it is complete
+        let file_download_tx = if is_last {
+            self.client_file_downloads.remove(client_id)
+        } else {
+            self.client_file_downloads.get(client_id).cloned()
+        };
+        if let Some(file_download_tx) = file_download_tx {
+            let _ = file_download_tx.send((file_name, bytes));
+        }
+    }
+
     pub fn get_client_os_api(&self, client_id: &str) -> Option<&Box<dyn ClientOsApi>> {
         self.client_id_to_channels.get(client_id).map(|c| &c.os_api)
     }
@@ -76,6 +192,9 @@ impl ConnectionTable {
             client_channels.cleanup();
         }
         self.client_read_only_status.remove(client_id);
+        self.client_session_names.remove(client_id);
+        self.client_session_tokens.remove(client_id);
+        self.client_file_downloads.remove(client_id);
     }
 
     pub fn get_should_not_reconnect_flag(&self, client_id: &str) -> Option<Arc<AtomicBool>> {
@@ -83,6 +202,28 @@ impl ConnectionTable {
             .get(client_id)
             .map(|c| c.should_not_reconnect.clone())
     }
+
+    /// Close the websockets of all clients, telling them which session to reconnect to once the
+    /// web server taking over from this one is up
+    pub fn close_connections_for_restart(&mut self) {
+        self.server_is_restarting
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        for (client_id, client_channels) in self.client_id_to_channels.iter_mut() {
+            if let Some(control_channel_tx) = client_channels.control_channel_tx.as_ref() {
+                let server_restarting = WebServerToWebClientControlMessage::ServerRestarting {
+                    session_name: self.client_session_names.get(client_id).cloned(),
+                };
+                let _ = control_channel_tx.send(Message::Text(
+                    serde_json::to_string(&server_restarting).unwrap().into(),
+                ));
+                let _ = control_channel_tx.send(Message::Close(Some(CloseFrame {
+                    code: SERVER_RESTARTING_CLOSE_CODE,
+                    reason: "Server restarting".into(),
+                })));
+            }
+            client_channels.cleanup();
+        }
+    }
 }
 
 impl ClientConnectionBus {
@@ -119,17 +260,15 @@ impl ClientConnectionBus {
         }
     }
     pub fn close_connection(&mut self) {
-        let should_not_reconnect = self
-            .connection_table
-            .lock()
-            .unwrap()
-            .get_should_not_reconnect_flag(&self.web_client_id)
-            .map(|f| f.load(std::sync::atomic::Ordering::Relaxed))
-            .unwrap_or(false);
-        let code = if should_not_reconnect {
-            4001u16
-        } else {
-            axum::extract::ws::close_code::NORMAL
+        let code = {
+            let connection_table = self.connection_table.lock().unwrap();
+            let should_not_reconnect = connection_table
+                .get_should_not_reconnect_flag(&self.web_client_id)
+                .unwrap_or_default();
+            close_code(
+                &should_not_reconnect,
+                &connection_table.server_is_restarting,
+            )
         };
         let close_frame = CloseFrame {
             code,
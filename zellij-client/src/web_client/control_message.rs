@@ -22,6 +22,7 @@ pub enum WebServerToWebClientControlMessage {
     Log { lines: Vec<String> },
     LogError { lines: Vec<String> },
     SwitchedSession { new_session_name: String },
+    ServerRestarting { session_name: Option<String> }, // the session to reconnect to
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
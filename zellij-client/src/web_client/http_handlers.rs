@@ -1,15 +1,23 @@
-use crate::web_client::types::{AppState, CreateClientIdResponse, LoginRequest, LoginResponse};
+use crate::web_client::types::{
+    AppState, CreateClientIdResponse, LoginRequest, LoginResponse, SharedSession, UploadParams,
+};
 use crate::web_client::utils::{get_mime_type, parse_cookies};
 use axum::{
-    extract::{Path as AxumPath, Request, State},
+    body::Bytes,
+    extract::{Path as AxumPath, Query, Request, State},
     http::{header, StatusCode},
-    response::{Html, IntoResponse},
-    Json,
+    response::{Html, IntoResponse, Response},
+    Extension, Json,
 };
 use axum_extra::extract::cookie::{Cookie, SameSite};
 use include_dir;
+use std::time::Duration;
 use uuid::Uuid;
-use zellij_utils::{consts::VERSION, web_authentication_tokens::create_session_token};
+use zellij_utils::{
+    consts::VERSION,
+    ipc::{ClientToServerMsg, DownloadableFile, FILE_CHUNK_SIZE},
+    web_authentication_tokens::{create_session_token, create_session_token_for_share_link},
+};
 
 const WEB_CLIENT_PAGE: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
@@ -19,6 +27,9 @@ const WEB_CLIENT_PAGE: &str = include_str!(concat!(
 
 const ASSETS_DIR: include_dir::Dir<'_> = include_dir::include_dir!("$CARGO_MANIFEST_DIR/assets");
 
+pub const MAX_UPLOAD_SIZE: usize = 100 * 1024 * 1024;
+const FILE_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub async fn serve_html(State(state): State<AppState>, request: Request) -> Html<String> {
     let cookies = parse_cookies(&request);
     let is_authenticated = cookies.get("session_token").is_some();
@@ -92,12 +103,63 @@ pub async fn login_handler(
     }
 }
 
+pub async fn share_link_handler(
+    State(state): State<AppState>,
+    AxumPath(share_token): AxumPath<String>,
+) -> Response {
+    let shared_session_login = match create_session_token_for_share_link(&share_token) {
+        Ok(shared_session_login) => shared_session_login,
+        Err(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                "This share link is invalid or has expired",
+            )
+                .into_response()
+        },
+    };
+    // the cookie expires along with the share link, it is lax so that it is sent along when
+    // following the redirect of a link opened from another site
+    let cookie = Cookie::build(("session_token", shared_session_login.session_token))
+        .http_only(true)
+        .secure(state.is_https)
+        .same_site(SameSite::Lax)
+        .path("/")
+        .max_age(time::Duration::seconds(
+            shared_session_login.expires_in_seconds,
+        ))
+        .build();
+    let base_url = state
+        .config
+        .lock()
+        .unwrap()
+        .web_client
+        .base_url
+        .clone()
+        .unwrap_or("/".to_string());
+    let session_url = format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        urlencoding::encode(&shared_session_login.session_name)
+    );
+
+    let mut response = (
+        StatusCode::SEE_OTHER,
+        [(header::LOCATION, session_url.as_str())],
+    )
+        .into_response();
+    if let Ok(cookie_header) = axum::http::HeaderValue::from_str(&cookie.to_string()) {
+        response.headers_mut().insert("set-cookie", cookie_header);
+    }
+    response
+}
+
 pub async fn create_new_client(
     State(state): State<AppState>,
     request: axum::extract::Request,
 ) -> Result<Json<CreateClientIdResponse>, (StatusCode, impl IntoResponse)> {
     // Extract is_read_only from request extensions (set by auth middleware)
     let is_read_only = request.extensions().get::<bool>().copied().unwrap_or(false);
+    let session_token = parse_cookies(&request).remove("session_token");
 
     let web_client_id = String::from(Uuid::new_v4());
     let os_input = state
@@ -105,11 +167,12 @@ pub async fn create_new_client(
         .create_client_os_api()
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())))?;
 
-    state.connection_table.lock().unwrap().add_new_client(
-        web_client_id.to_owned(),
-        os_input,
-        is_read_only,
-    );
+    let mut connection_table = state.connection_table.lock().unwrap();
+    connection_table.add_new_client(web_client_id.to_owned(), os_input, is_read_only);
+    if let Some(session_token) = session_token {
+        connection_table.set_client_session_token(&web_client_id, session_token);
+    }
+    drop(connection_table);
 
     Ok(Json(CreateClientIdResponse {
         web_client_id,
@@ -117,6 +180,126 @@ pub async fn create_new_client(
     }))
 }
 
+pub async fn upload_file(
+    State(state): State<AppState>,
+    AxumPath(web_client_id): AxumPath<String>,
+    Query(upload_params): Query<UploadParams>,
+    body: Bytes,
+) -> Response {
+    let os_api = {
+        let connection_table = state.connection_table.lock().unwrap();
+        if connection_table.is_client_read_only(&web_client_id) {
+            return (
+                StatusCode::FORBIDDEN,
+                "Read-only clients cannot upload files",
+            )
+                .into_response();
+        }
+        match connection_table.get_client_os_api(&web_client_id) {
+            Some(os_api) => os_api.clone(),
+            None => return (StatusCode::NOT_FOUND, "Unknown web client").into_response(),
+        }
+    };
+
+    let mut chunks: Vec<&[u8]> = body.chunks(FILE_CHUNK_SIZE).collect();
+    if chunks.is_empty() {
+        // an empty file still needs a chunk to be created
+        chunks.push(&[]);
+    }
+    let last_chunk_index = chunks.len() - 1;
+    for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+        os_api.send_to_server(ClientToServerMsg::UploadFileChunk {
+            file_name: upload_params.file_name.clone(),
+            bytes: chunk.to_vec(),
+            is_first: chunk_index == 0,
+            is_last: chunk_index == last_chunk_index,
+        });
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn download_file(
+    State(state): State<AppState>,
+    AxumPath((web_client_id, file)): AxumPath<(String, String)>,
+    shared_session: Option<Extension<SharedSession>>,
+    request: Request,
+) -> Response {
+    let file = match file.as_str() {
+        "screen" => DownloadableFile::ScreenDump,
+        "log" => DownloadableFile::Log,
+        _ => return (StatusCode::NOT_FOUND, "Unknown file").into_response(),
+    };
+    // the log is that of the whole machine, and screens are not for watchers to take along
+    let is_read_only = request.extensions().get::<bool>().copied().unwrap_or(true);
+    if is_read_only || shared_session.is_some() {
+        return (
+            StatusCode::FORBIDDEN,
+            "Read-only and shared clients cannot download files",
+        )
+            .into_response();
+    }
+    let session_token = parse_cookies(&request)
+        .remove("session_token")
+        .unwrap_or_default();
+    let (file_download_tx, mut file_download_rx) = tokio::sync::mpsc::unbounded_channel();
+    let os_api = {
+        let mut connection_table = state.connection_table.lock().unwrap();
+        let Some(os_api) = connection_table.get_client_os_api(&web_client_id).cloned() else {
+            return (StatusCode::NOT_FOUND, "Unknown web client").into_response();
+        };
+        if connection_table.is_client_read_only(&web_client_id)
+            || !connection_table.is_client_owned_by(&web_client_id, &session_token)
+        {
+            return (
+                StatusCode::FORBIDDEN,
+                "Only the login that created a web client can download its files",
+            )
+                .into_response();
+        }
+        if !connection_table.add_client_file_download_tx(&web_client_id, file_download_tx) {
+            return (StatusCode::CONFLICT, "A download is already in progress").into_response();
+        }
+        os_api
+    };
+    os_api.send_to_server(ClientToServerMsg::DownloadFile { file });
+
+    let mut file_name = String::new();
+    let mut contents = vec![];
+    let download = tokio::time::timeout(FILE_DOWNLOAD_TIMEOUT, async {
+        while let Some((chunk_file_name, bytes)) = file_download_rx.recv().await {
+            file_name = chunk_file_name;
+            contents.extend(bytes);
+        }
+    })
+    .await;
+    if download.is_err() {
+        state
+            .connection_table
+            .lock()
+            .unwrap()
+            .remove_client_file_download_tx(&web_client_id);
+        return (
+            StatusCode::GATEWAY_TIMEOUT,
+            "Timed out waiting for the session to send the file",
+        )
+            .into_response();
+    }
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                String::from("application/octet-stream"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file_name),
+            ),
+        ],
+        contents,
+    )
+        .into_response()
+}
+
 pub async fn get_static_asset(AxumPath(path): AxumPath<String>) -> impl IntoResponse {
     let path = path.trim_start_matches('/');
 
@@ -1,35 +1,108 @@
-use axum_server::Handle;
-use std::io::{Read, Write};
+use crate::web_client::types::ConnectionTable;
+use crate::web_client::utils::bind_web_server_listener;
 use std::net::IpAddr;
-#[cfg(unix)]
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::{NamedPipeServer, ServerOptions};
 #[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Notify;
 use zellij_utils::consts::WEBSERVER_SOCKET_PATH;
 use zellij_utils::prost::Message;
-use zellij_utils::web_server_commands::{InstructionForWebServer, VersionInfo, WebServerResponse};
+use zellij_utils::web_server_commands::{
+    InstructionForWebServer, StatusInfo, VersionInfo, WebServerResponse,
+};
 use zellij_utils::web_server_contract::web_server_contract::InstructionForWebServer as ProtoInstructionForWebServer;
 use zellij_utils::web_server_contract::web_server_contract::WebServerResponse as ProtoWebServerResponse;
 
+/// Accepts the connections of `zellij web` commands controlling this web server.
 #[cfg(unix)]
-pub async fn create_webserver_receiver(
-    id: &str,
-) -> Result<UnixStream, Box<dyn std::error::Error + Send + Sync>> {
-    std::fs::create_dir_all(&WEBSERVER_SOCKET_PATH.as_path())?;
-    let socket_path = WEBSERVER_SOCKET_PATH.join(format!("{}", id));
-
-    if socket_path.exists() {
-        tokio::fs::remove_file(&socket_path).await?;
+pub struct WebServerInstructionListener {
+    listener: UnixListener,
+}
+
+#[cfg(unix)]
+impl WebServerInstructionListener {
+    pub async fn bind(id: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::create_dir_all(&WEBSERVER_SOCKET_PATH.as_path())?;
+        let socket_path = WEBSERVER_SOCKET_PATH.join(format!("{}", id));
+
+        if socket_path.exists() {
+            tokio::fs::remove_file(&socket_path).await?;
+        }
+
+        let listener = UnixListener::bind(&socket_path)?;
+        Ok(WebServerInstructionListener { listener })
+    }
+    pub async fn accept(&mut self) -> std::io::Result<UnixStream> {
+        let (stream, _) = self.listener.accept().await?;
+        Ok(stream)
     }
+}
 
-    let listener = UnixListener::bind(&socket_path)?;
-    let (stream, _) = listener.accept().await?;
-    Ok(stream)
+/// Accepts the connections of `zellij web` commands controlling this web server.
+///
+/// Named pipes are kernel objects, not filesystem entries, so discover_webserver_sockets() finds
+/// this instance by listing the pipe namespace. The marker file taking the place of the socket
+/// file (which the pipe is named after) is only looked for when the pipes cannot be listed.
+/// There is always one instance of the pipe waiting for the next connection, so that commands
+/// can connect while others are still being handled.
+#[cfg(windows)]
+pub struct WebServerInstructionListener {
+    marker_path: std::path::PathBuf,
+    pipe_path: String,
+    next_instance: NamedPipeServer,
+}
+
+#[cfg(windows)]
+impl WebServerInstructionListener {
+    pub async fn bind(id: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        std::fs::create_dir_all(&WEBSERVER_SOCKET_PATH.as_path())?;
+        let marker_path = WEBSERVER_SOCKET_PATH.join(format!("{}", id));
+        let pipe_path = zellij_utils::ipc::windows_pipe_path(&marker_path);
+
+        // creating the first instance fails rather than joining a pipe someone else created
+        // under this name
+        let next_instance = Self::create_instance(&pipe_path, true)?;
+        std::fs::File::create(&marker_path)?;
+        Ok(WebServerInstructionListener {
+            marker_path,
+            pipe_path,
+            next_instance,
+        })
+    }
+    pub async fn accept(&mut self) -> std::io::Result<NamedPipeServer> {
+        let connected = self.next_instance.connect().await;
+        // the next command connects to a new instance, whether this one connected or broke
+        let instance = std::mem::replace(
+            &mut self.next_instance,
+            Self::create_instance(&self.pipe_path, false)?,
+        );
+        connected.map(|_| instance)
+    }
+    fn create_instance(pipe_path: &str, first: bool) -> std::io::Result<NamedPipeServer> {
+        let mut security_attributes = zellij_utils::ipc::CurrentUserSecurityAttributes::new()?;
+        unsafe {
+            ServerOptions::new()
+                .first_pipe_instance(first)
+                .create_with_security_attributes_raw(pipe_path, security_attributes.as_mut_ptr())
+        }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for WebServerInstructionListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.marker_path);
+    }
 }
 
-#[cfg(unix)]
 pub async fn receive_webserver_instruction(
-    receiver: &mut UnixStream,
+    receiver: &mut (impl AsyncRead + Unpin),
 ) -> std::io::Result<InstructionForWebServer> {
     use zellij_utils::ipc::MAX_IPC_MSG_SIZE;
 
@@ -59,9 +132,8 @@ pub async fn receive_webserver_instruction(
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
 }
 
-#[cfg(unix)]
 pub async fn send_webserver_response(
-    sender: &mut UnixStream,
+    sender: &mut (impl AsyncWrite + Unpin),
     response: WebServerResponse,
 ) -> std::io::Result<()> {
     let proto_response: ProtoWebServerResponse = response.into();
@@ -75,131 +147,149 @@ pub async fn send_webserver_response(
     Ok(())
 }
 
-#[cfg(unix)]
-pub async fn listen_to_web_server_instructions(
-    server_handle: Handle,
-    id: &str,
-    web_server_ip: IpAddr,
-    web_server_port: u16,
-) {
+/// What the instructions of `zellij web` commands act on
+#[derive(Clone)]
+pub struct WebServerControl {
+    pub connection_table: Arc<Mutex<ConnectionTable>>,
+    pub web_server_ip: IpAddr,
+    pub web_server_port: Arc<AtomicU16>, // changes when the server is rebound to another port
+    pub listener_changes: UnboundedSender<ListenerChange>,
+    pub started_at: Instant,
+}
+
+/// Changes to the listener of the web server, carried out by serve_web_client
+pub enum ListenerChange {
+    Rebind(std::net::TcpListener),
+    Shutdown,
+    Restart, // shut down gracefully, letting the clients learn that they should reconnect
+}
+
+pub async fn listen_to_web_server_instructions(web_server_control: WebServerControl, id: &str) {
+    let mut listener = match WebServerInstructionListener::bind(id).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            log::error!("Failed to listen to ipc channel: {}", e);
+            return;
+        },
+    };
+    let shutdown_requested = Arc::new(Notify::new());
+
     loop {
-        let receiver = create_webserver_receiver(id).await;
+        let receiver = tokio::select! {
+            receiver = listener.accept() => receiver,
+            _ = shutdown_requested.notified() => break,
+        };
         match receiver {
-            Ok(mut receiver) => match receive_webserver_instruction(&mut receiver).await {
-                Ok(instruction) => match instruction {
-                    InstructionForWebServer::ShutdownWebServer => {
-                        server_handle.shutdown();
-                        break;
-                    },
-                    InstructionForWebServer::QueryVersion => {
-                        let response = WebServerResponse::Version(VersionInfo {
-                            version: zellij_utils::consts::VERSION.to_string(),
-                            ip: web_server_ip.to_string(),
-                            port: web_server_port,
-                        });
-                        let _ = send_webserver_response(&mut receiver, response).await;
-                    },
-                },
-                Err(e) => {
-                    log::error!("Failed to process web server instruction: {}", e);
-                },
+            Ok(receiver) => {
+                // each connection is handled on its own, so that a slow or malformed one does
+                // not hold up or end the others
+                tokio::spawn(handle_web_server_instruction(
+                    receiver,
+                    web_server_control.clone(),
+                    shutdown_requested.clone(),
+                ));
             },
             Err(e) => {
-                log::error!("Failed to listen to ipc channel: {}", e);
-                break;
+                log::error!("Failed to accept ipc connection: {}", e);
             },
         }
     }
 }
 
-// Windows implementation using ACL-secured named pipes (via CreateNamedPipeW).
-// Wrapped in spawn_blocking since named pipe I/O is synchronous.
-#[cfg(windows)]
-pub async fn listen_to_web_server_instructions(
-    server_handle: Handle,
-    id: &str,
-    web_server_ip: IpAddr,
-    web_server_port: u16,
+async fn handle_web_server_instruction(
+    mut receiver: impl AsyncRead + AsyncWrite + Unpin,
+    web_server_control: WebServerControl,
+    shutdown_requested: Arc<Notify>,
 ) {
-    std::fs::create_dir_all(&WEBSERVER_SOCKET_PATH.as_path()).ok();
-    let socket_path = WEBSERVER_SOCKET_PATH.join(format!("{}", id));
-
-    // Create marker file so discover_webserver_sockets() can find this instance.
-    // Named pipes are kernel objects, not filesystem entries, so we need this
-    // marker for the same discovery mechanism Unix uses with socket files.
-    if let Err(e) = std::fs::File::create(&socket_path) {
-        log::error!("Failed to create web server marker file: {}", e);
-        return;
-    }
-
-    loop {
-        let path = socket_path.clone();
-        let ip = web_server_ip;
-        let port = web_server_port;
-        let handle = server_handle.clone();
-
-        let result = tokio::task::spawn_blocking(move || -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-            let mut stream = zellij_utils::ipc::accept_secure_pipe_connection(&path)?;
-
-            // Read length prefix (4 bytes)
-            let mut len_bytes = [0u8; 4];
-            stream.read_exact(&mut len_bytes)?;
-            let len = u32::from_le_bytes(len_bytes) as usize;
-
-            if len > zellij_utils::ipc::MAX_IPC_MSG_SIZE {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("IPC message too large: {} bytes", len),
-                )));
-            }
-
-            // Read protobuf message
-            let mut buffer = vec![0u8; len];
-            stream.read_exact(&mut buffer)?;
-
-            let proto_instruction = ProtoInstructionForWebServer::decode(&buffer[..])
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-            let instruction: InstructionForWebServer = proto_instruction
-                .try_into()
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-
-            match instruction {
-                InstructionForWebServer::ShutdownWebServer => {
-                    handle.shutdown();
-                    Ok(true) // signal to break
-                },
-                InstructionForWebServer::QueryVersion => {
-                    let response = WebServerResponse::Version(VersionInfo {
-                        version: zellij_utils::consts::VERSION.to_string(),
-                        ip: ip.to_string(),
-                        port,
-                    });
-                    let proto_response: ProtoWebServerResponse = response.into();
-                    let encoded = proto_response.encode_to_vec();
-                    let len = encoded.len() as u32;
-                    stream.write_all(&len.to_le_bytes())?;
-                    stream.write_all(&encoded)?;
-                    stream.flush()?;
-                    Ok(false)
-                },
-            }
-        })
-        .await;
-
-        match result {
-            Ok(Ok(true)) => break,  // shutdown requested
-            Ok(Ok(false)) => {},     // handled, continue listening
-            Ok(Err(e)) => {
-                log::error!("Failed to process web server instruction: {}", e);
-                break;
+    let response = match receive_webserver_instruction(&mut receiver).await {
+        Ok(instruction) => match instruction {
+            InstructionForWebServer::ShutdownWebServer => {
+                let _ = web_server_control
+                    .listener_changes
+                    .send(ListenerChange::Shutdown);
+                shutdown_requested.notify_one();
+                None
             },
-            Err(e) => {
-                log::error!("Failed to listen to ipc channel: {}", e);
-                break;
+            InstructionForWebServer::QueryVersion => {
+                Some(WebServerResponse::Version(VersionInfo {
+                    version: zellij_utils::consts::VERSION.to_string(),
+                    ip: web_server_control.web_server_ip.to_string(),
+                    port: web_server_control.web_server_port.load(Ordering::SeqCst),
+                }))
             },
-        }
+            InstructionForWebServer::ListSessions => Some(WebServerResponse::Sessions(
+                web_server_control
+                    .connection_table
+                    .lock()
+                    .unwrap()
+                    .session_names(),
+            )),
+            InstructionForWebServer::ListConnectedWebClients => {
+                Some(WebServerResponse::ConnectedWebClients(
+                    web_server_control
+                        .connection_table
+                        .lock()
+                        .unwrap()
+                        .web_clients(),
+                ))
+            },
+            InstructionForWebServer::RebindPort(port) => {
+                Some(rebind_port(&web_server_control, port))
+            },
+            InstructionForWebServer::RestartWebServer => {
+                web_server_control
+                    .connection_table
+                    .lock()
+                    .unwrap()
+                    .close_connections_for_restart();
+                let _ = web_server_control
+                    .listener_changes
+                    .send(ListenerChange::Restart);
+                shutdown_requested.notify_one();
+                None
+            },
+            InstructionForWebServer::QueryStatus => {
+                let connection_table = web_server_control.connection_table.lock().unwrap();
+                Some(WebServerResponse::Status(StatusInfo {
+                    version: zellij_utils::consts::VERSION.to_string(),
+                    ip: web_server_control.web_server_ip.to_string(),
+                    port: web_server_control.web_server_port.load(Ordering::SeqCst),
+                    uptime_secs: web_server_control.started_at.elapsed().as_secs(),
+                    active_websocket_count: connection_table.active_websocket_count() as u32,
+                    session_client_counts: connection_table.session_client_counts(),
+                }))
+            },
+        },
+        Err(e) => {
+            log::error!("Failed to process web server instruction: {}", e);
+            None
+        },
+    };
+    if let Some(response) = response {
+        let _ = send_webserver_response(&mut receiver, response).await;
     }
+}
 
-    // Clean up marker file
-    let _ = std::fs::remove_file(&socket_path);
+fn rebind_port(web_server_control: &WebServerControl, port: u16) -> WebServerResponse {
+    if web_server_control.web_server_port.load(Ordering::SeqCst) == port {
+        return WebServerResponse::Error(format!("Already listening on port {}", port));
+    }
+    // the port is bound here rather than by serve_web_client, so that failing to bind it can be
+    // reported back
+    match bind_web_server_listener(web_server_control.web_server_ip, port) {
+        Ok(listener) => {
+            if web_server_control
+                .listener_changes
+                .send(ListenerChange::Rebind(listener))
+                .is_err()
+            {
+                return WebServerResponse::Error("The web server is shutting down".to_owned());
+            }
+            web_server_control
+                .web_server_port
+                .store(port, Ordering::SeqCst);
+            WebServerResponse::ReboundPort(port)
+        },
+        Err(e) => WebServerResponse::Error(e),
+    }
 }
@@ -3,6 +3,7 @@ use crate::keyboard_parser::KittyKeyboardParser;
 use crate::os_input_output::ClientOsApi;
 use crate::web_client::types::BRACKETED_PASTE_END;
 use crate::web_client::types::BRACKETED_PASTE_START;
+use crate::web_client::types::{KICKED_CLOSE_CODE, SERVER_RESTARTING_CLOSE_CODE};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -11,6 +12,8 @@ use std::sync::{
 use zellij_utils::{
     input::{actions::Action, cast_termwiz_key, mouse::MouseEvent},
     ipc::ClientToServerMsg,
+    prost::Message as _,
+    web_server_contract::web_server_contract::TerminalOutputMsg,
 };
 
 use axum::extract::ws::{CloseFrame, Message, WebSocket};
@@ -19,22 +22,53 @@ use termwiz::input::{InputEvent, InputParser};
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio_util::sync::CancellationToken;
 
+// deflating smaller frames of terminal output saves too little to be worth it
+const MIN_COMPRESSED_OUTPUT_SIZE: usize = 512;
+// terminal output compresses well even at the fastest level
+const OUTPUT_COMPRESSION_LEVEL: u8 = 1;
+
+/// Encode terminal output as a binary websocket frame, compressing it if the client can inflate it
+pub fn encode_terminal_output(output: String, compress: bool) -> Vec<u8> {
+    let output = output.into_bytes();
+    let terminal_output = if compress && output.len() >= MIN_COMPRESSED_OUTPUT_SIZE {
+        TerminalOutputMsg {
+            content: miniz_oxide::deflate::compress_to_vec(&output, OUTPUT_COMPRESSION_LEVEL),
+            compressed: true,
+        }
+    } else {
+        TerminalOutputMsg {
+            content: output,
+            compressed: false,
+        }
+    };
+    terminal_output.encode_to_vec()
+}
+
+/// The code to close a websocket of a web client with, telling it whether to reconnect
+pub fn close_code(should_not_reconnect: &AtomicBool, server_is_restarting: &AtomicBool) -> u16 {
+    if should_not_reconnect.load(Ordering::Relaxed) {
+        KICKED_CLOSE_CODE
+    } else if server_is_restarting.load(Ordering::Relaxed) {
+        SERVER_RESTARTING_CLOSE_CODE
+    } else {
+        axum::extract::ws::close_code::NORMAL
+    }
+}
+
 pub fn render_to_client(
     mut stdout_channel_rx: UnboundedReceiver<String>,
     mut client_channel_tx: SplitSink<WebSocket, Message>,
     cancellation_token: CancellationToken,
     should_not_reconnect: Arc<AtomicBool>,
+    server_is_restarting: Arc<AtomicBool>,
+    compress_output: bool,
 ) {
     tokio::spawn(async move {
         loop {
             tokio::select! {
                 biased;
                 _ = cancellation_token.cancelled() => {
-                    let code = if should_not_reconnect.load(Ordering::Relaxed) {
-                        4001u16
-                    } else {
-                        axum::extract::ws::close_code::NORMAL
-                    };
+                    let code = close_code(&should_not_reconnect, &server_is_restarting);
                     let close_frame = CloseFrame {
                         code,
                         reason: "Connection closed".into(),
@@ -53,7 +87,9 @@ pub fn render_to_client(
                     match result {
                         Some(rendered_bytes) => {
                             if client_channel_tx
-                                .send(Message::Text(rendered_bytes.into()))
+                                .send(Message::Binary(
+                                    encode_terminal_output(rendered_bytes, compress_output).into(),
+                                ))
                                 .await
                                 .is_err()
                             {
@@ -5,6 +5,7 @@ mod connection_manager;
 mod http_handlers;
 mod ipc_listener;
 mod message_handlers;
+mod rest_api;
 mod server_listener;
 mod session_management;
 mod types;
@@ -12,15 +13,19 @@ mod utils;
 mod websocket_handlers;
 
 use std::{
+    future::Future,
     net::{IpAddr, Ipv4Addr},
     path::PathBuf,
-    sync::{Arc, Mutex},
+    pin::Pin,
+    sync::{atomic::AtomicU16, Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
-    routing::{any, get, post},
+    routing::{any, delete, get, post},
     Router,
 };
 use tokio::runtime::Runtime;
@@ -39,20 +44,25 @@ use interprocess::unnamed_pipe::pipe;
 use std::io::{prelude::*, BufRead, BufReader};
 use zellij_utils::input::{config::Config, options::Options};
 
-use authentication::auth_middleware;
+use authentication::{api_auth_middleware, auth_middleware};
 use http_handlers::{
-    create_new_client, get_static_asset, login_handler, serve_html, version_handler,
+    create_new_client, download_file, get_static_asset, login_handler, serve_html,
+    share_link_handler, upload_file, version_handler, MAX_UPLOAD_SIZE,
 };
-use ipc_listener::listen_to_web_server_instructions;
+use ipc_listener::{listen_to_web_server_instructions, ListenerChange, WebServerControl};
+use rest_api::{create_session, kill_session, list_sessions, send_action};
 
 use types::{
     AppState, ClientOsApiFactory, ConnectionTable, RealClientOsApiFactory, RealSessionManager,
     SessionManager,
 };
-use utils::should_use_https;
+use utils::{bind_web_server_listener, should_use_https};
 use uuid::Uuid;
 use websocket_handlers::{ws_handler_control, ws_handler_terminal};
 
+// how long the websockets of clients have to close before the web server restarts regardless
+const RESTART_GRACE_PERIOD: Duration = Duration::from_secs(2);
+
 pub fn start_web_client(
     config: Config,
     config_options: Options,
@@ -125,9 +135,8 @@ pub fn start_web_client(
         unreachable!()
     } else {
         let runtime = Runtime::new().unwrap();
-        let listener = runtime.block_on(async move {
-            std::net::TcpListener::bind(format!("{}:{}", web_server_ip, web_server_port))
-        });
+        let listener = runtime
+            .block_on(async move { bind_web_server_listener(web_server_ip, web_server_port) });
         let tls_config = match (web_server_cert, web_server_key) {
             (Some(web_server_cert), Some(web_server_key)) => {
                 let tls_config = runtime.block_on(async move {
@@ -198,7 +207,6 @@ pub async fn serve_web_client(
         return;
     };
     let connection_table = Arc::new(Mutex::new(ConnectionTable::default()));
-    let server_handle = Handle::new();
     let session_manager = session_manager.unwrap_or_else(|| Arc::new(RealSessionManager));
     let client_os_api_factory =
         client_os_api_factory.unwrap_or_else(|| Arc::new(RealClientOsApiFactory));
@@ -224,45 +232,103 @@ pub async fn serve_web_client(
         is_https,
     };
 
-    tokio::spawn({
-        let server_handle = server_handle.clone();
-        async move {
-            listen_to_web_server_instructions(
-                server_handle,
-                &format!("{}", id),
-                web_server_ip,
-                web_server_port,
-            )
-            .await;
-        }
+    let (listener_changes_tx, mut listener_changes_rx) = tokio::sync::mpsc::unbounded_channel();
+    let web_server_control = WebServerControl {
+        connection_table: connection_table.clone(),
+        web_server_ip,
+        web_server_port: Arc::new(AtomicU16::new(web_server_port)),
+        listener_changes: listener_changes_tx,
+        started_at: Instant::now(),
+    };
+    tokio::spawn(async move {
+        listen_to_web_server_instructions(web_server_control, &format!("{}", id)).await;
     });
 
+    let api = Router::new()
+        .route("/sessions", get(list_sessions).post(create_session))
+        .route("/sessions/{session}", delete(kill_session))
+        .route("/sessions/{session}/actions", post(send_action))
+        .route_layer(middleware::from_fn(api_auth_middleware));
+
     let app = Router::new()
         .route("/ws/control", any(ws_handler_control))
         .route("/ws/terminal", any(ws_handler_terminal))
         .route("/ws/terminal/{session}", any(ws_handler_terminal))
         .route("/session", post(create_new_client))
+        .route(
+            "/upload/{web_client_id}",
+            post(upload_file).layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE)),
+        )
+        .route("/download/{web_client_id}/{file}", get(download_file))
         .route_layer(middleware::from_fn(auth_middleware))
         .route("/", get(serve_html))
         .route("/{session}", get(serve_html))
         .route("/assets/{*path}", get(get_static_asset))
         .route("/command/login", post(login_handler))
+        .route("/share/{share_token}", get(share_link_handler))
         .route("/info/version", get(version_handler))
+        .nest("/api", api)
         .with_state(state);
 
+    let mut listener = listener;
+    // the servers of the ports the web server was rebound from, which keep serving the clients
+    // that were connected to them
+    let mut previous_server_handles: Vec<Handle> = vec![];
+    loop {
+        let server_handle = Handle::new();
+        let mut server = serve_app(
+            listener,
+            rustls_config.clone(),
+            app.clone(),
+            server_handle.clone(),
+        );
+        tokio::select! {
+            _ = &mut server => break,
+            Some(listener_change) = listener_changes_rx.recv() => match listener_change {
+                ListenerChange::Rebind(new_listener) => {
+                    server_handle.graceful_shutdown(None);
+                    tokio::spawn(server);
+                    previous_server_handles.push(server_handle);
+                    listener = new_listener;
+                },
+                ListenerChange::Shutdown => {
+                    server_handle.shutdown();
+                    for previous_server_handle in previous_server_handles {
+                        previous_server_handle.shutdown();
+                    }
+                    let _ = server.await;
+                    break;
+                },
+                ListenerChange::Restart => {
+                    server_handle.graceful_shutdown(Some(RESTART_GRACE_PERIOD));
+                    for previous_server_handle in previous_server_handles {
+                        previous_server_handle.graceful_shutdown(Some(RESTART_GRACE_PERIOD));
+                    }
+                    let _ = server.await;
+                    break;
+                },
+            },
+        }
+    }
+}
+
+fn serve_app(
+    listener: std::net::TcpListener,
+    rustls_config: Option<RustlsConfig>,
+    app: Router,
+    server_handle: Handle,
+) -> Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>> {
     match rustls_config {
-        Some(rustls_config) => {
-            let _ = axum_server::from_tcp_rustls(listener, rustls_config)
+        Some(rustls_config) => Box::pin(
+            axum_server::from_tcp_rustls(listener, rustls_config)
                 .handle(server_handle)
-                .serve(app.into_make_service())
-                .await;
-        },
-        None => {
-            let _ = axum_server::from_tcp(listener)
+                .serve(app.into_make_service()),
+        ),
+        None => Box::pin(
+            axum_server::from_tcp(listener)
                 .handle(server_handle)
-                .serve(app.into_make_service())
-                .await;
-        },
+                .serve(app.into_make_service()),
+        ),
     }
 }
 
@@ -311,11 +377,9 @@ fn daemonize_web_server(
                 };
 
                 let listener = runtime.block_on(async move {
-                    std::net::TcpListener::bind(format!("{}:{}", web_server_ip, web_server_port))
-                });
-                listener
-                    .map(|listener| (runtime, listener, tls_config))
-                    .map_err(|e| e.to_string())
+                    bind_web_server_listener(web_server_ip, web_server_port)
+                })?;
+                Ok((runtime, listener, tls_config))
             },
         )
         .execute();
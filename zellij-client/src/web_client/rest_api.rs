@@ -0,0 +1,298 @@
+use crate::os_input_output::ClientOsApi;
+use crate::web_client::session_management::{create_first_message, create_ipc_pipe};
+use crate::web_client::types::{
+    ApiActionRequest, ApiActionResponse, ApiCreateSessionRequest, ApiSession, AppState,
+};
+use axum::{
+    extract::{Path as AxumPath, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use clap::Parser;
+use std::path::PathBuf;
+use zellij_utils::{
+    cli::CliAction,
+    data::LayoutInfo,
+    input::actions::Action,
+    ipc::{ClientAttributes, ClientToServerMsg, ExitReason, ServerToClientMsg},
+    pane_size::Size,
+    sessions::{
+        generate_unique_session_name, get_resurrectable_sessions, get_sessions,
+        validate_session_name,
+    },
+};
+
+// the same arguments `zellij action` accepts
+#[derive(Parser)]
+struct ApiCliAction {
+    #[clap(subcommand)]
+    action: CliAction,
+}
+
+pub async fn list_sessions() -> Response {
+    let sessions = match get_sessions() {
+        Ok(sessions) => sessions,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list sessions: {:?}", e),
+            )
+                .into_response()
+        },
+    };
+    let running_sessions = sessions.into_iter().map(|(name, created)| ApiSession {
+        name,
+        created_secs_ago: created.as_secs(),
+        is_exited: false,
+    });
+    let exited_sessions = get_resurrectable_sessions()
+        .into_iter()
+        .map(|(name, created)| ApiSession {
+            name,
+            created_secs_ago: created.as_secs(),
+            is_exited: true,
+        });
+    Json(
+        running_sessions
+            .chain(exited_sessions)
+            .collect::<Vec<ApiSession>>(),
+    )
+    .into_response()
+}
+
+pub async fn create_session(
+    State(state): State<AppState>,
+    Extension(is_read_only): Extension<bool>,
+    Json(create_session_request): Json<ApiCreateSessionRequest>,
+) -> Response {
+    if is_read_only {
+        return (
+            StatusCode::FORBIDDEN,
+            "Read-only tokens cannot create sessions",
+        )
+            .into_response();
+    }
+    let Some(session_name) = create_session_request
+        .name
+        .or_else(generate_unique_session_name)
+    else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to generate unique session name",
+        )
+            .into_response();
+    };
+    if let Err(e) = validate_session_name(&session_name) {
+        return (StatusCode::BAD_REQUEST, e).into_response();
+    }
+    match state.session_manager.session_exists(&session_name) {
+        Ok(false) => {},
+        Ok(true) => {
+            return (
+                StatusCode::CONFLICT,
+                format!("Session '{}' already exists", session_name),
+            )
+                .into_response()
+        },
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+
+    let config_options = state.config_options.clone();
+    let layout = match create_session_request.layout {
+        Some(layout) => LayoutInfo::from_cli(
+            &config_options.layout_dir,
+            &Some(PathBuf::from(layout)),
+            std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        ),
+        None => LayoutInfo::from_config(&config_options.layout_dir, &config_options.default_layout),
+    };
+    let client_attributes = ClientAttributes {
+        size: Size { cols: 50, rows: 50 }, // static number until a client connects
+        ..Default::default()
+    };
+    let first_message = create_first_message(
+        false,
+        Some(state.config_file_path.clone()),
+        client_attributes,
+        config_options,
+        true,
+        &session_name,
+        layout,
+    );
+    let os_input = match state.client_os_api_factory.create_client_os_api() {
+        Ok(os_input) => os_input,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    // the session keeps running in the background once this (detached) client is dropped
+    let spawned_session = tokio::task::spawn_blocking({
+        let session_name = session_name.clone();
+        move || {
+            let zellij_ipc_pipe = create_ipc_pipe(&session_name);
+            state.session_manager.spawn_session_if_needed(
+                &session_name,
+                os_input,
+                false,
+                &zellij_ipc_pipe,
+                first_message,
+            );
+        }
+    })
+    .await;
+    if let Err(e) = spawned_session {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    (
+        StatusCode::CREATED,
+        Json(ApiSession {
+            name: session_name,
+            created_secs_ago: 0,
+            is_exited: false,
+        }),
+    )
+        .into_response()
+}
+
+pub async fn kill_session(
+    State(state): State<AppState>,
+    Extension(is_read_only): Extension<bool>,
+    AxumPath(session_name): AxumPath<String>,
+) -> Response {
+    if is_read_only {
+        return (
+            StatusCode::FORBIDDEN,
+            "Read-only tokens cannot kill sessions",
+        )
+            .into_response();
+    }
+    let os_input = match connect_to_session(&state, &session_name) {
+        Ok(os_input) => os_input,
+        Err(error) => return error.into_response(),
+    };
+    let _ = tokio::task::spawn_blocking(move || {
+        os_input.send_to_server(ClientToServerMsg::KillSession);
+        // wait for the session to acknowledge the kill (or to go away)
+        let _ = os_input.recv_from_server();
+    })
+    .await;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+pub async fn send_action(
+    State(state): State<AppState>,
+    Extension(is_read_only): Extension<bool>,
+    AxumPath(session_name): AxumPath<String>,
+    Json(action_request): Json<ApiActionRequest>,
+) -> Response {
+    if is_read_only {
+        return (
+            StatusCode::FORBIDDEN,
+            "Read-only tokens cannot send actions",
+        )
+            .into_response();
+    }
+    let cli_action = match ApiCliAction::try_parse_from(
+        std::iter::once(String::from("action")).chain(action_request.action),
+    ) {
+        Ok(api_cli_action) => api_cli_action.action,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+    let config = state.config.lock().unwrap().clone();
+    let get_current_dir = || std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let actions =
+        match Action::actions_from_cli(cli_action, Box::new(get_current_dir), Some(config)) {
+            Ok(actions) => actions,
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        };
+    if actions
+        .iter()
+        .any(|action| matches!(action, Action::CliPipe { .. }))
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Pipes cannot be sent through the REST API",
+        )
+            .into_response();
+    }
+    let os_input = match connect_to_session(&state, &session_name) {
+        Ok(os_input) => os_input,
+        Err(error) => return error.into_response(),
+    };
+
+    let pane_id = action_request.pane_id;
+    let sent_actions = tokio::task::spawn_blocking(move || {
+        let mut output = vec![];
+        for action in actions {
+            os_input.send_to_server(ClientToServerMsg::Action {
+                action,
+                terminal_id: pane_id,
+                client_id: None,
+                is_cli_client: true,
+            });
+            if let Err(error_lines) = wait_for_action_to_be_handled(&*os_input, &mut output) {
+                os_input.send_to_server(ClientToServerMsg::ClientExited);
+                return Err(error_lines);
+            }
+        }
+        os_input.send_to_server(ClientToServerMsg::ClientExited);
+        Ok(output)
+    })
+    .await;
+    match sent_actions {
+        Ok(Ok(output)) => Json(ApiActionResponse { output }).into_response(),
+        Ok(Err(error_lines)) => {
+            (StatusCode::UNPROCESSABLE_ENTITY, error_lines.join("\n")).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+fn connect_to_session(
+    state: &AppState,
+    session_name: &str,
+) -> Result<Box<dyn ClientOsApi>, (StatusCode, String)> {
+    match state.session_manager.session_exists(session_name) {
+        Ok(true) => {},
+        Ok(false) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                format!("Session '{}' not found", session_name),
+            ))
+        },
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+    let os_input = state
+        .client_os_api_factory
+        .create_client_os_api()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    os_input.connect_to_server(&create_ipc_pipe(session_name));
+    Ok(os_input)
+}
+
+// mirrors the cli client: the server unblocks us once it handled the action, possibly logging
+// its output or an error
+fn wait_for_action_to_be_handled(
+    os_input: &dyn ClientOsApi,
+    output: &mut Vec<String>,
+) -> Result<(), Vec<String>> {
+    loop {
+        match os_input.recv_from_server() {
+            Some((ServerToClientMsg::UnblockInputThread, _)) => return Ok(()),
+            Some((ServerToClientMsg::Log { lines }, _)) => {
+                output.extend(lines);
+                return Ok(());
+            },
+            Some((ServerToClientMsg::LogError { lines }, _)) => return Err(lines),
+            Some((
+                ServerToClientMsg::Exit {
+                    exit_reason: ExitReason::Error(e),
+                },
+                _,
+            )) => return Err(vec![e]),
+            Some((ServerToClientMsg::Exit { .. }, _)) => return Ok(()),
+            Some(_) => {},
+            None => return Ok(()),
+        }
+    }
+}
@@ -78,6 +78,7 @@ pub fn zellij_server_listener(
                             rounded_corners: config.ui.pane_frames.rounded_corners,
                             hide_session_name: config.ui.pane_frames.hide_session_name,
                         },
+                        theme: None,
                     };
 
                     let session_name = PathBuf::from(path.clone())
@@ -114,6 +115,11 @@ pub fn zellij_server_listener(
                         first_message,
                     );
 
+                    connection_table
+                        .lock()
+                        .unwrap()
+                        .set_client_session_name(&web_client_id, session_name.clone());
+
                     if let Some(tx) = attachment_complete_tx.take() {
                         let _ = tx.send(());
                     }
@@ -138,6 +144,23 @@ pub fn zellij_server_listener(
                             Some(ServerToClientMsg::CliPipeOutput { .. } ) => {},
                             Some(ServerToClientMsg::UnblockCliPipeInput { .. } ) => {},
                             Some(ServerToClientMsg::StartWebServer { .. } ) => {},
+                            Some(ServerToClientMsg::Bell { .. } ) => {}, // the bell is part of the rendered output
+                            // web clients get the clipboard through OSC 52 in the rendered output
+                            Some(ServerToClientMsg::SetClipboard { .. } ) => {},
+                            Some(ServerToClientMsg::QueryClipboard { .. } ) => {},
+                            Some(ServerToClientMsg::Notify { .. } ) => {},
+                            // web clients do not send heartbeats
+                            Some(ServerToClientMsg::HeartbeatAck { .. } ) => {},
+                            // links are opened by the browser itself
+                            Some(ServerToClientMsg::OpenLink { .. } ) => {},
+                            Some(ServerToClientMsg::ShutdownPending) => {},
+                            Some(ServerToClientMsg::ConnStatusDetailed { .. } ) => {},
+                            Some(ServerToClientMsg::FileChunk { file_name, bytes, is_last }) => {
+                                connection_table
+                                    .lock()
+                                    .unwrap()
+                                    .send_file_chunk(&web_client_id, file_name, bytes, is_last);
+                            },
                             Some(ServerToClientMsg::Exit{exit_reason}) => {
                                 handle_exit_reason(&mut client_connection_bus, exit_reason);
                                 os_input.send_to_server(ClientToServerMsg::ClientExited);
@@ -172,6 +195,10 @@ pub fn zellij_server_listener(
                                 );
                             },
                             Some(ServerToClientMsg::RenamedSession{name: new_session_name}) => {
+                                connection_table
+                                    .lock()
+                                    .unwrap()
+                                    .set_client_session_name(&web_client_id, new_session_name.clone());
                                 client_connection_bus.send_control(
                                     WebServerToWebClientControlMessage::SwitchedSession {
                                         new_session_name,
@@ -254,6 +281,11 @@ fn handle_exit_reason(client_connection_bus: &mut ClientConnectionBus, exit_reas
                 "\u{1b}[2J\n Web Clients are not allowed to attach to this session."
             ));
         },
+        ExitReason::SessionLocked => {
+            client_connection_bus.send_stdout(format!(
+                "\u{1b}[2J\n This session is locked and cannot be attached to from the web."
+            ));
+        },
         ExitReason::Error(e) => {
             let goto_start_of_last_line = format!("\u{1b}[{};{}H", 1, 1);
             let clear_client_terminal_attributes = "\u{1b}[?1l\u{1b}=\u{1b}[r\u{1b}[?1000l\u{1b}[?1002l\u{1b}[?1003l\u{1b}[?1005l\u{1b}[?1006l\u{1b}[?12l";
@@ -52,7 +52,7 @@ pub fn spawn_new_session(
     let debug = false;
     envs::set_session_name(session_name.to_owned());
     os_input.update_session_name(session_name.to_owned());
-    spawn_server(&*zellij_ipc_pipe, debug).unwrap();
+    spawn_server(&*zellij_ipc_pipe, debug, None).unwrap();
 }
 
 pub fn create_first_message(
@@ -102,6 +102,8 @@ pub fn create_first_message(
             max_panes: None,
             force_run_layout_commands: false,
             cwd: None,
+            client_theme: None,
+            session_secret: None,
         };
 
         ClientToServerMsg::FirstClientConnected {
@@ -121,6 +123,8 @@ pub fn create_first_message(
             max_panes: None,
             force_run_layout_commands: false,
             cwd: None,
+            client_theme: None,
+            session_secret: None,
         };
         let is_web_client = true;
 
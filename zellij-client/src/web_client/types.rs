@@ -85,6 +85,10 @@ impl SessionManager for RealSessionManager {
 pub struct ConnectionTable {
     pub client_id_to_channels: HashMap<String, ClientChannels>,
     pub client_read_only_status: HashMap<String, bool>,
+    pub client_session_names: HashMap<String, String>, // the session each client is attached to
+    pub client_session_tokens: HashMap<String, String>, // the login each client was created with
+    pub client_file_downloads: HashMap<String, UnboundedSender<(String, Vec<u8>)>>, // (file name, chunk)
+    pub server_is_restarting: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone)]
@@ -169,6 +173,10 @@ pub struct AppState {
     pub is_https: bool,
 }
 
+/// The only session a web client that logged in through a share link may watch
+#[derive(Clone)]
+pub struct SharedSession(pub String);
+
 #[derive(Serialize)]
 pub struct CreateClientIdResponse {
     pub web_client_id: String,
@@ -178,6 +186,12 @@ pub struct CreateClientIdResponse {
 #[derive(Deserialize)]
 pub struct TerminalParams {
     pub web_client_id: String,
+    pub compression: Option<String>, // "deflate-raw" if the client can inflate terminal output
+}
+
+#[derive(Deserialize)]
+pub struct UploadParams {
+    pub file_name: String,
 }
 
 #[derive(Deserialize)]
@@ -192,5 +206,32 @@ pub struct LoginResponse {
     pub message: String,
 }
 
+/// A session as listed by the REST API
+#[derive(Serialize)]
+pub struct ApiSession {
+    pub name: String,
+    pub created_secs_ago: u64,
+    pub is_exited: bool, // exited sessions can be resurrected by attaching to them
+}
+
+#[derive(Deserialize)]
+pub struct ApiCreateSessionRequest {
+    pub name: Option<String>,
+    pub layout: Option<String>, // a layout name or a path to a layout file
+}
+
+#[derive(Deserialize)]
+pub struct ApiActionRequest {
+    pub action: Vec<String>, // the arguments of `zellij action`, eg. ["write-chars", "ls"]
+    pub pane_id: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct ApiActionResponse {
+    pub output: Vec<String>,
+}
+
+pub const KICKED_CLOSE_CODE: u16 = 4001;
+pub const SERVER_RESTARTING_CLOSE_CODE: u16 = 4002; // clients reconnect once the new server is up
 pub const BRACKETED_PASTE_START: [u8; 6] = [27, 91, 50, 48, 48, 126]; // \u{1b}[200~
 pub const BRACKETED_PASTE_END: [u8; 6] = [27, 91, 50, 48, 49, 126]; // \u{1b}[201~
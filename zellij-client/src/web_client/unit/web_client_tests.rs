@@ -17,6 +17,7 @@ use crate::web_client::control_message::{
     WebClientToWebServerControlMessage, WebClientToWebServerControlMessagePayload,
     WebServerToWebClientControlMessage,
 };
+use crate::web_client::types::SERVER_RESTARTING_CLOSE_CODE;
 use crate::web_client::ClientOsApiFactory;
 use zellij_utils::{
     data::{LayoutInfo, Palette},
@@ -2085,6 +2086,673 @@ mod web_client_tests {
             .unwrap();
         connect_async(request).await
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_share_link_only_allows_watching_its_session() {
+        use zellij_utils::web_authentication_tokens::create_share_link_token;
+
+        let _ = delete_db();
+
+        let share_token = create_share_link_token("shared-session", 60).unwrap();
+
+        let mock_session_manager = Arc::new(MockSessionManager::with_all_sessions_existing());
+        let mock_os_api_factory = Arc::new(MockClientOsApiFactory::new());
+
+        let config = Config::default();
+        let options = Options::default();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let port = addr.port();
+        let ip = addr.ip();
+
+        let temp_config_path = std::env::temp_dir().join("test_config.kdl");
+        let server_handle = tokio::spawn(async move {
+            serve_web_client(
+                config,
+                options,
+                Some(temp_config_path),
+                listener,
+                None,
+                Some(mock_session_manager),
+                Some(mock_os_api_factory),
+                ip,
+                port,
+            )
+            .await;
+        });
+
+        wait_for_server(port, Duration::from_secs(5))
+            .await
+            .expect("Server should start successfully");
+
+        let get = |url: String| {
+            timeout(
+                Duration::from_secs(5),
+                tokio::task::spawn_blocking(move || isahc::get(&url)),
+            )
+        };
+
+        let invalid_share_response = get(format!("http://127.0.0.1:{}/share/not-a-token", port))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(invalid_share_response.status(), 401);
+
+        let share_response = get(format!("http://127.0.0.1:{}/share/{}", port, share_token))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(share_response.status(), 303);
+        assert_eq!(
+            share_response.headers().get("location").unwrap(),
+            "/shared-session"
+        );
+        let session_token = share_response
+            .headers()
+            .get("set-cookie")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .and_then(|part| part.split('=').nth(1))
+            .unwrap()
+            .to_owned();
+
+        let session_url = format!("http://127.0.0.1:{}/session", port);
+        let mut client_response = timeout(
+            Duration::from_secs(5),
+            tokio::task::spawn_blocking({
+                let session_token = session_token.clone();
+                move || {
+                    isahc::Request::post(&session_url)
+                        .header("Cookie", format!("session_token={}", session_token))
+                        .header("Content-Type", "application/json")
+                        .body("{}")
+                        .unwrap()
+                        .send()
+                }
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        let client_data: serde_json::Value =
+            serde_json::from_str(&client_response.text().unwrap()).unwrap();
+        assert_eq!(
+            client_data["is_read_only"].as_bool(),
+            Some(true),
+            "Share links only allow watching"
+        );
+        let web_client_id = client_data["web_client_id"].as_str().unwrap();
+
+        let other_session_ws_url = format!(
+            "ws://127.0.0.1:{}/ws/terminal/other-session?web_client_id={}",
+            port, web_client_id
+        );
+        assert!(
+            connect_async_with_cookie(&other_session_ws_url, &session_token)
+                .await
+                .is_err(),
+            "Share links cannot be used to attach to other sessions"
+        );
+        let new_session_ws_url = format!(
+            "ws://127.0.0.1:{}/ws/terminal?web_client_id={}",
+            port, web_client_id
+        );
+        assert!(
+            connect_async_with_cookie(&new_session_ws_url, &session_token)
+                .await
+                .is_err(),
+            "Share links cannot be used to start new sessions"
+        );
+        let download_url = format!(
+            "http://127.0.0.1:{}/download/{}/screen",
+            port, web_client_id
+        );
+        let download_response = timeout(
+            Duration::from_secs(5),
+            tokio::task::spawn_blocking({
+                let session_token = session_token.clone();
+                move || {
+                    isahc::Request::get(&download_url)
+                        .header("Cookie", format!("session_token={}", session_token))
+                        .body(())
+                        .unwrap()
+                        .send()
+                }
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            download_response.status(),
+            403,
+            "Share links cannot be used to download files"
+        );
+
+        let shared_session_ws_url = format!(
+            "ws://127.0.0.1:{}/ws/terminal/shared-session?web_client_id={}",
+            port, web_client_id
+        );
+        assert!(
+            connect_async_with_cookie(&shared_session_ws_url, &session_token)
+                .await
+                .is_ok(),
+            "Share links can be used to watch their session"
+        );
+
+        server_handle.abort();
+        let _ = delete_db();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    async fn send_api_request(
+        port: u16,
+        method: &str,
+        path: &str,
+        auth_token: Option<&str>,
+        body: &str,
+    ) -> (u16, String) {
+        let url = format!("http://127.0.0.1:{}/api{}", port, path);
+        let method = method.to_owned();
+        let auth_token = auth_token.map(|t| t.to_owned());
+        let body = body.to_owned();
+        let mut response = timeout(
+            Duration::from_secs(5),
+            tokio::task::spawn_blocking(move || {
+                let mut request = isahc::Request::builder()
+                    .method(method.as_str())
+                    .uri(&url)
+                    .header("Content-Type", "application/json");
+                if let Some(auth_token) = auth_token {
+                    request = request.header("Authorization", format!("Bearer {}", auth_token));
+                }
+                request.body(body).unwrap().send()
+            }),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        let status = response.status().as_u16();
+        (status, response.text().unwrap_or_default())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_rest_api_creates_sessions_with_regular_tokens_only() {
+        let _ = delete_db();
+
+        let (auth_token, _) = create_token(Some("test-api".to_string()), false).unwrap();
+        let (read_only_auth_token, _) =
+            create_token(Some("test-api-readonly".to_string()), true).unwrap();
+
+        let mock_session_manager = Arc::new(MockSessionManager::new());
+        let session_manager_for_verification = mock_session_manager.clone();
+        let mock_os_api_factory = Arc::new(MockClientOsApiFactory::new());
+
+        let config = Config::default();
+        let options = Options::default();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let port = addr.port();
+        let ip = addr.ip();
+
+        let temp_config_path = std::env::temp_dir().join("test_config.kdl");
+        let server_handle = tokio::spawn(async move {
+            serve_web_client(
+                config,
+                options,
+                Some(temp_config_path),
+                listener,
+                None,
+                Some(mock_session_manager),
+                Some(mock_os_api_factory),
+                ip,
+                port,
+            )
+            .await;
+        });
+
+        wait_for_server(port, Duration::from_secs(5))
+            .await
+            .expect("Server should start successfully");
+
+        let create_session_body = r#"{"name": "api-session", "layout": "compact"}"#;
+
+        let (status, _) =
+            send_api_request(port, "POST", "/sessions", None, create_session_body).await;
+        assert_eq!(status, 401, "The REST API requires a token");
+
+        let (status, _) = send_api_request(
+            port,
+            "POST",
+            "/sessions",
+            Some("not-a-token"),
+            create_session_body,
+        )
+        .await;
+        assert_eq!(status, 401, "The REST API rejects unknown tokens");
+
+        let (status, _) = send_api_request(
+            port,
+            "POST",
+            "/sessions",
+            Some(&read_only_auth_token),
+            create_session_body,
+        )
+        .await;
+        assert_eq!(status, 403, "Read-only tokens cannot create sessions");
+        assert!(!session_manager_for_verification.was_session_created("api-session"));
+
+        let (status, body) = send_api_request(
+            port,
+            "POST",
+            "/sessions",
+            Some(&auth_token),
+            create_session_body,
+        )
+        .await;
+        assert_eq!(status, 201);
+        let created_session: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(created_session["name"], "api-session");
+        assert!(session_manager_for_verification.was_session_created("api-session"));
+        match session_manager_for_verification.get_first_message_for_session("api-session") {
+            Some(ClientToServerMsg::FirstClientConnected { cli_assets, .. }) => {
+                assert!(
+                    cli_assets.layout.is_some(),
+                    "The session is created with the requested layout"
+                );
+            },
+            other => panic!("Expected FirstClientConnected, got: {:?}", other),
+        }
+
+        server_handle.abort();
+        let _ = delete_db();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_rest_api_sends_actions_to_session() {
+        use zellij_utils::input::actions::Action;
+
+        let _ = delete_db();
+
+        let (auth_token, _) = create_token(Some("test-api".to_string()), false).unwrap();
+
+        let mock_session_manager = Arc::new(MockSessionManager::with_all_sessions_existing());
+        let mock_os_api_factory = Arc::new(MockClientOsApiFactory::new());
+        let os_api_factory_for_verification = mock_os_api_factory.clone();
+
+        let config = Config::default();
+        let options = Options::default();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let port = addr.port();
+        let ip = addr.ip();
+
+        let temp_config_path = std::env::temp_dir().join("test_config.kdl");
+        let server_handle = tokio::spawn(async move {
+            serve_web_client(
+                config,
+                options,
+                Some(temp_config_path),
+                listener,
+                None,
+                Some(mock_session_manager),
+                Some(mock_os_api_factory),
+                ip,
+                port,
+            )
+            .await;
+        });
+
+        wait_for_server(port, Duration::from_secs(5))
+            .await
+            .expect("Server should start successfully");
+
+        let (status, _) = send_api_request(
+            port,
+            "POST",
+            "/sessions/my-session/actions",
+            Some(&auth_token),
+            r#"{"action": ["not-an-action"]}"#,
+        )
+        .await;
+        assert_eq!(status, 400, "Unknown actions are rejected");
+
+        let (status, body) = send_api_request(
+            port,
+            "POST",
+            "/sessions/my-session/actions",
+            Some(&auth_token),
+            r#"{"action": ["write-chars", "ls"], "pane_id": 2}"#,
+        )
+        .await;
+        assert_eq!(status, 200);
+        let action_response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(action_response["output"].as_array().unwrap().is_empty());
+
+        let sent_messages: Vec<ClientToServerMsg> = os_api_factory_for_verification
+            .mock_apis
+            .lock()
+            .unwrap()
+            .values()
+            .flat_map(|mock_api| mock_api.get_sent_messages())
+            .collect();
+        assert!(
+            sent_messages.iter().any(|msg| matches!(
+                msg,
+                ClientToServerMsg::Action {
+                    action: Action::WriteChars { chars },
+                    terminal_id: Some(2),
+                    is_cli_client: true,
+                    ..
+                } if chars == "ls"
+            )),
+            "The action is sent to the session, got: {:?}",
+            sent_messages
+        );
+
+        server_handle.abort();
+        let _ = delete_db();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    #[test]
+    fn test_connection_table_tracks_sessions_of_web_clients() {
+        let mut connection_table = ConnectionTable::default();
+        connection_table.add_new_client(
+            "client-1".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            false,
+        );
+        connection_table.add_new_client(
+            "client-2".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            true,
+        );
+        connection_table.add_new_client(
+            "client-3".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            false,
+        );
+        connection_table.set_client_session_name("client-1", "session-b".to_owned());
+        connection_table.set_client_session_name("client-2", "session-a".to_owned());
+        connection_table.set_client_session_name("client-3", "session-b".to_owned());
+        connection_table.set_client_session_name("unknown-client", "session-c".to_owned());
+
+        assert_eq!(
+            connection_table.session_names(),
+            vec!["session-a".to_owned(), "session-b".to_owned()],
+            "sessions are listed once, and only for connected clients"
+        );
+
+        connection_table.remove_client("client-2");
+        let mut web_clients: Vec<(String, Option<String>, bool)> = connection_table
+            .web_clients()
+            .into_iter()
+            .map(|c| (c.web_client_id, c.session_name, c.is_read_only))
+            .collect();
+        web_clients.sort();
+        assert_eq!(
+            web_clients,
+            vec![
+                ("client-1".to_owned(), Some("session-b".to_owned()), false),
+                ("client-3".to_owned(), Some("session-b".to_owned()), false),
+            ],
+        );
+        assert_eq!(
+            connection_table.session_names(),
+            vec!["session-b".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_connection_table_reports_status_of_web_clients() {
+        use zellij_utils::web_server_commands::SessionClientCount;
+
+        let mut connection_table = ConnectionTable::default();
+        for client_id in ["client-1", "client-2", "client-3"] {
+            connection_table.add_new_client(
+                client_id.to_owned(),
+                Box::new(MockClientOsApi::new()),
+                false,
+            );
+        }
+        connection_table.set_client_session_name("client-1", "session-b".to_owned());
+        connection_table.set_client_session_name("client-2", "session-a".to_owned());
+        connection_table.set_client_session_name("client-3", "session-b".to_owned());
+
+        assert_eq!(
+            connection_table.session_client_counts(),
+            vec![
+                SessionClientCount {
+                    session_name: "session-a".to_owned(),
+                    client_count: 1,
+                },
+                SessionClientCount {
+                    session_name: "session-b".to_owned(),
+                    client_count: 2,
+                },
+            ]
+        );
+
+        let (control_tx, _control_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (terminal_tx, _terminal_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (closed_terminal_tx, closed_terminal_rx) = tokio::sync::mpsc::unbounded_channel();
+        drop(closed_terminal_rx);
+        connection_table.add_client_control_tx("client-1", control_tx);
+        connection_table.add_client_terminal_tx("client-1", terminal_tx);
+        connection_table.add_client_terminal_tx("client-2", closed_terminal_tx);
+        assert_eq!(
+            connection_table.active_websocket_count(),
+            2,
+            "websockets whose connection ended are not counted"
+        );
+    }
+
+    #[test]
+    fn test_connection_table_tells_web_clients_to_reconnect_on_restart() {
+        use axum::extract::ws::Message;
+
+        let mut connection_table = ConnectionTable::default();
+        connection_table.add_new_client(
+            "client-1".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            false,
+        );
+        connection_table.set_client_session_name("client-1", "my-session".to_owned());
+        let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+        connection_table.add_client_control_tx("client-1", control_tx);
+
+        connection_table.close_connections_for_restart();
+
+        match control_rx.try_recv() {
+            Ok(Message::Text(text)) => {
+                let message: serde_json::Value = serde_json::from_str(&text).unwrap();
+                assert_eq!(message["type"], "ServerRestarting");
+                assert_eq!(message["session_name"], "my-session");
+            },
+            other => panic!("Expected a ServerRestarting message, got: {:?}", other),
+        }
+        match control_rx.try_recv() {
+            Ok(Message::Close(Some(close_frame))) => {
+                assert_eq!(close_frame.code, SERVER_RESTARTING_CLOSE_CODE);
+            },
+            other => panic!("Expected a close frame, got: {:?}", other),
+        }
+        assert!(connection_table
+            .server_is_restarting
+            .load(std::sync::atomic::Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_share_links_only_allow_requests_for_watching_their_session() {
+        use crate::web_client::authentication::is_allowed_for_shared_session;
+
+        assert!(is_allowed_for_shared_session("/session", "my session"));
+        assert!(is_allowed_for_shared_session("/ws/control", "my session"));
+        assert!(is_allowed_for_shared_session(
+            "/ws/terminal/my%20session",
+            "my session"
+        ));
+        assert!(!is_allowed_for_shared_session(
+            "/ws/terminal/other-session",
+            "my session"
+        ));
+        assert!(!is_allowed_for_shared_session("/ws/terminal", "my session"));
+        assert!(!is_allowed_for_shared_session(
+            "/upload/web-client-id",
+            "my session"
+        ));
+        assert!(!is_allowed_for_shared_session(
+            "/download/web-client-id/log",
+            "my session"
+        ));
+    }
+
+    #[test]
+    fn test_connection_table_tracks_the_login_of_web_clients() {
+        let mut connection_table = ConnectionTable::default();
+        connection_table.add_new_client(
+            "client-1".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            false,
+        );
+        connection_table.set_client_session_token("client-1", "token-1".to_owned());
+        connection_table.set_client_session_token("unknown-client", "token-1".to_owned());
+
+        assert!(connection_table.is_client_owned_by("client-1", "token-1"));
+        assert!(!connection_table.is_client_owned_by("client-1", "token-2"));
+        assert!(!connection_table.is_client_owned_by("unknown-client", "token-1"));
+
+        connection_table.remove_client("client-1");
+        assert!(!connection_table.is_client_owned_by("client-1", "token-1"));
+    }
+
+    #[test]
+    fn test_connection_table_forwards_file_chunks_to_download() {
+        let mut connection_table = ConnectionTable::default();
+        connection_table.add_new_client(
+            "client-1".to_owned(),
+            Box::new(MockClientOsApi::new()),
+            false,
+        );
+        let (file_download_tx, mut file_download_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (unknown_client_tx, _unknown_client_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (second_download_tx, _second_download_rx) = tokio::sync::mpsc::unbounded_channel();
+
+        assert!(connection_table.add_client_file_download_tx("client-1", file_download_tx));
+        assert!(
+            !connection_table.add_client_file_download_tx("unknown-client", unknown_client_tx),
+            "unknown clients cannot download files"
+        );
+        assert!(
+            !connection_table.add_client_file_download_tx("client-1", second_download_tx),
+            "a client downloads one file at a time"
+        );
+
+        connection_table.send_file_chunk(
+            "client-1",
+            "zellij.log".to_owned(),
+            b"abc".to_vec(),
+            false,
+        );
+        connection_table.send_file_chunk(
+            "client-1",
+            "zellij.log".to_owned(),
+            b"def".to_vec(),
+            true,
+        );
+        connection_table.send_file_chunk(
+            "client-1",
+            "zellij.log".to_owned(),
+            b"ghi".to_vec(),
+            true,
+        );
+
+        let mut chunks = vec![];
+        while let Ok(chunk) = file_download_rx.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(
+            chunks,
+            vec![
+                ("zellij.log".to_owned(), b"abc".to_vec()),
+                ("zellij.log".to_owned(), b"def".to_vec()),
+            ],
+            "chunks after the last one are not forwarded"
+        );
+        assert!(
+            file_download_rx.is_closed(),
+            "the download is complete after its last chunk"
+        );
+    }
+
+    #[test]
+    fn test_terminal_output_is_encoded_as_protobuf_and_compressed_when_supported() {
+        use crate::web_client::message_handlers::encode_terminal_output;
+        use zellij_utils::prost::Message as _;
+        use zellij_utils::web_server_contract::web_server_contract::TerminalOutputMsg;
+
+        let short_output = String::from("\u{1b}[1;1Hhello");
+        let long_output = "\u{1b}[31mlots of red output\u{1b}[0m\n\r".repeat(100);
+
+        let short_frame =
+            TerminalOutputMsg::decode(&encode_terminal_output(short_output.clone(), true)[..])
+                .unwrap();
+        assert!(
+            !short_frame.compressed,
+            "short output is not worth compressing"
+        );
+        assert_eq!(short_frame.content, short_output.as_bytes());
+
+        let uncompressed_frame =
+            TerminalOutputMsg::decode(&encode_terminal_output(long_output.clone(), false)[..])
+                .unwrap();
+        assert!(!uncompressed_frame.compressed);
+        assert_eq!(uncompressed_frame.content, long_output.as_bytes());
+
+        let compressed_frame =
+            TerminalOutputMsg::decode(&encode_terminal_output(long_output.clone(), true)[..])
+                .unwrap();
+        assert!(compressed_frame.compressed);
+        assert!(compressed_frame.content.len() < long_output.len() / 10);
+        assert_eq!(
+            miniz_oxide::inflate::decompress_to_vec(&compressed_frame.content).unwrap(),
+            long_output.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_binding_a_port_in_use_explains_the_failure() {
+        use crate::web_client::utils::bind_web_server_listener;
+
+        let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+        let listener = bind_web_server_listener(ip, 0).unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let error = bind_web_server_listener(ip, port).unwrap_err();
+        assert_eq!(
+            error,
+            format!(
+                "Port {} on {} is already in use, stop whatever is listening on it or choose another port",
+                port, ip
+            )
+        );
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -2258,7 +2926,13 @@ impl ClientOsApi for MockClientOsApi {
         }
         msg
     }
-    fn handle_signals(&self, _sigwinch_cb: Box<dyn Fn()>, _quit_cb: Box<dyn Fn()>) {}
+    fn handle_signals(
+        &self,
+        _sigwinch_cb: Box<dyn Fn()>,
+        _quit_cb: Box<dyn Fn()>,
+        _break_cb: Box<dyn Fn()>,
+    ) {
+    }
     fn connect_to_server(&self, _path: &std::path::Path) {}
     fn load_palette(&self) -> Palette {
         Palette::default()
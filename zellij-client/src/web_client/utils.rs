@@ -1,7 +1,8 @@
 use axum::http::Request;
 use axum_extra::extract::cookie::Cookie;
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::io::ErrorKind;
+use std::net::{IpAddr, TcpListener};
 
 pub fn get_mime_type(ext: Option<&str>) -> &str {
     match ext {
@@ -43,6 +44,25 @@ pub fn should_use_https(
     }
 }
 
+/// Bind the listener of the web server, explaining the failures a user can act on
+pub fn bind_web_server_listener(ip: IpAddr, port: u16) -> Result<TcpListener, String> {
+    TcpListener::bind((ip, port)).map_err(|e| match e.kind() {
+        ErrorKind::AddrInUse => format!(
+            "Port {} on {} is already in use, stop whatever is listening on it or choose another port",
+            port, ip
+        ),
+        ErrorKind::AddrNotAvailable => format!(
+            "Cannot bind to {}: the address is not available on this machine",
+            ip
+        ),
+        ErrorKind::PermissionDenied => format!(
+            "Not permitted to bind to port {} on {}",
+            port, ip
+        ),
+        _ => format!("Failed to bind port {} on {}: {}", port, ip, e),
+    })
+}
+
 pub fn parse_cookies<T>(request: &Request<T>) -> HashMap<String, String> {
     let mut cookies = HashMap::new();
 
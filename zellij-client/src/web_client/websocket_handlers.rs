@@ -114,6 +114,7 @@ async fn handle_ws_terminal(
     state: AppState,
 ) {
     let web_client_id = params.web_client_id;
+    let compress_output = params.compression.as_deref() == Some("deflate-raw");
     let Some(os_input) = state
         .connection_table
         .lock()
@@ -154,11 +155,19 @@ async fn handle_ws_terminal(
         .unwrap()
         .get_should_not_reconnect_flag(&web_client_id)
         .unwrap_or_else(|| Arc::new(AtomicBool::new(false)));
+    let server_is_restarting = state
+        .connection_table
+        .lock()
+        .unwrap()
+        .server_is_restarting
+        .clone();
     render_to_client(
         stdout_channel_rx,
         client_terminal_channel_tx,
         terminal_channel_cancellation_token.clone(),
         should_not_reconnect,
+        server_is_restarting,
+        compress_output,
     );
     state
         .connection_table
@@ -0,0 +1,570 @@
+// for more info, please see:
+// https://github.com/microsoft/terminal/blob/main/doc/specs/%234999%20-%20Improved%20keyboard%20handling%20in%20Conpty.md
+//
+// In win32-input-mode every key event is sent as `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`, which
+// carries the full `KEY_EVENT_RECORD` of the console instead of the legacy VT encoding. This
+// lets us see key releases, tell numpad keys and AltGr apart and recover modifier combinations
+// (eg. Ctrl+Shift+<key>) that the legacy encoding collapses.
+use zellij_utils::data::{BareKey, KeyWithModifier};
+use zellij_utils::win32_input::{
+    LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED,
+};
+
+const VK_MENU: u32 = 0x12;
+
+/// Whether we are running in a terminal known to speak win32-input-mode. Only Windows Terminal
+/// does for now, and it identifies itself through `WT_SESSION`.
+pub fn win32_input_mode_supported() -> bool {
+    cfg!(windows) && std::env::var_os("WT_SESSION").is_some()
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Win32KeyEvent {
+    virtual_key_code: u32,
+    unicode_char: u32,
+    key_down: bool,
+    control_key_state: u32,
+    repeat_count: u32,
+}
+
+impl Win32KeyEvent {
+    /// A key event as found in a `KEY_EVENT_RECORD` of the console
+    #[cfg_attr(not(windows), allow(dead_code))] // the console is only read on Windows
+    pub fn new(
+        virtual_key_code: u32,
+        unicode_char: u32,
+        key_down: bool,
+        control_key_state: u32,
+        repeat_count: u32,
+    ) -> Self {
+        Win32KeyEvent {
+            virtual_key_code,
+            unicode_char,
+            key_down,
+            control_key_state,
+            repeat_count,
+        }
+    }
+    /// The character this event types, the same way `ReadConsoleW` would see it, along with
+    /// how many times it is typed
+    #[cfg_attr(not(windows), allow(dead_code))] // the console is only read on Windows
+    pub fn typed_character(&self) -> Option<(char, u32)> {
+        if self.key_down {
+            self.character()
+                .map(|character| (character, self.repeat_count.max(1)))
+        } else {
+            None
+        }
+    }
+    fn from_params(params: &[Option<u32>]) -> Self {
+        // omitted parameters default to 0, apart from the repeat count which defaults to 1
+        let param = |index: usize| params.get(index).copied().flatten();
+        Win32KeyEvent {
+            virtual_key_code: param(0).unwrap_or(0),
+            unicode_char: param(2).unwrap_or(0),
+            key_down: param(3).unwrap_or(0) != 0,
+            control_key_state: param(4).unwrap_or(0),
+            repeat_count: param(5).unwrap_or(1),
+        }
+    }
+    fn to_key_with_modifier(&self) -> Option<KeyWithModifier> {
+        if !self.key_down {
+            return None;
+        }
+        let state = self.control_key_state;
+        let ctrl = state & (LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) != 0;
+        let alt = state & (LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) != 0;
+        let shift = state & SHIFT_PRESSED != 0;
+        // Windows reports AltGr as RightAlt+LeftCtrl - if it produced a character, that
+        // character is all the user meant to type
+        let alt_gr = state & RIGHT_ALT_PRESSED != 0
+            && state & LEFT_CTRL_PRESSED != 0
+            && self.character().map(|c| !c.is_control()).unwrap_or(false);
+        let (ctrl, alt) = if alt_gr { (false, false) } else { (ctrl, alt) };
+
+        let bare_key = self.bare_key(ctrl || alt)?;
+        // a shifted character already carries the shift in the character itself, unless it is
+        // combined with another modifier (eg. Ctrl+Shift+a) in which case we keep the base key
+        let shift = match bare_key {
+            BareKey::Char(_) => shift && (ctrl || alt),
+            _ => shift,
+        };
+        let mut key = KeyWithModifier::new(bare_key);
+        if ctrl {
+            key = key.with_ctrl_modifier();
+        }
+        if alt {
+            key = key.with_alt_modifier();
+        }
+        if shift {
+            key = key.with_shift_modifier();
+        }
+        Some(key)
+    }
+    fn character(&self) -> Option<char> {
+        if self.unicode_char == 0 {
+            None
+        } else {
+            char::from_u32(self.unicode_char)
+        }
+    }
+    fn bare_key(&self, has_modifiers: bool) -> Option<BareKey> {
+        let bare_key = match self.virtual_key_code {
+            0x08 => BareKey::Backspace,
+            0x09 => BareKey::Tab,
+            0x0D => BareKey::Enter,
+            0x13 => BareKey::Pause,
+            0x14 => BareKey::CapsLock,
+            0x1B => BareKey::Esc,
+            0x20 => BareKey::Char(' '),
+            0x21 => BareKey::PageUp,
+            0x22 => BareKey::PageDown,
+            0x23 => BareKey::End,
+            0x24 => BareKey::Home,
+            0x25 => BareKey::Left,
+            0x26 => BareKey::Up,
+            0x27 => BareKey::Right,
+            0x28 => BareKey::Down,
+            0x2C => BareKey::PrintScreen,
+            0x2D => BareKey::Insert,
+            0x2E => BareKey::Delete,
+            0x5D => BareKey::Menu,
+            0x90 => BareKey::NumLock,
+            0x91 => BareKey::ScrollLock,
+            // the numpad always sends its own characters, even with modifiers that would
+            // otherwise leave us without a unicode character
+            0x60..=0x69 => BareKey::Char((b'0' + (self.virtual_key_code - 0x60) as u8) as char),
+            0x6A => BareKey::Char('*'),
+            0x6B => BareKey::Char('+'),
+            0x6D => BareKey::Char('-'),
+            0x6E => BareKey::Char('.'),
+            0x6F => BareKey::Char('/'),
+            0x70..=0x87 => BareKey::F((self.virtual_key_code - 0x70 + 1) as u8),
+            // modifier keys on their own (Shift, Ctrl, Alt, Win) are not keys we act on
+            0x10..=0x12 | 0x5B | 0x5C | 0xA0..=0xA5 => return None,
+            // with a modifier held, letters and digits are identified by their key so that
+            // eg. Ctrl+Shift+a does not arrive as a control character
+            0x30..=0x39 | 0x41..=0x5A if has_modifiers => {
+                BareKey::Char((self.virtual_key_code as u8 as char).to_ascii_lowercase())
+            },
+            _ => match self.character()? {
+                c if c.is_ascii_control() && c != '\u{7f}' => {
+                    // Ctrl+<punctuation> arrives as the corresponding C0 control character
+                    BareKey::Char(((c as u8) + 0x40).to_ascii_lowercase() as char)
+                },
+                c => BareKey::Char(c),
+            },
+        };
+        Some(bare_key)
+    }
+}
+
+/// Resolves the key events the console delivers composed characters with into plain key
+/// presses of those characters:
+/// - characters outside of the BMP (eg. emoji from the IME or the emoji picker) arrive as two
+///   key events, one for each half of their surrogate pair
+/// - characters entered with Alt+Numpad arrive with the release of Alt, the digits typed for
+///   them are not keys of their own
+///
+/// Characters the IME composes and dead keys need nothing more, they arrive as presses of the
+/// finished character.
+#[derive(Debug, Default)]
+pub(crate) struct KeyEventComposer {
+    high_surrogate: Option<u32>,
+}
+
+impl KeyEventComposer {
+    pub const fn new() -> Self {
+        KeyEventComposer {
+            high_surrogate: None,
+        }
+    }
+    /// Returns the event to act on, if any - the first half of a surrogate pair is held back
+    /// until the second one arrives
+    pub fn compose(&mut self, event: Win32KeyEvent) -> Option<Win32KeyEvent> {
+        let composed_character = |unicode_char: u32| Win32KeyEvent {
+            unicode_char,
+            key_down: true,
+            repeat_count: 1,
+            ..Default::default()
+        };
+        match event.unicode_char {
+            0xD800..=0xDBFF => {
+                if event.key_down {
+                    self.high_surrogate = Some(event.unicode_char);
+                }
+                None
+            },
+            0xDC00..=0xDFFF => {
+                if !event.key_down {
+                    return None;
+                }
+                let high_surrogate = self.high_surrogate.take()?;
+                Some(composed_character(
+                    0x10000 + ((high_surrogate - 0xD800) << 10) + (event.unicode_char - 0xDC00),
+                ))
+            },
+            unicode_char
+                if !event.key_down && event.virtual_key_code == VK_MENU && unicode_char != 0 =>
+            {
+                Some(composed_character(unicode_char))
+            },
+            0 if (0x60..=0x69).contains(&event.virtual_key_code)
+                && event.control_key_state & (LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED) != 0
+                && event.control_key_state & (LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED) == 0 =>
+            {
+                None
+            },
+            _ => Some(event),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Win32InputParsingState {
+    Ground,
+    ReceivedEscapeCharacter,
+    ParsingParams,
+}
+
+#[derive(Debug)]
+pub struct Win32InputParser {
+    state: Win32InputParsingState,
+    params: Vec<Option<u32>>,
+    current_param: Option<u32>,
+    events: Vec<Win32KeyEvent>,
+    composer: KeyEventComposer,
+    // the bytes of a sequence that was not finished by the end of the last buffer
+    held_back: Vec<u8>,
+}
+
+impl Win32InputParser {
+    pub fn new() -> Self {
+        Win32InputParser {
+            state: Win32InputParsingState::Ground,
+            params: vec![],
+            current_param: None,
+            events: vec![],
+            composer: KeyEventComposer::new(),
+            held_back: vec![],
+        }
+    }
+    /// Parse a buffer made up entirely of win32-input-mode sequences into the keys they
+    /// represent. Key releases and lone modifier keys yield no key, so this can return an empty
+    /// list. A sequence left unfinished at the end of the buffer is finished by the next one.
+    /// Returns `None` if the buffer contains anything else, in which case the bytes held back
+    /// from earlier buffers for an unfinished sequence are left in `take_held_back`.
+    pub fn parse(&mut self, buffer: &[u8]) -> Option<Vec<KeyWithModifier>> {
+        let held_back_from_earlier_buffers = self.held_back.clone();
+        for byte in buffer {
+            if !self.advance(*byte) {
+                self.reset();
+                self.held_back = held_back_from_earlier_buffers;
+                return None;
+            }
+            self.held_back.push(*byte);
+            if let Win32InputParsingState::Ground = self.state {
+                self.held_back.clear();
+            }
+        }
+        let mut keys = vec![];
+        for event in self.events.drain(..) {
+            let Some(event) = self.composer.compose(event) else {
+                continue;
+            };
+            if let Some(key) = event.to_key_with_modifier() {
+                for _ in 0..event.repeat_count.max(1) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        Some(keys)
+    }
+    /// The bytes held back for an unfinished sequence before `parse` gave up on it, to be
+    /// handled along with the buffer it gave up on
+    pub fn take_held_back(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.held_back)
+    }
+    fn reset(&mut self) {
+        self.state = Win32InputParsingState::Ground;
+        self.params.clear();
+        self.current_param = None;
+        self.events.clear();
+    }
+    fn advance(&mut self, byte: u8) -> bool {
+        // returns false if we failed parsing
+        match (&self.state, byte) {
+            (Win32InputParsingState::Ground, 0x1b) => {
+                self.state = Win32InputParsingState::ReceivedEscapeCharacter;
+            },
+            (Win32InputParsingState::ReceivedEscapeCharacter, b'[') => {
+                self.state = Win32InputParsingState::ParsingParams;
+            },
+            (Win32InputParsingState::ParsingParams, b'0'..=b'9') => {
+                let digit = (byte - b'0') as u32;
+                let current = self.current_param.unwrap_or(0);
+                match current.checked_mul(10).and_then(|c| c.checked_add(digit)) {
+                    Some(param) => self.current_param = Some(param),
+                    None => return false,
+                }
+            },
+            (Win32InputParsingState::ParsingParams, b';') => {
+                self.params.push(self.current_param.take());
+            },
+            (Win32InputParsingState::ParsingParams, b'_') => {
+                self.params.push(self.current_param.take());
+                self.events.push(Win32KeyEvent::from_params(
+                    &self.params.drain(..).collect::<Vec<_>>(),
+                ));
+                self.state = Win32InputParsingState::Ground;
+            },
+            _ => {
+                return false;
+            },
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+fn parse(buffer: &str) -> Option<Vec<KeyWithModifier>> {
+    Win32InputParser::new().parse(buffer.as_bytes())
+}
+
+#[test]
+pub fn can_parse_plain_characters() {
+    assert_eq!(
+        parse("\u{1b}[65;30;97;1;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('a'))]),
+        "a key press"
+    );
+    assert_eq!(
+        parse("\u{1b}[65;30;65;1;16;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('A'))]),
+        "shift is carried by the character itself"
+    );
+    assert_eq!(
+        parse("\u{1b}[13;28;13;1;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Enter)]),
+        "enter"
+    );
+    assert_eq!(
+        parse("\u{1b}[27;1;27;1;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Esc)]),
+        "escape"
+    );
+}
+
+#[test]
+pub fn key_releases_and_lone_modifiers_produce_no_keys() {
+    assert_eq!(
+        parse("\u{1b}[65;30;97;0;0;1_"),
+        Some(vec![]),
+        "a key release"
+    );
+    assert_eq!(
+        parse("\u{1b}[16;42;0;1;16;1_"),
+        Some(vec![]),
+        "pressing shift on its own"
+    );
+    assert_eq!(
+        parse("\u{1b}[65;30;97;1;0;1_\u{1b}[65;30;97;0;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('a'))]),
+        "a press followed by its release"
+    );
+}
+
+#[test]
+pub fn can_parse_ctrl_shift_combinations() {
+    assert_eq!(
+        parse("\u{1b}[65;30;1;1;8;1_"),
+        Some(vec![
+            KeyWithModifier::new(BareKey::Char('a')).with_ctrl_modifier()
+        ]),
+        "ctrl+a"
+    );
+    assert_eq!(
+        parse("\u{1b}[84;20;20;1;24;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('t'))
+            .with_ctrl_modifier()
+            .with_shift_modifier()]),
+        "ctrl+shift+t"
+    );
+    assert_eq!(
+        parse("\u{1b}[219;26;27;1;8;1_"),
+        Some(vec![
+            KeyWithModifier::new(BareKey::Char('[')).with_ctrl_modifier()
+        ]),
+        "ctrl+["
+    );
+    assert_eq!(
+        parse("\u{1b}[38;72;0;1;24;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Up)
+            .with_ctrl_modifier()
+            .with_shift_modifier()]),
+        "ctrl+shift+up"
+    );
+}
+
+#[test]
+pub fn alt_gr_produces_a_plain_character_and_right_alt_is_alt() {
+    assert_eq!(
+        parse("\u{1b}[81;16;64;1;9;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('@'))]),
+        "AltGr+q on a german layout"
+    );
+    assert_eq!(
+        parse("\u{1b}[72;35;104;1;1;1_"),
+        Some(vec![
+            KeyWithModifier::new(BareKey::Char('h')).with_alt_modifier()
+        ]),
+        "right alt+h on a layout without AltGr"
+    );
+    assert_eq!(
+        parse("\u{1b}[72;35;104;1;2;1_"),
+        Some(vec![
+            KeyWithModifier::new(BareKey::Char('h')).with_alt_modifier()
+        ]),
+        "left alt+h"
+    );
+}
+
+#[test]
+pub fn numpad_keys_keep_their_characters() {
+    assert_eq!(
+        parse("\u{1b}[103;71;55;1;32;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('7'))]),
+        "numpad 7"
+    );
+    assert_eq!(
+        parse("\u{1b}[107;78;0;1;40;1_"),
+        Some(vec![
+            KeyWithModifier::new(BareKey::Char('+')).with_ctrl_modifier()
+        ]),
+        "ctrl+numpad plus"
+    );
+    assert_eq!(
+        parse("\u{1b}[36;71;0;1;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Home)]),
+        "numpad 7 with numlock off is home"
+    );
+}
+
+#[test]
+pub fn omitted_parameters_take_their_defaults_and_repeats_are_expanded() {
+    assert_eq!(
+        parse("\u{1b}[40;;;1;;_"),
+        Some(vec![KeyWithModifier::new(BareKey::Down)]),
+        "omitted parameters"
+    );
+    assert_eq!(
+        parse("\u{1b}[65;30;97;1;0;3_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('a')); 3]),
+        "repeat count"
+    );
+}
+
+#[test]
+pub fn composed_characters_are_resolved() {
+    assert_eq!(
+        parse("\u{1b}[0;0;55357;1;0;1_\u{1b}[0;0;56832;1;0;1_\u{1b}[0;0;56832;0;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('😀'))]),
+        "a surrogate pair from the emoji picker"
+    );
+    let mut parser = Win32InputParser::new();
+    assert_eq!(
+        parser.parse("\u{1b}[0;0;55357;1;0;1_".as_bytes()),
+        Some(vec![]),
+        "the first half of a surrogate pair is held back"
+    );
+    assert_eq!(
+        parser.parse("\u{1b}[0;0;56832;1;0;1_".as_bytes()),
+        Some(vec![KeyWithModifier::new(BareKey::Char('😀'))]),
+        "until the second half arrives"
+    );
+    assert_eq!(
+        parse("\u{1b}[0;0;56832;1;0;1_"),
+        Some(vec![]),
+        "a lone second half of a surrogate pair"
+    );
+    assert_eq!(
+        parse(concat!(
+            "\u{1b}[18;56;0;1;2;1_",
+            "\u{1b}[98;80;0;1;2;1_",
+            "\u{1b}[98;80;0;0;2;1_",
+            "\u{1b}[18;56;233;0;0;1_"
+        )),
+        Some(vec![KeyWithModifier::new(BareKey::Char('é'))]),
+        "alt+numpad 0233 arrives with the release of alt"
+    );
+    assert_eq!(
+        parse("\u{1b}[0;0;12354;1;0;1_\u{1b}[0;0;12354;0;0;1_"),
+        Some(vec![KeyWithModifier::new(BareKey::Char('あ'))]),
+        "a character composed by the IME"
+    );
+}
+
+#[test]
+pub fn composed_characters_are_typed() {
+    let mut composer = KeyEventComposer::new();
+    let typed: String = [
+        Win32KeyEvent::new(0, 0xD83D, true, 0, 1),
+        Win32KeyEvent::new(0, 0xDE00, true, 0, 1),
+        Win32KeyEvent::new(0, 0xDE00, false, 0, 1),
+        Win32KeyEvent::new(0xDE, 0, true, 0, 1), // a dead key
+        Win32KeyEvent::new(0x45, 0xE9, true, 0, 1),
+        Win32KeyEvent::new(0x45, 0xE9, false, 0, 1),
+        Win32KeyEvent::new(0x41, 0x61, true, 0, 2),
+    ]
+    .into_iter()
+    .filter_map(|event| composer.compose(event))
+    .filter_map(|event| event.typed_character())
+    .flat_map(|(character, repeat_count)| std::iter::repeat_n(character, repeat_count as usize))
+    .collect();
+    assert_eq!(typed, "😀éaa");
+}
+
+#[test]
+pub fn other_input_is_not_parsed() {
+    assert_eq!(parse("a"), None, "plain text");
+    assert_eq!(parse("\u{1b}[97;5u"), None, "a kitty keyboard sequence");
+    assert_eq!(parse("\u{1b}[A"), None, "a legacy arrow key");
+    assert_eq!(
+        parse("\u{1b}[65;30;97;1;0;1_a"),
+        None,
+        "a sequence followed by plain text"
+    );
+}
+
+#[test]
+pub fn sequences_split_across_buffers_are_parsed() {
+    let mut parser = Win32InputParser::new();
+    assert_eq!(
+        parser.parse("\u{1b}[65;30;".as_bytes()),
+        Some(vec![]),
+        "the start of a sequence is held back"
+    );
+    assert_eq!(
+        parser.parse("97;1;0;1_".as_bytes()),
+        Some(vec![KeyWithModifier::new(BareKey::Char('a'))]),
+        "until the rest of it arrives"
+    );
+    assert!(parser.take_held_back().is_empty());
+}
+
+#[test]
+pub fn unfinished_sequences_that_turn_out_to_be_other_input_are_handed_back() {
+    let mut parser = Win32InputParser::new();
+    assert_eq!(parser.parse("\u{1b}[12;".as_bytes()), Some(vec![]));
+    assert_eq!(
+        parser.parse("5R".as_bytes()),
+        None,
+        "a cursor position report"
+    );
+    assert_eq!(parser.take_held_back(), "\u{1b}[12;".as_bytes().to_vec());
+    assert_eq!(
+        parser.parse("\u{1b}[65;30;97;1;0;1_".as_bytes()),
+        Some(vec![KeyWithModifier::new(BareKey::Char('a'))]),
+        "the parser starts over afterwards"
+    );
+}
@@ -0,0 +1,134 @@
+//! Watches the accept loop of the session pipes on Windows, where each client connects to the
+//! main pipe and then to the reverse pipe, for the reverse connections no longer arriving.
+//!
+//! One client whose reverse pipe does not connect in time is a probe (eg. `list-sessions` of an
+//! older version), several in a row mean the reverse acceptor is stuck or gone - and that a late
+//! reverse connection may sit in its queue waiting to be paired with the wrong client. The accept
+//! loop then recreates both listeners, which drops anything queued.
+
+use std::time::Instant;
+
+/// How long the accept loop waits for the reverse pipe of a client it accepted on the main pipe
+#[cfg(windows)]
+pub(crate) const REVERSE_PIPE_ACCEPT_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(5);
+
+// clients in a row without a reverse pipe after which the listeners are reset
+const MAX_CONSECUTIVE_TIMEOUTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AcceptVerdict {
+    KeepAccepting,
+    ResetListeners,
+}
+
+#[derive(Debug)]
+pub(crate) struct AcceptWatchdog {
+    consecutive_timeouts: usize,
+    total_timeouts: usize,
+    resets: usize,
+    last_paired: Option<Instant>,
+    listening_since: Instant,
+}
+
+impl AcceptWatchdog {
+    pub fn new() -> Self {
+        AcceptWatchdog {
+            consecutive_timeouts: 0,
+            total_timeouts: 0,
+            resets: 0,
+            last_paired: None,
+            listening_since: Instant::now(),
+        }
+    }
+    pub fn reverse_pipe_accepted(&mut self) {
+        self.consecutive_timeouts = 0;
+        self.last_paired = Some(Instant::now());
+    }
+    /// `acceptor_gone` - the thread accepting the reverse pipes has ended, so none will arrive
+    pub fn reverse_pipe_timed_out(&mut self, acceptor_gone: bool) -> AcceptVerdict {
+        self.consecutive_timeouts += 1;
+        self.total_timeouts += 1;
+        if acceptor_gone || self.consecutive_timeouts >= MAX_CONSECUTIVE_TIMEOUTS {
+            AcceptVerdict::ResetListeners
+        } else {
+            AcceptVerdict::KeepAccepting
+        }
+    }
+    pub fn listeners_reset(&mut self) {
+        self.consecutive_timeouts = 0;
+        self.resets += 1;
+        self.listening_since = Instant::now();
+    }
+    pub fn diagnostics(&self) -> String {
+        format!(
+            "{} clients in a row without a reverse pipe ({} in total), last client paired {}, listening for {:?}, listeners reset {} times before",
+            self.consecutive_timeouts,
+            self.total_timeouts,
+            self.last_paired
+                .map(|last_paired| format!("{:?} ago", last_paired.elapsed()))
+                .unwrap_or_else(|| "never".to_owned()),
+            self.listening_since.elapsed(),
+            self.resets,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_single_probe_does_not_reset_the_listeners() {
+        let mut watchdog = AcceptWatchdog::new();
+        assert_eq!(
+            watchdog.reverse_pipe_timed_out(false),
+            AcceptVerdict::KeepAccepting
+        );
+    }
+
+    #[test]
+    fn repeated_timeouts_reset_the_listeners() {
+        let mut watchdog = AcceptWatchdog::new();
+        for _ in 1..MAX_CONSECUTIVE_TIMEOUTS {
+            assert_eq!(
+                watchdog.reverse_pipe_timed_out(false),
+                AcceptVerdict::KeepAccepting
+            );
+        }
+        assert_eq!(
+            watchdog.reverse_pipe_timed_out(false),
+            AcceptVerdict::ResetListeners
+        );
+        watchdog.listeners_reset();
+        assert_eq!(
+            watchdog.reverse_pipe_timed_out(false),
+            AcceptVerdict::KeepAccepting,
+            "The count starts over with the new listeners"
+        );
+    }
+
+    #[test]
+    fn paired_clients_in_between_timeouts_keep_the_listeners() {
+        let mut watchdog = AcceptWatchdog::new();
+        for _ in 0..MAX_CONSECUTIVE_TIMEOUTS * 2 {
+            assert_eq!(
+                watchdog.reverse_pipe_timed_out(false),
+                AcceptVerdict::KeepAccepting
+            );
+            watchdog.reverse_pipe_accepted();
+        }
+    }
+
+    #[test]
+    fn a_gone_reverse_acceptor_resets_the_listeners_at_once() {
+        let mut watchdog = AcceptWatchdog::new();
+        assert_eq!(
+            watchdog.reverse_pipe_timed_out(true),
+            AcceptVerdict::ResetListeners
+        );
+        assert!(watchdog.diagnostics().starts_with(
+            "1 clients in a row without a reverse pipe (1 in total), last client paired never"
+        ));
+    }
+}
@@ -5,6 +5,7 @@ use zellij_utils::consts::{
 use zellij_utils::data::{Event, HttpVerb, SessionInfo, WebServerStatus};
 use zellij_utils::errors::{prelude::*, BackgroundJobContext, ContextType};
 use zellij_utils::input::layout::RunPlugin;
+use zellij_utils::input::options::IdleShutdownAction;
 use zellij_utils::shared::parse_base_url;
 
 #[cfg(feature = "web_server_capability")]
@@ -68,6 +69,10 @@ pub enum BackgroundJob {
     ClearHelpText {
         client_id: ClientId,
     },
+    MonitorPaneActivity,
+    ReportForegroundProcesses,
+    MonitorIdleSession,
+    OpenPaneWindow(String, PaneId), // String - session name
     Exit,
 }
 
@@ -95,6 +100,12 @@ impl From<&BackgroundJob> for BackgroundJobContext {
                 BackgroundJobContext::QueryZellijWebServerStatus
             },
             BackgroundJob::ClearHelpText { .. } => BackgroundJobContext::ClearHelpText,
+            BackgroundJob::MonitorPaneActivity => BackgroundJobContext::MonitorPaneActivity,
+            BackgroundJob::ReportForegroundProcesses => {
+                BackgroundJobContext::ReportForegroundProcesses
+            },
+            BackgroundJob::MonitorIdleSession => BackgroundJobContext::MonitorIdleSession,
+            BackgroundJob::OpenPaneWindow(..) => BackgroundJobContext::OpenPaneWindow,
             BackgroundJob::Exit => BackgroundJobContext::Exit,
         }
     }
@@ -107,12 +118,19 @@ static SESSION_READ_DURATION: u64 = 1000;
 static DEFAULT_SERIALIZATION_INTERVAL: u64 = 60000;
 static REPAINT_DELAY_MS: u64 = 10;
 static HELP_TEXT_DEBOUNCE_DURATION: u64 = 5000;
+static PANE_ACTIVITY_POLL_DURATION: u64 = 1000;
+static DEFAULT_PANE_IDLE_THRESHOLD: u64 = 10; // seconds
+static FOREGROUND_PROCESS_POLL_DURATION: u64 = 1000;
+static IDLE_SESSION_POLL_DURATION: u64 = 60000;
 
 pub(crate) fn background_jobs_main(
     bus: Bus<BackgroundJob>,
     serialization_interval: Option<u64>,
     disable_session_metadata: bool,
     web_server_base_url: String,
+    pane_idle_threshold: Option<u64>,
+    idle_shutdown_hours: Option<u64>,
+    idle_shutdown_action: IdleShutdownAction,
 ) -> Result<()> {
     let err_context = || "failed to write to pty".to_string();
     let mut running_jobs: HashMap<BackgroundJob, Instant> = HashMap::new();
@@ -557,6 +575,115 @@ pub(crate) fn background_jobs_main(
                     });
                 }
             },
+            BackgroundJob::MonitorPaneActivity => {
+                // like ReadAllSessionInfosOnMachine, this job is started once and keeps
+                // polling the pty backend for panes crossing the idle threshold
+                if running_jobs.contains_key(&job) {
+                    continue;
+                }
+                let idle_threshold = pane_idle_threshold.unwrap_or(DEFAULT_PANE_IDLE_THRESHOLD);
+                if idle_threshold == 0 {
+                    continue;
+                }
+                let Some(os_input) = bus.os_input.clone() else {
+                    continue;
+                };
+                running_jobs.insert(job, Instant::now());
+                runtime.spawn({
+                    let senders = bus.senders.clone();
+                    let idle_threshold = Duration::from_secs(idle_threshold);
+                    async move {
+                        loop {
+                            let events: Vec<_> = os_input
+                                .pane_activity_changes(idle_threshold)
+                                .into_iter()
+                                .map(|(terminal_id, became_idle)| {
+                                    let pane_id = PaneId::Terminal(terminal_id).into();
+                                    let event = if became_idle {
+                                        Event::PaneBecameIdle(pane_id)
+                                    } else {
+                                        Event::PaneBecameActive(pane_id)
+                                    };
+                                    (None, None, event)
+                                })
+                                .collect();
+                            if !events.is_empty() {
+                                let _ = senders.send_to_plugin(PluginInstruction::Update(events));
+                            }
+                            tokio::time::sleep(Duration::from_millis(PANE_ACTIVITY_POLL_DURATION))
+                                .await;
+                        }
+                    }
+                });
+            },
+            BackgroundJob::ReportForegroundProcesses => {
+                // this job is started once and reports the program running in each terminal pane
+                // to the screen whenever it changes
+                if running_jobs.contains_key(&job) {
+                    continue;
+                }
+                let Some(os_input) = bus.os_input.clone() else {
+                    continue;
+                };
+                running_jobs.insert(job, Instant::now());
+                runtime.spawn({
+                    let senders = bus.senders.clone();
+                    async move {
+                        let mut last_foreground_processes = BTreeMap::new();
+                        loop {
+                            let foreground_processes = os_input.foreground_processes();
+                            if foreground_processes != last_foreground_processes {
+                                let _ = senders.send_to_screen(
+                                    ScreenInstruction::UpdateForegroundProcesses(
+                                        foreground_processes.clone(),
+                                    ),
+                                );
+                                last_foreground_processes = foreground_processes;
+                            }
+                            tokio::time::sleep(Duration::from_millis(
+                                FOREGROUND_PROCESS_POLL_DURATION,
+                            ))
+                            .await;
+                        }
+                    }
+                });
+            },
+            BackgroundJob::MonitorIdleSession => {
+                // started once, asks the screen every minute whether the session has been idle
+                // for idle_shutdown_hours
+                if running_jobs.contains_key(&job) {
+                    continue;
+                }
+                let Some(idle_shutdown_hours) = idle_shutdown_hours else {
+                    continue;
+                };
+                running_jobs.insert(job, Instant::now());
+                runtime.spawn({
+                    let senders = bus.senders.clone();
+                    let idle_shutdown_after = Duration::from_secs(idle_shutdown_hours * 60 * 60);
+                    async move {
+                        loop {
+                            tokio::time::sleep(Duration::from_millis(IDLE_SESSION_POLL_DURATION))
+                                .await;
+                            let _ = senders.send_to_screen(ScreenInstruction::CheckIdleShutdown(
+                                idle_shutdown_after,
+                                idle_shutdown_action,
+                            ));
+                        }
+                    }
+                });
+            },
+            BackgroundJob::OpenPaneWindow(session_name, pane_id) => {
+                if let Err(e) = open_pane_window(&session_name, pane_id) {
+                    log::error!("Failed to open a window for pane {}: {}", pane_id, e);
+                    let _ = bus
+                        .senders
+                        .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                            vec![pane_id],
+                            "Cannot open a window for this pane!".into(),
+                        ));
+                }
+            },
             BackgroundJob::Exit => {
                 for loading_plugin in loading_plugins.values() {
                     loading_plugin.store(false, Ordering::SeqCst);
@@ -571,6 +698,57 @@ pub(crate) fn background_jobs_main(
     }
 }
 
+/// Open a new terminal window with a client attached to `session_name` that focuses `pane_id`, in
+/// Windows Terminal if it is installed and in a console window of its own otherwise.
+#[cfg(windows)]
+fn open_pane_window(session_name: &str, pane_id: PaneId) -> std::io::Result<()> {
+    use std::os::windows::process::CommandExt;
+    use std::process::{Command, Stdio};
+    use windows_sys::Win32::System::Threading::CREATE_NEW_CONSOLE;
+
+    let zellij = std::env::current_exe()?;
+    let attach_args = [
+        "attach".to_owned(),
+        session_name.to_owned(),
+        "--pane-id".to_owned(),
+        pane_id.to_string(),
+    ];
+    // the new client must not take itself for a pane of this session
+    let new_command = |program: &std::ffi::OsStr| {
+        let mut command = Command::new(program);
+        command
+            .env_remove("ZELLIJ")
+            .env_remove(zellij_utils::envs::SESSION_NAME_ENV_KEY)
+            .env_remove("ZELLIJ_PANE_ID")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        command
+    };
+    // wt.exe splits its command line into several commands at every ;
+    let opened_in_windows_terminal = new_command("wt.exe".as_ref())
+        .args(["-w", "new"])
+        .arg(&zellij)
+        .args(attach_args.iter().map(|arg| arg.replace(';', "\\;")))
+        .spawn()
+        .is_ok();
+    if !opened_in_windows_terminal {
+        new_command(zellij.as_os_str())
+            .args(&attach_args)
+            .creation_flags(CREATE_NEW_CONSOLE)
+            .spawn()?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn open_pane_window(_session_name: &str, _pane_id: PaneId) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "opening terminal windows is only supported on Windows",
+    ))
+}
+
 fn job_already_running(
     job: BackgroundJob,
     running_jobs: &mut HashMap<BackgroundJob, Instant>,
@@ -734,7 +912,7 @@ fn query_webserver_via_ipc(web_server_base_url: &str) -> Result<WebServerStatus>
                     return Ok(WebServerStatus::DifferentVersion(info.version));
                 }
             },
-            Err(_) => continue,
+            Ok(_) | Err(_) => continue,
         }
     }
 
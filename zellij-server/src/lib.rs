@@ -1,3 +1,6 @@
+#[cfg(any(test, feature = "mock_pty_backend"))]
+#[path = "os_input_output_mock.rs"]
+mod os_input_output_mock;
 #[cfg(not(windows))]
 #[path = "os_input_output_unix.rs"]
 mod os_input_output_unix;
@@ -10,6 +13,8 @@ pub mod output;
 pub mod panes;
 pub mod tab;
 
+#[cfg(any(windows, test))]
+mod accept_watchdog;
 mod background_jobs;
 mod global_async_runtime;
 mod logging_pipe;
@@ -27,6 +32,8 @@ mod ui;
 #[cfg(unix)]
 pub use daemonize;
 
+#[cfg(windows)]
+use accept_watchdog::{AcceptVerdict, AcceptWatchdog, REVERSE_PIPE_ACCEPT_TIMEOUT};
 use background_jobs::{background_jobs_main, BackgroundJob};
 use log::info;
 #[cfg(unix)]
@@ -38,6 +45,7 @@ use std::{
     path::PathBuf,
     sync::{Arc, RwLock},
     thread,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use zellij_utils::envs;
 use zellij_utils::pane_size::Size;
@@ -47,17 +55,18 @@ use zellij_utils::input::cli_assets::CliAssets;
 use wasmi::Engine;
 
 use crate::{
-    os_input_output::ServerOsApi,
+    os_input_output::{ServerOsApi, SessionResourceLimits},
     plugins::{plugin_thread_main, PluginInstruction},
     pty::{get_default_shell, pty_thread_main, Pty, PtyInstruction},
     screen::{screen_thread_main, ScreenInstruction},
     thread_bus::{Bus, ThreadSenders},
 };
-use route::{route_thread_main, NotificationEnd};
+use route::{route_thread_main, session_secret_matches, NotificationEnd};
 use zellij_utils::{
     channels::{self, ChannelWithContext, SenderWithContext},
     consts::{
-        DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE, ZELLIJ_SEEN_RELEASE_NOTES_CACHE_FILE,
+        scrollback_spill_folder_for_session, DEFAULT_SCROLL_BUFFER_SIZE, SCROLLBACK_SPILL_LINES,
+        SCROLL_BUFFER_SIZE, VERSION, ZELLIJ_SEEN_RELEASE_NOTES_CACHE_FILE,
     },
     data::{
         ConnectToSession, Event, InputMode, KeyWithModifier, LayoutInfo, LayoutWithError,
@@ -72,11 +81,12 @@ use zellij_utils::{
         get_mode_info,
         keybinds::Keybinds,
         layout::{FloatingPaneLayout, Layout, PluginAlias, Run, RunPluginOrAlias},
-        options::Options,
+        options::{IdleShutdownAction, Options},
         plugins::PluginAliases,
     },
-    ipc::{ClientAttributes, ExitReason, ServerToClientMsg},
-    shared::{default_palette, web_server_base_url},
+    ipc::{ClientAttributes, ExitReason, ServerToClientMsg, FILE_CHUNK_SIZE},
+    sessions::take_server_lock,
+    shared::{create_private_dir, default_palette, web_server_base_url, write_atomically},
 };
 
 pub type ClientId = u16;
@@ -105,6 +115,10 @@ pub enum ServerInstruction {
     ),
     AttachWatcherClient(ClientId, Size, bool), // bool -> is_web_client
     ConnStatus(ClientId),
+    ConnStatusDetailed(ClientId),
+    // usize -> tab count, usize -> pane count
+    ReportTabsAndPanes(usize, usize),
+    Heartbeat(ClientId, u64), // u64 -> heartbeat id
     Log(Vec<String>, ClientId, Option<NotificationEnd>),
     LogError(Vec<String>, ClientId, Option<NotificationEnd>),
     SwitchSession(ConnectToSession, ClientId, Option<NotificationEnd>),
@@ -123,6 +137,7 @@ pub enum ServerInstruction {
         write_config_to_disk: bool,
     },
     ConfigWrittenToDisk(Config),
+    ConfigFileFailedToLoad(String), // String - why
     FailedToWriteConfigToDisk(ClientId, Option<PathBuf>), // Pathbuf - file we failed to write
     RebindKeys {
         client_id: ClientId,
@@ -134,9 +149,18 @@ pub enum ServerInstruction {
     ShareCurrentSession(ClientId),
     StopSharingCurrentSession(ClientId),
     SendWebClientsForbidden(ClientId),
+    SetSessionSecret {
+        secret: Option<String>,
+        current_secret: Option<String>,
+        client_id: ClientId,
+        completion_tx: Option<NotificationEnd>,
+    },
+    SendSessionLocked(ClientId),
     WebServerStarted(String), // String -> base_url
     FailedToStartWebServer(String),
     ClearMouseHelpText(ClientId),
+    SendFile(ClientId, String, Vec<u8>), // String -> file name, Vec<u8> -> file contents
+    ShutDownIdleSession(IdleShutdownAction),
 }
 
 impl From<&ServerInstruction> for ServerContext {
@@ -153,6 +177,9 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::AttachClient(..) => ServerContext::AttachClient,
             ServerInstruction::AttachWatcherClient(..) => ServerContext::AttachClient,
             ServerInstruction::ConnStatus(..) => ServerContext::ConnStatus,
+            ServerInstruction::ConnStatusDetailed(..) => ServerContext::ConnStatusDetailed,
+            ServerInstruction::ReportTabsAndPanes(..) => ServerContext::ReportTabsAndPanes,
+            ServerInstruction::Heartbeat(..) => ServerContext::Heartbeat,
             ServerInstruction::Log(..) => ServerContext::Log,
             ServerInstruction::LogError(..) => ServerContext::LogError,
             ServerInstruction::SwitchSession(..) => ServerContext::SwitchSession,
@@ -181,10 +208,15 @@ impl From<&ServerInstruction> for ServerContext {
             ServerInstruction::WebServerStarted(..) => ServerContext::WebServerStarted,
             ServerInstruction::FailedToStartWebServer(..) => ServerContext::FailedToStartWebServer,
             ServerInstruction::ConfigWrittenToDisk(..) => ServerContext::ConfigWrittenToDisk,
+            ServerInstruction::ConfigFileFailedToLoad(..) => ServerContext::ConfigFileFailedToLoad,
             ServerInstruction::SendWebClientsForbidden(..) => {
                 ServerContext::SendWebClientsForbidden
             },
+            ServerInstruction::SetSessionSecret { .. } => ServerContext::SetSessionSecret,
+            ServerInstruction::SendSessionLocked(..) => ServerContext::SendSessionLocked,
             ServerInstruction::ClearMouseHelpText(..) => ServerContext::ClearMouseHelpText,
+            ServerInstruction::SendFile(..) => ServerContext::SendFile,
+            ServerInstruction::ShutDownIdleSession(..) => ServerContext::ShutDownIdleSession,
         }
     }
 }
@@ -332,6 +364,8 @@ pub(crate) struct SessionMetaData {
     // initialization because we don't want it to be overridden by
     // configuration changes, the only way it can be overwritten is by
     // explicit plugin action
+    /// Required of clients attaching, set on session initialization or by an explicit action
+    pub session_secret: Option<String>,
     screen_thread: Option<thread::JoinHandle<()>>,
     pty_thread: Option<thread::JoinHandle<()>>,
     plugin_thread: Option<thread::JoinHandle<()>>,
@@ -410,6 +444,7 @@ impl SessionMetaData {
                         .advanced_mouse_actions
                         .unwrap_or(true),
                     mouse_hover_effects: new_config.options.mouse_hover_effects.unwrap_or(true),
+                    mute_notifications: new_config.options.mute_notifications.unwrap_or(false),
                 })
                 .unwrap();
             self.senders
@@ -511,6 +546,9 @@ pub(crate) struct SessionState {
     pipes: HashMap<String, ClientId>,                 // String => pipe_id
     watchers: HashMap<ClientId, bool>, // watcher clients (read-only observers) bool -> is_web_client
     last_active_client: Option<ClientId>, // last client that sent a Key message
+    created_at: SystemTime,
+    tab_count: usize,
+    pane_count: usize,
 }
 
 impl SessionState {
@@ -520,6 +558,9 @@ impl SessionState {
             pipes: HashMap::new(),
             watchers: HashMap::new(),
             last_active_client: None,
+            created_at: SystemTime::now(),
+            tab_count: 0,
+            pane_count: 0,
         }
     }
     pub fn new_client(&mut self) -> ClientId {
@@ -651,6 +692,27 @@ impl SessionState {
             self.last_active_client = None;
         }
     }
+    pub fn set_tab_and_pane_count(&mut self, tab_count: usize, pane_count: usize) {
+        self.tab_count = tab_count;
+        self.pane_count = pane_count;
+    }
+    // what `zellij list-sessions` shows of a running session
+    pub fn detailed_status(&self) -> ServerToClientMsg {
+        // clients without a size are not attached, eg. the one asking for this
+        let attached_clients = self.clients.values().filter(|c| c.is_some()).count();
+        ServerToClientMsg::ConnStatusDetailed {
+            created_at: self
+                .created_at
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            connected_clients: (attached_clients + self.watchers.len()) as u32,
+            tab_count: self.tab_count as u32,
+            pane_count: self.pane_count as u32,
+            web_client_connected: !self.web_client_ids().is_empty()
+                || !self.web_watcher_client_ids().is_empty(),
+        }
+    }
 }
 
 pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
@@ -669,11 +731,22 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
     }
     #[cfg(windows)]
     {
-        // On Windows, the caller (commands.rs) handles spawning us as a
-        // detached process with CREATE_NO_WINDOW. No fork/daemonize needed.
+        // On Windows, the caller (spawn_server or commands.rs) spawns us as a
+        // detached process with `shared::spawn_detached`. No fork/daemonize needed.
     }
 
     envs::set_zellij("0".to_string());
+    // one server per session, locked after daemonizing so that the lock stays with this process
+    if let Err(e) = take_server_lock(&socket_path) {
+        log::error!(
+            "A server is already running at {:?}, refusing to start another one: {}",
+            socket_path,
+            e
+        );
+        std::process::exit(1);
+    }
+    #[cfg(windows)]
+    let pipe_trustees = pipe_trustees();
 
     // Eagerly create the session info cache directory so that `list-sessions`
     // can discover this session immediately, rather than waiting for the
@@ -681,15 +754,26 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
     if let Some(session_name) = socket_path.file_name().and_then(|n| n.to_str()) {
         let session_info_dir =
             zellij_utils::consts::session_info_folder_for_session(session_name);
-        if let Err(e) = std::fs::create_dir_all(&session_info_dir) {
+        if let Err(e) = create_private_dir(&session_info_dir) {
             log::warn!("Failed to create session info cache dir: {:?}", e);
         }
+        // A lock left behind by an earlier session of the same name does not apply to this one
+        let _ = std::fs::remove_file(zellij_utils::consts::session_lock_file_name(session_name));
         // Write server PID so clients can quickly check if the session is alive
         // without waiting for an IPC timeout.
         let pid_file = zellij_utils::consts::session_pid_file_name(session_name);
-        if let Err(e) = std::fs::write(&pid_file, std::process::id().to_string()) {
+        if let Err(e) = write_atomically(&pid_file, std::process::id().to_string().as_bytes()) {
             log::warn!("Failed to write server PID file: {:?}", e);
         }
+        let server_info_file = zellij_utils::consts::session_server_info_file_name(session_name);
+        if let Err(e) = write_atomically(&server_info_file, server_info(&socket_path).as_bytes()) {
+            log::warn!("Failed to write server info file: {:?}", e);
+        }
+    }
+    if let Some(sock_dir) = socket_path.parent() {
+        if let Err(e) = create_private_dir(sock_dir) {
+            log::warn!("Failed to create the socket dir: {:?}", e);
+        }
     }
 
     let (to_server, server_receiver): ChannelWithContext<ServerInstruction> = channels::bounded(50);
@@ -697,6 +781,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
     let session_data: Arc<RwLock<Option<SessionMetaData>>> = Arc::new(RwLock::new(None));
     let session_state = Arc::new(RwLock::new(SessionState::new()));
 
+    zellij_utils::crash_dump::enable();
     std::panic::set_hook({
         use zellij_utils::errors::handle_panic;
         let to_server = to_server.clone();
@@ -718,126 +803,124 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
             let socket_path = socket_path.clone();
             move || {
                 // On Unix, clean up stale socket files; on Windows named pipes
-                // are managed by the OS and don't leave filesystem artifacts.
+                // are managed by the OS and don't leave filesystem artifacts, but
+                // the pipes of a renamed session may be aliased to this name or
+                // still hold the pipe names derived from it.
                 #[cfg(unix)]
                 drop(std::fs::remove_file(&socket_path));
-                let listener = ListenerOptions::new()
-                    .name(
-                        zellij_utils::ipc::path_to_ipc_name(socket_path.as_path())
-                            .unwrap(),
-                    )
-                    .create_sync()
-                    .unwrap();
-                // set the sticky bit to avoid the socket file being potentially cleaned up
-                // https://specifications.freedesktop.org/basedir-spec/basedir-spec-latest.html states that for XDG_RUNTIME_DIR:
-                // "To ensure that your files are not removed, they should have their access time timestamp modified at least once every 6 hours of monotonic time or the 'sticky' bit should be set on the file. "
-                // It is not guaranteed that all platforms allow setting the sticky bit on sockets!
-                #[cfg(unix)]
-                drop(set_permissions(&socket_path, 0o1700));
-                // On Windows, we need a second pipe for server→client messages because
-                // synchronous named pipes deadlock when using DuplicateHandle for
-                // concurrent read/write on the same pipe instance.
-                // On Windows, a dedicated thread continuously accepts reverse-pipe
-                // connections and feeds them through a channel. The main listener
-                // loop receives from this channel with a timeout after each main
-                // pipe accept. This avoids permanently wedging the listener when
-                // a probe (e.g. list-sessions) connects to the main pipe only.
                 #[cfg(windows)]
-                let reverse_rx = {
-                    let reverse_listener = ListenerOptions::new()
-                        .name(
-                            zellij_utils::ipc::path_to_ipc_name_reverse(
-                                socket_path.as_path(),
-                            )
-                            .unwrap(),
-                        )
-                        .create_sync()
-                        .unwrap();
-                    let (tx, rx) =
-                        std::sync::mpsc::channel::<interprocess::local_socket::Stream>();
-                    std::thread::Builder::new()
-                        .name("reverse_pipe_acceptor".to_string())
-                        .spawn(move || {
-                            for stream in reverse_listener.incoming() {
-                                match stream {
-                                    Ok(s) => {
-                                        if tx.send(s).is_err() {
-                                            break;
-                                        }
-                                    },
-                                    Err(e) => {
-                                        log::warn!("Reverse pipe accept error: {:?}", e);
-                                        break;
-                                    },
-                                }
-                            }
-                        })
-                        .unwrap();
-                    rx
-                };
-                for stream in listener.incoming() {
-                    match stream {
-                        Ok(stream) => {
-                            let mut os_input = os_input.clone();
-                            let client_id = session_state.write().unwrap().new_client();
-                            #[cfg(not(windows))]
-                            let receiver = os_input.new_client(client_id, stream).unwrap();
-                            #[cfg(windows)]
-                            let receiver = {
-                                match reverse_rx
-                                    .recv_timeout(std::time::Duration::from_secs(5))
-                                {
-                                    Ok(reverse_stream) => {
-                                        os_input
-                                            .new_client_with_reverse_stream(
-                                                client_id,
-                                                stream,
-                                                reverse_stream,
-                                            )
-                                            .unwrap()
-                                    },
-                                    Err(_) => {
-                                        // Timed out — likely a probe from list-sessions
-                                        // that only connected to the main pipe.
-                                        log::warn!(
-                                            "Reverse pipe accept timed out for client {}, \
-                                             likely a probe connection",
-                                            client_id
-                                        );
-                                        session_state
-                                            .write()
-                                            .unwrap()
-                                            .remove_client(client_id);
-                                        continue;
-                                    },
-                                }
-                            };
-                            let session_data = session_data.clone();
-                            let session_state = session_state.clone();
-                            let to_server = to_server.clone();
-                            thread::Builder::new()
-                                .name("server_router".to_string())
-                                .spawn(move || {
-                                    route_thread_main(
-                                        session_data,
-                                        session_state,
-                                        os_input,
-                                        to_server,
-                                        receiver,
-                                        client_id,
-                                    )
-                                    .fatal()
-                                })
-                                .unwrap();
-                        },
-                        Err(err) => {
-                            panic!("err {:?}", err);
-                        },
+                if let Err(e) = zellij_utils::ipc::claim_pipe_name(&socket_path) {
+                    log::error!("Failed to claim the pipe names of the session: {}", e);
+                }
+                #[cfg(windows)]
+                let mut watchdog = AcceptWatchdog::new();
+                // the listeners are only recreated on Windows, when the watchdog finds their
+                // accept loop wedged
+                loop {
+                    let listener_options = ListenerOptions::new()
+                        .name(zellij_utils::ipc::path_to_ipc_name(socket_path.as_path()).unwrap());
+                    #[cfg(windows)]
+                    let listener_options = with_pipe_security(listener_options, &pipe_trustees);
+                    let listener = listener_options.create_sync().unwrap();
+                    // set the sticky bit to avoid the socket file being potentially cleaned up
+                    // https://specifications.freedesktop.org/basedir-spec/basedir-spec-latest.html states that for XDG_RUNTIME_DIR:
+                    // "To ensure that your files are not removed, they should have their access time timestamp modified at least once every 6 hours of monotonic time or the 'sticky' bit should be set on the file. "
+                    // It is not guaranteed that all platforms allow setting the sticky bit on sockets!
+                    #[cfg(unix)]
+                    drop(set_permissions(&socket_path, 0o1700));
+                    // On Windows, we need a second pipe for server→client messages because
+                    // synchronous named pipes deadlock when using DuplicateHandle for
+                    // concurrent read/write on the same pipe instance.
+                    // On Windows, a dedicated thread continuously accepts reverse-pipe
+                    // connections and feeds them through a channel. The main listener
+                    // loop receives from this channel with a timeout after each main
+                    // pipe accept. This avoids permanently wedging the listener when
+                    // a probe (e.g. list-sessions) connects to the main pipe only.
+                    #[cfg(windows)]
+                    let (reverse_rx, reverse_acceptor) =
+                        spawn_reverse_pipe_acceptor(&socket_path, &pipe_trustees);
+                    for stream in listener.incoming() {
+                        match stream {
+                            Ok(stream) => {
+                                let mut os_input = os_input.clone();
+                                let client_id = session_state.write().unwrap().new_client();
+                                #[cfg(not(windows))]
+                                let receiver = os_input.new_client(client_id, stream).unwrap();
+                                #[cfg(windows)]
+                                let receiver = {
+                                    match reverse_rx.recv_timeout(REVERSE_PIPE_ACCEPT_TIMEOUT) {
+                                        Ok(reverse_stream) => {
+                                            watchdog.reverse_pipe_accepted();
+                                            os_input
+                                                .new_client_with_reverse_stream(
+                                                    client_id,
+                                                    stream,
+                                                    reverse_stream,
+                                                )
+                                                .unwrap()
+                                        },
+                                        Err(e) => {
+                                            // Timed out — likely a probe from list-sessions
+                                            // that only connected to the main pipe.
+                                            log::warn!(
+                                                "Reverse pipe accept timed out for client {}, \
+                                                 likely a probe connection",
+                                                client_id
+                                            );
+                                            session_state.write().unwrap().remove_client(client_id);
+                                            let acceptor_gone = matches!(
+                                                e,
+                                                std::sync::mpsc::RecvTimeoutError::Disconnected
+                                            );
+                                            if watchdog.reverse_pipe_timed_out(acceptor_gone)
+                                                == AcceptVerdict::ResetListeners
+                                            {
+                                                log::error!(
+                                                    "The pipe accept loop is wedged, resetting \
+                                                     the listeners: {}",
+                                                    watchdog.diagnostics()
+                                                );
+                                                drop(reverse_rx);
+                                                stop_reverse_pipe_acceptor(
+                                                    &socket_path,
+                                                    reverse_acceptor,
+                                                );
+                                                watchdog.listeners_reset();
+                                                break;
+                                            }
+                                            continue;
+                                        },
+                                    }
+                                };
+                                let session_data = session_data.clone();
+                                let session_state = session_state.clone();
+                                let to_server = to_server.clone();
+                                thread::Builder::new()
+                                    .name("server_router".to_string())
+                                    .spawn(move || {
+                                        route_thread_main(
+                                            session_data,
+                                            session_state,
+                                            os_input,
+                                            to_server,
+                                            receiver,
+                                            client_id,
+                                        )
+                                        .fatal()
+                                    })
+                                    .unwrap();
+                            },
+                            Err(err) => {
+                                panic!("err {:?}", err);
+                            },
+                        }
                     }
                 }
             }
         });
 
+    // set when an idle session is killed rather than serialized
+    let mut delete_serialized_session = false;
     loop {
         let (instruction, mut err_ctx) = server_receiver.recv().unwrap();
         err_ctx.add_call(ContextType::IPCServer((&instruction).into()));
@@ -876,6 +959,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         rounded_corners: config.ui.pane_frames.rounded_corners,
                         hide_session_name: config.ui.pane_frames.hide_session_name,
                     },
+                    theme: None,
                 };
 
                 let mut session = init_session(
@@ -1016,6 +1100,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 let mut rlock = session_data.write().unwrap();
                 let session_data = rlock.as_mut().unwrap();
                 let config = session_data.session_configuration.saved_config.clone();
+                let client_theme = cli_assets.client_theme;
                 let runtime_config_options = match cli_assets.configuration_options {
                     Some(configuration_options) => config.options.merge(configuration_options),
                     None => config.options.clone(),
@@ -1030,6 +1115,7 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         rounded_corners: config.ui.pane_frames.rounded_corners,
                         hide_session_name: config.ui.pane_frames.hide_session_name,
                     },
+                    theme: client_theme,
                 };
 
                 let mut runtime_configuration = config.clone();
@@ -1064,6 +1150,10 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         is_web_client,
                         tab_position_to_focus,
                         pane_id_to_focus,
+                        client_attributes
+                            .theme
+                            .as_ref()
+                            .map(|_| client_attributes.style),
                     ))
                     .unwrap();
                 session_data
@@ -1339,8 +1429,54 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                         .unwrap();
                 }
             },
+            ServerInstruction::SetSessionSecret {
+                secret,
+                current_secret,
+                client_id,
+                completion_tx: _completion_tx, // dropping this ends the action
+            } => {
+                let mut session_data = session_data.write().unwrap();
+                if let Some(session_data) = session_data.as_mut() {
+                    if session_secret_matches(
+                        session_data.session_secret.as_deref(),
+                        current_secret.as_deref(),
+                    ) {
+                        mark_session_locked(secret.is_some());
+                        session_data.session_secret = secret;
+                    } else {
+                        log::warn!(
+                            "Client {} tried to change the secret of the session without the current one",
+                            client_id
+                        );
+                        let _ = os_input.send_to_client(
+                            client_id,
+                            ServerToClientMsg::LogError {
+                                lines: vec![
+                                    "The session is locked, changing its secret needs the current one (--current-secret)".to_owned(),
+                                ],
+                            },
+                        );
+                    }
+                }
+            },
+            ServerInstruction::SendSessionLocked(client_id) => {
+                let _ = os_input.send_to_client(
+                    client_id,
+                    ServerToClientMsg::Exit {
+                        exit_reason: ExitReason::SessionLocked,
+                    },
+                );
+                remove_client!(client_id, os_input, session_state);
+            },
             ServerInstruction::KillSession => {
                 let client_ids = session_state.read().unwrap().client_ids();
+                for client_id in &client_ids {
+                    let _ = os_input.send_to_client(*client_id, ServerToClientMsg::ShutdownPending);
+                }
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    let client_id = client_ids.first().copied().unwrap_or_default();
+                    shut_down_session(&session_data.senders, &*os_input, client_id, true);
+                }
                 for client_id in client_ids {
                     let _ = os_input.send_to_client(
                         client_id,
@@ -1479,6 +1615,27 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                 let _ = os_input.send_to_client(client_id, ServerToClientMsg::Connected);
                 remove_client!(client_id, os_input, session_state);
             },
+            ServerInstruction::ConnStatusDetailed(client_id) => {
+                let detailed_status = session_state.read().unwrap().detailed_status();
+                let _ = os_input.send_to_client(client_id, detailed_status);
+                remove_client!(client_id, os_input, session_state);
+            },
+            ServerInstruction::ReportTabsAndPanes(tab_count, pane_count) => {
+                session_state
+                    .write()
+                    .unwrap()
+                    .set_tab_and_pane_count(tab_count, pane_count);
+            },
+            ServerInstruction::Heartbeat(client_id, id) => {
+                // answered from here rather than from the client's route thread, so that a
+                // server too busy to handle its instructions shows up as a slow connection
+                send_to_client!(
+                    client_id,
+                    os_input,
+                    ServerToClientMsg::HeartbeatAck { id },
+                    session_state
+                );
+            },
             ServerInstruction::Log(
                 lines_to_log,
                 client_id,
@@ -1634,6 +1791,22 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     );
                 }
             },
+            ServerInstruction::ConfigFileFailedToLoad(error) => {
+                // the session keeps its current config until the file parses again
+                log::error!("Failed to reload the config file: {}", error);
+                let client_ids = session_state.read().unwrap().client_ids();
+                for client_id in client_ids {
+                    send_to_client!(
+                        client_id,
+                        os_input,
+                        ServerToClientMsg::Notify {
+                            title: "Failed to reload the config".to_owned(),
+                            body: error.clone(),
+                        },
+                        session_state
+                    );
+                }
+            },
             ServerInstruction::FailedToWriteConfigToDisk(_client_id, file_path) => {
                 session_data
                     .write()
@@ -1786,12 +1959,66 @@ pub fn start_server(mut os_input: Box<dyn ServerOsApi>, socket_path: PathBuf) {
                     .send_to_screen(ScreenInstruction::ClearMouseHelpText(client_id))
                     .unwrap();
             },
+            ServerInstruction::ShutDownIdleSession(idle_shutdown_action) => {
+                if !session_state.read().unwrap().client_ids().is_empty() {
+                    // a client attached after the screen found the session idle
+                    continue;
+                }
+                log::info!("Shutting down idle session ({})", idle_shutdown_action);
+                let save_session = idle_shutdown_action == IdleShutdownAction::Serialize;
+                if let Some(session_data) = session_data.read().unwrap().as_ref() {
+                    shut_down_session(
+                        &session_data.senders,
+                        &*os_input,
+                        ClientId::default(),
+                        save_session,
+                    );
+                }
+                delete_serialized_session = !save_session;
+                break;
+            },
+            ServerInstruction::SendFile(client_id, file_name, contents) => {
+                let mut chunks: Vec<&[u8]> = contents.chunks(FILE_CHUNK_SIZE).collect();
+                if chunks.is_empty() {
+                    // an empty file still needs its last chunk to complete the transfer
+                    chunks.push(&[]);
+                }
+                let last_chunk_index = chunks.len() - 1;
+                for (chunk_index, chunk) in chunks.into_iter().enumerate() {
+                    send_to_client!(
+                        client_id,
+                        os_input,
+                        ServerToClientMsg::FileChunk {
+                            file_name: file_name.clone(),
+                            bytes: chunk.to_vec(),
+                            is_last: chunk_index == last_chunk_index,
+                        },
+                        session_state
+                    );
+                }
+            },
         }
     }
 
     // Drop cached session data before exit.
     *session_data.write().unwrap() = None;
 
+    if let Ok(session_name) = envs::get_session_name() {
+        let _ = std::fs::remove_dir_all(scrollback_spill_folder_for_session(&session_name));
+    }
+
+    if delete_serialized_session {
+        if let Ok(session_name) = envs::get_session_name() {
+            let session_info_folder =
+                zellij_utils::consts::session_info_folder_for_session(&session_name);
+            let _ = std::fs::remove_dir_all(session_info_folder);
+        }
+    }
+
+    // the session may have been renamed since it started listening at `socket_path`
+    let socket_path = envs::get_session_name()
+        .map(|session_name| socket_path.with_file_name(session_name))
+        .unwrap_or(socket_path);
     drop(std::fs::remove_file(&socket_path));
 }
 
@@ -1813,6 +2040,35 @@ fn init_session(
             .scroll_buffer_size
             .unwrap_or(DEFAULT_SCROLL_BUFFER_SIZE),
     );
+    let _ = SCROLLBACK_SPILL_LINES.set(config_options.scrollback_spill_lines.unwrap_or(0));
+    if let Ok(session_name) = envs::get_session_name() {
+        // left behind by a server of the same name that did not exit cleanly
+        let _ = std::fs::remove_dir_all(scrollback_spill_folder_for_session(&session_name));
+    }
+    if let Some(pane_kill_grace_ms) = config_options.pane_kill_grace_ms {
+        os_input.set_kill_grace_period(std::time::Duration::from_millis(pane_kill_grace_ms));
+    }
+    if let Some(conpty) = &config_options.conpty {
+        os_input.set_conpty_host(conpty).non_fatal();
+    }
+    if let Some(pty_system) = config_options.pty_system {
+        os_input.set_pty_system(pty_system).non_fatal();
+    }
+    os_input.set_utf8_codepage(config_options.utf8_codepage.unwrap_or(false));
+    for (name, value) in config.env.inner() {
+        os_input.set_session_env_var(name.clone(), Some(value.clone()));
+    }
+    let session_resource_limits = SessionResourceLimits {
+        max_memory_mb: config_options.session_max_memory_mb,
+        max_processes: config_options.session_max_processes,
+        cpu_rate_percent: config_options.session_cpu_rate_percent,
+    };
+    if !session_resource_limits.is_empty() {
+        os_input
+            .set_session_resource_limits(session_resource_limits)
+            .non_fatal();
+    }
+    zellij_utils::logging::apply_log_options(&config_options);
 
     let (to_screen, screen_receiver): ChannelWithContext<ScreenInstruction> = channels::unbounded();
     let to_screen = SenderWithContext::new(to_screen);
@@ -1843,6 +2099,9 @@ fn init_session(
 
     let serialization_interval = config_options.serialization_interval;
     let disable_session_metadata = config_options.disable_session_metadata.unwrap_or(false);
+    let pane_idle_threshold = config_options.pane_idle_threshold;
+    let idle_shutdown_hours = config_options.idle_shutdown_hours;
+    let idle_shutdown_action = config_options.idle_shutdown_action.unwrap_or_default();
     let web_server_ip = config_options
         .web_server_ip
         .unwrap_or_else(|| IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
@@ -1884,6 +2143,7 @@ fn init_session(
                 cli_assets.is_debug,
                 config_options.scrollback_editor.clone(),
                 config_options.post_command_discovery_hook.clone(),
+                config_options.pane_output_rate_limit,
             );
 
             move || pty_thread_main(pty, layout.clone()).fatal()
@@ -2022,6 +2282,9 @@ fn init_session(
                     serialization_interval,
                     disable_session_metadata,
                     web_server_base_url,
+                    pane_idle_threshold,
+                    idle_shutdown_hours,
+                    idle_shutdown_action,
                 )
                 .fatal()
             }
@@ -2048,6 +2311,8 @@ fn init_session(
         }
     }
 
+    mark_session_locked(cli_assets.session_secret.is_some());
+
     SessionMetaData {
         senders: ThreadSenders {
             to_screen: Some(to_screen),
@@ -2074,6 +2339,188 @@ fn init_session(
         #[cfg(not(feature = "web_server_capability"))]
         web_sharing: WebSharing::Disabled,
         config_file_path: cli_assets.config_file_path,
+        session_secret: cli_assets.session_secret,
+    }
+}
+
+/// The accounts, besides the current user, the `pipe_access` option of the client that spawned
+/// this server grants access to the pipes of the session
+#[cfg(windows)]
+fn pipe_trustees() -> Vec<String> {
+    use std::str::FromStr;
+    use zellij_utils::input::options::PipeAccess;
+
+    let Ok(pipe_access) = envs::get_pipe_access() else {
+        return vec![];
+    };
+    // the panes of the session have no business with it
+    std::env::remove_var(envs::PIPE_ACCESS_ENV_KEY);
+    match PipeAccess::from_str(&pipe_access) {
+        Ok(pipe_access) => pipe_access.0,
+        Err(e) => {
+            log::error!("{}, only the current user can access the session", e);
+            vec![]
+        },
+    }
+}
+
+/// Accepts the reverse pipes of the clients on a thread of its own, handing them to the accept
+/// loop of the main pipe through the returned channel
+#[cfg(windows)]
+fn spawn_reverse_pipe_acceptor(
+    socket_path: &std::path::Path,
+    pipe_trustees: &[String],
+) -> (
+    std::sync::mpsc::Receiver<interprocess::local_socket::Stream>,
+    thread::JoinHandle<()>,
+) {
+    use interprocess::local_socket::{prelude::*, ListenerOptions};
+
+    let reverse_listener_options = ListenerOptions::new()
+        .name(zellij_utils::ipc::path_to_ipc_name_reverse(socket_path).unwrap());
+    let reverse_listener = with_pipe_security(reverse_listener_options, pipe_trustees)
+        .create_sync()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel::<interprocess::local_socket::Stream>();
+    let reverse_acceptor = thread::Builder::new()
+        .name("reverse_pipe_acceptor".to_string())
+        .spawn(move || {
+            for stream in reverse_listener.incoming() {
+                match stream {
+                    Ok(s) => {
+                        if tx.send(s).is_err() {
+                            break;
+                        }
+                    },
+                    Err(e) => {
+                        log::warn!("Reverse pipe accept error: {:?}", e);
+                        break;
+                    },
+                }
+            }
+        })
+        .unwrap();
+    (rx, reverse_acceptor)
+}
+
+/// Ends a reverse pipe acceptor whose channel was dropped, so that its listener does not take
+/// the reverse pipes of new clients from the one replacing it
+#[cfg(windows)]
+fn stop_reverse_pipe_acceptor(
+    socket_path: &std::path::Path,
+    reverse_acceptor: thread::JoinHandle<()>,
+) {
+    use interprocess::local_socket::{prelude::*, Stream as LocalSocketStream};
+
+    if reverse_acceptor.is_finished() {
+        return;
+    }
+    // it is blocked accepting, once it accepts us it fails to hand us over and ends
+    let woken = zellij_utils::ipc::path_to_ipc_name_reverse(socket_path)
+        .and_then(LocalSocketStream::connect);
+    match woken {
+        Ok(_) => {
+            let _ = reverse_acceptor.join();
+        },
+        Err(e) => log::error!("Failed to stop the reverse pipe acceptor: {}", e),
+    }
+}
+
+/// Restricts the pipe `listener_options` creates to the current user and `pipe_trustees`,
+/// falling back to only the current user if any of them cannot be granted access
+#[cfg(windows)]
+fn with_pipe_security<'a>(
+    listener_options: interprocess::local_socket::ListenerOptions<'a>,
+    pipe_trustees: &[String],
+) -> interprocess::local_socket::ListenerOptions<'a> {
+    use interprocess::os::windows::local_socket::ListenerOptionsExt;
+    use zellij_utils::ipc::session_pipe_security_descriptor;
+
+    let security_descriptor = session_pipe_security_descriptor(pipe_trustees).or_else(|e| {
+        log::error!(
+            "Failed to grant {:?} access to the session, only the current user can access it: {}",
+            pipe_trustees,
+            e
+        );
+        session_pipe_security_descriptor(&[])
+    });
+    match security_descriptor {
+        Ok(security_descriptor) => listener_options.security_descriptor(security_descriptor),
+        Err(e) => {
+            log::error!("Failed to restrict access to the session pipes: {}", e);
+            listener_options
+        },
+    }
+}
+
+/// Save the session while its panes are still running, then close them and give their processes
+/// the configured grace period to exit before the server exits
+fn shut_down_session(
+    senders: &ThreadSenders,
+    os_input: &dyn ServerOsApi,
+    client_id: ClientId,
+    save_session: bool,
+) {
+    if save_session {
+        let (completion_tx, completion_rx) = tokio::sync::oneshot::channel();
+        let _ = senders.send_to_screen(ScreenInstruction::SaveSession(
+            client_id,
+            Some(NotificationEnd::new(completion_tx)),
+        ));
+        route::wait_for_action_completion(completion_rx, "SaveSession", false);
+    }
+
+    let (completion_tx, completion_rx) = tokio::sync::oneshot::channel();
+    let _ = senders.send_to_pty(PtyInstruction::CloseAllPanes(Some(NotificationEnd::new(
+        completion_tx,
+    ))));
+    route::wait_for_action_completion(completion_rx, "CloseAllPanes", false);
+    os_input.wait_for_pending_kills();
+}
+
+/// What the server info file of the session records about this server: its PID, version, socket
+/// path and when it started
+fn server_info(socket_path: &std::path::Path) -> String {
+    use kdl::{KdlDocument, KdlNode, KdlValue};
+
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut server_info = KdlDocument::new();
+    for (name, value) in [
+        ("pid", KdlValue::Base10(std::process::id() as i64)),
+        ("version", KdlValue::String(VERSION.to_owned())),
+        (
+            "socket_path",
+            KdlValue::String(socket_path.display().to_string()),
+        ),
+        ("started_at", KdlValue::Base10(started_at as i64)),
+    ] {
+        let mut node = KdlNode::new(name);
+        node.push(value);
+        server_info.nodes_mut().push(node);
+    }
+    server_info.fmt();
+    server_info.to_string()
+}
+
+// lets clients ask for the secret before attaching, the secret itself never leaves this process
+fn mark_session_locked(is_locked: bool) {
+    let Ok(session_name) = envs::get_session_name() else {
+        return;
+    };
+    let lock_file = zellij_utils::consts::session_lock_file_name(&session_name);
+    let marked = if is_locked {
+        std::fs::write(&lock_file, "")
+    } else {
+        match std::fs::remove_file(&lock_file) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    };
+    if let Err(e) = marked {
+        log::warn!("Failed to mark session as locked: {:?}", e);
     }
 }
 
@@ -2161,7 +2608,12 @@ fn report_changes_in_config_file(
         watch_config_file_changes(config_file_path, move |new_config| {
             let to_server = to_server.clone();
             async move {
-                let _ = to_server.send(ServerInstruction::ConfigWrittenToDisk(new_config));
+                let _ = match new_config {
+                    Ok(new_config) => {
+                        to_server.send(ServerInstruction::ConfigWrittenToDisk(new_config))
+                    },
+                    Err(error) => to_server.send(ServerInstruction::ConfigFileFailedToLoad(error)),
+                };
             }
         })
         .await;
@@ -2287,7 +2739,11 @@ fn update_new_saved_config(
 
 pub fn get_engine() -> Engine {
     log::info!("Loading plugins using Wasmi interpreter");
-    Engine::default()
+    let mut config = wasmi::Config::default();
+    // validate and translate the functions of a plugin on their first call rather than while the
+    // session starts, most of a plugin's code does not run for the first frames (if ever)
+    config.compilation_mode(wasmi::CompilationMode::Lazy);
+    Engine::new(&config)
 }
 
 // TODO: move elsewhere
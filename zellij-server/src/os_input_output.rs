@@ -2,6 +2,8 @@ use crate::{panes::PaneId, ClientId};
 
 use interprocess::local_socket::Stream as LocalSocketStream;
 
+#[cfg(any(test, feature = "mock_pty_backend"))]
+pub use crate::os_input_output_mock::MockPtyBackend;
 #[cfg(not(windows))]
 use crate::os_input_output_unix::UnixPtyBackend as PtyBackendImpl;
 #[cfg(windows)]
@@ -15,7 +17,10 @@ use zellij_utils::{
     channels::TrySendError,
     data::Palette,
     errors::prelude::*,
-    input::command::{RunCommand, TerminalAction},
+    input::{
+        command::{RunCommand, TerminalAction},
+        options::{ConPtyHost, PtySystem},
+    },
     ipc::{
         ClientToServerMsg, ExitReason, IpcReceiverWithContext, IpcSenderWithContext,
         ServerToClientMsg,
@@ -31,6 +36,7 @@ use std::{
     path::PathBuf,
     process::Command,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 pub use async_trait::async_trait;
@@ -257,9 +263,26 @@ impl ClientSender {
     }
 }
 
+/// Limits shared by the processes of all panes of a session, enforced through a job object on
+/// Windows
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionResourceLimits {
+    pub max_memory_mb: Option<u64>,
+    pub max_processes: Option<u32>,
+    pub cpu_rate_percent: Option<u32>,
+}
+
+impl SessionResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_memory_mb.is_none()
+            && self.max_processes.is_none()
+            && self.cpu_rate_percent.is_none()
+    }
+}
+
 #[derive(Clone)]
 pub struct ServerOsInputOutput {
-    pty_backend: PtyBackendImpl,
+    pty_backend: Box<dyn PtyBackend>,
     client_senders: Arc<Mutex<HashMap<ClientId, ClientSender>>>,
     cached_resizes: Arc<Mutex<Option<BTreeMap<u32, (u16, u16, Option<u16>, Option<u16>)>>>>,
 }
@@ -281,6 +304,67 @@ impl AsyncReader for NullAsyncReader {
     }
 }
 
+/// The platform-specific part of [`ServerOsInputOutput`]: spawning terminals and talking to
+/// their pseudo terminals and processes. Implemented by the Unix and Windows (ConPTY)
+/// backends, and by an in-memory [`MockPtyBackend`] for tests.
+pub trait PtyBackend: Send + Sync {
+    /// Spawn `cmd` (or `failover_cmd` if `cmd` cannot be started) in a new pseudo terminal
+    /// registered as `terminal_id`. Returns a reader for the terminal's output and the PID of
+    /// the spawned process.
+    fn spawn_terminal(
+        &self,
+        cmd: RunCommand,
+        failover_cmd: Option<RunCommand>,
+        quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        terminal_id: u32,
+    ) -> Result<(Box<dyn AsyncReader>, u32)>;
+    fn set_terminal_size(
+        &self,
+        terminal_id: u32,
+        cols: u16,
+        rows: u16,
+        width_in_pixels: Option<u16>,
+        height_in_pixels: Option<u16>,
+    ) -> Result<()>;
+    fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize>;
+    fn tcdrain(&self, terminal_id: u32) -> Result<()>;
+    fn set_kill_grace_period(&self, _grace_period: Duration) {}
+    fn set_conpty_host(&self, _host: &ConPtyHost) -> Result<()> {
+        Ok(())
+    }
+    fn set_pty_system(&self, _pty_system: PtySystem) -> Result<()> {
+        Ok(())
+    }
+    fn set_utf8_codepage(&self, _enabled: bool) {}
+    fn set_session_resource_limits(&self, _limits: SessionResourceLimits) -> Result<()> {
+        Ok(())
+    }
+    fn set_session_env_var(&self, _name: String, _value: Option<String>) {}
+    fn kill(&self, pid: u32) -> Result<()>;
+    fn wait_for_pending_kills(&self) {}
+    fn force_kill(&self, pid: u32) -> Result<()>;
+    fn send_sigint(&self, pid: u32) -> Result<()>;
+    fn reserve_terminal_id(&self, terminal_id: u32);
+    fn clear_terminal_id(&self, terminal_id: u32);
+    fn next_terminal_id(&self) -> Option<u32>;
+    fn pane_activity_changes(&self, _idle_threshold: Duration) -> Vec<(u32, bool)> {
+        vec![]
+    }
+    fn take_failover_command(&self, _terminal_id: u32) -> Option<RunCommand> {
+        None
+    }
+    fn foreground_processes(&self) -> BTreeMap<u32, String> {
+        BTreeMap::new()
+    }
+    fn box_clone(&self) -> Box<dyn PtyBackend>;
+}
+
+impl Clone for Box<dyn PtyBackend> {
+    fn clone(&self) -> Box<dyn PtyBackend> {
+        self.box_clone()
+    }
+}
+
 /// The `ServerOsApi` trait represents an abstract interface to the features of an operating system that
 /// Zellij server requires.
 pub trait ServerOsApi: Send + Sync {
@@ -312,6 +396,9 @@ pub trait ServerOsApi: Send + Sync {
     fn tcdrain(&self, terminal_id: u32) -> Result<()>;
     /// Terminate the process with process ID `pid`. (SIGHUP)
     fn kill(&self, pid: u32) -> Result<()>;
+    /// Block until the processes given a grace period by `kill` have exited or were forcefully
+    /// terminated.
+    fn wait_for_pending_kills(&self) {}
     /// Terminate the process with process ID `pid`. (SIGKILL)
     fn force_kill(&self, pid: u32) -> Result<()>;
     /// Send SIGINT to the process with process ID `pid`
@@ -361,6 +448,46 @@ pub trait ServerOsApi: Send + Sync {
     fn clear_terminal_id(&self, terminal_id: u32) -> Result<()>;
     fn cache_resizes(&mut self) {}
     fn apply_cached_resizes(&mut self) {}
+    /// Returns the terminals whose activity state changed since the last call, as
+    /// (terminal_id, became_idle). A terminal is idle once it had neither output nor input
+    /// for `idle_threshold`.
+    fn pane_activity_changes(&self, _idle_threshold: Duration) -> Vec<(u32, bool)> {
+        vec![]
+    }
+    /// Returns the executable name of the process running in the foreground of each terminal,
+    /// for terminals whose shell is currently running another program.
+    fn foreground_processes(&self) -> BTreeMap<u32, String> {
+        BTreeMap::new()
+    }
+    /// Returns the command `terminal_id` was originally asked to run if it was started with its
+    /// failover command instead. The command is only returned once.
+    fn take_failover_command(&self, _terminal_id: u32) -> Option<RunCommand> {
+        None
+    }
+    /// Give processes this long to exit on their own after `kill` (and `send_sigint` on
+    /// Windows) before forcefully terminating them. Zero terminates immediately.
+    fn set_kill_grace_period(&self, _grace_period: Duration) {}
+    /// Select the ConPTY implementation that hosts panes spawned from now on. Only has an
+    /// effect on Windows.
+    fn set_conpty_host(&self, _host: &ConPtyHost) -> Result<()> {
+        Ok(())
+    }
+    /// Select the pseudo terminal implementation that runs panes spawned from now on. Only has
+    /// an effect on Windows.
+    fn set_pty_system(&self, _pty_system: PtySystem) -> Result<()> {
+        Ok(())
+    }
+    /// Switch the console of panes spawned from now on to the UTF-8 codepage. Only has an
+    /// effect on Windows.
+    fn set_utf8_codepage(&self, _enabled: bool) {}
+    /// Confine the processes of panes spawned from now on to `limits`, shared by all of them.
+    /// Only has an effect on Windows.
+    fn set_session_resource_limits(&self, _limits: SessionResourceLimits) -> Result<()> {
+        Ok(())
+    }
+    /// Set `name` to `value` in the environment of panes spawned from now on, or remove it from
+    /// their environment if `value` is `None`
+    fn set_session_env_var(&self, _name: String, _value: Option<String>) {}
 }
 
 impl ServerOsApi for ServerOsInputOutput {
@@ -401,7 +528,7 @@ impl ServerOsApi for ServerOsInputOutput {
             .spawn_terminal(cmd, failover_cmd, quit_cb, terminal_id)
             .with_context(err_context)?;
 
-        Ok((terminal_id, async_reader, Some(child_fd as u32)))
+        Ok((terminal_id, async_reader, Some(child_fd)))
     }
     fn reserve_terminal_id(&self) -> Result<u32> {
         let terminal_id = self
@@ -423,6 +550,9 @@ impl ServerOsApi for ServerOsInputOutput {
     fn kill(&self, pid: u32) -> Result<()> {
         self.pty_backend.kill(pid)
     }
+    fn wait_for_pending_kills(&self) {
+        self.pty_backend.wait_for_pending_kills()
+    }
     fn force_kill(&self, pid: u32) -> Result<()> {
         self.pty_backend.force_kill(pid)
     }
@@ -626,12 +756,39 @@ impl ServerOsApi for ServerOsInputOutput {
         let (async_reader, child_fd) =
             self.pty_backend
                 .spawn_terminal(run_command, None, quit_cb, terminal_id)?;
-        Ok((async_reader, Some(child_fd as u32)))
+        Ok((async_reader, Some(child_fd)))
     }
     fn clear_terminal_id(&self, terminal_id: u32) -> Result<()> {
         self.pty_backend.clear_terminal_id(terminal_id);
         Ok(())
     }
+    fn pane_activity_changes(&self, idle_threshold: Duration) -> Vec<(u32, bool)> {
+        self.pty_backend.pane_activity_changes(idle_threshold)
+    }
+    fn foreground_processes(&self) -> BTreeMap<u32, String> {
+        self.pty_backend.foreground_processes()
+    }
+    fn take_failover_command(&self, terminal_id: u32) -> Option<RunCommand> {
+        self.pty_backend.take_failover_command(terminal_id)
+    }
+    fn set_kill_grace_period(&self, grace_period: Duration) {
+        self.pty_backend.set_kill_grace_period(grace_period)
+    }
+    fn set_conpty_host(&self, host: &ConPtyHost) -> Result<()> {
+        self.pty_backend.set_conpty_host(host)
+    }
+    fn set_pty_system(&self, pty_system: PtySystem) -> Result<()> {
+        self.pty_backend.set_pty_system(pty_system)
+    }
+    fn set_utf8_codepage(&self, enabled: bool) {
+        self.pty_backend.set_utf8_codepage(enabled)
+    }
+    fn set_session_resource_limits(&self, limits: SessionResourceLimits) -> Result<()> {
+        self.pty_backend.set_session_resource_limits(limits)
+    }
+    fn set_session_env_var(&self, name: String, value: Option<String>) {
+        self.pty_backend.set_session_env_var(name, value)
+    }
     fn cache_resizes(&mut self) {
         if self.cached_resizes.lock().unwrap().is_none() {
             *self.cached_resizes.lock().unwrap() = Some(BTreeMap::new());
@@ -662,11 +819,19 @@ impl Clone for Box<dyn ServerOsApi> {
 }
 
 pub fn get_server_os_input() -> Result<ServerOsInputOutput, std::io::Error> {
-    Ok(ServerOsInputOutput {
-        pty_backend: PtyBackendImpl::new()?,
+    Ok(get_server_os_input_with_backend(Box::new(
+        PtyBackendImpl::new()?,
+    )))
+}
+
+/// Like [`get_server_os_input`], but with terminals spawned by `pty_backend` instead of the
+/// platform's pseudo terminals.
+pub fn get_server_os_input_with_backend(pty_backend: Box<dyn PtyBackend>) -> ServerOsInputOutput {
+    ServerOsInputOutput {
+        pty_backend,
         client_senders: Arc::new(Mutex::new(HashMap::new())),
         cached_resizes: Arc::new(Mutex::new(None)),
-    })
+    }
 }
 
 use crate::pty_writer::PtyWriteInstruction;
@@ -0,0 +1,282 @@
+use crate::os_input_output::{AsyncReader, PtyBackend};
+use crate::panes::PaneId;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use zellij_utils::{errors::prelude::*, input::command::RunCommand};
+
+use async_trait::async_trait;
+
+/// PIDs handed out by the mock start here so they are easy to tell apart from real ones
+const FIRST_MOCK_PID: u32 = 10_000;
+
+/// Output of a mock terminal, fed through [`MockPtyBackend::send_output`]. Returns EOF once the
+/// terminal exited.
+struct MockAsyncReader {
+    rx: UnboundedReceiver<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+#[async_trait]
+impl AsyncReader for MockAsyncReader {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        if self.pending.is_empty() {
+            match self.rx.recv().await {
+                Some(bytes) => self.pending = bytes,
+                None => return Ok(0), // EOF
+            }
+        }
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        Ok(len)
+    }
+}
+
+struct MockTerminal {
+    pid: u32,
+    command: RunCommand,
+    /// `None` once the terminal exited
+    output: Option<UnboundedSender<Vec<u8>>>,
+    quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+    input: Vec<u8>,
+    size: Option<(u16, u16)>,
+}
+
+#[derive(Default)]
+struct MockPtyState {
+    /// `None` for terminal ids that were reserved but not spawned yet
+    terminals: BTreeMap<u32, Option<MockTerminal>>,
+    next_pid: u32,
+    killed_pids: Vec<u32>,
+    interrupted_pids: Vec<u32>,
+}
+
+/// An in-memory [`PtyBackend`] that spawns no processes, so the pty and screen threads can be
+/// tested deterministically. Tests play the part of the spawned programs: they feed output with
+/// [`send_output`](Self::send_output), end them with [`exit`](Self::exit) and inspect what the
+/// server wrote to them.
+///
+/// Clones share their state, so a test can keep a clone of the backend it passed to
+/// [`get_server_os_input_with_backend`](crate::os_input_output::get_server_os_input_with_backend).
+#[derive(Clone, Default)]
+pub struct MockPtyBackend {
+    state: Arc<Mutex<MockPtyState>>,
+}
+
+impl MockPtyBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `bytes` the next output of `terminal_id`. Returns false if the terminal is not
+    /// running.
+    pub fn send_output(&self, terminal_id: u32, bytes: &[u8]) -> bool {
+        let state = self.state.lock().unwrap();
+        match state.terminals.get(&terminal_id) {
+            Some(Some(MockTerminal {
+                output: Some(output),
+                ..
+            })) => output.send(bytes.to_vec()).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// End the program running in `terminal_id` with `exit_status`: its output reaches EOF and
+    /// its quit callback is called, as it would be for a real process.
+    pub fn exit(&self, terminal_id: u32, exit_status: Option<i32>) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(Some(terminal)) = state.terminals.get_mut(&terminal_id) {
+            if terminal.output.take().is_some() {
+                (terminal.quit_cb)(
+                    PaneId::Terminal(terminal_id),
+                    exit_status,
+                    terminal.command.clone(),
+                );
+            }
+        }
+    }
+
+    /// Everything the server wrote to the standard input of `terminal_id` so far
+    pub fn written_input(&self, terminal_id: u32) -> Vec<u8> {
+        self.with_terminal(terminal_id, |terminal| terminal.input.clone())
+            .unwrap_or_default()
+    }
+
+    /// The last (cols, rows) `terminal_id` was resized to
+    pub fn terminal_size(&self, terminal_id: u32) -> Option<(u16, u16)> {
+        self.with_terminal(terminal_id, |terminal| terminal.size)
+            .flatten()
+    }
+
+    pub fn spawned_command(&self, terminal_id: u32) -> Option<RunCommand> {
+        self.with_terminal(terminal_id, |terminal| terminal.command.clone())
+    }
+
+    pub fn pid(&self, terminal_id: u32) -> Option<u32> {
+        self.with_terminal(terminal_id, |terminal| terminal.pid)
+    }
+
+    /// PIDs passed to `kill` or `force_kill`, in order
+    pub fn killed_pids(&self) -> Vec<u32> {
+        self.state.lock().unwrap().killed_pids.clone()
+    }
+
+    /// PIDs passed to `send_sigint`, in order
+    pub fn interrupted_pids(&self) -> Vec<u32> {
+        self.state.lock().unwrap().interrupted_pids.clone()
+    }
+
+    fn with_terminal<T>(&self, terminal_id: u32, f: impl FnOnce(&MockTerminal) -> T) -> Option<T> {
+        match self.state.lock().unwrap().terminals.get(&terminal_id) {
+            Some(Some(terminal)) => Some(f(terminal)),
+            _ => None,
+        }
+    }
+
+    fn terminal_id_of_pid(&self, pid: u32) -> Option<u32> {
+        self.state
+            .lock()
+            .unwrap()
+            .terminals
+            .iter()
+            .find_map(|(terminal_id, terminal)| match terminal {
+                Some(terminal) if terminal.pid == pid => Some(*terminal_id),
+                _ => None,
+            })
+    }
+
+    fn record_kill(&self, pid: u32) {
+        self.state.lock().unwrap().killed_pids.push(pid);
+        if let Some(terminal_id) = self.terminal_id_of_pid(pid) {
+            self.exit(terminal_id, None);
+        }
+    }
+}
+
+impl PtyBackend for MockPtyBackend {
+    fn spawn_terminal(
+        &self,
+        cmd: RunCommand,
+        _failover_cmd: Option<RunCommand>,
+        quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        terminal_id: u32,
+    ) -> Result<(Box<dyn AsyncReader>, u32)> {
+        let mut state = self.state.lock().unwrap();
+        let pid = FIRST_MOCK_PID + state.next_pid;
+        state.next_pid += 1;
+        let (output, rx) = unbounded_channel();
+        state.terminals.insert(
+            terminal_id,
+            Some(MockTerminal {
+                pid,
+                command: cmd,
+                output: Some(output),
+                quit_cb,
+                input: vec![],
+                size: None,
+            }),
+        );
+        let async_reader = Box::new(MockAsyncReader {
+            rx,
+            pending: vec![],
+        }) as Box<dyn AsyncReader>;
+        Ok((async_reader, pid))
+    }
+
+    fn set_terminal_size(
+        &self,
+        terminal_id: u32,
+        cols: u16,
+        rows: u16,
+        _width_in_pixels: Option<u16>,
+        _height_in_pixels: Option<u16>,
+    ) -> Result<()> {
+        match self.state.lock().unwrap().terminals.get_mut(&terminal_id) {
+            Some(Some(terminal)) => {
+                if cols > 0 && rows > 0 {
+                    terminal.size = Some((cols, rows));
+                }
+            },
+            _ => {
+                Err::<(), _>(anyhow!("failed to find terminal for id {terminal_id}"))
+                    .with_context(|| {
+                        format!(
+                            "failed to set terminal id {} to size ({}, {})",
+                            terminal_id, rows, cols
+                        )
+                    })
+                    .non_fatal();
+            },
+        }
+        Ok(())
+    }
+
+    fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize> {
+        match self.state.lock().unwrap().terminals.get_mut(&terminal_id) {
+            Some(Some(terminal)) => {
+                terminal.input.extend_from_slice(buf);
+                Ok(buf.len())
+            },
+            _ => Err(anyhow!("could not find terminal"))
+                .with_context(|| format!("failed to write to stdin of TTY ID {}", terminal_id)),
+        }
+    }
+
+    fn tcdrain(&self, terminal_id: u32) -> Result<()> {
+        match self.state.lock().unwrap().terminals.get(&terminal_id) {
+            Some(Some(_)) => Ok(()),
+            _ => Err(anyhow!("could not find terminal"))
+                .with_context(|| format!("failed to tcdrain to TTY ID {}", terminal_id)),
+        }
+    }
+
+    fn kill(&self, pid: u32) -> Result<()> {
+        self.record_kill(pid);
+        Ok(())
+    }
+
+    fn force_kill(&self, pid: u32) -> Result<()> {
+        self.record_kill(pid);
+        Ok(())
+    }
+
+    fn send_sigint(&self, pid: u32) -> Result<()> {
+        self.state.lock().unwrap().interrupted_pids.push(pid);
+        Ok(())
+    }
+
+    fn reserve_terminal_id(&self, terminal_id: u32) {
+        self.state
+            .lock()
+            .unwrap()
+            .terminals
+            .insert(terminal_id, None);
+    }
+
+    fn clear_terminal_id(&self, terminal_id: u32) {
+        self.state.lock().unwrap().terminals.remove(&terminal_id);
+    }
+
+    fn next_terminal_id(&self) -> Option<u32> {
+        self.state
+            .lock()
+            .unwrap()
+            .terminals
+            .keys()
+            .copied()
+            .collect::<BTreeSet<u32>>()
+            .last()
+            .map(|l| l + 1)
+            .or(Some(0))
+    }
+
+    fn box_clone(&self) -> Box<dyn PtyBackend> {
+        Box::new((*self).clone())
+    }
+}
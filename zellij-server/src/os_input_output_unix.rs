@@ -1,4 +1,4 @@
-use crate::os_input_output::{command_exists, AsyncReader};
+use crate::os_input_output::{command_exists, AsyncReader, PtyBackend};
 use crate::panes::PaneId;
 
 use nix::{
@@ -28,7 +28,7 @@ use std::{
     process::{Child, Command},
     sync::{Arc, Mutex},
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use zellij_utils::{errors::prelude::*, input::command::RunCommand};
@@ -178,11 +178,32 @@ fn handle_command_exit(mut child: Child) -> Result<Option<i32>> {
     }
 }
 
+/// Blocks until the child process `pid` exits, leaving it unreaped so its PID
+/// cannot be reused until the caller waits on it.
+fn wait_for_exit_without_reaping(pid: u32) {
+    loop {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if res == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            break;
+        }
+    }
+}
+
 fn handle_openpty(
     open_pty_res: OpenptyResult,
     cmd: RunCommand,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
     terminal_id: u32,
+    session_env: &BTreeMap<String, Option<String>>,
+    live_children: Arc<Mutex<BTreeSet<u32>>>,
 ) -> Result<(RawFd, RawFd)> {
     let err_context = |cmd: &RunCommand| {
         format!(
@@ -216,6 +237,12 @@ fn handle_openpty(
                 );
             }
         }
+        for (name, value) in session_env {
+            match value {
+                Some(value) => command.env(name, value),
+                None => command.env_remove(name),
+            };
+        }
         command
             .args(&cmd.args)
             .env("ZELLIJ_PANE_ID", &format!("{}", terminal_id))
@@ -231,7 +258,12 @@ fn handle_openpty(
     };
 
     let child_id = child.id();
+    live_children.lock().unwrap().insert(child_id);
     thread::spawn(move || {
+        // stop signalling the PID before reaping it, after which it may be
+        // reused by an unrelated process
+        wait_for_exit_without_reaping(child_id);
+        live_children.lock().unwrap().remove(&child_id);
         child.wait().with_context(|| err_context(&cmd)).fatal();
         let exit_status = handle_command_exit(child)
             .with_context(|| err_context(&cmd))
@@ -251,18 +283,33 @@ fn handle_terminal(
     orig_termios: Option<termios::Termios>,
     quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
     terminal_id: u32,
+    session_env: &BTreeMap<String, Option<String>>,
+    live_children: Arc<Mutex<BTreeSet<u32>>>,
 ) -> Result<(RawFd, RawFd)> {
     let err_context = || "failed to spawn child terminal".to_string();
 
     // Create a pipe to allow the child the communicate the shell's pid to its
     // parent.
     match openpty(None, &orig_termios) {
-        Ok(open_pty_res) => handle_openpty(open_pty_res, cmd, quit_cb, terminal_id),
+        Ok(open_pty_res) => handle_openpty(
+            open_pty_res,
+            cmd,
+            quit_cb,
+            terminal_id,
+            session_env,
+            live_children,
+        ),
         Err(e) => match failover_cmd {
-            Some(failover_cmd) => {
-                handle_terminal(failover_cmd, None, orig_termios, quit_cb, terminal_id)
-                    .with_context(err_context)
-            },
+            Some(failover_cmd) => handle_terminal(
+                failover_cmd,
+                None,
+                orig_termios,
+                quit_cb,
+                terminal_id,
+                session_env,
+                live_children,
+            )
+            .with_context(err_context),
             None => Err::<(i32, i32), _>(e)
                 .context("failed to start pty")
                 .with_context(err_context)
@@ -271,11 +318,34 @@ fn handle_terminal(
     }
 }
 
+/// How often `kill` checks whether a process exited during its grace period.
+const KILL_GRACE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Sends `signal` to `pid` only if it is a spawned process that has not exited
+/// yet. The lock is held while signalling so the process cannot be reaped, and
+/// its PID reused, in between.
+fn signal_live_child(live_children: &Mutex<BTreeSet<u32>>, pid: u32, signal: Signal) {
+    let live_children = live_children.lock().unwrap();
+    if live_children.contains(&pid) {
+        let _ = kill(unistd::Pid::from_raw(pid as i32), Some(signal));
+    }
+}
+
 /// The Unix PTY backend. Manages native PTY file descriptors and signals.
 #[derive(Clone)]
 pub(crate) struct UnixPtyBackend {
     orig_termios: Arc<Mutex<Option<termios::Termios>>>,
     terminal_id_to_raw_fd: Arc<Mutex<BTreeMap<u32, Option<RawFd>>>>,
+    /// How long `kill` waits for a process to exit after SIGHUP before
+    /// sending it SIGKILL, zero never escalates
+    kill_grace_period: Arc<Mutex<Duration>>,
+    /// The threads of `kill` waiting for processes to exit
+    pending_kills: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
+    /// PIDs of spawned processes that have not exited yet, the only ones it
+    /// is safe to signal
+    live_children: Arc<Mutex<BTreeSet<u32>>>,
+    /// Variables set in (or removed from, if `None`) the environment of new panes
+    session_env: Arc<Mutex<BTreeMap<String, Option<String>>>>,
 }
 
 impl UnixPtyBackend {
@@ -287,27 +357,36 @@ impl UnixPtyBackend {
         Ok(Self {
             orig_termios: Arc::new(Mutex::new(current_termios)),
             terminal_id_to_raw_fd: Arc::new(Mutex::new(BTreeMap::new())),
+            kill_grace_period: Arc::new(Mutex::new(Duration::ZERO)),
+            pending_kills: Arc::new(Mutex::new(Vec::new())),
+            live_children: Arc::new(Mutex::new(BTreeSet::new())),
+            session_env: Arc::new(Mutex::new(BTreeMap::new())),
         })
     }
+}
 
-    pub fn spawn_terminal(
+impl PtyBackend for UnixPtyBackend {
+    fn spawn_terminal(
         &self,
         cmd: RunCommand,
         failover_cmd: Option<RunCommand>,
         quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
         terminal_id: u32,
-    ) -> Result<(Box<dyn AsyncReader>, RawFd)> {
+    ) -> Result<(Box<dyn AsyncReader>, u32)> {
         let orig_termios = self
             .orig_termios
             .lock()
             .to_anyhow()
             .context("failed to lock orig_termios")?;
+        let session_env = self.session_env.lock().to_anyhow()?.clone();
         let (pid_primary, child_fd) = handle_terminal(
             cmd,
             failover_cmd,
             orig_termios.clone(),
             quit_cb,
             terminal_id,
+            &session_env,
+            self.live_children.clone(),
         )?;
         self.terminal_id_to_raw_fd
             .lock()
@@ -317,10 +396,10 @@ impl UnixPtyBackend {
             RawFdAsyncReader::new(pid_primary)
                 .map_err(|e| anyhow::anyhow!("failed to create async reader: {}", e))?,
         ) as Box<dyn AsyncReader>;
-        Ok((async_reader, child_fd))
+        Ok((async_reader, child_fd as u32))
     }
 
-    pub fn set_terminal_size(
+    fn set_terminal_size(
         &self,
         terminal_id: u32,
         cols: u16,
@@ -355,7 +434,7 @@ impl UnixPtyBackend {
         Ok(())
     }
 
-    pub fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize> {
+    fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize> {
         let err_context = || format!("failed to write to stdin of TTY ID {}", terminal_id);
 
         match self
@@ -370,7 +449,7 @@ impl UnixPtyBackend {
         }
     }
 
-    pub fn tcdrain(&self, terminal_id: u32) -> Result<()> {
+    fn tcdrain(&self, terminal_id: u32) -> Result<()> {
         let err_context = || format!("failed to tcdrain to TTY ID {}", terminal_id);
 
         match self
@@ -385,36 +464,93 @@ impl UnixPtyBackend {
         }
     }
 
-    pub fn kill(&self, pid: u32) -> Result<()> {
-        let _ = kill(unistd::Pid::from_raw(pid as i32), Some(Signal::SIGHUP));
+    fn set_kill_grace_period(&self, grace_period: Duration) {
+        *self.kill_grace_period.lock().unwrap() = grace_period;
+    }
+
+    fn set_session_env_var(&self, name: String, value: Option<String>) {
+        self.session_env.lock().unwrap().insert(name, value);
+    }
+
+    fn kill(&self, pid: u32) -> Result<()> {
+        let grace_period = *self.kill_grace_period.lock().to_anyhow()?;
+        signal_live_child(&self.live_children, pid, Signal::SIGHUP);
+        if !grace_period.is_zero() {
+            let live_children = self.live_children.clone();
+            let pending_kill = thread::Builder::new()
+                .name(format!("pty_kill_{}", pid))
+                .spawn(move || {
+                    let deadline = Instant::now() + grace_period;
+                    while live_children.lock().unwrap().contains(&pid) {
+                        if Instant::now() >= deadline {
+                            log::info!(
+                                "PID {} did not exit within {:?} of SIGHUP, sending SIGKILL",
+                                pid,
+                                grace_period
+                            );
+                            signal_live_child(&live_children, pid, Signal::SIGKILL);
+                            break;
+                        }
+                        thread::sleep(KILL_GRACE_POLL_INTERVAL);
+                    }
+                })
+                .with_context(|| format!("failed to schedule SIGKILL for process {}", pid))?;
+            let mut pending_kills = self.pending_kills.lock().to_anyhow()?;
+            pending_kills.retain(|handle| !handle.is_finished());
+            pending_kills.push(pending_kill);
+        }
         Ok(())
     }
 
-    pub fn force_kill(&self, pid: u32) -> Result<()> {
-        let _ = kill(unistd::Pid::from_raw(pid as i32), Some(Signal::SIGKILL));
+    fn wait_for_pending_kills(&self) {
+        let pending_kills = std::mem::take(&mut *self.pending_kills.lock().unwrap());
+        for pending_kill in pending_kills {
+            let _ = pending_kill.join();
+        }
+    }
+
+    fn force_kill(&self, pid: u32) -> Result<()> {
+        signal_live_child(&self.live_children, pid, Signal::SIGKILL);
         Ok(())
     }
 
-    pub fn send_sigint(&self, pid: u32) -> Result<()> {
-        let _ = kill(unistd::Pid::from_raw(pid as i32), Some(Signal::SIGINT));
+    fn send_sigint(&self, pid: u32) -> Result<()> {
+        // SIGINT is already a request the process can handle, so the grace
+        // period does not apply here
+        signal_live_child(&self.live_children, pid, Signal::SIGINT);
         Ok(())
     }
 
-    pub fn reserve_terminal_id(&self, terminal_id: u32) {
+    fn reserve_terminal_id(&self, terminal_id: u32) {
         self.terminal_id_to_raw_fd
             .lock()
             .unwrap()
             .insert(terminal_id, None);
     }
 
-    pub fn clear_terminal_id(&self, terminal_id: u32) {
+    fn clear_terminal_id(&self, terminal_id: u32) {
         self.terminal_id_to_raw_fd
             .lock()
             .unwrap()
             .remove(&terminal_id);
     }
 
-    pub fn next_terminal_id(&self) -> Option<u32> {
+    fn pane_activity_changes(&self, _idle_threshold: Duration) -> Vec<(u32, bool)> {
+        // activity tracking is only implemented for the ConPTY backend
+        vec![]
+    }
+
+    fn take_failover_command(&self, _terminal_id: u32) -> Option<RunCommand> {
+        // here the failover command is only used if the pty could not be opened, which is logged
+        None
+    }
+
+    fn foreground_processes(&self) -> BTreeMap<u32, String> {
+        // foreground process tracking is only implemented for the ConPTY backend
+        BTreeMap::new()
+    }
+
+    fn next_terminal_id(&self) -> Option<u32> {
         self.terminal_id_to_raw_fd
             .lock()
             .unwrap()
@@ -425,4 +561,8 @@ impl UnixPtyBackend {
             .map(|l| l + 1)
             .or(Some(0))
     }
+
+    fn box_clone(&self) -> Box<dyn PtyBackend> {
+        Box::new((*self).clone())
+    }
 }
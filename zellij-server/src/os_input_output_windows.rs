@@ -1,83 +1,226 @@
-use crate::os_input_output::{command_exists, AsyncReader};
+use crate::os_input_output::{command_exists, AsyncReader, PtyBackend, SessionResourceLimits};
 use crate::panes::PaneId;
 
-use portable_pty::{CommandBuilder, MasterPty, PtySize};
+use portable_pty::win::error::ConPtyError;
+use portable_pty::win::PtySystemKind;
+use portable_pty::{CommandBuilder, MasterPty, PtySize, SlavePty};
 
 use std::{
     collections::{BTreeMap, BTreeSet},
-    io::{self, Read, Write},
+    io::{self, Write},
+    os::windows::io::{FromRawHandle, IntoRawHandle, OwnedHandle},
     sync::{Arc, Mutex},
     thread,
+    time::{Duration, Instant},
 };
 
-use zellij_utils::{errors::prelude::*, input::command::RunCommand};
+use tokio::{io::AsyncReadExt, net::windows::named_pipe::NamedPipeServer};
+use zellij_utils::{
+    errors::prelude::*,
+    input::{
+        command::RunCommand,
+        options::{ConPtyHost, PtySystem},
+    },
+};
 
 pub use async_trait::async_trait;
 
-/// Wraps a `portable-pty` reader, bridging blocking I/O to async via a channel.
+/// Reads the output of a pseudoconsole straight from tokio, through the end
+/// of its output pipe that `portable-pty` opened for overlapped I/O.
 ///
-/// A background thread reads from the PTY master in a loop and sends chunks
-/// through a `tokio::sync::mpsc` channel. The `AsyncReader::read()` impl
-/// awaits on the channel receiver.
+/// The pipe is registered with the runtime on the first read, since panes are
+/// spawned outside of it.
 struct WindowsAsyncReader {
-    rx: tokio::sync::mpsc::Receiver<io::Result<Vec<u8>>>,
-    pending: Vec<u8>,
+    pending: Option<OwnedHandle>,
+    pipe: Option<NamedPipeServer>,
+    terminal_id: u32,
+    pane_activity: Arc<Mutex<BTreeMap<u32, PaneActivity>>>,
+    recover: Box<dyn FnMut() -> Option<OwnedHandle> + Send + Sync>,
 }
 
 impl WindowsAsyncReader {
-    fn new(mut reader: Box<dyn Read + Send>) -> Self {
-        let (tx, rx) = tokio::sync::mpsc::channel(64);
+    /// `recover` is called when reading fails, and returns the output pipe of
+    /// a recreated pseudoconsole to continue with if the pane could be saved.
+    fn new(
+        pipe: OwnedHandle,
+        terminal_id: u32,
+        pane_activity: Arc<Mutex<BTreeMap<u32, PaneActivity>>>,
+        recover: impl FnMut() -> Option<OwnedHandle> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            pending: Some(pipe),
+            pipe: None,
+            terminal_id,
+            pane_activity,
+            recover: Box::new(recover),
+        }
+    }
+
+    fn pipe(&mut self) -> io::Result<&mut NamedPipeServer> {
+        if let Some(handle) = self.pending.take() {
+            // SAFETY: the handle is owned and was opened for overlapped I/O
+            self.pipe =
+                Some(unsafe { NamedPipeServer::from_raw_handle(handle.into_raw_handle()) }?);
+        }
+        self.pipe
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "pty reader is closed"))
+    }
+}
+
+#[async_trait]
+impl AsyncReader for WindowsAsyncReader {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        loop {
+            match self.pipe()?.read(buf).await {
+                Ok(n) => {
+                    if n > 0 {
+                        if let Some(activity) = self
+                            .pane_activity
+                            .lock()
+                            .unwrap()
+                            .get_mut(&self.terminal_id)
+                        {
+                            activity.last_output = Instant::now();
+                        }
+                    }
+                    return Ok(n);
+                },
+                // the pseudoconsole was closed
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(0),
+                Err(e) => {
+                    self.pipe = None;
+                    match (self.recover)() {
+                        Some(recovered_pipe) => {
+                            log::warn!(
+                                "Recovered terminal {} after its pseudoconsole failed: {}",
+                                self.terminal_id,
+                                e
+                            );
+                            self.pending = Some(recovered_pipe);
+                        },
+                        None => return Err(e),
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// How many pending writes a pane's stdin queue holds before further writes
+/// are rejected.
+const STDIN_QUEUE_DEPTH: usize = 1024;
+
+/// How long Ctrl+C waits for the 0x03 it sends to reach the pseudoconsole
+/// before concluding that the pane is not reading its input.
+const CTRL_C_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+enum StdinRequest {
+    Write(Vec<u8>),
+    Flush,
+    /// Notifies the sender once every earlier write reached the pipe
+    Written(std::sync::mpsc::Sender<()>),
+}
+
+/// Owns the ConPTY input pipe of a single pane on a dedicated thread.
+///
+/// Writing to the pipe blocks once its buffer is full, which happens whenever
+/// the child stops reading stdin. Doing that while holding the
+/// `terminal_id_to_master` lock would stall input to every pane, so writes
+/// are enqueued here and the method returns immediately.
+#[derive(Clone)]
+struct QueuedWriter {
+    sender: std::sync::mpsc::SyncSender<StdinRequest>,
+}
+
+impl QueuedWriter {
+    fn new(terminal_id: u32, mut writer: Box<dyn Write + Send>) -> io::Result<Self> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(STDIN_QUEUE_DEPTH);
         thread::Builder::new()
-            .name("pty_reader".to_string())
+            .name(format!("pty_stdin_{}", terminal_id))
             .spawn(move || {
-                let mut buf = vec![0u8; 8192];
-                loop {
-                    match reader.read(&mut buf) {
-                        Ok(0) => {
-                            // EOF
-                            break;
+                for request in receiver.iter() {
+                    let result = match request {
+                        StdinRequest::Write(bytes) => {
+                            writer.write_all(&bytes).and_then(|_| writer.flush())
                         },
-                        Ok(n) => {
-                            if tx.blocking_send(Ok(buf[..n].to_vec())).is_err() {
-                                break; // receiver dropped
-                            }
-                        },
-                        Err(e) => {
-                            let _ = tx.blocking_send(Err(e));
-                            break;
+                        StdinRequest::Flush => writer.flush(),
+                        StdinRequest::Written(notify) => {
+                            let _ = notify.send(());
+                            Ok(())
                         },
+                    };
+                    if let Err(e) = result {
+                        log::error!(
+                            "Failed to write to stdin of terminal {}: {}",
+                            terminal_id,
+                            e
+                        );
+                        break;
                     }
                 }
-            })
-            .expect("failed to spawn pty_reader thread");
-        Self {
-            rx,
-            pending: Vec::new(),
+            })?;
+        Ok(Self { sender })
+    }
+
+    fn write(&self, buf: &[u8]) -> io::Result<usize> {
+        use std::sync::mpsc::TrySendError;
+        match self.sender.try_send(StdinRequest::Write(buf.to_vec())) {
+            Ok(()) => Ok(buf.len()),
+            Err(TrySendError::Full(_)) => Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "stdin queue is full, the pane is not reading its input",
+            )),
+            Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "stdin writer has exited",
+            )),
+        }
+    }
+
+    /// Write `buf` and block until it reached the pipe, or `timeout` passed.
+    fn write_and_wait(&self, buf: &[u8], timeout: Duration) -> io::Result<()> {
+        self.write(buf)?;
+        let (notify, written) = std::sync::mpsc::channel();
+        self.sender
+            .try_send(StdinRequest::Written(notify))
+            .map_err(|_| io::Error::new(io::ErrorKind::WouldBlock, "stdin queue is full"))?;
+        written.recv_timeout(timeout).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the pane did not read its input in time",
+            )
+        })
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        use std::sync::mpsc::TrySendError;
+        match self.sender.try_send(StdinRequest::Flush) {
+            // every queued write is flushed by the writer thread anyway
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Disconnected(_)) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "stdin writer has exited",
+            )),
         }
     }
 }
 
-#[async_trait]
-impl AsyncReader for WindowsAsyncReader {
-    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
-        // Drain any pending data first
-        if !self.pending.is_empty() {
-            let n = std::cmp::min(buf.len(), self.pending.len());
-            buf[..n].copy_from_slice(&self.pending[..n]);
-            self.pending.drain(..n);
-            return Ok(n);
-        }
-        match self.rx.recv().await {
-            Some(Ok(data)) => {
-                let n = std::cmp::min(buf.len(), data.len());
-                buf[..n].copy_from_slice(&data[..n]);
-                if n < data.len() {
-                    self.pending.extend_from_slice(&data[n..]);
-                }
-                Ok(n)
-            },
-            Some(Err(e)) => Err(e),
-            None => Ok(0), // channel closed = EOF
+/// Last time a pane produced output or received input, used to report idle
+/// and active transitions to plugins.
+struct PaneActivity {
+    last_output: Instant,
+    last_input: Instant,
+    idle: bool,
+}
+
+impl PaneActivity {
+    fn new() -> Self {
+        let now = Instant::now();
+        PaneActivity {
+            last_output: now,
+            last_input: now,
+            idle: false,
         }
     }
 }
@@ -85,65 +228,162 @@ impl AsyncReader for WindowsAsyncReader {
 /// Holds the master side of a PTY plus ancillary handles.
 struct MasterHandle {
     master: Box<dyn MasterPty + Send>,
-    writer: Option<Box<dyn Write + Send>>,
+    writer: Option<QueuedWriter>,
     killer: Box<dyn portable_pty::ChildKiller + Send + Sync>,
     child_pid: u32,
+    /// The PID reported to the server when the terminal was spawned, which
+    /// differs from `child_pid` once the pseudoconsole was recreated
+    spawned_pid: u32,
+    /// The conhost or OpenConsole process hosting the pseudoconsole, which
+    /// must survive terminating the shell's descendants
+    conhost_pid: Option<u32>,
+    /// The shell, held open so that `child_pid` is not recycled while the
+    /// pane refers to it
+    process: Option<Arc<ProcessWaitHandle>>,
+    size: PtySize,
+}
+
+impl MasterHandle {
+    fn runs_pid(&self, pid: u32) -> bool {
+        self.child_pid == pid || self.spawned_pid == pid
+    }
+}
+
+impl std::fmt::Debug for MasterHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MasterHandle")
+            .field("child_pid", &self.child_pid)
+            .field("spawned_pid", &self.spawned_pid)
+            .field("conhost_pid", &self.conhost_pid)
+            .field("size", &self.size)
+            .field("writer", &self.writer.is_some())
+            .finish()
+    }
 }
 
+type QuitCallback = Arc<Mutex<Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>>>;
+
+/// How long to wait before trying to open a pseudoconsole again after it
+/// failed for lack of memory
+const OUT_OF_MEMORY_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// The pseudoconsole failure behind `error`, if that is what it is
+fn conpty_error(error: &anyhow::Error) -> Option<&ConPtyError> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ConPtyError>())
+}
+
+/// How many times the pseudoconsole of a single pane is recreated before the
+/// pane is given up on
+const MAX_PSEUDOCONSOLE_RECOVERIES: usize = 3;
+
+/// The codepage identifier of UTF-8 (CP_UTF8)
+const UTF8_CODEPAGE: u32 = 65001;
+
+/// How long after the last input or output of a pane its foreground process
+/// is still looked for, covering programs that are slow to start
+const FOREGROUND_PROCESS_SETTLE_TIME: Duration = Duration::from_secs(5);
+
 /// The Windows PTY backend. Uses `portable-pty` (ConPTY) under the hood.
 #[derive(Clone)]
 pub(crate) struct WindowsPtyBackend {
     terminal_id_to_master: Arc<Mutex<BTreeMap<u32, Option<MasterHandle>>>>,
+    pane_activity: Arc<Mutex<BTreeMap<u32, PaneActivity>>>,
+    /// The originally requested command of terminals that were started with
+    /// their failover command instead
+    failover_commands: Arc<Mutex<BTreeMap<u32, RunCommand>>>,
+    /// How long `kill` and `send_sigint` wait for processes to exit on their
+    /// own before terminating them
+    kill_grace_period: Arc<Mutex<Duration>>,
+    /// The threads of `kill` waiting for processes to exit
+    pending_kills: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
+    /// Shells that were replaced after their pseudoconsole broke, whose exit
+    /// must not close their pane
+    replaced_children: Arc<Mutex<BTreeSet<u32>>>,
+    /// The implementation opening the pseudo terminals of new panes
+    pty_system: Arc<Mutex<PtySystemKind>>,
+    /// Whether the console of new panes is switched to the UTF-8 codepage
+    utf8_codepage: Arc<Mutex<bool>>,
+    /// SDDL restricting the pipes of new pseudoconsoles to the current user,
+    /// if it could be built
+    pipe_security_descriptor: Option<String>,
+    /// The job object the shells of new panes are placed in, if the session
+    /// has resource limits
+    session_job: Arc<Mutex<Option<Arc<SessionJob>>>>,
+    /// Variables set in (or removed from, if `None`) the environment of new
+    /// panes
+    session_env: Arc<Mutex<BTreeMap<String, Option<String>>>>,
+    /// When the processes were last looked up by `foreground_processes` and
+    /// what it found
+    last_foreground_processes: Arc<Mutex<Option<(Instant, BTreeMap<u32, String>)>>>,
 }
 
 impl WindowsPtyBackend {
     pub fn new() -> Result<Self, io::Error> {
+        let pipe_security_descriptor = match zellij_utils::ipc::current_user_only_sddl() {
+            Ok(sddl) => Some(sddl),
+            Err(e) => {
+                log::warn!(
+                    "Failed to restrict pseudoconsole pipes to the current user: {}",
+                    e
+                );
+                None
+            },
+        };
         Ok(Self {
             terminal_id_to_master: Arc::new(Mutex::new(BTreeMap::new())),
+            pane_activity: Arc::new(Mutex::new(BTreeMap::new())),
+            failover_commands: Arc::new(Mutex::new(BTreeMap::new())),
+            kill_grace_period: Arc::new(Mutex::new(Duration::ZERO)),
+            pending_kills: Arc::new(Mutex::new(Vec::new())),
+            replaced_children: Arc::new(Mutex::new(BTreeSet::new())),
+            pty_system: Arc::new(Mutex::new(PtySystemKind::default())),
+            utf8_codepage: Arc::new(Mutex::new(false)),
+            pipe_security_descriptor,
+            session_job: Arc::new(Mutex::new(None)),
+            session_env: Arc::new(Mutex::new(BTreeMap::new())),
+            last_foreground_processes: Arc::new(Mutex::new(None)),
         })
     }
 
-    pub fn spawn_terminal(
+    /// Open a pseudoconsole of `size` and start `cmd` in it.
+    fn open_pseudoconsole(
         &self,
-        cmd: RunCommand,
-        failover_cmd: Option<RunCommand>,
-        quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        cmd: &RunCommand,
         terminal_id: u32,
-    ) -> Result<(Box<dyn AsyncReader>, u32)> {
-        let err_context = |cmd: &RunCommand| {
-            format!(
-                "failed to spawn terminal for command '{}'",
-                cmd.command.to_string_lossy()
-            )
-        };
+        size: PtySize,
+    ) -> Result<(
+        MasterHandle,
+        OwnedHandle,
+        Box<dyn portable_pty::Child + Send + Sync>,
+    )> {
+        // The pipes of these pty systems are opened for overlapped I/O, which
+        // lets tokio read the output without a thread per pane.
+        use portable_pty::win::{conpty::ConPtyMasterPty, winpty::WinPtyMasterPty};
+        let pty_system = portable_pty::win::pty_system(
+            *self.pty_system.lock().unwrap(),
+            self.pipe_security_descriptor.as_deref(),
+        );
 
-        if !command_exists(&cmd) {
-            if let Some(failover) = failover_cmd {
-                return self.spawn_terminal(failover, None, quit_cb, terminal_id);
+        let pair = pty_system.openpty(size).context("failed to open pty")?;
+
+        // A new console starts out with the OEM codepage of the system, in
+        // which legacy programs mangle anything outside of it. The codepage
+        // is set before the shell starts so that it inherits it.
+        if *self.utf8_codepage.lock().unwrap() {
+            let master: &dyn MasterPty = &*pair.master;
+            if let Some(master) = master.downcast_ref::<ConPtyMasterPty>() {
+                if let Err(e) = master.set_console_codepage(UTF8_CODEPAGE) {
+                    log::warn!(
+                        "failed to switch terminal {} to the UTF-8 codepage: {:?}",
+                        terminal_id,
+                        e
+                    );
+                }
             }
-            return Err(ZellijError::CommandNotFound {
-                terminal_id,
-                command: cmd.command.to_string_lossy().to_string(),
-            })
-            .with_context(|| err_context(&cmd));
         }
 
-        // Use ConPtySystem directly for the large (1MB) output pipe buffer
-        // which reduces conhost lock contention during heavy output.
-        use portable_pty::win::conpty::ConPtySystem;
-        use portable_pty::PtySystem;
-        let pty_system = ConPtySystem::default();
-
-        let pair = pty_system
-            .openpty(PtySize {
-                rows: 24,
-                cols: 80,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| anyhow::anyhow!("failed to open pty: {}", e))
-            .with_context(|| err_context(&cmd))?;
-
         let mut cmd_builder = CommandBuilder::new(&cmd.command);
         cmd_builder.args(&cmd.args);
         if let Some(cwd) = &cmd.cwd {
@@ -156,79 +396,484 @@ impl WindowsPtyBackend {
                 );
             }
         }
+        for (name, value) in self.session_env.lock().unwrap().iter() {
+            match value {
+                Some(value) => cmd_builder.env(name, value),
+                None => cmd_builder.env_remove(name),
+            }
+        }
         cmd_builder.env("ZELLIJ_PANE_ID", format!("{}", terminal_id));
 
-        let mut child = pair
-            .slave
-            .spawn_command(cmd_builder)
-            .map_err(|e| anyhow::anyhow!("failed to spawn command: {}", e))
-            .with_context(|| err_context(&cmd))?;
+        let child = self.spawn_in_session_job(&*pair.slave, cmd_builder)?;
 
-        let child_pid = child
-            .process_id()
-            .unwrap_or(0);
+        let child_pid = child.process_id().unwrap_or(0);
 
-        let reader = pair
-            .master
-            .try_clone_reader()
-            .map_err(|e| anyhow::anyhow!("failed to clone pty reader: {}", e))
-            .with_context(|| err_context(&cmd))?;
+        let master: &dyn MasterPty = &*pair.master;
+        let (reader, conhost_pid) = if let Some(master) = master.downcast_ref::<ConPtyMasterPty>() {
+            (master.try_clone_async_reader(), master.host_process_id())
+        } else if let Some(master) = master.downcast_ref::<WinPtyMasterPty>() {
+            (master.try_clone_async_reader(), None)
+        } else {
+            bail!("pty cannot be read asynchronously");
+        };
+        let reader = reader.map_err(|e| anyhow::anyhow!("failed to clone pty reader: {}", e))?;
+        log::debug!(
+            "terminal {} runs PID {} in a pseudoconsole hosted by PID {:?}",
+            terminal_id,
+            child_pid,
+            conhost_pid
+        );
 
-        let mut writer = pair
+        let writer = pair
             .master
             .take_writer()
-            .map_err(|e| anyhow::anyhow!("failed to take pty writer: {}", e))
-            .with_context(|| err_context(&cmd))?;
+            .map_err(|e| anyhow::anyhow!("failed to take pty writer: {}", e))?;
+        let writer = QueuedWriter::new(terminal_id, writer)?;
 
-        // NOTE: Pre-sending DSR response (ESC[1;1R) was removed because with
-        // flags=0, system conhost doesn't send ESC[6n, making the unsolicited
-        // response confuse conhost and cause a 5+ second startup stall.
+        // The pseudoconsole inherits the cursor, so conhost queries its
+        // position (ESC[6n) before drawing anything. The query reaches the
+        // pane's grid like any other output, which answers with the pane's
+        // cursor position through the writer above. It is not forwarded to
+        // the client's terminal: the cursor conhost inherits is the one of
+        // the pane it draws in, which only the grid knows, while the
+        // client's terminal would answer with wherever zellij last drew.
 
         let killer = child.clone_killer();
+        // `child` still holds the shell open here, so its PID refers to it
+        let process = ProcessWaitHandle::open(child_pid).map(Arc::new);
 
         let handle = MasterHandle {
             master: pair.master,
             writer: Some(writer),
             killer,
             child_pid,
+            spawned_pid: child_pid,
+            conhost_pid,
+            process,
+            size,
         };
+        Ok((handle, reader, child))
+    }
 
-        self.terminal_id_to_master
-            .lock()
-            .to_anyhow()
-            .with_context(|| err_context(&cmd))?
-            .insert(terminal_id, Some(handle));
+    /// Start `cmd` in `slave`, inside the session job object if the session
+    /// has resource limits. ConPTY shells start suspended until they are in
+    /// the job, so that nothing they spawn escapes it.
+    fn spawn_in_session_job(
+        &self,
+        slave: &dyn SlavePty,
+        cmd: CommandBuilder,
+    ) -> Result<Box<dyn portable_pty::Child + Send + Sync>> {
+        use portable_pty::win::conpty::ConPtySlavePty;
+        let session_job = self.session_job.lock().unwrap().clone();
+        let Some(session_job) = session_job else {
+            return slave.spawn_command(cmd).context("failed to spawn command");
+        };
+        if let Some(slave) = slave.downcast_ref::<ConPtySlavePty>() {
+            let child = slave
+                .spawn_command_suspended(cmd)
+                .context("failed to spawn command")?;
+            if let Some(process) = child.as_raw_handle() {
+                if let Err(e) = session_job.assign(process) {
+                    log::error!(
+                        "Failed to place PID {:?} in the session job object: {}",
+                        child.process_id(),
+                        e
+                    );
+                }
+            }
+            let child = child.resume().context("failed to resume spawned command")?;
+            return Ok(Box::new(child));
+        }
+        let child = slave
+            .spawn_command(cmd)
+            .context("failed to spawn command")?;
+        if let Some(process) = child.as_raw_handle() {
+            if let Err(e) = session_job.assign(process) {
+                log::error!(
+                    "Failed to place PID {:?} in the session job object: {}",
+                    child.process_id(),
+                    e
+                );
+            }
+        }
+        Ok(child)
+    }
 
-        // Spawn a thread to wait for child exit and invoke the quit callback
-        let cmd_for_cb = cmd.clone();
+    /// Wait for `child` to exit on its own thread and invoke the quit callback,
+    /// unless it was replaced by a recreated pseudoconsole in the meantime.
+    fn watch_child(
+        &self,
+        mut child: Box<dyn portable_pty::Child + Send + Sync>,
+        child_pid: u32,
+        terminal_id: u32,
+        cmd: RunCommand,
+        quit_cb: QuitCallback,
+    ) -> io::Result<()> {
+        let replaced_children = self.replaced_children.clone();
         thread::Builder::new()
             .name(format!("pty_wait_{}", terminal_id))
             .spawn(move || {
                 let exit_status = child.wait();
+                if replaced_children.lock().unwrap().remove(&child_pid) {
+                    return;
+                }
                 let exit_code = match exit_status {
                     Ok(status) => {
-                        if status.success() {
-                            Some(0)
-                        } else {
-                            // portable-pty ExitStatus doesn't expose the raw code on all
-                            // platforms, so we report non-zero generically
-                            Some(1)
+                        if status.was_terminated() || status.is_ntstatus_error() {
+                            log::info!("terminal {} {}", terminal_id, status);
                         }
+                        // NTSTATUS codes such as STATUS_CONTROL_C_EXIT come out negative,
+                        // which is how cmd.exe and PowerShell report them too
+                        Some(status.exit_code_i32())
                     },
                     Err(e) => {
                         log::error!("Error waiting for child process: {}", e);
                         None
                     },
                 };
-                quit_cb(PaneId::Terminal(terminal_id), exit_code, cmd_for_cb);
+                (quit_cb.lock().unwrap())(PaneId::Terminal(terminal_id), exit_code, cmd);
+            })?;
+        Ok(())
+    }
+
+    /// Recreate the pseudoconsole of `terminal_id` after reading from it
+    /// failed while its shell is still alive, so that the pane keeps working
+    /// instead of freezing. A process cannot be moved to another
+    /// pseudoconsole, so the shell is restarted in the new one and the old
+    /// one is terminated. Returns the output pipe of the new pseudoconsole.
+    fn recover_pseudoconsole(
+        &self,
+        terminal_id: u32,
+        cmd: &RunCommand,
+        quit_cb: &QuitCallback,
+    ) -> Option<OwnedHandle> {
+        let mut map = self.terminal_id_to_master.lock().unwrap();
+        let (old_pid, spawned_pid, size, shell_is_alive) = match map.get(&terminal_id) {
+            Some(Some(handle)) => {
+                log::debug!("terminal {} failed with {:?}", terminal_id, handle);
+                let shell_is_alive = handle
+                    .process
+                    .as_ref()
+                    .is_some_and(|process| process.is_running());
+                (
+                    handle.child_pid,
+                    handle.spawned_pid,
+                    handle.size,
+                    shell_is_alive,
+                )
+            },
+            _ => return None, // the pane is being closed
+        };
+        if !shell_is_alive {
+            return None;
+        }
+        let (mut handle, reader, child) = match self.open_pseudoconsole(cmd, terminal_id, size) {
+            Ok(opened) => opened,
+            Err(e) => {
+                log::error!(
+                    "Failed to recreate the pseudoconsole of terminal {}: {:?}",
+                    terminal_id,
+                    e
+                );
+                return None;
+            },
+        };
+        let new_pid = handle.child_pid;
+        handle.spawned_pid = spawned_pid;
+        self.replaced_children.lock().unwrap().insert(old_pid);
+        let old_handle = map.insert(terminal_id, Some(handle)).flatten();
+        drop(map);
+
+        let watched = self.watch_child(child, new_pid, terminal_id, cmd.clone(), quit_cb.clone());
+        if let Err(e) = watched {
+            log::error!("Failed to watch PID {}: {}", new_pid, e);
+        }
+        if let Some(old_handle) = old_handle {
+            let _ = Self::close_gracefully(old_handle, Duration::ZERO);
+        }
+        Some(reader)
+    }
+
+    /// Close the pseudoconsole of a pane, which sends `CTRL_CLOSE_EVENT` to
+    /// every process attached to it, and only terminate the shell and its
+    /// descendants if they are still running after `grace_period`.
+    ///
+    /// Runs on its own thread since closing the pseudoconsole blocks until
+    /// conhost has exited on older versions of Windows.
+    fn close_gracefully(
+        handle: MasterHandle,
+        grace_period: Duration,
+    ) -> Option<thread::JoinHandle<()>> {
+        let MasterHandle {
+            master,
+            writer,
+            mut killer,
+            child_pid,
+            conhost_pid,
+            process,
+            ..
+        } = handle;
+        let spawned = thread::Builder::new()
+            .name(format!("pty_kill_{}", child_pid))
+            .spawn(move || {
+                drop(writer);
+                drop(master);
+                let Some(process) = process else {
+                    let _ = killer.kill();
+                    return;
+                };
+                if !process.wait(grace_period) {
+                    log::info!(
+                        "PID {} did not exit within {:?}, terminating it",
+                        child_pid,
+                        grace_period
+                    );
+                    Self::terminate_descendants(&process, conhost_pid);
+                    let _ = killer.kill();
+                }
+            });
+        spawned
+            .map_err(|e| log::error!("Failed to close PID {} gracefully: {}", child_pid, e))
+            .ok()
+    }
+
+    /// Spawn a short-lived helper process inside the ConPTY that detects
+    /// whether the 0x03 event was consumed by a stdin-reading program, and
+    /// terminate the descendants of the shell unless it was. The helper
+    /// exits with 42 if the event is still in the console input buffer.
+    fn spawn_ctrl_c_helper(
+        master: &Box<dyn portable_pty::MasterPty + Send>,
+        shell: Arc<ProcessWaitHandle>,
+        conhost_pid: Option<u32>,
+    ) -> Result<u32> {
+        use portable_pty::win::conpty::{ConPtyMasterPty, HelperOptions};
+        let exe =
+            std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("zellij.exe"));
+        let mut cmd = portable_pty::CommandBuilder::new(&exe);
+        cmd.arg("--conpty-ctrl-c");
+        let master: &dyn MasterPty = &**master;
+        master
+            .downcast_ref::<ConPtyMasterPty>()
+            .ok_or_else(|| anyhow::anyhow!("pty is not a ConPTY"))?
+            .spawn_helper(
+                cmd,
+                HelperOptions {
+                    discard_output: true,
+                },
+                move |status| match status {
+                    // 0x03 not consumed — terminate
+                    Ok(status) if status.exit_code() == 42 => {
+                        Self::terminate_descendants(&shell, conhost_pid);
+                    },
+                    // 0x03 was consumed — program handles it
+                    Ok(_) => {},
+                    // Helper failed — terminate as fallback
+                    Err(_) => Self::terminate_descendants(&shell, conhost_pid),
+                },
+            )
+    }
+
+    /// Have the pseudoconsole of `master` checked for a program that resized
+    /// its console, see `ConPtyMasterPty::reconcile_console_size`.
+    fn reconcile_console_size(master: &Box<dyn portable_pty::MasterPty + Send>) -> Result<()> {
+        use portable_pty::win::conpty::ConPtyMasterPty;
+        let master: &dyn MasterPty = &**master;
+        let Some(master) = master.downcast_ref::<ConPtyMasterPty>() else {
+            // winpty keeps its console to itself
+            return Ok(());
+        };
+        let exe =
+            std::env::current_exe().unwrap_or_else(|_| std::path::PathBuf::from("zellij.exe"));
+        let mut cmd = portable_pty::CommandBuilder::new(&exe);
+        cmd.arg("--conpty-console-size");
+        master.reconcile_console_size(cmd)
+    }
+
+    /// Find all descendant PIDs of `parent_pid` using the Toolhelp API.
+    ///
+    /// Toolhelp reports parent PIDs without any guarantee that the parent is
+    /// still the process that spawned the child: once a parent exits its PID
+    /// can be recycled. This is common on ARM64 where x64 children run under
+    /// emulation and an intermediate launcher may exit early, so we only
+    /// accept a child whose creation time is not earlier than its parent's.
+    fn find_descendants(parent_pid: u32) -> Vec<u32> {
+        let all_procs: Vec<(u32, u32)> = process_snapshot()
+            .iter()
+            .map(|process| (process.pid, process.ppid))
+            .collect();
+
+        let mut creation_times: BTreeMap<u32, Option<u64>> = BTreeMap::new();
+        let mut creation_time_of = |pid: u32| {
+            *creation_times
+                .entry(pid)
+                .or_insert_with(|| process_creation_time(pid))
+        };
+
+        let mut descendants: Vec<u32> = Vec::new();
+        let mut queue: Vec<u32> = vec![parent_pid];
+        while let Some(pid) = queue.pop() {
+            let pid_created = creation_time_of(pid);
+            for &(child_pid, ppid) in &all_procs {
+                if ppid != pid || child_pid == parent_pid || descendants.contains(&child_pid) {
+                    continue;
+                }
+                if let (Some(parent_created), Some(child_created)) =
+                    (pid_created, creation_time_of(child_pid))
+                {
+                    if child_created < parent_created {
+                        // PID was recycled: this process predates its "parent"
+                        continue;
+                    }
+                }
+                descendants.push(child_pid);
+                queue.push(child_pid);
+            }
+        }
+        descendants
+    }
+
+    /// Check whether `parent_pid` has any descendant processes.
+    fn has_descendants(parent_pid: u32) -> bool {
+        !Self::find_descendants(parent_pid).is_empty()
+    }
+
+    /// Terminate all descendant processes of `shell` without killing the
+    /// shell itself or `conhost_pid`, the process hosting its pseudoconsole.
+    /// Terminates bottom-up (leaves first). Holding `shell` open keeps its PID
+    /// from being recycled while its descendants are looked up.
+    fn terminate_descendants(shell: &ProcessWaitHandle, conhost_pid: Option<u32>) {
+        use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+        use windows_sys::Win32::System::Threading::{
+            OpenProcess, TerminateProcess, PROCESS_TERMINATE,
+        };
+
+        let parent_pid = shell.pid;
+        let mut descendants = Self::find_descendants(parent_pid);
+        descendants.retain(|pid| Some(*pid) != conhost_pid);
+        if descendants.is_empty() {
+            return;
+        }
+
+        log::info!(
+            "Terminating {} descendants of PID {}: {:?}",
+            descendants.len(),
+            parent_pid,
+            descendants
+        );
+
+        for &pid in descendants.iter().rev() {
+            unsafe {
+                let proc_handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+                if !proc_handle.is_null() && proc_handle != INVALID_HANDLE_VALUE {
+                    TerminateProcess(proc_handle, 1);
+                    CloseHandle(proc_handle);
+                }
+            }
+        }
+    }
+}
+
+impl PtyBackend for WindowsPtyBackend {
+    fn spawn_terminal(
+        &self,
+        cmd: RunCommand,
+        failover_cmd: Option<RunCommand>,
+        quit_cb: Box<dyn Fn(PaneId, Option<i32>, RunCommand) + Send>,
+        terminal_id: u32,
+    ) -> Result<(Box<dyn AsyncReader>, u32)> {
+        let err_context = |cmd: &RunCommand| {
+            format!(
+                "failed to spawn terminal for command '{}'",
+                cmd.command.to_string_lossy()
+            )
+        };
+
+        if !command_exists(&cmd) {
+            if let Some(failover) = failover_cmd {
+                log::warn!(
+                    "Command '{}' not found, starting '{}' instead",
+                    cmd.command.to_string_lossy(),
+                    failover.command.to_string_lossy()
+                );
+                let spawned = self.spawn_terminal(failover, None, quit_cb, terminal_id)?;
+                self.failover_commands
+                    .lock()
+                    .to_anyhow()
+                    .with_context(|| err_context(&cmd))?
+                    .insert(terminal_id, cmd);
+                return Ok(spawned);
+            }
+            return Err(ZellijError::CommandNotFound {
+                terminal_id,
+                command: cmd.command.to_string_lossy().to_string(),
             })
+            .with_context(|| err_context(&cmd));
+        }
+
+        let size = PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        };
+        let opened = match self.open_pseudoconsole(&cmd, terminal_id, size) {
+            Err(e) if conpty_error(&e).map_or(false, ConPtyError::is_out_of_memory) => {
+                log::warn!(
+                    "Out of memory opening the pseudoconsole of terminal {}, retrying: {:?}",
+                    terminal_id,
+                    e
+                );
+                thread::sleep(OUT_OF_MEMORY_RETRY_DELAY);
+                self.open_pseudoconsole(&cmd, terminal_id, size)
+            },
+            opened => opened,
+        };
+        let (handle, reader, child) = match opened {
+            Err(e) if conpty_error(&e).map_or(false, ConPtyError::is_not_found) => {
+                return Err(ZellijError::CommandNotFound {
+                    terminal_id,
+                    command: cmd.command.to_string_lossy().to_string(),
+                })
+                .with_context(|| err_context(&cmd));
+            },
+            opened => opened.with_context(|| err_context(&cmd))?,
+        };
+        let child_pid = handle.child_pid;
+
+        self.terminal_id_to_master
+            .lock()
+            .to_anyhow()
+            .with_context(|| err_context(&cmd))?
+            .insert(terminal_id, Some(handle));
+        self.pane_activity
+            .lock()
+            .to_anyhow()
+            .with_context(|| err_context(&cmd))?
+            .insert(terminal_id, PaneActivity::new());
+
+        let quit_cb: QuitCallback = Arc::new(Mutex::new(quit_cb));
+        self.watch_child(child, child_pid, terminal_id, cmd.clone(), quit_cb.clone())
             .with_context(|| err_context(&cmd))?;
 
-        let async_reader = Box::new(WindowsAsyncReader::new(reader)) as Box<dyn AsyncReader>;
+        let backend = self.clone();
+        let mut recoveries = 0;
+        let recover = move || {
+            if recoveries == MAX_PSEUDOCONSOLE_RECOVERIES {
+                return None;
+            }
+            recoveries += 1;
+            backend.recover_pseudoconsole(terminal_id, &cmd, &quit_cb)
+        };
+        let async_reader = Box::new(WindowsAsyncReader::new(
+            reader,
+            terminal_id,
+            self.pane_activity.clone(),
+            recover,
+        )) as Box<dyn AsyncReader>;
         Ok((async_reader, child_pid as u32))
     }
 
-    pub fn set_terminal_size(
+    fn set_terminal_size(
         &self,
         terminal_id: u32,
         cols: u16,
@@ -251,17 +896,43 @@ impl WindowsPtyBackend {
 
         match map.get_mut(&terminal_id) {
             Some(Some(handle)) => {
-                if cols > 0 && rows > 0 {
-                    handle
-                        .master
-                        .resize(PtySize {
-                            rows,
-                            cols,
-                            pixel_width: 0,
-                            pixel_height: 0,
-                        })
-                        .map_err(|e| anyhow::anyhow!("resize failed: {}", e))
-                        .with_context(err_context)?;
+                let size = PtySize {
+                    rows,
+                    cols,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                };
+                let pty_size = handle.master.get_size().ok();
+                if handle.size == size && pty_size.is_none_or(|pty_size| pty_size == size) {
+                    // Nothing to resize, but a program in the pane may have
+                    // resized its console behind our back
+                    if let Err(e) = Self::reconcile_console_size(&handle.master) {
+                        log::debug!(
+                            "failed to check the console size of terminal {}: {}",
+                            terminal_id,
+                            e
+                        );
+                    }
+                    return Ok(());
+                }
+                // Sizes such as 0x0 while the client is minimized are refused
+                // by the pty; the pane keeps its last good size until a valid
+                // one comes in
+                match handle.master.resize(size) {
+                    Ok(()) => handle.size = size,
+                    Err(e) if conpty_error(&e).map_or(false, ConPtyError::is_invalid_size) => {
+                        log::warn!(
+                            "keeping terminal {} at {}x{}: {}",
+                            terminal_id,
+                            handle.size.cols,
+                            handle.size.rows,
+                            e
+                        );
+                    },
+                    Err(e) => {
+                        return Err(anyhow::anyhow!("resize failed: {}", e))
+                            .with_context(err_context);
+                    },
                 }
             },
             _ => {
@@ -273,9 +944,19 @@ impl WindowsPtyBackend {
         Ok(())
     }
 
-    pub fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize> {
+    fn write_to_tty_stdin(&self, terminal_id: u32, buf: &[u8]) -> Result<usize> {
         let err_context = || format!("failed to write to stdin of TTY ID {}", terminal_id);
 
+        if let Some(activity) = self
+            .pane_activity
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?
+            .get_mut(&terminal_id)
+        {
+            activity.last_input = Instant::now();
+        }
+
         let mut map = self
             .terminal_id_to_master
             .lock()
@@ -289,79 +970,64 @@ impl WindowsPtyBackend {
                     //
                     // GenerateConsoleCtrlEvent(CTRL_C_EVENT) is broken in ConPTY
                     // on Windows 11 — it returns success but never delivers the
-                    // event. We use a multi-mechanism approach instead:
+                    // event. Which of these is sent is decided up front, and
+                    // only one of them is:
                     //
-                    // 1. Write 0x03 to the ConPTY pipe — works for idle prompt
-                    //    and programs that read stdin (Claude Code/node.js).
+                    // 1. If no child processes (idle prompt or a built-in
+                    //    command like dir /s): send Ctrl+Break VT sequence,
+                    //    which conhost always parses and delivers to the shell.
                     //
-                    // 2. If no child processes (built-in command like dir /s):
-                    //    send Ctrl+Break VT sequence, which conhost always parses.
-                    //
-                    // 3. If child processes exist: spawn a detection helper inside
-                    //    the ConPTY that waits 100ms, then peeks the console input
-                    //    buffer. If the 0x03 event was consumed (a program read it),
-                    //    do nothing — the program handles Ctrl+C itself (e.g. Claude
-                    //    Code). If unconsumed, terminate descendants (e.g. ping).
-                    if let Some(writer) = handle.writer.as_mut() {
-                        let _ = writer.write_all(b"\x03");
-                        let _ = writer.flush();
+                    // 2. If child processes exist: write 0x03 to the ConPTY pipe
+                    //    and, once it reached the pipe, spawn a detection helper
+                    //    inside the ConPTY that waits 100ms, then peeks the
+                    //    console input buffer. If the 0x03 event was consumed (a
+                    //    program read it), do nothing — the program handles
+                    //    Ctrl+C itself (e.g. Claude Code). If unconsumed,
+                    //    terminate descendants (e.g. ping).
+                    let Some(writer) = handle.writer.clone() else {
+                        return Err(anyhow!("writer not available")).with_context(err_context);
+                    };
+                    let conhost_pid = handle.conhost_pid;
+                    let shell = handle
+                        .process
+                        .clone()
+                        .filter(|shell| Self::has_descendants(shell.pid));
+                    let Some(shell) = shell else {
+                        let ctrl_break = zellij_utils::win32_input::Win32KeyEvent::ctrl_break();
+                        let _ = writer.write(&ctrl_break.encode_press());
+                        return Ok(1);
+                    };
+                    drop(map);
+
+                    // the helper must not peek before the 0x03 is in the buffer
+                    if let Err(e) = writer.write_and_wait(b"\x03", CTRL_C_WRITE_TIMEOUT) {
+                        log::warn!("Ctrl+C did not reach terminal {}: {}", terminal_id, e);
+                        Self::terminate_descendants(&shell, conhost_pid);
+                        return Ok(1);
                     }
-                    let shell_pid = handle.child_pid;
-
-                    if Self::has_descendants(shell_pid) {
-                        // Spawn detection helper inside ConPTY
-                        let helper = Self::spawn_ctrl_c_helper(&handle.master);
-                        drop(map);
-
-                        match helper {
-                            Some(mut child) => {
-                                // Wait for helper in background thread
-                                thread::spawn(move || {
-                                    match child.wait() {
-                                        Ok(status) if status.exit_code() == 42 => {
-                                            // 0x03 not consumed — terminate
-                                            Self::terminate_descendants(shell_pid);
-                                        },
-                                        Ok(_) => {
-                                            // 0x03 was consumed — program handles it
-                                        },
-                                        Err(_) => {
-                                            // Helper failed — terminate as fallback
-                                            Self::terminate_descendants(shell_pid);
-                                        },
-                                    }
-                                });
-                            },
-                            None => {
-                                // Helper spawn failed — fall back to delayed terminate
-                                thread::spawn(move || {
-                                    thread::sleep(std::time::Duration::from_millis(100));
-                                    Self::terminate_descendants(shell_pid);
-                                });
-                            },
-                        }
-                    } else {
-                        drop(map);
-                        // No child processes — likely a built-in command.
-                        // Send CTRL_BREAK_EVENT via win32-input-mode VT sequence.
-                        // Format: CSI Vk;Sc;Uc;Kd;Cs;Rc _
-                        //   VK_CANCEL=3, Sc=70, Uc=0, Kd=1/0, Cs=8(LEFT_CTRL), Rc=1
-                        let mut map = self
-                            .terminal_id_to_master
-                            .lock()
-                            .to_anyhow()
-                            .with_context(err_context)?;
-                        if let Some(Some(handle)) = map.get_mut(&terminal_id) {
-                            if let Some(writer) = handle.writer.as_mut() {
-                                let _ = writer.write_all(b"\x1b[3;70;0;1;8;1_");
-                                let _ = writer.write_all(b"\x1b[3;70;0;0;8;1_");
-                                let _ = writer.flush();
-                            }
-                        }
+                    let helper = match self
+                        .terminal_id_to_master
+                        .lock()
+                        .to_anyhow()
+                        .with_context(err_context)?
+                        .get(&terminal_id)
+                    {
+                        Some(Some(handle)) => {
+                            Self::spawn_ctrl_c_helper(&handle.master, shell.clone(), conhost_pid)
+                        },
+                        _ => return Ok(1), // the pane was closed in the meantime
+                    };
+                    if let Err(e) = helper {
+                        log::warn!("Failed to spawn Ctrl+C helper: {}", e);
+                        // Helper spawn failed — fall back to delayed terminate
+                        thread::spawn(move || {
+                            thread::sleep(std::time::Duration::from_millis(100));
+                            Self::terminate_descendants(&shell, conhost_pid);
+                        });
                     }
                     return Ok(1);
                 }
-                if let Some(writer) = handle.writer.as_mut() {
+                if let Some(writer) = handle.writer.as_ref() {
                     writer
                         .write(buf)
                         .map_err(|e| anyhow::anyhow!("{}", e))
@@ -374,7 +1040,7 @@ impl WindowsPtyBackend {
         }
     }
 
-    pub fn tcdrain(&self, terminal_id: u32) -> Result<()> {
+    fn tcdrain(&self, terminal_id: u32) -> Result<()> {
         let err_context = || format!("failed to tcdrain to TTY ID {}", terminal_id);
 
         let mut map = self
@@ -385,7 +1051,7 @@ impl WindowsPtyBackend {
 
         match map.get_mut(&terminal_id) {
             Some(Some(handle)) => {
-                if let Some(writer) = handle.writer.as_mut() {
+                if let Some(writer) = handle.writer.as_ref() {
                     writer
                         .flush()
                         .map_err(|e| anyhow::anyhow!("{}", e))
@@ -398,159 +1064,531 @@ impl WindowsPtyBackend {
         }
     }
 
-    pub fn kill(&self, pid: u32) -> Result<()> {
+    fn set_kill_grace_period(&self, grace_period: Duration) {
+        *self.kill_grace_period.lock().unwrap() = grace_period;
+    }
+
+    fn set_conpty_host(&self, host: &ConPtyHost) -> Result<()> {
+        use portable_pty::win::conpty::{set_conpty_host, ConPtyHost as PtyConPtyHost};
+        let pty_host = match host {
+            ConPtyHost::Bundled => PtyConPtyHost::Sideloaded,
+            ConPtyHost::System => PtyConPtyHost::System,
+            ConPtyHost::Path(dir) => PtyConPtyHost::Directory(dir.clone()),
+        };
+        set_conpty_host(pty_host).with_context(|| format!("failed to use the {} ConPTY", host))
+    }
+
+    fn set_utf8_codepage(&self, enabled: bool) {
+        *self.utf8_codepage.lock().unwrap() = enabled;
+    }
+
+    fn set_session_env_var(&self, name: String, value: Option<String>) {
+        self.session_env.lock().unwrap().insert(name, value);
+    }
+
+    fn set_session_resource_limits(&self, limits: SessionResourceLimits) -> Result<()> {
+        let err_context = || "failed to limit the resources of the session";
+        let mut session_job = self
+            .session_job
+            .lock()
+            .to_anyhow()
+            .with_context(err_context)?;
+        let job = match session_job.as_ref() {
+            Some(job) => job.clone(),
+            None => Arc::new(SessionJob::create().with_context(err_context)?),
+        };
+        job.set_limits(&limits).with_context(err_context)?;
+        *session_job = Some(job);
+        Ok(())
+    }
+
+    fn set_pty_system(&self, pty_system: PtySystem) -> Result<()> {
+        let kind = match pty_system {
+            PtySystem::ConPty => PtySystemKind::ConPty,
+            PtySystem::ConPtyPassthrough => PtySystemKind::ConPtyPassthrough,
+            PtySystem::WinPty => PtySystemKind::WinPty,
+        };
+        *self.pty_system.lock().to_anyhow()? = kind;
+        Ok(())
+    }
+
+    fn kill(&self, pid: u32) -> Result<()> {
+        let grace_period = *self.kill_grace_period.lock().to_anyhow()?;
+        if !grace_period.is_zero() {
+            let graceful = self
+                .terminal_id_to_master
+                .lock()
+                .to_anyhow()?
+                .values_mut()
+                .find(|handle_opt| matches!(handle_opt, Some(handle) if handle.runs_pid(pid)))
+                .and_then(Option::take);
+            if let Some(handle) = graceful {
+                if let Some(pending_kill) = Self::close_gracefully(handle, grace_period) {
+                    let mut pending_kills = self.pending_kills.lock().to_anyhow()?;
+                    pending_kills.retain(|handle| !handle.is_finished());
+                    pending_kills.push(pending_kill);
+                }
+                return Ok(());
+            }
+        }
+        self.force_kill(pid)
+    }
+
+    fn wait_for_pending_kills(&self) {
+        let pending_kills = std::mem::take(&mut *self.pending_kills.lock().unwrap());
+        for pending_kill in pending_kills {
+            let _ = pending_kill.join();
+        }
+    }
+
+    fn force_kill(&self, pid: u32) -> Result<()> {
+        // On Windows, TerminateProcess is already forceful
         let mut map = self.terminal_id_to_master.lock().to_anyhow()?;
         for handle_opt in map.values_mut() {
             if let Some(handle) = handle_opt {
-                if handle.child_pid == pid {
+                if handle.runs_pid(pid) {
                     let _ = handle.killer.kill();
                     return Ok(());
                 }
             }
         }
         // Fallback: use TerminateProcess directly
-        use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
-        unsafe {
-            let proc_handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
-            if !proc_handle.is_null() {
-                TerminateProcess(proc_handle, 1);
-                windows_sys::Win32::Foundation::CloseHandle(proc_handle);
-            }
-        }
+        terminate_process(pid);
         Ok(())
     }
 
-    pub fn force_kill(&self, pid: u32) -> Result<()> {
-        // On Windows, TerminateProcess is already forceful
-        self.kill(pid)
-    }
-
-    pub fn send_sigint(&self, pid: u32) -> Result<()> {
+    fn send_sigint(&self, pid: u32) -> Result<()> {
         // Terminate descendant processes of the shell. This is used for
         // programmatic signals (closing panes, plugin signals) where we
-        // need immediate termination rather than graceful Ctrl+C.
-        Self::terminate_descendants(pid);
+        // need immediate termination rather than graceful Ctrl+C, unless
+        // the user configured a grace period for them to exit on their own.
+        let grace_period = *self.kill_grace_period.lock().to_anyhow()?;
+        let map = self.terminal_id_to_master.lock().to_anyhow()?;
+        let Some(handle) = map.values().flatten().find(|handle| handle.runs_pid(pid)) else {
+            // not a pane's shell, the PID may belong to anyone by now
+            return Ok(());
+        };
+        let conhost_pid = handle.conhost_pid;
+        let Some(shell) = handle.process.clone() else {
+            return Ok(());
+        };
+        if grace_period.is_zero() {
+            drop(map);
+            Self::terminate_descendants(&shell, conhost_pid);
+            return Ok(());
+        }
+        if let Some(writer) = handle.writer.as_ref() {
+            let _ = writer.write(&[0x03]);
+        }
+        drop(map);
+        thread::Builder::new()
+            .name(format!("pty_sigint_{}", pid))
+            .spawn(move || {
+                thread::sleep(grace_period);
+                Self::terminate_descendants(&shell, conhost_pid);
+            })
+            .with_context(|| format!("failed to interrupt process {}", pid))?;
         Ok(())
     }
 
-    /// Spawn a short-lived helper process inside the ConPTY that detects
-    /// whether the 0x03 event was consumed by a stdin-reading program.
-    /// Returns None if spawning failed.
-    fn spawn_ctrl_c_helper(
-        master: &Box<dyn portable_pty::MasterPty + Send>,
-    ) -> Option<Box<dyn portable_pty::Child + Send + Sync>> {
-        let exe = std::env::current_exe()
-            .unwrap_or_else(|_| std::path::PathBuf::from("zellij.exe"));
-        let mut cmd = portable_pty::CommandBuilder::new(&exe);
-        cmd.arg("--conpty-ctrl-c");
-        match master.spawn_command_in_pty(cmd) {
-            Ok(child) => Some(child),
-            Err(e) => {
-                log::warn!("Failed to spawn Ctrl+C helper: {}", e);
-                None
-            },
+    fn reserve_terminal_id(&self, terminal_id: u32) {
+        self.terminal_id_to_master
+            .lock()
+            .unwrap()
+            .insert(terminal_id, None);
+    }
+
+    fn clear_terminal_id(&self, terminal_id: u32) {
+        use portable_pty::win::conpty::ConPtyMasterPty;
+        let handle = self
+            .terminal_id_to_master
+            .lock()
+            .unwrap()
+            .remove(&terminal_id)
+            .flatten();
+        if let Some(mut handle) = handle {
+            // Closing the pseudoconsole can block until conhost has flushed
+            // its output, so it is done off the pty thread. The input pipe is
+            // closed first so that conhost can exit.
+            handle.writer.take();
+            let spawned = thread::Builder::new()
+                .name(format!("pty_close_{}", terminal_id))
+                .spawn(move || {
+                    let master: &dyn MasterPty = &*handle.master;
+                    if let Some(master) = master.downcast_ref::<ConPtyMasterPty>() {
+                        master.shutdown();
+                    }
+                });
+            if let Err(e) = spawned {
+                log::error!(
+                    "Failed to close the pseudoconsole of terminal {} in the background: {}",
+                    terminal_id,
+                    e
+                );
+            }
         }
+        self.pane_activity.lock().unwrap().remove(&terminal_id);
+        self.failover_commands.lock().unwrap().remove(&terminal_id);
     }
 
-    /// Find all descendant PIDs of `parent_pid` using the Toolhelp API.
-    fn find_descendants(parent_pid: u32) -> Vec<u32> {
-        use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
-        use windows_sys::Win32::System::Diagnostics::ToolHelp::*;
+    fn take_failover_command(&self, terminal_id: u32) -> Option<RunCommand> {
+        self.failover_commands.lock().unwrap().remove(&terminal_id)
+    }
 
-        unsafe {
-            let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
-            if snapshot == INVALID_HANDLE_VALUE {
-                log::error!("CreateToolhelp32Snapshot failed");
-                return Vec::new();
+    /// Returns `(terminal_id, became_idle)` for every pane that crossed
+    /// `idle_threshold` in either direction since the previous call.
+    fn pane_activity_changes(&self, idle_threshold: Duration) -> Vec<(u32, bool)> {
+        let mut changes = vec![];
+        let mut pane_activity = self.pane_activity.lock().unwrap();
+        for (terminal_id, activity) in pane_activity.iter_mut() {
+            let last_activity = activity.last_output.max(activity.last_input);
+            let is_idle = last_activity.elapsed() >= idle_threshold;
+            if is_idle != activity.idle {
+                activity.idle = is_idle;
+                changes.push((*terminal_id, is_idle));
             }
+        }
+        changes
+    }
 
-            let mut entry: PROCESSENTRY32W = std::mem::zeroed();
-            entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+    /// Returns the executable name of the foreground process of every pane
+    /// whose shell is currently running something.
+    ///
+    /// ConPTY has no notion of a foreground process group, so starting at the
+    /// shell we follow the newest child of each process down to a leaf.
+    fn foreground_processes(&self) -> BTreeMap<u32, String> {
+        let shells: Vec<(u32, u32)> = self
+            .terminal_id_to_master
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(terminal_id, handle)| {
+                handle
+                    .as_ref()
+                    .map(|handle| (*terminal_id, handle.child_pid))
+            })
+            .collect();
+        let mut foreground_processes = BTreeMap::new();
+        if shells.is_empty() {
+            return foreground_processes;
+        }
 
-            let mut all_procs: Vec<(u32, u32)> = Vec::new();
-            if Process32FirstW(snapshot, &mut entry) != 0 {
-                loop {
-                    all_procs.push((entry.th32ProcessID, entry.th32ParentProcessID));
-                    if Process32NextW(snapshot, &mut entry) == 0 {
-                        break;
-                    }
-                }
+        // Programs are started by input and their shell prints a prompt once
+        // they exit, so while every pane is quiet the previous snapshot holds
+        let mut last_foreground_processes = self.last_foreground_processes.lock().unwrap();
+        if let Some((looked_up_at, previous)) = &*last_foreground_processes {
+            let panes_were_quiet = self.pane_activity.lock().unwrap().values().all(|activity| {
+                activity.last_output.max(activity.last_input) + FOREGROUND_PROCESS_SETTLE_TIME
+                    <= *looked_up_at
+            });
+            if panes_were_quiet {
+                return previous
+                    .iter()
+                    .filter(|(terminal_id, _)| shells.iter().any(|(id, _)| id == *terminal_id))
+                    .map(|(terminal_id, name)| (*terminal_id, name.clone()))
+                    .collect();
             }
-            CloseHandle(snapshot);
-
-            let mut descendants: Vec<u32> = Vec::new();
-            let mut queue: Vec<u32> = vec![parent_pid];
-            while let Some(pid) = queue.pop() {
-                for &(child_pid, ppid) in &all_procs {
-                    if ppid == pid && child_pid != parent_pid {
-                        descendants.push(child_pid);
-                        queue.push(child_pid);
+        }
+        let looked_up_at = Instant::now();
+
+        let all_procs = process_snapshot();
+        let mut creation_times: BTreeMap<u32, Option<u64>> = BTreeMap::new();
+        let mut creation_time_of = |pid: u32| {
+            *creation_times
+                .entry(pid)
+                .or_insert_with(|| process_creation_time(pid))
+        };
+
+        for (terminal_id, shell_pid) in shells {
+            let mut visited: BTreeSet<u32> = BTreeSet::new();
+            let mut foreground: Option<&ProcessEntry> = None;
+            let mut current_pid = shell_pid;
+            loop {
+                let current_created = creation_time_of(current_pid);
+                let mut newest_child: Option<(Option<u64>, &ProcessEntry)> = None;
+                for process in &all_procs {
+                    if process.ppid != current_pid
+                        || process.pid == shell_pid
+                        || visited.contains(&process.pid)
+                    {
+                        continue;
+                    }
+                    let child_created = creation_time_of(process.pid);
+                    if let (Some(parent_created), Some(child_created)) =
+                        (current_created, child_created)
+                    {
+                        if child_created < parent_created {
+                            // PID was recycled: this process predates its "parent"
+                            continue;
+                        }
                     }
+                    if newest_child.map_or(true, |(newest, _)| child_created >= newest) {
+                        newest_child = Some((child_created, process));
+                    }
+                }
+                match newest_child {
+                    Some((_, child)) => {
+                        visited.insert(child.pid);
+                        foreground = Some(child);
+                        current_pid = child.pid;
+                    },
+                    None => break,
                 }
             }
-            descendants
+            if let Some(foreground) = foreground {
+                foreground_processes.insert(terminal_id, foreground.exe_name.clone());
+            }
         }
+        *last_foreground_processes = Some((looked_up_at, foreground_processes.clone()));
+        foreground_processes
     }
 
-    /// Check whether `parent_pid` has any descendant processes.
-    fn has_descendants(parent_pid: u32) -> bool {
-        !Self::find_descendants(parent_pid).is_empty()
+    fn next_terminal_id(&self) -> Option<u32> {
+        self.terminal_id_to_master
+            .lock()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect::<BTreeSet<u32>>()
+            .last()
+            .map(|l| l + 1)
+            .or(Some(0))
     }
 
-    /// Terminate all descendant processes of `parent_pid` without killing
-    /// `parent_pid` itself (the shell). Terminates bottom-up (leaves first).
-    fn terminate_descendants(parent_pid: u32) {
-        use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
-        use windows_sys::Win32::System::Threading::{
-            OpenProcess, TerminateProcess, PROCESS_TERMINATE,
-        };
+    fn box_clone(&self) -> Box<dyn PtyBackend> {
+        Box::new((*self).clone())
+    }
+}
 
-        let descendants = Self::find_descendants(parent_pid);
-        if descendants.is_empty() {
-            return;
+/// A process as reported by a Toolhelp snapshot.
+struct ProcessEntry {
+    pid: u32,
+    ppid: u32,
+    /// Executable name without its extension, e.g. "cargo"
+    exe_name: String,
+}
+
+/// Snapshot of all processes on the system, empty if the snapshot failed.
+fn process_snapshot() -> Vec<ProcessEntry> {
+    use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::*;
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0);
+        if snapshot == INVALID_HANDLE_VALUE {
+            log::error!("CreateToolhelp32Snapshot failed");
+            return Vec::new();
         }
 
-        log::info!(
-            "Terminating {} descendants of PID {}: {:?}",
-            descendants.len(),
-            parent_pid,
-            descendants
-        );
+        let mut entry: PROCESSENTRY32W = std::mem::zeroed();
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
 
-        for &pid in descendants.iter().rev() {
-            unsafe {
-                let proc_handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
-                if !proc_handle.is_null() && proc_handle != INVALID_HANDLE_VALUE {
-                    TerminateProcess(proc_handle, 1);
-                    CloseHandle(proc_handle);
+        let mut all_procs = Vec::new();
+        if Process32FirstW(snapshot, &mut entry) != 0 {
+            loop {
+                let exe_file = &entry.szExeFile;
+                let len = exe_file
+                    .iter()
+                    .position(|c| *c == 0)
+                    .unwrap_or(exe_file.len());
+                let exe_file = String::from_utf16_lossy(&exe_file[..len]);
+                let exe_name = std::path::Path::new(&exe_file)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or(exe_file);
+                all_procs.push(ProcessEntry {
+                    pid: entry.th32ProcessID,
+                    ppid: entry.th32ParentProcessID,
+                    exe_name,
+                });
+                if Process32NextW(snapshot, &mut entry) == 0 {
+                    break;
                 }
             }
         }
+        CloseHandle(snapshot);
+        all_procs
     }
+}
 
-    pub fn reserve_terminal_id(&self, terminal_id: u32) {
-        self.terminal_id_to_master
-            .lock()
-            .unwrap()
-            .insert(terminal_id, None);
+/// Creation time of `pid` as a raw FILETIME value, or `None` if the process
+/// could not be opened (already exited, or access denied).
+/// Terminate `pid` with `TerminateProcess`, ignoring processes that already exited.
+fn terminate_process(pid: u32) {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenProcess, TerminateProcess, PROCESS_TERMINATE};
+    unsafe {
+        let proc_handle = OpenProcess(PROCESS_TERMINATE, 0, pid);
+        if !proc_handle.is_null() {
+            TerminateProcess(proc_handle, 1);
+            CloseHandle(proc_handle);
+        }
     }
+}
 
-    pub fn clear_terminal_id(&self, terminal_id: u32) {
-        self.terminal_id_to_master
-            .lock()
-            .unwrap()
-            .remove(&terminal_id);
+/// A handle that can be waited on for a process to exit. Holding it keeps the
+/// PID from being recycled while we wait.
+struct ProcessWaitHandle {
+    handle: windows_sys::Win32::Foundation::HANDLE,
+    pid: u32,
+}
+
+// SAFETY: process handles can be used and closed from any thread
+unsafe impl Send for ProcessWaitHandle {}
+unsafe impl Sync for ProcessWaitHandle {}
+
+impl ProcessWaitHandle {
+    fn open(pid: u32) -> Option<Self> {
+        use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_SYNCHRONIZE};
+        let proc_handle = unsafe { OpenProcess(PROCESS_SYNCHRONIZE, 0, pid) };
+        if proc_handle.is_null() {
+            None
+        } else {
+            Some(ProcessWaitHandle {
+                handle: proc_handle,
+                pid,
+            })
+        }
     }
 
-    pub fn next_terminal_id(&self) -> Option<u32> {
-        self.terminal_id_to_master
-            .lock()
-            .unwrap()
-            .keys()
-            .copied()
-            .collect::<BTreeSet<u32>>()
-            .last()
-            .map(|l| l + 1)
-            .or(Some(0))
+    /// Returns true if the process exited within `timeout`.
+    fn wait(&self, timeout: Duration) -> bool {
+        use windows_sys::Win32::Foundation::WAIT_OBJECT_0;
+        use windows_sys::Win32::System::Threading::WaitForSingleObject;
+        let timeout_ms = timeout.as_millis().min(u32::MAX as u128 - 1) as u32;
+        unsafe { WaitForSingleObject(self.handle, timeout_ms) == WAIT_OBJECT_0 }
+    }
+
+    fn is_running(&self) -> bool {
+        !self.wait(Duration::ZERO)
+    }
+}
+
+impl Drop for ProcessWaitHandle {
+    fn drop(&mut self) {
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.handle);
+        }
+    }
+}
+
+/// A job object holding the shells of all panes of the session, along with
+/// everything they spawn, which share its limits
+struct SessionJob(windows_sys::Win32::Foundation::HANDLE);
+
+// SAFETY: job object handles can be used and closed from any thread
+unsafe impl Send for SessionJob {}
+unsafe impl Sync for SessionJob {}
+
+impl SessionJob {
+    fn create() -> io::Result<Self> {
+        use windows_sys::Win32::System::JobObjects::CreateJobObjectW;
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if job.is_null() {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(SessionJob(job))
+        }
+    }
+
+    fn set_limits(&self, limits: &SessionResourceLimits) -> io::Result<()> {
+        use windows_sys::Win32::System::JobObjects::{
+            JobObjectCpuRateControlInformation, JobObjectExtendedLimitInformation,
+            SetInformationJobObject, JOBOBJECT_CPU_RATE_CONTROL_INFORMATION,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_CPU_RATE_CONTROL_ENABLE,
+            JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP, JOB_OBJECT_LIMIT_ACTIVE_PROCESS,
+            JOB_OBJECT_LIMIT_JOB_MEMORY,
+        };
+
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+        if let Some(max_memory_mb) = limits.max_memory_mb {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+            info.JobMemoryLimit =
+                usize::try_from(max_memory_mb.saturating_mul(1024 * 1024)).unwrap_or(usize::MAX);
+        }
+        if let Some(max_processes) = limits.max_processes {
+            info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_ACTIVE_PROCESS;
+            info.BasicLimitInformation.ActiveProcessLimit = max_processes;
+        }
+        let set = unsafe {
+            SetInformationJobObject(
+                self.0,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+        };
+        if set == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if let Some(cpu_rate_percent) = limits.cpu_rate_percent {
+            let mut cpu_rate: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION =
+                unsafe { std::mem::zeroed() };
+            cpu_rate.ControlFlags =
+                JOB_OBJECT_CPU_RATE_CONTROL_ENABLE | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+            // in hundredths of a percent of all processors
+            cpu_rate.Anonymous.CpuRate = cpu_rate_percent.clamp(1, 100) * 100;
+            let set = unsafe {
+                SetInformationJobObject(
+                    self.0,
+                    JobObjectCpuRateControlInformation,
+                    &cpu_rate as *const _ as *const std::ffi::c_void,
+                    std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+                )
+            };
+            if set == 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    fn assign(&self, process: std::os::windows::io::RawHandle) -> io::Result<()> {
+        use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+        if unsafe { AssignProcessToJobObject(self.0, process as _) } == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for SessionJob {
+    fn drop(&mut self) {
+        // closing it neither ends the processes in the job nor lifts its limits
+        unsafe {
+            windows_sys::Win32::Foundation::CloseHandle(self.0);
+        }
+    }
+}
+
+fn process_creation_time(pid: u32) -> Option<u64> {
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME};
+    use windows_sys::Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let proc_handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if proc_handle.is_null() {
+            return None;
+        }
+        let mut created: FILETIME = std::mem::zeroed();
+        let mut exited: FILETIME = std::mem::zeroed();
+        let mut kernel: FILETIME = std::mem::zeroed();
+        let mut user: FILETIME = std::mem::zeroed();
+        let ok = GetProcessTimes(
+            proc_handle,
+            &mut created,
+            &mut exited,
+            &mut kernel,
+            &mut user,
+        );
+        CloseHandle(proc_handle);
+        if ok == 0 {
+            return None;
+        }
+        Some(((created.dwHighDateTime as u64) << 32) | created.dwLowDateTime as u64)
     }
 }
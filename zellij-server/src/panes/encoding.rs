@@ -0,0 +1,204 @@
+//! Transcoding of pane output in legacy codepages to UTF-8 before it reaches the grid.
+
+use encoding_rs::{Decoder, Encoding};
+use std::str::FromStr;
+use zellij_utils::errors::prelude::*;
+
+/// The encoding a terminal pane's program writes its output in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneEncoding {
+    Utf8,
+    /// The US IBM PC codepage, not supported by encoding_rs
+    Cp437,
+    /// The western European DOS codepage, not supported by encoding_rs
+    Cp850,
+    Other(&'static Encoding),
+    /// Valid UTF-8 is passed through and anything else is decoded from the system's OEM
+    /// codepage. Used for `cmd.exe` panes, in which tools often print in the OEM codepage.
+    Auto,
+}
+
+impl PaneEncoding {
+    fn from_codepage(codepage: u32) -> Option<Self> {
+        let encoding = match codepage {
+            437 => return Some(PaneEncoding::Cp437),
+            850 => return Some(PaneEncoding::Cp850),
+            65001 => return Some(PaneEncoding::Utf8),
+            866 => encoding_rs::IBM866,
+            932 => encoding_rs::SHIFT_JIS,
+            936 => encoding_rs::GBK,
+            949 => encoding_rs::EUC_KR,
+            950 => encoding_rs::BIG5,
+            874 | 1250..=1258 => Encoding::for_label(format!("windows-{}", codepage).as_bytes())?,
+            _ => return None,
+        };
+        Some(PaneEncoding::Other(encoding))
+    }
+    /// The system's OEM codepage, as long as it can be decoded one byte at a time
+    fn oem() -> Self {
+        #[cfg(windows)]
+        {
+            let codepage = unsafe { windows_sys::Win32::Globalization::GetOEMCP() };
+            match PaneEncoding::from_codepage(codepage) {
+                Some(PaneEncoding::Other(encoding)) if !encoding.is_single_byte() => {
+                    PaneEncoding::Utf8
+                },
+                Some(encoding) => encoding,
+                None => PaneEncoding::Cp437,
+            }
+        }
+        #[cfg(not(windows))]
+        PaneEncoding::Cp437
+    }
+}
+
+/// Accepts codepage numbers (`437`, `cp850`, `1252`), the labels known to encoding_rs
+/// (`windows-1252`, `ibm866`, `shift_jis`...) and `auto`.
+impl FromStr for PaneEncoding {
+    type Err = anyhow::Error;
+    fn from_str(label: &str) -> Result<Self> {
+        let label = label.trim().to_lowercase();
+        let codepage = label
+            .strip_prefix("cp")
+            .or_else(|| label.strip_prefix("ibm"))
+            .unwrap_or(&label);
+        if label == "auto" {
+            Ok(PaneEncoding::Auto)
+        } else if let Some(encoding) = codepage.parse().ok().and_then(PaneEncoding::from_codepage) {
+            Ok(encoding)
+        } else {
+            match Encoding::for_label(label.as_bytes()) {
+                Some(encoding) if encoding == encoding_rs::UTF_8 => Ok(PaneEncoding::Utf8),
+                Some(encoding) => Ok(PaneEncoding::Other(encoding)),
+                None => Err(anyhow!("unknown encoding: {}", label)),
+            }
+        }
+    }
+}
+
+/// Transcodes the output of a pane to UTF-8, keeping the state of characters split across reads.
+pub struct PaneDecoder {
+    encoding: PaneEncoding,
+    decoder: Option<Decoder>,
+    /// What `Auto` decodes bytes that are not valid UTF-8 from
+    fallback: PaneEncoding,
+    /// The start of a UTF-8 character whose remaining bytes were not read yet
+    pending: Vec<u8>,
+}
+
+impl PaneDecoder {
+    pub fn new(encoding: PaneEncoding) -> Self {
+        PaneDecoder {
+            encoding,
+            decoder: match encoding {
+                PaneEncoding::Other(encoding) => Some(encoding.new_decoder_without_bom_handling()),
+                _ => None,
+            },
+            fallback: match encoding {
+                PaneEncoding::Auto => PaneEncoding::oem(),
+                _ => PaneEncoding::Utf8,
+            },
+            pending: vec![],
+        }
+    }
+    pub fn encoding(&self) -> PaneEncoding {
+        self.encoding
+    }
+    pub fn decode(&mut self, bytes: Vec<u8>) -> Vec<u8> {
+        match self.encoding {
+            PaneEncoding::Utf8 => bytes,
+            PaneEncoding::Cp437 => decode_with_table(&CP437, &bytes),
+            PaneEncoding::Cp850 => decode_with_table(&CP850, &bytes),
+            PaneEncoding::Other(_) => match self.decoder.as_mut() {
+                Some(decoder) => {
+                    let mut decoded = String::with_capacity(
+                        decoder
+                            .max_utf8_buffer_length(bytes.len())
+                            .unwrap_or(bytes.len() * 3),
+                    );
+                    let _ = decoder.decode_to_string(&bytes, &mut decoded, false);
+                    decoded.into_bytes()
+                },
+                None => bytes,
+            },
+            PaneEncoding::Auto => self.decode_utf8_or_fallback(bytes),
+        }
+    }
+    fn decode_utf8_or_fallback(&mut self, bytes: Vec<u8>) -> Vec<u8> {
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(&bytes);
+        let mut decoded = Vec::with_capacity(input.len());
+        let mut rest = &input[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    decoded.extend_from_slice(valid.as_bytes());
+                    break;
+                },
+                Err(e) => {
+                    let (valid, invalid) = rest.split_at(e.valid_up_to());
+                    decoded.extend_from_slice(valid);
+                    match e.error_len() {
+                        Some(len) => {
+                            for byte in &invalid[..len] {
+                                decoded.extend(self.decode_single_byte(*byte));
+                            }
+                            rest = &invalid[len..];
+                        },
+                        None => {
+                            self.pending = invalid.to_vec();
+                            break;
+                        },
+                    }
+                },
+            }
+        }
+        decoded
+    }
+    fn decode_single_byte(&self, byte: u8) -> Vec<u8> {
+        match self.fallback {
+            PaneEncoding::Cp437 => decode_with_table(&CP437, &[byte]),
+            PaneEncoding::Cp850 => decode_with_table(&CP850, &[byte]),
+            PaneEncoding::Other(encoding) => encoding
+                .decode_without_bom_handling(&[byte])
+                .0
+                .into_owned()
+                .into_bytes(),
+            PaneEncoding::Utf8 | PaneEncoding::Auto => vec![byte],
+        }
+    }
+}
+
+fn decode_with_table(table: &[char; 128], bytes: &[u8]) -> Vec<u8> {
+    let mut decoded = String::with_capacity(bytes.len());
+    for byte in bytes {
+        if byte.is_ascii() {
+            decoded.push(*byte as char);
+        } else {
+            decoded.push(table[(byte - 0x80) as usize]);
+        }
+    }
+    decoded.into_bytes()
+}
+
+/// Characters 0x80-0xFF of codepage 437
+const CP437: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// Characters 0x80-0xFF of codepage 850
+const CP850: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '®', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À', '©',
+    '╣', '║', '╗', '╝', '¢', '¥', '┐', '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '¤', 'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î', 'Ï', '┘', '┌', '█', '▄', '¦', 'Ì',
+    '▀', 'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ', 'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´', '\u{ad}',
+    '±', '‗', '¾', '¶', '§', '÷', '¸', '°', '¨', '·', '¹', '³', '²', '■', '\u{a0}',
+];
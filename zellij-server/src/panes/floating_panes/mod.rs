@@ -383,6 +383,7 @@ impl FloatingPanes {
         current_pane_group: HashMap<ClientId, Vec<PaneId>>,
         client_id_override: Option<ClientId>,
         help_text_visible: &HashMap<ClientId, bool>,
+        client_styles: &HashMap<ClientId, Style>,
     ) -> Result<()> {
         let err_context = || "failed to render output";
         let mut connected_clients: HashSet<ClientId> =
@@ -449,6 +450,7 @@ impl FloatingPanes {
                 pane,
                 output,
                 self.style,
+                client_styles,
                 &active_panes,
                 multiple_users_exist_in_session,
                 Some(z_index + 1), // +1 because 0 is reserved for non-floating panes
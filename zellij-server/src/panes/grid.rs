@@ -2,6 +2,7 @@ use super::sixel::{PixelRect, SixelGrid, SixelImageStore};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 use zellij_utils::data::Style;
 use zellij_utils::errors::prelude::*;
@@ -32,9 +33,10 @@ use crate::output::{CharacterChunk, OutputBuffer, SixelImageChunk};
 use crate::panes::alacritty_functions::{parse_number, xparse_color};
 use crate::panes::hyperlink_tracker::HyperlinkTracker;
 use crate::panes::link_handler::LinkHandler;
+use crate::panes::scrollback_spill::{strip_styles, ScrollbackSpill};
 use crate::panes::search::SearchResult;
 use crate::panes::terminal_character::{
-    AnsiCode, CharsetIndex, Cursor, CursorShape, RcCharacterStyles, StandardCharset,
+    AnsiCode, CharsetIndex, Cursor, CursorShape, LinkAnchor, RcCharacterStyles, StandardCharset,
     TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
 };
 use crate::panes::Selection;
@@ -121,6 +123,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     lines_above: &mut VecDeque<Row>,
     viewport: &mut Vec<Row>,
     sixel_grid: &mut SixelGrid,
+    scrollback_spill: &Option<Rc<RefCell<ScrollbackSpill>>>,
     count: usize,
     max_viewport_width: usize,
 ) -> usize {
@@ -152,7 +155,7 @@ fn transfer_rows_from_lines_above_to_viewport(
     }
     if !next_lines.is_empty() {
         let excess_row = Row::from_rows(next_lines);
-        bounded_push(lines_above, sixel_grid, excess_row);
+        bounded_push(lines_above, sixel_grid, scrollback_spill, excess_row);
     }
     match usize::try_from(lines_added_to_viewport) {
         Ok(n) => n,
@@ -164,6 +167,7 @@ fn transfer_rows_from_viewport_to_lines_above(
     viewport: &mut Vec<Row>,
     lines_above: &mut VecDeque<Row>,
     sixel_grid: &mut SixelGrid,
+    scrollback_spill: &Option<Rc<RefCell<ScrollbackSpill>>>,
     count: usize,
     max_viewport_width: usize,
 ) -> isize {
@@ -179,7 +183,12 @@ fn transfer_rows_from_viewport_to_lines_above(
             next_lines.append(&mut bottom_canonical_row_and_wraps_in_dst);
         }
         next_lines.push(next_line);
-        let dropped_line_width = bounded_push(lines_above, sixel_grid, Row::from_rows(next_lines));
+        let dropped_line_width = bounded_push(
+            lines_above,
+            sixel_grid,
+            scrollback_spill,
+            Row::from_rows(next_lines),
+        );
         if let Some(width) = dropped_line_width {
             transferred_rows_count -=
                 calculate_row_display_height(width, max_viewport_width) as isize;
@@ -228,19 +237,6 @@ fn transfer_rows_from_lines_below_to_viewport(
     }
 }
 
-fn bounded_push(vec: &mut VecDeque<Row>, sixel_grid: &mut SixelGrid, value: Row) -> Option<usize> {
-    let mut dropped_line_width = None;
-    if vec.len() >= *SCROLL_BUFFER_SIZE.get().unwrap() {
-        let line = vec.pop_front();
-        if let Some(line) = line {
-            sixel_grid.offset_grid_top();
-            dropped_line_width = Some(line.width());
-        }
-    }
-    vec.push_back(value);
-    dropped_line_width
-}
-
 pub fn create_horizontal_tabstops(columns: usize) -> BTreeSet<usize> {
     let mut i = TABSTOP_WIDTH;
     let mut horizontal_tabstops = BTreeSet::new();
@@ -327,6 +323,29 @@ macro_rules! dump_screen_with_ansi {
     }};
 }
 
+fn bounded_push(
+    vec: &mut VecDeque<Row>,
+    sixel_grid: &mut SixelGrid,
+    scrollback_spill: &Option<Rc<RefCell<ScrollbackSpill>>>,
+    value: Row,
+) -> Option<usize> {
+    let mut dropped_line_width = None;
+    if vec.len() >= *SCROLL_BUFFER_SIZE.get().unwrap() {
+        let line = vec.pop_front();
+        if let Some(line) = line {
+            sixel_grid.offset_grid_top();
+            dropped_line_width = Some(line.width());
+            if let Some(scrollback_spill) = scrollback_spill {
+                scrollback_spill
+                    .borrow_mut()
+                    .push_line(&dump_screen_with_ansi!([line]));
+            }
+        }
+    }
+    vec.push_back(value);
+    dropped_line_width
+}
+
 fn utf8_mouse_coordinates(column: usize, line: isize) -> Vec<u8> {
     let mut coordinates = vec![];
     let mouse_pos_encode = |pos: usize| -> Vec<u8> {
@@ -387,12 +406,16 @@ pub struct Grid {
     pub is_scrolled: bool,
     pub link_handler: Rc<RefCell<LinkHandler>>,
     pub ring_bell: bool,
+    pub suppress_bell: bool,
+    bell_rang: bool, // since the last call to take_bell
     scrollback_buffer_lines: usize,
     pub mouse_mode: MouseMode,
     pub mouse_tracking: MouseTracking,
     pub focus_event_tracking: bool,
     pub search_results: SearchResult,
     pub pending_clipboard_update: Option<String>,
+    clipboard_queried: bool, // since the last call to take_clipboard_query
+    taskbar_progress: Option<String>, // OSC 9;4 reported since the last call to take_taskbar_progress
     ui_component_bytes: Option<Vec<u8>>,
     style: Style,
     debug: bool,
@@ -404,6 +427,7 @@ pub struct Grid {
     // disabled by user config?
     click: Click,
     hyperlink_tracker: HyperlinkTracker,
+    scrollback_spill: Option<Rc<RefCell<ScrollbackSpill>>>,
 }
 
 const CLICK_TIME_THRESHOLD: u128 = 400; // Doherty Threshold
@@ -575,6 +599,8 @@ impl Grid {
             is_scrolled: false,
             link_handler,
             ring_bell: false,
+            suppress_bell: false,
+            bell_rang: false,
             scrollback_buffer_lines: 0,
             mouse_mode: MouseMode::default(),
             mouse_tracking: MouseTracking::default(),
@@ -583,6 +609,8 @@ impl Grid {
             search_results: Default::default(),
             sixel_grid,
             pending_clipboard_update: None,
+            clipboard_queried: false,
+            taskbar_progress: None,
             ui_component_bytes: None,
             style,
             debug,
@@ -594,6 +622,27 @@ impl Grid {
             explicitly_disable_kitty_keyboard_protocol,
             click: Click::default(),
             hyperlink_tracker: HyperlinkTracker::new(),
+            scrollback_spill: None,
+        }
+    }
+    /// Keep up to `max_lines` lines that fall out of the scroll buffer in files named after
+    /// `file_stem` in `folder` rather than dropping them
+    pub fn spill_scrollback_to(&mut self, folder: PathBuf, file_stem: &str, max_lines: usize) {
+        self.scrollback_spill = Some(Rc::new(RefCell::new(ScrollbackSpill::new(
+            folder, file_stem, max_lines,
+        ))));
+    }
+    fn spilled_lines(&self, count: Option<usize>) -> Vec<String> {
+        match &self.scrollback_spill {
+            Some(scrollback_spill) if count != Some(0) => {
+                scrollback_spill.borrow_mut().read_lines(count)
+            },
+            _ => vec![],
+        }
+    }
+    fn clear_scrollback_spill(&mut self) {
+        if let Some(scrollback_spill) = &self.scrollback_spill {
+            scrollback_spill.borrow_mut().clear();
         }
     }
     pub fn render_full_viewport(&mut self) {
@@ -761,6 +810,7 @@ impl Grid {
                 &mut self.lines_above,
                 &mut self.viewport,
                 &mut self.sixel_grid,
+                &self.scrollback_spill,
                 1,
                 self.width,
             );
@@ -804,8 +854,12 @@ impl Grid {
                 }
             };
 
-            let dropped_line_width =
-                bounded_push(&mut self.lines_above, &mut self.sixel_grid, line_to_push_up);
+            let dropped_line_width = bounded_push(
+                &mut self.lines_above,
+                &mut self.sixel_grid,
+                &self.scrollback_spill,
+                line_to_push_up,
+            );
             if let Some(width) = dropped_line_width {
                 let dropped_line_height = calculate_row_display_height(width, self.width);
 
@@ -1035,6 +1089,7 @@ impl Grid {
                         &mut self.lines_above,
                         &mut self.viewport,
                         &mut self.sixel_grid,
+                        &self.scrollback_spill,
                         row_count_to_transfer,
                         new_columns,
                     );
@@ -1062,6 +1117,7 @@ impl Grid {
                         &mut self.viewport,
                         &mut self.lines_above,
                         &mut self.sixel_grid,
+                        &self.scrollback_spill,
                         row_count_to_transfer,
                         new_columns,
                     );
@@ -1157,6 +1213,14 @@ impl Grid {
                         .len()
                         .saturating_sub(scrollback_lines_to_serialize)
                 };
+                // lines that were spilled to disk come before everything in lines_above
+                let spilled_lines = if scrollback_lines_to_serialize == 0 {
+                    self.spilled_lines(None)
+                } else {
+                    self.spilled_lines(Some(
+                        scrollback_lines_to_serialize.saturating_sub(self.lines_above.len()),
+                    ))
+                };
                 let mut to_serialize = vec![];
                 for line in self.lines_above.iter().skip(first_index) {
                     to_serialize.push(line.clone());
@@ -1164,9 +1228,15 @@ impl Grid {
                 for line in &self.viewport {
                     to_serialize.push(line.clone())
                 }
+                let mut serialized = String::new();
+                for line in spilled_lines {
+                    serialized.push_str("\n\r");
+                    serialized.push_str(&line);
+                }
                 self.output_buffer
                     .serialize(to_serialize.as_slice(), self.osc8_hyperlinks, None)
                     .ok()
+                    .map(|s| serialized + &s)
             },
             None => self
                 .output_buffer
@@ -1253,6 +1323,15 @@ impl Grid {
             sixel_image_chunks,
         )));
     }
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_rang)
+    }
+    pub fn take_clipboard_query(&mut self) -> bool {
+        std::mem::take(&mut self.clipboard_queried)
+    }
+    pub fn take_taskbar_progress(&mut self) -> Option<String> {
+        self.taskbar_progress.take()
+    }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         if self.cursor_is_hidden || self.cursor.x >= self.width || self.cursor.y >= self.height {
             None
@@ -1278,7 +1357,12 @@ impl Grid {
         if !full {
             return viewport;
         }
-        let mut scrollback: String = dump_screen!(self.lines_above);
+        let mut scrollback: String = self
+            .spilled_lines(None)
+            .iter()
+            .map(|line| strip_styles(line) + "\n")
+            .collect();
+        scrollback.push_str(&dump_screen!(self.lines_above));
         if !scrollback.is_empty() {
             scrollback.push('\n');
         }
@@ -1291,7 +1375,12 @@ impl Grid {
         if !full {
             return viewport;
         }
-        let mut scrollback: String = dump_screen_with_ansi!(self.lines_above);
+        let mut scrollback: String = self
+            .spilled_lines(None)
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect();
+        scrollback.push_str(&dump_screen_with_ansi!(self.lines_above));
         if !scrollback.is_empty() {
             scrollback.push('\n');
         }
@@ -1510,6 +1599,18 @@ impl Grid {
             .and_then(|current_line| current_line.columns.get(absolute_x_in_line))
             .cloned()
     }
+    /// The uri of the hyperlink (an OSC 8 one or one we detected) at `position` in the viewport
+    pub fn link_at(&self, position: &Position) -> Option<String> {
+        let line = usize::try_from(position.line.0).ok()?;
+        let row = self.viewport.get(line)?;
+        let character = row
+            .columns
+            .get(row.absolute_character_index(position.column.0))?;
+        match character.styles.link_anchor {
+            Some(LinkAnchor::Start(link_index)) => self.link_handler.borrow().uri(link_index),
+            _ => None,
+        }
+    }
     pub fn get_absolute_character_index(&self, x: usize, y: usize) -> Option<usize> {
         Some(self.viewport.get(y)?.absolute_character_index(x))
     }
@@ -1589,6 +1690,7 @@ impl Grid {
     }
     fn clear_lines_above(&mut self) {
         self.lines_above.clear();
+        self.clear_scrollback_spill();
         self.scrollback_buffer_lines = self.recalculate_scrollback_buffer_count();
     }
 
@@ -1806,6 +1908,7 @@ impl Grid {
     }
     pub fn reset_terminal_state(&mut self) {
         self.lines_above = VecDeque::new();
+        self.clear_scrollback_spill();
         self.lines_below = vec![];
         self.is_scrolled = false;
         self.viewport = vec![Row::new().canonical()];
@@ -2126,6 +2229,7 @@ impl Grid {
             &mut self.viewport,
             &mut self.lines_above,
             &mut self.sixel_grid,
+            &self.scrollback_spill,
             count,
             self.width,
         );
@@ -2508,6 +2612,7 @@ impl Grid {
     }
     pub fn delete_viewport_and_scroll(&mut self) {
         self.lines_above.clear();
+        self.clear_scrollback_spill();
         self.viewport.clear();
         self.lines_below.clear();
     }
@@ -2572,7 +2677,10 @@ impl Perform for Grid {
     fn execute(&mut self, byte: u8) {
         match byte {
             7 => {
-                self.ring_bell = true;
+                if !self.suppress_bell {
+                    self.ring_bell = true;
+                    self.bell_rang = true;
+                }
             },
             8 => {
                 // backspace
@@ -2713,6 +2821,29 @@ impl Perform for Grid {
                 })
             },
 
+            // Report progress (ConEmu's OSC 9;4;state;progress), which Windows Terminal shows
+            // on its taskbar button
+            b"9" => {
+                if params.get(1) != Some(&&b"4"[..]) {
+                    return;
+                }
+                let state = params
+                    .get(2)
+                    .and_then(|state| parse_number(state))
+                    .unwrap_or(0);
+                let progress = params
+                    .get(3)
+                    .and_then(|progress| parse_number(progress))
+                    .unwrap_or(0);
+                if state <= 4 {
+                    self.taskbar_progress = Some(format!(
+                        "\u{1b}]9;4;{};{}\u{1b}\\",
+                        state,
+                        progress.min(100)
+                    ));
+                }
+            },
+
             // Get/set Foreground (b"10") or background (b"11") colors
             b"10" | b"11" => {
                 if params.len() >= 2 {
@@ -2784,7 +2915,8 @@ impl Perform for Grid {
                 let _clipboard = params[1].get(0).unwrap_or(&b'c');
                 match params[2] {
                     b"?" => {
-                        // TBD: paste from own clipboard - currently unsupported
+                        // answered asynchronously by one of the clients, if it allows it
+                        self.clipboard_queried = true;
                     },
                     base64 => {
                         if let Ok(bytes) = base64::decode(base64) {
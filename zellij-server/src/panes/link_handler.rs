@@ -88,6 +88,10 @@ impl LinkHandler {
         })
     }
 
+    pub fn uri(&self, link_index: u16) -> Option<String> {
+        self.links.get(&link_index).map(|link| link.uri.clone())
+    }
+
     #[cfg(test)]
     pub fn links(&self) -> HashMap<u16, Link> {
         self.links.clone()
@@ -7,14 +7,17 @@ pub mod sixel;
 pub mod terminal_character;
 
 mod active_panes;
+mod encoding;
 pub mod floating_panes;
 mod plugin_pane;
+mod scrollback_spill;
 mod search;
 pub mod terminal_pane;
 mod tiled_panes;
 
 pub use active_panes::*;
 pub use alacritty_functions::*;
+pub use encoding::*;
 pub use floating_panes::*;
 pub use grid::*;
 pub use link_handler::*;
@@ -0,0 +1,131 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+/// Scrollback lines that fell out of a pane's in-memory scroll buffer, kept on disk so that
+/// long running panes can retain a large history without holding it all in memory.
+///
+/// Lines are stored with their ANSI styling, one per line, in two segments of up to half of
+/// `max_lines` each. When the current segment fills up it replaces the previous one, so the file
+/// backed history never grows past `max_lines` and the oldest lines are dropped first.
+#[derive(Debug)]
+pub struct ScrollbackSpill {
+    current_path: PathBuf,
+    previous_path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    lines_in_current: usize,
+    lines_in_previous: usize,
+    max_lines: usize,
+    failed: bool,
+}
+
+impl ScrollbackSpill {
+    pub fn new(folder: PathBuf, file_stem: &str, max_lines: usize) -> Self {
+        ScrollbackSpill {
+            current_path: folder.join(format!("{}.log", file_stem)),
+            previous_path: folder.join(format!("{}.1.log", file_stem)),
+            writer: None,
+            lines_in_current: 0,
+            lines_in_previous: 0,
+            max_lines,
+            failed: false,
+        }
+    }
+    pub fn push_line(&mut self, line: &str) {
+        if self.failed {
+            return;
+        }
+        if let Err(e) = self.try_push_line(line) {
+            // we don't want to spam the log (or the disk) for every line once this breaks
+            log::error!(
+                "Failed to spill scrollback to {:?}, no longer spilling for this pane: {}",
+                self.current_path,
+                e
+            );
+            self.failed = true;
+            self.writer = None;
+        }
+    }
+    pub fn line_count(&self) -> usize {
+        self.lines_in_previous + self.lines_in_current
+    }
+    /// Returns the last `count` spilled lines (or all of them if `count` is `None`), oldest first
+    pub fn read_lines(&mut self, count: Option<usize>) -> Vec<String> {
+        if let Some(writer) = self.writer.as_mut() {
+            let _ = writer.flush();
+        }
+        let total = self.line_count();
+        let to_skip = count.map(|c| total.saturating_sub(c)).unwrap_or(0);
+        let mut lines = Vec::with_capacity(total - to_skip);
+        for path in [&self.previous_path, &self.current_path] {
+            if let Ok(file) = File::open(path) {
+                lines.extend(BufReader::new(file).lines().map_while(|l| l.ok()));
+            }
+        }
+        lines.drain(..to_skip.min(lines.len()));
+        lines
+    }
+    pub fn clear(&mut self) {
+        self.writer = None;
+        self.lines_in_current = 0;
+        self.lines_in_previous = 0;
+        let _ = fs::remove_file(&self.current_path);
+        let _ = fs::remove_file(&self.previous_path);
+    }
+    fn segment_size(&self) -> usize {
+        std::cmp::max(self.max_lines / 2, 1)
+    }
+    fn try_push_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.lines_in_current >= self.segment_size() {
+            if let Some(mut writer) = self.writer.take() {
+                writer.flush()?;
+            }
+            fs::rename(&self.current_path, &self.previous_path)?;
+            self.lines_in_previous = self.lines_in_current;
+            self.lines_in_current = 0;
+        }
+        if self.writer.is_none() {
+            if let Some(folder) = self.current_path.parent() {
+                fs::create_dir_all(folder)?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.current_path)?;
+            self.writer = Some(BufWriter::new(file));
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(line.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
+        self.lines_in_current += 1;
+        Ok(())
+    }
+}
+
+impl Drop for ScrollbackSpill {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Strips the SGR sequences spilled lines are stored with
+pub fn strip_styles(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
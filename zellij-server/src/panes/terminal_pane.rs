@@ -1,10 +1,10 @@
 use crate::output::{CharacterChunk, SixelImageChunk};
 use crate::panes::sixel::SixelImageStore;
-use crate::panes::LinkHandler;
 use crate::panes::{
     grid::Grid,
     terminal_character::{render_first_run_banner, TerminalCharacter, EMPTY_TERMINAL_CHARACTER},
 };
+use crate::panes::{LinkHandler, PaneDecoder, PaneEncoding};
 use crate::pty::VteBytes;
 use crate::route::NotificationEnd;
 use crate::tab::{AdjustedInput, Pane};
@@ -20,10 +20,12 @@ use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::mouse::{MouseEvent, MouseEventType};
 use zellij_utils::pane_size::Offset;
 use zellij_utils::{
+    consts::{scrollback_spill_folder_for_session, SCROLLBACK_SPILL_LINES},
     data::{
         BareKey, InputMode, KeyWithModifier, Palette, PaletteColor, PaneId as ZellijUtilsPaneId,
         Style, Styling,
     },
+    envs,
     errors::prelude::*,
     input::layout::Run,
     pane_size::PaneGeom,
@@ -149,6 +151,9 @@ pub struct TerminalPane {
     // held on startup and can possibly be used to display some errors
     pane_frame_color_override: Option<(PaletteColor, Option<String>)>,
     invoked_with: Option<Run>,
+    foreground_process: Option<String>, // the program the shell is running, if any
+    failover_from: Option<RunCommand>,  // the command that was not found when this pane started
+    decoder: Option<PaneDecoder>,       // transcodes output that is not written in UTF-8
     #[allow(dead_code)]
     arrow_fonts: bool,
     notification_end: Option<NotificationEnd>,
@@ -202,6 +207,10 @@ impl Pane for TerminalPane {
     }
     fn handle_pty_bytes(&mut self, bytes: VteBytes) {
         self.set_should_render(true);
+        let bytes = match self.decoder.as_mut() {
+            Some(decoder) => decoder.decode(bytes),
+            None => bytes,
+        };
         for &byte in &bytes {
             self.vte_parser.advance(&mut self.grid, byte);
         }
@@ -227,6 +236,10 @@ impl Pane for TerminalPane {
         raw_input_bytes_are_kitty: bool,
         client_id: Option<ClientId>,
     ) -> Option<AdjustedInput> {
+        if self.failover_from.take().is_some() {
+            // the failover notice is shown in the frame until the user starts typing
+            self.set_should_render(true);
+        }
         // there are some cases in which the terminal state means that input sent to it
         // needs to be adjusted.
         // here we match against those cases - if need be, we adjust the input and if not
@@ -383,10 +396,19 @@ impl Pane for TerminalPane {
                 modifier_text.push(']');
             }
             format!("SEARCHING: {}{}", self.search_term, modifier_text)
+        } else if let Some(failover_from) = self
+            .failover_from
+            .as_ref()
+            .filter(|_| self.pane_name.is_empty())
+        {
+            format!(
+                "command '{}' not found, started fallback instead",
+                failover_from.command.display()
+            )
         } else if self.pane_name.is_empty() {
-            self.grid
-                .title
+            self.foreground_process
                 .clone()
+                .or_else(|| self.grid.title.clone())
                 .unwrap_or_else(|| self.pane_title.clone())
         } else {
             self.pane_name.clone()
@@ -618,6 +640,10 @@ impl Pane for TerminalPane {
         self.grid.get_selected_text()
     }
 
+    fn link_at(&self, position: &Position) -> Option<String> {
+        self.grid.link_at(position)
+    }
+
     fn set_frame(&mut self, _frame: bool) {
         self.frame.clear();
     }
@@ -816,6 +842,39 @@ impl Pane for TerminalPane {
             Some(self.pane_name.clone())
         }
     }
+    fn set_suppress_bell(&mut self, suppress_bell: bool) {
+        self.grid.suppress_bell = suppress_bell;
+    }
+    fn take_bell(&mut self) -> bool {
+        self.grid.take_bell()
+    }
+    fn take_clipboard_query(&mut self) -> bool {
+        self.grid.take_clipboard_query()
+    }
+    fn take_taskbar_progress(&mut self) -> Option<String> {
+        self.grid.take_taskbar_progress()
+    }
+    fn set_encoding(&mut self, encoding: PaneEncoding) {
+        self.decoder = Some(PaneDecoder::new(encoding));
+    }
+    fn encoding(&self) -> Option<PaneEncoding> {
+        self.decoder.as_ref().map(|decoder| decoder.encoding())
+    }
+    fn indicate_failover(&mut self, original_command: RunCommand) {
+        self.failover_from = Some(original_command);
+        self.set_should_render(true);
+    }
+    fn set_foreground_process(&mut self, foreground_process: Option<String>) -> bool {
+        if self.foreground_process == foreground_process {
+            return false;
+        }
+        self.foreground_process = foreground_process;
+        self.set_should_render(true);
+        true
+    }
+    fn foreground_process(&self) -> Option<String> {
+        self.foreground_process.clone()
+    }
     fn exit_status(&self) -> Option<i32> {
         self.is_held
             .as_ref()
@@ -959,7 +1018,7 @@ impl TerminalPane {
     ) -> TerminalPane {
         let initial_pane_title =
             initial_pane_title.unwrap_or_else(|| format!("Pane #{}", pane_index));
-        let grid = Grid::new(
+        let mut grid = Grid::new(
             position_and_size.rows.as_usize(),
             position_and_size.cols.as_usize(),
             terminal_emulator_colors,
@@ -974,6 +1033,17 @@ impl TerminalPane {
             osc8_hyperlinks,
             explicitly_disable_keyboard_protocol,
         );
+        if let Some(scrollback_spill_lines) =
+            SCROLLBACK_SPILL_LINES.get().copied().filter(|l| *l > 0)
+        {
+            if let Ok(session_name) = envs::get_session_name() {
+                grid.spill_scrollback_to(
+                    scrollback_spill_folder_for_session(&session_name),
+                    &format!("terminal_{}", pid),
+                    scrollback_spill_lines,
+                );
+            }
+        }
         if let Some(notification_end) = notification_end.as_mut() {
             notification_end.set_affected_pane_id(PaneId::Terminal(pid));
         }
@@ -1000,6 +1070,9 @@ impl TerminalPane {
             banner: None,
             pane_frame_color_override: None,
             invoked_with,
+            foreground_process: None,
+            failover_from: None,
+            decoder: None,
             arrow_fonts,
             notification_end,
         }
@@ -1006,6 +1006,7 @@ impl TiledPanes {
         current_pane_group: HashMap<ClientId, Vec<PaneId>>,
         client_id_override: Option<ClientId>,
         help_text_visible: &HashMap<ClientId, bool>,
+        client_styles: &HashMap<ClientId, Style>,
     ) -> Result<()> {
         let err_context = || "failed to render tiled panes";
 
@@ -1083,6 +1084,7 @@ impl TiledPanes {
                     pane,
                     output,
                     self.style,
+                    client_styles,
                     &active_panes,
                     multiple_users_exist_in_session,
                     None,
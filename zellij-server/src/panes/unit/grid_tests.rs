@@ -2986,6 +2986,170 @@ pub fn ring_bell() {
     assert!(grid.ring_bell);
 }
 
+#[test]
+pub fn suppressed_bell() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let osc8_hyperlinks = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        134,
+        64,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        osc8_hyperlinks,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    let fixture_name = "ring_bell";
+    let content = read_fixture(fixture_name);
+    for byte in content.clone() {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert!(grid.take_bell(), "bell is reported");
+    assert!(!grid.take_bell(), "bell is only reported once");
+    grid.ring_bell = false;
+    grid.suppress_bell = true;
+    for byte in content {
+        vte_parser.advance(&mut grid, byte);
+    }
+    assert!(!grid.ring_bell, "suppressed bell is not passed on");
+    assert!(!grid.take_bell(), "suppressed bell is not reported");
+}
+
+#[test]
+pub fn osc_52_clipboard_query() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let osc8_hyperlinks = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        20,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        osc8_hyperlinks,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "\u{1b}]52;c;aGVsbG8=\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.pending_clipboard_update, Some("hello".to_owned()));
+    assert!(!grid.take_clipboard_query(), "a write is not a query");
+    for byte in "\u{1b}]52;c;?\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert!(grid.take_clipboard_query(), "query is reported");
+    assert!(!grid.take_clipboard_query(), "query is only reported once");
+}
+
+#[test]
+pub fn osc_9_4_taskbar_progress() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let osc8_hyperlinks = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        20,
+        20,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        osc8_hyperlinks,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "\u{1b}]9;4;1;42\u{7}\u{1b}]9;4;1;250\u{1b}\\".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.take_taskbar_progress(),
+        Some("\u{1b}]9;4;1;100\u{1b}\\".to_owned()),
+        "the last progress is reported, capped at 100%"
+    );
+    assert_eq!(grid.take_taskbar_progress(), None);
+    for byte in "\u{1b}]9;some notification\u{7}\u{1b}]9;4;7;5\u{7}".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.take_taskbar_progress(),
+        None,
+        "other OSC 9 sequences and unknown states are not progress"
+    );
+}
+
+#[test]
+pub fn link_at_position() {
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let osc8_hyperlinks = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        20,
+        40,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        osc8_hyperlinks,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    let content = "see \u{1b}]8;;https://zellij.dev\u{1b}\\the docs\u{1b}]8;;\u{1b}\\ here";
+    for byte in content.as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(grid.link_at(&Position::new(0, 3)), None);
+    assert_eq!(
+        grid.link_at(&Position::new(0, 4)),
+        Some("https://zellij.dev".to_owned())
+    );
+    assert_eq!(
+        grid.link_at(&Position::new(0, 11)),
+        Some("https://zellij.dev".to_owned())
+    );
+    assert_eq!(grid.link_at(&Position::new(0, 12)), None);
+    assert_eq!(grid.link_at(&Position::new(5, 4)), None);
+}
+
 #[test]
 pub fn alternate_screen_change_size() {
     let mut vte_parser = vte::Parser::new();
@@ -3269,6 +3433,44 @@ fn terminal_pixel_size_reports() {
     );
 }
 
+#[test]
+fn cursor_position_queries_are_answered_with_the_pane_cursor() {
+    // a pseudoconsole inheriting the cursor asks for it before drawing anything
+    let mut vte_parser = vte::Parser::new();
+    let sixel_image_store = Rc::new(RefCell::new(SixelImageStore::default()));
+    let terminal_emulator_color_codes = Rc::new(RefCell::new(HashMap::new()));
+    let debug = false;
+    let arrow_fonts = true;
+    let styled_underlines = true;
+    let osc8_hyperlinks = true;
+    let explicitly_disable_kitty_keyboard_protocol = false;
+    let mut grid = Grid::new(
+        51,
+        97,
+        Rc::new(RefCell::new(Palette::default())),
+        terminal_emulator_color_codes,
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        sixel_image_store,
+        Style::default(),
+        debug,
+        arrow_fonts,
+        styled_underlines,
+        osc8_hyperlinks,
+        explicitly_disable_kitty_keyboard_protocol,
+    );
+    for byte in "\u{1b}[6nabc\r\ndef\u{1b}[6n".as_bytes() {
+        vte_parser.advance(&mut grid, *byte);
+    }
+    assert_eq!(
+        grid.pending_messages_to_pty
+            .iter()
+            .map(|bytes| String::from_utf8(bytes.clone()).unwrap())
+            .collect::<Vec<String>>(),
+        vec!["\x1b[1;1R", "\x1b[2;4R"]
+    );
+}
+
 #[test]
 fn terminal_pixel_size_reports_in_unsupported_terminals() {
     let mut vte_parser = vte::Parser::new();
@@ -4362,3 +4564,69 @@ fn single_click_drag_selection_preserved_after_scroll() {
     assert_eq!(grid.selection.start.column, start_before.column);
     assert_eq!(grid.selection.end.column, end_before.column);
 }
+
+fn grid_spilling_scrollback(folder: std::path::PathBuf, max_lines: usize) -> Grid {
+    let mut grid = Grid::new(
+        10,
+        80,
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+        Style::default(),
+        false,
+        true,
+        true,
+        true,
+        false,
+    );
+    grid.spill_scrollback_to(folder, "terminal_1", max_lines);
+    grid
+}
+
+#[test]
+pub fn lines_dropped_from_scroll_buffer_are_spilled_to_disk() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = grid_spilling_scrollback(spill_dir.path().to_path_buf(), 100_000);
+    for i in 0..10_050 {
+        for byte in format!("line {}\n\r", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let full_dump = grid.dump_screen(true);
+    let dumped_lines: Vec<&str> = full_dump.lines().collect();
+    assert_eq!(dumped_lines[0], "line 0", "oldest line retrieved from disk");
+    assert_eq!(dumped_lines[10_049], "line 10049");
+    assert!(spill_dir.path().join("terminal_1.log").exists());
+    let serialized = grid.serialize(Some(0)).unwrap();
+    assert!(
+        serialized.starts_with("\n\r\u{1b}[mline 0\u{1b}[m\n\r\u{1b}[mline 1\u{1b}[m"),
+        "spilled lines serialized ahead of the scroll buffer"
+    );
+}
+
+#[test]
+pub fn spilled_scrollback_is_capped_and_removed_with_the_grid() {
+    let spill_dir = tempfile::tempdir().unwrap();
+    let mut vte_parser = vte::Parser::new();
+    let mut grid = grid_spilling_scrollback(spill_dir.path().to_path_buf(), 100);
+    for i in 0..10_500 {
+        for byte in format!("line {}\n\r", i).as_bytes() {
+            vte_parser.advance(&mut grid, *byte);
+        }
+    }
+    let full_dump = grid.dump_screen(true);
+    let first_line = full_dump.lines().next().unwrap().to_owned();
+    let first_line_index: usize = first_line.trim_start_matches("line ").parse().unwrap();
+    let lines_dropped_from_memory = 10_500 - 10_000 - 9;
+    assert!(
+        first_line_index >= lines_dropped_from_memory - 100,
+        "kept at most 100 spilled lines, first line was: {}",
+        first_line
+    );
+    drop(grid);
+    assert!(!spill_dir.path().join("terminal_1.log").exists());
+    assert!(!spill_dir.path().join("terminal_1.1.log").exists());
+}
@@ -1,6 +1,6 @@
 use super::super::TerminalPane;
 use crate::panes::sixel::SixelImageStore;
-use crate::panes::LinkHandler;
+use crate::panes::{LinkHandler, PaneEncoding};
 use crate::tab::Pane;
 use ::insta::assert_snapshot;
 use std::cell::RefCell;
@@ -800,3 +800,73 @@ pub fn frameless_pane_position_is_on_frame() {
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 130)));
     assert!(!terminal_pane.position_is_on_frame(&Position::new(30, 131)));
 }
+
+fn terminal_pane_for_encoding_tests() -> TerminalPane {
+    let mut fake_win_size = PaneGeom::default();
+    fake_win_size.cols.set_inner(40);
+    fake_win_size.rows.set_inner(5);
+    TerminalPane::new(
+        1,
+        fake_win_size,
+        Style::default(),
+        0,
+        String::new(),
+        Rc::new(RefCell::new(LinkHandler::new())),
+        Rc::new(RefCell::new(None)),
+        Rc::new(RefCell::new(SixelImageStore::default())),
+        Rc::new(RefCell::new(Palette::default())),
+        Rc::new(RefCell::new(HashMap::new())),
+        None,
+        None,
+        false,
+        true,
+        true,
+        true,
+        false,
+        None,
+    )
+}
+
+#[test]
+pub fn pane_output_in_legacy_codepage_is_transcoded() {
+    let mut terminal_pane = terminal_pane_for_encoding_tests();
+    terminal_pane.set_encoding("cp437".parse().unwrap());
+    // "┌─┐ Größe" in codepage 437
+    terminal_pane.handle_pty_bytes(vec![0xda, 0xc4, 0xbf, b' ', b'G', b'r', 0x94, 0xe1, b'e']);
+    assert!(format!("{:?}", terminal_pane.grid).contains("┌─┐ Größe"));
+
+    let mut terminal_pane = terminal_pane_for_encoding_tests();
+    terminal_pane.set_encoding("windows-1252".parse().unwrap());
+    terminal_pane.handle_pty_bytes(vec![0x80, b' ', 0xe9, b't', 0xe9]);
+    assert!(format!("{:?}", terminal_pane.grid).contains("€ été"));
+}
+
+#[test]
+pub fn auto_encoding_keeps_utf8_split_across_reads() {
+    let mut terminal_pane = terminal_pane_for_encoding_tests();
+    terminal_pane.set_encoding(PaneEncoding::Auto);
+    let bytes = "Größe │".as_bytes();
+    let (first, second) = bytes.split_at(3); // splits the "ö"
+    terminal_pane.handle_pty_bytes(first.to_vec());
+    terminal_pane.handle_pty_bytes(second.to_vec());
+    assert!(format!("{:?}", terminal_pane.grid).contains("Größe │"));
+}
+
+#[cfg(not(windows))] // the fallback is the OEM codepage of the system on Windows
+#[test]
+pub fn auto_encoding_decodes_invalid_utf8_as_cp437() {
+    let mut terminal_pane = terminal_pane_for_encoding_tests();
+    terminal_pane.set_encoding(PaneEncoding::Auto);
+    terminal_pane.handle_pty_bytes([b"ok ".as_slice(), &[0xfb], " ü".as_bytes()].concat());
+    assert!(format!("{:?}", terminal_pane.grid).contains("ok √ ü"));
+}
+
+#[test]
+pub fn unknown_encoding_is_rejected() {
+    assert!("not-an-encoding".parse::<PaneEncoding>().is_err());
+    assert_eq!(
+        "CP850".parse::<PaneEncoding>().unwrap(),
+        PaneEncoding::Cp850
+    );
+    assert_eq!("65001".parse::<PaneEncoding>().unwrap(), PaneEncoding::Utf8);
+}
@@ -154,12 +154,14 @@ impl<'a> PluginLoader<'a> {
         Ok(module)
     }
     fn load_module_from_memory(&mut self) -> Result<Module> {
+        // modules are reference counted, so all instances of a plugin (eg. the tab-bar of every
+        // tab) share the one module we parsed for the first of them
         let module = self
             .plugin_cache
             .lock()
             .unwrap()
-            .remove(&self.plugin_config.path) // TODO: do we still bring it back later?
-            // maybe we can forgo this dance?
+            .get(&self.plugin_config.path)
+            .cloned()
             .ok_or(anyhow!("Plugin is not stored in memory"))?;
         Ok(module)
     }
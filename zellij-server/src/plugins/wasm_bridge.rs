@@ -2004,6 +2004,8 @@ fn check_event_permission(
         | Event::CwdChanged(..)
         | Event::AvailableLayoutInfo(..)
         | Event::PluginConfigurationChanged(..)
+        | Event::PaneBecameIdle(..)
+        | Event::PaneBecameActive(..)
         | Event::InputReceived => PermissionType::ReadApplicationState,
         Event::WebServerStatus(..) => PermissionType::StartWebServer,
         Event::PaneRenderReport(..) => PermissionType::ReadPaneContents,
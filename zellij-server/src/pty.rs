@@ -12,8 +12,13 @@ use crate::{
     thread_bus::{Bus, ThreadSenders},
     ClientId, ServerInstruction,
 };
+use std::fs::{File, OpenOptions};
+use std::io::Write;
 use std::sync::Arc;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use tokio::task::{self, JoinHandle};
 use zellij_utils::{
     data::{
@@ -153,6 +158,19 @@ pub enum PtyInstruction {
         response_channel: crossbeam::channel::Sender<GetPaneCwdResponse>,
     },
     UpdateAndReportCwds,
+    UploadFileChunk {
+        file_name: String,
+        bytes: Vec<u8>,
+        is_first: bool,
+        is_last: bool,
+        client_id: ClientId,
+    },
+    CloseAllPanes(Option<NotificationEnd>), // kills the processes of every terminal pane
+    SetSessionEnv {
+        name: String,
+        value: Option<String>, // None removes the variable
+        completion_tx: Option<NotificationEnd>,
+    },
     Exit,
 }
 
@@ -185,6 +203,9 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::GetPaneRunningCommand { .. } => PtyContext::GetPaneRunningCommand,
             PtyInstruction::GetPaneCwd { .. } => PtyContext::GetPaneCwd,
             PtyInstruction::UpdateAndReportCwds => PtyContext::UpdateAndReportCwds,
+            PtyInstruction::UploadFileChunk { .. } => PtyContext::UploadFileChunk,
+            PtyInstruction::CloseAllPanes(..) => PtyContext::CloseAllPanes,
+            PtyInstruction::SetSessionEnv { .. } => PtyContext::SetSessionEnv,
             PtyInstruction::Exit => PtyContext::Exit,
         }
     }
@@ -199,8 +220,11 @@ pub(crate) struct Pty {
     task_handles: HashMap<u32, JoinHandle<()>>, // terminal_id to join-handle
     default_editor: Option<PathBuf>,
     post_command_discovery_hook: Option<String>,
+    output_rate_limit: Option<u64>,       // MB/s
     plugin_cwds: HashMap<u32, PathBuf>,   // plugin_id -> cwd
     terminal_cwds: HashMap<u32, PathBuf>, // terminal_id -> cwd
+    // (client_id, file name) -> (path, file) of the files being uploaded by clients
+    file_uploads: HashMap<(ClientId, String), (PathBuf, File)>,
 }
 
 pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
@@ -879,6 +903,32 @@ pub(crate) fn pty_thread_main(mut pty: Pty, layout: Box<Layout>) -> Result<()> {
             PtyInstruction::UpdateAndReportCwds => {
                 pty.update_and_report_cwds();
             },
+            PtyInstruction::UploadFileChunk {
+                file_name,
+                bytes,
+                is_first,
+                is_last,
+                client_id,
+            } => {
+                pty.upload_file_chunk(file_name, bytes, is_first, is_last, client_id);
+            },
+            PtyInstruction::CloseAllPanes(_completion_tx) => {
+                let terminal_ids: Vec<u32> = pty.id_to_child_pid.keys().copied().collect();
+                for id in terminal_ids {
+                    pty.close_pane(PaneId::Terminal(id))
+                        .with_context(|| format!("failed to close pane for pid {id}"))
+                        .non_fatal();
+                }
+            },
+            PtyInstruction::SetSessionEnv {
+                name,
+                value,
+                completion_tx: _completion_tx,
+            } => {
+                if let Some(os_input) = pty.bus.os_input.as_ref() {
+                    os_input.set_session_env_var(name, value);
+                }
+            },
             PtyInstruction::Exit => break,
         }
     }
@@ -891,6 +941,7 @@ impl Pty {
         debug_to_file: bool,
         default_editor: Option<PathBuf>,
         post_command_discovery_hook: Option<String>,
+        output_rate_limit: Option<u64>,
     ) -> Self {
         Pty {
             active_panes: HashMap::new(),
@@ -901,8 +952,10 @@ impl Pty {
             default_editor,
             originating_plugins: HashMap::new(),
             post_command_discovery_hook,
+            output_rate_limit,
             plugin_cwds: HashMap::new(),
             terminal_cwds: HashMap::new(),
+            file_uploads: HashMap::new(),
         }
     }
     pub fn get_default_terminal(
@@ -950,26 +1003,28 @@ impl Pty {
             TerminalAction::OpenFile(payload) => &mut payload.cwd,
         };
         if cwd.is_none() {
-            *cwd = self
-                .active_panes
-                .get(&client_id)
-                .and_then(|pane| match pane {
-                    PaneId::Plugin(plugin_id) => self.plugin_cwds.get(plugin_id).cloned(),
-                    PaneId::Terminal(id) => {
-                        // Try to get CWD from OS, fall back to cached value
-                        self.id_to_child_pid
-                            .get(id)
-                            .and_then(|&pid| {
-                                self.bus
-                                    .os_input
-                                    .as_ref()
-                                    .and_then(|input| input.get_cwd(pid))
-                            })
-                            .or_else(|| self.terminal_cwds.get(id).cloned())
-                    },
-                })
+            *cwd = self.focused_pane_cwd(client_id);
         };
     }
+    fn focused_pane_cwd(&self, client_id: ClientId) -> Option<PathBuf> {
+        self.active_panes
+            .get(&client_id)
+            .and_then(|pane| match pane {
+                PaneId::Plugin(plugin_id) => self.plugin_cwds.get(plugin_id).cloned(),
+                PaneId::Terminal(id) => {
+                    // Try to get CWD from OS, fall back to cached value
+                    self.id_to_child_pid
+                        .get(id)
+                        .and_then(|&pid| {
+                            self.bus
+                                .os_input
+                                .as_ref()
+                                .and_then(|input| input.get_cwd(pid))
+                        })
+                        .or_else(|| self.terminal_cwds.get(id).cloned())
+                },
+            })
+    }
     fn fill_cwd_from_pane_id(&self, terminal_action: &mut TerminalAction, pane_id: &PaneId) {
         let cwd = match terminal_action {
             TerminalAction::RunCommand(run_command) => &mut run_command.cwd,
@@ -1112,12 +1167,19 @@ impl Pty {
                 |terminal_id: u32| format!("failed to run async task for terminal {terminal_id}");
             let senders = self.bus.senders.clone();
             let debug_to_file = self.debug_to_file;
+            let output_rate_limit = self.output_rate_limit;
             async move {
-                TerminalBytes::new(terminal_id, reader, senders, debug_to_file)
-                    .listen()
-                    .await
-                    .with_context(|| err_context(terminal_id))
-                    .fatal();
+                TerminalBytes::new(
+                    terminal_id,
+                    reader,
+                    senders,
+                    debug_to_file,
+                    output_rate_limit,
+                )
+                .listen()
+                .await
+                .with_context(|| err_context(terminal_id))
+                .fatal();
             }
         });
 
@@ -1305,12 +1367,19 @@ impl Pty {
                     let terminal_bytes = async_runtime().spawn({
                         let senders = self.bus.senders.clone();
                         let debug_to_file = self.debug_to_file;
+                        let output_rate_limit = self.output_rate_limit;
                         async move {
-                            TerminalBytes::new(terminal_id, reader, senders, debug_to_file)
-                                .listen()
-                                .await
-                                .context("failed to spawn terminals for layout")
-                                .fatal();
+                            TerminalBytes::new(
+                                terminal_id,
+                                reader,
+                                senders,
+                                debug_to_file,
+                                output_rate_limit,
+                            )
+                            .listen()
+                            .await
+                            .context("failed to spawn terminals for layout")
+                            .fatal();
                         }
                     });
                     self.task_handles.insert(terminal_id, terminal_bytes);
@@ -1473,12 +1542,19 @@ impl Pty {
                     let terminal_bytes = async_runtime().spawn({
                         let senders = self.bus.senders.clone();
                         let debug_to_file = self.debug_to_file;
+                        let output_rate_limit = self.output_rate_limit;
                         async move {
-                            TerminalBytes::new(terminal_id, reader, senders, debug_to_file)
-                                .listen()
-                                .await
-                                .context("failed to spawn terminals for layout")
-                                .fatal();
+                            TerminalBytes::new(
+                                terminal_id,
+                                reader,
+                                senders,
+                                debug_to_file,
+                                output_rate_limit,
+                            )
+                            .listen()
+                            .await
+                            .context("failed to spawn terminals for layout")
+                            .fatal();
                         }
                     });
                     self.task_handles.insert(terminal_id, terminal_bytes);
@@ -1852,8 +1928,9 @@ impl Pty {
                         |pane_id| format!("failed to run async task for pane {pane_id:?}");
                     let senders = self.bus.senders.clone();
                     let debug_to_file = self.debug_to_file;
+                    let output_rate_limit = self.output_rate_limit;
                     async move {
-                        TerminalBytes::new(id, reader, senders, debug_to_file)
+                        TerminalBytes::new(id, reader, senders, debug_to_file, output_rate_limit)
                             .listen()
                             .await
                             .with_context(|| err_context(pane_id))
@@ -1943,27 +2020,7 @@ impl Pty {
         floating_pane_coordinates: Option<FloatingPaneCoordinates>,
         completion_tx: Option<NotificationEnd>,
     ) -> Result<()> {
-        let get_focused_cwd = || {
-            self.active_panes
-                .get(&client_id)
-                .and_then(|pane| match pane {
-                    PaneId::Plugin(plugin_id) => self.plugin_cwds.get(plugin_id).cloned(),
-                    PaneId::Terminal(id) => {
-                        // Try to get CWD from OS, fall back to cached value
-                        self.id_to_child_pid
-                            .get(id)
-                            .and_then(|&pid| {
-                                self.bus
-                                    .os_input
-                                    .as_ref()
-                                    .and_then(|input| input.get_cwd(pid))
-                            })
-                            .or_else(|| self.terminal_cwds.get(id).cloned())
-                    },
-                })
-        };
-
-        let cwd = cwd.or_else(get_focused_cwd);
+        let cwd = cwd.or_else(|| self.focused_pane_cwd(client_id));
         let focused_plugin_id = self
             .active_panes
             .get(&client_id)
@@ -1973,7 +2030,7 @@ impl Pty {
             });
 
         if let RunPluginOrAlias::Alias(alias) = &mut run {
-            let cwd = get_focused_cwd();
+            let cwd = self.focused_pane_cwd(client_id);
             alias.set_caller_cwd_if_not_set(cwd);
         }
         self.bus.senders.send_to_plugin(PluginInstruction::Load(
@@ -2158,6 +2215,84 @@ impl Pty {
             )),
         }
     }
+    /// Write a chunk of a file uploaded by `client_id` into the cwd of its focused pane. The
+    /// first chunk creates the file (never overwriting an existing one), the last one reports
+    /// where it was saved.
+    pub fn upload_file_chunk(
+        &mut self,
+        file_name: String,
+        bytes: Vec<u8>,
+        is_first: bool,
+        is_last: bool,
+        client_id: ClientId,
+    ) {
+        let upload_key = (client_id, file_name);
+        if is_first {
+            match self.create_uploaded_file(&upload_key.1, client_id) {
+                Ok(path_and_file) => {
+                    self.file_uploads.insert(upload_key.clone(), path_and_file);
+                },
+                Err(e) => {
+                    self.log_error_to_client(
+                        format!("Failed to upload {}: {}", upload_key.1, e),
+                        client_id,
+                    );
+                    return;
+                },
+            }
+        }
+        let Some((path, file)) = self.file_uploads.get_mut(&upload_key) else {
+            // an earlier chunk of this upload failed and was already reported
+            return;
+        };
+        if let Err(e) = file.write_all(&bytes) {
+            let path = path.clone();
+            self.file_uploads.remove(&upload_key);
+            let _ = std::fs::remove_file(&path);
+            self.log_error_to_client(
+                format!("Failed to write {}: {}", path.display(), e),
+                client_id,
+            );
+            return;
+        }
+        if is_last {
+            if let Some((path, _file)) = self.file_uploads.remove(&upload_key) {
+                let _ = self.bus.senders.send_to_server(ServerInstruction::Log(
+                    vec![format!("Uploaded {}", path.display())],
+                    client_id,
+                    None,
+                ));
+            }
+        }
+    }
+    fn create_uploaded_file(
+        &self,
+        file_name: &str,
+        client_id: ClientId,
+    ) -> std::result::Result<(PathBuf, File), String> {
+        // only keep the last component, so that uploads cannot escape the cwd
+        let file_name = Path::new(file_name)
+            .file_name()
+            .ok_or_else(|| String::from("invalid file name"))?;
+        let cwd = self
+            .focused_pane_cwd(client_id)
+            .ok_or_else(|| String::from("could not find the cwd of the focused pane"))?;
+        let path = cwd.join(file_name);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("{} ({})", e, path.display()))?;
+        Ok((path, file))
+    }
+    fn log_error_to_client(&self, error: String, client_id: ClientId) {
+        log::error!("{}", error);
+        let _ = self.bus.senders.send_to_server(ServerInstruction::LogError(
+            vec![error],
+            client_id,
+            None,
+        ));
+    }
     pub fn get_pane_cwd(&self, pane_id: PaneId) -> GetPaneCwdResponse {
         match pane_id {
             PaneId::Terminal(terminal_id) => {
@@ -32,13 +32,18 @@ use zellij_utils::{
         layout::Layout,
     },
     ipc::{
-        ClientAttributes, ClientToServerMsg, ExitReason, IpcReceiverWithContext, ServerToClientMsg,
+        ClientAttributes, ClientToServerMsg, DownloadableFile, ExitReason, IpcReceiverWithContext,
+        ServerToClientMsg,
     },
+    logging,
 };
 
 use crate::ClientId;
 
 const ACTION_COMPLETION_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long clients detached by `zellij attach --force` get to restore their terminals and
+/// acknowledge with ClientExited before they are disconnected regardless
+const FORCE_DETACH_TIMEOUT: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Clone)]
 pub struct ActionCompletionResult {
@@ -402,12 +407,16 @@ pub(crate) fn route_action(
         Action::DumpScreen {
             file_path,
             include_scrollback,
+            keep_ansi,
+            crlf_line_endings,
         } => {
             senders
                 .send_to_screen(ScreenInstruction::DumpScreen(
                     file_path,
                     client_id,
                     include_scrollback,
+                    keep_ansi,
+                    crlf_line_endings,
                     Some(NotificationEnd::new(completion_tx)),
                 ))
                 .with_context(err_context)?;
@@ -848,6 +857,24 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::NewBackgroundPane {
+            command: run_command,
+            pane_name: name,
+        } => {
+            let run_cmd = Some(TerminalAction::RunCommand(run_command.into()));
+            let start_suppressed = true;
+            senders
+                .send_to_pty(PtyInstruction::SpawnTerminal(
+                    run_cmd,
+                    name,
+                    NewPanePlacement::default(),
+                    start_suppressed,
+                    ClientTabIndexOrPaneId::ClientId(client_id),
+                    Some(NotificationEnd::new(completion_tx)),
+                    false, // set_blocking
+                ))
+                .with_context(err_context)?;
+        },
         Action::TogglePaneEmbedOrFloating => {
             senders
                 .send_to_screen(ScreenInstruction::TogglePaneEmbedOrFloating(
@@ -1467,6 +1494,48 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::BreakPaneToWindow => {
+            senders
+                .send_to_screen(ScreenInstruction::BreakPaneToWindow(
+                    default_shell.clone(),
+                    client_id,
+                    Some(NotificationEnd::new(completion_tx)),
+                ))
+                .with_context(err_context)?;
+        },
+        Action::ListClipboardHistory => {
+            senders
+                .send_to_screen(ScreenInstruction::ListClipboardHistory(
+                    cli_client_id.unwrap_or(client_id),
+                    Some(NotificationEnd::new(completion_tx)),
+                ))
+                .with_context(err_context)?;
+        },
+        Action::PasteFromClipboardHistory {
+            index,
+            pane_id,
+            copy_only,
+        } => {
+            senders
+                .send_to_screen(ScreenInstruction::PasteFromClipboardHistory(
+                    index,
+                    pane_id.map(|pane_id| pane_id.into()),
+                    copy_only,
+                    client_id,
+                    cli_client_id.unwrap_or(client_id),
+                    Some(NotificationEnd::new(completion_tx)),
+                ))
+                .with_context(err_context)?;
+        },
+        Action::PickFromClipboardHistory { .. } => {
+            // the picker itself runs in the cli client, this opens a pane running it
+            senders
+                .send_to_screen(ScreenInstruction::PickFromClipboardHistory(
+                    client_id,
+                    Some(NotificationEnd::new(completion_tx)),
+                ))
+                .with_context(err_context)?;
+        },
         Action::RenameSession { name } => {
             senders
                 .send_to_screen(ScreenInstruction::RenameSession(
@@ -1476,6 +1545,39 @@ pub(crate) fn route_action(
                 ))
                 .with_context(err_context)?;
         },
+        Action::SetSessionSecret {
+            secret,
+            current_secret,
+        } => {
+            senders
+                .send_to_server(ServerInstruction::SetSessionSecret {
+                    secret,
+                    current_secret,
+                    client_id: cli_client_id.unwrap_or(client_id),
+                    completion_tx: Some(NotificationEnd::new(completion_tx)),
+                })
+                .with_context(err_context)?;
+        },
+        Action::SetLogLevel { level } => {
+            logging::set_log_level(level.into());
+        },
+        Action::SetSessionEnv { name, value } => {
+            senders
+                .send_to_pty(PtyInstruction::SetSessionEnv {
+                    name,
+                    value,
+                    completion_tx: Some(NotificationEnd::new(completion_tx)),
+                })
+                .with_context(err_context)?;
+        },
+        Action::SetIdleShutdownExempt { exempt } => {
+            senders
+                .send_to_screen(ScreenInstruction::SetIdleShutdownExempt(
+                    exempt,
+                    Some(NotificationEnd::new(completion_tx)),
+                ))
+                .with_context(err_context)?;
+        },
         Action::CliPipe {
             pipe_id,
             mut name,
@@ -1623,6 +1725,30 @@ pub(crate) fn route_action(
             }
             drop(NotificationEnd::new(completion_tx));
         },
+        Action::ListBackgroundJobs { output_json } => {
+            // hidden panes are not selectable, so they are only listed with all the others
+            let show_all = true;
+            let maybe_panes =
+                request_panes_from_screen(&senders, show_all).with_context(err_context)?;
+
+            if let Some(pane_entries) = maybe_panes {
+                let background_jobs: Vec<PaneListEntry> =
+                    pane_entries.into_iter().filter(is_background_job).collect();
+                let output_lines = if output_json {
+                    format_panes_as_json(&background_jobs)
+                } else {
+                    format_background_jobs_table(&background_jobs)
+                };
+                send_output_to_client(cli_client_id, os_input.as_ref(), output_lines);
+            } else {
+                send_error_to_client(
+                    cli_client_id,
+                    os_input.as_ref(),
+                    "Timeout listing background jobs",
+                );
+            }
+            drop(NotificationEnd::new(completion_tx));
+        },
         Action::ListTabs {
             show_state,
             show_dimensions,
@@ -1801,6 +1927,53 @@ macro_rules! send_to_screen_or_retry_queue {
     }};
 }
 
+fn detach_other_clients(
+    os_input: &dyn ServerOsApi,
+    session_state: &Arc<RwLock<SessionState>>,
+    to_server: &SenderWithContext<ServerInstruction>,
+    client_id: ClientId,
+) {
+    let other_client_ids: Vec<ClientId> = session_state
+        .read()
+        .unwrap()
+        .client_ids()
+        .into_iter()
+        .filter(|c| *c != client_id)
+        .collect();
+    for other_client_id in &other_client_ids {
+        let _ = os_input.send_to_client(
+            *other_client_id,
+            ServerToClientMsg::Exit {
+                exit_reason: ExitReason::ForceDetached,
+            },
+        );
+    }
+    let started_at = std::time::Instant::now();
+    loop {
+        let remaining_client_ids: Vec<ClientId> = {
+            let session_state = session_state.read().unwrap();
+            let client_ids = session_state.client_ids();
+            other_client_ids
+                .iter()
+                .copied()
+                .filter(|c| client_ids.contains(c))
+                .collect()
+        };
+        if remaining_client_ids.is_empty() {
+            break;
+        }
+        if started_at.elapsed() >= FORCE_DETACH_TIMEOUT {
+            log::warn!(
+                "Clients {:?} did not acknowledge being detached, disconnecting them",
+                remaining_client_ids
+            );
+            let _ = to_server.send(ServerInstruction::DetachSession(remaining_client_ids, None));
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
 pub(crate) fn route_thread_main(
     session_data: Arc<RwLock<Option<SessionMetaData>>>,
     session_state: Arc<RwLock<SessionState>>,
@@ -1813,6 +1986,8 @@ pub(crate) fn route_thread_main(
     let err_context = || format!("failed to handle instruction for client {client_id}");
     let mut seen_cli_pipes = HashSet::new();
     let mut consecutive_unknown_messages_received = 0;
+    // whether this connection attached with the secret of the session, or proved it knows it
+    let mut is_authenticated = false;
     'route_loop: loop {
         match receiver.recv_client_msg() {
             Some((instruction, err_ctx)) => {
@@ -1874,6 +2049,30 @@ pub(crate) fn route_thread_main(
                         return Ok(should_break);
                     }
 
+                    // a locked session only takes instructions from connections that attached
+                    // with its secret or otherwise proved they know it
+                    let session_secret = session_data
+                        .read()
+                        .ok()
+                        .and_then(|s| s.as_ref().and_then(|s| s.session_secret.clone()));
+                    let needs_secret = !matches!(
+                        instruction,
+                        ClientToServerMsg::FirstClientConnected { .. }
+                            | ClientToServerMsg::AttachClient { .. }
+                            | ClientToServerMsg::Authenticate { .. }
+                            | ClientToServerMsg::ClientExited
+                            | ClientToServerMsg::ConnStatus
+                            | ClientToServerMsg::ConnStatusDetailed
+                    );
+                    if session_secret.is_some() && needs_secret && !is_authenticated {
+                        log::warn!(
+                            "Client {} sent an instruction to a locked session without its secret",
+                            client_id
+                        );
+                        let _ = to_server.send(ServerInstruction::SendSessionLocked(client_id));
+                        return Ok(true);
+                    }
+
                     match instruction {
                         ClientToServerMsg::Key {
                             key,
@@ -2147,6 +2346,8 @@ pub(crate) fn route_thread_main(
                             cli_assets,
                             is_web_client,
                         } => {
+                            // the session is created with the secret of this client, if any
+                            is_authenticated = true;
                             let new_client_instruction = ServerInstruction::FirstClientConnected(
                                 cli_assets,
                                 is_web_client,
@@ -2170,7 +2371,19 @@ pub(crate) fn route_thread_main(
                                 })
                                 .unwrap_or(false);
                             let should_allow_connection = !is_web_client || allow_web_connections;
-                            if should_allow_connection {
+                            let is_locked_out = !session_secret_matches(
+                                session_secret.as_deref(),
+                                cli_assets.session_secret.as_deref(),
+                            );
+                            if should_allow_connection && is_locked_out {
+                                log::warn!(
+                                    "Client {} tried to attach to a locked session",
+                                    client_id
+                                );
+                                let _ =
+                                    to_server.send(ServerInstruction::SendSessionLocked(client_id));
+                            } else if should_allow_connection {
+                                is_authenticated = true;
                                 let attach_client_instruction = ServerInstruction::AttachClient(
                                     cli_assets,
                                     tab_position_to_focus,
@@ -2239,11 +2452,106 @@ pub(crate) fn route_thread_main(
                             let _ = to_server.send(ServerInstruction::ConnStatus(client_id));
                             should_break = true;
                         },
+                        ClientToServerMsg::ConnStatusDetailed => {
+                            let _ =
+                                to_server.send(ServerInstruction::ConnStatusDetailed(client_id));
+                            should_break = true;
+                        },
+                        ClientToServerMsg::Heartbeat { id } => {
+                            let _ = to_server.send(ServerInstruction::Heartbeat(client_id, id));
+                        },
+                        ClientToServerMsg::Redraw => {
+                            send_to_screen_or_retry_queue!(
+                                senders,
+                                ScreenInstruction::ForceRender,
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
+                        ClientToServerMsg::TerminalFocus { focused } => {
+                            send_to_screen_or_retry_queue!(
+                                senders,
+                                ScreenInstruction::TerminalFocus(client_id, focused),
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
+                        ClientToServerMsg::UploadFileChunk {
+                            file_name,
+                            bytes,
+                            is_first,
+                            is_last,
+                        } => {
+                            if let Some(senders) = senders.as_ref() {
+                                senders
+                                    .send_to_pty(PtyInstruction::UploadFileChunk {
+                                        file_name,
+                                        bytes,
+                                        is_first,
+                                        is_last,
+                                        client_id,
+                                    })
+                                    .with_context(err_context)?;
+                            }
+                        },
+                        ClientToServerMsg::DownloadFile {
+                            file: DownloadableFile::ScreenDump,
+                        } => {
+                            send_to_screen_or_retry_queue!(
+                                senders,
+                                ScreenInstruction::DownloadScreenDump(client_id),
+                                instruction,
+                                retry_queue
+                            )
+                            .with_context(err_context)?;
+                        },
+                        ClientToServerMsg::DownloadFile {
+                            file: DownloadableFile::Log,
+                        } => {
+                            let log = std::fs::read(logging::log_file()).unwrap_or_else(|e| {
+                                let _ = to_server.send(ServerInstruction::LogError(
+                                    vec![format!("Failed to read the log file: {}", e)],
+                                    client_id,
+                                    None,
+                                ));
+                                vec![]
+                            });
+                            let _ = to_server.send(ServerInstruction::SendFile(
+                                client_id,
+                                String::from("zellij.log"),
+                                log,
+                            ));
+                        },
                         ClientToServerMsg::DetachSession { client_ids } => {
                             let _ =
                                 to_server.send(ServerInstruction::DetachSession(client_ids, None));
                             should_break = true;
                         },
+                        ClientToServerMsg::Authenticate {
+                            session_secret: given_secret,
+                        } => {
+                            if session_secret_matches(
+                                session_secret.as_deref(),
+                                Some(&given_secret),
+                            ) {
+                                is_authenticated = true;
+                            } else {
+                                log::warn!(
+                                    "Client {} gave the wrong secret of a locked session",
+                                    client_id
+                                );
+                                let _ =
+                                    to_server.send(ServerInstruction::SendSessionLocked(client_id));
+                                should_break = true;
+                            }
+                        },
+                        ClientToServerMsg::DetachOtherClients => {
+                            // we only go on to handle the AttachClient message following this
+                            // once the other clients are gone
+                            detach_other_clients(&*os_input, &session_state, &to_server, client_id);
+                        },
                         ClientToServerMsg::WebServerStarted { base_url } => {
                             let _ = to_server.send(ServerInstruction::WebServerStarted(base_url));
                         },
@@ -2251,6 +2559,20 @@ pub(crate) fn route_thread_main(
                             let _ =
                                 to_server.send(ServerInstruction::FailedToStartWebServer(error));
                         },
+                        ClientToServerMsg::ClipboardContent {
+                            terminal_id,
+                            content,
+                        } => {
+                            // a client that declined to share its clipboard sends no content,
+                            // which still settles the query
+                            let _ = senders.as_ref().map(|s| {
+                                s.send_to_screen(ScreenInstruction::ClipboardContent(
+                                    terminal_id,
+                                    content,
+                                    client_id,
+                                ))
+                            });
+                        },
                     }
                     Ok(should_break)
                 };
@@ -2549,6 +2871,37 @@ fn build_table_row(
     row.join("  ")
 }
 
+// command panes that are hidden, eg. started with `zellij run --in-background`
+fn is_background_job(entry: &PaneListEntry) -> bool {
+    !entry.pane_info.is_plugin
+        && entry.pane_info.is_suppressed
+        && entry.pane_info.terminal_command.is_some()
+}
+
+fn format_background_jobs_table(entries: &[PaneListEntry]) -> Vec<String> {
+    let mut lines = vec!["PANE_ID  TAB_NAME  STATUS  COMMAND".to_owned()];
+    for entry in entries {
+        let status = if entry.pane_info.exited || entry.pane_info.is_held {
+            match entry.pane_info.exit_status {
+                Some(exit_status) => format!("exited({})", exit_status),
+                None => "exited".to_owned(),
+            }
+        } else {
+            "running".to_owned()
+        };
+        lines.push(
+            [
+                format_pane_id(&entry.pane_info),
+                entry.tab_name.clone(),
+                status,
+                extract_command(entry),
+            ]
+            .join("  "),
+        );
+    }
+    lines
+}
+
 fn format_pane_id(pane_info: &zellij_utils::data::PaneInfo) -> String {
     if pane_info.is_plugin {
         format!("plugin_{}", pane_info.id)
@@ -2718,6 +3071,24 @@ fn format_current_tab_info_plain(tab_info: &TabInfo) -> Vec<String> {
     ]
 }
 
+/// Whether `given` is the secret a session locked with `expected` needs, anything goes for a
+/// session that is not locked. Compared in constant time, so that how long it takes does not
+/// tell how much of the secret was right.
+pub(crate) fn session_secret_matches(expected: Option<&str>, given: Option<&str>) -> bool {
+    match (expected, given) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(expected), Some(given)) => {
+            let (expected, given) = (expected.as_bytes(), given.as_bytes());
+            let difference = expected
+                .iter()
+                .zip(given)
+                .fold(0u8, |difference, (e, g)| difference | (e ^ g));
+            std::hint::black_box(difference) == 0 && expected.len() == given.len()
+        },
+    }
+}
+
 fn send_error_to_client(
     cli_client_id: Option<ClientId>,
     os_input: Option<&Box<dyn ServerOsApi>>,
@@ -2819,4 +3190,55 @@ mod tests {
         // But channel should be None (as per the Clone implementation comment)
         assert!(cloned.channel.is_none());
     }
+
+    #[test]
+    fn session_secrets_are_compared_in_full() {
+        assert!(session_secret_matches(None, None));
+        assert!(session_secret_matches(None, Some("anything")));
+        assert!(!session_secret_matches(Some("secret"), None));
+        assert!(!session_secret_matches(Some("secret"), Some("secreT")));
+        assert!(!session_secret_matches(
+            Some("secret"),
+            Some("secret and more")
+        ));
+        assert!(!session_secret_matches(Some("secret"), Some("")));
+        assert!(session_secret_matches(Some("secret"), Some("secret")));
+    }
+
+    #[test]
+    fn background_jobs_are_hidden_command_panes() {
+        let entry = |id, is_suppressed, terminal_command: Option<&str>| PaneListEntry {
+            pane_info: zellij_utils::data::PaneInfo {
+                id,
+                is_suppressed,
+                terminal_command: terminal_command.map(|c| c.to_owned()),
+                ..Default::default()
+            },
+            tab_id: 0,
+            tab_position: 0,
+            tab_name: "Tab #1".to_owned(),
+            pane_command: None,
+            pane_cwd: None,
+        };
+        let mut finished_job = entry(3, true, Some("cargo build"));
+        finished_job.pane_info.is_held = true;
+        finished_job.pane_info.exit_status = Some(101);
+        let background_jobs: Vec<PaneListEntry> = vec![
+            entry(1, false, Some("htop")),
+            entry(2, true, None),
+            entry(4, true, Some("sleep 60")),
+            finished_job,
+        ]
+        .into_iter()
+        .filter(is_background_job)
+        .collect();
+        assert_eq!(
+            format_background_jobs_table(&background_jobs),
+            vec![
+                "PANE_ID  TAB_NAME  STATUS  COMMAND",
+                "terminal_4  Tab #1  running  sleep 60",
+                "terminal_3  Tab #1  exited(101)  cargo build",
+            ]
+        );
+    }
 }
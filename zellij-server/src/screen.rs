@@ -50,7 +50,7 @@ use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::config::Config;
 use zellij_utils::input::keybinds::Keybinds;
 use zellij_utils::input::mouse::MouseEvent;
-use zellij_utils::input::options::Clipboard;
+use zellij_utils::input::options::{Clipboard, IdleShutdownAction};
 use zellij_utils::pane_size::{PaneGeom, Size, SizeInPixels};
 use zellij_utils::shared::clean_string_from_control_and_linebreak;
 use zellij_utils::{
@@ -78,7 +78,7 @@ use crate::{
     panes::PaneId,
     plugins::{DumpSessionLayoutResponse, PluginId, PluginInstruction, PluginRenderAsset},
     pty::{get_default_shell, ClientTabIndexOrPaneId, PtyInstruction, VteBytes},
-    tab::{SuppressedPanes, Tab},
+    tab::{ClipboardHistory, SuppressedPanes, Tab},
     thread_bus::Bus,
     ui::loading_indication::LoadingIndication,
     ClientId, ServerInstruction,
@@ -248,6 +248,7 @@ pub enum ScreenInstruction {
     PluginBytes(Vec<PluginRenderAsset>),
     Render,
     RenderToClients,
+    ForceRender,
     NewPane(
         PaneId,
         Option<InitialTitle>,
@@ -288,7 +289,8 @@ pub enum ScreenInstruction {
     MovePaneLeft(ClientId, Option<NotificationEnd>),
     Exit,
     ClearScreen(ClientId, Option<NotificationEnd>),
-    DumpScreen(String, ClientId, bool, Option<NotificationEnd>),
+    DumpScreen(String, ClientId, bool, bool, bool, Option<NotificationEnd>), // bools are full,
+    // keep_ansi and crlf_line_endings
     DumpLayout(Option<PathBuf>, ClientId, Option<NotificationEnd>), // PathBuf is the default configured
     // shell
     SaveSession(ClientId, Option<NotificationEnd>),
@@ -414,6 +416,7 @@ pub enum ScreenInstruction {
         bool,                // is_web_client
         Option<usize>,       // tab position to focus
         Option<(u32, bool)>, // (pane_id, is_plugin) => pane_id to focus
+        Option<Style>,       // the style of the client, if it attached with a theme of its own
     ),
     RemoveClient(ClientId),
     UpdateSearch(Vec<u8>, ClientId, Option<NotificationEnd>),
@@ -533,10 +536,22 @@ pub enum ScreenInstruction {
     ),
     BreakPaneRight(ClientId, Option<NotificationEnd>),
     BreakPaneLeft(ClientId, Option<NotificationEnd>),
+    BreakPaneToWindow(Option<TerminalAction>, ClientId, Option<NotificationEnd>),
+    ListClipboardHistory(ClientId, Option<NotificationEnd>),
+    PasteFromClipboardHistory(
+        usize,          // index
+        Option<PaneId>, // the focused pane of the client if none
+        bool,           // copy only, do not paste
+        ClientId,
+        ClientId, // the client to report errors to
+        Option<NotificationEnd>,
+    ),
+    PickFromClipboardHistory(ClientId, Option<NotificationEnd>),
     UpdateSessionInfos(
         BTreeMap<String, SessionInfo>, // String is the session name
         BTreeMap<String, Duration>,    // resurrectable sessions - <name, created>
     ),
+    UpdateForegroundProcesses(BTreeMap<u32, String>), // u32 is the terminal id
     ReplacePane(
         PaneId,
         HoldForCommand,
@@ -579,6 +594,7 @@ pub enum ScreenInstruction {
         default_editor: Option<PathBuf>,
         advanced_mouse_actions: bool,
         mouse_hover_effects: bool,
+        mute_notifications: bool,
     },
     RerunCommandPane(u32, Option<NotificationEnd>), // u32 - terminal pane id
     ResizePaneWithId(ResizeStrategy, PaneId),
@@ -648,6 +664,11 @@ pub enum ScreenInstruction {
     WatcherTerminalResize(ClientId, Size),
     ClearMouseHelpText(ClientId),
     UpdateAvailableLayouts(Vec<LayoutInfo>, Vec<LayoutWithError>),
+    TerminalFocus(ClientId, bool), // bool -> whether the terminal of the client is focused
+    DownloadScreenDump(ClientId),
+    CheckIdleShutdown(Duration, IdleShutdownAction), // Duration -> idle_shutdown_hours
+    SetIdleShutdownExempt(bool, Option<NotificationEnd>),
+    ClipboardContent(u32, Option<String>, ClientId), // u32 -> id of the terminal pane that asked for it
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -657,6 +678,7 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::PluginBytes(..) => ScreenContext::PluginBytes,
             ScreenInstruction::Render => ScreenContext::Render,
             ScreenInstruction::RenderToClients => ScreenContext::RenderToClients,
+            ScreenInstruction::ForceRender => ScreenContext::ForceRender,
             ScreenInstruction::NewPane(..) => ScreenContext::NewPane,
             ScreenInstruction::OpenInPlaceEditor(..) => ScreenContext::OpenInPlaceEditor,
             ScreenInstruction::TogglePaneEmbedOrFloating(..) => {
@@ -828,7 +850,18 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::BreakPane(..) => ScreenContext::BreakPane,
             ScreenInstruction::BreakPaneRight(..) => ScreenContext::BreakPaneRight,
             ScreenInstruction::BreakPaneLeft(..) => ScreenContext::BreakPaneLeft,
+            ScreenInstruction::BreakPaneToWindow(..) => ScreenContext::BreakPaneToWindow,
+            ScreenInstruction::ListClipboardHistory(..) => ScreenContext::ListClipboardHistory,
+            ScreenInstruction::PasteFromClipboardHistory(..) => {
+                ScreenContext::PasteFromClipboardHistory
+            },
+            ScreenInstruction::PickFromClipboardHistory(..) => {
+                ScreenContext::PickFromClipboardHistory
+            },
             ScreenInstruction::UpdateSessionInfos(..) => ScreenContext::UpdateSessionInfos,
+            ScreenInstruction::UpdateForegroundProcesses(..) => {
+                ScreenContext::UpdateForegroundProcesses
+            },
             ScreenInstruction::ReplacePane(..) => ScreenContext::ReplacePane,
             ScreenInstruction::NewInPlacePluginPane(..) => ScreenContext::NewInPlacePluginPane,
             ScreenInstruction::SerializeLayoutForResurrection => {
@@ -907,6 +940,11 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::WatcherTerminalResize(..) => ScreenContext::WatcherTerminalResize,
             ScreenInstruction::ClearMouseHelpText(..) => ScreenContext::ClearMouseHelpText,
             ScreenInstruction::UpdateAvailableLayouts(..) => ScreenContext::UpdateAvailableLayouts,
+            ScreenInstruction::TerminalFocus(..) => ScreenContext::TerminalFocus,
+            ScreenInstruction::DownloadScreenDump(..) => ScreenContext::DownloadScreenDump,
+            ScreenInstruction::CheckIdleShutdown(..) => ScreenContext::CheckIdleShutdown,
+            ScreenInstruction::SetIdleShutdownExempt(..) => ScreenContext::SetIdleShutdownExempt,
+            ScreenInstruction::ClipboardContent(..) => ScreenContext::ClipboardContent,
         }
     }
 }
@@ -1054,6 +1092,7 @@ pub(crate) struct Screen {
     mode_info: BTreeMap<ClientId, ModeInfo>,
     default_mode_info: ModeInfo, // TODO: restructure ModeInfo to prevent this duplication
     style: Style,
+    client_styles: HashMap<ClientId, Style>, // clients that attached with a theme of their own
     draw_pane_frames: bool,
     auto_layout: bool,
     session_serialization: bool,
@@ -1081,7 +1120,9 @@ pub(crate) struct Screen {
     current_pane_group: Rc<RefCell<PaneGroups>>,
     advanced_mouse_actions: bool,
     mouse_hover_effects: bool,
+    mute_notifications: bool,
     currently_marking_pane_group: Rc<RefCell<HashMap<ClientId, bool>>>,
+    clipboard_history: Rc<RefCell<ClipboardHistory>>,
     // the below are the configured values - the ones that will be set if and when the web server
     // is brought online
     web_server_ip: IpAddr,
@@ -1091,6 +1132,8 @@ pub(crate) struct Screen {
     followed_client_id: Option<ClientId>,
     cached_layouts: Vec<LayoutInfo>,
     cached_layout_errors: Vec<LayoutWithError>,
+    last_activity: Instant, // the last time a pane had output or a client was attached
+    idle_shutdown_exempt: bool,
 }
 
 impl Screen {
@@ -1122,6 +1165,7 @@ impl Screen {
         web_sharing: WebSharing,
         advanced_mouse_actions: bool,
         mouse_hover_effects: bool,
+        mute_notifications: bool,
         web_server_ip: IpAddr,
         web_server_port: u16,
     ) -> Self {
@@ -1140,6 +1184,7 @@ impl Screen {
             stacked_resize: Rc::new(RefCell::new(stacked_resize)),
             sixel_image_store: Rc::new(RefCell::new(SixelImageStore::default())),
             style: client_attributes.style,
+            client_styles: HashMap::new(),
             connected_clients: Rc::new(RefCell::new(HashMap::new())),
             active_tab_ids: BTreeMap::new(),
             global_last_active_tab_id: 0,
@@ -1174,8 +1219,10 @@ impl Screen {
             web_sharing,
             current_pane_group: Rc::new(RefCell::new(current_pane_group)),
             currently_marking_pane_group: Rc::new(RefCell::new(HashMap::new())),
+            clipboard_history: Rc::new(RefCell::new(ClipboardHistory::default())),
             advanced_mouse_actions,
             mouse_hover_effects,
+            mute_notifications,
             web_server_ip,
             web_server_port,
             render_blocker: RenderBlocker::new(100),
@@ -1183,6 +1230,8 @@ impl Screen {
             followed_client_id: None,
             cached_layouts: vec![],
             cached_layout_errors: vec![],
+            last_activity: Instant::now(),
+            idle_shutdown_exempt: false,
         }
     }
 
@@ -1957,10 +2006,14 @@ impl Screen {
             self.mouse_hover_effects,
             self.web_server_ip,
             self.web_server_port,
+            self.clipboard_history.clone(),
         );
         for (client_id, mode_info) in &self.mode_info {
             tab.change_mode_info(mode_info.clone(), *client_id);
         }
+        for (client_id, client_style) in &self.client_styles {
+            tab.set_client_style(*client_id, Some(*client_style));
+        }
         self.tabs.insert(tab_id, tab);
         Ok(())
     }
@@ -2147,10 +2200,12 @@ impl Screen {
 
         for (_, tab) in self.tabs.iter_mut() {
             tab.remove_client(client_id);
+            tab.set_client_style(client_id, None);
             if tab.has_no_connected_clients() {
                 tab.visible(false).with_context(err_context)?;
             }
         }
+        self.client_styles.remove(&client_id);
         if self.active_tab_ids.contains_key(&client_id) {
             self.global_last_active_tab_id = *self.active_tab_ids.get(&client_id).unwrap();
             self.active_tab_ids.remove(&client_id);
@@ -2163,6 +2218,19 @@ impl Screen {
             .with_context(err_context)
     }
 
+    /// Render `client_id` with a `style` of its own rather than the one of the session, for a
+    /// client that attached with its own theme.
+    pub fn set_client_style(&mut self, client_id: ClientId, style: Style) {
+        self.client_styles.insert(client_id, style);
+        self.mode_info
+            .entry(client_id)
+            .or_insert_with(|| self.default_mode_info.clone())
+            .style = style;
+        for tab in self.tabs.values_mut() {
+            tab.set_client_style(client_id, Some(style));
+        }
+    }
+
     pub fn add_watcher_client(&mut self, client_id: ClientId) -> Result<()> {
         // Initialize with a default size - will be updated when we receive the actual size
         let default_size = Size { rows: 24, cols: 80 }; // Reasonable default
@@ -2357,6 +2425,46 @@ impl Screen {
         }
     }
 
+    fn notify_clients_of_finished_command(
+        &self,
+        pane_id: PaneId,
+        exit_status: Option<i32>,
+        run_command: &RunCommand,
+    ) {
+        if self.mute_notifications {
+            return;
+        }
+        let body = match exit_status {
+            Some(exit_status) => format!("{} exited with code {}", run_command, exit_status),
+            None => format!("{} exited", run_command),
+        };
+        // hidden panes are background jobs, their id is needed to show them
+        let is_background_job = self.tabs.values().any(|tab| {
+            tab.get_suppressed_panes()
+                .any(|(_, (_, pane))| pane.pid() == pane_id)
+        });
+        let (title, body) = if is_background_job {
+            ("Background job finished", format!("{} ({})", body, pane_id))
+        } else {
+            ("Command finished", body)
+        };
+        if let Some(os_input) = &self.bus.os_input {
+            for (client_id, is_web_client) in self.connected_clients.borrow().iter() {
+                // web clients have no desktop to notify, and clients focused on the pane
+                // already see it finish
+                if !is_web_client && self.get_active_pane_id(client_id) != Some(pane_id) {
+                    let _ = os_input.send_to_client(
+                        *client_id,
+                        ServerToClientMsg::Notify {
+                            title: title.to_owned(),
+                            body: body.clone(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
     fn log_and_report_session_state(&mut self) -> Result<()> {
         let err_context = || format!("Failed to log and report session state");
 
@@ -2365,6 +2473,22 @@ impl Screen {
         let pane_manifest = self.generate_and_report_pane_state()?;
         let tab_infos = self.generate_and_report_tab_state()?;
 
+        // for `zellij list-sessions`, which counts the terminals rather than plugins such as the
+        // tab and status bars
+        let pane_count = pane_manifest
+            .panes
+            .values()
+            .flatten()
+            .filter(|pane_info| !pane_info.is_plugin)
+            .count();
+        let _ = self
+            .bus
+            .senders
+            .send_to_server(ServerInstruction::ReportTabsAndPanes(
+                tab_infos.len(),
+                pane_count,
+            ));
+
         // Lazy-load layouts on first call if cache is empty
         // After that, cache is updated by watcher via UpdateAvailableLayouts instruction
         if self.cached_layouts.is_empty() {
@@ -2419,6 +2543,21 @@ impl Screen {
             .send_to_background_jobs(BackgroundJob::ReadAllSessionInfosOnMachine)
             .with_context(err_context)?;
 
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::MonitorPaneActivity)
+            .with_context(err_context)?;
+
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::MonitorIdleSession)
+            .with_context(err_context)?;
+
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::ReportForegroundProcesses)
+            .with_context(err_context)?;
+
         // TODO: consider moving this elsewhere
         self.bus
             .senders
@@ -2709,7 +2848,9 @@ impl Screen {
             }
         }
 
-        self.style = mode_info.style;
+        if !self.client_styles.contains_key(&client_id) {
+            self.style = mode_info.style;
+        }
         self.mode_info.insert(client_id, mode_info.clone());
         for tab in self.tabs.values_mut() {
             tab.change_mode_info(mode_info.clone(), client_id);
@@ -3028,6 +3169,146 @@ impl Screen {
         }
         Ok(())
     }
+    /// Break the focused pane of `client_id` out into a tab of its own, leaving the client where
+    /// it is, and open a new terminal window attached to that pane. A pane that is alone in its
+    /// tab stays there.
+    pub fn break_pane_to_window(
+        &mut self,
+        default_shell: Option<TerminalAction>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        let err_context = || "failed to break pane out to a window".to_string();
+        let active_tab = self.get_active_tab(client_id)?;
+        let active_pane_id = active_tab
+            .get_active_pane_id(client_id)
+            .with_context(err_context)?;
+        if active_tab.get_selectable_tiled_panes_count() > 1
+            || active_tab.get_visible_selectable_floating_panes_count() > 0
+        {
+            let should_change_focus_to_new_tab = false;
+            self.break_multiple_panes_to_new_tab(
+                vec![active_pane_id],
+                default_shell,
+                should_change_focus_to_new_tab,
+                None,
+                client_id,
+            )
+            .with_context(err_context)?;
+        }
+        self.bus
+            .senders
+            .send_to_background_jobs(BackgroundJob::OpenPaneWindow(
+                self.session_name.clone(),
+                active_pane_id,
+            ))
+            .with_context(err_context)
+    }
+    pub fn list_clipboard_history(
+        &self,
+        client_id: ClientId,
+        completion_tx: Option<NotificationEnd>,
+    ) -> Result<()> {
+        let clipboard_history = self.clipboard_history.borrow().list();
+        self.bus
+            .senders
+            .send_to_server(ServerInstruction::Log(
+                clipboard_history,
+                client_id,
+                completion_tx,
+            ))
+            .context("failed to list the clipboard history")
+    }
+    /// Paste entry `index` of the clipboard history into `pane_id`, or the focused pane of
+    /// `client_id`, and copy it again, making it the most recent entry
+    pub fn paste_from_clipboard_history(
+        &mut self,
+        index: usize,
+        pane_id: Option<PaneId>,
+        copy_only: bool,
+        client_id: ClientId,
+        reporting_client_id: ClientId,
+        completion_tx: Option<NotificationEnd>,
+    ) -> Result<()> {
+        let err_context = || format!("failed to paste entry {index} of the clipboard history");
+        let content = self.clipboard_history.borrow().get(index).cloned();
+        let Some(content) = content else {
+            return self
+                .bus
+                .senders
+                .send_to_server(ServerInstruction::LogError(
+                    vec![format!(
+                        "There is no entry {} in the clipboard history",
+                        index
+                    )],
+                    reporting_client_id,
+                    completion_tx,
+                ))
+                .with_context(err_context);
+        };
+        let pane_id = match pane_id {
+            Some(pane_id) => pane_id,
+            None => self
+                .get_active_tab(client_id)?
+                .get_active_pane_id(client_id)
+                .with_context(err_context)?,
+        };
+        let tab = self
+            .tabs
+            .values_mut()
+            .find(|tab| tab.has_pane_with_pid(&pane_id))
+            .with_context(err_context)?;
+        if !copy_only {
+            tab.paste_to_pane_id(&content, pane_id)
+                .with_context(err_context)?;
+        }
+        tab.copy_text_to_clipboard(&content)
+            .with_context(err_context)?;
+        self.render(None)
+    }
+    /// Open a picker of the clipboard history in a floating pane, which pastes into the pane
+    /// focused now
+    pub fn pick_from_clipboard_history(
+        &mut self,
+        client_id: ClientId,
+        completion_tx: Option<NotificationEnd>,
+    ) -> Result<()> {
+        let err_context = || "failed to open the clipboard history".to_string();
+        let pane_id = self
+            .get_active_tab(client_id)?
+            .get_active_pane_id(client_id)
+            .with_context(err_context)?;
+        let picker = RunCommand {
+            command: std::env::current_exe().with_context(err_context)?,
+            args: vec![
+                "action".to_owned(),
+                "pick-from-clipboard-history".to_owned(),
+                "--pane-id".to_owned(),
+                pane_id.to_string(),
+            ],
+            ..Default::default()
+        };
+        self.bus
+            .senders
+            .send_to_pty(PtyInstruction::SpawnTerminal(
+                Some(TerminalAction::RunCommand(picker)),
+                Some("Clipboard history".to_owned()),
+                NewPanePlacement::Floating(None),
+                false,
+                ClientTabIndexOrPaneId::ClientId(client_id),
+                completion_tx,
+                false,
+            ))
+            .with_context(err_context)
+    }
+    /// Sets the clipboard of an attaching client to the most recent copy of the session
+    fn sync_clipboard_to_client(&self, client_id: ClientId) {
+        let Some(content) = self.clipboard_history.borrow().latest().cloned() else {
+            return;
+        };
+        if let Some(os_input) = &self.bus.os_input {
+            let _ = os_input.send_to_client(client_id, ServerToClientMsg::SetClipboard { content });
+        }
+    }
     pub fn break_multiple_panes_to_new_tab(
         &mut self,
         pane_ids: Vec<PaneId>,
@@ -3388,12 +3669,18 @@ impl Screen {
         default_editor: Option<PathBuf>,
         advanced_mouse_actions: bool,
         mouse_hover_effects: bool,
+        mute_notifications: bool,
         client_id: ClientId,
     ) -> Result<()> {
         let should_support_arrow_fonts = !simplified_ui;
+        // a client that attached with a theme of its own keeps it, and does not impose it on the
+        // rest of the session either
+        let client_has_own_theme = self.client_styles.contains_key(&client_id);
 
         // global configuration
-        self.default_mode_info.update_theme(theme);
+        if !client_has_own_theme {
+            self.default_mode_info.update_theme(theme);
+        }
         self.default_mode_info
             .update_rounded_corners(rounded_corners);
         self.default_shell = default_shell.clone().unwrap_or_else(|| get_default_shell());
@@ -3404,6 +3691,7 @@ impl Screen {
         self.draw_pane_frames = pane_frames;
         self.advanced_mouse_actions = advanced_mouse_actions;
         self.mouse_hover_effects = mouse_hover_effects;
+        self.mute_notifications = mute_notifications;
         self.default_mode_info
             .update_arrow_fonts(should_support_arrow_fonts);
         self.default_mode_info
@@ -3415,7 +3703,9 @@ impl Screen {
             self.copy_options.clipboard = copy_to_clipboard;
         }
         for tab in self.tabs.values_mut() {
-            tab.update_theme(theme);
+            if !client_has_own_theme {
+                tab.update_theme(theme);
+            }
             tab.update_rounded_corners(rounded_corners);
             tab.update_default_shell(default_shell.clone());
             tab.update_default_editor(self.default_editor.clone());
@@ -3442,7 +3732,9 @@ impl Screen {
                 .or_insert_with(|| self.default_mode_info.clone());
             mode_info.update_keybinds(new_keybinds);
             mode_info.update_default_mode(new_default_mode);
-            mode_info.update_theme(theme);
+            if !client_has_own_theme {
+                mode_info.update_theme(theme);
+            }
             mode_info.update_arrow_fonts(should_support_arrow_fonts);
             mode_info.update_hide_session_name(hide_session_name);
             for tab in self.tabs.values_mut() {
@@ -4116,6 +4408,7 @@ pub(crate) fn screen_thread_main(
     let web_sharing = config_options.web_sharing.unwrap_or_else(Default::default);
     let advanced_mouse_actions = config_options.advanced_mouse_actions.unwrap_or(true);
     let mouse_hover_effects = config_options.mouse_hover_effects.unwrap_or(true);
+    let mute_notifications = config_options.mute_notifications.unwrap_or(false);
 
     let thread_senders = bus.senders.clone();
     let mut screen = Screen::new(
@@ -4154,6 +4447,7 @@ pub(crate) fn screen_thread_main(
         web_sharing,
         advanced_mouse_actions,
         mouse_hover_effects,
+        mute_notifications,
         web_server_ip,
         web_server_port,
     );
@@ -4177,6 +4471,7 @@ pub(crate) fn screen_thread_main(
 
         match event {
             ScreenInstruction::PtyBytes(pid, vte_bytes) => {
+                screen.last_activity = Instant::now();
                 let all_tabs = screen.get_tabs_mut();
                 for tab in all_tabs.values_mut() {
                     if tab.has_terminal_pid(pid) {
@@ -4211,6 +4506,12 @@ pub(crate) fn screen_thread_main(
             ScreenInstruction::Render => {
                 screen.render(None)?;
             },
+            ScreenInstruction::ForceRender => {
+                for tab in screen.tabs.values_mut() {
+                    tab.set_force_render();
+                }
+                screen.render(None)?;
+            },
             ScreenInstruction::RenderToClients => {
                 // render_blocker.can_render() returning true means that either all pending plugins
                 // (only those waiting for a new tab layout to be applied!) have been rendered or
@@ -4610,16 +4911,20 @@ pub(crate) fn screen_thread_main(
                 file,
                 client_id,
                 full,
+                keep_ansi,
+                crlf_line_endings,
                 _completion_tx, // the action ends here, dropping this will release anything
                                 // waiting for it
             ) => {
                 active_tab_and_connected_client_id!(
                     screen,
                     client_id,
-                    |tab: &mut Tab, client_id: ClientId| tab.dump_active_terminal_screen(
-                        Some(file.to_string()),
+                    |tab: &mut Tab, client_id: ClientId| tab.dump_active_terminal_screen_to_file(
+                        file.to_string(),
                         client_id,
-                        full
+                        full,
+                        keep_ansi,
+                        crlf_line_endings
                     ),
                     ?
                 );
@@ -5115,6 +5420,7 @@ pub(crate) fn screen_thread_main(
             },
             ScreenInstruction::HoldPane(id, exit_status, run_command) => {
                 let is_first_run = false;
+                screen.notify_clients_of_finished_command(id, exit_status, &run_command);
                 for tab in screen.tabs.values_mut() {
                     if tab.get_all_pane_ids().contains(&id) {
                         tab.hold_pane(id, exit_status, is_first_run, run_command);
@@ -5554,8 +5860,15 @@ pub(crate) fn screen_thread_main(
                 is_web_client,
                 tab_position_to_focus,
                 pane_id_to_focus,
+                client_style,
             ) => {
                 screen.add_client(client_id, is_web_client)?;
+                if !is_web_client {
+                    screen.sync_clipboard_to_client(client_id);
+                }
+                if let Some(client_style) = client_style {
+                    screen.set_client_style(client_id, client_style);
+                }
                 let pane_id = pane_id_to_focus.map(|(pane_id, is_plugin)| {
                     if is_plugin {
                         PaneId::Plugin(pane_id)
@@ -6626,9 +6939,52 @@ pub(crate) fn screen_thread_main(
             ) => {
                 screen.break_pane_to_new_tab(Direction::Left, client_id)?;
             },
+            ScreenInstruction::BreakPaneToWindow(
+                default_shell,
+                client_id,
+                _completion_tx, // the action ends here, dropping this will release anything
+                                // waiting for it
+            ) => {
+                screen.break_pane_to_window(default_shell, client_id)?;
+            },
+            ScreenInstruction::ListClipboardHistory(client_id, completion_tx) => {
+                screen.list_clipboard_history(client_id, completion_tx)?;
+            },
+            ScreenInstruction::PasteFromClipboardHistory(
+                index,
+                pane_id,
+                copy_only,
+                client_id,
+                reporting_client_id,
+                completion_tx,
+            ) => {
+                screen.paste_from_clipboard_history(
+                    index,
+                    pane_id,
+                    copy_only,
+                    client_id,
+                    reporting_client_id,
+                    completion_tx,
+                )?;
+            },
+            ScreenInstruction::PickFromClipboardHistory(client_id, completion_tx) => {
+                screen.pick_from_clipboard_history(client_id, completion_tx)?;
+            },
             ScreenInstruction::UpdateSessionInfos(new_session_infos, resurrectable_sessions) => {
                 screen.update_session_infos(new_session_infos, resurrectable_sessions)?;
             },
+            ScreenInstruction::UpdateForegroundProcesses(foreground_processes) => {
+                let mut foreground_process_changed = false;
+                for tab in screen.get_tabs_mut().values_mut() {
+                    if tab.update_foreground_processes(&foreground_processes) {
+                        foreground_process_changed = true;
+                    }
+                }
+                if foreground_process_changed {
+                    screen.log_and_report_session_state()?;
+                    screen.render(None)?;
+                }
+            },
             ScreenInstruction::UpdateAvailableLayouts(layouts, errors) => {
                 screen.update_available_layouts(layouts, errors);
             },
@@ -6762,9 +7118,23 @@ pub(crate) fn screen_thread_main(
                     // rename socket file
                     let old_socket_file_path = ZELLIJ_SOCK_DIR.join(&old_session_name);
                     let new_socket_file_path = ZELLIJ_SOCK_DIR.join(&name);
+                    // the old name is free for a new session from now on
+                    if let Err(e) = zellij_utils::sessions::take_server_lock(&new_socket_file_path)
+                    {
+                        log::error!("Failed to lock the new name of the session: {:?}", e);
+                    }
+                    #[cfg(not(windows))]
                     if let Err(e) = std::fs::rename(old_socket_file_path, new_socket_file_path) {
                         log::error!("Failed to rename ipc socket: {:?}", e);
                     }
+                    // named pipes cannot be renamed, the new name gets an alias of them instead
+                    #[cfg(windows)]
+                    if let Err(e) = zellij_utils::ipc::move_pipe_alias(
+                        &old_socket_file_path,
+                        &new_socket_file_path,
+                    ) {
+                        log::error!("Failed to alias the session pipes to the new name: {:?}", e);
+                    }
 
                     // rename session_info folder (TODO: make this atomic, right now there is a
                     // chance background_jobs will re-create this folder before it knows the
@@ -6815,6 +7185,7 @@ pub(crate) fn screen_thread_main(
                 default_editor,
                 advanced_mouse_actions,
                 mouse_hover_effects,
+                mute_notifications,
             } => {
                 screen
                     .reconfigure(
@@ -6834,6 +7205,7 @@ pub(crate) fn screen_thread_main(
                         default_editor,
                         advanced_mouse_actions,
                         mouse_hover_effects,
+                        mute_notifications,
                         client_id,
                     )
                     .non_fatal();
@@ -7361,6 +7733,48 @@ pub(crate) fn screen_thread_main(
                     screen.render(None)?;
                 }
             },
+            ScreenInstruction::TerminalFocus(client_id, focused) => {
+                if let Ok(tab) = screen.get_active_tab(client_id) {
+                    tab.terminal_focus_changed(client_id, focused).non_fatal();
+                }
+            },
+            ScreenInstruction::DownloadScreenDump(client_id) => {
+                active_tab_and_connected_client_id!(
+                    screen,
+                    client_id,
+                    |tab: &mut Tab, client_id: ClientId| tab
+                        .send_active_terminal_screen_to_client(client_id),
+                    ?
+                );
+            },
+            ScreenInstruction::CheckIdleShutdown(idle_shutdown_after, idle_shutdown_action) => {
+                if !screen.connected_clients.borrow().is_empty() {
+                    screen.last_activity = Instant::now();
+                } else if !screen.idle_shutdown_exempt
+                    && screen.last_activity.elapsed() >= idle_shutdown_after
+                {
+                    screen
+                        .bus
+                        .senders
+                        .send_to_server(ServerInstruction::ShutDownIdleSession(
+                            idle_shutdown_action,
+                        ))
+                        .context("failed to shut down idle session")?;
+                }
+            },
+            ScreenInstruction::SetIdleShutdownExempt(exempt, _completion_tx) => {
+                screen.idle_shutdown_exempt = exempt;
+            },
+            ScreenInstruction::ClipboardContent(terminal_id, content, client_id) => {
+                let all_tabs = screen.get_tabs_mut();
+                for tab in all_tabs.values_mut() {
+                    if tab.has_terminal_pid(terminal_id) {
+                        tab.answer_clipboard_query(terminal_id, content, client_id)
+                            .context("failed to answer clipboard query")?;
+                        break;
+                    }
+                }
+            },
         }
     }
     Ok(())
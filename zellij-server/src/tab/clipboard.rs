@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use anyhow::Result;
 use zellij_utils::{data::CopyDestination, input::options::Clipboard};
 
@@ -48,3 +50,52 @@ impl ClipboardProvider {
         }
     }
 }
+
+const CLIPBOARD_HISTORY_LEN: usize = 20;
+const CLIPBOARD_HISTORY_PREVIEW_LEN: usize = 80;
+
+/// The recent copies of a session, selections and OSC 52 alike, most recent first
+#[derive(Debug, Default)]
+pub(crate) struct ClipboardHistory {
+    entries: VecDeque<String>,
+}
+
+impl ClipboardHistory {
+    pub(crate) fn push(&mut self, content: &str) {
+        if content.is_empty() {
+            return;
+        }
+        // copying an older entry again brings it to the front
+        self.entries.retain(|entry| entry != content);
+        self.entries.push_front(content.to_owned());
+        self.entries.truncate(CLIPBOARD_HISTORY_LEN);
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&String> {
+        self.entries.get(index)
+    }
+
+    pub(crate) fn latest(&self) -> Option<&String> {
+        self.entries.front()
+    }
+
+    /// One line per entry: its index and the start of it, with line breaks escaped
+    pub(crate) fn list(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let mut preview: String = entry
+                    .chars()
+                    .take(CLIPBOARD_HISTORY_PREVIEW_LEN)
+                    .collect::<String>()
+                    .escape_debug()
+                    .to_string();
+                if entry.chars().count() > CLIPBOARD_HISTORY_PREVIEW_LEN {
+                    preview.push_str("...");
+                }
+                format!("{:>2}  {}", index, preview)
+            })
+            .collect()
+    }
+}
@@ -722,6 +722,19 @@ impl<'a> LayoutApplier<'a> {
         if let Some(exclude_from_sync) = layout.exclude_from_sync {
             new_pane.set_exclude_from_sync(exclude_from_sync);
         }
+        if let Some(suppress_bell) = layout.suppress_bell {
+            new_pane.set_suppress_bell(suppress_bell);
+        }
+        if let Some(encoding) = &layout.encoding {
+            match encoding.parse() {
+                Ok(encoding) => new_pane.set_encoding(encoding),
+                Err(e) => {
+                    Err::<(), _>(e)
+                        .with_context(|| format!("failed to set encoding of pane {}", pid))
+                        .non_fatal();
+                },
+            }
+        }
         if let Some(held_command) = hold_for_command {
             new_pane.hold(None, true, held_command.clone());
         }
@@ -11,7 +11,7 @@ use copy_command::CopyCommand;
 pub use mouse_handler::{MouseEffect, MouseHandler, PaneEdge, PaneResizeState};
 use std::env::temp_dir;
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 use zellij_utils::data::PaneContents;
 use zellij_utils::data::{
@@ -23,7 +23,7 @@ use zellij_utils::input::command::RunCommand;
 use zellij_utils::input::mouse::MouseEvent;
 use zellij_utils::position::Position;
 use zellij_utils::position::{Column, Line};
-use zellij_utils::shared::clean_string_from_control_and_linebreak;
+use zellij_utils::shared::{clean_string_from_control_and_linebreak, make_tab_window_title};
 
 use crate::background_jobs::BackgroundJob;
 use crate::pane_groups::PaneGroups;
@@ -33,6 +33,7 @@ use crate::ui::{loading_indication::LoadingIndication, pane_boundaries_frame::Fr
 use layout_applier::LayoutApplier;
 use swap_layouts::SwapLayouts;
 
+pub(crate) use self::clipboard::ClipboardHistory;
 use self::clipboard::ClipboardProvider;
 use crate::route::NotificationEnd;
 use crate::{
@@ -41,7 +42,8 @@ use crate::{
     panes::floating_panes::floating_pane_grid::half_size_middle_geom,
     panes::sixel::SixelImageStore,
     panes::{FloatingPanes, TiledPanes},
-    panes::{LinkHandler, PaneId, PluginPane, TerminalPane},
+    panes::{LinkHandler, PaneEncoding, PaneId, PluginPane, TerminalPane},
+    panes::{BRACKETED_PASTE_BEGIN, BRACKETED_PASTE_END},
     plugins::PluginInstruction,
     pty::{ClientTabIndexOrPaneId, PtyInstruction, VteBytes},
     thread_bus::ThreadSenders,
@@ -49,12 +51,13 @@ use crate::{
 };
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     str,
 };
 use zellij_utils::{
+    consts::{DEFAULT_SCROLL_BUFFER_SIZE, SCROLL_BUFFER_SIZE},
     data::{
         Event, FloatingPaneCoordinates, InputMode, ModeInfo, Palette, PaletteColor, Style, Styling,
     },
@@ -66,6 +69,7 @@ use zellij_utils::{
         },
         parse_keys,
     },
+    ipc::ServerToClientMsg,
     pane_size::{Offset, PaneGeom, Size, SizeInPixels, Viewport},
 };
 
@@ -143,6 +147,10 @@ pub const MIN_TERMINAL_HEIGHT: usize = 5;
 pub const MIN_TERMINAL_WIDTH: usize = 5;
 
 const MAX_PENDING_VTE_EVENTS: usize = 7000;
+const HIDDEN_OUTPUT_TRUNCATED_MESSAGE: &str = "OUTPUT TRUNCATED";
+// bells rung more often than this are not forwarded to clients, so that a program ringing it in a
+// loop does not flood them with beeps and notifications
+const MIN_BELL_NOTIFICATION_INTERVAL: Duration = Duration::from_millis(500);
 
 type HoldForCommand = Option<RunCommand>;
 pub type SuppressedPanes = HashMap<PaneId, (bool, Box<dyn Pane>)>; // bool => is scrollback editor
@@ -173,10 +181,12 @@ pub(crate) struct Tab {
     mode_info: Rc<RefCell<HashMap<ClientId, ModeInfo>>>,
     default_mode_info: ModeInfo,
     pub style: Style,
+    client_styles: HashMap<ClientId, Style>, // clients that attached with a theme of their own
     connected_clients: Rc<RefCell<HashSet<ClientId>>>,
     draw_pane_frames: bool,
     auto_layout: bool,
     pending_vte_events: HashMap<u32, Vec<VteBytes>>,
+    hidden_output_lines: HashMap<u32, usize>, // lines received while no client was viewing the tab
     pub selecting_with_mouse_in_pane: Option<PaneId>, // this is only pub for the tests
     pane_being_resized_with_mouse: Option<PaneResizeState>,
     link_handler: Rc<RefCell<LinkHandler>>,
@@ -188,6 +198,8 @@ pub(crate) struct Tab {
     terminal_emulator_colors: Rc<RefCell<Palette>>,
     terminal_emulator_color_codes: Rc<RefCell<HashMap<usize, String>>>,
     pids_waiting_resize: HashSet<u32>, // u32 is the terminal_id
+    pids_checked_for_failover: HashSet<u32>, // u32 is the terminal_id
+    pids_checked_for_encoding: HashSet<u32>, // u32 is the terminal_id
     cursor_positions_and_shape: HashMap<ClientId, (usize, usize, String)>, // (x_position,
     // y_position,
     // cursor_shape_csi)
@@ -207,6 +219,10 @@ pub(crate) struct Tab {
     mouse_hover_pane_id: HashMap<ClientId, PaneId>,
     mouse_help_text_visible: HashMap<ClientId, bool>,
     last_mouse_activity_time: HashMap<ClientId, Instant>,
+    last_bell_notification: Option<Instant>,
+    pending_clipboard_queries: HashMap<u32, ClientId>, // terminal pane id -> the client asked to answer its OSC 52 query
+    taskbar_progress: Option<String>, // the OSC 9;4 last reported by one of our panes
+    host_windows: HashMap<ClientId, String>, // the title and progress last sent to each client
     current_pane_group: Rc<RefCell<PaneGroups>>,
     advanced_mouse_actions: bool,
     mouse_hover_effects: bool,
@@ -216,6 +232,7 @@ pub(crate) struct Tab {
     // is brought online
     web_server_ip: IpAddr,
     web_server_port: u16,
+    clipboard_history: Rc<RefCell<ClipboardHistory>>,
 }
 
 // FIXME: Use a struct that has a pane_type enum, to reduce all of the duplication
@@ -319,6 +336,9 @@ pub trait Pane {
     fn get_selected_text(&self, _client_id: ClientId) -> Option<String> {
         None
     }
+    fn link_at(&self, _position: &Position) -> Option<String> {
+        None
+    }
 
     fn right_boundary_x_coords(&self) -> usize {
         self.x() + self.cols()
@@ -556,6 +576,31 @@ pub trait Pane {
     fn progress_animation_offset(&mut self) {} // only relevant for plugins
     fn current_title(&self) -> String;
     fn custom_title(&self) -> Option<String>;
+    // only relevant for terminals, returns true if the foreground process changed
+    fn set_foreground_process(&mut self, _foreground_process: Option<String>) -> bool {
+        false
+    }
+    fn foreground_process(&self) -> Option<String> {
+        None
+    }
+    // returns true if the pane rang the bell since the last call
+    fn take_bell(&mut self) -> bool {
+        false
+    }
+    // returns true if the pane asked to read the clipboard (OSC 52) since the last call
+    fn take_clipboard_query(&mut self) -> bool {
+        false
+    }
+    // returns the OSC 9;4 progress last reported by the pane since the last call
+    fn take_taskbar_progress(&mut self) -> Option<String> {
+        None
+    }
+    fn indicate_failover(&mut self, _original_command: RunCommand) {} // only relevant for terminals
+    fn set_suppress_bell(&mut self, _suppress_bell: bool) {} // only relevant for terminals
+    fn set_encoding(&mut self, _encoding: PaneEncoding) {} // only relevant for terminals
+    fn encoding(&self) -> Option<PaneEncoding> {
+        None
+    }
     fn is_held(&self) -> bool {
         false
     }
@@ -624,6 +669,20 @@ pub fn get_next_terminal_position(
     tiled_panes_count + floating_panes_count + 1
 }
 
+/// Whether a pane runs `cmd.exe`, either as its command or as the default shell it was started
+/// with. Tools run from it often print in the OEM codepage rather than in UTF-8.
+fn runs_cmd_exe(invoked_with: &Option<Run>, default_shell: &Path) -> bool {
+    let command = match invoked_with {
+        Some(Run::Command(run_command)) => run_command.command.as_path(),
+        None => default_shell,
+        _ => return false,
+    };
+    command
+        .file_stem()
+        .map(|stem| stem.eq_ignore_ascii_case("cmd"))
+        .unwrap_or(false)
+}
+
 impl Tab {
     // FIXME: Still too many arguments for clippy to be happy...
     #[allow(clippy::too_many_arguments)]
@@ -664,6 +723,7 @@ impl Tab {
         mouse_hover_effects: bool,
         web_server_ip: IpAddr,
         web_server_port: u16,
+        clipboard_history: Rc<RefCell<ClipboardHistory>>,
     ) -> Self {
         let name = if name.is_empty() {
             format!("Tab #{}", id + 1)
@@ -739,6 +799,7 @@ impl Tab {
             draw_pane_frames,
             auto_layout,
             pending_vte_events: HashMap::new(),
+            hidden_output_lines: HashMap::new(),
             connected_clients,
             selecting_with_mouse_in_pane: None,
             pane_being_resized_with_mouse: None,
@@ -749,6 +810,8 @@ impl Tab {
             terminal_emulator_colors,
             terminal_emulator_color_codes,
             pids_waiting_resize: HashSet::new(),
+            pids_checked_for_failover: HashSet::new(),
+            pids_checked_for_encoding: HashSet::new(),
             cursor_positions_and_shape: HashMap::new(),
             is_pending: true, // will be switched to false once the layout is applied
             pending_instructions: vec![],
@@ -765,6 +828,11 @@ impl Tab {
             mouse_hover_pane_id: HashMap::new(),
             mouse_help_text_visible: HashMap::new(),
             last_mouse_activity_time: HashMap::new(),
+            last_bell_notification: None,
+            pending_clipboard_queries: HashMap::new(),
+            taskbar_progress: None,
+            host_windows: HashMap::new(),
+            client_styles: HashMap::new(),
             current_pane_group,
             currently_marking_pane_group,
             advanced_mouse_actions,
@@ -772,6 +840,7 @@ impl Tab {
             connected_clients_in_app,
             web_server_ip,
             web_server_port,
+            clipboard_history,
         }
     }
 
@@ -1167,10 +1236,43 @@ impl Tab {
                 client_id,
                 mode_info.unwrap_or_else(|| self.default_mode_info.clone()),
             );
+            self.repaint_after_hidden_output()
+                .with_context(|| format!("failed to add client {client_id}"))?;
         }
         self.set_force_render();
         Ok(())
     }
+    fn repaint_after_hidden_output(&mut self) -> Result<()> {
+        // panes that kept receiving output while the tab was hidden are redrawn from scratch, so
+        // the client does not end up with a mix of its stale frame and the current grid
+        if self.hidden_output_lines.is_empty() {
+            return Ok(());
+        }
+        self.set_should_clear_display_before_rendering();
+        let scrollback_lines = SCROLL_BUFFER_SIZE
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_SCROLL_BUFFER_SIZE);
+        let truncated_panes: Vec<PaneId> = self
+            .hidden_output_lines
+            .drain()
+            .filter(|(_pid, lines)| *lines > scrollback_lines)
+            .map(|(pid, _lines)| PaneId::Terminal(pid))
+            .filter(|pane_id| {
+                self.tiled_panes.panes_contain(pane_id)
+                    || self.floating_panes.panes_contain(pane_id)
+            })
+            .collect();
+        if !truncated_panes.is_empty() {
+            self.senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    truncated_panes,
+                    HIDDEN_OUTPUT_TRUNCATED_MESSAGE.into(),
+                ))
+                .context("failed to mark panes with truncated output")?;
+        }
+        Ok(())
+    }
 
     pub fn change_mode_info(&mut self, mode_info: ModeInfo, client_id: ClientId) {
         self.mode_info.borrow_mut().insert(client_id, mode_info);
@@ -1198,6 +1300,9 @@ impl Tab {
         self.connected_clients.borrow_mut().remove(&client_id);
         self.mouse_help_text_visible.remove(&client_id);
         self.last_mouse_activity_time.remove(&client_id);
+        self.host_windows.remove(&client_id);
+        self.pending_clipboard_queries
+            .retain(|_, asked_client_id| *asked_client_id != client_id);
         self.set_force_render();
     }
     pub fn drain_connected_clients(
@@ -1220,6 +1325,7 @@ impl Tab {
             .remove(&client_id)
             .unwrap_or_else(|| self.default_mode_info.clone());
         self.connected_clients.borrow_mut().remove(&client_id);
+        self.host_windows.remove(&client_id);
         (client_id, client_mode_info)
     }
     pub fn has_no_connected_clients(&self) -> bool {
@@ -2202,6 +2308,7 @@ impl Tab {
         };
         if let Some(replaced_pane) = replaced_pane.take() {
             let pane_id = replaced_pane.pid();
+            self.forget_closed_pane(pane_id);
             let _ = self
                 .senders
                 .send_to_pty(PtyInstruction::ClosePane(pane_id, completion_tx));
@@ -2394,6 +2501,29 @@ impl Tab {
                     .map(|(_, pane)| pane)
             })
     }
+    pub fn update_foreground_processes(
+        &mut self,
+        foreground_processes: &BTreeMap<u32, String>,
+    ) -> bool {
+        // returns true if any of this tab's panes changed its foreground process
+        let pane_ids: Vec<PaneId> = self
+            .get_static_and_floating_pane_ids()
+            .into_iter()
+            .chain(self.suppressed_panes.keys().copied())
+            .collect();
+        let mut foreground_process_changed = false;
+        for pane_id in pane_ids {
+            if let PaneId::Terminal(terminal_id) = pane_id {
+                let foreground_process = foreground_processes.get(&terminal_id).cloned();
+                if let Some(pane) = self.get_pane_with_id_mut(pane_id) {
+                    if pane.set_foreground_process(foreground_process) {
+                        foreground_process_changed = true;
+                    }
+                }
+            }
+        }
+        foreground_process_changed
+    }
     pub fn get_active_pane_mut(&mut self, client_id: ClientId) -> Option<&mut Box<dyn Pane>> {
         self.get_active_pane_id(client_id).and_then(|ap| {
             if self.floating_panes.panes_are_visible() {
@@ -2463,6 +2593,12 @@ impl Tab {
             return Ok(());
         }
         let err_context = || format!("failed to handle pty bytes from fd {pid}");
+        if self.has_no_connected_clients() {
+            // the bytes are still applied to the grid below, this is only used to repaint and
+            // mark truncated scrollback once the tab is visible again
+            let lines = bytes.iter().filter(|b| **b == b'\n').count();
+            *self.hidden_output_lines.entry(pid).or_insert(0) += lines;
+        }
         if let Some(terminal_output) = self
             .tiled_panes
             .get_pane_mut(PaneId::Terminal(pid))
@@ -2544,9 +2680,28 @@ impl Tab {
                 )
                 .with_context(err_context)?;
             }
+            if self.pids_checked_for_failover.insert(pid) {
+                if let Some(original_command) = self.os_api.take_failover_command(pid) {
+                    terminal_output.indicate_failover(original_command);
+                }
+            }
+            if self.pids_checked_for_encoding.insert(pid)
+                && terminal_output.encoding().is_none()
+                && runs_cmd_exe(terminal_output.invoked_with(), &self.default_shell)
+            {
+                terminal_output.set_encoding(PaneEncoding::Auto);
+            }
             terminal_output.handle_pty_bytes(bytes);
             let messages_to_pty = terminal_output.drain_messages_to_pty();
             let clipboard_update = terminal_output.drain_clipboard_update();
+            let clipboard_queried = terminal_output.take_clipboard_query();
+            if let Some(taskbar_progress) = terminal_output.take_taskbar_progress() {
+                self.taskbar_progress = Some(taskbar_progress);
+            }
+            if terminal_output.take_bell() {
+                let pane_name = terminal_output.current_title();
+                self.notify_clients_of_bell(pane_name);
+            }
             for message in messages_to_pty {
                 self.write_to_pane_id_without_preprocessing(message, PaneId::Terminal(pid))
                     .with_context(err_context)?;
@@ -2555,10 +2710,101 @@ impl Tab {
                 self.write_selection_to_clipboard(&string)
                     .with_context(err_context)?;
             }
+            if clipboard_queried {
+                self.query_clipboard_for_terminal(pid);
+            }
+        }
+        Ok(())
+    }
+
+    fn local_clients(&self) -> Vec<ClientId> {
+        let connected_clients_in_app = self.connected_clients_in_app.borrow();
+        let mut local_clients: Vec<ClientId> = self
+            .connected_clients
+            .borrow()
+            .iter()
+            .copied()
+            .filter(|client_id| connected_clients_in_app.get(client_id) == Some(&false))
+            .collect();
+        local_clients.sort();
+        local_clients
+    }
+
+    fn query_clipboard_for_terminal(&mut self, terminal_id: u32) {
+        // only one client is asked, so that the user does not have to answer several prompts -
+        // preferably one focused on the pane that asked
+        let local_clients = self.local_clients();
+        let client_id = local_clients
+            .iter()
+            .find(|client_id| {
+                self.get_active_pane_id(**client_id) == Some(PaneId::Terminal(terminal_id))
+            })
+            .or_else(|| local_clients.first())
+            .copied();
+        if let Some(client_id) = client_id {
+            self.pending_clipboard_queries
+                .insert(terminal_id, client_id);
+            let _ = self
+                .os_api
+                .send_to_client(client_id, ServerToClientMsg::QueryClipboard { terminal_id });
+        }
+    }
+
+    /// Answer the OSC 52 query of a terminal pane with the clipboard of the client we asked, if
+    /// it shared it - content sent by any other client or without a query is dropped
+    pub fn answer_clipboard_query(
+        &mut self,
+        terminal_id: u32,
+        content: Option<String>,
+        client_id: ClientId,
+    ) -> Result<()> {
+        if self.pending_clipboard_queries.get(&terminal_id) != Some(&client_id) {
+            log::warn!(
+                "Dropping clipboard content client {} sent to pane {} which did not ask it for it",
+                client_id,
+                terminal_id
+            );
+            return Ok(());
+        }
+        self.pending_clipboard_queries.remove(&terminal_id);
+        if let Some(content) = content {
+            let response = format!("\u{1b}]52;c;{}\u{1b}\\", base64::encode(content));
+            self.senders
+                .send_to_pty_writer(PtyWriteInstruction::Write(
+                    response.into_bytes(),
+                    terminal_id,
+                    None,
+                ))
+                .with_context(|| {
+                    format!("failed to answer the clipboard query of terminal {terminal_id}")
+                })?;
         }
         Ok(())
     }
 
+    fn notify_clients_of_bell(&mut self, pane_name: String) {
+        let now = Instant::now();
+        if self
+            .last_bell_notification
+            .is_some_and(|sent_at| now - sent_at < MIN_BELL_NOTIFICATION_INTERVAL)
+        {
+            return;
+        }
+        self.last_bell_notification = Some(now);
+        // bells are sent to every client of the session rather than those of this tab, so that
+        // they can be noticed from other tabs
+        for (client_id, is_web_client) in self.connected_clients_in_app.borrow().iter() {
+            if !is_web_client {
+                let _ = self.os_api.send_to_client(
+                    *client_id,
+                    ServerToClientMsg::Bell {
+                        pane_name: pane_name.clone(),
+                    },
+                );
+            }
+        }
+    }
+
     pub fn write_to_terminals_on_current_tab(
         &mut self,
         key_with_modifier: &Option<KeyWithModifier>,
@@ -2612,7 +2858,8 @@ impl Tab {
         // floating panes finish loading. When that happens, the floating pane is
         // never focused for the client. Auto-focus it now.
         if floating_visible && floating_active.is_none() {
-            self.floating_panes.focus_first_pane_if_client_not_focused(client_id);
+            self.floating_panes
+                .focus_first_pane_if_client_not_focused(client_id);
             floating_active = self.floating_panes.get_active_pane_id(client_id);
         }
         let pane_id = if floating_visible {
@@ -2827,6 +3074,20 @@ impl Tab {
         }
         Ok(should_update_ui)
     }
+    /// Pastes `text` into the pane as a terminal would, the bracketed paste markers are dropped
+    /// for panes that are not in bracketed paste mode
+    pub fn paste_to_pane_id(&mut self, text: &str, pane_id: PaneId) -> Result<()> {
+        let err_context = || format!("failed to paste to pane with id {pane_id:?}");
+        for bytes in [
+            BRACKETED_PASTE_BEGIN.to_vec(),
+            text.as_bytes().to_vec(),
+            BRACKETED_PASTE_END.to_vec(),
+        ] {
+            self.write_to_pane_id(&None, bytes, false, pane_id, None, None)
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
     pub fn active_terminal_is_mid_frame(&self, client_id: ClientId) -> Option<bool> {
         let active_pane_id = if self.floating_panes.panes_are_visible() {
             self.floating_panes
@@ -2998,6 +3259,7 @@ impl Tab {
                 current_pane_group.clone(),
                 client_id_override,
                 &self.mouse_help_text_visible,
+                &self.client_styles,
             )
             .with_context(err_context)?;
         if (self.floating_panes.panes_are_visible() && self.floating_panes.has_active_panes())
@@ -3010,10 +3272,12 @@ impl Tab {
                     current_pane_group,
                     client_id_override,
                     &self.mouse_help_text_visible,
+                    &self.client_styles,
                 )
                 .with_context(err_context)?;
         }
 
+        self.render_host_window(output, &connected_clients);
         self.render_cursor(output);
         if output.has_rendered_assets() {
             self.hide_cursor_and_clear_display_as_needed(output);
@@ -3022,6 +3286,26 @@ impl Tab {
         Ok(())
     }
 
+    /// Title the windows of the host terminals `session: tab` and forward them the progress our
+    /// panes report, for those clients that do not already have them. Only on Windows, where
+    /// Windows Terminal shows the progress on its taskbar button - elsewhere the focused pane
+    /// titles the window, see PaneContentsAndUi::render_terminal_title_if_needed.
+    fn render_host_window(&mut self, output: &mut Output, connected_clients: &HashSet<ClientId>) {
+        if !cfg!(windows) {
+            return;
+        }
+        let mut host_window = make_tab_window_title(&self.name);
+        if let Some(taskbar_progress) = &self.taskbar_progress {
+            host_window.push_str(taskbar_progress);
+        }
+        for client_id in connected_clients {
+            if self.host_windows.get(client_id) != Some(&host_window) {
+                output.add_post_vte_instruction_to_client(*client_id, &host_window);
+                self.host_windows.insert(*client_id, host_window.clone());
+            }
+        }
+    }
+
     fn hide_cursor_and_clear_display_as_needed(&mut self, output: &mut Output) {
         let hide_cursor = "\u{1b}[?25l";
         let connected_clients: Vec<ClientId> =
@@ -3687,12 +3971,19 @@ impl Tab {
                 closed_pane.update_exit_status(exit_status);
             }
         }
+        self.forget_closed_pane(id);
         let _ = self.senders.send_to_plugin(PluginInstruction::Update(vec![(
             None,
             None,
             Event::PaneClosed(id.into()),
         )]));
     }
+    fn forget_closed_pane(&mut self, id: PaneId) {
+        if let PaneId::Terminal(terminal_id) = id {
+            self.pids_checked_for_failover.remove(&terminal_id);
+            self.pids_checked_for_encoding.remove(&terminal_id);
+        }
+    }
     pub fn extract_pane(
         &mut self,
         id: PaneId,
@@ -3919,6 +4210,50 @@ impl Tab {
         }
         Ok(())
     }
+    /// Dump the pane focused by `client_id` to `file` for `zellij action dump-screen`, optionally
+    /// keeping its styling and ending its lines with CRLF
+    pub fn dump_active_terminal_screen_to_file(
+        &mut self,
+        file: String,
+        client_id: ClientId,
+        full: bool,
+        keep_ansi: bool,
+        crlf_line_endings: bool,
+    ) -> Result<()> {
+        let err_context = || format!("failed to dump active terminal screen to {file}");
+
+        if let Some(active_pane) = self.get_active_pane_or_floating_pane_mut(client_id) {
+            let mut dump = if keep_ansi {
+                active_pane.dump_screen_with_ansi(full, Some(client_id))
+            } else {
+                active_pane.dump_screen(full, Some(client_id))
+            };
+            if crlf_line_endings {
+                dump = dump.replace("\r\n", "\n").replace('\n', "\r\n");
+            }
+            self.os_api
+                .write_to_file(dump, Some(file.clone()))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+    /// Send the full contents of the pane focused by `client_id` to that client as a file
+    pub fn send_active_terminal_screen_to_client(&mut self, client_id: ClientId) -> Result<()> {
+        let err_context =
+            || format!("failed to send active terminal screen to client {client_id}");
+
+        let dump = self
+            .get_active_pane_or_floating_pane_mut(client_id)
+            .map(|active_pane| active_pane.dump_screen(true, Some(client_id)))
+            .unwrap_or_default();
+        self.senders
+            .send_to_server(ServerInstruction::SendFile(
+                client_id,
+                String::from("screen-dump.txt"),
+                dump.into_bytes(),
+            ))
+            .with_context(err_context)
+    }
     pub fn dump_terminal_screen(
         &mut self,
         file: Option<String>,
@@ -4359,27 +4694,43 @@ impl Tab {
     fn write_selection_to_clipboard(&self, selection: &str) -> Result<()> {
         let err_context = || format!("failed to write selection to clipboard: '{}'", selection);
 
+        self.clipboard_history.borrow_mut().push(selection);
+
         let mut output = Output::default();
         let connected_clients: HashSet<ClientId> =
             { self.connected_clients.borrow().iter().copied().collect() };
+        // the Windows console drops OSC 52, so there local clients set the clipboard themselves
+        let clients_setting_clipboard: HashSet<ClientId> =
+            if cfg!(windows) && matches!(self.clipboard_provider, ClipboardProvider::Osc52(_)) {
+                self.local_clients().into_iter().collect()
+            } else {
+                HashSet::new()
+            };
         output.add_clients(&connected_clients, self.link_handler.clone(), None);
-        let client_ids = connected_clients.iter().copied();
+        let client_ids = connected_clients
+            .difference(&clients_setting_clipboard)
+            .copied();
         let clipboard_event =
             match self
                 .clipboard_provider
                 .set_content(selection, &mut output, client_ids)
             {
-                Ok(_) => output
-                    .serialize()
+                Ok(_) => clients_setting_clipboard
+                    .iter()
+                    .try_for_each(|client_id| {
+                        self.os_api.send_to_client(
+                            *client_id,
+                            ServerToClientMsg::SetClipboard {
+                                content: selection.to_owned(),
+                            },
+                        )
+                    })
+                    .and_then(|_| output.serialize())
                     .and_then(|serialized_output| {
                         self.senders
                             .send_to_server(ServerInstruction::Render(Some(serialized_output)))
                     })
-                    .and_then(|_| {
-                        Ok(Event::CopyToClipboard(
-                            self.clipboard_provider.as_copy_destination(),
-                        ))
-                    })
+                    .map(|_| Event::CopyToClipboard(self.clipboard_provider.as_copy_destination()))
                     .with_context(err_context)?,
                 Err(err) => {
                     Err::<(), _>(err).with_context(err_context).non_fatal();
@@ -4419,6 +4770,31 @@ impl Tab {
         self.mouse_help_text_visible.insert(client_id, false);
     }
 
+    /// Let the pane focused by `client_id` know that the terminal of that client gained or lost
+    /// focus, if it asked to be told (mode 1004).
+    pub fn terminal_focus_changed(&self, client_id: ClientId, focused: bool) -> Result<()> {
+        let err_context = || format!("failed to report terminal focus of client {client_id}");
+        let Some(active_pane) = self.get_active_pane(client_id) else {
+            return Ok(());
+        };
+        let focus_event = if focused {
+            active_pane.focus_event()
+        } else {
+            active_pane.unfocus_event()
+        };
+        if let (PaneId::Terminal(terminal_id), Some(focus_event)) = (active_pane.pid(), focus_event)
+        {
+            self.senders
+                .send_to_pty_writer(PtyWriteInstruction::Write(
+                    focus_event.into_bytes(),
+                    terminal_id,
+                    None,
+                ))
+                .with_context(err_context)?;
+        }
+        Ok(())
+    }
+
     pub fn update_active_pane_name(&mut self, buf: Vec<u8>, client_id: ClientId) -> Result<()> {
         let err_context =
             || format!("failed to update name of active pane to '{buf:?}' for client {client_id}");
@@ -5144,6 +5520,15 @@ impl Tab {
             pane.update_theme(theme);
         }
     }
+    /// Render the UI for `client_id` in the palette of `style` rather than the one of the
+    /// session, or stop doing so with `None`.
+    pub fn set_client_style(&mut self, client_id: ClientId, style: Option<Style>) {
+        match style {
+            Some(style) => self.client_styles.insert(client_id, style),
+            None => self.client_styles.remove(&client_id),
+        };
+        self.set_force_render();
+    }
     pub fn update_rounded_corners(&mut self, rounded_corners: bool) {
         self.style.rounded_corners = rounded_corners;
         self.floating_panes
@@ -5462,6 +5847,7 @@ pub fn pane_info_for_pane(
     pane_info.exited = pane.exited();
     pane_info.exit_status = pane.exit_status();
     pane_info.is_held = pane.is_held();
+    pane_info.foreground_process = pane.foreground_process();
     let index_in_pane_group: BTreeMap<ClientId, usize> = current_pane_group
         .iter()
         .filter_map(|(client_id, pane_ids)| {
@@ -2,6 +2,7 @@ use std::time::Instant;
 use zellij_utils::data::{Direction, Resize, ResizeStrategy};
 use zellij_utils::errors::prelude::*;
 use zellij_utils::input::mouse::{MouseEvent, MouseEventType};
+use zellij_utils::ipc::ServerToClientMsg;
 use zellij_utils::pane_size::PaneGeom;
 use zellij_utils::position::Position;
 
@@ -144,6 +145,10 @@ enum MouseAction {
     FrameIntercepted {
         pane_id: PaneId,
     },
+    OpenLink {
+        pane_id: PaneId,
+        position: Position,
+    },
     NoAction,
 }
 
@@ -728,6 +733,9 @@ impl MouseHandler {
                 tab.set_force_render();
                 Ok(MouseEffect::state_changed())
             },
+            MouseAction::OpenLink { pane_id, position } => {
+                Self::execute_open_link(tab, pane_id, position, client_id)
+            },
             MouseAction::NoAction => Ok(MouseEffect::default()),
         }
     }
@@ -930,6 +938,25 @@ impl MouseHandler {
         Ok(mouse_effect)
     }
 
+    fn execute_open_link(
+        tab: &mut Tab,
+        pane_id: PaneId,
+        position: Position,
+        client_id: ClientId,
+    ) -> Result<MouseEffect> {
+        // the link is opened on the machine of the client that clicked it
+        let uri = tab.get_pane_with_id(pane_id).and_then(|pane| {
+            let relative_position = pane.relative_position(&position);
+            pane.link_at(&relative_position)
+        });
+        if let Some(uri) = uri {
+            tab.os_api
+                .send_to_client(client_id, ServerToClientMsg::OpenLink { uri })
+                .with_context(|| format!("failed to open link for client {client_id}"))?;
+        }
+        Ok(MouseEffect::default())
+    }
+
     fn execute_send_to_terminal(
         tab: &mut Tab,
         pane_id: PaneId,
@@ -1082,8 +1109,12 @@ impl MouseHandler {
                         position: event.position,
                     });
                 }
+                return Ok(MouseAction::NoAction);
             }
-            return Ok(MouseAction::NoAction);
+            return Ok(MouseAction::OpenLink {
+                pane_id: details.pane_id,
+                position: event.position,
+            });
         }
 
         let is_plain_left_press =
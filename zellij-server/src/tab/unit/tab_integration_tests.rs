@@ -1,4 +1,4 @@
-use super::{Output, Tab};
+use super::{ClipboardHistory, Output, Tab};
 use crate::panes::sixel::SixelImageStore;
 use crate::screen::CopyOptions;
 use crate::Arc;
@@ -91,7 +91,7 @@ impl ServerOsApi for FakeInputOutput {
         Box::new((*self).clone())
     }
     fn send_to_client(&self, _client_id: ClientId, _msg: ServerToClientMsg) -> Result<()> {
-        unimplemented!()
+        Ok(())
     }
     fn new_client(
         &mut self,
@@ -268,6 +268,7 @@ fn create_new_tab(size: Size, default_mode: ModeInfo) -> Tab {
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -353,6 +354,7 @@ fn create_new_tab_without_pane_frames(size: Size, default_mode: ModeInfo) -> Tab
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -453,6 +455,7 @@ fn create_new_tab_with_swap_layouts(
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     let (
         base_layout,
@@ -554,6 +557,7 @@ fn create_new_tab_with_os_api(
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -641,6 +645,7 @@ fn create_new_tab_with_layout(size: Size, default_mode: ModeInfo, layout: &str)
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     let pane_ids = tab_layout
         .extract_run_instructions()
@@ -742,6 +747,7 @@ fn create_new_tab_with_mock_pty_writer(
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -834,6 +840,7 @@ fn create_new_tab_with_sixel_support(
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -1301,6 +1308,51 @@ fn dump_screen() {
     );
 }
 
+#[test]
+fn dump_screen_with_ansi_and_crlf_line_endings() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut tab = create_new_tab(size, ModeInfo::default());
+    let map = Arc::new(Mutex::new(HashMap::new()));
+    tab.os_api = Box::new(FakeInputOutput {
+        file_dumps: map.clone(),
+        ..Default::default()
+    });
+    let new_pane_id = PaneId::Terminal(2);
+    tab.new_pane(
+        new_pane_id,
+        None,
+        None,
+        false,
+        true,
+        NewPanePlacement::default(),
+        Some(client_id),
+        None,
+    )
+    .unwrap();
+    tab.handle_pty_bytes(2, Vec::from("\u{1b}[31mred\u{1b}[m\n\rplain".as_bytes()))
+        .unwrap();
+    let file = "C:\\Users\\me\\dump.txt";
+    tab.dump_active_terminal_screen_to_file(file.to_string(), client_id, true, false, true)
+        .unwrap();
+    assert_eq!(
+        map.lock().unwrap().get(file).unwrap(),
+        "red\r\nplain",
+        "screen was dumped without styling and with CRLF line endings"
+    );
+    tab.dump_active_terminal_screen_to_file(file.to_string(), client_id, true, true, false)
+        .unwrap();
+    let dump = map.lock().unwrap().get(file).unwrap().clone();
+    assert!(
+        dump.contains("\u{1b}[") && dump.contains("red") && !dump.contains('\r'),
+        "screen was dumped with styling and LF line endings: {:?}",
+        dump
+    );
+}
+
 #[test]
 fn clear_screen() {
     let size = Size {
@@ -3964,6 +4016,93 @@ fn pane_in_sgr_button_event_tracking_mouse_mode() {
     );
 }
 
+#[test]
+fn terminal_focus_is_reported_to_pane_with_focus_tracking() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+
+    let mut pty_instruction_bus = MockPtyInstructionBus::new();
+    let mut tab = create_new_tab_with_mock_pty_writer(
+        size,
+        ModeInfo::default(),
+        pty_instruction_bus.pty_write_sender(),
+    );
+    pty_instruction_bus.start();
+
+    // not reported before the pane asks for it
+    tab.terminal_focus_changed(client_id, false).unwrap();
+    let focus_event_tracking = String::from("\u{1b}[?1004h");
+    tab.handle_pty_bytes(1, focus_event_tracking.as_bytes().to_vec())
+        .unwrap();
+    tab.terminal_focus_changed(client_id, false).unwrap();
+    tab.terminal_focus_changed(client_id, true).unwrap();
+
+    pty_instruction_bus.exit();
+
+    assert_eq!(
+        pty_instruction_bus.clone_output(),
+        vec!["\u{1b}[O".to_string(), "\u{1b}[I".to_string()]
+    );
+}
+
+#[test]
+fn clipboard_content_only_answers_a_pending_clipboard_query() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let other_client_id = 2;
+
+    let mut pty_instruction_bus = MockPtyInstructionBus::new();
+    let mut tab = create_new_tab_with_mock_pty_writer(
+        size,
+        ModeInfo::default(),
+        pty_instruction_bus.pty_write_sender(),
+    );
+    pty_instruction_bus.start();
+
+    tab.answer_clipboard_query(1, Some("unasked".to_owned()), client_id)
+        .unwrap();
+    let clipboard_query = String::from("\u{1b}]52;c;?\u{1b}\\");
+    tab.handle_pty_bytes(1, clipboard_query.as_bytes().to_vec())
+        .unwrap();
+    tab.answer_clipboard_query(1, Some("not asked".to_owned()), other_client_id)
+        .unwrap();
+    tab.answer_clipboard_query(1, Some("hello".to_owned()), client_id)
+        .unwrap();
+    tab.answer_clipboard_query(1, Some("already answered".to_owned()), client_id)
+        .unwrap();
+
+    pty_instruction_bus.exit();
+
+    assert_eq!(
+        pty_instruction_bus.clone_output(),
+        vec!["\u{1b}]52;c;aGVsbG8=\u{1b}\\".to_string()]
+    );
+}
+
+#[test]
+fn bells_rung_in_quick_succession_notify_clients_once() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut tab = create_new_tab(size, ModeInfo::default());
+
+    tab.handle_pty_bytes(1, b"\x07".to_vec()).unwrap();
+    let first_notification = tab.last_bell_notification;
+    assert!(first_notification.is_some(), "Bell is forwarded to clients");
+    tab.handle_pty_bytes(1, b"\x07\x07".to_vec()).unwrap();
+    assert_eq!(
+        tab.last_bell_notification, first_notification,
+        "Bells rung right after it are not forwarded"
+    );
+}
+
 #[test]
 fn pane_in_sgr_normal_event_tracking_mouse_mode() {
     let size = Size {
@@ -1,4 +1,4 @@
-use super::Tab;
+use super::{ClipboardHistory, Tab};
 use crate::pane_groups::PaneGroups;
 use crate::panes::sixel::SixelImageStore;
 use crate::screen::CopyOptions;
@@ -207,6 +207,7 @@ fn create_new_tab(size: Size, stacked_resize: bool) -> Tab {
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -291,6 +292,7 @@ fn create_new_tab_with_layout(size: Size, layout: TiledPaneLayout) -> Tab {
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     let mut new_terminal_ids = vec![];
     for i in 0..layout.extract_run_instructions().len() {
@@ -381,6 +383,7 @@ fn create_new_tab_with_cell_size(
         true, // mouse_hover_effects
         web_server_ip,
         web_server_port,
+        Rc::new(RefCell::new(ClipboardHistory::default())),
     );
     tab.apply_layout(
         TiledPaneLayout::default(),
@@ -15245,3 +15248,57 @@ fn get_pane_z_index_returns_none_for_nonexistent_pane() {
         "Different floating panes should have different z-indices"
     );
 }
+
+#[test]
+fn output_received_while_hidden_repaints_tab_when_client_returns() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let stacked_resize = false;
+    let mut tab = create_new_tab(size, stacked_resize);
+    let client_id = 1;
+
+    tab.remove_client(client_id);
+    tab.handle_pty_bytes(1, "line\n".repeat(10).into_bytes())
+        .unwrap();
+    assert_eq!(
+        tab.hidden_output_lines.get(&1),
+        Some(&10),
+        "Output of a hidden tab is counted"
+    );
+
+    tab.should_clear_display_before_rendering = false;
+    tab.add_client(client_id, None).unwrap();
+    assert!(
+        tab.should_clear_display_before_rendering,
+        "Display is cleared before repainting a tab that received output while hidden"
+    );
+    assert!(
+        tab.hidden_output_lines.is_empty(),
+        "Hidden output is reset once the tab is visible"
+    );
+}
+
+#[test]
+fn closing_a_pane_forgets_it_was_checked_for_failover_and_encoding() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let stacked_resize = false;
+    let mut tab = create_new_tab(size, stacked_resize);
+    let new_pane_id = PaneId::Terminal(2);
+    tab.horizontal_split(new_pane_id, None, 1, None, None)
+        .unwrap();
+    tab.handle_pty_bytes(2, b"output".to_vec()).unwrap();
+    assert!(tab.pids_checked_for_failover.contains(&2));
+    assert!(tab.pids_checked_for_encoding.contains(&2));
+
+    tab.close_pane(new_pane_id, false, None);
+    assert!(
+        !tab.pids_checked_for_failover.contains(&2),
+        "Closed panes are not tracked anymore"
+    );
+    assert!(!tab.pids_checked_for_encoding.contains(&2));
+}
@@ -1,4 +1,7 @@
-use crate::{os_input_output::AsyncReader, screen::ScreenInstruction, thread_bus::ThreadSenders};
+use crate::{
+    background_jobs::BackgroundJob, os_input_output::AsyncReader, panes::PaneId,
+    screen::ScreenInstruction, thread_bus::ThreadSenders,
+};
 use std::time::{Duration, Instant};
 use tokio::task;
 use zellij_utils::{
@@ -6,11 +9,52 @@ use zellij_utils::{
     logging::debug_to_file,
 };
 
+/// The period over which a pane's output is measured against its rate limit.
+const OUTPUT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Tracks how much a pane has output in the current `OUTPUT_RATE_WINDOW` and
+/// tells the reader when to pause so that a runaway pane cannot flood the
+/// screen thread and slow every other pane down.
+struct OutputRateLimiter {
+    bytes_per_window: usize,
+    window_start: Instant,
+    bytes_in_window: usize,
+}
+
+impl OutputRateLimiter {
+    fn new(bytes_per_window: usize) -> Self {
+        OutputRateLimiter {
+            bytes_per_window,
+            window_start: Instant::now(),
+            bytes_in_window: 0,
+        }
+    }
+    /// Records `n_bytes` read from the pane, returns how long to stop reading from it if it
+    /// went over its limit for the current window.
+    fn record(&mut self, n_bytes: usize) -> Option<Duration> {
+        if self.window_start.elapsed() >= OUTPUT_RATE_WINDOW {
+            self.window_start = Instant::now();
+            self.bytes_in_window = 0;
+        }
+        self.bytes_in_window += n_bytes;
+        if self.bytes_in_window > self.bytes_per_window {
+            let pause = OUTPUT_RATE_WINDOW.saturating_sub(self.window_start.elapsed());
+            // the next window starts once we resume reading
+            self.window_start = Instant::now() + pause;
+            self.bytes_in_window = 0;
+            Some(pause)
+        } else {
+            None
+        }
+    }
+}
+
 pub(crate) struct TerminalBytes {
     terminal_id: u32,
     senders: ThreadSenders,
     async_reader: Box<dyn AsyncReader>,
     debug: bool,
+    output_rate_limit: Option<u64>, // in MB/s
 }
 
 impl TerminalBytes {
@@ -19,12 +63,14 @@ impl TerminalBytes {
         async_reader: Box<dyn AsyncReader>,
         senders: ThreadSenders,
         debug: bool,
+        output_rate_limit: Option<u64>,
     ) -> Self {
         TerminalBytes {
             terminal_id,
             senders,
             debug,
             async_reader,
+            output_rate_limit,
         }
     }
     pub async fn listen(&mut self) -> Result<()> {
@@ -44,6 +90,10 @@ impl TerminalBytes {
         let mut err_ctx = get_current_ctx();
         err_ctx.add_call(ContextType::AsyncTask);
         let mut buf = [0u8; 65536];
+        let mut rate_limiter = self
+            .output_rate_limit
+            .filter(|limit| *limit > 0)
+            .map(|limit| OutputRateLimiter::new(limit as usize * 1024 * 1024));
         loop {
             match self.async_reader.read(&mut buf).await {
                 Ok(0) => break, // EOF
@@ -62,6 +112,13 @@ impl TerminalBytes {
                     ))
                     .await
                     .with_context(err_context)?;
+                    if let Some(pause) = rate_limiter
+                        .as_mut()
+                        .and_then(|rate_limiter| rate_limiter.record(n_bytes))
+                    {
+                        self.indicate_throttling();
+                        tokio::time::sleep(pause).await;
+                    }
                 },
             }
         }
@@ -84,6 +141,18 @@ impl TerminalBytes {
 
         Ok(())
     }
+    fn indicate_throttling(&self) {
+        if let Some(output_rate_limit) = self.output_rate_limit {
+            // while the pane keeps going over its limit this is sent every window,
+            // background jobs only flash the frame once per flash duration
+            let _ = self
+                .senders
+                .send_to_background_jobs(BackgroundJob::DisplayPaneError(
+                    vec![PaneId::Terminal(self.terminal_id)],
+                    format!("OUTPUT THROTTLED (over {} MB/s)", output_rate_limit),
+                ));
+        }
+    }
     async fn async_send_to_screen(
         &self,
         screen_instruction: ScreenInstruction,
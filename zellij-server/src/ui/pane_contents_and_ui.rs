@@ -11,6 +11,7 @@ pub struct PaneContentsAndUi<'a> {
     pane: &'a mut Box<dyn Pane>,
     output: &'a mut Output,
     style: Style,
+    client_styles: HashMap<ClientId, Style>,
     focused_clients: Vec<ClientId>,
     multiple_users_exist_in_session: bool,
     z_index: Option<usize>,
@@ -27,6 +28,7 @@ impl<'a> PaneContentsAndUi<'a> {
         pane: &'a mut Box<dyn Pane>,
         output: &'a mut Output,
         style: Style,
+        client_styles: &HashMap<ClientId, Style>,
         active_panes: &HashMap<ClientId, PaneId>,
         multiple_users_exist_in_session: bool,
         z_index: Option<usize>,
@@ -57,6 +59,7 @@ impl<'a> PaneContentsAndUi<'a> {
             pane,
             output,
             style,
+            client_styles: client_styles.clone(),
             focused_clients,
             multiple_users_exist_in_session,
             z_index,
@@ -158,7 +161,9 @@ impl<'a> PaneContentsAndUi<'a> {
                 })?;
             if let Some(colors) = client_id_to_colors(
                 *fake_cursor_client_id,
-                self.style.colors.multiplayer_user_colors,
+                self.style_for_client(client_id)
+                    .colors
+                    .multiplayer_user_colors,
             ) {
                 let cursor_is_visible = self
                     .pane
@@ -194,7 +199,8 @@ impl<'a> PaneContentsAndUi<'a> {
         client_mode: InputMode,
         previous_title: &mut Option<String>,
     ) {
-        if !self.focused_clients.contains(&client_id) {
+        if cfg!(windows) || !self.focused_clients.contains(&client_id) {
+            // on Windows the tab titles the window instead, see Tab::render_host_window
             return;
         }
         let vte_output = self.pane.render_terminal_title(client_mode);
@@ -239,7 +245,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 focused_client,
                 is_main_client: pane_focused_for_client_id,
                 other_focused_clients: vec![],
-                style: self.style,
+                style: self.style_for_client(client_id),
                 color: frame_color.map(|c| c.0),
                 other_cursors_exist_in_session: false,
                 pane_is_stacked_over: self.pane_is_stacked_over,
@@ -258,7 +264,7 @@ impl<'a> PaneContentsAndUi<'a> {
                 focused_client,
                 is_main_client: pane_focused_for_client_id,
                 other_focused_clients,
-                style: self.style,
+                style: self.style_for_client(client_id),
                 color: frame_color.map(|c| c.0),
                 other_cursors_exist_in_session: self.multiple_users_exist_in_session,
                 pane_is_stacked_over: self.pane_is_stacked_over,
@@ -316,6 +322,7 @@ impl<'a> PaneContentsAndUi<'a> {
     ) -> Option<(PaletteColor, usize)> {
         // (color, color_precedence) (the color_precedence is used
         // for the no-pane-frames mode)
+        let style = self.style_for_client(client_id);
         let pane_focused_for_client_id = self.focused_clients.contains(&client_id);
         let pane_is_in_group = self
             .current_pane_group
@@ -327,34 +334,40 @@ impl<'a> PaneContentsAndUi<'a> {
                 .frame_color_override()
                 .map(|override_color| (override_color, 4))
         } else if pane_is_in_group && !pane_focused_for_client_id {
-            Some((self.style.colors.frame_highlight.emphasis_0, 2))
+            Some((style.colors.frame_highlight.emphasis_0, 2))
         } else if pane_is_in_group && pane_focused_for_client_id {
-            Some((self.style.colors.frame_highlight.emphasis_1, 3))
+            Some((style.colors.frame_highlight.emphasis_1, 3))
         } else if pane_focused_for_client_id {
             match mode {
                 InputMode::Normal | InputMode::Locked => {
                     if session_is_mirrored || !self.multiple_users_exist_in_session {
-                        Some((self.style.colors.frame_selected.base, 3))
+                        Some((style.colors.frame_selected.base, 3))
                     } else {
-                        let colors = client_id_to_colors(
-                            client_id,
-                            self.style.colors.multiplayer_user_colors,
-                        );
+                        let colors =
+                            client_id_to_colors(client_id, style.colors.multiplayer_user_colors);
                         colors.map(|colors| (colors.0, 3))
                     }
                 },
-                _ => Some((self.style.colors.frame_highlight.base, 3)),
+                _ => Some((style.colors.frame_highlight.base, 3)),
             }
         } else if self
             .mouse_is_hovering_over_pane_for_clients
             .contains(&client_id)
         {
-            Some((self.style.colors.frame_highlight.base, 1))
+            Some((style.colors.frame_highlight.base, 1))
         } else {
-            self.style
-                .colors
-                .frame_unselected
-                .map(|frame| (frame.base, 0))
+            style.colors.frame_unselected.map(|frame| (frame.base, 0))
+        }
+    }
+    /// The style of the session, in the palette of `client_id` if it attached with a theme
+    /// of its own
+    fn style_for_client(&self, client_id: ClientId) -> Style {
+        match self.client_styles.get(&client_id) {
+            Some(client_style) => Style {
+                colors: client_style.colors,
+                ..self.style
+            },
+            None => self.style,
         }
     }
 }
@@ -18,53 +18,132 @@ fn get_cwd() {
 
 // --- Signal delivery tests (Unix only) ---
 
+/// Spawns a pane running `script`, returning its PID and a channel receiving
+/// its exit code once it exits.
+#[cfg(not(windows))]
+fn spawn_pane(
+    server: &ServerOsInputOutput,
+    script: &str,
+) -> (u32, std::sync::mpsc::Receiver<Option<i32>>) {
+    let (exit_sender, exit_receiver) = std::sync::mpsc::channel();
+    let exit_sender = Mutex::new(exit_sender);
+    let (_terminal_id, _reader, pid) = server
+        .spawn_terminal(
+            TerminalAction::RunCommand(RunCommand {
+                command: PathBuf::from("sh"),
+                args: vec!["-c".to_owned(), script.to_owned()],
+                ..Default::default()
+            }),
+            Box::new(move |_, exit_status, _| {
+                let _ = exit_sender.lock().unwrap().send(exit_status);
+            }),
+            None,
+        )
+        .expect("failed to spawn pane");
+    // Give the shell time to install any traps
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    (pid.expect("pane should have a PID"), exit_receiver)
+}
+
 #[cfg(not(windows))]
 #[test]
 fn kill_sends_sighup_to_process() {
-    let child = Command::new("sleep")
-        .arg("60")
-        .spawn()
-        .expect("failed to spawn sleep");
-    let pid = child.id();
-
     let server = make_server();
+    let (pid, exit_receiver) = spawn_pane(&server, "exec sleep 60");
 
     server.kill(pid).expect("kill should succeed");
 
-    // Give the signal time to be delivered
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    let exit_status = exit_receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("process should exit on SIGHUP");
+    assert_eq!(exit_status, None);
 }
 
 #[cfg(not(windows))]
 #[test]
 fn force_kill_sends_sigkill_to_process() {
-    let child = Command::new("sleep")
+    let server = make_server();
+    let (pid, exit_receiver) = spawn_pane(&server, "trap '' HUP; exec sleep 60");
+
+    server.force_kill(pid).expect("force_kill should succeed");
+
+    let exit_status = exit_receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("process should exit on SIGKILL");
+    assert_eq!(exit_status, None);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn kill_sends_sigkill_after_grace_period() {
+    let server = make_server();
+    server.set_kill_grace_period(std::time::Duration::from_millis(200));
+    let (pid, exit_receiver) = spawn_pane(&server, "trap '' HUP; exec sleep 60");
+
+    server.kill(pid).expect("kill should succeed");
+
+    let exit_status = exit_receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("process ignoring SIGHUP should be killed once the grace period ends");
+    assert_eq!(exit_status, None, "a killed process has no exit code");
+}
+
+#[cfg(not(windows))]
+#[test]
+fn wait_for_pending_kills_waits_out_the_grace_period() {
+    let server = make_server();
+    let grace_period = std::time::Duration::from_millis(200);
+    server.set_kill_grace_period(grace_period);
+    let (pid, exit_receiver) = spawn_pane(&server, "trap '' HUP; exec sleep 60");
+
+    let killed_at = std::time::Instant::now();
+    server.kill(pid).expect("kill should succeed");
+    server.wait_for_pending_kills();
+    assert!(
+        killed_at.elapsed() >= grace_period,
+        "should wait for the process to exit or be killed"
+    );
+
+    let exit_status = exit_receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("process should have been killed");
+    assert_eq!(exit_status, None);
+}
+
+#[cfg(not(windows))]
+#[test]
+fn kill_does_not_signal_processes_it_did_not_spawn() {
+    let mut child = Command::new("sleep")
         .arg("60")
         .spawn()
         .expect("failed to spawn sleep");
     let pid = child.id();
 
     let server = make_server();
-
+    server.kill(pid).expect("kill should succeed");
     server.force_kill(pid).expect("force_kill should succeed");
-
     std::thread::sleep(std::time::Duration::from_millis(100));
+
+    assert!(
+        child.try_wait().expect("failed to poll child").is_none(),
+        "a PID that is not a live pane process may belong to anyone"
+    );
+    let _ = child.kill();
+    let _ = child.wait();
 }
 
 #[cfg(not(windows))]
 #[test]
 fn send_sigint_to_process() {
-    let child = Command::new("cat")
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-        .expect("failed to spawn cat");
-    let pid = child.id();
-
     let server = make_server();
+    let (pid, exit_receiver) = spawn_pane(&server, "exec sleep 60");
 
     server.send_sigint(pid).expect("send_sigint should succeed");
 
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    let exit_status = exit_receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("process should exit on SIGINT");
+    assert_eq!(exit_status, None);
 }
 
 // --- Terminal ID management tests (cross-platform) ---
@@ -117,6 +196,77 @@ fn reserve_and_clear_all_returns_to_zero() {
     assert_eq!(backend.next_terminal_id(), Some(0));
 }
 
+// --- Mock PTY backend tests (cross-platform) ---
+
+#[tokio::test]
+async fn mock_backend_round_trips_terminal_io() {
+    let backend = MockPtyBackend::new();
+    let server = get_server_os_input_with_backend(Box::new(backend.clone()));
+
+    let (terminal_id, mut reader, pid) = server
+        .spawn_terminal(
+            TerminalAction::RunCommand(RunCommand {
+                command: PathBuf::from("vim"),
+                ..Default::default()
+            }),
+            Box::new(|_, _, _| {}),
+            None,
+        )
+        .expect("spawn_terminal should succeed");
+    assert_eq!(terminal_id, 0);
+    assert_eq!(pid, backend.pid(terminal_id));
+    assert_eq!(
+        backend.spawned_command(terminal_id).map(|cmd| cmd.command),
+        Some(PathBuf::from("vim"))
+    );
+
+    server
+        .write_to_tty_stdin(terminal_id, b":q\r")
+        .expect("write_to_tty_stdin should succeed");
+    server
+        .set_terminal_size_using_terminal_id(terminal_id, 80, 24, None, None)
+        .expect("set_terminal_size should succeed");
+    assert_eq!(backend.written_input(terminal_id), b":q\r");
+    assert_eq!(backend.terminal_size(terminal_id), Some((80, 24)));
+
+    assert!(backend.send_output(terminal_id, b"hello"));
+    let mut buf = [0u8; 3];
+    assert_eq!(reader.read(&mut buf).await.unwrap(), 3);
+    assert_eq!(&buf, b"hel");
+    assert_eq!(reader.read(&mut buf).await.unwrap(), 2);
+    assert_eq!(&buf[..2], b"lo");
+}
+
+#[tokio::test]
+async fn mock_backend_kill_ends_terminal() {
+    let backend = MockPtyBackend::new();
+    let server = get_server_os_input_with_backend(Box::new(backend.clone()));
+    let exit_status = Arc::new(Mutex::new(None));
+    let exit_status_clone = exit_status.clone();
+
+    let (terminal_id, mut reader, pid) = server
+        .spawn_terminal(
+            TerminalAction::RunCommand(RunCommand::default()),
+            Box::new(move |pane_id, status, _| {
+                *exit_status_clone.lock().unwrap() = Some((pane_id, status));
+            }),
+            None,
+        )
+        .expect("spawn_terminal should succeed");
+    let pid = pid.unwrap();
+
+    server.kill(pid).expect("kill should succeed");
+
+    assert_eq!(backend.killed_pids(), vec![pid]);
+    assert_eq!(
+        *exit_status.lock().unwrap(),
+        Some((PaneId::Terminal(terminal_id), None))
+    );
+    assert!(!backend.send_output(terminal_id, b"too late"));
+    let mut buf = [0u8; 8];
+    assert_eq!(reader.read(&mut buf).await.unwrap(), 0, "output should reach EOF");
+}
+
 // --- Windows PTY backend tests ---
 
 #[cfg(windows)]
@@ -170,6 +320,14 @@ mod windows_pty_tests {
 
         let result = backend.spawn_terminal(bad_cmd, Some(good_cmd), noop_quit_cb(), 0);
         assert!(result.is_ok(), "should fall back to failover command");
+
+        let original_command = backend.take_failover_command(0);
+        assert_eq!(
+            original_command.map(|c| c.command),
+            Some(PathBuf::from("this_command_does_not_exist_12345")),
+            "the original command should be recorded once"
+        );
+        assert!(backend.take_failover_command(0).is_none());
     }
 
     #[tokio::test]
@@ -287,7 +445,7 @@ mod windows_pty_tests {
 
         // Phase 2: ConPTY keeps the read pipe open even after the child exits.
         // Drop the master handle by clearing the terminal ID -- this closes
-        // the ConPTY, which causes the reader thread to get an error/EOF.
+        // the ConPTY, which causes the reader to get an error/EOF.
         backend.clear_terminal_id(0);
 
         // Phase 3: Drain remaining data and wait for EOF.
@@ -18,7 +18,7 @@ use zellij_utils::input::layout::{
     RunPlugin, RunPluginLocation, RunPluginOrAlias, SplitDirection, SplitSize, TiledPaneLayout,
 };
 use zellij_utils::input::mouse::MouseEvent;
-use zellij_utils::input::options::Options;
+use zellij_utils::input::options::{IdleShutdownAction, Options};
 use zellij_utils::ipc::IpcReceiverWithContext;
 use zellij_utils::pane_size::{Size, SizeInPixels};
 use zellij_utils::position::Position;
@@ -40,7 +40,7 @@ use zellij_utils::{
     channels::{self, ChannelWithContext, Receiver},
     data::{
         Direction, FloatingPaneCoordinates, InputMode, ModeInfo, NewPanePlacement, Palette,
-        PluginCapabilities,
+        PaletteColor, PluginCapabilities,
     },
     ipc::{ClientAttributes, ClientToServerMsg, ServerToClientMsg},
 };
@@ -310,6 +310,7 @@ fn create_new_screen(
         web_sharing,
         advanced_mouse_actions,
         mouse_hover_effects,
+        false,
         web_server_ip,
         web_server_port,
     );
@@ -586,6 +587,7 @@ impl MockScreen {
             current_input_modes: self.session_metadata.current_input_modes.clone(),
             web_sharing: WebSharing::Off,
             config_file_path: self.session_metadata.config_file_path.clone(),
+            session_secret: self.session_metadata.session_secret.clone(),
         }
     }
 }
@@ -646,6 +648,7 @@ impl MockScreen {
             current_input_modes: HashMap::new(),
             web_sharing: WebSharing::Off,
             config_file_path: None,
+            session_secret: None,
         };
 
         let os_input = FakeInputOutput::default();
@@ -1512,6 +1515,70 @@ fn attach_after_first_tab_closed() {
     screen.add_client(1, false).expect("TEST");
 }
 
+#[test]
+fn client_with_own_theme_does_not_change_session_style() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size, true, true);
+    new_tab(&mut screen, 1, 0);
+    let session_style = screen.style;
+
+    let mut client_style = session_style;
+    client_style.colors.frame_selected.base = PaletteColor::Rgb((255, 0, 0));
+    screen.add_client(2, false).expect("TEST");
+    screen.set_client_style(2, client_style);
+    let mut mode_info = screen.mode_info.get(&2).unwrap().clone();
+    mode_info.mode = InputMode::Locked;
+    screen.change_mode(mode_info, 2).expect("TEST");
+
+    assert_eq!(screen.style, session_style, "session style is untouched");
+    assert_eq!(
+        screen.mode_info.get(&2).unwrap().style,
+        client_style,
+        "client keeps its own style"
+    );
+    screen.remove_client(2).expect("TEST");
+    assert!(
+        screen.client_styles.is_empty(),
+        "style is dropped on detach"
+    );
+}
+
+#[test]
+fn finished_commands_only_notify_clients_not_focused_on_their_pane() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let mut screen = create_new_screen(size, true, true);
+    let fake_os_input = FakeInputOutput::default();
+    let server_to_client_messages = fake_os_input.server_to_client_messages.clone();
+    screen.bus.os_input = Some(Box::new(fake_os_input));
+    new_tab(&mut screen, 1, 0);
+    new_tab(&mut screen, 2, 1);
+    screen.connected_clients.borrow_mut().insert(1, false);
+
+    let run_command = RunCommand::default();
+    screen.notify_clients_of_finished_command(PaneId::Terminal(2), Some(0), &run_command);
+    assert!(
+        server_to_client_messages.lock().unwrap().get(&1).is_none(),
+        "client focused on the pane is not notified"
+    );
+    screen.notify_clients_of_finished_command(PaneId::Terminal(1), Some(0), &run_command);
+    let notifications = server_to_client_messages
+        .lock()
+        .unwrap()
+        .get(&1)
+        .map(|messages| messages.len());
+    assert_eq!(
+        notifications,
+        Some(1),
+        "client focused on another pane is notified"
+    );
+}
+
 #[test]
 fn open_new_floating_pane_with_custom_coordinates() {
     let size = Size {
@@ -2425,6 +2492,8 @@ pub fn send_cli_dump_screen_action() {
     let cli_action = CliAction::DumpScreen {
         path: PathBuf::from("/tmp/foo"),
         full: true,
+        ansi: false,
+        crlf: false,
     };
     let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
         0,
@@ -2456,7 +2525,7 @@ pub fn send_cli_edit_scrollback_action() {
         PtyInstruction::Exit,
         pty_receiver
     );
-    let cli_action = CliAction::EditScrollback;
+    let cli_action = CliAction::EditScrollback { ansi: false };
     let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
         0,
         "fill pane up with something".as_bytes().to_vec(),
@@ -3018,6 +3087,7 @@ pub fn send_cli_new_pane_action_with_default_parameters() {
         unblock_condition: None,
         near_current_pane: false,
         borderless: Some(false),
+        in_background: false,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -3067,6 +3137,7 @@ pub fn send_cli_new_pane_action_with_split_direction() {
         unblock_condition: None,
         near_current_pane: false,
         borderless: Some(false),
+        in_background: false,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -3116,6 +3187,7 @@ pub fn send_cli_new_pane_action_with_command_and_cwd() {
         unblock_condition: None,
         near_current_pane: false,
         borderless: Some(false),
+        in_background: false,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -3176,6 +3248,7 @@ pub fn send_cli_new_pane_action_with_floating_pane_and_coordinates() {
         unblock_condition: None,
         near_current_pane: false,
         borderless: Some(false),
+        in_background: false,
     };
     send_cli_action_to_server(&session_metadata, cli_new_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100)); // give time for actions to be
@@ -3447,7 +3520,7 @@ pub fn send_cli_close_pane_action() {
         ServerInstruction::KillSession,
         server_receiver
     );
-    let close_pane_action = CliAction::ClosePane;
+    let close_pane_action = CliAction::ClosePane { pane_id: None };
     send_cli_action_to_server(&session_metadata, close_pane_action, client_id);
     std::thread::sleep(std::time::Duration::from_millis(100));
     mock_screen.teardown(vec![server_instruction, screen_thread]);
@@ -3462,6 +3535,77 @@ pub fn send_cli_close_pane_action() {
     assert_snapshot!(format!("{}", snapshot_count));
 }
 
+#[test]
+pub fn send_cli_rename_pane_with_pane_id_action() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![TiledPaneLayout::default(), TiledPaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.drop_all_pty_messages();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(Some(initial_layout), vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_instruction = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let rename_pane_action = CliAction::RenamePane {
+        name: "not-focused".to_owned(),
+        pane_id: Some("terminal_1".to_owned()),
+    };
+    send_cli_action_to_server(&session_metadata, rename_pane_action, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_instruction, screen_thread]);
+    let snapshots = take_snapshots_and_cursor_coordinates_from_render_events(
+        received_server_instructions.lock().unwrap().iter(),
+        size,
+    );
+    let (_cursor_coordinates, last_snapshot) = snapshots.last().unwrap();
+    assert!(
+        format!("{}", last_snapshot).contains("not-focused"),
+        "pane was renamed by its id: {}",
+        last_snapshot
+    );
+}
+
+#[test]
+pub fn send_cli_switch_mode_for_all_clients_action() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    mock_screen.drop_all_pty_messages();
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_instruction = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    let switch_mode_action = CliAction::SwitchModeForAllClients {
+        input_mode: InputMode::Locked,
+    };
+    send_cli_action_to_server(&session_metadata, switch_mode_action, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_instruction, screen_thread]);
+    assert!(
+        received_server_instructions
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|instruction| matches!(
+                instruction,
+                ServerInstruction::ChangeModeForAllClients(InputMode::Locked)
+            )),
+        "all clients were switched to locked mode"
+    );
+}
+
 #[test]
 pub fn send_cli_new_tab_action_default_params() {
     let size = Size { cols: 80, rows: 10 };
@@ -3831,6 +3975,51 @@ pub fn send_cli_query_tab_names_action() {
     assert_snapshot!(format!("{:#?}", log_tab_names_instruction));
 }
 
+#[test]
+pub fn send_cli_list_clipboard_history_action() {
+    let size = Size { cols: 80, rows: 10 };
+    let client_id = 10; // fake client id should not appear in the screen's state
+    let mut mock_screen = MockScreen::new(size);
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(Some(TiledPaneLayout::default()), vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    // copy "first", "second" and "first" again through OSC 52
+    for copied in ["Zmlyc3Q=", "c2Vjb25k", "Zmlyc3Q="] {
+        let _ = mock_screen.to_screen.send(ScreenInstruction::PtyBytes(
+            0,
+            format!("\u{1b}]52;c;{}\u{1b}\\", copied)
+                .as_bytes()
+                .to_vec(),
+        ));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    send_cli_action_to_server(
+        &session_metadata,
+        CliAction::ListClipboardHistory,
+        client_id,
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+    let clipboard_history = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .find_map(|instruction| match instruction {
+            ServerInstruction::Log(lines, ..) => Some(lines.clone()),
+            _ => None,
+        });
+    assert_eq!(
+        clipboard_history,
+        Some(vec![" 0  first".to_owned(), " 1  second".to_owned()])
+    );
+}
+
 #[test]
 pub fn send_cli_launch_or_focus_plugin_action() {
     let size = Size {
@@ -4135,6 +4324,43 @@ pub fn screen_can_break_pane_to_a_new_tab() {
     assert_snapshot!(format!("{}", snapshot_count));
 }
 
+#[test]
+pub fn screen_can_break_pane_to_a_window_without_leaving_its_tab() {
+    let size = Size { cols: 80, rows: 20 };
+    let client_id = 1;
+    let mut initial_layout = TiledPaneLayout::default();
+    initial_layout.children_split_direction = SplitDirection::Vertical;
+    initial_layout.children = vec![TiledPaneLayout::default(), TiledPaneLayout::default()];
+    let mut mock_screen = MockScreen::new(size);
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(Some(initial_layout), vec![]);
+    let received_plugin_instructions = Arc::new(Mutex::new(vec![]));
+    let plugin_receiver = mock_screen.plugin_receiver.take().unwrap();
+    let plugin_thread = log_actions_in_thread!(
+        received_plugin_instructions,
+        PluginInstruction::Exit,
+        plugin_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    send_cli_action_to_server(&session_metadata, CliAction::BreakPaneToWindow, client_id);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![plugin_thread, screen_thread]);
+
+    let new_tabs_focused: Vec<bool> = received_plugin_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            PluginInstruction::NewTab(_, _, _, _, _, _, _, should_change_focus_to_new_tab, ..) => {
+                Some(*should_change_focus_to_new_tab)
+            },
+            _ => None,
+        })
+        .collect();
+    // the first one is the initial tab
+    assert_eq!(new_tabs_focused, vec![true, false]);
+}
+
 #[test]
 pub fn screen_cannot_break_last_selectable_pane_to_a_new_tab() {
     let size = Size { cols: 80, rows: 20 };
@@ -4945,3 +5171,60 @@ pub fn send_cli_close_tab_by_id_action() {
     // The action should complete successfully
     assert!(true, "CloseTabById CLI action completed without errors");
 }
+
+#[test]
+pub fn idle_session_is_shut_down_once_its_clients_leave() {
+    let size = Size {
+        cols: 121,
+        rows: 20,
+    };
+    let client_id = 1;
+    let mut mock_screen = MockScreen::new(size);
+    let session_metadata = mock_screen.clone_session_metadata();
+    let screen_thread = mock_screen.run(None, vec![]);
+    let received_server_instructions = Arc::new(Mutex::new(vec![]));
+    let server_receiver = mock_screen.server_receiver.take().unwrap();
+    let server_thread = log_actions_in_thread!(
+        received_server_instructions,
+        ServerInstruction::KillSession,
+        server_receiver
+    );
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let idle_shutdown_after = std::time::Duration::ZERO;
+    let check_idle_shutdown =
+        ScreenInstruction::CheckIdleShutdown(idle_shutdown_after, IdleShutdownAction::Kill);
+    // attached, so not idle
+    let _ = mock_screen.to_screen.send(check_idle_shutdown.clone());
+    let _ = mock_screen
+        .to_screen
+        .send(ScreenInstruction::RemoveClient(client_id));
+    // exempt
+    send_cli_action_to_server(
+        &session_metadata,
+        CliAction::SetIdleShutdownExempt { exempt: true },
+        client_id,
+    );
+    let _ = mock_screen.to_screen.send(check_idle_shutdown.clone());
+    send_cli_action_to_server(
+        &session_metadata,
+        CliAction::SetIdleShutdownExempt { exempt: false },
+        client_id,
+    );
+    let _ = mock_screen.to_screen.send(check_idle_shutdown);
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    mock_screen.teardown(vec![server_thread, screen_thread]);
+
+    let idle_shutdowns: Vec<_> = received_server_instructions
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            ServerInstruction::ShutDownIdleSession(idle_shutdown_action) => {
+                Some(*idle_shutdown_action)
+            },
+            _ => None,
+        })
+        .collect();
+    assert_eq!(idle_shutdowns, vec![IdleShutdownAction::Kill]);
+}
@@ -157,6 +157,10 @@ pub struct TiledPaneLayout {
     pub hide_floating_panes: bool,
     #[prost(string, optional, tag="13")]
     pub pane_initial_contents: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, optional, tag="14")]
+    pub suppress_bell: ::core::option::Option<bool>,
+    #[prost(string, optional, tag="15")]
+    pub encoding: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -603,6 +607,10 @@ pub struct DumpScreenPayload {
     pub file_path: ::prost::alloc::string::String,
     #[prost(bool, tag="2")]
     pub include_scrollback: bool,
+    #[prost(bool, tag="3")]
+    pub keep_ansi: bool,
+    #[prost(bool, tag="4")]
+    pub crlf_line_endings: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -9,7 +9,7 @@ pub struct EventNameList {
 pub struct Event {
     #[prost(enumeration="EventType", tag="1")]
     pub name: i32,
-    #[prost(oneof="event::Payload", tags="2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35")]
+    #[prost(oneof="event::Payload", tags="2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36")]
     pub payload: ::core::option::Option<event::Payload>,
 }
 /// Nested message and enum types in `Event`.
@@ -85,6 +85,8 @@ pub mod event {
         AvailableLayoutInfoPayload(super::AvailableLayoutInfoPayload),
         #[prost(message, tag="35")]
         PluginConfigurationChangedPayload(super::PluginConfigurationChangedPayload),
+        #[prost(message, tag="36")]
+        PaneActivityPayload(super::PaneActivityPayload),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -543,6 +545,8 @@ pub struct PaneInfo {
     pub is_selectable: bool,
     #[prost(message, repeated, tag="23")]
     pub index_in_pane_group: ::prost::alloc::vec::Vec<IndexInPaneGroup>,
+    #[prost(string, optional, tag="24")]
+    pub foreground_process: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -719,6 +723,12 @@ pub struct PluginConfigurationChangedPayload {
     #[prost(message, repeated, tag="1")]
     pub configuration: ::prost::alloc::vec::Vec<ContextItem>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PaneActivityPayload {
+    #[prost(message, optional, tag="1")]
+    pub pane_id: ::core::option::Option<PaneId>,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum EventType {
@@ -778,6 +788,8 @@ pub enum EventType {
     CwdChanged = 39,
     AvailableLayoutInfo = 40,
     PluginConfigurationChanged = 41,
+    PaneBecameIdle = 42,
+    PaneBecameActive = 43,
 }
 impl EventType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -827,6 +839,8 @@ impl EventType {
             EventType::CwdChanged => "CwdChanged",
             EventType::AvailableLayoutInfo => "AvailableLayoutInfo",
             EventType::PluginConfigurationChanged => "PluginConfigurationChanged",
+            EventType::PaneBecameIdle => "PaneBecameIdle",
+            EventType::PaneBecameActive => "PaneBecameActive",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -873,6 +887,8 @@ impl EventType {
             "CwdChanged" => Some(Self::CwdChanged),
             "AvailableLayoutInfo" => Some(Self::AvailableLayoutInfo),
             "PluginConfigurationChanged" => Some(Self::PluginConfigurationChanged),
+            "PaneBecameIdle" => Some(Self::PaneBecameIdle),
+            "PaneBecameActive" => Some(Self::PaneBecameActive),
             _ => None,
         }
     }
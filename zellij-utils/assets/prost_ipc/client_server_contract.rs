@@ -119,7 +119,7 @@ pub struct RgbColor {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Action {
-    #[prost(oneof="action::ActionType", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105")]
+    #[prost(oneof="action::ActionType", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115")]
     pub action_type: ::core::option::Option<action::ActionType>,
 }
 /// Nested message and enum types in `Action`.
@@ -337,6 +337,26 @@ pub mod action {
         ListTabs(super::ListTabsAction),
         #[prost(message, tag="105")]
         CurrentTabInfo(super::CurrentTabInfoAction),
+        #[prost(message, tag="106")]
+        SetSessionSecret(super::SetSessionSecretAction),
+        #[prost(message, tag="107")]
+        SetLogLevel(super::SetLogLevelAction),
+        #[prost(message, tag="108")]
+        NewBackgroundPane(super::NewBackgroundPaneAction),
+        #[prost(message, tag="109")]
+        ListBackgroundJobs(super::ListBackgroundJobsAction),
+        #[prost(message, tag="110")]
+        SetSessionEnv(super::SetSessionEnvAction),
+        #[prost(message, tag="111")]
+        SetIdleShutdownExempt(super::SetIdleShutdownExemptAction),
+        #[prost(message, tag="112")]
+        BreakPaneToWindow(super::BreakPaneToWindowAction),
+        #[prost(message, tag="113")]
+        ListClipboardHistory(super::ListClipboardHistoryAction),
+        #[prost(message, tag="114")]
+        PasteFromClipboardHistory(super::PasteFromClipboardHistoryAction),
+        #[prost(message, tag="115")]
+        PickFromClipboardHistory(super::PickFromClipboardHistoryAction),
     }
 }
 // Action message definitions (all 92 variants)
@@ -556,6 +576,51 @@ pub struct ListPanesAction {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListBackgroundJobsAction {
+    #[prost(bool, tag="1")]
+    pub output_json: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetSessionEnvAction {
+    #[prost(string, tag="1")]
+    pub name: ::prost::alloc::string::String,
+    /// Unset to remove the variable
+    #[prost(string, optional, tag="2")]
+    pub value: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetIdleShutdownExemptAction {
+    #[prost(bool, tag="1")]
+    pub exempt: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BreakPaneToWindowAction {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClipboardHistoryAction {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PasteFromClipboardHistoryAction {
+    #[prost(uint32, tag="1")]
+    pub index: u32,
+    #[prost(message, optional, tag="2")]
+    pub pane_id: ::core::option::Option<PaneId>,
+    #[prost(bool, tag="3")]
+    pub copy_only: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PickFromClipboardHistoryAction {
+    #[prost(message, optional, tag="1")]
+    pub pane_id: ::core::option::Option<PaneId>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListTabsAction {
     #[prost(bool, tag="1")]
     pub show_state: bool,
@@ -666,6 +731,10 @@ pub struct DumpScreenAction {
     pub file_path: ::prost::alloc::string::String,
     #[prost(bool, tag="2")]
     pub include_scrollback: bool,
+    #[prost(bool, tag="3")]
+    pub keep_ansi: bool,
+    #[prost(bool, tag="4")]
+    pub crlf_line_endings: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -737,6 +806,14 @@ pub struct NewTiledPaneAction {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NewBackgroundPaneAction {
+    #[prost(message, optional, tag="1")]
+    pub command: ::core::option::Option<RunCommandAction>,
+    #[prost(string, optional, tag="2")]
+    pub pane_name: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NewInPlacePaneAction {
     #[prost(message, optional, tag="1")]
     pub command: ::core::option::Option<RunCommandAction>,
@@ -1019,6 +1096,20 @@ pub struct RenameSessionAction {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetSessionSecretAction {
+    #[prost(string, optional, tag="1")]
+    pub secret: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="2")]
+    pub current_secret: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetLogLevelAction {
+    #[prost(string, tag="1")]
+    pub level: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CliPipeAction {
     #[prost(string, tag="1")]
     pub pipe_id: ::prost::alloc::string::String,
@@ -1136,6 +1227,10 @@ pub struct CliAssets {
     pub force_run_layout_commands: bool,
     #[prost(string, optional, tag="11")]
     pub cwd: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="12")]
+    pub client_theme: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="13")]
+    pub session_secret: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1385,9 +1480,13 @@ pub struct TiledPaneLayout {
     pub is_expanded_in_stack: bool,
     #[prost(bool, tag="12")]
     pub hide_floating_panes: bool,
-    /// NOTE: run_instructions_to_ignore is not represented here because it's a field used only inside the server itself and not part of the server/client contract
     #[prost(string, optional, tag="13")]
     pub pane_initial_contents: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, optional, tag="14")]
+    pub suppress_bell: ::core::option::Option<bool>,
+    /// NOTE: run_instructions_to_ignore is not represented here because it's a field used only inside the server itself and not part of the server/client contract
+    #[prost(string, optional, tag="15")]
+    pub encoding: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1775,6 +1874,74 @@ pub struct Options {
     pub mouse_hover_effects: ::core::option::Option<bool>,
     #[prost(uint64, optional, tag="42")]
     pub client_async_worker_tasks: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag="43")]
+    pub pane_idle_threshold: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag="44")]
+    pub pane_kill_grace_ms: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag="45")]
+    pub pane_output_rate_limit: ::core::option::Option<u64>,
+    #[prost(enumeration="BellNotification", optional, tag="46")]
+    pub bell_notification: ::core::option::Option<i32>,
+    #[prost(string, optional, tag="47")]
+    pub conpty: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="48")]
+    pub pty_system: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, optional, tag="49")]
+    pub utf8_codepage: ::core::option::Option<bool>,
+    #[prost(uint32, optional, tag="50")]
+    pub paste_warning_size: ::core::option::Option<u32>,
+    #[prost(bool, optional, tag="51")]
+    pub multi_line_paste_warning: ::core::option::Option<bool>,
+    #[prost(bool, optional, tag="52")]
+    pub allow_clipboard_read: ::core::option::Option<bool>,
+    #[prost(string, optional, tag="53")]
+    pub copy_fallback_command: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="54")]
+    pub copy_line_endings: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="55")]
+    pub ctrl_break_action: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="56")]
+    pub ctrl_c_action: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="57")]
+    pub ctrl_z_action: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="58")]
+    pub detach_chord: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint32, optional, tag="59")]
+    pub max_fps: ::core::option::Option<u32>,
+    #[prost(bool, optional, tag="60")]
+    pub legacy_console_rendering: ::core::option::Option<bool>,
+    #[prost(bool, optional, tag="61")]
+    pub mute_notifications: ::core::option::Option<bool>,
+    #[prost(bool, optional, tag="62")]
+    pub windows_event_log: ::core::option::Option<bool>,
+    #[prost(string, optional, tag="63")]
+    pub pipe_access: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag="64")]
+    pub session_max_memory_mb: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag="65")]
+    pub session_max_processes: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag="66")]
+    pub session_cpu_rate_percent: ::core::option::Option<u32>,
+    #[prost(string, optional, tag="67")]
+    pub log_dir: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag="68")]
+    pub log_level: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag="69")]
+    pub log_max_size_mb: ::core::option::Option<u64>,
+    #[prost(uint32, optional, tag="70")]
+    pub log_max_files: ::core::option::Option<u32>,
+    #[prost(uint64, optional, tag="71")]
+    pub log_rotate_hours: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag="72")]
+    pub idle_shutdown_hours: ::core::option::Option<u64>,
+    #[prost(string, optional, tag="73")]
+    pub idle_shutdown_action: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, optional, tag="74")]
+    pub detach_on_lock: ::core::option::Option<bool>,
+    #[prost(uint64, optional, tag="75")]
+    pub scrollback_spill_lines: ::core::option::Option<u64>,
+    #[prost(bool, optional, tag="76")]
+    pub detect_unbracketed_paste: ::core::option::Option<bool>,
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
@@ -2179,6 +2346,7 @@ pub enum ExitReason {
     Error = 7,
     CustomExitStatus = 8,
     KickedByHost = 9,
+    SessionLocked = 10,
 }
 impl ExitReason {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -2197,6 +2365,7 @@ impl ExitReason {
             ExitReason::Error => "EXIT_REASON_ERROR",
             ExitReason::CustomExitStatus => "EXIT_REASON_CUSTOM_EXIT_STATUS",
             ExitReason::KickedByHost => "EXIT_REASON_KICKED_BY_HOST",
+            ExitReason::SessionLocked => "EXIT_REASON_SESSION_LOCKED",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -2212,6 +2381,7 @@ impl ExitReason {
             "EXIT_REASON_ERROR" => Some(Self::Error),
             "EXIT_REASON_CUSTOM_EXIT_STATUS" => Some(Self::CustomExitStatus),
             "EXIT_REASON_KICKED_BY_HOST" => Some(Self::KickedByHost),
+            "EXIT_REASON_SESSION_LOCKED" => Some(Self::SessionLocked),
             _ => None,
         }
     }
@@ -2446,6 +2616,41 @@ impl OnForceClose {
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum BellNotification {
+    Unspecified = 0,
+    None = 1,
+    Beep = 2,
+    Flash = 3,
+    Toast = 4,
+}
+impl BellNotification {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            BellNotification::Unspecified => "BELL_NOTIFICATION_UNSPECIFIED",
+            BellNotification::None => "BELL_NOTIFICATION_NONE",
+            BellNotification::Beep => "BELL_NOTIFICATION_BEEP",
+            BellNotification::Flash => "BELL_NOTIFICATION_FLASH",
+            BellNotification::Toast => "BELL_NOTIFICATION_TOAST",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "BELL_NOTIFICATION_UNSPECIFIED" => Some(Self::Unspecified),
+            "BELL_NOTIFICATION_NONE" => Some(Self::None),
+            "BELL_NOTIFICATION_BEEP" => Some(Self::Beep),
+            "BELL_NOTIFICATION_FLASH" => Some(Self::Flash),
+            "BELL_NOTIFICATION_TOAST" => Some(Self::Toast),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum Clipboard {
     Unspecified = 0,
     System = 1,
@@ -2537,7 +2742,7 @@ impl WebSharing {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ServerToClientMsg {
-    #[prost(oneof="server_to_client_msg::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13")]
+    #[prost(oneof="server_to_client_msg::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22")]
     pub message: ::core::option::Option<server_to_client_msg::Message>,
 }
 /// Nested message and enum types in `ServerToClientMsg`.
@@ -2571,6 +2776,24 @@ pub mod server_to_client_msg {
         RenamedSession(super::RenamedSessionMsg),
         #[prost(message, tag="13")]
         ConfigFileUpdated(super::ConfigFileUpdatedMsg),
+        #[prost(message, tag="14")]
+        Bell(super::BellMsg),
+        #[prost(message, tag="15")]
+        SetClipboard(super::SetClipboardMsg),
+        #[prost(message, tag="16")]
+        QueryClipboard(super::QueryClipboardMsg),
+        #[prost(message, tag="17")]
+        Notify(super::NotifyMsg),
+        #[prost(message, tag="18")]
+        HeartbeatAck(super::HeartbeatAckMsg),
+        #[prost(message, tag="19")]
+        OpenLink(super::OpenLinkMsg),
+        #[prost(message, tag="20")]
+        FileChunk(super::FileChunkMsg),
+        #[prost(message, tag="21")]
+        ShutdownPending(super::ShutdownPendingMsg),
+        #[prost(message, tag="22")]
+        ConnStatusDetailed(super::ConnStatusDetailedReplyMsg),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2652,8 +2875,75 @@ pub struct ConfigFileUpdatedMsg {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BellMsg {
+    #[prost(string, tag="1")]
+    pub pane_name: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetClipboardMsg {
+    #[prost(string, tag="1")]
+    pub content: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryClipboardMsg {
+    #[prost(uint32, tag="1")]
+    pub terminal_id: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NotifyMsg {
+    #[prost(string, tag="1")]
+    pub title: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub body: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HeartbeatAckMsg {
+    #[prost(uint64, tag="1")]
+    pub id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OpenLinkMsg {
+    #[prost(string, tag="1")]
+    pub uri: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FileChunkMsg {
+    #[prost(string, tag="1")]
+    pub file_name: ::prost::alloc::string::String,
+    #[prost(bytes="vec", tag="2")]
+    pub bytes: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag="3")]
+    pub is_last: bool,
+}
+/// Empty message
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ShutdownPendingMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConnStatusDetailedReplyMsg {
+    #[prost(uint64, tag="1")]
+    pub created_at: u64,
+    #[prost(uint32, tag="2")]
+    pub connected_clients: u32,
+    #[prost(uint32, tag="3")]
+    pub tab_count: u32,
+    #[prost(uint32, tag="4")]
+    pub pane_count: u32,
+    #[prost(bool, tag="5")]
+    pub web_client_connected: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClientToServerMsg {
-    #[prost(oneof="client_to_server_msg::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16")]
+    #[prost(oneof="client_to_server_msg::Message", tags="1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25")]
     pub message: ::core::option::Option<client_to_server_msg::Message>,
 }
 /// Nested message and enum types in `ClientToServerMsg`.
@@ -2693,6 +2983,24 @@ pub mod client_to_server_msg {
         FailedToStartWebServer(super::FailedToStartWebServerMsg),
         #[prost(message, tag="16")]
         AttachWatcherClient(super::AttachWatcherClientMsg),
+        #[prost(message, tag="17")]
+        ClipboardContent(super::ClipboardContentMsg),
+        #[prost(message, tag="18")]
+        Heartbeat(super::HeartbeatMsg),
+        #[prost(message, tag="19")]
+        Redraw(super::RedrawMsg),
+        #[prost(message, tag="20")]
+        TerminalFocus(super::TerminalFocusMsg),
+        #[prost(message, tag="21")]
+        UploadFileChunk(super::UploadFileChunkMsg),
+        #[prost(message, tag="22")]
+        DownloadFile(super::DownloadFileMsg),
+        #[prost(message, tag="23")]
+        ConnStatusDetailed(super::ConnStatusDetailedMsg),
+        #[prost(message, tag="24")]
+        DetachOtherClients(super::DetachOtherClientsMsg),
+        #[prost(message, tag="25")]
+        Authenticate(super::AuthenticateMsg),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -2808,3 +3116,90 @@ pub struct FailedToStartWebServerMsg {
     #[prost(string, tag="1")]
     pub error: ::prost::alloc::string::String,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClipboardContentMsg {
+    #[prost(uint32, tag="1")]
+    pub terminal_id: u32,
+    #[prost(string, optional, tag="2")]
+    pub content: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HeartbeatMsg {
+    #[prost(uint64, tag="1")]
+    pub id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RedrawMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TerminalFocusMsg {
+    #[prost(bool, tag="1")]
+    pub focused: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UploadFileChunkMsg {
+    #[prost(string, tag="1")]
+    pub file_name: ::prost::alloc::string::String,
+    #[prost(bytes="vec", tag="2")]
+    pub bytes: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag="3")]
+    pub is_first: bool,
+    #[prost(bool, tag="4")]
+    pub is_last: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DownloadFileMsg {
+    #[prost(enumeration="DownloadableFile", tag="1")]
+    pub file: i32,
+}
+/// Empty message (connection status request, answered with the state of the session)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConnStatusDetailedMsg {
+}
+/// Empty message (detach every other client of the session before this one attaches)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DetachOtherClientsMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuthenticateMsg {
+    #[prost(string, tag="1")]
+    pub session_secret: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum DownloadableFile {
+    Unspecified = 0,
+    ScreenDump = 1,
+    Log = 2,
+}
+impl DownloadableFile {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            DownloadableFile::Unspecified => "DOWNLOADABLE_FILE_UNSPECIFIED",
+            DownloadableFile::ScreenDump => "DOWNLOADABLE_FILE_SCREEN_DUMP",
+            DownloadableFile::Log => "DOWNLOADABLE_FILE_LOG",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DOWNLOADABLE_FILE_UNSPECIFIED" => Some(Self::Unspecified),
+            "DOWNLOADABLE_FILE_SCREEN_DUMP" => Some(Self::ScreenDump),
+            "DOWNLOADABLE_FILE_LOG" => Some(Self::Log),
+            _ => None,
+        }
+    }
+}
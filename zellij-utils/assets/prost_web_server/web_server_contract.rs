@@ -1,7 +1,7 @@
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct InstructionForWebServer {
-    #[prost(oneof="instruction_for_web_server::Instruction", tags="1, 2")]
+    #[prost(oneof="instruction_for_web_server::Instruction", tags="1, 2, 3, 4, 5, 6, 7")]
     pub instruction: ::core::option::Option<instruction_for_web_server::Instruction>,
 }
 /// Nested message and enum types in `InstructionForWebServer`.
@@ -11,11 +11,18 @@ pub mod instruction_for_web_server {
     pub enum Instruction {
         #[prost(message, tag="1")]
         ShutdownWebServer(super::ShutdownWebServerMsg),
-        /// Future commands can be added here
-        /// RestartWebServerMsg restart_web_server = 3;
-        /// ReloadConfigMsg reload_config = 4;
         #[prost(message, tag="2")]
         QueryVersion(super::QueryVersionMsg),
+        #[prost(message, tag="3")]
+        ListSessions(super::ListSessionsMsg),
+        #[prost(message, tag="4")]
+        ListConnectedWebClients(super::ListConnectedWebClientsMsg),
+        #[prost(message, tag="5")]
+        RebindPort(super::RebindPortMsg),
+        #[prost(message, tag="6")]
+        QueryStatus(super::QueryStatusMsg),
+        #[prost(message, tag="7")]
+        RestartWebServer(super::RestartWebServerMsg),
     }
 }
 /// Empty for now, but allows for future parameters like graceful timeout
@@ -29,8 +36,30 @@ pub struct QueryVersionMsg {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSessionsMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListConnectedWebClientsMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RebindPortMsg {
+    #[prost(uint32, tag="1")]
+    pub port: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryStatusMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestartWebServerMsg {
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WebServerResponse {
-    #[prost(oneof="web_server_response::Response", tags="1")]
+    #[prost(oneof="web_server_response::Response", tags="1, 2, 3, 4, 5, 6")]
     pub response: ::core::option::Option<web_server_response::Response>,
 }
 /// Nested message and enum types in `WebServerResponse`.
@@ -40,6 +69,16 @@ pub mod web_server_response {
     pub enum Response {
         #[prost(message, tag="1")]
         Version(super::VersionResponseMsg),
+        #[prost(message, tag="2")]
+        Sessions(super::SessionsResponseMsg),
+        #[prost(message, tag="3")]
+        ConnectedWebClients(super::ConnectedWebClientsResponseMsg),
+        #[prost(message, tag="4")]
+        ReboundPort(super::ReboundPortResponseMsg),
+        #[prost(message, tag="5")]
+        Error(super::ErrorResponseMsg),
+        #[prost(message, tag="6")]
+        Status(super::StatusResponseMsg),
     }
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -52,3 +91,73 @@ pub struct VersionResponseMsg {
     #[prost(uint32, tag="3")]
     pub port: u32,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionsResponseMsg {
+    /// the sessions web clients are attached to
+    #[prost(string, repeated, tag="1")]
+    pub session_names: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ConnectedWebClientsResponseMsg {
+    #[prost(message, repeated, tag="1")]
+    pub web_clients: ::prost::alloc::vec::Vec<WebClientInfoMsg>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WebClientInfoMsg {
+    #[prost(string, tag="1")]
+    pub web_client_id: ::prost::alloc::string::String,
+    /// not set until the client is attached to a session
+    #[prost(string, optional, tag="2")]
+    pub session_name: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(bool, tag="3")]
+    pub is_read_only: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReboundPortResponseMsg {
+    #[prost(uint32, tag="1")]
+    pub port: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ErrorResponseMsg {
+    #[prost(string, tag="1")]
+    pub message: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StatusResponseMsg {
+    #[prost(string, tag="1")]
+    pub version: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub ip: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub port: u32,
+    #[prost(uint64, tag="4")]
+    pub uptime_secs: u64,
+    #[prost(uint32, tag="5")]
+    pub active_websocket_count: u32,
+    #[prost(message, repeated, tag="6")]
+    pub session_client_counts: ::prost::alloc::vec::Vec<SessionClientCountMsg>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionClientCountMsg {
+    #[prost(string, tag="1")]
+    pub session_name: ::prost::alloc::string::String,
+    #[prost(uint32, tag="2")]
+    pub client_count: u32,
+}
+/// A frame of terminal output sent to a web client over its terminal websocket
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TerminalOutputMsg {
+    /// utf-8 terminal output, raw deflate compressed if compressed is set
+    #[prost(bytes="vec", tag="1")]
+    pub content: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bool, tag="2")]
+    pub compressed: bool,
+}
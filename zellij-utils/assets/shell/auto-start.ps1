@@ -0,0 +1,29 @@
+# The following snippet is meant to be used like this in your PowerShell profile ($PROFILE):
+#
+# # Configure the session to attach to (default is "main") and auto-exit to your likings.
+# # $env:ZELLIJ_AUTO_ATTACH_SESSION = 'main'
+# # $env:ZELLIJ_AUTO_EXIT = 'true'
+# Invoke-Expression (& zellij setup --generate-auto-start powershell | Out-String)
+#
+# It attaches to the session (creating it if it does not exist yet) in interactive shells only,
+# and not in shells started inside zellij or in the integrated terminal of VS Code.
+& {
+    $arguments = [Environment]::GetCommandLineArgs() | Select-Object -Skip 1
+    $runsCommand = $arguments -match '^[-/](c|command|f|file|e|ec|encodedcommand)$'
+    $staysOpen = $arguments -match '^[-/]noe'
+    $isInteractive = [Environment]::UserInteractive -and
+        $Host.Name -eq 'ConsoleHost' -and
+        -not ($arguments -match '^[-/]noni') -and
+        ($staysOpen -or -not $runsCommand)
+    if ($isInteractive -and -not $env:ZELLIJ -and $env:TERM_PROGRAM -ne 'vscode') {
+        $session = $env:ZELLIJ_AUTO_ATTACH_SESSION
+        if (-not $session) {
+            $session = 'main'
+        }
+        zellij attach --create $session
+
+        if ($env:ZELLIJ_AUTO_EXIT -eq 'true') {
+            [Environment]::Exit($LASTEXITCODE)
+        }
+    }
+}
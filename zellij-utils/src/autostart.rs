@@ -0,0 +1,73 @@
+//! Starts zellij at logon (through the Run key of the current user) to resurrect the sessions
+//! that were running when Windows shut down, e.g. to install an update.
+use std::io;
+use windows_sys::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows_sys::Win32::System::Registry::{
+    RegCloseKey, RegCreateKeyExW, RegDeleteKeyValueW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+    KEY_SET_VALUE, REG_OPTION_NON_VOLATILE, REG_SZ,
+};
+
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+const RUN_VALUE: &str = "zellij";
+
+fn to_wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(Some(0)).collect()
+}
+
+fn check(status: u32) -> io::Result<()> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(status as i32))
+    }
+}
+
+/// Runs `zellij setup --resurrect-sessions` with this executable at logon
+pub fn install() -> io::Result<()> {
+    let zellij = std::env::current_exe()?;
+    let command = to_wide(&format!(
+        "\"{}\" setup --resurrect-sessions",
+        zellij.display()
+    ));
+    let key_name = to_wide(RUN_KEY);
+    let mut key: HKEY = std::ptr::null_mut();
+    check(unsafe {
+        RegCreateKeyExW(
+            HKEY_CURRENT_USER,
+            key_name.as_ptr(),
+            0,
+            std::ptr::null(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            std::ptr::null(),
+            &mut key,
+            std::ptr::null_mut(),
+        )
+    })?;
+    let result = check(unsafe {
+        RegSetValueExW(
+            key,
+            to_wide(RUN_VALUE).as_ptr(),
+            0,
+            REG_SZ,
+            command.as_ptr() as *const u8,
+            (command.len() * 2) as u32,
+        )
+    });
+    unsafe {
+        RegCloseKey(key);
+    }
+    result
+}
+
+/// Stops running zellij at logon, doing nothing if it was not installed
+pub fn uninstall() -> io::Result<()> {
+    let key_name = to_wide(RUN_KEY);
+    let value_name = to_wide(RUN_VALUE);
+    let status =
+        unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, key_name.as_ptr(), value_name.as_ptr()) };
+    if status == ERROR_FILE_NOT_FOUND {
+        return Ok(());
+    }
+    check(status)
+}
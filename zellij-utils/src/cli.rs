@@ -1,8 +1,12 @@
 use crate::data::{Direction, InputMode, Resize, UnblockCondition};
 use crate::setup::Setup;
 use crate::{
-    consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV},
-    input::{layout::PluginUserConfiguration, options::Options},
+    consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV, ZELLIJ_SESSION_SECRET_ENV},
+    input::{
+        actions::{SearchDirection, SearchOption},
+        layout::PluginUserConfiguration,
+        options::{LogLevel, Options},
+    },
 };
 use clap::{Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
@@ -64,6 +68,16 @@ pub struct CliArgs {
     #[clap(short, long, value_parser, overrides_with = "new_session_with_layout")]
     pub new_session_with_layout: Option<PathBuf>,
 
+    /// Name of a session template from the configuration, attaches to the session of that name
+    /// if it is running, otherwise starts it with the template's layout, cwd, env and commands
+    #[clap(
+        long,
+        value_parser,
+        overrides_with = "template",
+        conflicts_with_all = &["session", "layout", "new-session-with-layout"]
+    )]
+    pub template: Option<String>,
+
     /// Change where zellij looks for the configuration file
     #[clap(short, long, overrides_with = "config", env = ZELLIJ_CONFIG_FILE_ENV, value_parser)]
     pub config: Option<PathBuf>,
@@ -72,12 +86,28 @@ pub struct CliArgs {
     #[clap(long, overrides_with = "config_dir", env = ZELLIJ_CONFIG_DIR_ENV, value_parser)]
     pub config_dir: Option<PathBuf>,
 
+    /// Secret to lock a new session with, without which other clients cannot attach to it, or
+    /// to attach to a locked session with
+    #[clap(
+        long,
+        overrides_with = "session_secret",
+        env = ZELLIJ_SESSION_SECRET_ENV,
+        hide_env_values = true,
+        value_parser
+    )]
+    pub session_secret: Option<String>,
+
     #[clap(subcommand)]
     pub command: Option<Command>,
 
     /// Specify emitting additional debug information
     #[clap(short, long, value_parser)]
     pub debug: bool,
+
+    /// Do not install console control or signal handlers, for when the client is embedded in an
+    /// application that handles them itself
+    #[clap(long, value_parser)]
+    pub no_signal_handlers: bool,
 }
 
 impl CliArgs {
@@ -95,6 +125,18 @@ impl CliArgs {
         }
         None
     }
+    pub fn attach_theme(&self) -> Option<String> {
+        if let Some(Command::Sessions(Sessions::Attach { theme, .. })) = &self.command {
+            return theme.clone();
+        }
+        None
+    }
+    pub fn force_attach(&self) -> bool {
+        matches!(
+            &self.command,
+            Some(Command::Sessions(Sessions::Attach { force: true, .. }))
+        )
+    }
 }
 
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
@@ -200,6 +242,47 @@ pub struct WebCli {
         display_order = 15
     )]
     pub key: Option<PathBuf>,
+    /// Serve new connections of the running server on this port, the clients that are already
+    /// connected stay on the port they connected to
+    #[clap(
+        long,
+        value_parser,
+        exclusive(true),
+        value_name = "PORT",
+        display_order = 16
+    )]
+    pub rebind_port: Option<u16>,
+    /// Create a link that lets whoever has it watch this session from a browser (read-only) until
+    /// the link expires
+    #[clap(
+        long,
+        value_parser,
+        value_name = "SESSION_NAME",
+        conflicts_with_all(&["stop", "status", "create-token", "revoke-token", "revoke-all-tokens"]),
+        display_order = 17
+    )]
+    pub create_share_link: Option<String>,
+    /// Minutes until the created share link expires (defaults to 60)
+    #[clap(
+        long,
+        value_parser,
+        requires = "create-share-link",
+        value_name = "MINUTES",
+        display_order = 18
+    )]
+    pub share_link_expiry: Option<u64>,
+    /// Revoke all share links, no one can log in through them anymore
+    #[clap(long, value_parser, exclusive(true), display_order = 19)]
+    pub revoke_share_links: bool,
+    /// Restart the running web server with the current configuration (eg. to use renewed
+    /// certificates), connected web clients reconnect to their sessions on their own
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all(&["stop", "status", "create-token", "revoke-token", "revoke-all-tokens"]),
+        display_order = 20
+    )]
+    pub restart: bool,
 }
 
 impl WebCli {
@@ -211,7 +294,11 @@ impl WebCli {
                 || self.create_read_only_token
                 || self.revoke_token.is_some()
                 || self.revoke_all_tokens
-                || self.list_tokens)
+                || self.list_tokens
+                || self.rebind_port.is_some()
+                || self.create_share_link.is_some()
+                || self.revoke_share_links
+                || self.restart)
     }
 }
 
@@ -242,6 +329,9 @@ pub enum Sessions {
     /// List existing plugin aliases
     #[clap(visible_alias = "la")]
     ListAliases,
+    /// Check what zellij needs from this machine (pseudo terminals, pipes, the cache and config
+    /// dirs, the terminal) and suggest what to do about what is missing
+    Doctor,
     /// Attach to a session
     #[clap(visible_alias = "a")]
     Attach {
@@ -280,6 +370,19 @@ pub enum Sessions {
         /// Delete saved session before connecting
         #[clap(long, value_parser)]
         forget: bool,
+
+        /// Render this client with the given theme, leaving the other clients of the session as
+        /// they are
+        #[clap(long, value_parser)]
+        theme: Option<String>,
+
+        /// Focus this pane once attached, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(long, value_parser)]
+        pane_id: Option<String>,
+
+        /// Detach every other client of the session first, letting them restore their terminals
+        #[clap(long, value_parser)]
+        force: bool,
     },
 
     /// Watch a session (read-only)
@@ -447,6 +550,25 @@ pub enum Sessions {
         /// mouse)
         #[clap(short, long, value_parser)]
         borderless: Option<bool>,
+        /// Run the command in a hidden pane and get notified when it exits, see the
+        /// list-background-jobs and show-background-job actions
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            conflicts_with("floating"),
+            conflicts_with("direction"),
+            conflicts_with("in-place"),
+            conflicts_with("stacked"),
+            conflicts_with("close-on-exit"),
+            conflicts_with("start-suspended"),
+            conflicts_with("blocking"),
+            conflicts_with("block-until-exit-success"),
+            conflicts_with("block-until-exit-failure"),
+            conflicts_with("block-until-exit")
+        )]
+        in_background: bool,
     },
     /// Load a plugin
     /// Returns: Created pane ID (format: plugin_<id>)
@@ -602,6 +724,9 @@ tail -f /tmp/my-live-logfile | zellij pipe --name logs --plugin https://example.
     },
 }
 
+// Actions without a counterpart here: ClearScreen is `clear`, ToggleMouseMode is handled by the
+// client whose keys triggered it (which the CLI client is not), MouseEvent needs the coordinates
+// of a real mouse and Confirm, Deny and SkipConfirm are deprecated
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
 pub enum CliAction {
     /// Write bytes to the terminal.
@@ -662,13 +787,25 @@ pub enum CliAction {
         /// Dump the pane with full scrollback
         #[clap(short, long, value_parser, default_value("false"), takes_value(false))]
         full: bool,
+
+        /// Keep the ANSI styling of the pane in the dump rather than stripping it
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        ansi: bool,
+
+        /// End the dumped lines with CRLF rather than LF
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        crlf: bool,
     },
     /// Dump current layout to stdout
     DumpLayout,
     /// Save the current session state to disk immediately
     SaveSession,
     /// Open the pane scrollback in your default editor
-    EditScrollback,
+    EditScrollback {
+        /// Keep the ANSI styling of the pane in the scrollback opened in the editor
+        #[clap(long, value_parser, default_value("false"), takes_value(false))]
+        ansi: bool,
+    },
     /// Scroll up in the focused pane
     ScrollUp,
     /// Scroll down in focus pane.
@@ -791,6 +928,23 @@ pub enum CliAction {
         /// mouse)
         #[clap(long, value_parser)]
         borderless: Option<bool>,
+        /// Run the command in a hidden pane and get notified when it exits, see the
+        /// list-background-jobs and show-background-job actions
+        #[clap(
+            long,
+            value_parser,
+            default_value("false"),
+            takes_value(false),
+            requires("command"),
+            conflicts_with("floating"),
+            conflicts_with("direction"),
+            conflicts_with("in-place"),
+            conflicts_with("stacked"),
+            conflicts_with("close-on-exit"),
+            conflicts_with("start-suspended"),
+            conflicts_with("blocking")
+        )]
+        in_background: bool,
     },
     /// Open the specified file in a new zellij pane with your default EDITOR
     /// Returns: Created pane ID (format: terminal_<id>)
@@ -851,15 +1005,36 @@ pub enum CliAction {
     SwitchMode {
         input_mode: InputMode,
     },
+    /// Switch all connected clients to the given input mode, and tell the plugins about it
+    /// [locked|pane|tab|resize|move|search|session]
+    SwitchModeForAllClients {
+        input_mode: InputMode,
+    },
     /// Embed focused pane if floating or float focused pane if embedded
     TogglePaneEmbedOrFloating,
     /// Toggle the visibility of all floating panes in the current Tab, open one if none exist
     ToggleFloatingPanes,
-    /// Close the focused pane.
-    ClosePane,
-    /// Renames the focused pane
+    /// Close the focused pane, or the pane with the given id
+    ClosePane {
+        /// The pane_id of the pane, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Renames the focused pane, or the pane with the given id
     RenamePane {
         name: String,
+        /// The pane_id of the pane, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
+    /// Focus the pane with the given id, switching to its tab if needed
+    FocusPaneId {
+        /// The pane_id of the pane, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(value_parser)]
+        pane_id: String,
+        /// Float the pane if it is hidden (eg. suppressed) rather than showing it in place
+        #[clap(short, long, value_parser)]
+        floating: bool,
     },
     /// Remove a previously set pane name
     UndoRenamePane,
@@ -867,6 +1042,8 @@ pub enum CliAction {
     GoToNextTab,
     /// Go to the previous tab.
     GoToPreviousTab,
+    /// Go back to the tab that was focused before the current one
+    ToggleTab,
     /// Close the current tab.
     CloseTab,
     /// Go to tab with index [index]
@@ -1056,6 +1233,85 @@ pub enum CliAction {
     RenameSession {
         name: String,
     },
+    /// Lock the session with a secret other clients need to attach to it (eg. with
+    /// `--session-secret`), or unlock it if no secret is given
+    SetSessionSecret {
+        secret: Option<String>,
+        /// The secret the session is locked with now, needed to change or remove it
+        #[clap(long, value_parser)]
+        current_secret: Option<String>,
+    },
+    /// Change the most verbose messages the session logs (off, error, warn, info, debug or
+    /// trace), until it ends or this is used again
+    SetLogLevel {
+        #[clap(value_parser)]
+        level: LogLevel,
+    },
+    /// Set an environment variable of the panes opened in this session from now on, or remove it
+    /// from their environment if no value is given
+    SetSessionEnv {
+        /// The name of the variable, eg. HTTP_PROXY
+        #[clap(value_parser)]
+        name: String,
+        #[clap(value_parser)]
+        value: Option<String>,
+    },
+    /// Exempt this session from being shut down when it is idle for idle_shutdown_hours
+    SetIdleShutdownExempt {
+        /// Whether the session is exempt (flag present) or not (flag absent)
+        #[clap(short, long, value_parser)]
+        exempt: bool,
+    },
+    /// Break the focused pane out into a new tab of its own
+    BreakPane,
+    /// Move the focused pane to the next tab
+    BreakPaneRight,
+    /// Move the focused pane to the previous tab
+    BreakPaneLeft,
+    /// Copy the selection of the focused pane to the clipboard
+    Copy,
+    /// Search the scrollback of the focused pane for the given text
+    SearchInput {
+        #[clap(value_parser)]
+        input: String,
+    },
+    /// Go to the [down|up] next match of the search in the focused pane
+    Search {
+        #[clap(value_parser)]
+        direction: SearchDirection,
+    },
+    /// Toggle an option of the search in the focused pane [casesensitivity|wholeword|wrap]
+    SearchToggleOption {
+        #[clap(value_parser)]
+        option: SearchOption,
+    },
+    /// Add the focused pane to the group of marked panes, or remove it from it
+    TogglePaneInGroup,
+    /// Start or stop marking panes into a group with the mouse
+    ToggleGroupMarking,
+    /// Break the focused pane out into a tab of its own and open a new terminal window attached to
+    /// it (Windows only)
+    BreakPaneToWindow,
+    /// List the recent copies of this session (selections and OSC 52), most recent first
+    ListClipboardHistory,
+    /// Paste a recent copy of this session into a pane and copy it again
+    PasteFromClipboardHistory {
+        /// The index of the copy, as listed by list-clipboard-history
+        #[clap(value_parser)]
+        index: usize,
+        /// The pane_id of the pane, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+        /// Only copy it again, without pasting it
+        #[clap(short, long, value_parser)]
+        copy_only: bool,
+    },
+    /// Choose a recent copy of this session to paste into a pane (the one this runs in if none)
+    PickFromClipboardHistory {
+        /// The pane_id of the pane, eg. terminal_1, plugin_2 or 3 (equivalent to terminal_3)
+        #[clap(short, long, value_parser)]
+        pane_id: Option<String>,
+    },
     /// Send data to one or more plugins, launch them if they are not running.
     #[clap(override_usage(
 r#"
@@ -1160,6 +1416,23 @@ tail -f /tmp/my-live-logfile | zellij action pipe --name logs --plugin https://e
         #[clap(short, long, value_parser)]
         json: bool,
     },
+    /// List the commands running in hidden panes, eg. started with `zellij run --in-background`
+    ///
+    /// Returns: Formatted list of background jobs (table or JSON) to stdout
+    ListBackgroundJobs {
+        /// Output as JSON
+        #[clap(short, long, value_parser)]
+        json: bool,
+    },
+    /// Bring the hidden pane of a background job into view and focus it
+    ShowBackgroundJob {
+        /// The pane id of the job, eg. terminal_1 or 1 (equivalent to terminal_1)
+        #[clap(value_parser)]
+        pane_id: String,
+        /// Show it as a floating pane
+        #[clap(short, long, value_parser)]
+        floating: bool,
+    },
     /// List all tabs with their information
     ///
     /// Returns: Tab information in table or JSON format
@@ -9,10 +9,12 @@ use uuid::Uuid;
 
 pub const ZELLIJ_CONFIG_FILE_ENV: &str = "ZELLIJ_CONFIG_FILE";
 pub const ZELLIJ_CONFIG_DIR_ENV: &str = "ZELLIJ_CONFIG_DIR";
+pub const ZELLIJ_SESSION_SECRET_ENV: &str = "ZELLIJ_SESSION_SECRET";
 pub const ZELLIJ_LAYOUT_DIR_ENV: &str = "ZELLIJ_LAYOUT_DIR";
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const DEFAULT_SCROLL_BUFFER_SIZE: usize = 10_000;
 pub static SCROLL_BUFFER_SIZE: OnceLock<usize> = OnceLock::new();
+pub static SCROLLBACK_SPILL_LINES: OnceLock<usize> = OnceLock::new();
 pub static DEBUG_MODE: OnceLock<bool> = OnceLock::new();
 
 pub const SYSTEM_DEFAULT_CONFIG_DIR: &str = "/etc/zellij";
@@ -22,22 +24,42 @@ pub static ZELLIJ_DEFAULT_THEMES: Dir = include_dir!("$CARGO_MANIFEST_DIR/assets
 
 pub const CLIENT_SERVER_CONTRACT_VERSION: usize = 1;
 
+pub const SESSION_INFO_CACHE_FILE_NAME: &str = "session-metadata.kdl";
+pub const SESSION_PID_FILE_NAME: &str = "server.pid";
+pub const SESSION_SERVER_INFO_FILE_NAME: &str = "server-info.kdl";
+pub const SESSION_LAYOUT_CACHE_FILE_NAME: &str = "session-layout.kdl";
+pub const SESSION_LOCK_FILE_NAME: &str = "session.locked";
+/// The dir in the socket dir holding the files the servers lock while serving a session
+pub const SERVER_LOCKS_DIR_NAME: &str = "server_locks";
+
 pub fn session_info_cache_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("session-metadata.kdl")
+    session_info_folder_for_session(session_name).join(SESSION_INFO_CACHE_FILE_NAME)
 }
 
 pub fn session_pid_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("server.pid")
+    session_info_folder_for_session(session_name).join(SESSION_PID_FILE_NAME)
+}
+
+pub fn session_server_info_file_name(session_name: &str) -> PathBuf {
+    session_info_folder_for_session(session_name).join(SESSION_SERVER_INFO_FILE_NAME)
 }
 
 pub fn session_layout_cache_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("session-layout.kdl")
+    session_info_folder_for_session(session_name).join(SESSION_LAYOUT_CACHE_FILE_NAME)
+}
+
+pub fn session_lock_file_name(session_name: &str) -> PathBuf {
+    session_info_folder_for_session(session_name).join(SESSION_LOCK_FILE_NAME)
 }
 
 pub fn session_info_folder_for_session(session_name: &str) -> PathBuf {
     ZELLIJ_SESSION_INFO_CACHE_DIR.join(session_name)
 }
 
+pub fn scrollback_spill_folder_for_session(session_name: &str) -> PathBuf {
+    ZELLIJ_SCROLLBACK_SPILL_DIR.join(session_name)
+}
+
 pub fn create_config_and_cache_folders() {
     if let Err(e) = std::fs::create_dir_all(&ZELLIJ_CACHE_DIR.as_path()) {
         log::error!("Failed to create cache dir: {:?}", e);
@@ -82,6 +104,7 @@ lazy_static! {
     pub static ref ZELLIJ_PLUGIN_ARTIFACT_DIR: PathBuf = ZELLIJ_CACHE_DIR.join(VERSION);
     pub static ref ZELLIJ_SEEN_RELEASE_NOTES_CACHE_FILE: PathBuf =
         ZELLIJ_CACHE_DIR.join(VERSION).join("seen_release_notes");
+    pub static ref ZELLIJ_SCROLLBACK_SPILL_DIR: PathBuf = ZELLIJ_CACHE_DIR.join("scrollback");
 }
 
 pub const FEATURES: &[&str] = &[
@@ -0,0 +1,141 @@
+//! Minidumps of crashed servers, so that crashes only seen on Windows can be debugged from the
+//! files users attach to their reports.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+static CRASH_DUMPS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Write a minidump (Windows only) when this process panics or crashes, the server does this as
+/// soon as it starts
+pub fn enable() {
+    CRASH_DUMPS_ENABLED.store(true, Ordering::SeqCst);
+    #[cfg(windows)]
+    windows::catch_unhandled_exceptions();
+}
+
+/// Writes a minidump of this process next to a text file with the panic and its error context,
+/// returning the path of the minidump
+pub(crate) fn write_for_panic(context: &str) -> Option<PathBuf> {
+    if !CRASH_DUMPS_ENABLED.load(Ordering::SeqCst) {
+        return None;
+    }
+    let dump = minidump()?;
+    if let Err(e) = fs::write(dump.with_extension("txt"), context) {
+        log::error!("Failed to write the context of the crash dump: {}", e);
+    }
+    Some(dump)
+}
+
+#[cfg(windows)]
+fn minidump() -> Option<PathBuf> {
+    let dump = windows::dump_path()?;
+    match windows::write_minidump(&dump, std::ptr::null()) {
+        Ok(()) => Some(dump),
+        Err(e) => {
+            log::error!("Failed to write a crash dump to {}: {}", dump.display(), e);
+            None
+        },
+    }
+}
+
+#[cfg(not(windows))]
+fn minidump() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::{
+        fs::{self, File},
+        io,
+        os::windows::io::AsRawHandle,
+        path::{Path, PathBuf},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        MiniDumpWithDataSegs, MiniDumpWithThreadInfo, MiniDumpWithUnloadedModules,
+        MiniDumpWriteDump, SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+        MINIDUMP_EXCEPTION_INFORMATION,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId,
+    };
+
+    use crate::consts::ZELLIJ_CACHE_DIR;
+    use crate::envs;
+
+    // lets Windows Error Reporting carry on with the crash once we have dumped it
+    const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+    // crash_dumps/<session name>-<pid>-<seconds since the epoch>.dmp in the cache dir
+    pub fn dump_path() -> Option<PathBuf> {
+        let dump_dir = ZELLIJ_CACHE_DIR.join("crash_dumps");
+        if let Err(e) = fs::create_dir_all(&dump_dir) {
+            log::error!("Failed to create the crash dump directory: {}", e);
+            return None;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let session_name = envs::get_session_name().unwrap_or_else(|_| "zellij".to_owned());
+        Some(dump_dir.join(format!(
+            "{}-{}-{}.dmp",
+            session_name,
+            std::process::id(),
+            timestamp
+        )))
+    }
+
+    /// `exception` is null for panics, whose stack is still that of the panicking thread
+    pub fn write_minidump(path: &Path, exception: *const EXCEPTION_POINTERS) -> io::Result<()> {
+        let file = File::create(path)?;
+        let exception_information = MINIDUMP_EXCEPTION_INFORMATION {
+            ThreadId: unsafe { GetCurrentThreadId() },
+            ExceptionPointers: exception as *mut EXCEPTION_POINTERS,
+            ClientPointers: 0,
+        };
+        let written = unsafe {
+            MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                file.as_raw_handle() as _,
+                MiniDumpWithDataSegs | MiniDumpWithThreadInfo | MiniDumpWithUnloadedModules,
+                if exception.is_null() {
+                    std::ptr::null()
+                } else {
+                    &exception_information
+                },
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if written == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn on_unhandled_exception(exception: *const EXCEPTION_POINTERS) -> i32 {
+        if let Some(dump) = dump_path() {
+            match write_minidump(&dump, exception) {
+                Ok(()) => log::error!("Crashed, a crash dump was written to {}", dump.display()),
+                Err(e) => log::error!("Crashed, and failed to write a crash dump: {}", e),
+            }
+        }
+        EXCEPTION_CONTINUE_SEARCH
+    }
+
+    /// Access violations and the like do not panic, they end the process through the unhandled
+    /// exception filter
+    pub fn catch_unhandled_exceptions() {
+        unsafe {
+            SetUnhandledExceptionFilter(Some(on_unhandled_exception));
+        }
+    }
+}
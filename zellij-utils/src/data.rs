@@ -1013,6 +1013,10 @@ pub enum Event {
     CwdChanged(PaneId, PathBuf, Vec<ClientId>), // pane_id, cwd, focused_client_ids
     AvailableLayoutInfo(Vec<LayoutInfo>, Vec<LayoutWithError>),
     PluginConfigurationChanged(BTreeMap<String, String>),
+    /// A terminal pane had no output or input for longer than the `pane_idle_threshold`
+    PaneBecameIdle(PaneId),
+    /// A previously idle terminal pane had output or input again
+    PaneBecameActive(PaneId),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, EnumDiscriminants, Display, Serialize, Deserialize)]
@@ -2260,6 +2264,9 @@ pub struct PaneInfo {
     /// Grouped panes (usually through an explicit user action) that are staged for a bulk action
     /// the index is kept track of in order to preserve the pane group order
     pub index_in_pane_group: BTreeMap<ClientId, usize>,
+    /// The name of the program running in the foreground of a terminal pane (eg. `cargo` or
+    /// `vim`), if the pane's shell is currently running one
+    pub foreground_process: Option<String>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -26,6 +26,17 @@ pub fn set_session_name(v: String) {
     set_var(SESSION_NAME_ENV_KEY, v);
 }
 
+/// Set on the detached server process on Windows, so that `zellij --server` knows it has
+/// already been spawned in the background
+pub const SERVER_MODE_ENV_KEY: &str = "ZELLIJ_SERVER_MODE";
+
+/// Set on the detached server process on Windows to the accounts, besides the current user,
+/// granted access to the pipes of its session by the `pipe_access` option
+pub const PIPE_ACCESS_ENV_KEY: &str = "ZELLIJ_PIPE_ACCESS";
+pub fn get_pipe_access() -> Result<String> {
+    Ok(var(PIPE_ACCESS_ENV_KEY)?)
+}
+
 pub const SOCKET_DIR_ENV_KEY: &str = "ZELLIJ_SOCKET_DIR";
 pub fn get_socket_dir() -> Result<String> {
     Ok(var(SOCKET_DIR_ENV_KEY)?)
@@ -214,6 +214,7 @@ pub enum ScreenContext {
     PluginBytes,
     Render,
     RenderToClients,
+    ForceRender,
     NewPane,
     OpenInPlaceEditor,
     ToggleFloatingPanes,
@@ -352,8 +353,13 @@ pub enum ScreenContext {
     BreakPane,
     BreakPaneRight,
     BreakPaneLeft,
+    BreakPaneToWindow,
+    ListClipboardHistory,
+    PasteFromClipboardHistory,
+    PickFromClipboardHistory,
     UpdateSessionInfos,
     UpdateAvailableLayouts,
+    UpdateForegroundProcesses,
     ReplacePane,
     NewInPlacePluginPane,
     SerializeLayoutForResurrection,
@@ -411,6 +417,11 @@ pub enum ScreenContext {
     SetFollowedClient,
     WatcherTerminalResize,
     ClearMouseHelpText,
+    TerminalFocus,
+    DownloadScreenDump,
+    CheckIdleShutdown,
+    SetIdleShutdownExempt,
+    ClipboardContent,
 }
 
 /// Stack call representations corresponding to the different types of [`PtyInstruction`]s.
@@ -444,6 +455,9 @@ pub enum PtyContext {
     GetPaneRunningCommand,
     GetPaneCwd,
     UpdateAndReportCwds,
+    UploadFileChunk,
+    CloseAllPanes,
+    SetSessionEnv,
     Exit,
 }
 
@@ -512,6 +526,7 @@ pub enum ClientContext {
     DoneParsingStdinQuery,
     SwitchSession,
     SetSynchronisedOutput,
+    KittyKeyboardProtocolSupported,
     UnblockCliPipeInput,
     CliPipeOutput,
     QueryTerminalSize,
@@ -519,6 +534,16 @@ pub enum ClientContext {
     StartWebServer,
     RenamedSession,
     ConfigFileUpdated,
+    Bell,
+    SetClipboard,
+    QueryClipboard,
+    Notify,
+    SendHeartbeat,
+    HeartbeatAck,
+    OpenLink,
+    FileChunk,
+    ShutdownPending,
+    DesktopLocked,
 }
 
 /// Stack call representations corresponding to the different types of [`ServerInstruction`]s.
@@ -534,6 +559,9 @@ pub enum ServerContext {
     DetachSession,
     AttachClient,
     ConnStatus,
+    ConnStatusDetailed,
+    ReportTabsAndPanes,
+    Heartbeat,
     Log,
     LogError,
     SwitchSession,
@@ -545,6 +573,7 @@ pub enum ServerContext {
     ChangeModeForAllClients,
     Reconfigure,
     ConfigWrittenToDisk,
+    ConfigFileFailedToLoad,
     FailedToWriteConfigToDisk,
     RebindKeys,
     StartWebServer,
@@ -553,7 +582,11 @@ pub enum ServerContext {
     WebServerStarted,
     FailedToStartWebServer,
     SendWebClientsForbidden,
+    SetSessionSecret,
+    SendSessionLocked,
     ClearMouseHelpText,
+    SendFile,
+    ShutDownIdleSession,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -581,6 +614,10 @@ pub enum BackgroundJobContext {
     HighlightPanesWithMessage,
     QueryZellijWebServerStatus,
     ClearHelpText,
+    MonitorPaneActivity,
+    ReportForegroundProcesses,
+    MonitorIdleSession,
+    OpenPaneWindow,
     Exit,
 }
 
@@ -717,7 +754,7 @@ mod not_wasm {
 -> To help us fix this, please open an issue: https://github.com/zellij-org/zellij/issues
 
 ",
-                crate::consts::ZELLIJ_TMP_LOG_FILE.display().to_string()
+                crate::logging::log_file().display()
             )
         }
     }
@@ -756,6 +793,17 @@ mod not_wasm {
             report = report.wrap_err(format!("{}", err_ctx));
         }
 
+        let crash_dump = crate::crash_dump::write_for_panic(&format!(
+            "thread: {}\nlocation: {}\nmessage: {}\nerror context: {:?}\n",
+            thread, location_string, msg, err_ctx
+        ));
+        if let Some(crash_dump) = &crash_dump {
+            report = report.wrap_err(format!(
+                "A crash dump was written to: {}",
+                crash_dump.display()
+            ));
+        }
+
         report = report.wrap_err(format!(
             "Thread '\u{1b}[0;31m{}\u{1b}[0;0m' panicked.",
             thread
@@ -767,8 +815,14 @@ mod not_wasm {
                 "Panic occured:
              thread: {}
              location: {}
-             message: {}",
-                thread, location_string, msg
+             message: {}
+             crash dump: {}",
+                thread,
+                location_string,
+                msg,
+                crash_dump
+                    .map(|d| d.display().to_string())
+                    .unwrap_or_else(|| "none".to_owned())
             )
         );
 
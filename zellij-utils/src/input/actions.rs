@@ -14,7 +14,7 @@ use crate::data::{FloatingPaneCoordinates, InputMode};
 use crate::home::{find_default_config_dir, get_layout_dir};
 use crate::input::config::{Config, ConfigError, KdlError};
 use crate::input::mouse::MouseEvent;
-use crate::input::options::OnForceClose;
+use crate::input::options::{LogLevel, OnForceClose};
 use miette::{NamedSource, Report};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -173,6 +173,8 @@ pub enum Action {
     DumpScreen {
         file_path: String,
         include_scrollback: bool,
+        keep_ansi: bool,
+        crlf_line_endings: bool,
     },
     /// Dumps
     DumpLayout,
@@ -268,6 +270,12 @@ pub enum Action {
         pane_name: Option<String>,
         near_current_pane: bool,
     },
+    /// Run a command in a hidden pane, notifying the clients when it exits
+    /// Returns: Created pane ID (format: terminal_<id>)
+    NewBackgroundPane {
+        command: RunCommandAction,
+        pane_name: Option<String>,
+    },
     /// Embed focused pane in tab if floating or float focused pane if embedded
     TogglePaneEmbedOrFloating,
     /// Toggle the visibility of all floating panes (if any) in the current Tab
@@ -446,9 +454,44 @@ pub enum Action {
     BreakPane,
     BreakPaneRight,
     BreakPaneLeft,
+    /// Breaks the focused pane out into a tab of its own and opens a new terminal window
+    /// attached to it
+    BreakPaneToWindow,
+    /// Lists the recent copies of the session, most recent first
+    ListClipboardHistory,
+    /// Pastes an entry of the clipboard history into a pane (the focused one if none) and copies
+    /// it again
+    PasteFromClipboardHistory {
+        index: usize,
+        pane_id: Option<PaneId>,
+        copy_only: bool,
+    },
+    /// Opens a picker of the clipboard history that pastes into a pane (the focused one if none)
+    PickFromClipboardHistory {
+        pane_id: Option<PaneId>,
+    },
     RenameSession {
         name: String,
     },
+    /// Locks the session with a secret required to attach to it, or unlocks it if `None`. A
+    /// locked session only changes its secret if given the current one
+    SetSessionSecret {
+        secret: Option<String>,
+        current_secret: Option<String>,
+    },
+    /// Changes the most verbose messages the session logs
+    SetLogLevel {
+        level: LogLevel,
+    },
+    /// Sets an environment variable of the panes opened from now on, or removes it if `None`
+    SetSessionEnv {
+        name: String,
+        value: Option<String>,
+    },
+    /// Exempts the session from being shut down when idle, or makes it subject to it again
+    SetIdleShutdownExempt {
+        exempt: bool,
+    },
     CliPipe {
         pipe_id: String,
         name: Option<String>,
@@ -486,6 +529,10 @@ pub enum Action {
         show_all: bool,
         output_json: bool,
     },
+    /// Lists the commands running or held in hidden panes
+    ListBackgroundJobs {
+        output_json: bool,
+    },
     ListTabs {
         show_state: bool,
         show_dimensions: bool,
@@ -657,13 +704,27 @@ impl Action {
             CliAction::MovePaneBackwards => Ok(vec![Action::MovePaneBackwards]),
             CliAction::MoveTab { direction } => Ok(vec![Action::MoveTab { direction }]),
             CliAction::Clear => Ok(vec![Action::ClearScreen]),
-            CliAction::DumpScreen { path, full } => Ok(vec![Action::DumpScreen {
-                file_path: path.as_os_str().to_string_lossy().into(),
+            CliAction::DumpScreen {
+                path,
+                full,
+                ansi,
+                crlf,
+            } => Ok(vec![Action::DumpScreen {
+                // the server might not share our working directory
+                file_path: get_current_dir().join(path).to_string_lossy().into(),
                 include_scrollback: full,
+                keep_ansi: ansi,
+                crlf_line_endings: crlf,
             }]),
             CliAction::DumpLayout => Ok(vec![Action::DumpLayout]),
             CliAction::SaveSession => Ok(vec![Action::SaveSession]),
-            CliAction::EditScrollback => Ok(vec![Action::EditScrollback]),
+            CliAction::EditScrollback { ansi } => {
+                if ansi {
+                    Ok(vec![Action::EditScrollbackRaw])
+                } else {
+                    Ok(vec![Action::EditScrollback])
+                }
+            },
             CliAction::ScrollUp => Ok(vec![Action::ScrollUp]),
             CliAction::ScrollDown => Ok(vec![Action::ScrollDown]),
             CliAction::ScrollToBottom => Ok(vec![Action::ScrollToBottom]),
@@ -697,6 +758,7 @@ impl Action {
                 unblock_condition,
                 near_current_pane,
                 borderless,
+                in_background,
             } => {
                 let current_dir = get_current_dir();
                 // cwd should only be specified in a plugin alias if it was explicitly given to us,
@@ -705,7 +767,28 @@ impl Action {
                 let cwd = cwd
                     .map(|cwd| current_dir.join(cwd))
                     .or_else(|| Some(current_dir.clone()));
-                if blocking || unblock_condition.is_some() {
+                if in_background {
+                    if plugin.is_some() || command.is_empty() {
+                        return Err("Background panes need a command to run".to_string());
+                    }
+                    let mut command = command.clone();
+                    let (command, args) = (PathBuf::from(command.remove(0)), command);
+                    // the pane is held when the command exits, so that its exit status can be
+                    // listed and its output shown
+                    let hold_on_close = true;
+                    let hold_on_start = false;
+                    Ok(vec![Action::NewBackgroundPane {
+                        command: RunCommandAction {
+                            command,
+                            args,
+                            cwd,
+                            hold_on_close,
+                            hold_on_start,
+                            ..Default::default()
+                        },
+                        pane_name: name,
+                    }])
+                } else if blocking || unblock_condition.is_some() {
                     // For blocking panes, we don't support plugins
                     if plugin.is_some() {
                         return Err("Blocking panes do not support plugin variants".to_string());
@@ -931,18 +1014,57 @@ impl Action {
                 }])
             },
             CliAction::SwitchMode { input_mode } => Ok(vec![Action::SwitchToMode { input_mode }]),
+            CliAction::SwitchModeForAllClients { input_mode } => {
+                Ok(vec![Action::SwitchModeForAllClients { input_mode }])
+            },
             CliAction::TogglePaneEmbedOrFloating => Ok(vec![Action::TogglePaneEmbedOrFloating]),
             CliAction::ToggleFloatingPanes => Ok(vec![Action::ToggleFloatingPanes]),
-            CliAction::ClosePane => Ok(vec![Action::CloseFocus]),
-            CliAction::RenamePane { name } => Ok(vec![
-                Action::UndoRenamePane,
-                Action::PaneNameInput {
-                    input: name.as_bytes().to_vec(),
+            CliAction::ClosePane { pane_id } => match pane_id {
+                Some(pane_id) => match PaneId::from_str(&pane_id) {
+                    Ok(PaneId::Terminal(pane_id)) => {
+                        Ok(vec![Action::CloseTerminalPane { pane_id }])
+                    },
+                    Ok(PaneId::Plugin(pane_id)) => Ok(vec![Action::ClosePluginPane { pane_id }]),
+                    Err(_) => Err(format!("Malformed pane id: {}", pane_id)),
                 },
-            ]),
+                None => Ok(vec![Action::CloseFocus]),
+            },
+            CliAction::RenamePane { name, pane_id } => match pane_id {
+                Some(pane_id) => match PaneId::from_str(&pane_id) {
+                    Ok(PaneId::Terminal(pane_id)) => Ok(vec![Action::RenameTerminalPane {
+                        pane_id,
+                        name: name.into_bytes(),
+                    }]),
+                    Ok(PaneId::Plugin(pane_id)) => Ok(vec![Action::RenamePluginPane {
+                        pane_id,
+                        name: name.into_bytes(),
+                    }]),
+                    Err(_) => Err(format!("Malformed pane id: {}", pane_id)),
+                },
+                None => Ok(vec![
+                    Action::UndoRenamePane,
+                    Action::PaneNameInput {
+                        input: name.as_bytes().to_vec(),
+                    },
+                ]),
+            },
+            CliAction::FocusPaneId { pane_id, floating } => match PaneId::from_str(&pane_id) {
+                Ok(PaneId::Terminal(pane_id)) => Ok(vec![Action::FocusTerminalPaneWithId {
+                    pane_id,
+                    should_float_if_hidden: floating,
+                    should_be_in_place_if_hidden: false,
+                }]),
+                Ok(PaneId::Plugin(pane_id)) => Ok(vec![Action::FocusPluginPaneWithId {
+                    pane_id,
+                    should_float_if_hidden: floating,
+                    should_be_in_place_if_hidden: false,
+                }]),
+                Err(_) => Err(format!("Malformed pane id: {}", pane_id)),
+            },
             CliAction::UndoRenamePane => Ok(vec![Action::UndoRenamePane]),
             CliAction::GoToNextTab => Ok(vec![Action::GoToNextTab]),
             CliAction::GoToPreviousTab => Ok(vec![Action::GoToPreviousTab]),
+            CliAction::ToggleTab => Ok(vec![Action::ToggleTab]),
             CliAction::CloseTab => Ok(vec![Action::CloseTab]),
             CliAction::GoToTab { index } => Ok(vec![Action::GoToTab { index }]),
             CliAction::GoToTabName { name, create } => {
@@ -1305,6 +1427,67 @@ impl Action {
                 }])
             },
             CliAction::RenameSession { name } => Ok(vec![Action::RenameSession { name }]),
+            CliAction::SetSessionSecret {
+                secret,
+                current_secret,
+            } => Ok(vec![Action::SetSessionSecret {
+                secret,
+                current_secret,
+            }]),
+            CliAction::SetLogLevel { level } => Ok(vec![Action::SetLogLevel { level }]),
+            CliAction::SetSessionEnv { name, value } => {
+                if name.is_empty() || name.contains(['=', '\0']) {
+                    return Err(format!("Invalid environment variable name: {:?}", name));
+                }
+                if value.as_ref().is_some_and(|value| value.contains('\0')) {
+                    return Err(format!("The value of {} contains a NUL character", name));
+                }
+                Ok(vec![Action::SetSessionEnv { name, value }])
+            },
+            CliAction::SetIdleShutdownExempt { exempt } => {
+                Ok(vec![Action::SetIdleShutdownExempt { exempt }])
+            },
+            CliAction::BreakPane => Ok(vec![Action::BreakPane]),
+            CliAction::BreakPaneRight => Ok(vec![Action::BreakPaneRight]),
+            CliAction::BreakPaneLeft => Ok(vec![Action::BreakPaneLeft]),
+            CliAction::Copy => Ok(vec![Action::Copy]),
+            CliAction::SearchInput { input } => Ok(vec![Action::SearchInput {
+                input: input.into_bytes(),
+            }]),
+            CliAction::Search { direction } => Ok(vec![Action::Search { direction }]),
+            CliAction::SearchToggleOption { option } => {
+                Ok(vec![Action::SearchToggleOption { option }])
+            },
+            CliAction::TogglePaneInGroup => Ok(vec![Action::TogglePaneInGroup]),
+            CliAction::ToggleGroupMarking => Ok(vec![Action::ToggleGroupMarking]),
+            CliAction::BreakPaneToWindow => Ok(vec![Action::BreakPaneToWindow]),
+            CliAction::ListClipboardHistory => Ok(vec![Action::ListClipboardHistory]),
+            CliAction::PasteFromClipboardHistory {
+                index,
+                pane_id,
+                copy_only,
+            } => {
+                let pane_id = pane_id
+                    .map(|pane_id| {
+                        PaneId::from_str(&pane_id)
+                            .map_err(|_| format!("Malformed pane id: {}", pane_id))
+                    })
+                    .transpose()?;
+                Ok(vec![Action::PasteFromClipboardHistory {
+                    index,
+                    pane_id,
+                    copy_only,
+                }])
+            },
+            CliAction::PickFromClipboardHistory { pane_id } => {
+                let pane_id = pane_id
+                    .map(|pane_id| {
+                        PaneId::from_str(&pane_id)
+                            .map_err(|_| format!("Malformed pane id: {}", pane_id))
+                    })
+                    .transpose()?;
+                Ok(vec![Action::PickFromClipboardHistory { pane_id }])
+            },
             CliAction::Pipe {
                 name,
                 payload,
@@ -1375,6 +1558,25 @@ impl Action {
                 Ok(vec![Action::CurrentTabInfo { output_json: json }])
             },
             CliAction::TogglePanePinned => Ok(vec![Action::TogglePanePinned]),
+            CliAction::ListBackgroundJobs { json } => {
+                Ok(vec![Action::ListBackgroundJobs { output_json: json }])
+            },
+            CliAction::ShowBackgroundJob { pane_id, floating } => {
+                match PaneId::from_str(&pane_id) {
+                    Ok(PaneId::Terminal(terminal_id)) => {
+                        Ok(vec![Action::FocusTerminalPaneWithId {
+                            pane_id: terminal_id,
+                            should_float_if_hidden: floating,
+                            should_be_in_place_if_hidden: false,
+                        }])
+                    },
+                    Ok(PaneId::Plugin(_)) => Err(format!(
+                        "{} is a plugin pane, not a background job",
+                        pane_id
+                    )),
+                    Err(_) => Err(format!("Malformed pane id: {}", pane_id)),
+                }
+            },
             CliAction::StackPanes { pane_ids } => {
                 let mut malformed_ids = vec![];
                 let pane_ids = pane_ids
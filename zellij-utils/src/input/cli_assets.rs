@@ -22,6 +22,8 @@ pub struct CliAssets {
     pub max_panes: Option<usize>,
     pub force_run_layout_commands: bool,
     pub cwd: Option<PathBuf>,
+    pub client_theme: Option<String>, // the theme of this client only, from `zellij attach --theme`
+    pub session_secret: Option<String>, // locks a new session, or unlocks a locked one to attach to
 }
 
 impl CliAssets {
@@ -17,6 +17,7 @@ use super::keybinds::Keybinds;
 use super::layout::RunPluginOrAlias;
 use super::options::Options;
 use super::plugins::{PluginAliases, PluginsConfigError};
+use super::session_templates::SessionTemplates;
 use super::theme::{Themes, UiConfig};
 use super::web_client::WebClientConfig;
 use crate::cli::{CliArgs, Command};
@@ -38,6 +39,7 @@ pub struct Config {
     pub env: EnvironmentVariables,
     pub background_plugins: HashSet<RunPluginOrAlias>,
     pub web_client: WebClientConfig,
+    pub session_templates: SessionTemplates,
 }
 
 #[derive(Error, Debug, Serialize, Deserialize)]
@@ -133,6 +135,8 @@ pub enum ConfigError {
     ConversionError(#[from] ConversionError),
     #[error("{0}")]
     DownloadError(String),
+    #[error("{0}")]
+    SessionTemplateError(String),
     #[error("failed to block on async task")]
     Async(#[from] std::io::Error),
 }
@@ -268,6 +272,7 @@ impl Config {
         self.plugins.merge(other.plugins);
         self.ui = self.ui.merge(other.ui);
         self.env = self.env.merge(other.env);
+        self.session_templates = self.session_templates.merge(other.session_templates);
         Ok(())
     }
     pub fn config_file_path(opts: &CliArgs) -> Option<PathBuf> {
@@ -438,10 +443,15 @@ impl Config {
     }
 }
 
+// how long the config file has to be left alone after a change before it is reloaded, editors
+// often save in several steps (eg. truncate and write, or write a temp file and rename it over)
+#[cfg(not(target_family = "wasm"))]
+const CONFIG_FILE_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(300);
+
 #[cfg(not(target_family = "wasm"))]
 pub async fn watch_config_file_changes<F, Fut>(config_file_path: PathBuf, on_config_change: F)
 where
-    F: Fn(Config) -> Fut + Send + 'static,
+    F: Fn(Result<Config, String>) -> Fut + Send + 'static,
     Fut: std::future::Future<Output = ()> + Send,
 {
     // in a gist, what we do here is fire the `on_config_change` function whenever there is a
@@ -454,55 +464,62 @@ where
     // we do this because the alternative is to watch its parent folder and this might cause the
     // classic "too many open files" issue if there are a lot of files there and/or lots of Zellij
     // instances
+    //
+    // bursts of events are debounced, and a config that fails to parse is reported to
+    // `on_config_change` as an error rather than applied
     use crate::setup::Setup;
-    use notify::{self, Config as WatcherConfig, Event, PollWatcher, RecursiveMode, Watcher};
+    use notify::{self, Event};
     use std::time::Duration;
     use tokio::sync::mpsc;
     loop {
         if config_file_path.exists() {
             let (tx, mut rx) = mpsc::unbounded_channel();
 
-            let mut watcher = match PollWatcher::new(
-                move |res: Result<Event, notify::Error>| {
-                    let _ = tx.send(res);
-                },
-                WatcherConfig::default().with_poll_interval(Duration::from_secs(1)),
-            ) {
+            let mut watcher = match config_file_watcher(move |res: Result<Event, notify::Error>| {
+                let _ = tx.send(res);
+            }) {
                 Ok(watcher) => watcher,
-                Err(_) => break,
+                Err(e) => {
+                    log::error!("Failed to watch the config file for changes: {}", e);
+                    break;
+                },
             };
 
-            if watcher
-                .watch(&config_file_path, RecursiveMode::NonRecursive)
-                .is_err()
-            {
+            if let Err(e) = watcher.watch(&config_file_path, notify::RecursiveMode::NonRecursive) {
+                log::error!(
+                    "Failed to watch {} for changes: {}",
+                    config_file_path.display(),
+                    e
+                );
                 break;
             }
 
             while let Some(event_result) = rx.recv().await {
-                match event_result {
-                    Ok(event) => {
-                        if event.paths.contains(&config_file_path) {
-                            if event.kind.is_remove() {
-                                break;
-                            } else if event.kind.is_create() || event.kind.is_modify() {
-                                tokio::time::sleep(Duration::from_millis(100)).await;
-
-                                if !config_file_path.exists() {
-                                    continue;
-                                }
-
-                                let mut cli_args_for_config = CliArgs::default();
-                                cli_args_for_config.config = Some(PathBuf::from(&config_file_path));
-                                if let Ok(new_config) = Setup::from_cli_args(&cli_args_for_config)
-                                    .map_err(|e| e.to_string())
-                                {
-                                    on_config_change(new_config.0).await;
-                                }
-                            }
-                        }
-                    },
-                    Err(_) => break,
+                let mut changes = ConfigFileChanges::default();
+                changes.add(event_result, &config_file_path);
+                loop {
+                    match tokio::time::timeout(CONFIG_FILE_DEBOUNCE, rx.recv()).await {
+                        Ok(Some(event_result)) => changes.add(event_result, &config_file_path),
+                        Ok(None) => {
+                            changes.watch_lost = true;
+                            break;
+                        },
+                        Err(_elapsed) => break,
+                    }
+                }
+                // a removed file that exists again was replaced by the editor saving it
+                if (changes.modified || changes.removed) && config_file_path.exists() {
+                    let cli_args_for_config = CliArgs {
+                        config: Some(config_file_path.clone()),
+                        ..Default::default()
+                    };
+                    let new_config = Setup::from_cli_args(&cli_args_for_config)
+                        .map(|(config, ..)| config)
+                        .map_err(|e| describe_config_error(&e, &config_file_path));
+                    on_config_change(new_config).await;
+                }
+                if changes.removed || changes.watch_lost {
+                    break;
                 }
             }
         }
@@ -513,6 +530,90 @@ where
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+#[derive(Default)]
+struct ConfigFileChanges {
+    modified: bool,
+    removed: bool,
+    watch_lost: bool,
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl ConfigFileChanges {
+    fn add(
+        &mut self,
+        event_result: Result<notify::Event, notify::Error>,
+        config_file_path: &std::path::Path,
+    ) {
+        match event_result {
+            Ok(event) if event.paths.iter().any(|p| p == config_file_path) => {
+                if event.kind.is_remove() {
+                    self.removed = true;
+                } else if event.kind.is_create() || event.kind.is_modify() {
+                    self.modified = true;
+                }
+            },
+            Ok(_) => {},
+            Err(e) => {
+                log::error!("Error while watching the config file: {}", e);
+                self.watch_lost = true;
+            },
+        }
+    }
+}
+
+// Windows reports changes to the file through ReadDirectoryChangesW on its folder, which holds a
+// single handle no matter how many files are in there
+#[cfg(windows)]
+fn config_file_watcher<F: notify::EventHandler>(
+    event_handler: F,
+) -> notify::Result<Box<dyn notify::Watcher + Send>> {
+    use notify::{Config as WatcherConfig, RecommendedWatcher, Watcher};
+    Ok(Box::new(RecommendedWatcher::new(
+        event_handler,
+        WatcherConfig::default(),
+    )?))
+}
+
+#[cfg(not(any(windows, target_family = "wasm")))]
+fn config_file_watcher<F: notify::EventHandler>(
+    event_handler: F,
+) -> notify::Result<Box<dyn notify::Watcher + Send>> {
+    use notify::{Config as WatcherConfig, PollWatcher};
+    use std::time::Duration;
+    Ok(Box::new(PollWatcher::new(
+        event_handler,
+        WatcherConfig::default().with_poll_interval(Duration::from_secs(1)),
+    )?))
+}
+
+/// A one line description of why a config file failed to load, with the position of parse
+/// errors in it
+#[cfg(not(target_family = "wasm"))]
+pub fn describe_config_error(error: &ConfigError, config_file_path: &std::path::Path) -> String {
+    let (message, offset) = match error {
+        ConfigError::KdlError(kdl_error) => (kdl_error.error_message.clone(), kdl_error.offset),
+        ConfigError::KdlDeserializationError(kdl_error) => {
+            (kdl_error.to_string(), Some(kdl_error.span.offset()))
+        },
+        error => (error.to_string(), None),
+    };
+    let position = offset.and_then(|offset| {
+        let contents = std::fs::read_to_string(config_file_path).ok()?;
+        let before_error = contents.get(..offset)?;
+        let line = before_error.matches('\n').count() + 1;
+        let line_start = before_error.rfind('\n').map_or(0, |i| i + 1);
+        let column = before_error[line_start..].chars().count() + 1;
+        Some(format!(":{}:{}", line, column))
+    });
+    format!(
+        "{}{}: {}",
+        config_file_path.display(),
+        position.unwrap_or_default(),
+        message
+    )
+}
+
 #[cfg(not(target_family = "wasm"))]
 pub async fn watch_layout_dir_changes<F, Fut>(
     layout_dir: PathBuf,
@@ -582,9 +683,12 @@ pub async fn watch_layout_dir_changes<F, Fut>(
 #[cfg(test)]
 mod config_test {
     use super::*;
-    use crate::data::{InputMode, Palette, PaletteColor, StyleDeclaration, Styling};
-    use crate::input::layout::RunPlugin;
-    use crate::input::options::{Clipboard, OnForceClose};
+    use crate::data::{
+        BareKey, InputMode, KeyWithModifier, Palette, PaletteColor, StyleDeclaration, Styling,
+    };
+    use crate::input::layout::{Layout, RunPlugin};
+    use crate::input::options::{Clipboard, IdleShutdownAction, KeyChord, LogLevel, OnForceClose};
+    use crate::input::session_templates::SessionTemplateCommand;
     use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
     use std::collections::{BTreeMap, HashMap};
     use std::io::Write;
@@ -768,6 +872,132 @@ mod config_test {
         );
     }
 
+    #[test]
+    fn max_fps_out_of_range_is_rejected() {
+        let config = Config::from_kdl("max_fps 60", None).unwrap();
+        assert_eq!(config.options.max_fps, Some(60));
+        assert!(
+            Config::from_kdl("max_fps -1", None).is_err(),
+            "Negative frame rates are rejected"
+        );
+        assert!(
+            Config::from_kdl("max_fps 4294967296", None).is_err(),
+            "Frame rates that do not fit a u32 are rejected"
+        );
+    }
+
+    #[test]
+    fn utf8_codepage_is_only_set_when_configured() {
+        let config = Config::from_kdl("", None).unwrap();
+        assert_eq!(
+            config.options.utf8_codepage, None,
+            "Panes keep the codepage of the system unless asked to switch"
+        );
+        let config_contents = r#"
+            utf8_codepage true
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(config.options.utf8_codepage, Some(true));
+    }
+
+    #[test]
+    fn can_define_detach_chord_in_configfile() {
+        let config_contents = r#"
+            detach_chord "Ctrl Alt \\ Shift d"
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(
+            config.options.detach_chord,
+            Some(KeyChord(vec![
+                KeyWithModifier::new(BareKey::Char('\\'))
+                    .with_ctrl_modifier()
+                    .with_alt_modifier(),
+                KeyWithModifier::new(BareKey::Char('d')).with_shift_modifier(),
+            ])),
+            "Modifiers apply to the key following them"
+        );
+        let config_contents = r#"
+            detach_chord ""
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(
+            config.options.detach_chord,
+            Some(KeyChord(vec![])),
+            "Empty chord disables it"
+        );
+        let config_contents = r#"
+            detach_chord "Ctrl"
+        "#;
+        assert!(
+            Config::from_kdl(config_contents, None).is_err(),
+            "Chord cannot end with a modifier"
+        );
+        assert_eq!(
+            KeyChord::default_detach().0.is_empty(),
+            !cfg!(windows),
+            "Only Windows detaches with a chord unless one is configured"
+        );
+    }
+
+    #[test]
+    fn can_define_log_options_in_configfile() {
+        let config_contents = r#"
+            log_dir "logs"
+            log_level "debug"
+            log_max_size_mb 64
+            log_max_files 5
+            log_rotate_hours 24
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(config.options.log_dir, Some(PathBuf::from("logs")));
+        assert_eq!(config.options.log_level, Some(LogLevel::Debug));
+        assert_eq!(config.options.log_max_size_mb, Some(64));
+        assert_eq!(config.options.log_max_files, Some(5));
+        assert_eq!(config.options.log_rotate_hours, Some(24));
+        let config_contents = r#"
+            log_level "verbose"
+        "#;
+        assert!(
+            Config::from_kdl(config_contents, None).is_err(),
+            "Unknown log level"
+        );
+        let config_contents = r#"
+            log_max_files 0
+        "#;
+        assert!(
+            Config::from_kdl(config_contents, None).is_err(),
+            "At least one rotated log is kept"
+        );
+    }
+
+    #[test]
+    fn can_define_idle_shutdown_options_in_configfile() {
+        let config_contents = r#"
+            idle_shutdown_hours 72
+            idle_shutdown_action "kill"
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        assert_eq!(config.options.idle_shutdown_hours, Some(72));
+        assert_eq!(
+            config.options.idle_shutdown_action,
+            Some(IdleShutdownAction::Kill)
+        );
+        let config_contents = r#"
+            idle_shutdown_hours 0
+        "#;
+        assert!(
+            Config::from_kdl(config_contents, None).is_err(),
+            "Sessions are idle for at least an hour"
+        );
+        let config_contents = r#"
+            idle_shutdown_action "detach"
+        "#;
+        assert!(
+            Config::from_kdl(config_contents, None).is_err(),
+            "Unknown idle shutdown action"
+        );
+    }
+
     #[test]
     fn can_define_themes_in_configfile() {
         let config_contents = r#"
@@ -1332,4 +1562,140 @@ mod config_test {
             "Env variables defined in config"
         );
     }
+
+    #[test]
+    fn can_define_session_templates_in_config_file() {
+        let config_contents = r#"
+            session_templates {
+                my-backend-dev {
+                    layout "compact"
+                    cwd "/src/backend"
+                    env {
+                        RUST_LOG "debug"
+                    }
+                    command "cargo" "watch" "-x" "run"
+                    command "npm" "run" "dev"
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let template = config.session_templates.get("my-backend-dev").unwrap();
+        let mut expected_env_config = HashMap::new();
+        expected_env_config.insert("RUST_LOG".into(), "debug".into());
+        assert_eq!(template.layout, Some(PathBuf::from("compact")));
+        assert_eq!(template.cwd, Some(PathBuf::from("/src/backend")));
+        assert_eq!(
+            template.env,
+            EnvironmentVariables::from_data(expected_env_config)
+        );
+        assert_eq!(
+            template.commands,
+            vec![
+                SessionTemplateCommand {
+                    command: "cargo".into(),
+                    args: vec!["watch".into(), "-x".into(), "run".into()],
+                },
+                SessionTemplateCommand {
+                    command: "npm".into(),
+                    args: vec!["run".into(), "dev".into()],
+                },
+            ],
+        );
+        let reparsed_config = Config::from_kdl(&config.to_string(false), None).unwrap();
+        assert_eq!(
+            reparsed_config.session_templates, config.session_templates,
+            "Session templates survive serializing the config"
+        );
+    }
+
+    #[test]
+    fn session_template_adds_a_tab_running_its_commands_to_the_layout() {
+        let config_contents = r#"
+            session_templates {
+                my-backend-dev {
+                    cwd "/src/backend"
+                    command "cargo" "watch" "-x" "run"
+                    command "npm" "run" "dev"
+                }
+            }
+        "#;
+        let config = Config::from_kdl(config_contents, None).unwrap();
+        let template = config.session_templates.get("my-backend-dev").unwrap();
+        let layout_info = template
+            .layout_info(
+                "my-backend-dev",
+                Some(LayoutInfo::BuiltIn("default".into())),
+                &None,
+            )
+            .unwrap();
+        let Some(LayoutInfo::Stringified(stringified_layout)) = layout_info else {
+            panic!("Expected a stringified layout, got: {:?}", layout_info);
+        };
+        let layout = Layout::from_kdl(&stringified_layout, None, None, None).unwrap();
+        assert_eq!(
+            layout.tabs.len(),
+            1,
+            "Only the tab of the commands is started"
+        );
+        assert_eq!(layout.tabs[0].0, Some("my-backend-dev".into()));
+        assert!(
+            layout.template.is_some(),
+            "The panes of the default layout surround the tab"
+        );
+        assert!(
+            !layout.swap_tiled_layouts.is_empty(),
+            "The swap layouts of the default layout are kept"
+        );
+        assert!(
+            stringified_layout.contains("cwd \"/src/backend\""),
+            "The layout starts in the cwd of the template"
+        );
+    }
+
+    #[test]
+    fn config_errors_point_at_the_line_of_the_error() {
+        let tmp = tempdir().unwrap();
+        let config_file_path = tmp.path().join(DEFAULT_CONFIG_FILE_NAME);
+        File::create(&config_file_path)
+            .unwrap()
+            .write_all(b"simplified_ui true\nkeybinds {\n    normal {\n        bind \"a\" { NotAnAction; }\n    }\n}\n")
+            .unwrap();
+        let opts = CliArgs {
+            config: Some(config_file_path.clone()),
+            ..Default::default()
+        };
+        let error = Config::try_from(&opts).unwrap_err();
+        let description = describe_config_error(&error, &config_file_path);
+        assert!(
+            description.starts_with(&format!("{}:4:", config_file_path.display())),
+            "Unexpected description: {}",
+            description
+        );
+    }
+
+    #[test]
+    fn config_file_changes_only_count_events_of_the_config_file() {
+        use notify::event::{CreateKind, EventKind, ModifyKind, RemoveKind};
+        let config_file_path = PathBuf::from("/config/config.kdl");
+        let event = |kind, path: &str| Ok(notify::Event::new(kind).add_path(PathBuf::from(path)));
+        let mut changes = ConfigFileChanges::default();
+        changes.add(
+            event(EventKind::Modify(ModifyKind::Any), "/config/layout.kdl"),
+            &config_file_path,
+        );
+        assert!(!changes.modified && !changes.removed && !changes.watch_lost);
+        changes.add(
+            event(EventKind::Remove(RemoveKind::File), "/config/config.kdl"),
+            &config_file_path,
+        );
+        changes.add(
+            event(EventKind::Create(CreateKind::File), "/config/config.kdl"),
+            &config_file_path,
+        );
+        assert!(
+            changes.modified && changes.removed,
+            "A config file saved by replacing it is both removed and modified"
+        );
+        assert!(!changes.watch_lost);
+    }
 }
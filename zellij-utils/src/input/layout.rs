@@ -852,6 +852,8 @@ pub struct TiledPaneLayout {
     pub children_are_stacked: bool,
     pub is_expanded_in_stack: bool,
     pub exclude_from_sync: Option<bool>,
+    pub suppress_bell: Option<bool>,
+    pub encoding: Option<String>,
     pub run_instructions_to_ignore: Vec<Option<Run>>,
     pub hide_floating_panes: bool, // only relevant if this is the base layout
     pub pane_initial_contents: Option<String>,
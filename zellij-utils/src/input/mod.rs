@@ -8,6 +8,7 @@ pub mod mouse;
 pub mod options;
 pub mod permission;
 pub mod plugins;
+pub mod session_templates;
 pub mod theme;
 pub mod web_client;
 
@@ -91,6 +92,13 @@ mod not_wasm {
             return KeyWithModifier::new(BareKey::Char('h')).with_ctrl_modifier();
         };
 
+        // Ctrl+\, Ctrl+], Ctrl+^ and Ctrl+_ arrive from the Windows console as the bare control
+        // characters
+        #[cfg(windows)]
+        if let [byte @ 0x1c..=0x1f] = raw_bytes {
+            return KeyWithModifier::new(BareKey::Char((byte | 0x40) as char)).with_ctrl_modifier();
+        }
+
         if raw_bytes == [10] {
             if let Some((keybinds, mode)) = keybinds_mode {
                 let ctrl_j = KeyWithModifier::new(BareKey::Char('j')).with_ctrl_modifier();
@@ -1,8 +1,10 @@
 //! Handles cli and configuration options
 use crate::cli::Command;
-use crate::data::{InputMode, WebSharing};
+use crate::data::{BareKey, InputMode, KeyModifier, KeyWithModifier, WebSharing};
 use clap::{ArgEnum, Args};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -250,6 +252,232 @@ pub struct Options {
     /// NOTE: This only applies to web clients at the moment.
     #[clap(long)]
     pub client_async_worker_tasks: Option<usize>,
+
+    /// Seconds without input or output after which a pane is reported as idle to plugins
+    /// (PaneBecameIdle / PaneBecameActive events), 0 disables activity tracking
+    /// default is 10
+    #[clap(long, value_parser)]
+    pub pane_idle_threshold: Option<u64>,
+
+    /// Milliseconds a pane's processes are given to shut down gracefully when the pane is closed
+    /// or interrupted, before they are forcefully terminated. 0 terminates immediately
+    /// default is 0
+    #[clap(long, value_parser)]
+    pub pane_kill_grace_ms: Option<u64>,
+
+    /// Megabytes per second a single pane may output before reading from it is throttled
+    /// and its frame shows a warning, 0 disables the limit
+    /// default is 0
+    #[clap(long, value_parser)]
+    pub pane_output_rate_limit: Option<u64>,
+
+    /// How to notify about a bell (BEL) rung in a pane
+    /// (none, beep, flash or toast; only beep, flash and toast are Windows specific)
+    /// default is none
+    #[clap(long, arg_enum, hide_possible_values = true, value_parser)]
+    pub bell_notification: Option<BellNotification>,
+
+    /// Which ConPTY implementation hosts the panes (Windows only)
+    /// (bundled, system or the path of a directory with conpty.dll and OpenConsole.exe)
+    /// default is bundled
+    #[clap(long, value_parser)]
+    pub conpty: Option<ConPtyHost>,
+
+    /// Which pseudo terminal implementation runs the panes (Windows only)
+    /// (conpty, conpty_passthrough or winpty)
+    /// default is conpty
+    #[clap(long, value_parser)]
+    pub pty_system: Option<PtySystem>,
+
+    /// Whether to switch the console of new panes to the UTF-8 codepage, which runs chcp.com in
+    /// it before the shell starts (Windows only)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub utf8_codepage: Option<bool>,
+
+    /// Ask for confirmation before pasting more than this many bytes (Windows only)
+    /// default is to never ask
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub paste_warning_size: Option<usize>,
+
+    /// Ask for confirmation before pasting text spanning multiple lines (Windows only)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub multi_line_paste_warning: Option<bool>,
+
+    /// Treat a single read of several lines of plain text as a paste, for consoles that paste
+    /// without bracketed paste markers (Windows only) - several lines typed quickly enough can be
+    /// mistaken for one
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub detect_unbracketed_paste: Option<bool>,
+
+    /// Whether panes may read the clipboard through OSC 52, after asking for permission every
+    /// time (Windows only)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub allow_clipboard_read: Option<bool>,
+
+    /// A command to pipe copied text to when the clipboard cannot be opened, eg. because
+    /// another application holds it (Windows only)
+    /// default is to not copy the text then
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub copy_fallback_command: Option<String>,
+
+    /// The line endings of text copied to the clipboard (Windows only)
+    /// (crlf, lf or unchanged)
+    /// default is crlf
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub copy_line_endings: Option<CopyLineEndings>,
+
+    /// What Ctrl+Break does (Windows only)
+    /// (forward, detach or quit)
+    /// default is what on_force_close is set to
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub ctrl_break_action: Option<ConsoleKeyAction>,
+
+    /// What Ctrl+C does
+    /// (forward, detach or quit)
+    /// default is forward
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub ctrl_c_action: Option<ConsoleKeyAction>,
+
+    /// What Ctrl+Z does
+    /// (forward, detach or quit)
+    /// default is forward
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub ctrl_z_action: Option<ConsoleKeyAction>,
+
+    /// A sequence of keys that detaches this client, intercepted before any keybinding,
+    /// pressing its first key twice sends that key on
+    /// default is `Ctrl \ d` on Windows and none elsewhere, an empty string disables it
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub detach_chord: Option<KeyChord>,
+
+    /// The most frames per second the client draws, output arriving in between is drawn with
+    /// the next frame - for terminals that cannot keep up with rendering
+    /// default is no limit
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub max_fps: Option<usize>,
+
+    /// Draw for the classic Windows console (conhost): colors mapped to the 256 color palette,
+    /// square box-drawing corners and no synchronized output
+    /// default is on in a classic console window and off otherwise (Windows only)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub legacy_console_rendering: Option<bool>,
+
+    /// Whether to keep the desktop notifications of this session (eg. about finished commands)
+    /// to itself
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub mute_notifications: Option<bool>,
+
+    /// Whether to mirror the warnings and errors logged by zellij to the Windows Event Log, under
+    /// the "zellij" source (register it with `zellij setup --register-event-source`)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub windows_event_log: Option<bool>,
+
+    /// Comma separated accounts or groups, besides the current user, allowed to connect to the
+    /// pipes of new sessions and attach to them, given as SIDs (eg. `S-1-5-32-544`), SDDL
+    /// aliases (eg. `BA`) or account names (eg. `SERVER\alice`)
+    /// default is only the current user (Windows only)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub pipe_access: Option<PipeAccess>,
+
+    /// Megabytes of memory the processes of all panes of a session may commit together
+    /// default is no limit (Windows only)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub session_max_memory_mb: Option<u64>,
+
+    /// The most processes that may run in all panes of a session together
+    /// default is no limit (Windows only)
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub session_max_processes: Option<u32>,
+
+    /// Percentage (1 to 100) of the total CPU time of the machine the processes of all panes of a
+    /// session may use together
+    /// default is no limit (Windows only)
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..=100))]
+    #[serde(default)]
+    pub session_cpu_rate_percent: Option<u32>,
+
+    /// The directory zellij writes its logs to, relative paths are taken from the local data
+    /// directory (under %LOCALAPPDATA% on Windows)
+    /// default is the zellij-log folder of the temporary directory
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub log_dir: Option<PathBuf>,
+
+    /// The most verbose messages to log (off, error, warn, info, debug or trace)
+    /// default is info
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub log_level: Option<LogLevel>,
+
+    /// Megabytes the log may grow to before it is rotated
+    /// default is 16
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    #[serde(default)]
+    pub log_max_size_mb: Option<u64>,
+
+    /// How many rotated logs to keep next to the current one
+    /// default is 1
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    #[serde(default)]
+    pub log_max_files: Option<u32>,
+
+    /// Rotate the log after this many hours, even if it has not grown to log_max_size_mb
+    /// default is to only rotate it by size
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    #[serde(default)]
+    pub log_rotate_hours: Option<u64>,
+
+    /// Shut the session down after this many hours with no clients attached and no output from
+    /// its panes
+    /// default is to never shut idle sessions down
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    #[serde(default)]
+    pub idle_shutdown_hours: Option<u64>,
+
+    /// How to shut idle sessions down: serialize them so that they can be resurrected, or kill
+    /// them (serialize or kill)
+    /// default is serialize
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub idle_shutdown_action: Option<IdleShutdownAction>,
+
+    /// Detach this client when the Windows desktop is locked, offering to reattach once it is
+    /// unlocked (Windows only)
+    /// default is false
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub detach_on_lock: Option<bool>,
+
+    /// Number of scrollback lines per pane to keep in a file under the cache directory once they
+    /// fall out of the in-memory scroll buffer (`scroll_buffer_size`), 0 disables this
+    /// default is 0
+    #[clap(long, value_parser)]
+    #[serde(default)]
+    pub scrollback_spill_lines: Option<usize>,
 }
 
 #[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
@@ -277,6 +505,352 @@ impl FromStr for Clipboard {
     }
 }
 
+/// How the client notifies the user about a bell rung in one of the panes
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum BellNotification {
+    #[serde(alias = "none")]
+    #[default]
+    None,
+    #[serde(alias = "beep")]
+    Beep,
+    #[serde(alias = "flash")]
+    Flash,
+    #[serde(alias = "toast")]
+    Toast,
+}
+
+impl FromStr for BellNotification {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "beep" => Ok(Self::Beep),
+            "flash" => Ok(Self::Flash),
+            "toast" => Ok(Self::Toast),
+            _ => Err(format!("No such bell notification: {}", s)),
+        }
+    }
+}
+
+/// Which ConPTY implementation hosts the panes on Windows
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
+pub enum ConPtyHost {
+    /// `conpty.dll` and `OpenConsole.exe` next to the zellij executable, or the system's ConPTY
+    /// if they are missing
+    #[default]
+    Bundled,
+    /// The ConPTY built into Windows
+    System,
+    /// `conpty.dll` and `OpenConsole.exe` from this directory
+    Path(PathBuf),
+}
+
+impl FromStr for ConPtyHost {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bundled" => Ok(Self::Bundled),
+            "system" => Ok(Self::System),
+            "" => Err("No such ConPTY host: ''".to_owned()),
+            path => Ok(Self::Path(PathBuf::from(path))),
+        }
+    }
+}
+
+impl fmt::Display for ConPtyHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bundled => write!(f, "bundled"),
+            Self::System => write!(f, "system"),
+            Self::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Which pseudo terminal implementation runs the panes on Windows
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum PtySystem {
+    /// ConPTY, hosted as selected by `conpty`
+    #[default]
+    ConPty,
+    /// ConPTY in passthrough mode, which needs a recent conpty.dll and lets sixel images drawn
+    /// in the panes through
+    ConPtyPassthrough,
+    /// winpty, for Windows builds whose ConPTY is broken; needs `winpty.dll` and
+    /// `winpty-agent.exe` next to the zellij executable
+    WinPty,
+}
+
+impl FromStr for PtySystem {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "conpty" => Ok(Self::ConPty),
+            "conpty_passthrough" => Ok(Self::ConPtyPassthrough),
+            "winpty" => Ok(Self::WinPty),
+            _ => Err(format!("No such pty system: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for PtySystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConPty => write!(f, "conpty"),
+            Self::ConPtyPassthrough => write!(f, "conpty_passthrough"),
+            Self::WinPty => write!(f, "winpty"),
+        }
+    }
+}
+
+/// The most verbose messages zellij logs
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            _ => Err(format!("No such log level: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Error => write!(f, "error"),
+            Self::Warn => write!(f, "warn"),
+            Self::Info => write!(f, "info"),
+            Self::Debug => write!(f, "debug"),
+            Self::Trace => write!(f, "trace"),
+        }
+    }
+}
+
+/// What happens to a session that was idle for `idle_shutdown_hours`
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdleShutdownAction {
+    /// Serialize the session before exiting, so that it can be resurrected
+    #[default]
+    Serialize,
+    /// Exit and delete the serialized session
+    Kill,
+}
+
+impl FromStr for IdleShutdownAction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "serialize" => Ok(Self::Serialize),
+            "kill" => Ok(Self::Kill),
+            _ => Err(format!("No such idle shutdown action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for IdleShutdownAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serialize => write!(f, "serialize"),
+            Self::Kill => write!(f, "kill"),
+        }
+    }
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(log_level: LogLevel) -> Self {
+        match log_level {
+            LogLevel::Off => log::LevelFilter::Off,
+            LogLevel::Error => log::LevelFilter::Error,
+            LogLevel::Warn => log::LevelFilter::Warn,
+            LogLevel::Info => log::LevelFilter::Info,
+            LogLevel::Debug => log::LevelFilter::Debug,
+            LogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// The line endings of text copied to the clipboard on Windows
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum CopyLineEndings {
+    /// Windows line endings, which most Windows applications expect when pasting
+    #[default]
+    Crlf,
+    /// Unix line endings
+    Lf,
+    /// Copy the text as it is
+    Unchanged,
+}
+
+impl CopyLineEndings {
+    pub fn normalize(&self, text: &str) -> String {
+        match self {
+            Self::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+            Self::Lf => text.replace("\r\n", "\n"),
+            Self::Unchanged => text.to_owned(),
+        }
+    }
+}
+
+impl FromStr for CopyLineEndings {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crlf" => Ok(Self::Crlf),
+            "lf" => Ok(Self::Lf),
+            "unchanged" => Ok(Self::Unchanged),
+            _ => Err(format!("No such line endings: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for CopyLineEndings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Crlf => write!(f, "crlf"),
+            Self::Lf => write!(f, "lf"),
+            Self::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// What one of the console control keys (Ctrl+Break, Ctrl+C and Ctrl+Z) does
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleKeyAction {
+    /// Send the key to the focused pane, subject to the keybindings
+    Forward,
+    /// Detach from the session
+    Detach,
+    /// Quit the session
+    Quit,
+}
+
+impl From<OnForceClose> for ConsoleKeyAction {
+    fn from(on_force_close: OnForceClose) -> Self {
+        match on_force_close {
+            OnForceClose::Quit => Self::Quit,
+            OnForceClose::Detach => Self::Detach,
+        }
+    }
+}
+
+impl FromStr for ConsoleKeyAction {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward" => Ok(Self::Forward),
+            "detach" => Ok(Self::Detach),
+            "quit" => Ok(Self::Quit),
+            _ => Err(format!("No such console key action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ConsoleKeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Forward => write!(f, "forward"),
+            Self::Detach => write!(f, "detach"),
+            Self::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// A sequence of keys pressed one after the other, eg. `Ctrl \ d`. Modifiers apply to the key
+/// following them.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct KeyChord(pub Vec<KeyWithModifier>);
+
+impl KeyChord {
+    /// The chord detaching a client unless configured otherwise: Ctrl+\ followed by d on Windows,
+    /// where Ctrl+C and Ctrl+Break are taken by the console, and none elsewhere
+    pub fn default_detach() -> Self {
+        if cfg!(windows) {
+            KeyChord(vec![
+                KeyWithModifier::new(BareKey::Char('\\')).with_ctrl_modifier(),
+                KeyWithModifier::new(BareKey::Char('d')),
+            ])
+        } else {
+            KeyChord(vec![])
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = vec![];
+        let mut key_modifiers = BTreeSet::new();
+        for part in s.split_ascii_whitespace() {
+            if let Ok(key_modifier) = KeyModifier::from_str(part) {
+                key_modifiers.insert(key_modifier);
+                continue;
+            }
+            let bare_key = BareKey::from_str(part)
+                .map_err(|_| format!("No such key in chord '{}': {}", s, part))?;
+            keys.push(KeyWithModifier::new_with_modifiers(
+                bare_key,
+                std::mem::take(&mut key_modifiers),
+            ));
+        }
+        if !key_modifiers.is_empty() {
+            return Err(format!("Chord '{}' ends with a modifier", s));
+        }
+        Ok(KeyChord(keys))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|key| key.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// The accounts or groups, besides the current user, granted access to the pipes of a session
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct PipeAccess(pub Vec<String>);
+
+impl FromStr for PipeAccess {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut trustees = vec![];
+        for trustee in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            // these would let a trustee end its ACE and add arbitrary ones to the DACL
+            if trustee.contains(['(', ')', ';', ':']) {
+                return Err(format!(
+                    "Invalid account in pipe access '{}': {}",
+                    s, trustee
+                ));
+            }
+            trustees.push(trustee.to_owned());
+        }
+        Ok(PipeAccess(trustees))
+    }
+}
+
+impl fmt::Display for PipeAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join(", "))
+    }
+}
+
 impl Options {
     pub fn from_yaml(from_yaml: Option<Options>) -> Options {
         if let Some(opts) = from_yaml {
@@ -351,6 +925,50 @@ impl Options {
         let client_async_worker_tasks = other
             .client_async_worker_tasks
             .or(self.client_async_worker_tasks);
+        let pane_idle_threshold = other.pane_idle_threshold.or(self.pane_idle_threshold);
+        let pane_kill_grace_ms = other.pane_kill_grace_ms.or(self.pane_kill_grace_ms);
+        let pane_output_rate_limit = other.pane_output_rate_limit.or(self.pane_output_rate_limit);
+        let bell_notification = other.bell_notification.or(self.bell_notification);
+        let conpty = other.conpty.or_else(|| self.conpty.clone());
+        let pty_system = other.pty_system.or(self.pty_system);
+        let utf8_codepage = other.utf8_codepage.or(self.utf8_codepage);
+        let paste_warning_size = other.paste_warning_size.or(self.paste_warning_size);
+        let multi_line_paste_warning = other
+            .multi_line_paste_warning
+            .or(self.multi_line_paste_warning);
+        let detect_unbracketed_paste = other
+            .detect_unbracketed_paste
+            .or(self.detect_unbracketed_paste);
+        let allow_clipboard_read = other.allow_clipboard_read.or(self.allow_clipboard_read);
+        let copy_fallback_command = other
+            .copy_fallback_command
+            .or_else(|| self.copy_fallback_command.clone());
+        let copy_line_endings = other.copy_line_endings.or(self.copy_line_endings);
+        let ctrl_break_action = other.ctrl_break_action.or(self.ctrl_break_action);
+        let ctrl_c_action = other.ctrl_c_action.or(self.ctrl_c_action);
+        let ctrl_z_action = other.ctrl_z_action.or(self.ctrl_z_action);
+        let detach_chord = other.detach_chord.or_else(|| self.detach_chord.clone());
+        let max_fps = other.max_fps.or(self.max_fps);
+        let legacy_console_rendering = other
+            .legacy_console_rendering
+            .or(self.legacy_console_rendering);
+        let mute_notifications = other.mute_notifications.or(self.mute_notifications);
+        let windows_event_log = other.windows_event_log.or(self.windows_event_log);
+        let pipe_access = other.pipe_access.or_else(|| self.pipe_access.clone());
+        let session_max_memory_mb = other.session_max_memory_mb.or(self.session_max_memory_mb);
+        let session_max_processes = other.session_max_processes.or(self.session_max_processes);
+        let session_cpu_rate_percent = other
+            .session_cpu_rate_percent
+            .or(self.session_cpu_rate_percent);
+        let log_dir = other.log_dir.or_else(|| self.log_dir.clone());
+        let log_level = other.log_level.or(self.log_level);
+        let log_max_size_mb = other.log_max_size_mb.or(self.log_max_size_mb);
+        let log_max_files = other.log_max_files.or(self.log_max_files);
+        let log_rotate_hours = other.log_rotate_hours.or(self.log_rotate_hours);
+        let idle_shutdown_hours = other.idle_shutdown_hours.or(self.idle_shutdown_hours);
+        let idle_shutdown_action = other.idle_shutdown_action.or(self.idle_shutdown_action);
+        let detach_on_lock = other.detach_on_lock.or(self.detach_on_lock);
+        let scrollback_spill_lines = other.scrollback_spill_lines.or(self.scrollback_spill_lines);
 
         Options {
             simplified_ui,
@@ -395,6 +1013,40 @@ impl Options {
             enforce_https_for_localhost,
             post_command_discovery_hook,
             client_async_worker_tasks,
+            pane_idle_threshold,
+            pane_kill_grace_ms,
+            pane_output_rate_limit,
+            bell_notification,
+            conpty,
+            pty_system,
+            utf8_codepage,
+            paste_warning_size,
+            multi_line_paste_warning,
+            detect_unbracketed_paste,
+            allow_clipboard_read,
+            copy_fallback_command,
+            copy_line_endings,
+            ctrl_break_action,
+            ctrl_c_action,
+            ctrl_z_action,
+            detach_chord,
+            max_fps,
+            legacy_console_rendering,
+            mute_notifications,
+            windows_event_log,
+            pipe_access,
+            session_max_memory_mb,
+            session_max_processes,
+            session_cpu_rate_percent,
+            log_dir,
+            log_level,
+            log_max_size_mb,
+            log_max_files,
+            log_rotate_hours,
+            idle_shutdown_hours,
+            idle_shutdown_action,
+            detach_on_lock,
+            scrollback_spill_lines,
         }
     }
 
@@ -476,6 +1128,50 @@ impl Options {
         let client_async_worker_tasks = other
             .client_async_worker_tasks
             .or(self.client_async_worker_tasks);
+        let pane_idle_threshold = other.pane_idle_threshold.or(self.pane_idle_threshold);
+        let pane_kill_grace_ms = other.pane_kill_grace_ms.or(self.pane_kill_grace_ms);
+        let pane_output_rate_limit = other.pane_output_rate_limit.or(self.pane_output_rate_limit);
+        let bell_notification = other.bell_notification.or(self.bell_notification);
+        let conpty = other.conpty.or_else(|| self.conpty.clone());
+        let pty_system = other.pty_system.or(self.pty_system);
+        let utf8_codepage = other.utf8_codepage.or(self.utf8_codepage);
+        let paste_warning_size = other.paste_warning_size.or(self.paste_warning_size);
+        let multi_line_paste_warning = other
+            .multi_line_paste_warning
+            .or(self.multi_line_paste_warning);
+        let detect_unbracketed_paste = other
+            .detect_unbracketed_paste
+            .or(self.detect_unbracketed_paste);
+        let allow_clipboard_read = other.allow_clipboard_read.or(self.allow_clipboard_read);
+        let copy_fallback_command = other
+            .copy_fallback_command
+            .or_else(|| self.copy_fallback_command.clone());
+        let copy_line_endings = other.copy_line_endings.or(self.copy_line_endings);
+        let ctrl_break_action = other.ctrl_break_action.or(self.ctrl_break_action);
+        let ctrl_c_action = other.ctrl_c_action.or(self.ctrl_c_action);
+        let ctrl_z_action = other.ctrl_z_action.or(self.ctrl_z_action);
+        let detach_chord = other.detach_chord.or_else(|| self.detach_chord.clone());
+        let max_fps = other.max_fps.or(self.max_fps);
+        let legacy_console_rendering = other
+            .legacy_console_rendering
+            .or(self.legacy_console_rendering);
+        let mute_notifications = other.mute_notifications.or(self.mute_notifications);
+        let windows_event_log = other.windows_event_log.or(self.windows_event_log);
+        let pipe_access = other.pipe_access.or_else(|| self.pipe_access.clone());
+        let session_max_memory_mb = other.session_max_memory_mb.or(self.session_max_memory_mb);
+        let session_max_processes = other.session_max_processes.or(self.session_max_processes);
+        let session_cpu_rate_percent = other
+            .session_cpu_rate_percent
+            .or(self.session_cpu_rate_percent);
+        let log_dir = other.log_dir.or_else(|| self.log_dir.clone());
+        let log_level = other.log_level.or(self.log_level);
+        let log_max_size_mb = other.log_max_size_mb.or(self.log_max_size_mb);
+        let log_max_files = other.log_max_files.or(self.log_max_files);
+        let log_rotate_hours = other.log_rotate_hours.or(self.log_rotate_hours);
+        let idle_shutdown_hours = other.idle_shutdown_hours.or(self.idle_shutdown_hours);
+        let idle_shutdown_action = other.idle_shutdown_action.or(self.idle_shutdown_action);
+        let detach_on_lock = other.detach_on_lock.or(self.detach_on_lock);
+        let scrollback_spill_lines = other.scrollback_spill_lines.or(self.scrollback_spill_lines);
 
         Options {
             simplified_ui,
@@ -520,6 +1216,40 @@ impl Options {
             enforce_https_for_localhost,
             post_command_discovery_hook,
             client_async_worker_tasks,
+            pane_idle_threshold,
+            pane_kill_grace_ms,
+            pane_output_rate_limit,
+            bell_notification,
+            conpty,
+            pty_system,
+            utf8_codepage,
+            paste_warning_size,
+            multi_line_paste_warning,
+            detect_unbracketed_paste,
+            allow_clipboard_read,
+            copy_fallback_command,
+            copy_line_endings,
+            ctrl_break_action,
+            ctrl_c_action,
+            ctrl_z_action,
+            detach_chord,
+            max_fps,
+            legacy_console_rendering,
+            mute_notifications,
+            windows_event_log,
+            pipe_access,
+            session_max_memory_mb,
+            session_max_processes,
+            session_cpu_rate_percent,
+            log_dir,
+            log_level,
+            log_max_size_mb,
+            log_max_files,
+            log_rotate_hours,
+            idle_shutdown_hours,
+            idle_shutdown_action,
+            detach_on_lock,
+            scrollback_spill_lines,
         }
     }
 
@@ -0,0 +1,298 @@
+use kdl::{KdlDocument, KdlNode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::{
+    data::LayoutInfo, envs::EnvironmentVariables, kdl_children_nodes_or_error,
+    kdl_get_child_entry_string_value, kdl_name, kdl_string_arguments,
+};
+
+use super::config::ConfigError;
+use super::layout::Layout;
+
+// nodes of a layout that do not describe its panes
+const LAYOUT_TEMPLATE_NODES: &[&str] = &[
+    "cwd",
+    "default_tab_template",
+    "new_tab_template",
+    "tab_template",
+    "pane_template",
+    "swap_tiled_layout",
+    "swap_floating_layout",
+];
+
+/// Named sessions from the `session_templates` block of the config, started (or attached to if
+/// they are already running) with `zellij --template <name>`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTemplates(BTreeMap<String, SessionTemplate>);
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    /// A layout name or path, resolved like the one given to `--layout`
+    pub layout: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub env: EnvironmentVariables,
+    /// Each started in its own pane, in a tab named after the template
+    pub commands: Vec<SessionTemplateCommand>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTemplateCommand {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl SessionTemplates {
+    pub fn get(&self, name: &str) -> Option<&SessionTemplate> {
+        self.0.get(name)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn merge(&self, other: SessionTemplates) -> Self {
+        let mut merged = self.clone();
+        merged.0.extend(other.0);
+        merged
+    }
+    pub fn from_kdl(kdl: &KdlNode) -> Result<Self, ConfigError> {
+        let mut templates = BTreeMap::new();
+        for template in kdl_children_nodes_or_error!(kdl, "empty session_templates block") {
+            templates.insert(
+                kdl_name!(template).to_owned(),
+                SessionTemplate::from_kdl(template)?,
+            );
+        }
+        Ok(SessionTemplates(templates))
+    }
+    pub fn to_kdl(&self) -> Option<KdlNode> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut session_templates = KdlNode::new("session_templates");
+        let mut templates = KdlDocument::new();
+        for (name, template) in &self.0 {
+            templates.nodes_mut().push(template.to_kdl(name));
+        }
+        session_templates.set_children(templates);
+        Some(session_templates)
+    }
+}
+
+impl SessionTemplate {
+    pub fn from_kdl(kdl: &KdlNode) -> Result<Self, ConfigError> {
+        let mut template = SessionTemplate {
+            layout: kdl_get_child_entry_string_value!(kdl, "layout").map(PathBuf::from),
+            cwd: kdl_get_child_entry_string_value!(kdl, "cwd").map(PathBuf::from),
+            ..Default::default()
+        };
+        for child in kdl_children_nodes_or_error!(kdl, "empty session template") {
+            match kdl_name!(child) {
+                "env" => {
+                    template.env = EnvironmentVariables::from_kdl(child)?;
+                },
+                "command" => {
+                    let mut arguments = kdl_string_arguments!(child).into_iter();
+                    let command = arguments.next().ok_or(ConfigError::new_kdl_error(
+                        "command needs the program to run".into(),
+                        child.span().offset(),
+                        child.span().len(),
+                    ))?;
+                    template.commands.push(SessionTemplateCommand {
+                        command: command.to_owned(),
+                        args: arguments.map(|a| a.to_owned()).collect(),
+                    });
+                },
+                "layout" | "cwd" => {},
+                other => {
+                    return Err(ConfigError::new_kdl_error(
+                        format!("Unknown session template setting: {}", other),
+                        child.span().offset(),
+                        child.span().len(),
+                    ));
+                },
+            }
+        }
+        Ok(template)
+    }
+    pub fn to_kdl(&self, name: &str) -> KdlNode {
+        let mut template = KdlNode::new(name);
+        let mut template_children = KdlDocument::new();
+        if let Some(layout) = &self.layout {
+            let mut layout_node = KdlNode::new("layout");
+            layout_node.push(layout.display().to_string());
+            template_children.nodes_mut().push(layout_node);
+        }
+        if let Some(cwd) = &self.cwd {
+            let mut cwd_node = KdlNode::new("cwd");
+            cwd_node.push(cwd.display().to_string());
+            template_children.nodes_mut().push(cwd_node);
+        }
+        if let Some(env) = self.env.to_kdl() {
+            template_children.nodes_mut().push(env);
+        }
+        for command in &self.commands {
+            let mut command_node = KdlNode::new("command");
+            command_node.push(command.command.clone());
+            for arg in &command.args {
+                command_node.push(arg.clone());
+            }
+            template_children.nodes_mut().push(command_node);
+        }
+        template.set_children(template_children);
+        template
+    }
+    /// The layout to start the session with: the template's (or the default) layout, with the
+    /// template's cwd and a tab running its commands added to it
+    pub fn layout_info(
+        &self,
+        name: &str,
+        base_layout_info: Option<LayoutInfo>,
+        layout_dir: &Option<PathBuf>,
+    ) -> Result<Option<LayoutInfo>, ConfigError> {
+        if self.commands.is_empty() && self.cwd.is_none() {
+            return Ok(base_layout_info);
+        }
+        let (raw_layout, raw_swap_layouts) = match base_layout_info {
+            Some(LayoutInfo::File(path, _)) => {
+                let (_, raw_layout, raw_swap_layouts) =
+                    Layout::stringified_from_dir(&PathBuf::from(path), layout_dir.as_ref())?;
+                (raw_layout, raw_swap_layouts)
+            },
+            Some(LayoutInfo::BuiltIn(layout_name)) => {
+                let (_, raw_layout, raw_swap_layouts) =
+                    Layout::stringified_from_default_assets(&PathBuf::from(layout_name))?;
+                (raw_layout, raw_swap_layouts)
+            },
+            Some(LayoutInfo::Stringified(raw_layout)) => (raw_layout, None),
+            Some(LayoutInfo::Url(_)) => {
+                // commands of layouts from the web start suspended, which would not hold for the
+                // layout we make of them
+                return Err(ConfigError::SessionTemplateError(format!(
+                    "Session template \"{}\" cannot add a cwd or commands to a layout from a url",
+                    name
+                )));
+            },
+            None => {
+                let (_, raw_layout, raw_swap_layouts) =
+                    Layout::stringified_from_path_or_default(None, layout_dir.clone())?;
+                (raw_layout, raw_swap_layouts)
+            },
+        };
+        self.add_to_layout(name, &raw_layout, raw_swap_layouts.map(|(_, s)| s))
+            .map(|layout| Some(LayoutInfo::Stringified(layout)))
+    }
+    fn add_to_layout(
+        &self,
+        name: &str,
+        raw_layout: &str,
+        raw_swap_layouts: Option<String>,
+    ) -> Result<String, ConfigError> {
+        let mut document: KdlDocument = raw_layout.parse()?;
+        let layout_node = document
+            .nodes_mut()
+            .iter_mut()
+            .find(|n| kdl_name!(n) == "layout")
+            .ok_or(ConfigError::SessionTemplateError(format!(
+                "The layout of session template \"{}\" has no layout node",
+                name
+            )))?;
+        let layout_children = layout_node
+            .children_mut()
+            .get_or_insert_with(KdlDocument::new);
+
+        // the swap layouts would otherwise be lost once the layout is no longer read from its file
+        if let Some(raw_swap_layouts) = raw_swap_layouts {
+            let swap_document: KdlDocument = raw_swap_layouts.parse()?;
+            layout_children
+                .nodes_mut()
+                .extend(swap_document.nodes().iter().cloned());
+        }
+
+        if let Some(cwd) = &self.cwd {
+            match layout_children.get_mut("cwd") {
+                Some(layout_cwd) => {
+                    let joined_cwd = kdl_string_arguments!(layout_cwd)
+                        .first()
+                        .map(|layout_cwd| cwd.join(layout_cwd))
+                        .unwrap_or_else(|| cwd.clone());
+                    layout_cwd.entries_mut().clear();
+                    layout_cwd.push(joined_cwd.display().to_string());
+                },
+                None => {
+                    let mut cwd_node = KdlNode::new("cwd");
+                    cwd_node.push(cwd.display().to_string());
+                    layout_children.nodes_mut().insert(0, cwd_node);
+                },
+            }
+        }
+
+        if !self.commands.is_empty() {
+            let has_tabs = layout_children
+                .nodes()
+                .iter()
+                .any(|n| kdl_name!(n) == "tab");
+            if !has_tabs {
+                wrap_panes_in_tab(layout_children);
+            }
+            layout_children.nodes_mut().push(self.commands_tab(name));
+        }
+
+        document.clear_fmt_recursive();
+        document.fmt();
+        Ok(document.to_string())
+    }
+    fn commands_tab(&self, name: &str) -> KdlNode {
+        let mut tab = KdlNode::new("tab");
+        tab.insert("name", name);
+        tab.insert("focus", true);
+        let mut panes = KdlDocument::new();
+        for command in &self.commands {
+            let mut pane = KdlNode::new("pane");
+            pane.insert("command", command.command.clone());
+            if !command.args.is_empty() {
+                let mut args = KdlNode::new("args");
+                for arg in &command.args {
+                    args.push(arg.clone());
+                }
+                let mut pane_children = KdlDocument::new();
+                pane_children.nodes_mut().push(args);
+                pane.set_children(pane_children);
+            }
+            panes.nodes_mut().push(pane);
+        }
+        tab.set_children(panes);
+        tab
+    }
+}
+
+// a layout without tabs describes its panes at the top level, so that they can sit next to the
+// tab we add, we make them the default tab template - with its first bare pane (the one the
+// built-in layouts leave for the user) holding the tab's panes - or their own tab otherwise
+fn wrap_panes_in_tab(layout_children: &mut KdlDocument) {
+    let (template_nodes, mut pane_nodes): (Vec<KdlNode>, Vec<KdlNode>) = layout_children
+        .nodes()
+        .iter()
+        .cloned()
+        .partition(|n| LAYOUT_TEMPLATE_NODES.contains(&kdl_name!(n)));
+    let has_default_tab_template = template_nodes
+        .iter()
+        .any(|n| kdl_name!(n) == "default_tab_template");
+    let bare_pane = pane_nodes
+        .iter_mut()
+        .find(|n| kdl_name!(n) == "pane" && n.entries().is_empty() && n.children().is_none());
+    let mut wrapper = match bare_pane {
+        Some(bare_pane) if !has_default_tab_template => {
+            *bare_pane = KdlNode::new("children");
+            KdlNode::new("default_tab_template")
+        },
+        _ => KdlNode::new("tab"),
+    };
+    let mut wrapped_panes = KdlDocument::new();
+    wrapped_panes.nodes_mut().extend(pane_nodes);
+    wrapper.set_children(wrapped_panes);
+    let layout_nodes = layout_children.nodes_mut();
+    layout_nodes.clear();
+    layout_nodes.extend(template_nodes);
+    layout_nodes.push(wrapper);
+}
@@ -1096,6 +1096,28 @@ fn layout_with_pane_excluded_from_sync() {
     assert_snapshot!(format!("{:#?}", layout));
 }
 
+#[test]
+fn layout_with_pane_with_suppressed_bell() {
+    let kdl_layout = r#"
+        layout {
+            pane suppress_bell=true
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    assert_snapshot!(format!("{:#?}", layout));
+}
+
+#[test]
+fn layout_with_pane_with_encoding() {
+    let kdl_layout = r#"
+        layout {
+            pane encoding="cp437"
+        }
+    "#;
+    let layout = Layout::from_kdl(kdl_layout, Some("layout_file_name".into()), None, None).unwrap();
+    assert_snapshot!(format!("{:#?}", layout));
+}
+
 #[test]
 fn cannot_define_tab_template_name_with_space() {
     let kdl_layout = r#"
@@ -53,23 +53,218 @@ pub fn path_to_ipc_name(path: &Path) -> io::Result<Name<'_>> {
 /// separate pipes: one for client→server (main) and one for server→client (reverse).
 #[cfg(windows)]
 pub fn path_to_ipc_name_reverse(path: &Path) -> io::Result<Name<'static>> {
-    path_to_windows_pipe_name(path, "-srv")
+    path_to_windows_pipe_name(path, REVERSE_PIPE_SUFFIX)
 }
 
+#[cfg(windows)]
+const REVERSE_PIPE_SUFFIX: &str = "-srv";
+
 // Security note: pipe names derived from path components are predictable, but this is
-// mitigated by accept_secure_pipe_connection() which creates pipes with:
-//   - ACL restricting access to the current user (SDDL `D:P(A;;GA;;;{SID})`)
-//   - nMaxInstances = 1 (prevents pipe squatting — attacker can't create a second instance)
+// mitigated for the web server control pipe, which is created with:
+//   - ACL restricting access to the current user (CurrentUserSecurityAttributes)
+//   - FILE_FLAG_FIRST_PIPE_INSTANCE on the first instance (prevents pipe squatting — creating
+//     it fails if an attacker already created a pipe with the same name)
+// The pipes of sessions get an ACL restricting access to the current user too, plus whoever
+// the `pipe_access` option deliberately shares them with (session_pipe_security_descriptor).
 // Adding randomness would require a shared secret mechanism between client and server,
 // adding complexity for marginal benefit given the above protections.
 #[cfg(windows)]
 fn path_to_windows_pipe_name(path: &Path, suffix: &str) -> io::Result<Name<'static>> {
     use interprocess::local_socket::GenericNamespaced;
+    format!("{}{}", pipe_name_serving(path), suffix).to_ns_name::<GenericNamespaced>()
+}
+
+/// The path in the `\\.\pipe\` namespace of the named pipe [`path_to_ipc_name`] names for
+/// `path`, for creating the pipe with APIs that want its full path.
+#[cfg(windows)]
+pub fn windows_pipe_path(path: &Path) -> String {
+    format!("\\\\.\\pipe\\{}", pipe_name_serving(path))
+}
+
+// Pipes keep the name they were created with, so when a session is renamed (or its name is still
+// held by the pipes of a session renamed away from it) a marker file at its path in the socket
+// dir records the pipe serving it. Marker files without such a record (eg. those of web servers)
+// are not aliases.
+
+/// The name of the main pipe serving `path`: the one recorded in the marker file at `path`, if
+/// there is one, or the one derived from `path` otherwise
+#[cfg(windows)]
+fn pipe_name_serving(path: &Path) -> String {
+    pipe_alias(path).unwrap_or_else(|| windows_pipe_name(path, ""))
+}
+
+#[cfg(windows)]
+fn pipe_alias(path: &Path) -> Option<String> {
+    let pipe_name = std::fs::read_to_string(path).ok()?;
+    let pipe_name = pipe_name.trim();
+    let dir = path.parent()?;
+    pipe_name
+        .starts_with(&windows_pipe_name_prefix(dir))
+        .then(|| pipe_name.to_owned())
+}
+
+/// Makes the pipes serving `old_path` serve `new_path` instead, for a session being renamed: the
+/// pipes cannot be renamed, so the marker file at `new_path` records them in their stead.
+#[cfg(windows)]
+pub fn move_pipe_alias(old_path: &Path, new_path: &Path) -> io::Result<()> {
+    std::fs::write(new_path, pipe_name_serving(old_path))?;
+    match std::fs::remove_file(old_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Makes [`path_to_ipc_name`] name pipes a new server can create at `path`: a marker left at
+/// `path` by a session that was renamed to its name and died is removed, and if a session renamed
+/// away from its name still holds the pipes derived from it, `path` is given pipes of its own.
+#[cfg(windows)]
+pub fn claim_pipe_name(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {},
+    }
+    let pipe_names = list_named_pipes()?;
+    let is_taken = |pipe_name: &String| {
+        pipe_names.contains(pipe_name)
+            || pipe_names.contains(&format!("{}{}", pipe_name, REVERSE_PIPE_SUFFIX))
+    };
+    let derived_pipe_name = windows_pipe_name(path, "");
+    if !is_taken(&derived_pipe_name) {
+        return Ok(());
+    }
+    let free_pipe_name = (1..)
+        .map(|n| format!("{}-{}", derived_pipe_name, n))
+        .find(|pipe_name| !is_taken(pipe_name))
+        .unwrap_or(derived_pipe_name);
+    std::fs::write(path, free_pipe_name)
+}
+
+/// The prefix [`path_to_ipc_name`] gives the names of the pipes of all paths directly in `dir`,
+/// for finding them among [`list_named_pipes`].
+#[cfg(windows)]
+pub fn windows_pipe_name_prefix(dir: &Path) -> String {
+    let dir_name = dir.file_name().and_then(|d| d.to_str()).unwrap_or("");
+    format!("zellij-{}-", dir_name)
+}
+
+/// The names of the named pipes that currently exist, listed from the `\\.\pipe\` namespace.
+///
+/// Unlike files marking a pipe, this never lists the pipe of a process that has died.
+#[cfg(windows)]
+pub fn list_named_pipes() -> io::Result<Vec<String>> {
+    use windows_sys::Win32::Foundation::{
+        ERROR_FILE_NOT_FOUND, ERROR_NO_MORE_FILES, INVALID_HANDLE_VALUE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{
+        FindClose, FindFirstFileW, FindNextFileW, WIN32_FIND_DATAW,
+    };
+
+    let pattern: Vec<u16> = "\\\\.\\pipe\\*"
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut find_data: WIN32_FIND_DATAW = unsafe { std::mem::zeroed() };
+    let handle = unsafe { FindFirstFileW(pattern.as_ptr(), &mut find_data) };
+    if handle == INVALID_HANDLE_VALUE {
+        let error = io::Error::last_os_error();
+        return if error.raw_os_error() == Some(ERROR_FILE_NOT_FOUND as i32) {
+            Ok(vec![])
+        } else {
+            Err(error)
+        };
+    }
+    let mut pipe_names = vec![];
+    loop {
+        let name_len = find_data
+            .cFileName
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(find_data.cFileName.len());
+        pipe_names.push(String::from_utf16_lossy(&find_data.cFileName[..name_len]));
+        if unsafe { FindNextFileW(handle, &mut find_data) } == 0 {
+            let error = io::Error::last_os_error();
+            unsafe { FindClose(handle) };
+            return if error.raw_os_error() == Some(ERROR_NO_MORE_FILES as i32) {
+                Ok(pipe_names)
+            } else {
+                Err(error)
+            };
+        }
+    }
+}
+
+/// The file names of the paths directly in `dir` whose named pipes (as named by
+/// [`path_to_ipc_name`]) currently exist, whether or not anything exists at the paths themselves.
+/// Reverse pipes are not listed separately.
+#[cfg(windows)]
+pub fn list_pipes_in(dir: &Path) -> io::Result<Vec<String>> {
+    let pipe_name_prefix = windows_pipe_name_prefix(dir);
+    let names: std::collections::BTreeSet<String> = list_named_pipes()?
+        .iter()
+        .filter_map(|pipe_name| pipe_name.strip_prefix(&pipe_name_prefix))
+        .map(|name| name.to_owned())
+        .collect();
+    let aliases = list_pipe_aliases_in(dir);
+    Ok(names
+        .iter()
+        .filter(|name| {
+            !name
+                .strip_suffix(REVERSE_PIPE_SUFFIX)
+                .is_some_and(|main_pipe_name| names.contains(main_pipe_name))
+        })
+        .map(|name| {
+            aliases
+                .get(&format!("{}{}", pipe_name_prefix, name))
+                .cloned()
+                .unwrap_or_else(|| name.clone())
+        })
+        .collect())
+}
+
+/// The file names of the marker files directly in `dir` recording the pipes serving them, by the
+/// names of those pipes
+#[cfg(windows)]
+fn list_pipe_aliases_in(dir: &Path) -> std::collections::HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Default::default();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pipe_name = pipe_alias(&entry.path())?;
+            let name = entry.file_name().to_str()?.to_owned();
+            Some((pipe_name, name))
+        })
+        .collect()
+}
+
+/// The names of the reverse pipes of the paths directly in `dir` whose main pipes are gone. They
+/// are held open by processes that no longer serve the path, and go away with them.
+#[cfg(windows)]
+pub fn list_orphaned_reverse_pipes_in(dir: &Path) -> io::Result<Vec<String>> {
+    let pipe_name_prefix = windows_pipe_name_prefix(dir);
+    let pipe_names: Vec<String> = list_named_pipes()?;
+    Ok(pipe_names
+        .iter()
+        .filter(|pipe_name| {
+            pipe_name
+                .strip_prefix(&pipe_name_prefix)
+                .and_then(|name| name.strip_suffix(REVERSE_PIPE_SUFFIX))
+                .is_some_and(|main_name| {
+                    !pipe_names.contains(&format!("{}{}", pipe_name_prefix, main_name))
+                })
+        })
+        .cloned()
+        .collect())
+}
+
+#[cfg(windows)]
+fn windows_pipe_name(path: &Path, suffix: &str) -> String {
     let components: Vec<&str> = path
         .components()
         .filter_map(|c| c.as_os_str().to_str())
         .collect();
-    let name = if components.len() >= 2 {
+    if components.len() >= 2 {
         let len = components.len();
         format!(
             "zellij-{}-{}{}",
@@ -85,8 +280,7 @@ fn path_to_windows_pipe_name(path: &Path, suffix: &str) -> io::Result<Name<'stat
                 .replace(['\\', '/', ':'], "-"),
             suffix
         )
-    };
-    name.to_ns_name::<GenericNamespaced>()
+    }
 }
 
 type SessionId = u64;
@@ -124,6 +318,7 @@ pub enum ClientType {
 pub struct ClientAttributes {
     pub size: Size,
     pub style: Style,
+    pub theme: Option<String>, // the theme the client attached with for itself, if any
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -211,6 +406,31 @@ pub enum ClientToServerMsg {
     FailedToStartWebServer {
         error: String,
     },
+    ClipboardContent {
+        terminal_id: u32,
+        content: Option<String>, // None if the user or the platform declined to share it
+    },
+    Heartbeat {
+        id: u64, // echoed back by the server in HeartbeatAck
+    },
+    Redraw,
+    TerminalFocus {
+        focused: bool, // whether the terminal of the client gained or lost focus
+    },
+    UploadFileChunk {
+        file_name: String, // created in the cwd of the focused pane of the client
+        bytes: Vec<u8>,
+        is_first: bool,
+        is_last: bool,
+    },
+    DownloadFile {
+        file: DownloadableFile,
+    },
+    ConnStatusDetailed, // like ConnStatus, answered with the state of the session for listing it
+    DetachOtherClients, // sent before attaching with --force, answered once the others are gone
+    Authenticate {
+        session_secret: String, // lets a connection that does not attach use a locked session
+    },
 }
 
 // Types of messages sent from the server to the client
@@ -246,6 +466,45 @@ pub enum ServerToClientMsg {
         name: String,
     },
     ConfigFileUpdated,
+    Bell {
+        pane_name: String,
+    },
+    SetClipboard {
+        content: String,
+    },
+    QueryClipboard {
+        terminal_id: u32,
+    },
+    Notify {
+        title: String,
+        body: String,
+    },
+    HeartbeatAck {
+        id: u64,
+    },
+    OpenLink {
+        uri: String,
+    },
+    FileChunk {
+        file_name: String,
+        bytes: Vec<u8>,
+        is_last: bool,
+    },
+    ShutdownPending, // the session is being killed, its panes are closing
+    ConnStatusDetailed {
+        created_at: u64, // seconds since the unix epoch
+        connected_clients: u32,
+        tab_count: u32,
+        pane_count: u32,
+        web_client_connected: bool,
+    },
+}
+
+/// The files of a session a client can ask to download with [`ClientToServerMsg::DownloadFile`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DownloadableFile {
+    ScreenDump, // the full scrollback of the focused pane of the client
+    Log,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -257,6 +516,7 @@ pub enum ExitReason {
     Disconnect,
     WebClientsForbidden,
     KickedByHost,
+    SessionLocked,
     CustomExitStatus(i32),
     Error(String),
 }
@@ -303,6 +563,10 @@ There are a few things you can try now:
                 )
             },
             Self::KickedByHost => write!(f, "Disconnected by host"),
+            Self::SessionLocked => write!(
+                f,
+                "This session is locked - attach to it with its secret (--session-secret)"
+            ),
             Self::CustomExitStatus(exit_status) => write!(f, "Exit {}", exit_status),
             Self::Error(e) => write!(f, "Error occurred in server:\n{}", e),
         }
@@ -358,6 +622,7 @@ impl<T: Serialize> IpcSenderWithContext<T> {
 /// Receives messages on a stream socket, along with an [`ErrorContext`].
 pub struct IpcReceiverWithContext<T> {
     receiver: io::BufReader<Box<dyn IpcStream>>,
+    disconnected: bool,
     _phantom: PhantomData<T>,
 }
 
@@ -369,6 +634,7 @@ where
     pub fn new(receiver: LocalSocketStream) -> Self {
         Self {
             receiver: io::BufReader::new(Box::new(receiver)),
+            disconnected: false,
             _phantom: PhantomData,
         }
     }
@@ -376,6 +642,7 @@ where
     fn from_boxed(receiver: Box<dyn IpcStream>) -> Self {
         Self {
             receiver: io::BufReader::new(receiver),
+            disconnected: false,
             _phantom: PhantomData,
         }
     }
@@ -389,7 +656,12 @@ where
                     None
                 },
             },
-            Err(_e) => None,
+            Err(e) => {
+                if is_disconnection(&e) {
+                    self.disconnected = true;
+                }
+                None
+            },
         }
     }
 
@@ -402,10 +674,20 @@ where
                     None
                 },
             },
-            Err(_e) => None,
+            Err(e) => {
+                if is_disconnection(&e) {
+                    self.disconnected = true;
+                }
+                None
+            },
         }
     }
 
+    /// Whether the other end went away, as opposed to having sent something we could not read.
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
+    }
+
     /// Returns an [`IpcSenderWithContext`] with the same socket as this receiver.
     pub fn get_sender<F: Serialize>(&self) -> IpcSenderWithContext<F> {
         let socket = self.receiver.get_ref().try_clone_stream().unwrap();
@@ -417,6 +699,21 @@ where
 // than this to prevent a malicious or corrupted peer from causing OOM.
 pub const MAX_IPC_MSG_SIZE: usize = 64 * 1024 * 1024;
 
+// Files are sent over IPC in chunks of at most this many bytes, well under MAX_IPC_MSG_SIZE
+pub const FILE_CHUNK_SIZE: usize = 1024 * 1024;
+
+fn is_disconnection(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<io::Error>().map(|e| e.kind()),
+        Some(
+            io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        )
+    )
+}
+
 // Protobuf wire format utilities
 fn read_protobuf_message<T: Message + Default>(reader: &mut impl Read) -> Result<T> {
     // Read length-prefixed protobuf message
@@ -502,50 +799,26 @@ pub fn recv_protobuf_server_to_client(
     }
 }
 
-/// Creates a named pipe with a security descriptor restricting access to the current user,
-/// waits for a client connection, and returns the connected pipe as a `std::fs::File`.
+/// The SDDL of a security descriptor whose protected DACL grants only the current user access:
+/// `D:P(A;;GA;;;{SID})`, granting the SID of the user this process runs as Generic All.
+#[cfg(windows)]
+pub fn current_user_only_sddl() -> io::Result<String> {
+    shared_pipe_sddl(&[])
+}
+
+/// The SDDL of a security descriptor whose protected DACL grants the current user, and each of
+/// `trustees` on top, access: `D:P(A;;GA;;;{SID})(A;;GA;;;{trustee SID})...`.
 ///
-/// The pipe is created with `nMaxInstances = 1` to prevent pipe squatting — if zellij creates
-/// the pipe first, an attacker cannot create another instance with the same name.
-/// The ACL uses SDDL `D:P(A;;GA;;;{SID})` granting only the current user Generic All access.
+/// Trustees are SIDs (eg. `S-1-5-32-544`), SDDL SID aliases (eg. `BA`) or account names (eg.
+/// `SERVER\alice`), which are looked up.
 #[cfg(windows)]
-pub fn accept_secure_pipe_connection(path: &Path) -> io::Result<std::fs::File> {
-    use std::os::windows::io::FromRawHandle;
-    use windows_sys::Win32::Foundation::{
-        CloseHandle, HANDLE, INVALID_HANDLE_VALUE, LocalFree,
-    };
-    use windows_sys::Win32::Security::{
-        GetTokenInformation, PSECURITY_DESCRIPTOR, TokenUser, SECURITY_ATTRIBUTES,
-        TOKEN_QUERY, TOKEN_USER,
-    };
-    use windows_sys::Win32::Security::Authorization::{
-        ConvertSidToStringSidW, ConvertStringSecurityDescriptorToSecurityDescriptorW,
-    };
-    use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
-    use windows_sys::Win32::System::Pipes::{ConnectNamedPipe, CreateNamedPipeW};
+pub fn shared_pipe_sddl(trustees: &[String]) -> io::Result<String> {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{GetTokenInformation, TokenUser, TOKEN_QUERY, TOKEN_USER};
     use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
 
-    // Compute pipe name (same logic as path_to_windows_pipe_name)
-    let components: Vec<&str> = path
-        .components()
-        .filter_map(|c| c.as_os_str().to_str())
-        .collect();
-    let name = if components.len() >= 2 {
-        let len = components.len();
-        format!("zellij-{}-{}", components[len - 2], components[len - 1])
-    } else {
-        format!(
-            "zellij-{}",
-            path.display()
-                .to_string()
-                .replace(['\\', '/', ':'], "-")
-        )
-    };
-    let pipe_path = format!("\\\\.\\pipe\\{}", name);
-    let pipe_path_wide: Vec<u16> = pipe_path.encode_utf16().chain(std::iter::once(0)).collect();
-
-    unsafe {
-        // 1. Get current user SID via process token
+    let user_sid = unsafe {
+        // Get current user SID via process token
         let mut token: HANDLE = std::ptr::null_mut();
         if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
             return Err(io::Error::last_os_error());
@@ -570,71 +843,187 @@ pub fn accept_secure_pipe_connection(path: &Path) -> io::Result<std::fs::File> {
         CloseHandle(token);
 
         let token_user = &*(token_buf.as_ptr() as *const TOKEN_USER);
-        let mut sid_wide: *mut u16 = std::ptr::null_mut();
-        if ConvertSidToStringSidW(token_user.User.Sid, &mut sid_wide) == 0 {
-            return Err(io::Error::last_os_error());
-        }
+        sid_to_string(token_user.User.Sid)?
+    };
 
-        // Convert wide SID string to Rust String
-        let sid_str = {
-            let mut len = 0;
-            while *sid_wide.add(len) != 0 {
-                len += 1;
-            }
-            let slice = std::slice::from_raw_parts(sid_wide, len);
-            String::from_utf16_lossy(slice)
-        };
-        LocalFree(sid_wide as _);
+    // Protected DACL, the current user and every trustee get Generic All
+    let mut sddl = format!("D:P(A;;GA;;;{})", user_sid);
+    for trustee in trustees {
+        sddl.push_str(&format!("(A;;GA;;;{})", trustee_sid(trustee)?));
+    }
+    Ok(sddl)
+}
 
-        // 2. Build SDDL: Protected DACL, only current user gets Generic All
-        let sddl = format!("D:P(A;;GA;;;{})", sid_str);
-        let sddl_wide: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+/// The string SID of `trustee` as it goes in an ACE: SIDs and SDDL SID aliases as they are,
+/// account names looked up
+#[cfg(windows)]
+fn trustee_sid(trustee: &str) -> io::Result<String> {
+    use windows_sys::Win32::Security::{LookupAccountNameW, SID_NAME_USE};
 
-        let mut sd: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
-        if ConvertStringSecurityDescriptorToSecurityDescriptorW(
-            sddl_wide.as_ptr(),
-            1, // SDDL_REVISION_1
-            &mut sd,
+    if trustee.contains(['(', ')', ';', ':']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid account: {}", trustee),
+        ));
+    }
+    let is_sid = trustee.starts_with("S-1-");
+    let is_sid_alias = trustee.len() == 2 && trustee.chars().all(|c| c.is_ascii_uppercase());
+    if is_sid || is_sid_alias {
+        return Ok(trustee.to_owned());
+    }
+
+    let account_name: Vec<u16> = trustee.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut sid_len: u32 = 0;
+    let mut domain_len: u32 = 0;
+    let mut sid_name_use: SID_NAME_USE = 0;
+    unsafe {
+        // the first call only finds out how large the SID and the domain name are
+        LookupAccountNameW(
+            std::ptr::null(),
+            account_name.as_ptr(),
             std::ptr::null_mut(),
+            &mut sid_len,
+            std::ptr::null_mut(),
+            &mut domain_len,
+            &mut sid_name_use,
+        );
+        if sid_len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No such account: {}", trustee),
+            ));
+        }
+        let mut sid = vec![0u8; sid_len as usize];
+        let mut domain = vec![0u16; domain_len as usize];
+        if LookupAccountNameW(
+            std::ptr::null(),
+            account_name.as_ptr(),
+            sid.as_mut_ptr() as _,
+            &mut sid_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            &mut sid_name_use,
         ) == 0
         {
             return Err(io::Error::last_os_error());
         }
+        sid_to_string(sid.as_mut_ptr() as _)
+    }
+}
 
-        // 3. Create named pipe with security attributes and nMaxInstances=1
-        let sa = SECURITY_ATTRIBUTES {
-            nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
-            lpSecurityDescriptor: sd,
-            bInheritHandle: 0,
-        };
+/// The string form (eg. `S-1-5-32-544`) of the SID `sid` points to
+#[cfg(windows)]
+unsafe fn sid_to_string(sid: windows_sys::Win32::Security::PSID) -> io::Result<String> {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::ConvertSidToStringSidW;
 
-        let handle = CreateNamedPipeW(
-            pipe_path_wide.as_ptr(),
-            PIPE_ACCESS_DUPLEX,
-            0, // PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT (all zero)
-            1,    // nMaxInstances — prevents pipe squatting
-            4096, // output buffer size
-            4096, // input buffer size
-            0,    // default timeout
-            &sa,
-        );
+    let mut sid_wide: *mut u16 = std::ptr::null_mut();
+    if ConvertSidToStringSidW(sid, &mut sid_wide) == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Convert wide SID string to Rust String
+    let sid_str = {
+        let mut len = 0;
+        while *sid_wide.add(len) != 0 {
+            len += 1;
+        }
+        let slice = std::slice::from_raw_parts(sid_wide, len);
+        String::from_utf16_lossy(slice)
+    };
+    LocalFree(sid_wide as _);
+    Ok(sid_str)
+}
 
+/// The security descriptor of the named pipes of a session, granting the current user and
+/// `trustees` access as described by [`shared_pipe_sddl`].
+#[cfg(windows)]
+pub fn session_pipe_security_descriptor(
+    trustees: &[String],
+) -> io::Result<interprocess::os::windows::security_descriptor::SecurityDescriptor> {
+    use interprocess::os::windows::security_descriptor::{
+        AsSecurityDescriptorExt, BorrowedSecurityDescriptor,
+    };
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Authorization::ConvertStringSecurityDescriptorToSecurityDescriptorW;
+    use windows_sys::Win32::Security::PSECURITY_DESCRIPTOR;
+
+    let sddl = shared_pipe_sddl(trustees)?;
+    let sddl_wide: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut sd: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+    if unsafe {
+        ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl_wide.as_ptr(),
+            1, // SDDL_REVISION_1
+            &mut sd,
+            std::ptr::null_mut(),
+        )
+    } == 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+    // the converted descriptor is self-relative and allocated by the system, the listener wants
+    // an owned copy
+    let security_descriptor = unsafe { BorrowedSecurityDescriptor::from_ptr(sd) }.to_owned_sd();
+    unsafe {
         LocalFree(sd as _);
+    }
+    security_descriptor
+}
+
+/// `SECURITY_ATTRIBUTES` whose security descriptor grants only the current user access, as
+/// described by [`current_user_only_sddl`], for creating the named pipes zellij listens on.
+#[cfg(windows)]
+pub struct CurrentUserSecurityAttributes {
+    attributes: windows_sys::Win32::Security::SECURITY_ATTRIBUTES,
+}
+
+#[cfg(windows)]
+impl CurrentUserSecurityAttributes {
+    pub fn new() -> io::Result<Self> {
+        Self::from_sddl(&current_user_only_sddl()?)
+    }
+    /// For creating directories: the access granted is also inherited by everything created in
+    /// them
+    pub fn for_directory() -> io::Result<Self> {
+        Self::from_sddl(&current_user_only_sddl()?.replace("(A;;", "(A;OICI;"))
+    }
+    fn from_sddl(sddl: &str) -> io::Result<Self> {
+        use windows_sys::Win32::Security::Authorization::ConvertStringSecurityDescriptorToSecurityDescriptorW;
+        use windows_sys::Win32::Security::{PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES};
 
-        if handle == INVALID_HANDLE_VALUE {
+        let sddl_wide: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut sd: PSECURITY_DESCRIPTOR = std::ptr::null_mut();
+        if unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl_wide.as_ptr(),
+                1, // SDDL_REVISION_1
+                &mut sd,
+                std::ptr::null_mut(),
+            )
+        } == 0
+        {
             return Err(io::Error::last_os_error());
         }
+        Ok(CurrentUserSecurityAttributes {
+            attributes: SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: sd,
+                bInheritHandle: 0,
+            },
+        })
+    }
+    /// A pointer to the attributes, valid for as long as `self` is alive
+    pub fn as_mut_ptr(&mut self) -> *mut std::ffi::c_void {
+        &mut self.attributes as *mut _ as *mut std::ffi::c_void
+    }
+}
 
-        // 4. Wait for a client to connect
-        if ConnectNamedPipe(handle, std::ptr::null_mut()) == 0 {
-            let err = io::Error::last_os_error();
-            // ERROR_PIPE_CONNECTED (535) means client connected before ConnectNamedPipe
-            if err.raw_os_error() != Some(535) {
-                CloseHandle(handle);
-                return Err(err);
-            }
+#[cfg(windows)]
+impl Drop for CurrentUserSecurityAttributes {
+    fn drop(&mut self) {
+        unsafe {
+            windows_sys::Win32::Foundation::LocalFree(self.attributes.lpSecurityDescriptor as _);
         }
-
-        Ok(std::fs::File::from_raw_handle(handle as _))
     }
 }
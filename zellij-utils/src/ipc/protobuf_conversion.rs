@@ -1,22 +1,25 @@
 use crate::{
     client_server_contract::client_server_contract::{
         client_to_server_msg, server_to_client_msg, ActionMsg, AttachClientMsg,
-        AttachWatcherClientMsg, BackgroundColorMsg, CliPipeOutputMsg, ClientExitedMsg,
-        ClientToServerMsg as ProtoClientToServerMsg, ColorRegistersMsg, ConfigFileUpdatedMsg,
-        ConnStatusMsg, ConnectedMsg, DetachSessionMsg, ExitMsg, ExitReason as ProtoExitReason,
-        FailedToStartWebServerMsg, FirstClientConnectedMsg, ForegroundColorMsg,
-        InputMode as ProtoInputMode, KeyMsg, KillSessionMsg, LayoutMetadata as ProtoLayoutMetadata,
-        LogErrorMsg, LogMsg, PaneMetadata as ProtoPaneMetadata, QueryTerminalSizeMsg,
-        RenamedSessionMsg, RenderMsg, ServerToClientMsg as ProtoServerToClientMsg,
-        StartWebServerMsg, SwitchSessionMsg, TabMetadata as ProtoTabMetadata,
-        TerminalPixelDimensionsMsg, TerminalResizeMsg, UnblockCliPipeInputMsg,
-        UnblockInputThreadMsg, WebServerStartedMsg,
+        AttachWatcherClientMsg, AuthenticateMsg, BackgroundColorMsg, BellMsg, CliPipeOutputMsg,
+        ClientExitedMsg, ClientToServerMsg as ProtoClientToServerMsg, ClipboardContentMsg,
+        ColorRegistersMsg, ConfigFileUpdatedMsg, ConnStatusDetailedMsg, ConnStatusDetailedReplyMsg,
+        ConnStatusMsg, ConnectedMsg, DetachOtherClientsMsg, DetachSessionMsg, DownloadFileMsg,
+        DownloadableFile as ProtoDownloadableFile, ExitMsg, ExitReason as ProtoExitReason,
+        FailedToStartWebServerMsg, FileChunkMsg, FirstClientConnectedMsg, ForegroundColorMsg,
+        HeartbeatAckMsg, HeartbeatMsg, InputMode as ProtoInputMode, KeyMsg, KillSessionMsg,
+        LayoutMetadata as ProtoLayoutMetadata, LogErrorMsg, LogMsg, NotifyMsg, OpenLinkMsg,
+        PaneMetadata as ProtoPaneMetadata, QueryClipboardMsg, QueryTerminalSizeMsg, RedrawMsg,
+        RenamedSessionMsg, RenderMsg, ServerToClientMsg as ProtoServerToClientMsg, SetClipboardMsg,
+        ShutdownPendingMsg, StartWebServerMsg, SwitchSessionMsg, TabMetadata as ProtoTabMetadata,
+        TerminalFocusMsg, TerminalPixelDimensionsMsg, TerminalResizeMsg, UnblockCliPipeInputMsg,
+        UnblockInputThreadMsg, UploadFileChunkMsg, WebServerStartedMsg,
     },
     data::InputMode,
     errors::prelude::*,
     ipc::{
-        ClientToServerMsg, ColorRegister, ExitReason, PaneReference, PixelDimensions,
-        ServerToClientMsg,
+        ClientToServerMsg, ColorRegister, DownloadableFile, ExitReason, PaneReference,
+        PixelDimensions, ServerToClientMsg,
     },
 };
 use std::collections::BTreeMap;
@@ -114,6 +117,45 @@ impl From<ClientToServerMsg> for ProtoClientToServerMsg {
                     error,
                 })
             },
+            ClientToServerMsg::ClipboardContent {
+                terminal_id,
+                content,
+            } => client_to_server_msg::Message::ClipboardContent(ClipboardContentMsg {
+                terminal_id,
+                content,
+            }),
+            ClientToServerMsg::Heartbeat { id } => {
+                client_to_server_msg::Message::Heartbeat(HeartbeatMsg { id })
+            },
+            ClientToServerMsg::Redraw => client_to_server_msg::Message::Redraw(RedrawMsg {}),
+            ClientToServerMsg::TerminalFocus { focused } => {
+                client_to_server_msg::Message::TerminalFocus(TerminalFocusMsg { focused })
+            },
+            ClientToServerMsg::UploadFileChunk {
+                file_name,
+                bytes,
+                is_first,
+                is_last,
+            } => client_to_server_msg::Message::UploadFileChunk(UploadFileChunkMsg {
+                file_name,
+                bytes,
+                is_first,
+                is_last,
+            }),
+            ClientToServerMsg::DownloadFile { file } => {
+                client_to_server_msg::Message::DownloadFile(DownloadFileMsg {
+                    file: ProtoDownloadableFile::from(file) as i32,
+                })
+            },
+            ClientToServerMsg::ConnStatusDetailed => {
+                client_to_server_msg::Message::ConnStatusDetailed(ConnStatusDetailedMsg {})
+            },
+            ClientToServerMsg::DetachOtherClients => {
+                client_to_server_msg::Message::DetachOtherClients(DetachOtherClientsMsg {})
+            },
+            ClientToServerMsg::Authenticate { session_secret } => {
+                client_to_server_msg::Message::Authenticate(AuthenticateMsg { session_secret })
+            },
         };
 
         ProtoClientToServerMsg {
@@ -228,6 +270,47 @@ impl TryFrom<ProtoClientToServerMsg> for ClientToServerMsg {
                     error: failed.error,
                 })
             },
+            Some(client_to_server_msg::Message::ClipboardContent(clipboard_content)) => {
+                Ok(ClientToServerMsg::ClipboardContent {
+                    terminal_id: clipboard_content.terminal_id,
+                    content: clipboard_content.content,
+                })
+            },
+            Some(client_to_server_msg::Message::Heartbeat(heartbeat)) => {
+                Ok(ClientToServerMsg::Heartbeat { id: heartbeat.id })
+            },
+            Some(client_to_server_msg::Message::Redraw(_)) => Ok(ClientToServerMsg::Redraw),
+            Some(client_to_server_msg::Message::TerminalFocus(terminal_focus)) => {
+                Ok(ClientToServerMsg::TerminalFocus {
+                    focused: terminal_focus.focused,
+                })
+            },
+            Some(client_to_server_msg::Message::UploadFileChunk(upload_file_chunk)) => {
+                Ok(ClientToServerMsg::UploadFileChunk {
+                    file_name: upload_file_chunk.file_name,
+                    bytes: upload_file_chunk.bytes,
+                    is_first: upload_file_chunk.is_first,
+                    is_last: upload_file_chunk.is_last,
+                })
+            },
+            Some(client_to_server_msg::Message::DownloadFile(download_file)) => {
+                let proto_file = ProtoDownloadableFile::from_i32(download_file.file)
+                    .ok_or_else(|| anyhow!("Invalid downloadable file"))?;
+                Ok(ClientToServerMsg::DownloadFile {
+                    file: proto_file.try_into()?,
+                })
+            },
+            Some(client_to_server_msg::Message::ConnStatusDetailed(_)) => {
+                Ok(ClientToServerMsg::ConnStatusDetailed)
+            },
+            Some(client_to_server_msg::Message::DetachOtherClients(_)) => {
+                Ok(ClientToServerMsg::DetachOtherClients)
+            },
+            Some(client_to_server_msg::Message::Authenticate(authenticate)) => {
+                Ok(ClientToServerMsg::Authenticate {
+                    session_secret: authenticate.session_secret,
+                })
+            },
             None => Err(anyhow!("Empty ClientToServerMsg message")),
         }
     }
@@ -290,6 +373,49 @@ impl From<ServerToClientMsg> for ProtoServerToClientMsg {
             ServerToClientMsg::ConfigFileUpdated => {
                 server_to_client_msg::Message::ConfigFileUpdated(ConfigFileUpdatedMsg {})
             },
+            ServerToClientMsg::Bell { pane_name } => {
+                server_to_client_msg::Message::Bell(BellMsg { pane_name })
+            },
+            ServerToClientMsg::SetClipboard { content } => {
+                server_to_client_msg::Message::SetClipboard(SetClipboardMsg { content })
+            },
+            ServerToClientMsg::QueryClipboard { terminal_id } => {
+                server_to_client_msg::Message::QueryClipboard(QueryClipboardMsg { terminal_id })
+            },
+            ServerToClientMsg::Notify { title, body } => {
+                server_to_client_msg::Message::Notify(NotifyMsg { title, body })
+            },
+            ServerToClientMsg::HeartbeatAck { id } => {
+                server_to_client_msg::Message::HeartbeatAck(HeartbeatAckMsg { id })
+            },
+            ServerToClientMsg::OpenLink { uri } => {
+                server_to_client_msg::Message::OpenLink(OpenLinkMsg { uri })
+            },
+            ServerToClientMsg::FileChunk {
+                file_name,
+                bytes,
+                is_last,
+            } => server_to_client_msg::Message::FileChunk(FileChunkMsg {
+                file_name,
+                bytes,
+                is_last,
+            }),
+            ServerToClientMsg::ShutdownPending => {
+                server_to_client_msg::Message::ShutdownPending(ShutdownPendingMsg {})
+            },
+            ServerToClientMsg::ConnStatusDetailed {
+                created_at,
+                connected_clients,
+                tab_count,
+                pane_count,
+                web_client_connected,
+            } => server_to_client_msg::Message::ConnStatusDetailed(ConnStatusDetailedReplyMsg {
+                created_at,
+                connected_clients,
+                tab_count,
+                pane_count,
+                web_client_connected,
+            }),
         };
 
         ProtoServerToClientMsg {
@@ -372,6 +498,50 @@ impl TryFrom<ProtoServerToClientMsg> for ServerToClientMsg {
             Some(server_to_client_msg::Message::ConfigFileUpdated(_)) => {
                 Ok(ServerToClientMsg::ConfigFileUpdated)
             },
+            Some(server_to_client_msg::Message::Bell(bell)) => Ok(ServerToClientMsg::Bell {
+                pane_name: bell.pane_name,
+            }),
+            Some(server_to_client_msg::Message::SetClipboard(set_clipboard)) => {
+                Ok(ServerToClientMsg::SetClipboard {
+                    content: set_clipboard.content,
+                })
+            },
+            Some(server_to_client_msg::Message::QueryClipboard(query_clipboard)) => {
+                Ok(ServerToClientMsg::QueryClipboard {
+                    terminal_id: query_clipboard.terminal_id,
+                })
+            },
+            Some(server_to_client_msg::Message::Notify(notify)) => Ok(ServerToClientMsg::Notify {
+                title: notify.title,
+                body: notify.body,
+            }),
+            Some(server_to_client_msg::Message::HeartbeatAck(heartbeat_ack)) => {
+                Ok(ServerToClientMsg::HeartbeatAck {
+                    id: heartbeat_ack.id,
+                })
+            },
+            Some(server_to_client_msg::Message::OpenLink(open_link)) => {
+                Ok(ServerToClientMsg::OpenLink { uri: open_link.uri })
+            },
+            Some(server_to_client_msg::Message::FileChunk(file_chunk)) => {
+                Ok(ServerToClientMsg::FileChunk {
+                    file_name: file_chunk.file_name,
+                    bytes: file_chunk.bytes,
+                    is_last: file_chunk.is_last,
+                })
+            },
+            Some(server_to_client_msg::Message::ShutdownPending(_)) => {
+                Ok(ServerToClientMsg::ShutdownPending)
+            },
+            Some(server_to_client_msg::Message::ConnStatusDetailed(conn_status_detailed)) => {
+                Ok(ServerToClientMsg::ConnStatusDetailed {
+                    created_at: conn_status_detailed.created_at,
+                    connected_clients: conn_status_detailed.connected_clients,
+                    tab_count: conn_status_detailed.tab_count,
+                    pane_count: conn_status_detailed.pane_count,
+                    web_client_connected: conn_status_detailed.web_client_connected,
+                })
+            },
             None => Err(anyhow!("Empty ServerToClientMsg message")),
         }
     }
@@ -510,6 +680,8 @@ impl From<crate::input::cli_assets::CliAssets>
             max_panes: cli_assets.max_panes.map(|m| m as u32),
             force_run_layout_commands: cli_assets.force_run_layout_commands,
             cwd: cli_assets.cwd.map(|p| p.to_string_lossy().to_string()),
+            client_theme: cli_assets.client_theme,
+            session_secret: cli_assets.session_secret,
         }
     }
 }
@@ -539,6 +711,8 @@ impl TryFrom<crate::client_server_contract::client_server_contract::CliAssets>
             max_panes: cli_assets.max_panes.map(|m| m as usize),
             force_run_layout_commands: cli_assets.force_run_layout_commands,
             cwd: cli_assets.cwd.map(PathBuf::from),
+            client_theme: cli_assets.client_theme,
+            session_secret: cli_assets.session_secret,
         })
     }
 }
@@ -548,8 +722,8 @@ impl From<crate::input::options::Options>
 {
     fn from(options: crate::input::options::Options) -> Self {
         use crate::client_server_contract::client_server_contract::{
-            Clipboard as ProtoClipboard, OnForceClose as ProtoOnForceClose,
-            WebSharing as ProtoWebSharing,
+            BellNotification as ProtoBellNotification, Clipboard as ProtoClipboard,
+            OnForceClose as ProtoOnForceClose, WebSharing as ProtoWebSharing,
         };
 
         Self {
@@ -615,6 +789,53 @@ impl From<crate::input::options::Options>
             enforce_https_for_localhost: options.enforce_https_for_localhost,
             post_command_discovery_hook: options.post_command_discovery_hook,
             client_async_worker_tasks: options.client_async_worker_tasks.map(|v| v as u64),
+            pane_idle_threshold: options.pane_idle_threshold,
+            pane_kill_grace_ms: options.pane_kill_grace_ms,
+            pane_output_rate_limit: options.pane_output_rate_limit,
+            bell_notification: options.bell_notification.map(|b| match b {
+                crate::input::options::BellNotification::None => ProtoBellNotification::None as i32,
+                crate::input::options::BellNotification::Beep => ProtoBellNotification::Beep as i32,
+                crate::input::options::BellNotification::Flash => {
+                    ProtoBellNotification::Flash as i32
+                },
+                crate::input::options::BellNotification::Toast => {
+                    ProtoBellNotification::Toast as i32
+                },
+            }),
+            conpty: options.conpty.map(|c| c.to_string()),
+            pty_system: options.pty_system.map(|p| p.to_string()),
+            utf8_codepage: options.utf8_codepage,
+            paste_warning_size: options
+                .paste_warning_size
+                .map(|s| u32::try_from(s).unwrap_or(u32::MAX)),
+            multi_line_paste_warning: options.multi_line_paste_warning,
+            detect_unbracketed_paste: options.detect_unbracketed_paste,
+            allow_clipboard_read: options.allow_clipboard_read,
+            copy_fallback_command: options.copy_fallback_command,
+            copy_line_endings: options.copy_line_endings.map(|l| l.to_string()),
+            ctrl_break_action: options.ctrl_break_action.map(|a| a.to_string()),
+            ctrl_c_action: options.ctrl_c_action.map(|a| a.to_string()),
+            ctrl_z_action: options.ctrl_z_action.map(|a| a.to_string()),
+            detach_chord: options.detach_chord.map(|c| c.to_string()),
+            max_fps: options
+                .max_fps
+                .map(|f| u32::try_from(f).unwrap_or(u32::MAX)),
+            legacy_console_rendering: options.legacy_console_rendering,
+            mute_notifications: options.mute_notifications,
+            windows_event_log: options.windows_event_log,
+            pipe_access: options.pipe_access.map(|p| p.to_string()),
+            session_max_memory_mb: options.session_max_memory_mb,
+            session_max_processes: options.session_max_processes,
+            session_cpu_rate_percent: options.session_cpu_rate_percent,
+            log_dir: options.log_dir.map(|p| p.to_string_lossy().to_string()),
+            log_level: options.log_level.map(|l| l.to_string()),
+            log_max_size_mb: options.log_max_size_mb,
+            log_max_files: options.log_max_files,
+            log_rotate_hours: options.log_rotate_hours,
+            idle_shutdown_hours: options.idle_shutdown_hours,
+            idle_shutdown_action: options.idle_shutdown_action.map(|a| a.to_string()),
+            detach_on_lock: options.detach_on_lock,
+            scrollback_spill_lines: options.scrollback_spill_lines.map(|s| s as u64),
         }
     }
 }
@@ -627,8 +848,8 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Options>
         options: crate::client_server_contract::client_server_contract::Options,
     ) -> Result<Self> {
         use crate::client_server_contract::client_server_contract::{
-            Clipboard as ProtoClipboard, OnForceClose as ProtoOnForceClose,
-            WebSharing as ProtoWebSharing,
+            BellNotification as ProtoBellNotification, Clipboard as ProtoClipboard,
+            OnForceClose as ProtoOnForceClose, WebSharing as ProtoWebSharing,
         };
 
         Ok(Self {
@@ -707,6 +928,97 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Options>
             enforce_https_for_localhost: options.enforce_https_for_localhost,
             post_command_discovery_hook: options.post_command_discovery_hook,
             client_async_worker_tasks: options.client_async_worker_tasks.map(|v| v as usize),
+            pane_idle_threshold: options.pane_idle_threshold,
+            pane_kill_grace_ms: options.pane_kill_grace_ms,
+            pane_output_rate_limit: options.pane_output_rate_limit,
+            bell_notification: options
+                .bell_notification
+                .map(|b| match ProtoBellNotification::from_i32(b) {
+                    Some(ProtoBellNotification::None) => {
+                        Ok(crate::input::options::BellNotification::None)
+                    },
+                    Some(ProtoBellNotification::Beep) => {
+                        Ok(crate::input::options::BellNotification::Beep)
+                    },
+                    Some(ProtoBellNotification::Flash) => {
+                        Ok(crate::input::options::BellNotification::Flash)
+                    },
+                    Some(ProtoBellNotification::Toast) => {
+                        Ok(crate::input::options::BellNotification::Toast)
+                    },
+                    _ => Err(anyhow!("Invalid BellNotification value: {}", b)),
+                })
+                .transpose()?,
+            conpty: options
+                .conpty
+                .map(|c| c.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            pty_system: options
+                .pty_system
+                .map(|p| p.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            utf8_codepage: options.utf8_codepage,
+            paste_warning_size: options.paste_warning_size.map(|s| s as usize),
+            multi_line_paste_warning: options.multi_line_paste_warning,
+            detect_unbracketed_paste: options.detect_unbracketed_paste,
+            allow_clipboard_read: options.allow_clipboard_read,
+            copy_fallback_command: options.copy_fallback_command,
+            copy_line_endings: options
+                .copy_line_endings
+                .map(|l| l.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            ctrl_break_action: options
+                .ctrl_break_action
+                .map(|a| a.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            ctrl_c_action: options
+                .ctrl_c_action
+                .map(|a| a.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            ctrl_z_action: options
+                .ctrl_z_action
+                .map(|a| a.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            detach_chord: options
+                .detach_chord
+                .map(|c| c.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            max_fps: options.max_fps.map(|f| f as usize),
+            legacy_console_rendering: options.legacy_console_rendering,
+            mute_notifications: options.mute_notifications,
+            windows_event_log: options.windows_event_log,
+            pipe_access: options
+                .pipe_access
+                .map(|p| p.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            session_max_memory_mb: options.session_max_memory_mb,
+            session_max_processes: options.session_max_processes,
+            session_cpu_rate_percent: options.session_cpu_rate_percent,
+            log_dir: options.log_dir.map(PathBuf::from),
+            log_level: options
+                .log_level
+                .map(|l| l.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            log_max_size_mb: options.log_max_size_mb,
+            log_max_files: options.log_max_files,
+            log_rotate_hours: options.log_rotate_hours,
+            idle_shutdown_hours: options.idle_shutdown_hours,
+            idle_shutdown_action: options
+                .idle_shutdown_action
+                .map(|a| a.parse())
+                .transpose()
+                .map_err(|e: String| anyhow!(e))?,
+            detach_on_lock: options.detach_on_lock,
+            scrollback_spill_lines: options.scrollback_spill_lines.map(|s| s as usize),
         })
     }
 }
@@ -718,28 +1030,32 @@ impl From<crate::input::actions::Action>
     fn from(action: crate::input::actions::Action) -> Self {
         use crate::client_server_contract::client_server_contract::{
             action::ActionType, BreakPaneAction, BreakPaneLeftAction, BreakPaneRightAction,
-            ChangeFloatingPaneCoordinatesAction, ClearScreenAction, CliPipeAction,
-            CloseFocusAction, ClosePluginPaneAction, CloseTabAction, CloseTabByIdAction,
-            CloseTerminalPaneAction, ConfirmAction, CopyAction, CurrentTabInfoAction, DenyAction,
-            DetachAction, DumpLayoutAction, DumpScreenAction, EditFileAction, EditScrollbackAction,
-            FocusNextPaneAction, FocusPluginPaneWithIdAction, FocusPreviousPaneAction,
-            FocusTerminalPaneWithIdAction, GoToNextTabAction, GoToPreviousTabAction, GoToTabAction,
-            GoToTabByIdAction, GoToTabNameAction, HalfPageScrollDownAction, HalfPageScrollUpAction,
-            KeybindPipeAction, LaunchOrFocusPluginAction, LaunchPluginAction, ListClientsAction,
-            ListPanesAction, ListTabsAction, MouseEventAction, MoveFocusAction,
-            MoveFocusOrTabAction, MovePaneAction, MovePaneBackwardsAction, MoveTabAction,
-            NewBlockingPaneAction, NewFloatingPaneAction, NewFloatingPluginPaneAction,
-            NewInPlacePaneAction, NewInPlacePluginPaneAction, NewPaneAction, NewStackedPaneAction,
-            NewTabAction, NewTiledPaneAction, NewTiledPluginPaneAction, NextSwapLayoutAction,
-            NoOpAction, OverrideLayoutAction, PageScrollDownAction, PageScrollUpAction,
-            PaneIdWithPlugin, PaneNameInputAction, PreviousSwapLayoutAction, QueryTabNamesAction,
-            QuitAction, RenamePluginPaneAction, RenameSessionAction, RenameTabAction,
-            RenameTabByIdAction, RenameTerminalPaneAction, ResizeAction, RunAction,
-            SaveSessionAction, ScrollDownAction, ScrollDownAtAction, ScrollToBottomAction,
-            ScrollToTopAction, ScrollUpAction, ScrollUpAtAction, SearchAction, SearchInputAction,
-            SearchToggleOptionAction, SetPaneBorderlessAction, SkipConfirmAction, StackPanesAction,
-            StartOrReloadPluginAction, SwitchFocusAction, SwitchModeForAllClientsAction,
-            SwitchSessionAction, SwitchToModeAction, TabNameInputAction, ToggleActiveSyncTabAction,
+            BreakPaneToWindowAction, ChangeFloatingPaneCoordinatesAction, ClearScreenAction,
+            CliPipeAction, CloseFocusAction, ClosePluginPaneAction, CloseTabAction,
+            CloseTabByIdAction, CloseTerminalPaneAction, ConfirmAction, CopyAction,
+            CurrentTabInfoAction, DenyAction, DetachAction, DumpLayoutAction, DumpScreenAction,
+            EditFileAction, EditScrollbackAction, FocusNextPaneAction, FocusPluginPaneWithIdAction,
+            FocusPreviousPaneAction, FocusTerminalPaneWithIdAction, GoToNextTabAction,
+            GoToPreviousTabAction, GoToTabAction, GoToTabByIdAction, GoToTabNameAction,
+            HalfPageScrollDownAction, HalfPageScrollUpAction, KeybindPipeAction,
+            LaunchOrFocusPluginAction, LaunchPluginAction, ListBackgroundJobsAction,
+            ListClientsAction, ListClipboardHistoryAction, ListPanesAction, ListTabsAction,
+            MouseEventAction, MoveFocusAction, MoveFocusOrTabAction, MovePaneAction,
+            MovePaneBackwardsAction, MoveTabAction, NewBackgroundPaneAction, NewBlockingPaneAction,
+            NewFloatingPaneAction, NewFloatingPluginPaneAction, NewInPlacePaneAction,
+            NewInPlacePluginPaneAction, NewPaneAction, NewStackedPaneAction, NewTabAction,
+            NewTiledPaneAction, NewTiledPluginPaneAction, NextSwapLayoutAction, NoOpAction,
+            OverrideLayoutAction, PageScrollDownAction, PageScrollUpAction, PaneIdWithPlugin,
+            PaneNameInputAction, PasteFromClipboardHistoryAction, PickFromClipboardHistoryAction,
+            PreviousSwapLayoutAction, QueryTabNamesAction, QuitAction, RenamePluginPaneAction,
+            RenameSessionAction, RenameTabAction, RenameTabByIdAction, RenameTerminalPaneAction,
+            ResizeAction, RunAction, SaveSessionAction, ScrollDownAction, ScrollDownAtAction,
+            ScrollToBottomAction, ScrollToTopAction, ScrollUpAction, ScrollUpAtAction,
+            SearchAction, SearchInputAction, SearchToggleOptionAction, SetIdleShutdownExemptAction,
+            SetLogLevelAction, SetPaneBorderlessAction, SetSessionEnvAction,
+            SetSessionSecretAction, SkipConfirmAction, StackPanesAction, StartOrReloadPluginAction,
+            SwitchFocusAction, SwitchModeForAllClientsAction, SwitchSessionAction,
+            SwitchToModeAction, TabNameInputAction, ToggleActiveSyncTabAction,
             ToggleFloatingPanesAction, ToggleFocusFullscreenAction, ToggleGroupMarkingAction,
             ToggleMouseModeAction, TogglePaneBorderlessAction, TogglePaneEmbedOrFloatingAction,
             TogglePaneFramesAction, TogglePaneInGroupAction, TogglePanePinnedAction,
@@ -823,9 +1139,13 @@ impl From<crate::input::actions::Action>
             crate::input::actions::Action::DumpScreen {
                 file_path,
                 include_scrollback,
+                keep_ansi,
+                crlf_line_endings,
             } => ActionType::DumpScreen(DumpScreenAction {
                 file_path,
                 include_scrollback,
+                keep_ansi,
+                crlf_line_endings,
             }),
             crate::input::actions::Action::DumpLayout => {
                 ActionType::DumpLayout(DumpLayoutAction {})
@@ -928,6 +1248,12 @@ impl From<crate::input::actions::Action>
                 near_current_pane,
                 borderless,
             }),
+            crate::input::actions::Action::NewBackgroundPane { command, pane_name } => {
+                ActionType::NewBackgroundPane(NewBackgroundPaneAction {
+                    command: Some(command.into()),
+                    pane_name,
+                })
+            },
             crate::input::actions::Action::NewInPlacePane {
                 command,
                 pane_name,
@@ -1233,6 +1559,26 @@ impl From<crate::input::actions::Action>
             crate::input::actions::Action::BreakPaneLeft => {
                 ActionType::BreakPaneLeft(BreakPaneLeftAction {})
             },
+            crate::input::actions::Action::BreakPaneToWindow => {
+                ActionType::BreakPaneToWindow(BreakPaneToWindowAction {})
+            },
+            crate::input::actions::Action::ListClipboardHistory => {
+                ActionType::ListClipboardHistory(ListClipboardHistoryAction {})
+            },
+            crate::input::actions::Action::PasteFromClipboardHistory {
+                index,
+                pane_id,
+                copy_only,
+            } => ActionType::PasteFromClipboardHistory(PasteFromClipboardHistoryAction {
+                index: index as u32,
+                pane_id: pane_id.map(|p| p.into()),
+                copy_only,
+            }),
+            crate::input::actions::Action::PickFromClipboardHistory { pane_id } => {
+                ActionType::PickFromClipboardHistory(PickFromClipboardHistoryAction {
+                    pane_id: pane_id.map(|p| p.into()),
+                })
+            },
             crate::input::actions::Action::RenameSession { name } => {
                 ActionType::RenameSession(RenameSessionAction { name })
             },
@@ -1316,6 +1662,9 @@ impl From<crate::input::actions::Action>
                 show_all,
                 output_json,
             }),
+            crate::input::actions::Action::ListBackgroundJobs { output_json } => {
+                ActionType::ListBackgroundJobs(ListBackgroundJobsAction { output_json })
+            },
             crate::input::actions::Action::TogglePanePinned => {
                 ActionType::TogglePanePinned(TogglePanePinnedAction {})
             },
@@ -1370,6 +1719,24 @@ impl From<crate::input::actions::Action>
             crate::input::actions::Action::CurrentTabInfo { output_json } => {
                 ActionType::CurrentTabInfo(CurrentTabInfoAction { output_json })
             },
+            crate::input::actions::Action::SetSessionSecret {
+                secret,
+                current_secret,
+            } => ActionType::SetSessionSecret(SetSessionSecretAction {
+                secret,
+                current_secret,
+            }),
+            crate::input::actions::Action::SetLogLevel { level } => {
+                ActionType::SetLogLevel(SetLogLevelAction {
+                    level: level.to_string(),
+                })
+            },
+            crate::input::actions::Action::SetSessionEnv { name, value } => {
+                ActionType::SetSessionEnv(SetSessionEnvAction { name, value })
+            },
+            crate::input::actions::Action::SetIdleShutdownExempt { exempt } => {
+                ActionType::SetIdleShutdownExempt(SetIdleShutdownExemptAction { exempt })
+            },
         };
 
         Self {
@@ -1474,6 +1841,8 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Action>
                 Ok(crate::input::actions::Action::DumpScreen {
                     file_path: dump_screen_action.file_path,
                     include_scrollback: dump_screen_action.include_scrollback,
+                    keep_ansi: dump_screen_action.keep_ansi,
+                    crlf_line_endings: dump_screen_action.crlf_line_endings,
                 })
             },
             ActionType::DumpLayout(_) => Ok(crate::input::actions::Action::DumpLayout),
@@ -1564,6 +1933,15 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Action>
                     borderless: new_tiled_action.borderless,
                 })
             },
+            ActionType::NewBackgroundPane(new_background_action) => {
+                Ok(crate::input::actions::Action::NewBackgroundPane {
+                    command: new_background_action
+                        .command
+                        .ok_or_else(|| anyhow!("Missing command"))?
+                        .try_into()?,
+                    pane_name: new_background_action.pane_name,
+                })
+            },
             ActionType::NewInPlacePane(new_in_place_action) => {
                 Ok(crate::input::actions::Action::NewInPlacePane {
                     command: new_in_place_action
@@ -1922,6 +2300,24 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Action>
             ActionType::BreakPane(_) => Ok(crate::input::actions::Action::BreakPane),
             ActionType::BreakPaneRight(_) => Ok(crate::input::actions::Action::BreakPaneRight),
             ActionType::BreakPaneLeft(_) => Ok(crate::input::actions::Action::BreakPaneLeft),
+            ActionType::BreakPaneToWindow(_) => {
+                Ok(crate::input::actions::Action::BreakPaneToWindow)
+            },
+            ActionType::ListClipboardHistory(_) => {
+                Ok(crate::input::actions::Action::ListClipboardHistory)
+            },
+            ActionType::PasteFromClipboardHistory(paste_action) => {
+                Ok(crate::input::actions::Action::PasteFromClipboardHistory {
+                    index: paste_action.index as usize,
+                    pane_id: paste_action.pane_id.and_then(|p| p.try_into().ok()),
+                    copy_only: paste_action.copy_only,
+                })
+            },
+            ActionType::PickFromClipboardHistory(pick_action) => {
+                Ok(crate::input::actions::Action::PickFromClipboardHistory {
+                    pane_id: pick_action.pane_id.and_then(|p| p.try_into().ok()),
+                })
+            },
             ActionType::RenameSession(rename_session_action) => {
                 Ok(crate::input::actions::Action::RenameSession {
                     name: rename_session_action.name,
@@ -1984,6 +2380,11 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Action>
                     output_json: list_panes_action.output_json,
                 })
             },
+            ActionType::ListBackgroundJobs(list_background_jobs_action) => {
+                Ok(crate::input::actions::Action::ListBackgroundJobs {
+                    output_json: list_background_jobs_action.output_json,
+                })
+            },
             ActionType::ListTabs(list_tabs_action) => Ok(crate::input::actions::Action::ListTabs {
                 show_state: list_tabs_action.show_state,
                 show_dimensions: list_tabs_action.show_dimensions,
@@ -1997,6 +2398,31 @@ impl TryFrom<crate::client_server_contract::client_server_contract::Action>
                     output_json: current_tab_info_action.output_json,
                 })
             },
+            ActionType::SetSessionSecret(set_session_secret_action) => {
+                Ok(crate::input::actions::Action::SetSessionSecret {
+                    secret: set_session_secret_action.secret,
+                    current_secret: set_session_secret_action.current_secret,
+                })
+            },
+            ActionType::SetLogLevel(set_log_level_action) => {
+                Ok(crate::input::actions::Action::SetLogLevel {
+                    level: set_log_level_action
+                        .level
+                        .parse()
+                        .map_err(|e: String| anyhow!(e))?,
+                })
+            },
+            ActionType::SetSessionEnv(set_session_env_action) => {
+                Ok(crate::input::actions::Action::SetSessionEnv {
+                    name: set_session_env_action.name,
+                    value: set_session_env_action.value,
+                })
+            },
+            ActionType::SetIdleShutdownExempt(set_idle_shutdown_exempt_action) => {
+                Ok(crate::input::actions::Action::SetIdleShutdownExempt {
+                    exempt: set_idle_shutdown_exempt_action.exempt,
+                })
+            },
             ActionType::TogglePanePinned(_) => Ok(crate::input::actions::Action::TogglePanePinned),
             ActionType::StackPanes(stack_panes_action) => {
                 Ok(crate::input::actions::Action::StackPanes {
@@ -2280,6 +2706,7 @@ impl From<ExitReason> for ProtoExitReason {
             ExitReason::Disconnect => ProtoExitReason::Disconnect,
             ExitReason::WebClientsForbidden => ProtoExitReason::WebClientsForbidden,
             ExitReason::KickedByHost => ProtoExitReason::KickedByHost,
+            ExitReason::SessionLocked => ProtoExitReason::SessionLocked,
             ExitReason::Error(_msg) => ProtoExitReason::Error,
             ExitReason::CustomExitStatus(_status) => ProtoExitReason::CustomExitStatus,
         }
@@ -2297,6 +2724,7 @@ impl TryFrom<ProtoExitReason> for ExitReason {
             ProtoExitReason::Disconnect => Ok(ExitReason::Disconnect),
             ProtoExitReason::WebClientsForbidden => Ok(ExitReason::WebClientsForbidden),
             ProtoExitReason::KickedByHost => Ok(ExitReason::KickedByHost),
+            ProtoExitReason::SessionLocked => Ok(ExitReason::SessionLocked),
             ProtoExitReason::Error => Ok(ExitReason::Error("Protobuf error".to_string())),
             ProtoExitReason::CustomExitStatus => Ok(ExitReason::CustomExitStatus(0)),
             ProtoExitReason::Unspecified => Err(anyhow!("Unspecified exit reason")),
@@ -2304,6 +2732,26 @@ impl TryFrom<ProtoExitReason> for ExitReason {
     }
 }
 
+impl From<DownloadableFile> for ProtoDownloadableFile {
+    fn from(file: DownloadableFile) -> Self {
+        match file {
+            DownloadableFile::ScreenDump => ProtoDownloadableFile::ScreenDump,
+            DownloadableFile::Log => ProtoDownloadableFile::Log,
+        }
+    }
+}
+
+impl TryFrom<ProtoDownloadableFile> for DownloadableFile {
+    type Error = anyhow::Error;
+    fn try_from(file: ProtoDownloadableFile) -> Result<Self> {
+        match file {
+            ProtoDownloadableFile::ScreenDump => Ok(DownloadableFile::ScreenDump),
+            ProtoDownloadableFile::Log => Ok(DownloadableFile::Log),
+            ProtoDownloadableFile::Unspecified => Err(anyhow!("Unspecified downloadable file")),
+        }
+    }
+}
+
 // InputMode conversion helper functions
 fn input_mode_to_proto_i32(mode: InputMode) -> i32 {
     match mode {
@@ -3089,6 +3537,8 @@ impl From<crate::input::layout::TiledPaneLayout>
             borderless: layout.borderless,
             focus: layout.focus.map(|f| f.to_string()),
             exclude_from_sync: layout.exclude_from_sync,
+            suppress_bell: layout.suppress_bell,
+            encoding: layout.encoding,
             children_are_stacked: layout.children_are_stacked,
             external_children_index: layout.external_children_index.map(|l| l as u32),
             is_expanded_in_stack: layout.is_expanded_in_stack,
@@ -3520,6 +3970,8 @@ impl TryFrom<crate::client_server_contract::client_server_contract::TiledPaneLay
             children_are_stacked: layout.children_are_stacked,
             is_expanded_in_stack: layout.is_expanded_in_stack,
             exclude_from_sync: layout.exclude_from_sync,
+            suppress_bell: layout.suppress_bell,
+            encoding: layout.encoding,
             run_instructions_to_ignore: vec![], // not represented in protobuf
             hide_floating_panes: layout.hide_floating_panes,
             pane_initial_contents: layout.pane_initial_contents,
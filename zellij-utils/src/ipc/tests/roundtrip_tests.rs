@@ -13,9 +13,13 @@ use crate::input::layout::{
     TiledPaneLayout,
 };
 use crate::input::mouse::{MouseEvent, MouseEventType};
-use crate::input::options::{Clipboard, OnForceClose, Options};
+use crate::input::options::{
+    BellNotification, Clipboard, ConPtyHost, ConsoleKeyAction, CopyLineEndings, IdleShutdownAction,
+    KeyChord, LogLevel, OnForceClose, Options, PipeAccess, PtySystem,
+};
 use crate::ipc::{
-    ClientToServerMsg, ColorRegister, ExitReason, PaneReference, PixelDimensions, ServerToClientMsg,
+    ClientToServerMsg, ColorRegister, DownloadableFile, ExitReason, PaneReference, PixelDimensions,
+    ServerToClientMsg,
 };
 use crate::pane_size::{Size, SizeInPixels};
 use crate::position::Position;
@@ -406,6 +410,8 @@ fn test_client_messages() {
             max_panes: Some(4),
             force_run_layout_commands: true,
             cwd: Some(PathBuf::from("/path/to/cwd")),
+            client_theme: None,
+            session_secret: None,
         },
         is_web_client: true,
     });
@@ -422,6 +428,8 @@ fn test_client_messages() {
             max_panes: Some(4),
             force_run_layout_commands: true,
             cwd: Some(PathBuf::from("/path/to/cwd")),
+            client_theme: None,
+            session_secret: None,
         },
         is_web_client: true,
     });
@@ -472,6 +480,46 @@ fn test_client_messages() {
                 enforce_https_for_localhost: Some(true),
                 post_command_discovery_hook: Some("post_command_discovery_hook".to_owned()),
                 client_async_worker_tasks: Some(16),
+                pane_idle_threshold: Some(30),
+                pane_kill_grace_ms: Some(1500),
+                pane_output_rate_limit: Some(25),
+                bell_notification: Some(BellNotification::Flash),
+                conpty: Some(ConPtyHost::System),
+                pty_system: Some(PtySystem::WinPty),
+                utf8_codepage: Some(false),
+                paste_warning_size: Some(5120),
+                multi_line_paste_warning: Some(true),
+                detect_unbracketed_paste: Some(true),
+                allow_clipboard_read: Some(true),
+                copy_fallback_command: Some("clip".to_string()),
+                copy_line_endings: Some(CopyLineEndings::Lf),
+                ctrl_break_action: Some(ConsoleKeyAction::Forward),
+                ctrl_c_action: Some(ConsoleKeyAction::Detach),
+                ctrl_z_action: Some(ConsoleKeyAction::Quit),
+                detach_chord: Some(KeyChord(vec![
+                    KeyWithModifier::new(BareKey::Char('\\')).with_ctrl_modifier(),
+                    KeyWithModifier::new(BareKey::Char('d')),
+                ])),
+                max_fps: Some(30),
+                legacy_console_rendering: Some(true),
+                mute_notifications: Some(true),
+                windows_event_log: Some(true),
+                pipe_access: Some(PipeAccess(vec![
+                    "BA".to_string(),
+                    "SERVER\\alice".to_string(),
+                ])),
+                session_max_memory_mb: Some(8192),
+                session_max_processes: Some(512),
+                session_cpu_rate_percent: Some(50),
+                log_dir: Some(PathBuf::from("/path/to/logs")),
+                log_level: Some(LogLevel::Debug),
+                log_max_size_mb: Some(64),
+                log_max_files: Some(5),
+                log_rotate_hours: Some(24),
+                idle_shutdown_hours: Some(72),
+                idle_shutdown_action: Some(IdleShutdownAction::Kill),
+                detach_on_lock: Some(true),
+                scrollback_spill_lines: Some(500000),
                 mouse_hover_effects: Some(false),
             }),
             layout: None,
@@ -481,6 +529,8 @@ fn test_client_messages() {
             max_panes: Some(4),
             force_run_layout_commands: true,
             cwd: Some(PathBuf::from("/path/to/cwd")),
+            client_theme: Some("solarized-light".to_owned()),
+            session_secret: Some("correct horse battery staple".to_owned()),
         },
         is_web_client: true,
     });
@@ -874,6 +924,8 @@ fn test_client_messages() {
         action: Action::DumpScreen {
             file_path: "/path/to/file".to_owned(),
             include_scrollback: false,
+            keep_ansi: false,
+            crlf_line_endings: false,
         },
         terminal_id: Some(1),
         client_id: Some(100),
@@ -883,6 +935,8 @@ fn test_client_messages() {
         action: Action::DumpScreen {
             file_path: "/path/to/file".to_owned(),
             include_scrollback: true,
+            keep_ansi: true,
+            crlf_line_endings: true,
         },
         terminal_id: Some(1),
         client_id: Some(100),
@@ -2554,6 +2608,90 @@ fn test_client_messages() {
         client_id: Some(100),
         is_cli_client: true,
     });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::NewBackgroundPane {
+            command: RunCommandAction {
+                command: PathBuf::from("cargo"),
+                args: vec!["build".to_owned()],
+                cwd: Some(PathBuf::from("/path/to/project")),
+                direction: None,
+                hold_on_close: true,
+                hold_on_start: false,
+                originating_plugin: None,
+                use_terminal_title: false,
+            },
+            pane_name: Some("build".to_owned()),
+        },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::ListBackgroundJobs { output_json: true },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::SetLogLevel {
+            level: LogLevel::Trace,
+        },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::SetSessionEnv {
+            name: "HTTP_PROXY".to_owned(),
+            value: Some("http://proxy:3128".to_owned()),
+        },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::SetSessionEnv {
+            name: "HTTP_PROXY".to_owned(),
+            value: None,
+        },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::SetIdleShutdownExempt { exempt: true },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::BreakPaneToWindow,
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::ListClipboardHistory,
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::PasteFromClipboardHistory {
+            index: 2,
+            pane_id: Some(PaneId::Terminal(3)),
+            copy_only: true,
+        },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Action {
+        action: Action::PickFromClipboardHistory { pane_id: None },
+        terminal_id: None,
+        client_id: None,
+        is_cli_client: true,
+    });
     test_client_roundtrip!(ClientToServerMsg::Action {
         action: Action::CliPipe {
             pipe_id: "pipe_id_name".to_owned(),
@@ -2998,12 +3136,47 @@ fn test_client_messages() {
     test_client_roundtrip!(ClientToServerMsg::ClientExited);
     test_client_roundtrip!(ClientToServerMsg::KillSession);
     test_client_roundtrip!(ClientToServerMsg::ConnStatus);
+    test_client_roundtrip!(ClientToServerMsg::ConnStatusDetailed);
+    test_client_roundtrip!(ClientToServerMsg::DetachOtherClients);
+    test_client_roundtrip!(ClientToServerMsg::Authenticate {
+        session_secret: "correct horse battery staple".to_string(),
+    });
     test_client_roundtrip!(ClientToServerMsg::WebServerStarted {
         base_url: "http://localhost:8080".to_string(),
     });
     test_client_roundtrip!(ClientToServerMsg::FailedToStartWebServer {
         error: "Port already in use".to_string(),
     });
+    test_client_roundtrip!(ClientToServerMsg::ClipboardContent {
+        terminal_id: 3,
+        content: Some("yanked text".to_string()),
+    });
+    test_client_roundtrip!(ClientToServerMsg::ClipboardContent {
+        terminal_id: 3,
+        content: None,
+    });
+    test_client_roundtrip!(ClientToServerMsg::Heartbeat { id: 42 });
+    test_client_roundtrip!(ClientToServerMsg::Redraw);
+    test_client_roundtrip!(ClientToServerMsg::TerminalFocus { focused: true });
+    test_client_roundtrip!(ClientToServerMsg::TerminalFocus { focused: false });
+    test_client_roundtrip!(ClientToServerMsg::UploadFileChunk {
+        file_name: "notes.txt".to_string(),
+        bytes: vec![0, 1, 2, 255],
+        is_first: true,
+        is_last: false,
+    });
+    test_client_roundtrip!(ClientToServerMsg::UploadFileChunk {
+        file_name: "notes.txt".to_string(),
+        bytes: vec![],
+        is_first: false,
+        is_last: true,
+    });
+    test_client_roundtrip!(ClientToServerMsg::DownloadFile {
+        file: DownloadableFile::ScreenDump,
+    });
+    test_client_roundtrip!(ClientToServerMsg::DownloadFile {
+        file: DownloadableFile::Log,
+    });
 }
 
 fn test_server_messages() {
@@ -3024,6 +3197,34 @@ fn test_server_messages() {
     test_server_roundtrip!(ServerToClientMsg::RenamedSession {
         name: "my-session".to_string(),
     });
+    test_server_roundtrip!(ServerToClientMsg::Bell {
+        pane_name: "cargo build".to_string(),
+    });
+    test_server_roundtrip!(ServerToClientMsg::SetClipboard {
+        content: "yanked text".to_string(),
+    });
+    test_server_roundtrip!(ServerToClientMsg::QueryClipboard { terminal_id: 3 });
+    test_server_roundtrip!(ServerToClientMsg::Notify {
+        title: "Command finished".to_string(),
+        body: "cargo build exited with code 0".to_string(),
+    });
+    test_server_roundtrip!(ServerToClientMsg::HeartbeatAck { id: 42 });
+    test_server_roundtrip!(ServerToClientMsg::OpenLink {
+        uri: "https://github.com/zellij-org/zellij".to_string(),
+    });
+    test_server_roundtrip!(ServerToClientMsg::FileChunk {
+        file_name: "zellij.log".to_string(),
+        bytes: b"INFO: started".to_vec(),
+        is_last: true,
+    });
+    test_server_roundtrip!(ServerToClientMsg::ShutdownPending);
+    test_server_roundtrip!(ServerToClientMsg::ConnStatusDetailed {
+        created_at: 1_700_000_000,
+        connected_clients: 2,
+        tab_count: 3,
+        pane_count: 7,
+        web_client_connected: true,
+    });
     test_server_roundtrip!(ServerToClientMsg::UnblockCliPipeInput {
         pipe_name: "stdout".to_string(),
     });
@@ -3052,6 +3253,9 @@ fn test_server_messages() {
     test_server_roundtrip!(ServerToClientMsg::Exit {
         exit_reason: ExitReason::WebClientsForbidden,
     });
+    test_server_roundtrip!(ServerToClientMsg::Exit {
+        exit_reason: ExitReason::SessionLocked,
+    });
     test_server_roundtrip!(ServerToClientMsg::Log { lines: vec![] });
     test_server_roundtrip!(ServerToClientMsg::Log {
         lines: vec![
@@ -208,10 +208,15 @@ fn receiver_returns_none_on_closed_connection() {
 
     let msg = receiver.recv_client_msg();
     assert!(msg.is_some(), "should receive the sent message");
+    assert!(!receiver.is_disconnected());
 
     // After the sender is dropped, subsequent reads should return None
     let msg = receiver.recv_client_msg();
     assert!(msg.is_none(), "should return None after connection closed");
+    assert!(
+        receiver.is_disconnected(),
+        "a closed connection should be reported as a disconnection"
+    );
 }
 
 // --- Session discovery tests ---
@@ -340,6 +345,103 @@ fn socket_directory_enumeration_finds_sockets() {
     assert_eq!(entries[0], "test-session");
 }
 
+/// Running web servers are discovered by listing their pipes, which (unlike the marker files
+/// they leave behind) disappear with their process.
+#[cfg(windows)]
+#[test]
+fn windows_named_pipes_are_listed_while_they_exist() {
+    use crate::ipc::{list_named_pipes, path_to_ipc_name, windows_pipe_name_prefix};
+
+    let dir = TempDir::new().expect("failed to create temp dir");
+    let bus_dir = dir.path().join("pipe_listing_test_bus");
+    let pipe_name_prefix = windows_pipe_name_prefix(&bus_dir);
+    let listed_ids = || -> Vec<String> {
+        list_named_pipes()
+            .expect("list named pipes")
+            .iter()
+            .filter_map(|pipe_name| pipe_name.strip_prefix(&pipe_name_prefix))
+            .map(|id| id.to_owned())
+            .collect()
+    };
+
+    let listener = ListenerOptions::new()
+        .name(path_to_ipc_name(&bus_dir.join("a1b2c")).expect("pipe name"))
+        .create_sync()
+        .expect("listener");
+    assert_eq!(listed_ids(), vec!["a1b2c".to_owned()]);
+
+    drop(listener);
+    assert!(listed_ids().is_empty(), "pipes of closed listeners are not listed");
+}
+
+/// Sessions are discovered by listing their pipes, each server's reverse pipe being listed
+/// as part of its main pipe rather than as a session of its own.
+#[cfg(windows)]
+#[test]
+fn windows_pipes_in_dir_are_listed_without_their_reverse_pipes() {
+    use crate::ipc::{list_pipes_in, path_to_ipc_name, path_to_ipc_name_reverse};
+
+    let dir = TempDir::new().expect("failed to create temp dir");
+    let sock_dir = dir.path().join("pipe_listing_test_contract");
+    let session_path = sock_dir.join("listed-session");
+
+    let main_listener = ListenerOptions::new()
+        .name(path_to_ipc_name(&session_path).expect("main pipe name"))
+        .create_sync()
+        .expect("main listener");
+    let reverse_listener = ListenerOptions::new()
+        .name(path_to_ipc_name_reverse(&session_path).expect("reverse pipe name"))
+        .create_sync()
+        .expect("reverse listener");
+
+    assert_eq!(
+        list_pipes_in(&sock_dir).expect("list pipes"),
+        vec!["listed-session".to_owned()]
+    );
+
+    drop(main_listener);
+    drop(reverse_listener);
+    assert!(list_pipes_in(&sock_dir).expect("list pipes").is_empty());
+}
+
+/// Named pipes cannot be renamed, so a renamed session is found and connected to by its new name
+/// through an alias of its pipes, and a new session by its old name gets pipes of its own.
+#[cfg(windows)]
+#[test]
+fn windows_renamed_session_is_reached_by_its_new_name() {
+    use crate::ipc::{claim_pipe_name, list_pipes_in, move_pipe_alias, path_to_ipc_name};
+
+    let dir = TempDir::new().expect("failed to create temp dir");
+    let sock_dir = dir.path().join("pipe_alias_test_contract");
+    std::fs::create_dir_all(&sock_dir).expect("socket dir");
+    let old_path = sock_dir.join("old-name");
+    let new_path = sock_dir.join("new-name");
+
+    let renamed_listener = ListenerOptions::new()
+        .name(path_to_ipc_name(&old_path).expect("pipe name"))
+        .create_sync()
+        .expect("listener");
+    move_pipe_alias(&old_path, &new_path).expect("alias pipes");
+    assert_eq!(
+        list_pipes_in(&sock_dir).expect("list pipes"),
+        vec!["new-name".to_owned()]
+    );
+    LocalSocketStream::connect(path_to_ipc_name(&new_path).expect("aliased pipe name"))
+        .expect("connect by the new name");
+
+    claim_pipe_name(&old_path).expect("claim old name");
+    let new_listener = ListenerOptions::new()
+        .name(path_to_ipc_name(&old_path).expect("pipe name"))
+        .create_sync()
+        .expect("listener of a new session by the old name");
+    let mut listed = list_pipes_in(&sock_dir).expect("list pipes");
+    listed.sort();
+    assert_eq!(listed, vec!["new-name".to_owned(), "old-name".to_owned()]);
+
+    drop(renamed_listener);
+    drop(new_listener);
+}
+
 /// On Windows, session probing uses dual named pipes: the client sends ConnStatus
 /// on the main pipe and reads the Connected response from the reverse pipe.
 /// This test simulates the full dual-pipe handshake used by assert_socket().
@@ -105,6 +105,8 @@ impl<'a> KdlLayoutParser<'a> {
             || property_name == "stacked"
             || property_name == "expanded"
             || property_name == "exclude_from_sync"
+            || property_name == "suppress_bell"
+            || property_name == "encoding"
             || property_name == "contents_file"
     }
     fn is_a_valid_floating_pane_property(&self, property_name: &str) -> bool {
@@ -532,6 +534,10 @@ impl<'a> KdlLayoutParser<'a> {
             .map(|name| name.to_string());
         let exclude_from_sync =
             kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_sync");
+        let suppress_bell =
+            kdl_get_bool_property_or_child_value_with_error!(kdl_node, "suppress_bell");
+        let encoding = kdl_get_string_property_or_child_value_with_error!(kdl_node, "encoding")
+            .map(|encoding| encoding.to_string());
         let contents_file =
             kdl_get_string_property_or_child_value_with_error!(kdl_node, "contents_file");
         let split_size = self.parse_split_size(kdl_node)?;
@@ -581,6 +587,8 @@ impl<'a> KdlLayoutParser<'a> {
             children_split_direction,
             external_children_index,
             exclude_from_sync,
+            suppress_bell,
+            encoding,
             children,
             children_are_stacked,
             is_expanded_in_stack,
@@ -721,6 +729,11 @@ impl<'a> KdlLayoutParser<'a> {
                 let run = self.parse_command_plugin_or_edit_block_for_template(kdl_node)?;
                 let exclude_from_sync =
                     kdl_get_bool_property_or_child_value_with_error!(kdl_node, "exclude_from_sync");
+                let suppress_bell =
+                    kdl_get_bool_property_or_child_value_with_error!(kdl_node, "suppress_bell");
+                let encoding =
+                    kdl_get_string_property_or_child_value_with_error!(kdl_node, "encoding")
+                        .map(|encoding| encoding.to_string());
 
                 let external_children_index = if should_mark_external_children_index {
                     self.populate_external_children_index(kdl_node)?
@@ -760,6 +773,12 @@ impl<'a> KdlLayoutParser<'a> {
                 if let Some(exclude_from_sync) = exclude_from_sync {
                     pane_template.exclude_from_sync = Some(exclude_from_sync);
                 }
+                if let Some(suppress_bell) = suppress_bell {
+                    pane_template.suppress_bell = Some(suppress_bell);
+                }
+                if let Some(encoding) = encoding {
+                    pane_template.encoding = Some(encoding);
+                }
                 if let Some(split_size) = split_size {
                     pane_template.split_size = Some(split_size);
                 }
@@ -12,9 +12,13 @@ use crate::input::keybinds::Keybinds;
 use crate::input::layout::{
     Layout, PercentOrFixed, PluginUserConfiguration, RunPlugin, RunPluginOrAlias, TabLayoutInfo,
 };
-use crate::input::options::{Clipboard, OnForceClose, Options};
+use crate::input::options::{
+    BellNotification, Clipboard, ConPtyHost, ConsoleKeyAction, CopyLineEndings, IdleShutdownAction,
+    KeyChord, LogLevel, OnForceClose, Options, PipeAccess, PtySystem,
+};
 use crate::input::permission::{GrantedPermission, PermissionCache};
 use crate::input::plugins::PluginAliases;
+use crate::input::session_templates::SessionTemplates;
 use crate::input::theme::{FrameConfig, Theme, Themes, UiConfig};
 use crate::input::web_client::WebClientConfig;
 use kdl_layout_parser::KdlLayoutParser;
@@ -434,6 +438,20 @@ impl Action {
                 })? as u32;
                 Ok(Action::GoToTab { index: tab_index })
             },
+            "PasteFromClipboardHistory" => {
+                let index = *bytes.first().ok_or_else(|| {
+                    ConfigError::new_kdl_error(
+                        "Missing clipboard history index".to_string(),
+                        action_node.span().offset(),
+                        action_node.span().len(),
+                    )
+                })? as usize;
+                Ok(Action::PasteFromClipboardHistory {
+                    index,
+                    pane_id: None,
+                    copy_only: false,
+                })
+            },
             _ => Err(ConfigError::new_kdl_error(
                 "Failed to parse action".into(),
                 action_node.span().offset(),
@@ -540,6 +558,8 @@ impl Action {
             "DumpScreen" => Ok(Action::DumpScreen {
                 file_path: string,
                 include_scrollback: false,
+                keep_ansi: false,
+                crlf_line_endings: false,
             }),
             "DumpLayout" => Ok(Action::DumpLayout),
             "NewPane" => {
@@ -689,8 +709,7 @@ impl Action {
             },
             Action::MovePaneBackwards => Some(KdlNode::new("MovePaneBackwards")),
             Action::DumpScreen {
-                file_path: file,
-                include_scrollback: _,
+                file_path: file, ..
             } => {
                 let mut node = KdlNode::new("DumpScreen");
                 node.push(file.clone());
@@ -1186,6 +1205,19 @@ impl Action {
             Action::BreakPane => Some(KdlNode::new("BreakPane")),
             Action::BreakPaneRight => Some(KdlNode::new("BreakPaneRight")),
             Action::BreakPaneLeft => Some(KdlNode::new("BreakPaneLeft")),
+            Action::BreakPaneToWindow => Some(KdlNode::new("BreakPaneToWindow")),
+            Action::PasteFromClipboardHistory {
+                index,
+                pane_id: None,
+                copy_only: false,
+            } => {
+                let mut node = KdlNode::new("PasteFromClipboardHistory");
+                node.push(KdlValue::Base10(*index as i64));
+                Some(node)
+            },
+            Action::PickFromClipboardHistory { pane_id: None } => {
+                Some(KdlNode::new("PickFromClipboardHistory"))
+            },
             Action::KeybindPipe {
                 name,
                 payload,
@@ -2055,6 +2087,11 @@ impl TryFrom<(&KdlNode, &Options)> for Action {
             "BreakPane" => Ok(Action::BreakPane),
             "BreakPaneRight" => Ok(Action::BreakPaneRight),
             "BreakPaneLeft" => Ok(Action::BreakPaneLeft),
+            "BreakPaneToWindow" => Ok(Action::BreakPaneToWindow),
+            "PasteFromClipboardHistory" => {
+                parse_kdl_action_u8_arguments!(action_name, action_arguments, kdl_action)
+            },
+            "PickFromClipboardHistory" => Ok(Action::PickFromClipboardHistory { pane_id: None }),
             "RenameSession" => parse_kdl_action_char_or_string_arguments!(
                 action_name,
                 action_arguments,
@@ -2723,6 +2760,241 @@ impl Options {
                 },
                 None => None,
             };
+        let pane_idle_threshold =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "pane_idle_threshold")
+                .map(|(v, _)| v as u64);
+        let pane_kill_grace_ms =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "pane_kill_grace_ms")
+                .map(|(v, _)| v as u64);
+        let pane_output_rate_limit =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "pane_output_rate_limit")
+                .map(|(v, _)| v as u64);
+        let bell_notification =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "bell_notification") {
+                Some((string, entry)) => {
+                    Some(BellNotification::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for bell_notification: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        let conpty = match kdl_property_first_arg_as_string_or_error!(kdl_options, "conpty") {
+            Some((string, entry)) => Some(ConPtyHost::from_str(string).map_err(|_| {
+                kdl_parsing_error!(format!("Invalid value for conpty: '{}'", string), entry)
+            })?),
+            None => None,
+        };
+        let utf8_codepage =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "utf8_codepage").map(|(v, _)| v);
+        let paste_warning_size =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "paste_warning_size")
+                .map(|(paste_warning_size, _entry)| paste_warning_size as usize);
+        let multi_line_paste_warning =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "multi_line_paste_warning")
+                .map(|(v, _)| v);
+        let detect_unbracketed_paste =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "detect_unbracketed_paste")
+                .map(|(v, _)| v);
+        let allow_clipboard_read =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "allow_clipboard_read")
+                .map(|(v, _)| v);
+        let copy_fallback_command =
+            kdl_property_first_arg_as_string_or_error!(kdl_options, "copy_fallback_command")
+                .map(|(copy_fallback_command, _entry)| copy_fallback_command.to_string());
+        let copy_line_endings =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "copy_line_endings") {
+                Some((string, entry)) => Some(CopyLineEndings::from_str(string).map_err(|_| {
+                    kdl_parsing_error!(
+                        format!("Invalid value for copy_line_endings: '{}'", string),
+                        entry
+                    )
+                })?),
+                None => None,
+            };
+        let ctrl_break_action =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "ctrl_break_action") {
+                Some((string, entry)) => {
+                    Some(ConsoleKeyAction::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for ctrl_break_action: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        let ctrl_c_action =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "ctrl_c_action") {
+                Some((string, entry)) => {
+                    Some(ConsoleKeyAction::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for ctrl_c_action: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        let ctrl_z_action =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "ctrl_z_action") {
+                Some((string, entry)) => {
+                    Some(ConsoleKeyAction::from_str(string).map_err(|_| {
+                        kdl_parsing_error!(
+                            format!("Invalid value for ctrl_z_action: '{}'", string),
+                            entry
+                        )
+                    })?)
+                },
+                None => None,
+            };
+        let detach_chord =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "detach_chord") {
+                Some((string, entry)) => {
+                    Some(KeyChord::from_str(string).map_err(|e| kdl_parsing_error!(e, entry))?)
+                },
+                None => None,
+            };
+        let max_fps = match kdl_property_first_arg_as_i64_or_error!(kdl_options, "max_fps") {
+            Some((max_fps, _)) if (0..=u32::MAX as i64).contains(&max_fps) => {
+                Some(max_fps as usize)
+            },
+            Some((max_fps, entry)) => {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "max_fps must be between 0 and {}, found '{}'",
+                        u32::MAX,
+                        max_fps
+                    ),
+                    entry
+                ));
+            },
+            None => None,
+        };
+        let legacy_console_rendering =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "legacy_console_rendering")
+                .map(|(v, _)| v);
+        let mute_notifications =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "mute_notifications")
+                .map(|(v, _)| v);
+        let windows_event_log =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "windows_event_log")
+                .map(|(v, _)| v);
+        let pipe_access =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "pipe_access") {
+                Some((string, entry)) => {
+                    Some(PipeAccess::from_str(string).map_err(|e| kdl_parsing_error!(e, entry))?)
+                },
+                None => None,
+            };
+        let session_max_memory_mb =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "session_max_memory_mb")
+                .map(|(v, _)| v as u64);
+        let session_max_processes =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "session_max_processes")
+                .map(|(v, _)| v as u32);
+        let session_cpu_rate_percent = match kdl_property_first_arg_as_i64_or_error!(
+            kdl_options,
+            "session_cpu_rate_percent"
+        ) {
+            Some((rate, _)) if (1..=100).contains(&rate) => Some(rate as u32),
+            Some((rate, entry)) => {
+                return Err(kdl_parsing_error!(
+                    format!(
+                        "Invalid value for session_cpu_rate_percent: '{}', expected 1 to 100",
+                        rate
+                    ),
+                    entry
+                ));
+            },
+            None => None,
+        };
+        let log_dir = kdl_property_first_arg_as_string_or_error!(kdl_options, "log_dir")
+            .map(|(string, _entry)| PathBuf::from(string));
+        let log_level = match kdl_property_first_arg_as_string_or_error!(kdl_options, "log_level") {
+            Some((string, entry)) => {
+                Some(LogLevel::from_str(string).map_err(|e| kdl_parsing_error!(e, entry))?)
+            },
+            None => None,
+        };
+        let log_max_size_mb =
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, "log_max_size_mb") {
+                Some((limit, _)) if limit >= 1 => Some(limit as u64),
+                Some((limit, entry)) => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Invalid value for log_max_size_mb: '{}', expected at least 1",
+                            limit
+                        ),
+                        entry
+                    ));
+                },
+                None => None,
+            };
+        let log_max_files =
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, "log_max_files") {
+                Some((limit, _)) if limit >= 1 => Some(limit as u32),
+                Some((limit, entry)) => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Invalid value for log_max_files: '{}', expected at least 1",
+                            limit
+                        ),
+                        entry
+                    ));
+                },
+                None => None,
+            };
+        let log_rotate_hours =
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, "log_rotate_hours") {
+                Some((limit, _)) if limit >= 1 => Some(limit as u64),
+                Some((limit, entry)) => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Invalid value for log_rotate_hours: '{}', expected at least 1",
+                            limit
+                        ),
+                        entry
+                    ));
+                },
+                None => None,
+            };
+        let idle_shutdown_hours =
+            match kdl_property_first_arg_as_i64_or_error!(kdl_options, "idle_shutdown_hours") {
+                Some((hours, _)) if hours >= 1 => Some(hours as u64),
+                Some((hours, entry)) => {
+                    return Err(kdl_parsing_error!(
+                        format!(
+                            "Invalid value for idle_shutdown_hours: '{}', expected at least 1",
+                            hours
+                        ),
+                        entry
+                    ));
+                },
+                None => None,
+            };
+        let idle_shutdown_action =
+            match kdl_property_first_arg_as_string_or_error!(kdl_options, "idle_shutdown_action") {
+                Some((string, entry)) => Some(
+                    IdleShutdownAction::from_str(string)
+                        .map_err(|e| kdl_parsing_error!(e, entry))?,
+                ),
+                None => None,
+            };
+        let detach_on_lock =
+            kdl_property_first_arg_as_bool_or_error!(kdl_options, "detach_on_lock").map(|(v, _)| v);
+        let scrollback_spill_lines =
+            kdl_property_first_arg_as_i64_or_error!(kdl_options, "scrollback_spill_lines")
+                .map(|(scrollback_spill_lines, _entry)| scrollback_spill_lines as usize);
+        let pty_system = match kdl_property_first_arg_as_string_or_error!(kdl_options, "pty_system")
+        {
+            Some((string, entry)) => Some(PtySystem::from_str(string).map_err(|_| {
+                kdl_parsing_error!(format!("Invalid value for pty_system: '{}'", string), entry)
+            })?),
+            None => None,
+        };
 
         Ok(Options {
             simplified_ui,
@@ -2767,6 +3039,40 @@ impl Options {
             enforce_https_for_localhost,
             post_command_discovery_hook,
             client_async_worker_tasks,
+            pane_idle_threshold,
+            pane_kill_grace_ms,
+            pane_output_rate_limit,
+            bell_notification,
+            conpty,
+            pty_system,
+            utf8_codepage,
+            paste_warning_size,
+            multi_line_paste_warning,
+            detect_unbracketed_paste,
+            allow_clipboard_read,
+            copy_fallback_command,
+            copy_line_endings,
+            ctrl_break_action,
+            ctrl_c_action,
+            ctrl_z_action,
+            detach_chord,
+            max_fps,
+            legacy_console_rendering,
+            mute_notifications,
+            windows_event_log,
+            pipe_access,
+            session_max_memory_mb,
+            session_max_processes,
+            session_cpu_rate_percent,
+            log_dir,
+            log_level,
+            log_max_size_mb,
+            log_max_files,
+            log_rotate_hours,
+            idle_shutdown_hours,
+            idle_shutdown_action,
+            detach_on_lock,
+            scrollback_spill_lines,
         })
     }
     pub fn from_string(stringified_keybindings: &String) -> Result<Self, ConfigError> {
@@ -4003,43 +4309,1056 @@ impl Options {
             None
         }
     }
-    pub fn to_kdl(&self, add_comments: bool) -> Vec<KdlNode> {
-        let mut nodes = vec![];
-        if let Some(simplified_ui_node) = self.simplified_ui_to_kdl(add_comments) {
-            nodes.push(simplified_ui_node);
-        }
-        if let Some(osc8_hyperlinks_node) = self.osc8_hyperlinks_to_kdl(add_comments) {
-            nodes.push(osc8_hyperlinks_node);
-        }
-        if let Some(theme_node) = self.theme_to_kdl(add_comments) {
-            nodes.push(theme_node);
-        }
-        if let Some(default_mode) = self.default_mode_to_kdl(add_comments) {
-            nodes.push(default_mode);
-        }
-        if let Some(default_shell) = self.default_shell_to_kdl(add_comments) {
-            nodes.push(default_shell);
-        }
-        if let Some(default_cwd) = self.default_cwd_to_kdl(add_comments) {
-            nodes.push(default_cwd);
+    fn pane_idle_threshold_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Seconds without input or output after which a pane is considered idle.",
+            "// Plugins receive PaneBecameIdle / PaneBecameActive events when a pane crosses this threshold",
+            "// (eg. to show activity or silence indicators in the tab bar). 0 disables activity tracking.",
+            "// Default: 10",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("pane_idle_threshold");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(pane_idle_threshold) = self.pane_idle_threshold {
+            let mut node = create_node(pane_idle_threshold);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(10);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(default_layout) = self.default_layout_to_kdl(add_comments) {
-            nodes.push(default_layout);
+    }
+    fn pane_kill_grace_ms_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Milliseconds a pane's processes are given to exit gracefully when the pane is closed",
+            "// (or interrupted on Windows) before they are forcefully terminated.",
+            "// Useful when running databases or editors that need time to flush their state.",
+            "// 0 terminates immediately.",
+            "// Default: 0",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("pane_kill_grace_ms");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(pane_kill_grace_ms) = self.pane_kill_grace_ms {
+            let mut node = create_node(pane_kill_grace_ms);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(3000);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(layout_dir) = self.layout_dir_to_kdl(add_comments) {
-            nodes.push(layout_dir);
+    }
+    fn pane_output_rate_limit_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Megabytes per second a single pane may output before zellij throttles reading from it",
+            "// and flags it in the pane frame. This keeps a runaway program (eg. `cat` on a huge file)",
+            "// from slowing down every other pane, especially on Windows where conhost processing is expensive.",
+            "// 0 disables the limit.",
+            "// Default: 0",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("pane_output_rate_limit");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(pane_output_rate_limit) = self.pane_output_rate_limit {
+            let mut node = create_node(pane_output_rate_limit);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(50);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(theme_dir) = self.theme_dir_to_kdl(add_comments) {
-            nodes.push(theme_dir);
+    }
+    fn bell_notification_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// How to notify about a bell (BEL) rung in a pane, eg. when a long running command finishes,",
+            "// including panes in other tabs. Bells from panes with `suppress_bell=true` in the layout are ignored.",
+            "// Options:",
+            "//   - none (Default), the bell is only passed on to the terminal emulator",
+            "//   - beep, play the system alert sound (Windows only)",
+            "//   - flash, flash the terminal window in the taskbar until it is focused (Windows only)",
+            "//   - toast, show a desktop notification naming the pane (Windows only)",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("bell_notification");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(bell_notification) = &self.bell_notification {
+            let mut node = match bell_notification {
+                BellNotification::None => create_node("none"),
+                BellNotification::Beep => create_node("beep"),
+                BellNotification::Flash => create_node("flash"),
+                BellNotification::Toast => create_node("toast"),
+            };
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("flash");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(mouse_mode) = self.mouse_mode_to_kdl(add_comments) {
-            nodes.push(mouse_mode);
+    }
+    fn conpty_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Which ConPTY implementation hosts the panes (Windows only).",
+            "// The ConPTY built into Windows lags behind the one shipped with Windows Terminal, which fixes",
+            "// output passthrough and resize bugs.",
+            "// Options:",
+            "//   - bundled (Default), conpty.dll and OpenConsole.exe next to the zellij executable,",
+            "//     falling back to the system's when they are missing",
+            "//   - system, the ConPTY built into Windows",
+            "//   - the path of a directory with conpty.dll and OpenConsole.exe, eg. from a Windows Terminal install",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("conpty");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(conpty) = &self.conpty {
+            let mut node = create_node(&conpty.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("system");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(pane_frames) = self.pane_frames_to_kdl(add_comments) {
-            nodes.push(pane_frames);
+    }
+    fn pty_system_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Which pseudo terminal implementation runs the panes (Windows only).",
+            "// Options:",
+            "//   - conpty (Default)",
+            "//   - conpty_passthrough, ConPTY in passthrough mode, which needs a recent conpty.dll",
+            "//     and lets sixel images drawn in the panes through",
+            "//   - winpty, for Windows builds whose ConPTY is broken, which needs winpty.dll and",
+            "//     winpty-agent.exe next to the zellij executable",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("pty_system");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(pty_system) = &self.pty_system {
+            let mut node = create_node(&pty_system.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("winpty");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
         }
-        if let Some(mirror_session) = self.mirror_session_to_kdl(add_comments) {
-            nodes.push(mirror_session);
+    }
+    fn utf8_codepage_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Whether to switch the console of new panes to the UTF-8 codepage, so that legacy",
+            "// console programs do not mangle non-ASCII output. This runs chcp.com in every new",
+            "// pane before its shell starts, which delays it (Windows only)",
+            "// default is false",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("utf8_codepage");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(utf8_codepage) = self.utf8_codepage {
+            let mut node = create_node(utf8_codepage);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn paste_warning_size_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}",
+            " ",
+            "// Ask for confirmation before pasting more than this many bytes (Windows only)",
+            "// default is to never ask",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("paste_warning_size");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(paste_warning_size) = self.paste_warning_size {
+            let mut node = create_node(paste_warning_size);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(5120);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn multi_line_paste_warning_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Ask for confirmation before pasting text spanning multiple lines, so that a pasted",
+            "// script is not run line by line by accident (Windows only)",
+            "// default is false",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("multi_line_paste_warning");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(multi_line_paste_warning) = self.multi_line_paste_warning {
+            let mut node = create_node(multi_line_paste_warning);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn detect_unbracketed_paste_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Treat several lines of text arriving at once as a paste, for consoles that paste",
+            "// without bracketed paste markers. Several lines typed quickly enough can be mistaken",
+            "// for a paste (Windows only)",
+            "// default is false",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("detect_unbracketed_paste");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(detect_unbracketed_paste) = self.detect_unbracketed_paste {
+            let mut node = create_node(detect_unbracketed_paste);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn allow_clipboard_read_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Whether programs in panes may read the clipboard through OSC 52. Every read asks for",
+            "// permission first (Windows only)",
+            "// default is false",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("allow_clipboard_read");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(allow_clipboard_read) = self.allow_clipboard_read {
+            let mut node = create_node(allow_clipboard_read);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn copy_fallback_command_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// A command to pipe copied text to when the clipboard cannot be opened, eg. because",
+            "// another application holds it (Windows only)",
+            "// Default: not set, the text is not copied then",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("copy_fallback_command");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(copy_fallback_command) = &self.copy_fallback_command {
+            let mut node = create_node(copy_fallback_command);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("clip");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn copy_line_endings_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The line endings of text copied to the clipboard (Windows only).",
+            "// Options:",
+            "//   - crlf (Default), what most Windows applications expect when pasting",
+            "//   - lf",
+            "//   - unchanged, copy the text as it is",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("copy_line_endings");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(copy_line_endings) = &self.copy_line_endings {
+            let mut node = create_node(&copy_line_endings.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("lf");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn ctrl_break_action_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// What Ctrl+Break does (Windows only). It is forwarded as a win32-input-mode key event,",
+            "// having no byte of its own.",
+            "// Options:",
+            "//   - forward, send it to the focused pane",
+            "//   - detach, detach from the session",
+            "//   - quit, quit the session",
+            "// Default: what on_force_close is set to",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("ctrl_break_action");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(ctrl_break_action) = &self.ctrl_break_action {
+            let mut node = create_node(&ctrl_break_action.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("forward");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn ctrl_c_action_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// What Ctrl+C does",
+            "// Options:",
+            "//   - forward (Default), send it to the focused pane, subject to the keybindings",
+            "//   - detach, detach from the session",
+            "//   - quit, quit the session",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("ctrl_c_action");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(ctrl_c_action) = &self.ctrl_c_action {
+            let mut node = create_node(&ctrl_c_action.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("detach");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn ctrl_z_action_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// What Ctrl+Z does",
+            "// Options:",
+            "//   - forward (Default), send it to the focused pane, subject to the keybindings",
+            "//   - detach, detach from the session",
+            "//   - quit, quit the session",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("ctrl_z_action");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(ctrl_z_action) = &self.ctrl_z_action {
+            let mut node = create_node(&ctrl_z_action.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("detach");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn detach_chord_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// A sequence of keys that detaches this client, checked before any keybinding.",
+            "// Modifiers apply to the key following them. Pressing the first key of the sequence",
+            "// twice sends that key to the focused pane.",
+            "// Default: \"Ctrl \\\\ d\" on Windows and none elsewhere, an empty string disables it",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("detach_chord");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(detach_chord) = &self.detach_chord {
+            let mut node = create_node(&detach_chord.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("Ctrl g d");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn max_fps_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The most frames per second to draw, output arriving in between is drawn with the",
+            "// next frame. Useful for terminals that cannot keep up with rendering (eg. conhost)",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("max_fps");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(max_fps) = self.max_fps {
+            let mut node = create_node(max_fps);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(30);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn legacy_console_rendering_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Draw for the classic Windows console (conhost): colors mapped to the 256 color",
+            "// palette, square box-drawing corners and no synchronized output (Windows only)",
+            "// Default: true in a classic console window, false otherwise",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("legacy_console_rendering");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(legacy_console_rendering) = self.legacy_console_rendering {
+            let mut node = create_node(legacy_console_rendering);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn mute_notifications_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Do not show desktop notifications for this session, eg. when a command run in",
+            "// a pane finishes",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("mute_notifications");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(mute_notifications) = self.mute_notifications {
+            let mut node = create_node(mute_notifications);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn windows_event_log_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Mirror the warnings and errors zellij logs to the Windows Event Log, under the",
+            "// \"zellij\" source (register it once with `zellij setup --register-event-source`",
+            "// from an elevated shell)",
+            "// Default: false",
+            "// ",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("windows_event_log");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(windows_event_log) = self.windows_event_log {
+            let mut node = create_node(windows_event_log);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn pipe_access_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Comma separated accounts or groups, besides the current user, allowed to attach",
+            "// to new sessions through their pipes, given as SIDs, SDDL aliases (eg. BA for the",
+            "// local Administrators) or account names (eg. SERVER\\alice) (Windows only)",
+            "// Anyone allowed can take over the input of the sessions, consider locking them",
+            "// Default: only the current user",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("pipe_access");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(pipe_access) = &self.pipe_access {
+            let mut node = create_node(&pipe_access.to_string());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("BA, SERVER\\alice");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn session_max_memory_mb_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Megabytes of memory the processes of all panes of a session may commit together.",
+            "// Sessions are placed in a job object, where a process asking for more is refused",
+            "// the memory (Windows only)",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("session_max_memory_mb");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(session_max_memory_mb) = self.session_max_memory_mb {
+            let mut node = create_node(session_max_memory_mb);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(8192);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn session_max_processes_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The most processes that may run in all panes of a session together, more fail",
+            "// to start (Windows only)",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: u32| -> KdlNode {
+            let mut node = KdlNode::new("session_max_processes");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(session_max_processes) = self.session_max_processes {
+            let mut node = create_node(session_max_processes);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(512);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn session_cpu_rate_percent_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Percentage (1 to 100) of the total CPU time of the machine the processes of all",
+            "// panes of a session may use together, so that a runaway build cannot starve a",
+            "// shared machine (Windows only)",
+            "// Default: no limit",
+            "// ",
+        );
+
+        let create_node = |node_value: u32| -> KdlNode {
+            let mut node = KdlNode::new("session_cpu_rate_percent");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(session_cpu_rate_percent) = self.session_cpu_rate_percent {
+            let mut node = create_node(session_cpu_rate_percent);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(50);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn log_dir_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The directory zellij writes its logs to, relative paths are taken from the local",
+            "// data directory (under %LOCALAPPDATA% on Windows)",
+            "// Default: the zellij-log folder of the temporary directory",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("log_dir");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(log_dir) = &self.log_dir {
+            let mut node = create_node(log_dir.display().to_string().as_str());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("logs");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn log_level_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// The most verbose messages to log (off, error, warn, info, debug or trace), it can",
+            "// be changed in a running session with `zellij action set-log-level`",
+            "// Default: info",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("log_level");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(log_level) = self.log_level {
+            let mut node = create_node(log_level.to_string().as_str());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("debug");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn log_max_size_mb_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ", "// Megabytes the log may grow to before it is rotated", "// Default: 16", "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("log_max_size_mb");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(log_max_size_mb) = self.log_max_size_mb {
+            let mut node = create_node(log_max_size_mb);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(64);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn log_max_files_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ", "// How many rotated logs to keep next to the current one", "// Default: 1", "// ",
+        );
+
+        let create_node = |node_value: u32| -> KdlNode {
+            let mut node = KdlNode::new("log_max_files");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(log_max_files) = self.log_max_files {
+            let mut node = create_node(log_max_files);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(5);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn log_rotate_hours_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Rotate the log after this many hours, even if it has not grown to log_max_size_mb",
+            "// Default: only rotate it by size",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("log_rotate_hours");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(log_rotate_hours) = self.log_rotate_hours {
+            let mut node = create_node(log_rotate_hours);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(24);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn idle_shutdown_hours_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Shut the session down after this many hours with no clients attached and no output",
+            "// from its panes, sessions can be exempted with",
+            "// `zellij action set-idle-shutdown-exempt --exempt`",
+            "// Default: never shut idle sessions down",
+            "// ",
+        );
+
+        let create_node = |node_value: u64| -> KdlNode {
+            let mut node = KdlNode::new("idle_shutdown_hours");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(idle_shutdown_hours) = self.idle_shutdown_hours {
+            let mut node = create_node(idle_shutdown_hours);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(72);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn scrollback_spill_lines_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// Keep this many scrollback lines per pane in a file under the cache directory once they",
+            "// fall out of the in-memory scroll buffer (scroll_buffer_size), 0 disables this",
+            "// Default: 0",
+            "// ",
+        );
+
+        let create_node = |node_value: usize| -> KdlNode {
+            let mut node = KdlNode::new("scrollback_spill_lines");
+            node.push(KdlValue::Base10(node_value as i64));
+            node
+        };
+        if let Some(scrollback_spill_lines) = self.scrollback_spill_lines {
+            let mut node = create_node(scrollback_spill_lines);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(500000);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn detach_on_lock_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}",
+            " ",
+            "// Detach when the Windows desktop is locked and offer to reattach once it is unlocked",
+            "// (Windows only)",
+            "// default is false",
+        );
+
+        let create_node = |node_value: bool| -> KdlNode {
+            let mut node = KdlNode::new("detach_on_lock");
+            node.push(KdlValue::Bool(node_value));
+            node
+        };
+        if let Some(detach_on_lock) = self.detach_on_lock {
+            let mut node = create_node(detach_on_lock);
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node(true);
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    fn idle_shutdown_action_to_kdl(&self, add_comments: bool) -> Option<KdlNode> {
+        let comment_text = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            " ",
+            "// How to shut idle sessions down",
+            "// Options:",
+            "//   - serialize (Default), so that the session can be resurrected",
+            "//   - kill, deleting the serialized session",
+            "// ",
+        );
+
+        let create_node = |node_value: &str| -> KdlNode {
+            let mut node = KdlNode::new("idle_shutdown_action");
+            node.push(node_value.to_owned());
+            node
+        };
+        if let Some(idle_shutdown_action) = self.idle_shutdown_action {
+            let mut node = create_node(idle_shutdown_action.to_string().as_str());
+            if add_comments {
+                node.set_leading(format!("{}\n", comment_text));
+            }
+            Some(node)
+        } else if add_comments {
+            let mut node = create_node("kill");
+            node.set_leading(format!("{}\n// ", comment_text));
+            Some(node)
+        } else {
+            None
+        }
+    }
+    pub fn to_kdl(&self, add_comments: bool) -> Vec<KdlNode> {
+        let mut nodes = vec![];
+        if let Some(simplified_ui_node) = self.simplified_ui_to_kdl(add_comments) {
+            nodes.push(simplified_ui_node);
+        }
+        if let Some(osc8_hyperlinks_node) = self.osc8_hyperlinks_to_kdl(add_comments) {
+            nodes.push(osc8_hyperlinks_node);
+        }
+        if let Some(theme_node) = self.theme_to_kdl(add_comments) {
+            nodes.push(theme_node);
+        }
+        if let Some(default_mode) = self.default_mode_to_kdl(add_comments) {
+            nodes.push(default_mode);
+        }
+        if let Some(default_shell) = self.default_shell_to_kdl(add_comments) {
+            nodes.push(default_shell);
+        }
+        if let Some(default_cwd) = self.default_cwd_to_kdl(add_comments) {
+            nodes.push(default_cwd);
+        }
+        if let Some(default_layout) = self.default_layout_to_kdl(add_comments) {
+            nodes.push(default_layout);
+        }
+        if let Some(layout_dir) = self.layout_dir_to_kdl(add_comments) {
+            nodes.push(layout_dir);
+        }
+        if let Some(theme_dir) = self.theme_dir_to_kdl(add_comments) {
+            nodes.push(theme_dir);
+        }
+        if let Some(mouse_mode) = self.mouse_mode_to_kdl(add_comments) {
+            nodes.push(mouse_mode);
+        }
+        if let Some(pane_frames) = self.pane_frames_to_kdl(add_comments) {
+            nodes.push(pane_frames);
+        }
+        if let Some(mirror_session) = self.mirror_session_to_kdl(add_comments) {
+            nodes.push(mirror_session);
         }
         if let Some(on_force_close) = self.on_force_close_to_kdl(add_comments) {
             nodes.push(on_force_close);
@@ -4140,6 +5459,108 @@ impl Options {
         {
             nodes.push(client_async_worker_tasks);
         }
+        if let Some(pane_idle_threshold) = self.pane_idle_threshold_to_kdl(add_comments) {
+            nodes.push(pane_idle_threshold);
+        }
+        if let Some(pane_kill_grace_ms) = self.pane_kill_grace_ms_to_kdl(add_comments) {
+            nodes.push(pane_kill_grace_ms);
+        }
+        if let Some(pane_output_rate_limit) = self.pane_output_rate_limit_to_kdl(add_comments) {
+            nodes.push(pane_output_rate_limit);
+        }
+        if let Some(bell_notification) = self.bell_notification_to_kdl(add_comments) {
+            nodes.push(bell_notification);
+        }
+        if let Some(conpty) = self.conpty_to_kdl(add_comments) {
+            nodes.push(conpty);
+        }
+        if let Some(pty_system) = self.pty_system_to_kdl(add_comments) {
+            nodes.push(pty_system);
+        }
+        if let Some(utf8_codepage) = self.utf8_codepage_to_kdl(add_comments) {
+            nodes.push(utf8_codepage);
+        }
+        if let Some(paste_warning_size) = self.paste_warning_size_to_kdl(add_comments) {
+            nodes.push(paste_warning_size);
+        }
+        if let Some(multi_line_paste_warning) = self.multi_line_paste_warning_to_kdl(add_comments) {
+            nodes.push(multi_line_paste_warning);
+        }
+        if let Some(detect_unbracketed_paste) = self.detect_unbracketed_paste_to_kdl(add_comments) {
+            nodes.push(detect_unbracketed_paste);
+        }
+        if let Some(allow_clipboard_read) = self.allow_clipboard_read_to_kdl(add_comments) {
+            nodes.push(allow_clipboard_read);
+        }
+        if let Some(copy_fallback_command) = self.copy_fallback_command_to_kdl(add_comments) {
+            nodes.push(copy_fallback_command);
+        }
+        if let Some(copy_line_endings) = self.copy_line_endings_to_kdl(add_comments) {
+            nodes.push(copy_line_endings);
+        }
+        if let Some(ctrl_break_action) = self.ctrl_break_action_to_kdl(add_comments) {
+            nodes.push(ctrl_break_action);
+        }
+        if let Some(ctrl_c_action) = self.ctrl_c_action_to_kdl(add_comments) {
+            nodes.push(ctrl_c_action);
+        }
+        if let Some(ctrl_z_action) = self.ctrl_z_action_to_kdl(add_comments) {
+            nodes.push(ctrl_z_action);
+        }
+        if let Some(detach_chord) = self.detach_chord_to_kdl(add_comments) {
+            nodes.push(detach_chord);
+        }
+        if let Some(max_fps) = self.max_fps_to_kdl(add_comments) {
+            nodes.push(max_fps);
+        }
+        if let Some(legacy_console_rendering) = self.legacy_console_rendering_to_kdl(add_comments) {
+            nodes.push(legacy_console_rendering);
+        }
+        if let Some(mute_notifications) = self.mute_notifications_to_kdl(add_comments) {
+            nodes.push(mute_notifications);
+        }
+        if let Some(windows_event_log) = self.windows_event_log_to_kdl(add_comments) {
+            nodes.push(windows_event_log);
+        }
+        if let Some(pipe_access) = self.pipe_access_to_kdl(add_comments) {
+            nodes.push(pipe_access);
+        }
+        if let Some(session_max_memory_mb) = self.session_max_memory_mb_to_kdl(add_comments) {
+            nodes.push(session_max_memory_mb);
+        }
+        if let Some(session_max_processes) = self.session_max_processes_to_kdl(add_comments) {
+            nodes.push(session_max_processes);
+        }
+        if let Some(session_cpu_rate_percent) = self.session_cpu_rate_percent_to_kdl(add_comments) {
+            nodes.push(session_cpu_rate_percent);
+        }
+        if let Some(log_dir) = self.log_dir_to_kdl(add_comments) {
+            nodes.push(log_dir);
+        }
+        if let Some(log_level) = self.log_level_to_kdl(add_comments) {
+            nodes.push(log_level);
+        }
+        if let Some(log_max_size_mb) = self.log_max_size_mb_to_kdl(add_comments) {
+            nodes.push(log_max_size_mb);
+        }
+        if let Some(log_max_files) = self.log_max_files_to_kdl(add_comments) {
+            nodes.push(log_max_files);
+        }
+        if let Some(log_rotate_hours) = self.log_rotate_hours_to_kdl(add_comments) {
+            nodes.push(log_rotate_hours);
+        }
+        if let Some(idle_shutdown_hours) = self.idle_shutdown_hours_to_kdl(add_comments) {
+            nodes.push(idle_shutdown_hours);
+        }
+        if let Some(idle_shutdown_action) = self.idle_shutdown_action_to_kdl(add_comments) {
+            nodes.push(idle_shutdown_action);
+        }
+        if let Some(detach_on_lock) = self.detach_on_lock_to_kdl(add_comments) {
+            nodes.push(detach_on_lock);
+        }
+        if let Some(scrollback_spill_lines) = self.scrollback_spill_lines_to_kdl(add_comments) {
+            nodes.push(scrollback_spill_lines);
+        }
         nodes
     }
 }
@@ -4633,6 +6054,10 @@ impl Config {
             let config_web_client = WebClientConfig::from_kdl(&web_client_config)?;
             config.web_client = config.web_client.merge(config_web_client);
         }
+        if let Some(session_templates) = kdl_config.get("session_templates") {
+            let config_session_templates = SessionTemplates::from_kdl(session_templates)?;
+            config.session_templates = config.session_templates.merge(config_session_templates);
+        }
         Ok(config)
     }
     pub fn to_string(&self, add_comments: bool) -> String {
@@ -4662,6 +6087,10 @@ impl Config {
 
         document.nodes_mut().push(self.web_client.to_kdl());
 
+        if let Some(session_templates) = self.session_templates.to_kdl() {
+            document.nodes_mut().push(session_templates);
+        }
+
         document
             .nodes_mut()
             .append(&mut self.options.to_kdl(add_comments));
@@ -5809,6 +7238,7 @@ impl PaneInfo {
         let terminal_command = optional_string_node!("terminal_command");
         let plugin_url = optional_string_node!("plugin_url");
         let is_selectable = bool_node!("is_selectable");
+        let foreground_process = optional_string_node!("foreground_process");
 
         let pane_info = PaneInfo {
             id,
@@ -5834,6 +7264,7 @@ impl PaneInfo {
             plugin_url,
             is_selectable,
             index_in_pane_group: Default::default(), // we don't serialize this
+            foreground_process,
         };
         Ok((tab_position, pane_info))
     }
@@ -5894,6 +7325,9 @@ impl PaneInfo {
             string_node!("plugin_url", plugin_url.to_string());
         }
         bool_node!("is_selectable", self.is_selectable);
+        if let Some(foreground_process) = &self.foreground_process {
+            string_node!("foreground_process", foreground_process.to_string());
+        }
         kdl_doucment
     }
 }
@@ -5982,6 +7416,7 @@ fn serialize_and_deserialize_session_info_with_data() {
             plugin_url: None,
             is_selectable: true,
             index_in_pane_group: Default::default(), // we don't serialize this
+            foreground_process: Some("cargo".to_owned()),
         },
         PaneInfo {
             id: 1,
@@ -6007,6 +7442,7 @@ fn serialize_and_deserialize_session_info_with_data() {
             plugin_url: Some("i_am_a_fake_plugin".to_owned()),
             is_selectable: true,
             index_in_pane_group: Default::default(), // we don't serialize this
+            foreground_process: None,
         },
     ];
     let mut panes = HashMap::new();
@@ -13,13 +13,18 @@ pub mod position;
 pub mod session_serialization;
 pub mod setup;
 pub mod shared;
+pub mod win32_input;
 
 // The following modules can't be used when targeting wasm
+#[cfg(windows)]
+pub mod autostart; // Requires windows-sys
 #[cfg(not(target_family = "wasm"))]
 pub mod channels; // Requires tokio
 #[cfg(not(target_family = "wasm"))]
 pub mod common_path;
 #[cfg(not(target_family = "wasm"))]
+pub mod crash_dump;
+#[cfg(not(target_family = "wasm"))]
 pub mod downloader; // Requires tokio
 #[cfg(not(target_family = "wasm"))]
 pub mod ipc; // Requires interprocess
@@ -29,6 +34,12 @@ pub mod logging; // Requires log4rs
 pub mod remote_session_tokens;
 #[cfg(not(target_family = "wasm"))]
 pub mod sessions;
+#[cfg(not(target_family = "wasm"))]
+pub mod stale_artifacts;
+#[cfg(not(target_family = "wasm"))]
+pub mod setup_wizard;
+#[cfg(not(target_family = "wasm"))]
+pub mod windows_terminal;
 #[cfg(all(not(target_family = "wasm"), feature = "web_server_capability"))]
 pub mod web_authentication_tokens;
 #[cfg(all(not(target_family = "wasm"), feature = "web_server_capability"))]
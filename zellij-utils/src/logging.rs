@@ -4,37 +4,210 @@ use std::{
     fs,
     io::{self, prelude::*},
     path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
 };
 
 use log::LevelFilter;
 
 use log4rs::append::rolling_file::{
     policy::compound::{
-        roll::fixed_window::FixedWindowRoller, trigger::size::SizeTrigger, CompoundPolicy,
+        roll::fixed_window::FixedWindowRoller,
+        trigger::{size::SizeTrigger, Trigger},
+        CompoundPolicy,
     },
-    RollingFileAppender,
+    LogFile, RollingFileAppender,
 };
+use log4rs::append::Append;
 use log4rs::config::{Appender, Config, Logger, Root};
 use log4rs::encode::pattern::PatternEncoder;
+use log4rs::Handle;
 
-use crate::consts::{ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR, ZELLIJ_TMP_LOG_FILE};
+use crate::consts::{ZELLIJ_PROJ_DIR, ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR, ZELLIJ_TMP_LOG_FILE};
+use crate::input::options::Options;
 use crate::shared::set_permissions;
 
 const LOG_MAX_BYTES: u64 = 1024 * 1024 * 16; // 16 MiB per log
+const LOG_FILE_NAME: &str = "zellij.log";
+
+// so that the configuration can be changed once we read it from the config file
+static LOGGER: OnceLock<Handle> = OnceLock::new();
+static LOG_SETTINGS: Mutex<LogSettings> = Mutex::new(LogSettings {
+    log_dir: None,
+    level: LevelFilter::Info,
+    max_bytes: LOG_MAX_BYTES,
+    max_files: 1,
+    rotate_interval: None,
+    windows_event_log: false,
+});
+
+// the clock of the time based rotation keeps running across reconfigurations of the logger
+static LAST_ROTATION: OnceLock<Arc<Mutex<Instant>>> = OnceLock::new();
+
+/// What the `log_*` options (and `windows_event_log`) ask of the logger
+#[derive(Debug, Clone)]
+struct LogSettings {
+    log_dir: Option<PathBuf>, // None -> ZELLIJ_TMP_LOG_DIR
+    level: LevelFilter,
+    max_bytes: u64,
+    max_files: u32,
+    rotate_interval: Option<Duration>,
+    windows_event_log: bool,
+}
 
 pub fn configure_logger() {
     atomic_create_dir(&*ZELLIJ_TMP_DIR).unwrap();
     atomic_create_dir(&*ZELLIJ_TMP_LOG_DIR).unwrap();
     atomic_create_file(&*ZELLIJ_TMP_LOG_FILE).unwrap();
 
-    let trigger = SizeTrigger::new(LOG_MAX_BYTES);
+    let settings = LOG_SETTINGS.lock().unwrap().clone();
+    let handle = log4rs::init_config(logger_config(&settings, None)).unwrap();
+    let _ = LOGGER.set(handle);
+}
+
+/// Apply the logging options of the config: where to log, how verbosely, when to rotate the log
+/// and whether to mirror it to the Windows Event Log
+pub fn apply_log_options(options: &Options) {
+    let mut settings = LOG_SETTINGS.lock().unwrap().clone();
+    if let Some(log_dir) = &options.log_dir {
+        let log_dir = resolve_log_dir(log_dir);
+        match fs::create_dir_all(&log_dir).and_then(|_| set_permissions(&log_dir, 0o700)) {
+            Ok(()) => settings.log_dir = Some(log_dir),
+            Err(e) => log::error!("Failed to create the log directory {:?}: {}", log_dir, e),
+        }
+    }
+    if let Some(log_level) = options.log_level {
+        settings.level = log_level.into();
+    }
+    if let Some(log_max_size_mb) = options.log_max_size_mb {
+        settings.max_bytes = log_max_size_mb * 1024 * 1024;
+    }
+    if let Some(log_max_files) = options.log_max_files {
+        settings.max_files = log_max_files;
+    }
+    if let Some(log_rotate_hours) = options.log_rotate_hours {
+        settings.rotate_interval = Some(Duration::from_secs(log_rotate_hours * 60 * 60));
+    }
+    settings.windows_event_log = options.windows_event_log.unwrap_or(false);
+    if settings.windows_event_log && !cfg!(windows) {
+        log::warn!("The Windows Event Log is only available on Windows");
+        settings.windows_event_log = false;
+    }
+    reconfigure_logger(settings);
+}
+
+/// Change the most verbose messages logged, until this process ends or it is changed again
+pub fn set_log_level(level: LevelFilter) {
+    log::info!("Setting the log level to {}", level);
+    let mut settings = LOG_SETTINGS.lock().unwrap().clone();
+    settings.level = level;
+    reconfigure_logger(settings);
+}
+
+/// The log currently written to, which moves with the `log_dir` option
+pub fn log_file() -> PathBuf {
+    log_dir().join(LOG_FILE_NAME)
+}
+
+fn log_dir() -> PathBuf {
+    LOG_SETTINGS
+        .lock()
+        .ok()
+        .and_then(|settings| settings.log_dir.clone())
+        .unwrap_or_else(|| ZELLIJ_TMP_LOG_DIR.clone())
+}
+
+// ~ and environment variables (eg. $LOCALAPPDATA) are expanded, and what is still relative is
+// taken from the local data directory - under %LOCALAPPDATA% on Windows, like the rest of our
+// state there
+fn resolve_log_dir(log_dir: &Path) -> PathBuf {
+    let log_dir = shellexpand::full(&log_dir.to_string_lossy())
+        .map(|expanded| PathBuf::from(expanded.as_ref()))
+        .unwrap_or_else(|_| log_dir.to_path_buf());
+    if log_dir.is_absolute() {
+        log_dir
+    } else {
+        ZELLIJ_PROJ_DIR.data_local_dir().join(log_dir)
+    }
+}
+
+fn reconfigure_logger(settings: LogSettings) {
+    if let Ok(mut current_settings) = LOG_SETTINGS.lock() {
+        *current_settings = settings.clone();
+    }
+    if let Some(handle) = LOGGER.get() {
+        let event_log = if settings.windows_event_log {
+            windows_event_log()
+        } else {
+            None
+        };
+        handle.set_config(logger_config(&settings, event_log));
+    }
+}
+
+/// Mirrors the warnings and errors we log to the Windows Event Log, in addition to the log file.
+#[cfg(windows)]
+fn windows_event_log() -> Option<Box<dyn Append>> {
+    match event_log::EventLogAppender::new() {
+        Ok(event_log) => Some(Box::new(event_log)),
+        Err(e) => {
+            log::error!("Failed to open the Windows Event Log: {}", e);
+            None
+        },
+    }
+}
+
+#[cfg(not(windows))]
+fn windows_event_log() -> Option<Box<dyn Append>> {
+    None
+}
+
+/// Rotates the log once it grows past its size limit, or once it has been written to for longer
+/// than the rotation interval (if there is one)
+#[derive(Debug)]
+struct RotationTrigger {
+    size: SizeTrigger,
+    interval: Option<Duration>,
+    // shared by the appenders of a log file, so that they do not rotate it one after the other
+    last_rotation: Arc<Mutex<Instant>>,
+}
+
+impl Trigger for RotationTrigger {
+    fn trigger(&self, file: &LogFile) -> anyhow::Result<bool> {
+        let mut last_rotation = self
+            .last_rotation
+            .lock()
+            .map_err(|_| anyhow::anyhow!("log rotation lock poisoned"))?;
+        let is_due = self
+            .interval
+            .map(|interval| last_rotation.elapsed() >= interval)
+            .unwrap_or(false);
+        if is_due || self.size.trigger(file)? {
+            *last_rotation = Instant::now();
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+fn logger_config(settings: &LogSettings, event_log: Option<Box<dyn Append>>) -> Config {
+    let log_dir = settings
+        .log_dir
+        .clone()
+        .unwrap_or_else(|| ZELLIJ_TMP_LOG_DIR.clone());
+    let log_file_path = log_dir.join(LOG_FILE_NAME);
+    let last_rotation = LAST_ROTATION
+        .get_or_init(|| Arc::new(Mutex::new(Instant::now())))
+        .clone();
+    let trigger = || RotationTrigger {
+        size: SizeTrigger::new(settings.max_bytes),
+        interval: settings.rotate_interval,
+        last_rotation: last_rotation.clone(),
+    };
     let roller = FixedWindowRoller::builder()
         .build(
-            ZELLIJ_TMP_LOG_DIR
-                .join("zellij.log.old.{}")
-                .to_str()
-                .unwrap(),
-            1,
+            log_dir.join("zellij.log.old.{}").to_str().unwrap(),
+            settings.max_files,
         )
         .unwrap();
 
@@ -46,9 +219,9 @@ pub fn configure_logger() {
     let log_file = RollingFileAppender::builder()
         .encoder(Box::new(PatternEncoder::new(file_pattern)))
         .build(
-            &*ZELLIJ_TMP_LOG_FILE,
+            &log_file_path,
             Box::new(CompoundPolicy::new(
-                Box::new(trigger),
+                Box::new(trigger()),
                 Box::new(roller.clone()),
             )),
         )
@@ -61,15 +234,22 @@ pub fn configure_logger() {
             "{highlight({level:<6})} {message} {n}",
         )))
         .build(
-            &*ZELLIJ_TMP_LOG_FILE,
-            Box::new(CompoundPolicy::new(Box::new(trigger), Box::new(roller))),
+            &log_file_path,
+            Box::new(CompoundPolicy::new(Box::new(trigger()), Box::new(roller))),
         )
         .unwrap();
 
-    // Set the default logging level to "info" and log it to zellij.log file
+    let mut config = Config::builder();
+    let mut root = Root::builder().appender("logFile");
+    if let Some(event_log) = event_log {
+        config = config.appender(Appender::builder().build("eventLog", event_log));
+        root = root.appender("eventLog");
+    }
+
+    // Set the default logging level to "info" (or the log_level option) and log it to zellij.log file
     // Decrease verbosity for `wasmtime_wasi` module because it has a lot of useless info logs
     // For `zellij_server::logging_pipe`, we use custom format as we use logging macros to forward stderr output from plugins
-    let config = Config::builder()
+    config
         .appender(Appender::builder().build("logFile", Box::new(log_file)))
         .appender(Appender::builder().build("logPlugin", Box::new(log_plugin)))
         // reduce the verbosity of isahc, otherwise it logs on every failed web request
@@ -89,10 +269,16 @@ pub fn configure_logger() {
                 .additive(false)
                 .build("zellij_server::logging_pipe", LevelFilter::Trace),
         )
-        .build(Root::builder().appender("logFile").build(LevelFilter::Info))
-        .unwrap();
+        .build(root.build(settings.level))
+        .unwrap()
+}
 
-    let _ = log4rs::init_config(config).unwrap();
+/// Register zellij as a source of the Application log of the Windows Event Log, so that the
+/// Event Viewer shows our messages rather than complaining it cannot find their descriptions.
+/// This writes to HKLM and so needs an elevated shell.
+#[cfg(windows)]
+pub fn register_windows_event_source() -> io::Result<()> {
+    event_log::register_event_source()
 }
 
 pub fn atomic_create_file(file_name: &Path) -> io::Result<()> {
@@ -120,9 +306,7 @@ pub fn atomic_create_dir(dir_name: &Path) -> io::Result<()> {
 }
 
 pub fn debug_to_file(message: &[u8], terminal_id: i32) -> io::Result<()> {
-    let mut path = PathBuf::new();
-    path.push(&*ZELLIJ_TMP_LOG_DIR);
-    path.push(format!("zellij-{}.log", terminal_id));
+    let path = log_dir().join(format!("zellij-{}.log", terminal_id));
 
     let mut file = fs::OpenOptions::new()
         .append(true)
@@ -131,3 +315,148 @@ pub fn debug_to_file(message: &[u8], terminal_id: i32) -> io::Result<()> {
     set_permissions(&path, 0o600)?;
     file.write_all(message)
 }
+
+#[cfg(windows)]
+mod event_log {
+    use std::io;
+
+    use log::{Level, Record};
+    use log4rs::append::Append;
+    use windows_sys::Win32::System::EventLog::{
+        DeregisterEventSource, RegisterEventSourceW, ReportEventW, EVENTLOG_ERROR_TYPE,
+        EVENTLOG_WARNING_TYPE,
+    };
+
+    const EVENT_SOURCE: &str = "zellij";
+    const EVENT_SOURCE_KEY: &str =
+        "SYSTEM\\CurrentControlSet\\Services\\EventLog\\Application\\zellij";
+    // the message file of the .NET EventLog class (which ships with Windows), it formats every
+    // event as the one string reported with it
+    const EVENT_MESSAGE_FILE: &str =
+        "%SystemRoot%\\Microsoft.NET\\Framework64\\v4.0.30319\\EventLogMessages.dll";
+
+    fn to_wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    /// Reports the warnings and errors logged to the Application log of the Windows Event Log.
+    #[derive(Debug)]
+    pub struct EventLogAppender {
+        event_source: usize, // the HANDLE from RegisterEventSourceW
+    }
+
+    impl EventLogAppender {
+        pub fn new() -> io::Result<Self> {
+            let source_name = to_wide(EVENT_SOURCE);
+            let event_source =
+                unsafe { RegisterEventSourceW(std::ptr::null(), source_name.as_ptr()) };
+            if event_source.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(EventLogAppender {
+                event_source: event_source as usize,
+            })
+        }
+    }
+
+    impl Append for EventLogAppender {
+        fn append(&self, record: &Record) -> anyhow::Result<()> {
+            let event_type = match record.level() {
+                Level::Error => EVENTLOG_ERROR_TYPE,
+                Level::Warn => EVENTLOG_WARNING_TYPE,
+                _ => return Ok(()),
+            };
+            let message = to_wide(&format!(
+                "{} [{}:{}]: {}",
+                record.module_path().unwrap_or_default(),
+                record.file().unwrap_or_default(),
+                record.line().unwrap_or_default(),
+                record.args()
+            ));
+            let strings = [message.as_ptr()];
+            let reported = unsafe {
+                ReportEventW(
+                    self.event_source as _,
+                    event_type,
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    strings.len() as u16,
+                    0,
+                    strings.as_ptr(),
+                    std::ptr::null(),
+                )
+            };
+            if reported == 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            Ok(())
+        }
+        fn flush(&self) {}
+    }
+
+    impl Drop for EventLogAppender {
+        fn drop(&mut self) {
+            unsafe {
+                DeregisterEventSource(self.event_source as _);
+            }
+        }
+    }
+
+    pub fn register_event_source() -> io::Result<()> {
+        use windows_sys::Win32::System::Registry::{
+            RegCloseKey, RegCreateKeyExW, RegSetValueExW, HKEY, HKEY_LOCAL_MACHINE, KEY_SET_VALUE,
+            REG_DWORD, REG_EXPAND_SZ, REG_OPTION_NON_VOLATILE,
+        };
+        let check = |status: u32| -> io::Result<()> {
+            if status == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::from_raw_os_error(status as i32))
+            }
+        };
+        let key_name = to_wide(EVENT_SOURCE_KEY);
+        let mut key: HKEY = std::ptr::null_mut();
+        check(unsafe {
+            RegCreateKeyExW(
+                HKEY_LOCAL_MACHINE,
+                key_name.as_ptr(),
+                0,
+                std::ptr::null(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_SET_VALUE,
+                std::ptr::null(),
+                &mut key,
+                std::ptr::null_mut(),
+            )
+        })?;
+        let message_file = to_wide(EVENT_MESSAGE_FILE);
+        let types_supported: u32 = (EVENTLOG_ERROR_TYPE | EVENTLOG_WARNING_TYPE) as u32;
+        let result = check(unsafe {
+            RegSetValueExW(
+                key,
+                to_wide("EventMessageFile").as_ptr(),
+                0,
+                REG_EXPAND_SZ,
+                message_file.as_ptr() as *const u8,
+                (message_file.len() * 2) as u32,
+            )
+        })
+        .and_then(|_| {
+            check(unsafe {
+                RegSetValueExW(
+                    key,
+                    to_wide("TypesSupported").as_ptr(),
+                    0,
+                    REG_DWORD,
+                    &types_supported as *const u32 as *const u8,
+                    std::mem::size_of::<u32>() as u32,
+                )
+            })
+        });
+        unsafe {
+            RegCloseKey(key);
+        }
+        result
+    }
+}
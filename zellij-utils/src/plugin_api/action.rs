@@ -213,6 +213,8 @@ impl TryFrom<ProtobufAction> for Action {
                     Ok(Action::DumpScreen {
                         file_path,
                         include_scrollback,
+                        keep_ansi: payload.keep_ansi,
+                        crlf_line_endings: payload.crlf_line_endings,
                     })
                 },
                 _ => Err("Wrong payload for Action::DumpScreen"),
@@ -1121,11 +1123,15 @@ impl TryFrom<Action> for ProtobufAction {
             Action::DumpScreen {
                 file_path,
                 include_scrollback,
+                keep_ansi,
+                crlf_line_endings,
             } => Ok(ProtobufAction {
                 name: ProtobufActionName::DumpScreen as i32,
                 optional_payload: Some(OptionalPayload::DumpScreenPayload(DumpScreenPayload {
                     file_path,
                     include_scrollback,
+                    keep_ansi,
+                    crlf_line_endings,
                 })),
             }),
             Action::EditScrollback => Ok(ProtobufAction {
@@ -1792,7 +1798,17 @@ impl TryFrom<Action> for ProtobufAction {
             | Action::SwitchSession { .. }
             | Action::SaveSession
             | Action::ListTabs { .. }
-            | Action::CurrentTabInfo { .. } => Err("Unsupported action"),
+            | Action::CurrentTabInfo { .. }
+            | Action::SetSessionSecret { .. }
+            | Action::SetLogLevel { .. }
+            | Action::SetSessionEnv { .. }
+            | Action::SetIdleShutdownExempt { .. }
+            | Action::BreakPaneToWindow
+            | Action::ListClipboardHistory
+            | Action::PasteFromClipboardHistory { .. }
+            | Action::PickFromClipboardHistory { .. }
+            | Action::NewBackgroundPane { .. }
+            | Action::ListBackgroundJobs { .. } => Err("Unsupported action"),
         }
     }
 }
@@ -2869,6 +2885,8 @@ impl TryFrom<ProtobufTiledPaneLayout> for TiledPaneLayout {
             children_are_stacked: protobuf.children_are_stacked,
             is_expanded_in_stack: protobuf.is_expanded_in_stack,
             exclude_from_sync: protobuf.exclude_from_sync,
+            suppress_bell: protobuf.suppress_bell,
+            encoding: protobuf.encoding,
             run_instructions_to_ignore,
             hide_floating_panes: protobuf.hide_floating_panes,
             pane_initial_contents: protobuf.pane_initial_contents,
@@ -2901,6 +2919,8 @@ impl TryFrom<TiledPaneLayout> for ProtobufTiledPaneLayout {
             children_are_stacked: internal.children_are_stacked,
             is_expanded_in_stack: internal.is_expanded_in_stack,
             exclude_from_sync: internal.exclude_from_sync,
+            suppress_bell: internal.suppress_bell,
+            encoding: internal.encoding,
             hide_floating_panes: internal.hide_floating_panes,
             pane_initial_contents: internal.pane_initial_contents,
         })
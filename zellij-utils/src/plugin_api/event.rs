@@ -15,9 +15,10 @@ pub use super::generated_api::api::{
         LayoutInfo as ProtobufLayoutInfo, LayoutMetadata as ProtobufLayoutMetadata,
         LayoutParsingError as ProtobufLayoutParsingError,
         LayoutWithError as ProtobufLayoutWithError, ModeUpdatePayload as ProtobufModeUpdatePayload,
-        PaneContents as ProtobufPaneContents, PaneContentsEntry as ProtobufPaneContentsEntry,
-        PaneId as ProtobufPaneId, PaneInfo as ProtobufPaneInfo,
-        PaneManifest as ProtobufPaneManifest, PaneMetadata as ProtobufPaneMetadata,
+        PaneActivityPayload as ProtobufPaneActivityPayload, PaneContents as ProtobufPaneContents,
+        PaneContentsEntry as ProtobufPaneContentsEntry, PaneId as ProtobufPaneId,
+        PaneInfo as ProtobufPaneInfo, PaneManifest as ProtobufPaneManifest,
+        PaneMetadata as ProtobufPaneMetadata,
         PaneRenderReportPayload as ProtobufPaneRenderReportPayload,
         PaneScrollbackResponse as ProtobufPaneScrollbackResponse, PaneType as ProtobufPaneType,
         PluginConfigurationChangedPayload as ProtobufPluginConfigurationChangedPayload,
@@ -500,6 +501,24 @@ impl TryFrom<ProtobufEvent> for Event {
                 },
                 _ => Err("Malformed payload for PluginConfigurationChanged Event"),
             },
+            Some(ProtobufEventType::PaneBecameIdle) => match protobuf_event.payload {
+                Some(ProtobufEventPayload::PaneActivityPayload(payload)) => {
+                    let pane_id = payload
+                        .pane_id
+                        .ok_or("Malformed payload for the PaneBecameIdle Event")?;
+                    Ok(Event::PaneBecameIdle(PaneId::try_from(pane_id)?))
+                },
+                _ => Err("Malformed payload for the PaneBecameIdle Event"),
+            },
+            Some(ProtobufEventType::PaneBecameActive) => match protobuf_event.payload {
+                Some(ProtobufEventPayload::PaneActivityPayload(payload)) => {
+                    let pane_id = payload
+                        .pane_id
+                        .ok_or("Malformed payload for the PaneBecameActive Event")?;
+                    Ok(Event::PaneBecameActive(PaneId::try_from(pane_id)?))
+                },
+                _ => Err("Malformed payload for the PaneBecameActive Event"),
+            },
             None => Err("Unknown Protobuf Event"),
         }
     }
@@ -991,6 +1010,22 @@ impl TryFrom<Event> for ProtobufEvent {
                     payload: Some(event::Payload::PluginConfigurationChangedPayload(payload)),
                 })
             },
+            Event::PaneBecameIdle(pane_id) => Ok(ProtobufEvent {
+                name: ProtobufEventType::PaneBecameIdle as i32,
+                payload: Some(event::Payload::PaneActivityPayload(
+                    ProtobufPaneActivityPayload {
+                        pane_id: Some(pane_id.try_into()?),
+                    },
+                )),
+            }),
+            Event::PaneBecameActive(pane_id) => Ok(ProtobufEvent {
+                name: ProtobufEventType::PaneBecameActive as i32,
+                payload: Some(event::Payload::PaneActivityPayload(
+                    ProtobufPaneActivityPayload {
+                        pane_id: Some(pane_id.try_into()?),
+                    },
+                )),
+            }),
         }
     }
 }
@@ -1560,6 +1595,7 @@ impl TryFrom<ProtobufPaneInfo> for PaneInfo {
                     )
                 })
                 .collect(),
+            foreground_process: protobuf_pane_info.foreground_process,
         })
     }
 }
@@ -1603,6 +1639,7 @@ impl TryFrom<PaneInfo> for ProtobufPaneInfo {
                     index: index as u32,
                 })
                 .collect(),
+            foreground_process: pane_info.foreground_process,
         })
     }
 }
@@ -1893,6 +1930,8 @@ impl TryFrom<ProtobufEventType> for EventType {
             ProtobufEventType::CwdChanged => EventType::CwdChanged,
             ProtobufEventType::AvailableLayoutInfo => EventType::AvailableLayoutInfo,
             ProtobufEventType::PluginConfigurationChanged => EventType::PluginConfigurationChanged,
+            ProtobufEventType::PaneBecameIdle => EventType::PaneBecameIdle,
+            ProtobufEventType::PaneBecameActive => EventType::PaneBecameActive,
         })
     }
 }
@@ -1942,6 +1981,8 @@ impl TryFrom<EventType> for ProtobufEventType {
             EventType::CwdChanged => ProtobufEventType::CwdChanged,
             EventType::AvailableLayoutInfo => ProtobufEventType::AvailableLayoutInfo,
             EventType::PluginConfigurationChanged => ProtobufEventType::PluginConfigurationChanged,
+            EventType::PaneBecameIdle => ProtobufEventType::PaneBecameIdle,
+            EventType::PaneBecameActive => ProtobufEventType::PaneBecameActive,
         })
     }
 }
@@ -2499,6 +2540,7 @@ fn serialize_session_update_event_with_non_default_values() {
             plugin_url: None,
             is_selectable: true,
             index_in_pane_group: index_in_pane_group_1,
+            foreground_process: Some("cargo".to_owned()),
         },
         PaneInfo {
             id: 1,
@@ -2524,6 +2566,7 @@ fn serialize_session_update_event_with_non_default_values() {
             plugin_url: Some("i_am_a_fake_plugin".to_owned()),
             is_selectable: true,
             index_in_pane_group: index_in_pane_group_2,
+            foreground_process: None,
         },
     ];
     panes.insert(0, panes_list);
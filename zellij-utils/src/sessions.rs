@@ -1,9 +1,13 @@
 #[cfg(unix)]
 use crate::consts::is_ipc_socket;
+#[cfg(windows)]
+use crate::consts::session_pid_file_name;
+use crate::shared::create_private_dir;
 use crate::{
     consts::{
-        session_info_folder_for_session, session_layout_cache_file_name, session_pid_file_name,
-        ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
+        session_info_cache_file_name, session_info_folder_for_session,
+        session_layout_cache_file_name, SERVER_LOCKS_DIR_NAME, ZELLIJ_SESSION_INFO_CACHE_DIR,
+        ZELLIJ_SOCK_DIR,
     },
     envs,
     input::layout::Layout,
@@ -14,7 +18,7 @@ use humantime::format_duration;
 use crate::ipc::path_to_ipc_name;
 use interprocess::local_socket::{prelude::*, Stream as LocalSocketStream};
 use std::collections::HashMap;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{fs, io, process};
 use suggest::Suggest;
 
@@ -49,32 +53,39 @@ pub fn get_sessions() -> Result<Vec<(String, Duration)>, io::ErrorKind> {
     }
     #[cfg(windows)]
     {
-        match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
-            Ok(entries) => {
-                let mut sessions = Vec::new();
-                for entry in entries.filter_map(|e| e.ok()) {
-                    if entry.path().is_dir() {
-                        if let Some(name) = entry.file_name().to_str() {
-                            let ctime = std::fs::metadata(&entry.path())
-                                .ok()
-                                .and_then(|f| f.created().ok().or_else(|| f.modified().ok()))
-                                .and_then(|d| d.elapsed().ok())
-                                .unwrap_or_default();
-                            let duration = Duration::from_secs(ctime.as_secs());
-                            if assert_socket(name) {
-                                sessions.push((name.to_string(), duration));
-                            }
-                        }
-                    }
-                }
-                Ok(sessions)
-            },
-            Err(err) if io::ErrorKind::NotFound != err.kind() => Err(err.kind()),
-            Err(_) => Ok(Vec::with_capacity(0)),
+        let mut sessions = Vec::new();
+        for name in session_names_to_probe().map_err(|e| e.kind())? {
+            let ctime = std::fs::metadata(session_info_folder_for_session(&name))
+                .ok()
+                .and_then(|f| f.created().ok().or_else(|| f.modified().ok()))
+                .and_then(|d| d.elapsed().ok())
+                .unwrap_or_default();
+            let duration = Duration::from_secs(ctime.as_secs());
+            if assert_socket(&name) {
+                sessions.push((name, duration));
+            }
         }
+        Ok(sessions)
     }
 }
 
+/// The resurrectable sessions whose server went away without exiting, e.g. because the machine
+/// shut down, which leaves the metadata a session removes when it exits behind
+pub fn get_interrupted_sessions() -> Vec<String> {
+    let running_sessions: Vec<String> = get_sessions()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    get_resurrectable_sessions()
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| {
+            !running_sessions.contains(name) && session_info_cache_file_name(name).exists()
+        })
+        .collect()
+}
+
 pub fn get_resurrectable_sessions() -> Vec<(String, Duration)> {
     match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
         Ok(files_in_session_info_folder) => {
@@ -174,27 +185,44 @@ pub fn get_sessions_sorted_by_mtime() -> anyhow::Result<Vec<String>> {
     }
     #[cfg(windows)]
     {
-        match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
-            Ok(entries) => {
-                let mut sessions_with_mtime: Vec<(String, SystemTime)> = Vec::new();
-                for entry in entries {
-                    let entry = entry?;
-                    if entry.path().is_dir() {
-                        if let Some(name) = entry.file_name().to_str() {
-                            let file_modified_at = entry.metadata()?.modified()?;
-                            if assert_socket(name) {
-                                sessions_with_mtime.push((name.to_string(), file_modified_at));
-                            }
-                        }
-                    }
-                }
-                sessions_with_mtime.sort_by_key(|x| x.1);
-                let sessions = sessions_with_mtime.iter().map(|x| x.0.clone()).collect();
-                Ok(sessions)
-            },
-            Err(err) if io::ErrorKind::NotFound != err.kind() => Err(err.into()),
-            Err(_) => Ok(Vec::with_capacity(0)),
+        let mut sessions_with_mtime: Vec<(String, SystemTime)> = Vec::new();
+        for name in session_names_to_probe()? {
+            // sessions without a session info folder yet have only just started
+            let file_modified_at = fs::metadata(session_info_folder_for_session(&name))
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or_else(|_| SystemTime::now());
+            if assert_socket(&name) {
+                sessions_with_mtime.push((name, file_modified_at));
+            }
         }
+        sessions_with_mtime.sort_by_key(|x| x.1);
+        let sessions = sessions_with_mtime.iter().map(|x| x.0.clone()).collect();
+        Ok(sessions)
+    }
+}
+
+/// The names of the sessions whose servers may be running, to be confirmed by probing them.
+///
+/// Named pipes leave nothing on the filesystem, so these are the sessions with a live pipe.
+/// Only if the pipes cannot be listed are they looked for among the session info folders, which
+/// outlive their servers.
+#[cfg(windows)]
+fn session_names_to_probe() -> io::Result<Vec<String>> {
+    match crate::ipc::list_pipes_in(&ZELLIJ_SOCK_DIR) {
+        Ok(session_names) => return Ok(session_names),
+        Err(e) => log::warn!(
+            "Failed to list named pipes, looking for sessions in the session info cache instead: {}",
+            e
+        ),
+    }
+    match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
+        Ok(entries) => Ok(entries
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(|name| name.to_owned()))
+            .collect()),
+        Err(err) if io::ErrorKind::NotFound != err.kind() => Err(err),
+        Err(_) => Ok(Vec::with_capacity(0)),
     }
 }
 
@@ -208,23 +236,81 @@ fn assert_socket(name: &str) -> bool {
         }
     }
 
-    let path = &*ZELLIJ_SOCK_DIR.join(name);
-    let name_owned = name.to_owned();
+    probe_socket(&ZELLIJ_SOCK_DIR.join(name))
+}
+
+fn probe_socket(path: &std::path::Path) -> bool {
     let path_owned = path.to_path_buf();
     // Probe with a timeout to avoid blocking forever when the server is
     // gone but the named pipe or socket is still connectable (common on Windows).
     let (tx, rx) = std::sync::mpsc::channel();
     std::thread::spawn(move || {
-        let result = assert_socket_inner(&name_owned, &path_owned);
+        let result = assert_socket_inner(&path_owned);
         let _ = tx.send(result);
     });
     rx.recv_timeout(Duration::from_secs(3)).unwrap_or(false)
 }
 
+/// Whether a live server already serves the session at `socket_path`, which a server starting
+/// there must not take over
+pub fn session_server_is_running(socket_path: &std::path::Path) -> bool {
+    if server_lock_is_held(socket_path) {
+        return true;
+    }
+    // on windows the pipes derived from the path may be held by a session that was renamed away
+    // from it, which took its lock along
+    #[cfg(unix)]
+    return probe_socket(socket_path);
+    #[cfg(windows)]
+    return false;
+}
+
+lazy_static::lazy_static! {
+    static ref SERVER_LOCK: std::sync::Mutex<Option<fs::File>> = std::sync::Mutex::new(None);
+}
+
+/// Takes the lock on serving the session at `socket_path` for this process, in place of the one
+/// it held so far (if any). While another server holds it, this fails with
+/// [`io::ErrorKind::WouldBlock`].
+pub fn take_server_lock(socket_path: &std::path::Path) -> io::Result<()> {
+    let lock_file_name = server_lock_file_name(socket_path);
+    if let Some(server_locks_dir) = lock_file_name.parent() {
+        create_private_dir(server_locks_dir)?;
+    }
+    let lock_file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&lock_file_name)?;
+    lock_file.try_lock().map_err(|e| match e {
+        fs::TryLockError::WouldBlock => io::Error::from(io::ErrorKind::WouldBlock),
+        fs::TryLockError::Error(e) => e,
+    })?;
+    *SERVER_LOCK.lock().unwrap() = Some(lock_file);
+    Ok(())
+}
+
+fn server_lock_is_held(socket_path: &std::path::Path) -> bool {
+    let Ok(lock_file) = fs::File::open(server_lock_file_name(socket_path)) else {
+        return false;
+    };
+    matches!(
+        lock_file.try_lock_shared(),
+        Err(fs::TryLockError::WouldBlock)
+    )
+}
+
+fn server_lock_file_name(socket_path: &std::path::Path) -> std::path::PathBuf {
+    let session_name = socket_path.file_name().unwrap_or_default();
+    socket_path
+        .with_file_name(SERVER_LOCKS_DIR_NAME)
+        .join(session_name)
+}
+
 /// Check if the server process for a session is still alive using its PID file.
 /// Returns `Some(true)` if alive, `Some(false)` if dead, `None` if PID file missing.
 #[cfg(windows)]
-fn is_server_process_alive(session_name: &str) -> Option<bool> {
+pub(crate) fn is_server_process_alive(session_name: &str) -> Option<bool> {
     let pid_file = session_pid_file_name(session_name);
     let pid_str = fs::read_to_string(&pid_file).ok()?;
     let pid: u32 = pid_str.trim().parse().ok()?;
@@ -246,50 +332,9 @@ fn win_process_alive(pid: u32) -> bool {
     }
 }
 
-fn assert_socket_inner(_name: &str, path: &std::path::Path) -> bool {
-    let fs_name = match path_to_ipc_name(path) {
-        Ok(name) => name,
-        Err(_) => return false,
-    };
-    match LocalSocketStream::connect(fs_name) {
-        Ok(stream) => {
-            // On Windows, the server uses dual pipes: main (client→server) and
-            // reverse (server→client). We must connect to both so the server's
-            // listener thread doesn't block waiting for the reverse connection.
-            // The response comes on the reverse pipe, not the main pipe.
-            #[cfg(windows)]
-            {
-                let reverse_name = match crate::ipc::path_to_ipc_name_reverse(path) {
-                    Ok(name) => name,
-                    Err(_) => return false,
-                };
-                let reverse_stream = match LocalSocketStream::connect(reverse_name) {
-                    Ok(s) => s,
-                    Err(_) => return false,
-                };
-                let mut sender: IpcSenderWithContext<ClientToServerMsg> =
-                    IpcSenderWithContext::new(stream);
-                let _ = sender.send_client_msg(ClientToServerMsg::ConnStatus);
-                let mut receiver: IpcReceiverWithContext<ServerToClientMsg> =
-                    IpcReceiverWithContext::new(reverse_stream);
-                match receiver.recv_server_msg() {
-                    Some((ServerToClientMsg::Connected, _)) => true,
-                    None | Some((_, _)) => false,
-                }
-            }
-            #[cfg(not(windows))]
-            {
-                let mut sender: IpcSenderWithContext<ClientToServerMsg> =
-                    IpcSenderWithContext::new(stream);
-                let _ = sender.send_client_msg(ClientToServerMsg::ConnStatus);
-                let mut receiver: IpcReceiverWithContext<ServerToClientMsg> =
-                    sender.get_receiver();
-                match receiver.recv_server_msg() {
-                    Some((ServerToClientMsg::Connected, _)) => true,
-                    None | Some((_, _)) => false,
-                }
-            }
-        },
+fn assert_socket_inner(path: &std::path::Path) -> bool {
+    match query_server(path, ClientToServerMsg::ConnStatus) {
+        Ok(reply) => matches!(reply, Some(ServerToClientMsg::Connected)),
         Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
             drop(fs::remove_file(path));
             false
@@ -298,13 +343,85 @@ fn assert_socket_inner(_name: &str, path: &std::path::Path) -> bool {
     }
 }
 
+/// What `zellij list-sessions` shows of a running session, besides how long ago it was created
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SessionDetails {
+    pub connected_clients: usize,
+    pub tab_count: usize,
+    pub pane_count: usize, // terminal panes, plugins are not counted
+    pub web_client_connected: bool,
+}
+
+/// Asks the server of a running session how long it has been running and for its details, `None`
+/// if it does not answer in time (eg. because it is of an older version)
+fn session_details(name: &str) -> Option<(Duration, SessionDetails)> {
+    let path = ZELLIJ_SOCK_DIR.join(name);
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let reply = query_server(&path, ClientToServerMsg::ConnStatusDetailed);
+        let _ = tx.send(reply);
+    });
+    match rx.recv_timeout(Duration::from_secs(3)) {
+        Ok(Ok(Some(ServerToClientMsg::ConnStatusDetailed {
+            created_at,
+            connected_clients,
+            tab_count,
+            pane_count,
+            web_client_connected,
+        }))) => {
+            let created_at = UNIX_EPOCH + Duration::from_secs(created_at);
+            let running_for = created_at.elapsed().unwrap_or_default();
+            Some((
+                Duration::from_secs(running_for.as_secs()),
+                SessionDetails {
+                    connected_clients: connected_clients as usize,
+                    tab_count: tab_count as usize,
+                    pane_count: pane_count as usize,
+                    web_client_connected,
+                },
+            ))
+        },
+        _ => None,
+    }
+}
+
+/// Sends a message to the server of the session at `path` as a short-lived client and waits for
+/// its reply
+fn query_server(
+    path: &std::path::Path,
+    msg: ClientToServerMsg,
+) -> io::Result<Option<ServerToClientMsg>> {
+    let stream = LocalSocketStream::connect(path_to_ipc_name(path)?)?;
+    // On Windows, the server uses dual pipes: main (client→server) and
+    // reverse (server→client). We must connect to both so the server's
+    // listener thread doesn't block waiting for the reverse connection.
+    // The response comes on the reverse pipe, not the main pipe.
+    #[cfg(windows)]
+    {
+        let reverse_stream =
+            LocalSocketStream::connect(crate::ipc::path_to_ipc_name_reverse(path)?)?;
+        let mut sender: IpcSenderWithContext<ClientToServerMsg> = IpcSenderWithContext::new(stream);
+        let _ = sender.send_client_msg(msg);
+        let mut receiver: IpcReceiverWithContext<ServerToClientMsg> =
+            IpcReceiverWithContext::new(reverse_stream);
+        Ok(receiver.recv_server_msg().map(|(reply, _)| reply))
+    }
+    #[cfg(not(windows))]
+    {
+        let mut sender: IpcSenderWithContext<ClientToServerMsg> = IpcSenderWithContext::new(stream);
+        let _ = sender.send_client_msg(msg);
+        let mut receiver: IpcReceiverWithContext<ServerToClientMsg> = sender.get_receiver();
+        Ok(receiver.recv_server_msg().map(|(reply, _)| reply))
+    }
+}
+
 pub fn print_sessions(
-    mut sessions: Vec<(String, Duration, bool)>,
+    mut sessions: Vec<(String, Duration, bool, Option<SessionDetails>)>,
     no_formatting: bool,
     short: bool,
     reverse: bool,
 ) {
-    // (session_name, timestamp, is_dead)
+    // (session_name, timestamp, is_dead, details of running sessions)
     let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
     sessions.sort_by(|a, b| {
         if reverse {
@@ -316,11 +433,15 @@ pub fn print_sessions(
     });
     sessions
         .iter()
-        .for_each(|(session_name, timestamp, is_dead)| {
+        .for_each(|(session_name, timestamp, is_dead, details)| {
             if short {
                 println!("{}", session_name);
                 return;
             }
+            let details = details
+                .as_ref()
+                .map(|details| format!(" {}", format_session_details(details, no_formatting)))
+                .unwrap_or_default();
             if no_formatting {
                 let suffix = if curr_session == *session_name {
                     format!("(current)")
@@ -330,7 +451,7 @@ pub fn print_sessions(
                     String::new()
                 };
                 let timestamp = format!("[Created {} ago]", format_duration(*timestamp));
-                println!("{} {} {}", session_name, timestamp, suffix);
+                println!("{} {}{} {}", session_name, timestamp, details, suffix);
             } else {
                 let formatted_session_name = format!("\u{1b}[32;1m{}\u{1b}[m", session_name);
                 let suffix = if curr_session == *session_name {
@@ -344,11 +465,35 @@ pub fn print_sessions(
                     "[Created \u{1b}[35;1m{}\u{1b}[m ago]",
                     format_duration(*timestamp)
                 );
-                println!("{} {} {}", formatted_session_name, timestamp, suffix);
+                println!(
+                    "{} {}{} {}",
+                    formatted_session_name, timestamp, details, suffix
+                );
             }
         })
 }
 
+// eg. "[2 clients, 3 tabs, 7 panes, web client connected]"
+fn format_session_details(details: &SessionDetails, no_formatting: bool) -> String {
+    let count = |count: usize, noun: &str| {
+        let plural = if count == 1 { "" } else { "s" };
+        if no_formatting {
+            format!("{} {}{}", count, noun, plural)
+        } else {
+            format!("\u{1b}[35;1m{}\u{1b}[m {}{}", count, noun, plural)
+        }
+    };
+    let mut parts = vec![
+        count(details.connected_clients, "client"),
+        count(details.tab_count, "tab"),
+        count(details.pane_count, "pane"),
+    ];
+    if details.web_client_connected {
+        parts.push("web client connected".to_owned());
+    }
+    format!("[{}]", parts.join(", "))
+}
+
 pub fn print_sessions_with_index(sessions: Vec<String>) {
     let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
     for (i, session) in sessions.iter().enumerate() {
@@ -475,12 +620,17 @@ pub fn list_sessions(no_formatting: bool, short: bool, reverse: bool) {
     let exit_code = match get_sessions() {
         Ok(running_sessions) => {
             let resurrectable_sessions = get_resurrectable_sessions();
-            let mut all_sessions: HashMap<String, (Duration, bool)> = resurrectable_sessions
-                .iter()
-                .map(|(name, timestamp)| (name.clone(), (timestamp.clone(), true)))
-                .collect();
+            let mut all_sessions: HashMap<String, (Duration, bool, Option<SessionDetails>)> =
+                resurrectable_sessions
+                    .iter()
+                    .map(|(name, timestamp)| (name.clone(), (*timestamp, true, None)))
+                    .collect();
             for (session_name, duration) in running_sessions {
-                all_sessions.insert(session_name.clone(), (duration, false));
+                let (duration, details) = match session_details(&session_name) {
+                    Some((running_for, details)) => (running_for, Some(details)),
+                    None => (duration, None),
+                };
+                all_sessions.insert(session_name.clone(), (duration, false, details));
             }
             if all_sessions.is_empty() {
                 eprintln!("No active zellij sessions found.");
@@ -488,9 +638,9 @@ pub fn list_sessions(no_formatting: bool, short: bool, reverse: bool) {
             } else {
                 print_sessions(
                     all_sessions
-                        .iter()
-                        .map(|(name, (timestamp, is_dead))| {
-                            (name.clone(), timestamp.clone(), *is_dead)
+                        .into_iter()
+                        .map(|(name, (timestamp, is_dead, details))| {
+                            (name, timestamp, is_dead, details)
                         })
                         .collect(),
                     no_formatting,
@@ -640,6 +790,34 @@ pub fn validate_session_name(name: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Checks the socket path a server is started with: it must be a valid session name directly in
+/// the socket dir, the only place a server creates its socket or makes private
+pub fn validate_server_socket_path(path: &std::path::Path) -> Result<(), String> {
+    validate_socket_path_in(path, &ZELLIJ_SOCK_DIR)
+}
+
+fn validate_socket_path_in(
+    path: &std::path::Path,
+    sock_dir: &std::path::Path,
+) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err(format!("The socket path {:?} is not absolute.", path));
+    }
+    if path.parent() != Some(sock_dir) {
+        return Err(format!(
+            "The socket path {:?} is not in the socket dir {:?}.",
+            path, sock_dir
+        ));
+    }
+    match path.file_name().and_then(|n| n.to_str()) {
+        Some(session_name) => validate_session_name(session_name),
+        None => Err(format!(
+            "The socket path {:?} does not end in a session name.",
+            path
+        )),
+    }
+}
+
 pub fn assert_session_ne(name: &str) {
     if let Err(e) = validate_session_name(name) {
         eprintln!("{}", e);
@@ -838,3 +1016,41 @@ const NOUNS: &[&'static str] = &[
     "yak",
     "zebra",
 ];
+
+#[cfg(test)]
+mod sessions_tests {
+    use super::*;
+
+    #[test]
+    fn server_socket_path_must_be_a_session_name_in_the_socket_dir() {
+        let sock_dir = std::env::temp_dir().join("contract_version_1");
+        let is_valid = |path: &std::path::Path| validate_socket_path_in(path, &sock_dir).is_ok();
+        assert!(is_valid(&sock_dir.join("my-session")));
+        assert!(!is_valid(std::path::Path::new("my-session")));
+        assert!(!is_valid(&sock_dir.join("..").join("my-session")));
+        assert!(!is_valid(&sock_dir.join("my-session").join("..")));
+        assert!(!is_valid(&sock_dir.join("nested").join("my-session")));
+        assert!(!is_valid(&std::env::temp_dir().join("my-session")));
+        assert!(!is_valid(&sock_dir.join(" ")));
+    }
+
+    #[test]
+    fn a_server_lock_is_held_by_one_server_at_a_time() {
+        let sock_dir = tempfile::tempdir().unwrap();
+        let socket_path = sock_dir.path().join("my-session");
+        let renamed_socket_path = sock_dir.path().join("my-renamed-session");
+        assert!(!server_lock_is_held(&socket_path));
+        take_server_lock(&socket_path).unwrap();
+        assert!(server_lock_is_held(&socket_path));
+        // a lock file opened apart from the one held is locked out as another server would be
+        let other_lock_file = fs::File::open(server_lock_file_name(&socket_path)).unwrap();
+        assert!(matches!(
+            other_lock_file.try_lock(),
+            Err(fs::TryLockError::WouldBlock)
+        ));
+        take_server_lock(&renamed_socket_path).unwrap();
+        assert!(!server_lock_is_held(&socket_path));
+        assert!(server_lock_is_held(&renamed_socket_path));
+        *SERVER_LOCK.lock().unwrap() = None;
+    }
+}
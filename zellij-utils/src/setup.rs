@@ -223,6 +223,12 @@ pub const ZSH_AUTO_START_SCRIPT: &[u8] = include_bytes!(concat!(
     "assets/shell/auto-start.zsh"
 ));
 
+pub const POWERSHELL_AUTO_START_SCRIPT: &[u8] = include_bytes!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "assets/shell/auto-start.ps1"
+));
+
 pub fn add_layout_ext(s: &str) -> String {
     match s {
         c if s.ends_with(".kdl") => c.to_owned(),
@@ -321,10 +327,15 @@ pub struct Setup {
     pub dump_config: bool,
 
     /// Disables loading of configuration file at default location,
-    /// loads the defaults that zellij ships with
+    /// loads the defaults that zellij ships with, and removes what
+    /// sessions and web servers that are no longer running left behind
     #[clap(long, value_parser)]
     pub clean: bool,
 
+    /// With `--clean`, only lists what would be removed and exits
+    #[clap(long, value_parser, requires = "clean")]
+    pub dry_run: bool,
+
     /// Checks the configuration of zellij and displays
     /// currently used directories
     #[clap(long, value_parser)]
@@ -356,6 +367,41 @@ pub struct Setup {
     /// Generates auto-start script for the specified shell
     #[clap(long, value_name = "SHELL", value_parser)]
     pub generate_auto_start: Option<String>,
+
+    /// Asks for the shell, theme, keybindings and layout to use, offering the shells installed,
+    /// and writes them to the configuration file
+    #[clap(long, value_parser)]
+    pub wizard: bool,
+
+    /// Writes themes from the color schemes of Windows Terminal, a layout opening its profiles
+    /// and a config using both, from its settings.json or the one of the installed Windows
+    /// Terminal if unspecified
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser,
+        min_values = 0,
+        max_values = 1
+    )]
+    pub from_windows_terminal: Option<Option<PathBuf>>,
+
+    /// Registers zellij as a source of the Windows Event Log, for the `windows_event_log`
+    /// option (needs an elevated shell)
+    #[clap(long, value_parser)]
+    pub register_event_source: bool,
+
+    /// Starts zellij at logon to resurrect the sessions that were running when Windows shut
+    /// down, e.g. to install an update
+    #[clap(long, value_parser)]
+    pub install_autostart: bool,
+
+    /// Stops starting zellij at logon
+    #[clap(long, value_parser)]
+    pub uninstall_autostart: bool,
+
+    /// Resurrects in the background the sessions that were running when the machine shut down
+    #[clap(long, value_parser)]
+    pub resurrect_sessions: bool,
 }
 
 impl Setup {
@@ -383,6 +429,7 @@ impl Setup {
         // the attach CLI command can also have its own Options, we need to merge them if they
         // exist
         let cli_config_options = merge_attach_command_options(cli_config_options, &cli_args);
+        let cli_config_options = merge_session_template_options(cli_config_options, cli_args);
 
         let mut config_without_layout = config.clone();
         let (layout_info, mut config) =
@@ -435,8 +482,9 @@ impl Setup {
     }
 
     /// General setup helpers
-    pub fn from_cli(&self) -> Result<()> {
+    pub fn from_cli(&self, opts: &CliArgs) -> Result<()> {
         if self.clean {
+            Self::clean_up_stale_artifacts(self.dry_run);
             return Ok(());
         }
 
@@ -445,6 +493,14 @@ impl Setup {
             std::process::exit(0);
         }
 
+        if self.wizard {
+            Self::run_wizard(opts);
+        }
+
+        if let Some(settings) = &self.from_windows_terminal {
+            Self::import_windows_terminal(opts, settings.as_ref());
+        }
+
         if let Some(shell) = &self.generate_completion {
             Self::generate_completion(shell);
             std::process::exit(0);
@@ -465,6 +521,22 @@ impl Setup {
             std::process::exit(0);
         }
 
+        if self.register_event_source {
+            Self::register_event_source();
+        }
+
+        if self.install_autostart {
+            Self::install_autostart();
+        }
+
+        if self.uninstall_autostart {
+            Self::uninstall_autostart();
+        }
+
+        if self.resurrect_sessions {
+            Self::resurrect_sessions();
+        }
+
         Ok(())
     }
 
@@ -490,6 +562,200 @@ impl Setup {
         Ok(())
     }
 
+    #[cfg(not(target_family = "wasm"))]
+    fn clean_up_stale_artifacts(dry_run: bool) {
+        use crate::stale_artifacts::{find_stale_artifacts, remove_stale_artifacts};
+        if !dry_run {
+            remove_stale_artifacts();
+            return;
+        }
+        let stale_artifacts = find_stale_artifacts();
+        if stale_artifacts.is_empty() {
+            println!("Nothing to clean up");
+        }
+        for stale_artifact in stale_artifacts {
+            if stale_artifact.is_removable() {
+                println!("Would remove stale {}", stale_artifact);
+            } else {
+                println!("Cannot remove stale {}", stale_artifact);
+            }
+        }
+        std::process::exit(0);
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn clean_up_stale_artifacts(_dry_run: bool) {}
+
+    /// Runs before the configuration is read, so that a broken one can be replaced
+    #[cfg(not(target_family = "wasm"))]
+    fn run_wizard(opts: &CliArgs) {
+        let config_dir = opts
+            .config_dir
+            .clone()
+            .or_else(find_default_config_dir)
+            .unwrap_or_else(xdg_config_dir);
+        let config_file = opts
+            .config
+            .clone()
+            .unwrap_or_else(|| config_dir.join(CONFIG_NAME));
+        let layout_dir = config_dir.join("layouts");
+        let mut theme_names: Vec<String> = get_default_themes()
+            .inner()
+            .keys()
+            .filter(|theme_name| *theme_name != "default")
+            .cloned()
+            .collect();
+        theme_names.sort();
+        match crate::setup_wizard::run(&config_file, &layout_dir, theme_names) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!("Failed to write the configuration: {}", e);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn run_wizard(_opts: &CliArgs) {}
+
+    #[cfg(not(target_family = "wasm"))]
+    fn import_windows_terminal(opts: &CliArgs, settings: Option<&PathBuf>) {
+        use crate::windows_terminal::default_settings_path;
+        let settings = match settings.cloned().or_else(default_settings_path) {
+            Some(settings) => settings,
+            None => {
+                eprintln!("Windows Terminal's settings.json was not found, pass its path");
+                std::process::exit(1);
+            },
+        };
+        let config_dir = opts
+            .config_dir
+            .clone()
+            .or_else(find_default_config_dir)
+            .unwrap_or_else(xdg_config_dir);
+        let config_file = opts
+            .config
+            .clone()
+            .unwrap_or_else(|| config_dir.join(CONFIG_NAME));
+        match crate::windows_terminal::run(&settings, &config_file, &config_dir) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                eprintln!(
+                    "Failed to import the settings of Windows Terminal from {}: {}",
+                    settings.display(),
+                    e
+                );
+                std::process::exit(1);
+            },
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn import_windows_terminal(_opts: &CliArgs, _settings: Option<&PathBuf>) {}
+
+    #[cfg(windows)]
+    fn register_event_source() {
+        match crate::logging::register_windows_event_source() {
+            Ok(()) => {
+                println!("Registered zellij as a source of the Windows Event Log");
+                std::process::exit(0);
+            },
+            Err(e) => {
+                eprintln!(
+                    "Failed to register zellij as a source of the Windows Event Log: {}",
+                    e
+                );
+                eprintln!("Try again from an elevated shell (Run as administrator)");
+                std::process::exit(1);
+            },
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn register_event_source() {
+        eprintln!("The Windows Event Log is only available on Windows");
+        std::process::exit(1);
+    }
+
+    #[cfg(windows)]
+    fn install_autostart() {
+        match crate::autostart::install() {
+            Ok(()) => {
+                println!("zellij will resurrect the sessions interrupted by a shutdown at logon");
+                std::process::exit(0);
+            },
+            Err(e) => {
+                eprintln!("Failed to start zellij at logon: {}", e);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    #[cfg(windows)]
+    fn uninstall_autostart() {
+        match crate::autostart::uninstall() {
+            Ok(()) => {
+                println!("zellij will no longer start at logon");
+                std::process::exit(0);
+            },
+            Err(e) => {
+                eprintln!("Failed to stop starting zellij at logon: {}", e);
+                std::process::exit(1);
+            },
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn install_autostart() {
+        eprintln!("Starting zellij at logon is only available on Windows");
+        std::process::exit(1);
+    }
+
+    #[cfg(not(windows))]
+    fn uninstall_autostart() {
+        eprintln!("Starting zellij at logon is only available on Windows");
+        std::process::exit(1);
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn resurrect_sessions() {
+        let zellij = match std::env::current_exe() {
+            Ok(zellij) => zellij,
+            Err(e) => {
+                eprintln!("Failed to find the zellij executable: {}", e);
+                std::process::exit(1);
+            },
+        };
+        let mut failed = false;
+        for session_name in crate::sessions::get_interrupted_sessions() {
+            let mut command = std::process::Command::new(&zellij);
+            command.args(["attach", "--create-background", &session_name]);
+            #[cfg(windows)]
+            {
+                // at logon there is no console to show the sessions' servers in
+                use std::os::windows::process::CommandExt;
+                command.creation_flags(windows_sys::Win32::System::Threading::CREATE_NO_WINDOW);
+            }
+            match command.status() {
+                Ok(status) if status.success() => {
+                    println!("Resurrected session {}", session_name);
+                },
+                Ok(status) => {
+                    eprintln!("Failed to resurrect session {}: {}", session_name, status);
+                    failed = true;
+                },
+                Err(e) => {
+                    eprintln!("Failed to resurrect session {}: {}", session_name, e);
+                    failed = true;
+                },
+            }
+        }
+        std::process::exit(if failed { 1 } else { 0 });
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn resurrect_sessions() {}
+
     pub fn check_defaults_config(opts: &CliArgs, config_options: &Options) -> std::io::Result<()> {
         let data_dir = opts.data_dir.clone().unwrap_or_else(get_default_data_dir);
         let config_dir = opts.config_dir.clone().or_else(find_default_config_dir);
@@ -660,6 +926,9 @@ impl Setup {
             Shell::Zsh => {
                 let _ = out.write_all(ZSH_AUTO_START_SCRIPT);
             },
+            Shell::PowerShell => {
+                let _ = out.write_all(POWERSHELL_AUTO_START_SCRIPT);
+            },
             _ => {},
         }
     }
@@ -677,10 +946,29 @@ impl Setup {
             .or_else(|| get_layout_dir(find_default_config_dir()))
             // Try to get an absolute path, else let the resolution code figure this out.
             .map(|d| d.canonicalize().unwrap_or(d));
+        let session_template = match &cli_args.template {
+            Some(template_name) => Some(
+                config
+                    .session_templates
+                    .get(template_name)
+                    .cloned()
+                    .ok_or_else(|| {
+                        ConfigError::SessionTemplateError(format!(
+                            "No session template named \"{}\" was found.",
+                            template_name
+                        ))
+                    })?,
+            ),
+            None => None,
+        };
         // the chosen layout can either be a path relative to the layout_dir or a name of one
         // of our assets, this distinction is made when parsing the layout - TODO: ideally, this
         // logic should not be split up and all the decisions should happen here
-        let (layout_info, chosen_layout) = if let Some(chosen_layout) = cli_args.layout.clone() {
+        let (layout_info, chosen_layout) = if let Some(chosen_layout) = cli_args
+            .layout
+            .clone()
+            .or_else(|| session_template.as_ref().and_then(|t| t.layout.clone()))
+        {
             let layout_info = LayoutInfo::from_cli(
                 &layout_dir,
                 &Some(chosen_layout.clone()),
@@ -695,17 +983,32 @@ impl Setup {
             let layout_info = LayoutInfo::from_config(&layout_dir, &chosen_layout);
             (layout_info, chosen_layout)
         };
+        let (layout_info, config) = match (session_template, &cli_args.template) {
+            (Some(session_template), Some(template_name)) => {
+                let layout_info =
+                    session_template.layout_info(template_name, layout_info, &layout_dir)?;
+                let mut config = config;
+                // the server inherits these from the client that spawns it, and its panes from it
+                config.env = config.env.merge(session_template.env);
+                (layout_info, config)
+            },
+            _ => (layout_info, config),
+        };
         match layout_info {
             Some(LayoutInfo::Url(ref layout_url)) => {
                 Layout::from_url(layout_url, config).map(|(_layout, config)| (layout_info, config))
             },
+            Some(LayoutInfo::Stringified(ref stringified_layout)) => {
+                Layout::from_stringified_layout(stringified_layout, config)
+                    .map(|(_layout, config)| (layout_info, config))
+            },
             _ => Layout::from_path_or_default(chosen_layout.as_ref(), layout_dir.clone(), config)
                 .map(|(_layout, config)| (layout_info, config)),
         }
     }
     fn handle_setup_commands(cli_args: &CliArgs) {
         if let Some(Command::Setup(ref setup)) = &cli_args.command {
-            setup.from_cli().map_or_else(
+            setup.from_cli(cli_args).map_or_else(
                 |e| {
                     eprintln!("{:?}", e);
                     process::exit(1);
@@ -738,6 +1041,28 @@ fn merge_attach_command_options(
     cli_config_options
 }
 
+// a session template is started under its own name, or attached to if it is already running
+fn merge_session_template_options(
+    cli_config_options: Option<Options>,
+    cli_args: &CliArgs,
+) -> Option<Options> {
+    match &cli_args.template {
+        Some(template_name) => {
+            let template_options = Options {
+                session_name: Some(template_name.clone()),
+                attach_to_session: Some(true),
+                ..Default::default()
+            };
+            Some(
+                cli_config_options
+                    .unwrap_or_default()
+                    .merge(template_options),
+            )
+        },
+        None => cli_config_options,
+    }
+}
+
 #[cfg(test)]
 mod setup_test {
     use super::Setup;
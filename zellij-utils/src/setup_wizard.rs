@@ -0,0 +1,470 @@
+//! `zellij setup --wizard`: asks for the shell, theme, keybindings and layout to use and writes
+//! them to the config (and layout) files, detecting what is installed on the machine.
+use crate::data::{BareKey, InputMode, KeyWithModifier};
+use crate::input::options::{ConsoleKeyAction, Options};
+use kdl::KdlValue;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const UNLOCK_KEY: &str = "Ctrl g";
+const BUILTIN_LAYOUTS: &[&str] = &["default", "compact"];
+
+/// A shell found on this machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedShell {
+    pub name: String,
+    pub command: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl DetectedShell {
+    fn new(name: &str, command: PathBuf) -> Self {
+        DetectedShell {
+            name: name.to_owned(),
+            command,
+            args: vec![],
+            cwd: None,
+        }
+    }
+}
+
+/// What the wizard asked for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WizardChoices {
+    pub default_shell: Option<PathBuf>,
+    pub theme: Option<String>,
+    /// Start in locked mode, where only this key (to unlock) is not sent to the pane
+    pub unlock_key: Option<KeyWithModifier>,
+    /// Send Ctrl+C and Ctrl+Break to the pane rather than detaching or quitting
+    pub forward_console_keys: bool,
+    pub default_layout: Option<String>,
+}
+
+impl WizardChoices {
+    pub fn to_config(&self) -> String {
+        let options = Options {
+            default_shell: self.default_shell.clone(),
+            theme: self.theme.clone(),
+            default_layout: self.default_layout.as_ref().map(PathBuf::from),
+            default_mode: self.unlock_key.as_ref().map(|_| InputMode::Locked),
+            ctrl_c_action: Some(ConsoleKeyAction::Forward).filter(|_| self.forward_console_keys),
+            ctrl_break_action: Some(ConsoleKeyAction::Forward)
+                .filter(|_| self.forward_console_keys),
+            ..Default::default()
+        };
+        let mut config = String::from(
+            "// Written by `zellij setup --wizard`, `zellij setup --dump-config` shows every option\n\n",
+        );
+        for node in options.to_kdl(false) {
+            config.push_str(&format!("{}\n", node));
+        }
+        // the default keybindings lock and unlock with Ctrl g already
+        let default_unlock_key = KeyWithModifier::from_str(UNLOCK_KEY).ok();
+        if let Some(unlock_key) = self
+            .unlock_key
+            .as_ref()
+            .filter(|k| Some(*k) != default_unlock_key.as_ref())
+        {
+            let unlock_key = quoted(&unlock_key.to_kdl());
+            let default_unlock_key = quoted(UNLOCK_KEY);
+            config.push_str(&format!(
+                "\nkeybinds {{\n    locked {{\n        unbind {default_unlock_key}\n        bind {unlock_key} {{ SwitchToMode \"Normal\"; }}\n    }}\n    shared_except \"locked\" {{\n        unbind {default_unlock_key}\n        bind {unlock_key} {{ SwitchToMode \"Locked\"; }}\n    }}\n}}\n"
+            ));
+        }
+        config
+    }
+}
+
+/// A layout opening a tab for each of the shells, focusing the first
+pub fn shells_layout(shells: &[DetectedShell]) -> String {
+    let mut layout = String::from(
+        "layout {\n    default_tab_template {\n        pane size=1 borderless=true {\n            plugin location=\"tab-bar\"\n        }\n        children\n        pane size=2 borderless=true {\n            plugin location=\"status-bar\"\n        }\n    }\n",
+    );
+    for (i, shell) in shells.iter().enumerate() {
+        let focus = if i == 0 { " focus=true" } else { "" };
+        let command = quoted(&shell.command.display().to_string());
+        layout.push_str(&format!(
+            "    tab name={}{} {{\n",
+            quoted(&shell.name),
+            focus
+        ));
+        let cwd = shell
+            .cwd
+            .as_ref()
+            .map(|cwd| format!(" cwd={}", quoted(&cwd.display().to_string())))
+            .unwrap_or_default();
+        if shell.args.is_empty() {
+            layout.push_str(&format!("        pane command={}{}\n", command, cwd));
+        } else {
+            let args: Vec<String> = shell.args.iter().map(|arg| quoted(arg)).collect();
+            layout.push_str(&format!(
+                "        pane command={}{} {{\n            args {}\n        }}\n",
+                command,
+                cwd,
+                args.join(" ")
+            ));
+        }
+        layout.push_str("    }\n");
+    }
+    layout.push_str("}\n");
+    layout
+}
+
+/// Parses the key to unlock zellij with, refusing those the shell or the console need
+pub fn parse_unlock_key(key: &str) -> Result<KeyWithModifier, String> {
+    if key.to_lowercase().contains("break") {
+        return Err("Ctrl+Break stops the program running in the pane".to_owned());
+    }
+    let key = KeyWithModifier::from_str(key.trim())
+        .map_err(|e| format!("'{}' is not a key: {}", key.trim(), e))?;
+    if key.is_key_with_ctrl_modifier(BareKey::Char('c')) {
+        Err("Ctrl+C interrupts the program running in the pane".to_owned())
+    } else if key.is_key_with_ctrl_modifier(BareKey::Pause) {
+        Err("Ctrl+Pause is Ctrl+Break, which stops the program running in the pane".to_owned())
+    } else if key.is_key_with_ctrl_modifier(BareKey::Char('z')) {
+        Err("Ctrl+Z ends the input of cmd and PowerShell and suspends programs in WSL".to_owned())
+    } else if key.has_no_modifiers() {
+        Err("a key without modifiers could not be typed in the pane anymore".to_owned())
+    } else {
+        Ok(key)
+    }
+}
+
+/// The shells installed on this machine
+#[cfg(windows)]
+pub fn detect_shells() -> Vec<DetectedShell> {
+    let mut shells = vec![];
+    if let Some(pwsh) = find_in_path("pwsh.exe") {
+        shells.push(DetectedShell::new("PowerShell 7", pwsh));
+    }
+    if let Some(powershell) = find_in_path("powershell.exe") {
+        shells.push(DetectedShell::new("Windows PowerShell", powershell));
+    }
+    if let Some(cmd) = env::var_os("ComSpec")
+        .map(PathBuf::from)
+        .or_else(|| find_in_path("cmd.exe"))
+    {
+        shells.push(DetectedShell::new("Command Prompt", cmd));
+    }
+    let git_bash = env::var_os("ProgramFiles")
+        .map(|program_files| Path::new(&program_files).join("Git\\bin\\bash.exe"))
+        .filter(|bash| bash.is_file())
+        .or_else(|| {
+            // git.exe is in <git>\cmd, bash.exe in <git>\bin
+            find_in_path("git.exe")
+                .and_then(|git| Some(git.parent()?.parent()?.join("bin\\bash.exe")))
+                .filter(|bash| bash.is_file())
+        });
+    if let Some(git_bash) = git_bash {
+        shells.push(DetectedShell::new("Git Bash", git_bash));
+    }
+    if let Some(wsl) = find_in_path("wsl.exe") {
+        for distro in wsl_distros(&wsl) {
+            shells.push(DetectedShell {
+                name: distro.clone(),
+                command: wsl.clone(),
+                args: vec!["-d".to_owned(), distro],
+                cwd: None,
+            });
+        }
+    }
+    shells
+}
+
+/// The shells installed on this machine, the user's current shell first
+#[cfg(not(windows))]
+pub fn detect_shells() -> Vec<DetectedShell> {
+    let mut shells = vec![];
+    if let Some(shell) = env::var_os("SHELL").map(PathBuf::from) {
+        let name = shell
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| shell.display().to_string());
+        shells.push(DetectedShell::new(&name, shell));
+    }
+    for name in ["bash", "zsh", "fish", "nu", "pwsh"] {
+        if shells.iter().any(|shell| shell.name == name) {
+            continue;
+        }
+        if let Some(shell) = find_in_path(name) {
+            shells.push(DetectedShell::new(name, shell));
+        }
+    }
+    shells
+}
+
+fn find_in_path(executable: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    env::split_paths(&path)
+        .map(|dir| dir.join(executable))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(windows)]
+fn wsl_distros(wsl: &Path) -> Vec<String> {
+    use std::os::windows::process::CommandExt;
+    let output = std::process::Command::new(wsl)
+        .args(["--list", "--quiet"])
+        .creation_flags(windows_sys::Win32::System::Threading::CREATE_NO_WINDOW)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => parse_wsl_distros(&output.stdout),
+        _ => vec![],
+    }
+}
+
+/// `wsl --list` writes UTF-16 regardless of the console's code page
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_wsl_distros(stdout: &[u8]) -> Vec<String> {
+    let utf16: Vec<u16> = stdout
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    String::from_utf16_lossy(&utf16)
+        .lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}'))
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+pub(crate) fn quoted(text: &str) -> String {
+    KdlValue::String(text.to_owned()).to_string()
+}
+
+/// Asks for the choices on the terminal and writes them to `config_file`, and the shells layout
+/// to `layout_dir` if wanted
+pub fn run(config_file: &Path, layout_dir: &Path, theme_names: Vec<String>) -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut choices = WizardChoices::default();
+
+    let shells = detect_shells();
+    if shells.is_empty() {
+        println!("No shell was found, zellij will start the one of your system");
+    } else {
+        // default_shell takes no arguments, so WSL distributions are offered as wsl.exe, which
+        // starts the default one
+        let mut default_shells: Vec<DetectedShell> = vec![];
+        for shell in &shells {
+            if shell.args.is_empty() {
+                default_shells.push(shell.clone());
+            } else if !default_shells.iter().any(|s| s.command == shell.command) {
+                default_shells.push(DetectedShell::new("WSL", shell.command.clone()));
+            }
+        }
+        println!("Shells found:");
+        for (i, shell) in default_shells.iter().enumerate() {
+            println!("  {}) {}", i + 1, shell.name);
+        }
+        let shell = ask_for_choice(&mut input, "Default shell", default_shells.len())?;
+        choices.default_shell = Some(default_shells[shell].command.clone());
+    }
+
+    println!("Themes: default, {}", theme_names.join(", "));
+    loop {
+        let theme = ask(&mut input, "Theme", "default")?;
+        if theme == "default" {
+            break;
+        } else if theme_names.contains(&theme) {
+            choices.theme = Some(theme);
+            break;
+        }
+        println!("There is no theme called '{}'", theme);
+    }
+
+    println!("Keybindings:");
+    println!("  1) zellij's, modes are entered with Ctrl and a letter (Ctrl p, Ctrl t, ...)");
+    println!("  2) unlock first, every key goes to the shell until an unlock key is pressed");
+    if ask_for_choice(&mut input, "Keybindings", 2)? == 1 {
+        loop {
+            let key = ask(&mut input, "Unlock key", UNLOCK_KEY)?;
+            match parse_unlock_key(&key) {
+                Ok(key) => {
+                    choices.unlock_key = Some(key);
+                    break;
+                },
+                Err(e) => println!("Choose another key, {}", e),
+            }
+        }
+    }
+    choices.forward_console_keys = ask_yes_no(
+        &mut input,
+        "Send Ctrl+C and Ctrl+Break to the shell rather than detaching from or quitting the session?",
+        true,
+    )?;
+
+    println!("Layouts: {}", BUILTIN_LAYOUTS.join(", "));
+    let mut write_shells_layout = false;
+    if shells.len() > 1 {
+        write_shells_layout = ask_yes_no(
+            &mut input,
+            "Add a 'shells' layout opening a tab for each shell found?",
+            false,
+        )?;
+        if write_shells_layout {
+            println!("Layouts: {}, shells", BUILTIN_LAYOUTS.join(", "));
+        }
+    }
+    loop {
+        let layout = ask(&mut input, "Default layout", "default")?;
+        if layout == "default" {
+            break;
+        } else if BUILTIN_LAYOUTS.contains(&layout.as_str())
+            || (write_shells_layout && layout == "shells")
+        {
+            choices.default_layout = Some(layout);
+            break;
+        }
+        println!("There is no layout called '{}'", layout);
+    }
+
+    if write_shells_layout {
+        fs::create_dir_all(layout_dir)?;
+        let layout_file = layout_dir.join("shells.kdl");
+        write_file(&mut input, &layout_file, &shells_layout(&shells))?;
+    }
+    if let Some(config_dir) = config_file.parent() {
+        fs::create_dir_all(config_dir)?;
+    }
+    write_file(&mut input, config_file, &choices.to_config())?;
+    Ok(())
+}
+
+pub(crate) fn write_file(input: &mut impl BufRead, path: &Path, contents: &str) -> io::Result<()> {
+    if path.exists() {
+        let question = format!(
+            "{} exists, replace it (keeping a .bak copy)?",
+            path.display()
+        );
+        if !ask_yes_no(input, &question, false)? {
+            println!("Left {} as it was", path.display());
+            return Ok(());
+        }
+        fs::copy(path, path.with_extension("kdl.bak"))?;
+    }
+    fs::write(path, contents)?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
+/// Reads an answer, `default` if the line is empty or there is no more input
+fn ask(input: &mut impl BufRead, question: &str, default: &str) -> io::Result<String> {
+    print!("{} [{}]: ", question, default);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+    if answer.is_empty() {
+        Ok(default.to_owned())
+    } else {
+        Ok(answer.to_owned())
+    }
+}
+
+/// Reads the index of one of `count` numbered options, the first by default
+fn ask_for_choice(input: &mut impl BufRead, question: &str, count: usize) -> io::Result<usize> {
+    loop {
+        match ask(input, question, "1")?.parse::<usize>() {
+            Ok(choice) if choice >= 1 && choice <= count => return Ok(choice - 1),
+            _ => println!("Choose a number from 1 to {}", count),
+        }
+    }
+}
+
+fn ask_yes_no(input: &mut impl BufRead, question: &str, default: bool) -> io::Result<bool> {
+    loop {
+        let answer = ask(input, question, if default { "Y/n" } else { "y/N" })?;
+        match answer.to_lowercase().as_str() {
+            "y/n" => return Ok(default),
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("Answer yes or no"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod setup_wizard_tests {
+    use super::*;
+    use crate::input::config::Config;
+    use crate::input::layout::Layout;
+
+    #[test]
+    fn unlock_keys_the_shell_needs_are_refused() {
+        assert!(parse_unlock_key("Ctrl c").is_err());
+        assert!(parse_unlock_key("Ctrl Break").is_err());
+        assert!(parse_unlock_key("Ctrl Pause").is_err());
+        assert!(parse_unlock_key("g").is_err());
+        assert!(parse_unlock_key("Ctrl nope").is_err());
+        assert_eq!(
+            parse_unlock_key("Alt g").unwrap(),
+            KeyWithModifier::new(BareKey::Char('g')).with_alt_modifier()
+        );
+    }
+
+    #[test]
+    fn wizard_config_is_a_valid_config() {
+        let choices = WizardChoices {
+            default_shell: Some(PathBuf::from("C:\\Program Files\\PowerShell\\7\\pwsh.exe")),
+            theme: Some("dracula".to_owned()),
+            unlock_key: Some(KeyWithModifier::new(BareKey::Char('g')).with_alt_modifier()),
+            forward_console_keys: true,
+            default_layout: Some("compact".to_owned()),
+        };
+        let config = Config::from_kdl(&choices.to_config(), None).unwrap();
+        assert_eq!(config.options.default_shell, choices.default_shell);
+        assert_eq!(config.options.theme, choices.theme);
+        assert_eq!(config.options.default_mode, Some(InputMode::Locked));
+        assert_eq!(
+            config.options.ctrl_break_action,
+            Some(ConsoleKeyAction::Forward)
+        );
+        let alt_g = KeyWithModifier::new(BareKey::Char('g')).with_alt_modifier();
+        let ctrl_g = KeyWithModifier::new(BareKey::Char('g')).with_ctrl_modifier();
+        assert!(config
+            .keybinds
+            .get_actions_for_key_in_mode(&InputMode::Locked, &alt_g)
+            .is_some());
+        assert!(config
+            .keybinds
+            .get_actions_for_key_in_mode(&InputMode::Locked, &ctrl_g)
+            .is_none());
+        assert!(config
+            .keybinds
+            .get_actions_for_key_in_mode(&InputMode::Pane, &alt_g)
+            .is_some());
+    }
+
+    #[test]
+    fn shells_layout_is_a_valid_layout() {
+        let shells = vec![
+            DetectedShell::new(
+                "PowerShell 7",
+                PathBuf::from("C:\\Program Files\\PowerShell\\7\\pwsh.exe"),
+            ),
+            DetectedShell {
+                name: "Ubuntu".to_owned(),
+                command: PathBuf::from("C:\\Windows\\system32\\wsl.exe"),
+                args: vec!["-d".to_owned(), "Ubuntu".to_owned()],
+                cwd: None,
+            },
+        ];
+        let layout = Layout::from_kdl(&shells_layout(&shells), None, None, None).unwrap();
+        assert_eq!(layout.tabs.len(), 2);
+        assert_eq!(layout.tabs[1].0, Some("Ubuntu".to_owned()));
+    }
+
+    #[test]
+    fn wsl_distros_are_read_as_utf16() {
+        let stdout: Vec<u8> = "Ubuntu\r\nDebian\r\n\r\n"
+            .encode_utf16()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        assert_eq!(parse_wsl_distros(&stdout), vec!["Ubuntu", "Debian"]);
+    }
+}
@@ -25,6 +25,79 @@ mod unix_only {
         permissions.set_mode(mode);
         fs::set_permissions(path, permissions)
     }
+
+    /// Creates `path` and its missing parents accessible to the current user only. Dirs that
+    /// already exist are left as they are, they may not be ours to restrict.
+    pub fn create_private_dir(path: &Path) -> io::Result<()> {
+        use std::os::unix::fs::DirBuilderExt;
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(path)
+    }
+}
+
+#[cfg(windows)]
+pub use windows_only::*;
+
+#[cfg(windows)]
+mod windows_only {
+    use std::os::windows::process::CommandExt;
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+    use std::{fs, io};
+    use windows_sys::Win32::Foundation::ERROR_ACCESS_DENIED;
+    use windows_sys::Win32::System::Threading::{
+        CREATE_BREAKAWAY_FROM_JOB, CREATE_NEW_PROCESS_GROUP, CREATE_NO_WINDOW,
+    };
+
+    /// Spawns `cmd` in the background, in place of the fork/daemonize we do on unix: the child
+    /// gets a hidden console of its own instead of ours, its own process group so our Ctrl-C
+    /// doesn't reach it, and no handles to our stdio. It also leaves our job object when that
+    /// is allowed, since terminals and sshd kill the whole job when the launching shell exits.
+    pub fn spawn_detached(cmd: &mut Command) -> io::Result<Child> {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        cmd.creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW | CREATE_BREAKAWAY_FROM_JOB);
+        match cmd.spawn() {
+            Err(e) if e.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32) => {
+                // the job does not allow breaking away, stay in it
+                cmd.creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW);
+                cmd.spawn()
+            },
+            result => result,
+        }
+    }
+
+    /// Creates `path` (and its missing parents) accessible to the current user only, unless it
+    /// already exists: the ACL of `path` grants only the current user access, which everything
+    /// created in it inherits
+    pub fn create_private_dir(path: &Path) -> io::Result<()> {
+        use crate::ipc::CurrentUserSecurityAttributes;
+        use std::os::windows::ffi::OsStrExt;
+        use windows_sys::Win32::Foundation::ERROR_ALREADY_EXISTS;
+        use windows_sys::Win32::Storage::FileSystem::CreateDirectoryW;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut security_attributes = CurrentUserSecurityAttributes::for_directory()?;
+        let path_wide: Vec<u16> = path
+            .as_os_str()
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect();
+        if unsafe { CreateDirectoryW(path_wide.as_ptr(), security_attributes.as_mut_ptr() as _) }
+            == 0
+        {
+            let e = io::Error::last_os_error();
+            if e.raw_os_error() != Some(ERROR_ALREADY_EXISTS as i32) {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(not(unix))]
@@ -32,6 +105,27 @@ pub fn set_permissions(_path: &std::path::Path, _mode: u32) -> std::io::Result<(
     Ok(())
 }
 
+/// Replaces the contents of `path` with `contents` all at once: readers find either the old or
+/// the new contents in full, never a partially written file
+pub fn write_atomically(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut temp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_file_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_file_name);
+    let written = std::fs::File::create(&temp_path).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    match written.and_then(|_| std::fs::rename(&temp_path, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(e)
+        },
+    }
+}
+
 pub fn ansi_len(s: &str) -> usize {
     from_utf8(&strip(s).unwrap()).unwrap().width()
 }
@@ -72,7 +166,7 @@ pub fn make_terminal_title(pane_title: &str) -> String {
         "\u{1b}]0;{}{}\u{07}",
         get_session_name()
             .map(|n| if pane_title.is_empty() {
-                format!("{}", n)
+                n
             } else {
                 format!("{} | ", n)
             })
@@ -81,6 +175,23 @@ pub fn make_terminal_title(pane_title: &str) -> String {
     )
 }
 
+/// The OSC 2 sequence setting the title of the host terminal window to `session: tab`, which is
+/// how Windows titles it instead of after the focused pane (`make_terminal_title`).
+pub fn make_tab_window_title(tab_name: &str) -> String {
+    let tab_name = clean_string_from_control_and_linebreak(tab_name);
+    format!(
+        "\u{1b}]2;{}{}\u{07}",
+        get_session_name()
+            .map(|n| if tab_name.is_empty() {
+                n
+            } else {
+                format!("{}: ", n)
+            })
+            .unwrap_or_default(),
+        tab_name
+    )
+}
+
 // Colors
 pub mod colors {
     pub const WHITE: u8 = 255;
@@ -0,0 +1,340 @@
+//! Finds what sessions and web servers that died without cleaning up after themselves leave
+//! behind, which would otherwise make them look like they are still running.
+use crate::consts::{
+    is_ipc_socket, SESSION_INFO_CACHE_FILE_NAME, SESSION_LAYOUT_CACHE_FILE_NAME,
+    SESSION_LOCK_FILE_NAME, SESSION_PID_FILE_NAME, SESSION_SERVER_INFO_FILE_NAME,
+    ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
+};
+use crate::sessions::get_sessions;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// artifacts younger than this may belong to a session or web server that is still starting up
+const GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StaleArtifact {
+    /// The socket (or on windows, the marker file) of a session whose server is gone
+    SessionSocket(PathBuf),
+    /// The metadata, server pid and info or lock of a session that is gone but can still be resurrected
+    SessionMetadata(PathBuf),
+    /// The session info folder of a session that is gone and cannot be resurrected
+    SessionInfoFolder(PathBuf),
+    /// The socket or marker file of a web server that no longer answers
+    WebServerMarker(PathBuf),
+    /// The reverse pipe of a session whose main pipe is gone, which cannot be removed as it goes
+    /// away only with the process holding it open
+    OrphanedReversePipe(String),
+}
+
+impl StaleArtifact {
+    pub fn remove(&self) -> io::Result<()> {
+        match self {
+            StaleArtifact::SessionSocket(path)
+            | StaleArtifact::SessionMetadata(path)
+            | StaleArtifact::WebServerMarker(path) => fs::remove_file(path),
+            StaleArtifact::SessionInfoFolder(path) => fs::remove_dir_all(path),
+            StaleArtifact::OrphanedReversePipe(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "named pipes go away only with the process holding them open",
+            )),
+        }
+    }
+    pub fn is_removable(&self) -> bool {
+        !matches!(self, StaleArtifact::OrphanedReversePipe(_))
+    }
+}
+
+impl fmt::Display for StaleArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StaleArtifact::SessionSocket(path) => {
+                write!(f, "session socket {}", path.display())
+            },
+            StaleArtifact::SessionMetadata(path) => {
+                write!(f, "session metadata {}", path.display())
+            },
+            StaleArtifact::SessionInfoFolder(path) => {
+                write!(f, "session info folder {}", path.display())
+            },
+            StaleArtifact::WebServerMarker(path) => {
+                write!(f, "web server marker {}", path.display())
+            },
+            StaleArtifact::OrphanedReversePipe(pipe_name) => {
+                write!(f, "reverse pipe \\\\.\\pipe\\{}", pipe_name)
+            },
+        }
+    }
+}
+
+/// The artifacts left behind by sessions and web servers that are no longer running
+pub fn find_stale_artifacts() -> Vec<StaleArtifact> {
+    // without knowing which sessions are running, every session would look stale
+    let live_sessions: Vec<String> = match get_sessions() {
+        Ok(sessions) => sessions.into_iter().map(|(name, _)| name).collect(),
+        Err(e) => {
+            log::error!(
+                "Failed to list sessions, not looking for stale artifacts: {:?}",
+                e
+            );
+            return vec![];
+        },
+    };
+    let cutoff = SystemTime::now()
+        .checked_sub(GRACE_PERIOD)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut stale_artifacts = stale_session_sockets(&ZELLIJ_SOCK_DIR, &live_sessions, cutoff);
+    stale_artifacts.extend(stale_session_info(
+        &ZELLIJ_SESSION_INFO_CACHE_DIR,
+        &live_sessions,
+        cutoff,
+    ));
+    #[cfg(windows)]
+    match crate::ipc::list_orphaned_reverse_pipes_in(&ZELLIJ_SOCK_DIR) {
+        Ok(pipe_names) => stale_artifacts.extend(
+            pipe_names
+                .into_iter()
+                .map(StaleArtifact::OrphanedReversePipe),
+        ),
+        Err(e) => log::warn!("Failed to list named pipes: {}", e),
+    }
+    #[cfg(feature = "web_server_capability")]
+    stale_artifacts.extend(stale_web_server_markers(cutoff));
+    stale_artifacts
+}
+
+/// Removes the stale artifacts that can be removed, logging what was removed
+pub fn remove_stale_artifacts() {
+    for stale_artifact in find_stale_artifacts() {
+        if !stale_artifact.is_removable() {
+            log::debug!("Cannot remove stale {}", stale_artifact);
+            continue;
+        }
+        match stale_artifact.remove() {
+            Ok(()) => log::info!("Removed stale {}", stale_artifact),
+            Err(e) => log::warn!("Failed to remove stale {}: {}", stale_artifact, e),
+        }
+    }
+}
+
+fn stale_session_sockets(
+    sock_dir: &Path,
+    live_sessions: &[String],
+    cutoff: SystemTime,
+) -> Vec<StaleArtifact> {
+    let Ok(entries) = fs::read_dir(sock_dir) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_type()
+                .map(|file_type| is_ipc_socket(&file_type))
+                .unwrap_or(false)
+        })
+        .filter(|entry| {
+            let session_name = entry.file_name().to_string_lossy().to_string();
+            !live_sessions.contains(&session_name)
+        })
+        .map(|entry| entry.path())
+        .filter(|path| modified_before(path, cutoff) && refuses_connections(path))
+        .map(StaleArtifact::SessionSocket)
+        .collect()
+}
+
+// a session that did not answer when listing sessions may only be busy, but nothing listens on
+// a socket that refuses connections
+#[cfg(unix)]
+fn refuses_connections(path: &Path) -> bool {
+    use interprocess::local_socket::{traits::Stream, Stream as LocalSocketStream};
+    match crate::ipc::path_to_ipc_name(path) {
+        Ok(fs_name) => matches!(
+            LocalSocketStream::connect(fs_name),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused
+        ),
+        Err(_) => false,
+    }
+}
+
+// on windows the sessions are pipes, and marker files in the socket dir are never listened on
+#[cfg(windows)]
+fn refuses_connections(_path: &Path) -> bool {
+    true
+}
+
+fn stale_session_info(
+    session_info_dir: &Path,
+    live_sessions: &[String],
+    cutoff: SystemTime,
+) -> Vec<StaleArtifact> {
+    let Ok(entries) = fs::read_dir(session_info_dir) else {
+        return vec![];
+    };
+    let mut stale_artifacts = vec![];
+    for entry in entries.flatten() {
+        let session_name = entry.file_name().to_string_lossy().to_string();
+        if !entry.path().is_dir() || live_sessions.contains(&session_name) {
+            continue;
+        }
+        #[cfg(windows)]
+        if crate::sessions::is_server_process_alive(&session_name) == Some(true) {
+            // the server is still running even if it did not answer
+            continue;
+        }
+        let session_folder = entry.path();
+        if session_folder.join(SESSION_LAYOUT_CACHE_FILE_NAME).exists() {
+            // the layout is kept to resurrect the session, only what describes it as running goes
+            stale_artifacts.extend(
+                [
+                    SESSION_INFO_CACHE_FILE_NAME,
+                    SESSION_PID_FILE_NAME,
+                    SESSION_SERVER_INFO_FILE_NAME,
+                    SESSION_LOCK_FILE_NAME,
+                ]
+                .into_iter()
+                .map(|file_name| session_folder.join(file_name))
+                .filter(|path| path.exists() && modified_before(path, cutoff))
+                .map(StaleArtifact::SessionMetadata),
+            );
+        } else if modified_before(&session_folder, cutoff) {
+            stale_artifacts.push(StaleArtifact::SessionInfoFolder(session_folder));
+        }
+    }
+    stale_artifacts
+}
+
+#[cfg(feature = "web_server_capability")]
+fn stale_web_server_markers(cutoff: SystemTime) -> Vec<StaleArtifact> {
+    use crate::consts::WEBSERVER_SOCKET_PATH;
+    use crate::web_server_commands::{query_webserver_with_response, InstructionForWebServer};
+
+    let Ok(entries) = fs::read_dir(&*WEBSERVER_SOCKET_PATH) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| modified_before(path, cutoff))
+        .filter(|path| {
+            query_webserver_with_response(
+                path.to_str().unwrap_or(""),
+                InstructionForWebServer::QueryVersion,
+                500,
+            )
+            .is_err()
+        })
+        .map(StaleArtifact::WebServerMarker)
+        .collect()
+}
+
+fn modified_before(path: &Path, cutoff: SystemTime) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| modified < cutoff)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod stale_artifacts_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn far_future() -> SystemTime {
+        SystemTime::now() + Duration::from_secs(60 * 60)
+    }
+
+    fn create_session_folder(session_info_dir: &Path, session_name: &str, files: &[&str]) {
+        let session_folder = session_info_dir.join(session_name);
+        fs::create_dir_all(&session_folder).unwrap();
+        for file in files {
+            fs::write(session_folder.join(file), "").unwrap();
+        }
+    }
+
+    #[test]
+    fn exited_sessions_keep_only_what_resurrects_them() {
+        let session_info_dir = TempDir::new().unwrap();
+        let session_info_dir = session_info_dir.path();
+        create_session_folder(
+            session_info_dir,
+            "resurrectable",
+            &[
+                "session-layout.kdl",
+                "session-metadata.kdl",
+                "server.pid",
+                "server-info.kdl",
+            ],
+        );
+        create_session_folder(session_info_dir, "gone", &["session-metadata.kdl"]);
+        create_session_folder(
+            session_info_dir,
+            "running",
+            &["session-metadata.kdl", "server.pid"],
+        );
+
+        let mut stale_artifacts =
+            stale_session_info(session_info_dir, &["running".to_owned()], far_future());
+        stale_artifacts.sort_by_key(|stale_artifact| stale_artifact.to_string());
+
+        assert_eq!(
+            stale_artifacts,
+            vec![
+                StaleArtifact::SessionInfoFolder(session_info_dir.join("gone")),
+                StaleArtifact::SessionMetadata(
+                    session_info_dir
+                        .join("resurrectable")
+                        .join("server-info.kdl")
+                ),
+                StaleArtifact::SessionMetadata(
+                    session_info_dir.join("resurrectable").join("server.pid")
+                ),
+                StaleArtifact::SessionMetadata(
+                    session_info_dir
+                        .join("resurrectable")
+                        .join("session-metadata.kdl")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_artifacts_are_not_stale() {
+        let session_info_dir = TempDir::new().unwrap();
+        let session_info_dir = session_info_dir.path();
+        create_session_folder(session_info_dir, "starting", &["server.pid"]);
+
+        let cutoff = SystemTime::now() - GRACE_PERIOD;
+        assert!(stale_session_info(session_info_dir, &[], cutoff).is_empty());
+    }
+
+    #[test]
+    fn removing_stale_artifacts_removes_them_from_disk() {
+        let session_info_dir = TempDir::new().unwrap();
+        let session_info_dir = session_info_dir.path();
+        create_session_folder(
+            session_info_dir,
+            "resurrectable",
+            &["session-layout.kdl", "server.pid"],
+        );
+        create_session_folder(session_info_dir, "gone", &["session-metadata.kdl"]);
+
+        for stale_artifact in stale_session_info(session_info_dir, &[], far_future()) {
+            stale_artifact.remove().unwrap();
+        }
+
+        assert!(!session_info_dir.join("gone").exists());
+        assert!(!session_info_dir
+            .join("resurrectable")
+            .join("server.pid")
+            .exists());
+        assert!(session_info_dir
+            .join("resurrectable")
+            .join("session-layout.kdl")
+            .exists());
+    }
+}
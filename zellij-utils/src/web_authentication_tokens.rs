@@ -13,6 +13,14 @@ pub struct TokenInfo {
     pub read_only: bool,
 }
 
+/// A login through a share link, which can only watch the session it was created for
+#[derive(Debug)]
+pub struct SharedSessionLogin {
+    pub session_token: String,
+    pub session_name: String,
+    pub expires_in_seconds: i64,
+}
+
 #[derive(Debug)]
 pub enum TokenError {
     Database(rusqlite::Error),
@@ -104,6 +112,30 @@ fn init_db(conn: &Connection) -> Result<()> {
     )
     .ok();
 
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS share_links (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            share_token_hash TEXT UNIQUE NOT NULL,
+            session_name TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            expires_at DATETIME NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS share_link_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_token_hash TEXT UNIQUE NOT NULL,
+            share_token_hash TEXT NOT NULL,
+            session_name TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            expires_at DATETIME NOT NULL,
+            FOREIGN KEY (share_token_hash) REFERENCES share_links(share_token_hash)
+        )",
+        [],
+    )?;
+
     Ok(())
 }
 
@@ -198,8 +230,10 @@ pub fn validate_session_token(session_token: &str) -> Result<bool> {
 
     let session_token_hash = hash_token(session_token);
 
+    // session tokens of logins through a share link are kept apart, as they have no login token
     let count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM session_tokens WHERE session_token_hash = ?1 AND expires_at > datetime('now')",
+        "SELECT (SELECT COUNT(*) FROM session_tokens WHERE session_token_hash = ?1 AND expires_at > datetime('now'))
+         + (SELECT COUNT(*) FROM share_link_sessions WHERE session_token_hash = ?1 AND expires_at > datetime('now'))",
         [&session_token_hash],
         |row| row.get(0),
     )?;
@@ -214,6 +248,11 @@ pub fn is_session_token_read_only(session_token: &str) -> Result<bool> {
 
     let session_token_hash = hash_token(session_token);
 
+    // logins through a share link can only ever watch
+    if shared_session_of_session_token(session_token)?.is_some() {
+        return Ok(true);
+    }
+
     // Join session_tokens to tokens table to get read_only flag
     let read_only: i64 = match conn.query_row(
         "SELECT t.read_only FROM tokens t
@@ -230,6 +269,104 @@ pub fn is_session_token_read_only(session_token: &str) -> Result<bool> {
     Ok(read_only != 0)
 }
 
+/// The session a session token is restricted to, if it was created by logging in through a share
+/// link
+pub fn shared_session_of_session_token(session_token: &str) -> Result<Option<String>> {
+    let db_path = get_db_path()?;
+    let conn = Connection::open(db_path)?;
+    init_db(&conn)?;
+
+    let session_token_hash = hash_token(session_token);
+
+    match conn.query_row(
+        "SELECT session_name FROM share_link_sessions
+         WHERE session_token_hash = ?1 AND expires_at > datetime('now')",
+        [&session_token_hash],
+        |row| row.get::<_, String>(0),
+    ) {
+        Ok(session_name) => Ok(Some(session_name)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(TokenError::Database(e)),
+    }
+}
+
+/// Create the token of a link that lets whoever has it watch the given session until it expires
+pub fn create_share_link_token(session_name: &str, expires_in_seconds: u64) -> Result<String> {
+    let db_path = get_db_path()?;
+    let conn = Connection::open(db_path)?;
+    init_db(&conn)?;
+
+    let share_token = Uuid::new_v4().to_string();
+    let share_token_hash = hash_token(&share_token);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    conn.execute(
+        &format!(
+            "INSERT INTO share_links (share_token_hash, session_name, expires_at) VALUES (?1, ?2, datetime({}, 'unixepoch'))",
+            now + expires_in_seconds
+        ),
+        [&share_token_hash, session_name],
+    )?;
+
+    Ok(share_token)
+}
+
+/// Log in through a share link, the session token expires along with the link
+pub fn create_session_token_for_share_link(share_token: &str) -> Result<SharedSessionLogin> {
+    let db_path = get_db_path()?;
+    let conn = Connection::open(db_path)?;
+    init_db(&conn)?;
+
+    cleanup_expired_sessions()?;
+
+    let share_token_hash = hash_token(share_token);
+
+    let (session_name, expires_at, expires_in_seconds) = match conn.query_row(
+        "SELECT session_name, expires_at, CAST(strftime('%s', expires_at) AS INTEGER) - CAST(strftime('%s', 'now') AS INTEGER)
+         FROM share_links WHERE share_token_hash = ?1 AND expires_at > datetime('now')",
+        [&share_token_hash],
+        |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        },
+    ) {
+        Ok(share_link) => share_link,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(TokenError::InvalidToken),
+        Err(e) => return Err(TokenError::Database(e)),
+    };
+
+    let session_token = Uuid::new_v4().to_string();
+    let session_token_hash = hash_token(&session_token);
+
+    conn.execute(
+        "INSERT INTO share_link_sessions (session_token_hash, share_token_hash, session_name, expires_at) VALUES (?1, ?2, ?3, ?4)",
+        [&session_token_hash, &share_token_hash, &session_name, &expires_at],
+    )?;
+
+    Ok(SharedSessionLogin {
+        session_token,
+        session_name,
+        expires_in_seconds,
+    })
+}
+
+/// Revoke all share links along with the logins made through them
+pub fn revoke_all_share_links() -> Result<usize> {
+    let db_path = get_db_path()?;
+    let conn = Connection::open(db_path)?;
+    init_db(&conn)?;
+
+    conn.execute("DELETE FROM share_link_sessions", [])?;
+    let rows_affected = conn.execute("DELETE FROM share_links", [])?;
+    Ok(rows_affected)
+}
+
 pub fn cleanup_expired_sessions() -> Result<usize> {
     let db_path = get_db_path()?;
     let conn = Connection::open(db_path)?;
@@ -239,6 +376,14 @@ pub fn cleanup_expired_sessions() -> Result<usize> {
         "DELETE FROM session_tokens WHERE expires_at <= datetime('now')",
         [],
     )?;
+    conn.execute(
+        "DELETE FROM share_link_sessions WHERE expires_at <= datetime('now')",
+        [],
+    )?;
+    conn.execute(
+        "DELETE FROM share_links WHERE expires_at <= datetime('now')",
+        [],
+    )?;
 
     Ok(rows_affected)
 }
@@ -380,3 +525,23 @@ pub fn validate_token(token: &str) -> Result<bool> {
     )?;
     Ok(count > 0)
 }
+
+pub fn is_token_read_only(token: &str) -> Result<bool> {
+    let db_path = get_db_path()?;
+    let conn = Connection::open(db_path)?;
+    init_db(&conn)?;
+
+    let token_hash = hash_token(token);
+
+    let read_only: i64 = match conn.query_row(
+        "SELECT read_only FROM tokens WHERE token_hash = ?1",
+        [&token_hash],
+        |row| row.get(0),
+    ) {
+        Ok(val) => val,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Err(TokenError::InvalidToken),
+        Err(e) => return Err(TokenError::Database(e)),
+    };
+
+    Ok(read_only != 0)
+}
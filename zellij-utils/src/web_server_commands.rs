@@ -13,31 +13,17 @@ use std::io::{BufWriter, Read, Write};
 use std::path::PathBuf;
 
 pub fn shutdown_all_webserver_instances() -> Result<()> {
-    let entries = fs::read_dir(&*WEBSERVER_SOCKET_PATH)?;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if let Some(file_name) = path.file_name() {
-            if let Some(_file_name_str) = file_name.to_str() {
-                let metadata = entry.metadata()?;
-                let file_type = metadata.file_type();
-
-                if is_ipc_socket(&file_type) {
-                    match create_webserver_sender(path.to_str().unwrap_or("")) {
-                        Ok(mut sender) => {
-                            let _ = send_webserver_instruction(
-                                &mut sender,
-                                InstructionForWebServer::ShutdownWebServer,
-                            );
-                        },
-                        Err(_) => {
-                            // no-op
-                        },
-                    }
-                }
-            }
+    for socket_path in discover_webserver_sockets()? {
+        match create_webserver_sender(socket_path.to_str().unwrap_or("")) {
+            Ok(mut sender) => {
+                let _ = send_webserver_instruction(
+                    &mut sender,
+                    InstructionForWebServer::ShutdownWebServer,
+                );
+            },
+            Err(_) => {
+                // no-op
+            },
         }
     }
     Ok(())
@@ -47,6 +33,11 @@ pub fn shutdown_all_webserver_instances() -> Result<()> {
 pub enum InstructionForWebServer {
     ShutdownWebServer,
     QueryVersion,
+    ListSessions,
+    ListConnectedWebClients,
+    RebindPort(u16), // serve new connections on this port, the connected clients stay where they are
+    QueryStatus,
+    RestartWebServer, // shut down so that a new web server can take over, clients reconnect to it
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -56,9 +47,37 @@ pub struct VersionInfo {
     pub port: u16,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusInfo {
+    pub version: String,
+    pub ip: String,
+    pub port: u16,
+    pub uptime_secs: u64,
+    pub active_websocket_count: u32,
+    pub session_client_counts: Vec<SessionClientCount>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionClientCount {
+    pub session_name: String,
+    pub client_count: u32, // web clients attached to the session
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebClientInfo {
+    pub web_client_id: String,
+    pub session_name: Option<String>, // None until the client is attached to a session
+    pub is_read_only: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum WebServerResponse {
     Version(VersionInfo),
+    Sessions(Vec<String>), // the sessions web clients are attached to
+    ConnectedWebClients(Vec<WebClientInfo>),
+    ReboundPort(u16),
+    Error(String),
+    Status(StatusInfo),
 }
 
 pub fn create_webserver_sender(path: &str) -> Result<BufWriter<LocalSocketStream>> {
@@ -85,6 +104,17 @@ pub fn send_webserver_instruction(
 }
 
 pub fn discover_webserver_sockets() -> Result<Vec<PathBuf>> {
+    // named pipes are not filesystem entries, so on windows the running instances are found by
+    // listing their pipes, the marker files they create are only a fallback
+    #[cfg(windows)]
+    match discover_webserver_pipes() {
+        Ok(sockets) => return Ok(sockets),
+        Err(e) => log::warn!(
+            "Failed to list named pipes, looking for web server marker files instead: {}",
+            e
+        ),
+    }
+
     let mut sockets = Vec::new();
 
     if !WEBSERVER_SOCKET_PATH.exists() {
@@ -103,6 +133,40 @@ pub fn discover_webserver_sockets() -> Result<Vec<PathBuf>> {
     Ok(sockets)
 }
 
+#[cfg(windows)]
+fn discover_webserver_pipes() -> Result<Vec<PathBuf>> {
+    let sockets: Vec<PathBuf> = crate::ipc::list_pipes_in(&WEBSERVER_SOCKET_PATH)?
+        .iter()
+        .map(|id| WEBSERVER_SOCKET_PATH.join(id))
+        .collect();
+
+    // the marker files of web servers that died without cleaning up after themselves
+    if let Ok(entries) = fs::read_dir(&*WEBSERVER_SOCKET_PATH) {
+        for entry in entries.flatten() {
+            if !sockets.contains(&entry.path()) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    Ok(sockets)
+}
+
+/// The socket of the running web server instance that listens on `ip` and `port`, if any
+pub fn find_webserver_socket(ip: &str, port: u16) -> Result<Option<PathBuf>> {
+    for socket_path in discover_webserver_sockets()? {
+        let path_str = socket_path.to_str().unwrap_or("");
+        if let Ok(WebServerResponse::Version(info)) =
+            query_webserver_with_response(path_str, InstructionForWebServer::QueryVersion, 500)
+        {
+            if info.ip == ip && info.port == port {
+                return Ok(Some(socket_path));
+            }
+        }
+    }
+    Ok(None)
+}
+
 pub fn query_webserver_with_response(
     path: &str,
     instruction: InstructionForWebServer,
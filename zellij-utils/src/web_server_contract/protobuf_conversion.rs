@@ -1,11 +1,15 @@
 use crate::errors::prelude::*;
 use crate::web_server_commands::{
-    InstructionForWebServer as RustInstructionForWebServer, VersionInfo, WebServerResponse,
+    InstructionForWebServer as RustInstructionForWebServer, SessionClientCount, StatusInfo,
+    VersionInfo, WebClientInfo, WebServerResponse,
 };
 use crate::web_server_contract::web_server_contract::{
-    instruction_for_web_server, web_server_response,
-    InstructionForWebServer as ProtoInstructionForWebServer, QueryVersionMsg, ShutdownWebServerMsg,
-    VersionResponseMsg, WebServerResponse as ProtoWebServerResponse,
+    instruction_for_web_server, web_server_response, ConnectedWebClientsResponseMsg,
+    ErrorResponseMsg, InstructionForWebServer as ProtoInstructionForWebServer,
+    ListConnectedWebClientsMsg, ListSessionsMsg, QueryStatusMsg, QueryVersionMsg, RebindPortMsg,
+    ReboundPortResponseMsg, RestartWebServerMsg, SessionClientCountMsg, SessionsResponseMsg,
+    ShutdownWebServerMsg, StatusResponseMsg, VersionResponseMsg, WebClientInfoMsg,
+    WebServerResponse as ProtoWebServerResponse,
 };
 
 // Convert Rust InstructionForWebServer to protobuf
@@ -18,6 +22,25 @@ impl From<RustInstructionForWebServer> for ProtoInstructionForWebServer {
             RustInstructionForWebServer::QueryVersion => {
                 instruction_for_web_server::Instruction::QueryVersion(QueryVersionMsg {})
             },
+            RustInstructionForWebServer::ListSessions => {
+                instruction_for_web_server::Instruction::ListSessions(ListSessionsMsg {})
+            },
+            RustInstructionForWebServer::ListConnectedWebClients => {
+                instruction_for_web_server::Instruction::ListConnectedWebClients(
+                    ListConnectedWebClientsMsg {},
+                )
+            },
+            RustInstructionForWebServer::RebindPort(port) => {
+                instruction_for_web_server::Instruction::RebindPort(RebindPortMsg {
+                    port: port as u32,
+                })
+            },
+            RustInstructionForWebServer::QueryStatus => {
+                instruction_for_web_server::Instruction::QueryStatus(QueryStatusMsg {})
+            },
+            RustInstructionForWebServer::RestartWebServer => {
+                instruction_for_web_server::Instruction::RestartWebServer(RestartWebServerMsg {})
+            },
         };
 
         ProtoInstructionForWebServer {
@@ -38,6 +61,23 @@ impl TryFrom<ProtoInstructionForWebServer> for RustInstructionForWebServer {
             Some(instruction_for_web_server::Instruction::QueryVersion(_)) => {
                 Ok(RustInstructionForWebServer::QueryVersion)
             },
+            Some(instruction_for_web_server::Instruction::ListSessions(_)) => {
+                Ok(RustInstructionForWebServer::ListSessions)
+            },
+            Some(instruction_for_web_server::Instruction::ListConnectedWebClients(_)) => {
+                Ok(RustInstructionForWebServer::ListConnectedWebClients)
+            },
+            Some(instruction_for_web_server::Instruction::RebindPort(rebind_port_msg)) => {
+                let port = u16::try_from(rebind_port_msg.port)
+                    .map_err(|_| anyhow!("Invalid port: {}", rebind_port_msg.port))?;
+                Ok(RustInstructionForWebServer::RebindPort(port))
+            },
+            Some(instruction_for_web_server::Instruction::QueryStatus(_)) => {
+                Ok(RustInstructionForWebServer::QueryStatus)
+            },
+            Some(instruction_for_web_server::Instruction::RestartWebServer(_)) => {
+                Ok(RustInstructionForWebServer::RestartWebServer)
+            },
             None => Err(anyhow!("Missing instruction in InstructionForWebServer")),
         }
     }
@@ -54,6 +94,36 @@ impl From<WebServerResponse> for ProtoWebServerResponse {
                     port: version_info.port as u32,
                 })
             },
+            WebServerResponse::Sessions(session_names) => {
+                web_server_response::Response::Sessions(SessionsResponseMsg { session_names })
+            },
+            WebServerResponse::ConnectedWebClients(web_clients) => {
+                web_server_response::Response::ConnectedWebClients(ConnectedWebClientsResponseMsg {
+                    web_clients: web_clients.into_iter().map(|c| c.into()).collect(),
+                })
+            },
+            WebServerResponse::ReboundPort(port) => {
+                web_server_response::Response::ReboundPort(ReboundPortResponseMsg {
+                    port: port as u32,
+                })
+            },
+            WebServerResponse::Error(message) => {
+                web_server_response::Response::Error(ErrorResponseMsg { message })
+            },
+            WebServerResponse::Status(status_info) => {
+                web_server_response::Response::Status(StatusResponseMsg {
+                    version: status_info.version,
+                    ip: status_info.ip,
+                    port: status_info.port as u32,
+                    uptime_secs: status_info.uptime_secs,
+                    active_websocket_count: status_info.active_websocket_count,
+                    session_client_counts: status_info
+                        .session_client_counts
+                        .into_iter()
+                        .map(|c| c.into())
+                        .collect(),
+                })
+            },
         };
 
         ProtoWebServerResponse {
@@ -75,7 +145,77 @@ impl TryFrom<ProtoWebServerResponse> for WebServerResponse {
                     port: version_msg.port as u16,
                 }))
             },
+            Some(web_server_response::Response::Sessions(sessions_msg)) => {
+                Ok(WebServerResponse::Sessions(sessions_msg.session_names))
+            },
+            Some(web_server_response::Response::ConnectedWebClients(web_clients_msg)) => {
+                Ok(WebServerResponse::ConnectedWebClients(
+                    web_clients_msg
+                        .web_clients
+                        .into_iter()
+                        .map(|c| c.into())
+                        .collect(),
+                ))
+            },
+            Some(web_server_response::Response::ReboundPort(rebound_port_msg)) => {
+                Ok(WebServerResponse::ReboundPort(rebound_port_msg.port as u16))
+            },
+            Some(web_server_response::Response::Error(error_msg)) => {
+                Ok(WebServerResponse::Error(error_msg.message))
+            },
+            Some(web_server_response::Response::Status(status_msg)) => {
+                Ok(WebServerResponse::Status(StatusInfo {
+                    version: status_msg.version,
+                    ip: status_msg.ip,
+                    port: status_msg.port as u16,
+                    uptime_secs: status_msg.uptime_secs,
+                    active_websocket_count: status_msg.active_websocket_count,
+                    session_client_counts: status_msg
+                        .session_client_counts
+                        .into_iter()
+                        .map(|c| c.into())
+                        .collect(),
+                }))
+            },
             None => Err(anyhow!("Missing response in WebServerResponse")),
         }
     }
 }
+
+impl From<WebClientInfo> for WebClientInfoMsg {
+    fn from(web_client_info: WebClientInfo) -> Self {
+        WebClientInfoMsg {
+            web_client_id: web_client_info.web_client_id,
+            session_name: web_client_info.session_name,
+            is_read_only: web_client_info.is_read_only,
+        }
+    }
+}
+
+impl From<WebClientInfoMsg> for WebClientInfo {
+    fn from(web_client_info_msg: WebClientInfoMsg) -> Self {
+        WebClientInfo {
+            web_client_id: web_client_info_msg.web_client_id,
+            session_name: web_client_info_msg.session_name,
+            is_read_only: web_client_info_msg.is_read_only,
+        }
+    }
+}
+
+impl From<SessionClientCount> for SessionClientCountMsg {
+    fn from(session_client_count: SessionClientCount) -> Self {
+        SessionClientCountMsg {
+            session_name: session_client_count.session_name,
+            client_count: session_client_count.client_count,
+        }
+    }
+}
+
+impl From<SessionClientCountMsg> for SessionClientCount {
+    fn from(session_client_count_msg: SessionClientCountMsg) -> Self {
+        SessionClientCount {
+            session_name: session_client_count_msg.session_name,
+            client_count: session_client_count_msg.client_count,
+        }
+    }
+}
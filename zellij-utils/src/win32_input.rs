@@ -0,0 +1,121 @@
+//! Encoding of key events for win32-input-mode.
+//!
+//! A terminal or console that asks for win32-input-mode (`CSI ? 9001 h`)
+//! exchanges each `INPUT_RECORD` key event as
+//! `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _`, which carries the key up events, scan
+//! codes and modifier state that plain VT input cannot express. The client
+//! decodes these from the terminal, and both ends encode the Ctrl+Break they
+//! forward to a pane.
+//! See <https://github.com/microsoft/terminal/blob/main/doc/specs/%234999%20-%20Improved%20keyboard%20handling%20in%20Conpty.md>
+
+/// `dwControlKeyState` flags of a `KEY_EVENT_RECORD`
+pub const RIGHT_ALT_PRESSED: u32 = 0x0001;
+pub const LEFT_ALT_PRESSED: u32 = 0x0002;
+pub const RIGHT_CTRL_PRESSED: u32 = 0x0004;
+pub const LEFT_CTRL_PRESSED: u32 = 0x0008;
+pub const SHIFT_PRESSED: u32 = 0x0010;
+
+/// Asks the terminal to send key events in win32-input-mode
+pub const WIN32_INPUT_MODE_ENABLE: &[u8] = b"\x1b[?9001h";
+/// Asks the terminal to go back to sending plain VT input
+pub const WIN32_INPUT_MODE_DISABLE: &[u8] = b"\x1b[?9001l";
+
+/// A key event as described by a `KEY_EVENT_RECORD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Win32KeyEvent {
+    /// `wVirtualKeyCode`, eg. `0x03` for VK_CANCEL
+    pub virtual_key_code: u16,
+    /// `wVirtualScanCode`, the hardware scan code of the key
+    pub virtual_scan_code: u16,
+    /// `uChar.UnicodeChar`, the UTF-16 code unit the key produces, or 0
+    pub unicode_char: u16,
+    /// `bKeyDown`, false for a key up event
+    pub key_down: bool,
+    /// `dwControlKeyState`, a combination of the `*_PRESSED` flags among others
+    pub control_key_state: u32,
+    /// `wRepeatCount`
+    pub repeat_count: u16,
+}
+
+impl Win32KeyEvent {
+    /// A key down event repeated once
+    pub fn key_down(
+        virtual_key_code: u16,
+        virtual_scan_code: u16,
+        unicode_char: u16,
+        control_key_state: u32,
+    ) -> Self {
+        Self {
+            virtual_key_code,
+            virtual_scan_code,
+            unicode_char,
+            key_down: true,
+            control_key_state,
+            repeat_count: 1,
+        }
+    }
+
+    /// The key up event releasing this key
+    pub fn released(&self) -> Self {
+        Self {
+            key_down: false,
+            ..*self
+        }
+    }
+
+    /// Encode this event as a `CSI Vk ; Sc ; Uc ; Kd ; Cs ; Rc _` sequence
+    pub fn encode(&self) -> Vec<u8> {
+        format!(
+            "\x1b[{};{};{};{};{};{}_",
+            self.virtual_key_code,
+            self.virtual_scan_code,
+            self.unicode_char,
+            self.key_down as u8,
+            self.control_key_state,
+            self.repeat_count
+        )
+        .into_bytes()
+    }
+
+    /// Encode this event followed by its key up event, ie. a key press
+    pub fn encode_press(&self) -> Vec<u8> {
+        let mut bytes = self.encode();
+        bytes.extend(self.released().encode());
+        bytes
+    }
+
+    /// Ctrl+Break, which conhost turns into a CTRL_BREAK_EVENT for the
+    /// processes attached to the console even when none of them reads input
+    pub fn ctrl_break() -> Self {
+        // VK_CANCEL with the scan code of the Pause/Break key
+        Self::key_down(0x03, 0x46, 0, LEFT_CTRL_PRESSED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_key_event_fields_in_order() {
+        let event = Win32KeyEvent::key_down(0x41, 0x1e, 'a' as u16, SHIFT_PRESSED);
+        assert_eq!(event.encode(), b"\x1b[65;30;97;1;16;1_".to_vec());
+    }
+
+    #[test]
+    fn key_press_is_followed_by_its_release() {
+        let event = Win32KeyEvent::key_down(0x0d, 0x1c, '\r' as u16, 0);
+        assert_eq!(
+            event.encode_press(),
+            b"\x1b[13;28;13;1;0;1_\x1b[13;28;13;0;0;1_".to_vec()
+        );
+    }
+
+    #[test]
+    fn ctrl_break_is_a_ctrl_pressed_vk_cancel() {
+        assert_eq!(
+            Win32KeyEvent::ctrl_break().encode_press(),
+            b"\x1b[3;70;0;1;8;1_\x1b[3;70;0;0;8;1_".to_vec()
+        );
+    }
+}
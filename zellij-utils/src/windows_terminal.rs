@@ -0,0 +1,685 @@
+//! `zellij setup --from-windows-terminal`: turns the color schemes of a Windows Terminal
+//! settings.json into themes, its profiles into a layout opening a tab for each of them, and
+//! points the config at both.
+use crate::data::{Palette, PaletteColor, ThemeHue};
+use crate::input::options::Options;
+use crate::input::theme::{HexColor, Theme, Themes};
+use crate::setup_wizard::{quoted, shells_layout, write_file, DetectedShell};
+use kdl::KdlDocument;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const LAYOUT_NAME: &str = "windows-terminal";
+const DEFAULT_COLOR_SCHEME: &str = "Campbell";
+
+/// The schemes Windows Terminal ships with that its profiles use unless told otherwise, they
+/// are not in settings.json
+const BUILTIN_SCHEMES: &str = r##"[
+    {
+        "name": "Campbell",
+        "foreground": "#CCCCCC", "background": "#0C0C0C",
+        "black": "#0C0C0C", "red": "#C50F1F", "green": "#13A10E", "yellow": "#C19C00",
+        "blue": "#0037DA", "purple": "#881798", "cyan": "#3A96DD", "white": "#CCCCCC",
+        "brightBlack": "#767676", "brightRed": "#E74856", "brightGreen": "#16C60C",
+        "brightYellow": "#F9F1A5", "brightBlue": "#3B78FF", "brightPurple": "#B4009E",
+        "brightCyan": "#61D6D6", "brightWhite": "#F2F2F2"
+    },
+    {
+        "name": "Campbell Powershell",
+        "foreground": "#CCCCCC", "background": "#012456",
+        "black": "#0C0C0C", "red": "#C50F1F", "green": "#13A10E", "yellow": "#C19C00",
+        "blue": "#0037DA", "purple": "#881798", "cyan": "#3A96DD", "white": "#CCCCCC",
+        "brightBlack": "#767676", "brightRed": "#E74856", "brightGreen": "#16C60C",
+        "brightYellow": "#F9F1A5", "brightBlue": "#3B78FF", "brightPurple": "#B4009E",
+        "brightCyan": "#61D6D6", "brightWhite": "#F2F2F2"
+    },
+    {
+        "name": "One Half Dark",
+        "foreground": "#DCDFE4", "background": "#282C34",
+        "black": "#282C34", "red": "#E06C75", "green": "#98C379", "yellow": "#E5C07B",
+        "blue": "#61AFEF", "purple": "#C678DD", "cyan": "#56B6C2", "white": "#DCDFE4",
+        "brightBlack": "#5A6374", "brightRed": "#E06C75", "brightGreen": "#98C379",
+        "brightYellow": "#E5C07B", "brightBlue": "#61AFEF", "brightPurple": "#C678DD",
+        "brightCyan": "#56B6C2", "brightWhite": "#DCDFE4"
+    },
+    {
+        "name": "One Half Light",
+        "foreground": "#383A42", "background": "#FAFAFA",
+        "black": "#383A42", "red": "#E45649", "green": "#50A14F", "yellow": "#C18301",
+        "blue": "#0184BC", "purple": "#A626A4", "cyan": "#0997B3", "white": "#FAFAFA",
+        "brightBlack": "#4F525D", "brightRed": "#DF6C75", "brightGreen": "#98C379",
+        "brightYellow": "#E4C07A", "brightBlue": "#61AFEF", "brightPurple": "#C577DD",
+        "brightCyan": "#56B5C1", "brightWhite": "#FFFFFF"
+    }
+]"##;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowsTerminalSettings {
+    /// The guid (or name) of the profile opened first
+    default_profile: Option<String>,
+    profiles: Profiles,
+    schemes: Vec<ColorScheme>,
+}
+
+/// `profiles` is either the list of profiles or an object with the list and their defaults
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Profiles {
+    List(Vec<Profile>),
+    WithDefaults {
+        #[serde(default)]
+        defaults: Box<Profile>,
+        #[serde(default)]
+        list: Vec<Profile>,
+    },
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Profiles::List(vec![])
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Profile {
+    guid: Option<String>,
+    name: Option<String>,
+    commandline: Option<String>,
+    starting_directory: Option<String>,
+    hidden: bool,
+    /// The generator of a dynamic profile, e.g. `Windows.Terminal.Wsl`
+    source: Option<String>,
+    color_scheme: Option<SchemeName>,
+    font: Option<Font>,
+    // the font settings before Windows Terminal 1.10
+    font_face: Option<String>,
+    font_size: Option<f64>,
+}
+
+impl Profile {
+    fn with_defaults(self, defaults: &Profile) -> Profile {
+        Profile {
+            starting_directory: self
+                .starting_directory
+                .or_else(|| defaults.starting_directory.clone()),
+            color_scheme: self.color_scheme.or_else(|| defaults.color_scheme.clone()),
+            font: self.font.or_else(|| defaults.font.clone()),
+            font_face: self.font_face.or_else(|| defaults.font_face.clone()),
+            font_size: self.font_size.or(defaults.font_size),
+            ..self
+        }
+    }
+    fn color_scheme(&self) -> &str {
+        match &self.color_scheme {
+            Some(SchemeName::Single(name)) => name,
+            Some(SchemeName::PerHue { dark, .. }) => dark,
+            None => DEFAULT_COLOR_SCHEME,
+        }
+    }
+    fn font_face(&self) -> Option<&str> {
+        self.font
+            .as_ref()
+            .and_then(|font| font.face.as_deref())
+            .or(self.font_face.as_deref())
+    }
+    fn font_size(&self) -> Option<f64> {
+        self.font
+            .as_ref()
+            .and_then(|font| font.size)
+            .or(self.font_size)
+    }
+    fn is(&self, guid_or_name: &str) -> bool {
+        self.guid
+            .as_ref()
+            .map(|guid| guid.eq_ignore_ascii_case(guid_or_name))
+            .unwrap_or(false)
+            || self.name.as_deref() == Some(guid_or_name)
+    }
+}
+
+/// Either a scheme or one for each of the light and dark Windows themes
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum SchemeName {
+    Single(String),
+    PerHue { dark: String },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Font {
+    face: Option<String>,
+    size: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ColorScheme {
+    name: String,
+    foreground: HexColor,
+    background: HexColor,
+    black: HexColor,
+    red: HexColor,
+    green: HexColor,
+    yellow: HexColor,
+    blue: HexColor,
+    purple: HexColor,
+    cyan: HexColor,
+    white: HexColor,
+    bright_black: HexColor,
+    bright_red: HexColor,
+}
+
+impl ColorScheme {
+    fn to_theme(&self) -> Theme {
+        let rgb = |color: HexColor| PaletteColor::Rgb(color.into());
+        let (r, g, b): (u8, u8, u8) = self.background.into();
+        let luma = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+        let palette = Palette {
+            theme_hue: if luma > 127.5 {
+                ThemeHue::Light
+            } else {
+                ThemeHue::Dark
+            },
+            fg: rgb(self.foreground),
+            bg: rgb(self.bright_black),
+            black: rgb(self.black),
+            red: rgb(self.red),
+            green: rgb(self.green),
+            yellow: rgb(self.yellow),
+            blue: rgb(self.blue),
+            magenta: rgb(self.purple),
+            cyan: rgb(self.cyan),
+            white: rgb(self.white),
+            // there is no orange in the ANSI colors, the ansi theme uses bright red as well
+            orange: rgb(self.bright_red),
+            ..Default::default()
+        };
+        Theme {
+            palette: palette.into(),
+            sourced_from_external_file: false,
+        }
+    }
+}
+
+/// What `zellij setup --from-windows-terminal` writes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub themes: String,
+    pub layout: Option<String>,
+    pub config: String,
+    /// The profiles that could not be turned into a tab, with the reason
+    pub skipped_profiles: Vec<(String, String)>,
+}
+
+impl WindowsTerminalSettings {
+    /// Parses settings.json, which may have comments and trailing commas
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(&strip_json_comments(json)).map_err(|e| e.to_string())
+    }
+
+    fn profiles(&self) -> Vec<Profile> {
+        match &self.profiles {
+            Profiles::List(list) => list.clone(),
+            Profiles::WithDefaults { defaults, list } => list
+                .iter()
+                .map(|profile| profile.clone().with_defaults(defaults))
+                .collect(),
+        }
+    }
+
+    pub fn import(&self, source: &Path) -> Import {
+        let mut profiles: Vec<Profile> = self
+            .profiles()
+            .into_iter()
+            .filter(|profile| !profile.hidden)
+            .collect();
+        // the default profile is the one Windows Terminal opens first
+        if let Some(default_profile) = &self.default_profile {
+            if let Some(i) = profiles.iter().position(|p| p.is(default_profile)) {
+                let profile = profiles.remove(i);
+                profiles.insert(0, profile);
+            }
+        }
+
+        let mut schemes: HashMap<String, ColorScheme> = HashMap::new();
+        for scheme in &self.schemes {
+            schemes.insert(scheme.name.clone(), scheme.clone());
+        }
+        let builtin_schemes: Vec<ColorScheme> =
+            serde_json::from_str(BUILTIN_SCHEMES).unwrap_or_default();
+        for scheme in builtin_schemes {
+            if !schemes.contains_key(&scheme.name)
+                && profiles.iter().any(|p| p.color_scheme() == scheme.name)
+            {
+                schemes.insert(scheme.name.clone(), scheme);
+            }
+        }
+        let mut themes = Themes::default();
+        for (name, scheme) in &schemes {
+            themes.insert(name.clone(), scheme.to_theme());
+        }
+        let mut themes_document = KdlDocument::new();
+        themes_document.nodes_mut().extend(themes.to_kdl());
+        themes_document.fmt();
+
+        let mut shells = vec![];
+        let mut skipped_profiles = vec![];
+        for profile in &profiles {
+            let name = profile.name.clone().unwrap_or_else(|| "Profile".to_owned());
+            match profile_shell(profile) {
+                Ok(shell) => shells.push(shell),
+                Err(reason) => skipped_profiles.push((name, reason)),
+            }
+        }
+
+        let default_profile = profiles.first();
+        let theme = default_profile
+            .map(|profile| profile.color_scheme())
+            .filter(|scheme| schemes.contains_key(*scheme))
+            .map(|scheme| scheme.to_owned());
+        // default_shell takes no arguments
+        let default_shell = shells
+            .first()
+            .filter(|shell| shell.args.is_empty())
+            .map(|shell| shell.command.clone());
+        let options = Options {
+            theme,
+            default_shell,
+            default_layout: Some(PathBuf::from(LAYOUT_NAME)).filter(|_| !shells.is_empty()),
+            ..Default::default()
+        };
+        let mut config = format!(
+            "// Written by `zellij setup --from-windows-terminal` from {}\n\n",
+            source.display()
+        );
+        for node in options.to_kdl(false) {
+            config.push_str(&format!("{}\n", node));
+        }
+        if let Some(unknown_scheme) = default_profile
+            .map(|profile| profile.color_scheme())
+            .filter(|scheme| !schemes.contains_key(*scheme))
+        {
+            config.push_str(&format!(
+                "\n// Windows Terminal uses its built-in color scheme {}, which has no theme here\n",
+                quoted(unknown_scheme)
+            ));
+        }
+        let font_face = default_profile.and_then(|profile| profile.font_face());
+        let font_size = default_profile.and_then(|profile| profile.font_size());
+        let font = match (font_face, font_size) {
+            (Some(face), Some(size)) => Some(format!("{} at {}pt", quoted(face), size)),
+            (Some(face), None) => Some(quoted(face)),
+            (None, Some(size)) => Some(format!("size {}pt", size)),
+            (None, None) => None,
+        };
+        if let Some(font) = font {
+            config.push_str(&format!(
+                "\n// Windows Terminal draws the text in {}: zellij uses the font of the terminal it runs in, keep it in the profile that starts zellij\n",
+                font
+            ));
+        }
+
+        Import {
+            themes: themes_document.to_string(),
+            layout: Some(shells_layout(&shells)).filter(|_| !shells.is_empty()),
+            config,
+            skipped_profiles,
+        }
+    }
+}
+
+/// The command a profile runs, dynamic profiles have none and get the one of their generator
+fn profile_shell(profile: &Profile) -> Result<DetectedShell, String> {
+    let name = profile.name.clone().unwrap_or_default();
+    let mut cwd = profile.starting_directory.as_deref().map(|dir| {
+        PathBuf::from(shellexpand::tilde(&expand_environment_variables(dir)).into_owned())
+    });
+    let mut command_line = match (&profile.commandline, &profile.source) {
+        (Some(commandline), _) => split_command_line(&expand_environment_variables(commandline)),
+        (None, Some(source)) if source == "Windows.Terminal.PowershellCore" => {
+            vec!["pwsh.exe".to_owned()]
+        },
+        (None, Some(source)) if source == "Windows.Terminal.Wsl" || source == "Microsoft.WSL" => {
+            let mut command_line = vec!["wsl.exe".to_owned(), "-d".to_owned(), name.clone()];
+            // the starting directory of a distribution is a Linux one, `~` is the Linux home
+            if let Some(dir) = &profile.starting_directory {
+                command_line.extend(["--cd".to_owned(), dir.clone()]);
+                cwd = None;
+            }
+            command_line
+        },
+        (None, Some(source)) => {
+            return Err(format!("the profiles of {} have no command line", source));
+        },
+        // profiles without a command line start cmd.exe
+        (None, None) => vec![expand_environment_variables(
+            "%SystemRoot%\\System32\\cmd.exe",
+        )],
+    };
+    if command_line.is_empty() {
+        return Err("the command line is empty".to_owned());
+    }
+    let command = PathBuf::from(command_line.remove(0));
+    Ok(DetectedShell {
+        name,
+        command,
+        args: command_line,
+        cwd,
+    })
+}
+
+/// Replaces `%NAME%` with the environment variable, leaving those that are not set as they are
+fn expand_environment_variables(text: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        expanded.push_str(&rest[..start]);
+        let after_start = &rest[start + 1..];
+        match after_start.find('%') {
+            Some(end) => {
+                let name = &after_start[..end];
+                match env::var(name) {
+                    Ok(value) if !name.is_empty() => {
+                        expanded.push_str(&value);
+                        rest = &after_start[end + 1..];
+                    },
+                    _ => {
+                        // the closing '%' may open the next variable
+                        expanded.push('%');
+                        expanded.push_str(name);
+                        rest = &after_start[end..];
+                    },
+                }
+            },
+            None => {
+                expanded.push_str(&rest[start..]);
+                rest = "";
+            },
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Splits a command line into the program and its arguments the way `CommandLineToArgvW` does:
+/// double quotes group, and backslashes only escape the double quotes they precede
+fn split_command_line(command_line: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut arg = String::new();
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    let mut backslashes = 0;
+    for c in command_line.chars() {
+        match c {
+            '\\' => {
+                backslashes += 1;
+                in_arg = true;
+                continue;
+            },
+            '"' => {
+                arg.push_str(&"\\".repeat(backslashes / 2));
+                if backslashes % 2 == 1 {
+                    arg.push('"');
+                } else {
+                    in_quotes = !in_quotes;
+                }
+                in_arg = true;
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                arg.push_str(&"\\".repeat(backslashes));
+                if in_arg {
+                    args.push(std::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            },
+            c => {
+                arg.push_str(&"\\".repeat(backslashes));
+                arg.push(c);
+                in_arg = true;
+            },
+        }
+        backslashes = 0;
+    }
+    arg.push_str(&"\\".repeat(backslashes));
+    if in_arg {
+        args.push(arg);
+    }
+    args
+}
+
+/// Removes the `//` and `/* */` comments and the trailing commas Windows Terminal accepts in
+/// settings.json and JSON does not
+fn strip_json_comments(json: &str) -> String {
+    let mut stripped = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            if c == '\\' {
+                stripped.extend(chars.next());
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(c);
+            },
+            ('/', Some('/')) => {
+                while chars.peek().map(|c| *c != '\n').unwrap_or(false) {
+                    chars.next();
+                }
+            },
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+            },
+            (']', _) | ('}', _) => {
+                let content_end = stripped.trim_end().len();
+                if stripped[..content_end].ends_with(',') {
+                    stripped.remove(content_end - 1);
+                }
+                stripped.push(c);
+            },
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Where Windows Terminal (the Store, Preview or unpackaged one) keeps its settings
+pub fn default_settings_path() -> Option<PathBuf> {
+    let local_app_data = PathBuf::from(env::var_os("LOCALAPPDATA")?);
+    [
+        "Packages\\Microsoft.WindowsTerminal_8wekyb3d8bbwe\\LocalState\\settings.json",
+        "Packages\\Microsoft.WindowsTerminalPreview_8wekyb3d8bbwe\\LocalState\\settings.json",
+        "Microsoft\\Windows Terminal\\settings.json",
+    ]
+    .iter()
+    .map(|path| local_app_data.join(path))
+    .find(|path| path.is_file())
+}
+
+/// Reads `settings` and writes the themes, layout and config made from it, asking before
+/// replacing files
+pub fn run(settings: &Path, config_file: &Path, config_dir: &Path) -> io::Result<()> {
+    let json = fs::read_to_string(settings)?;
+    let import = WindowsTerminalSettings::from_json(&json)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        .import(settings);
+    for (profile, reason) in &import.skipped_profiles {
+        println!("Skipped the profile '{}': {}", profile, reason);
+    }
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    if !import.themes.trim().is_empty() {
+        let theme_dir = config_dir.join("themes");
+        fs::create_dir_all(&theme_dir)?;
+        write_file(
+            &mut input,
+            &theme_dir.join(format!("{}.kdl", LAYOUT_NAME)),
+            &import.themes,
+        )?;
+    }
+    if let Some(layout) = &import.layout {
+        let layout_dir = config_dir.join("layouts");
+        fs::create_dir_all(&layout_dir)?;
+        write_file(
+            &mut input,
+            &layout_dir.join(format!("{}.kdl", LAYOUT_NAME)),
+            layout,
+        )?;
+    }
+    if let Some(config_dir) = config_file.parent() {
+        fs::create_dir_all(config_dir)?;
+    }
+    write_file(&mut input, config_file, &import.config)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod windows_terminal_tests {
+    use super::*;
+    use crate::input::config::Config;
+    use crate::input::layout::Layout;
+
+    const SETTINGS: &str = r##"
+    // This file was initially generated by Windows Terminal
+    {
+        "$schema": "https://aka.ms/terminal-profiles-schema",
+        "defaultProfile": "{574e775e-4f2a-5b96-ac1e-a2962a402336}",
+        "profiles": {
+            "defaults": {
+                "font": { "face": "Cascadia Code", "size": 11 },
+            },
+            "list": [
+                {
+                    "guid": "{0caa0dad-35be-5f56-a8ff-afceeeaa6101}",
+                    "name": "Command Prompt",
+                    "commandline": "%SystemRoot%\\System32\\cmd.exe",
+                    "hidden": false
+                },
+                {
+                    "guid": "{574e775e-4f2a-5b96-ac1e-a2962a402336}",
+                    "name": "PowerShell",
+                    "source": "Windows.Terminal.PowershellCore",
+                    "colorScheme": "Night Owl",
+                    "startingDirectory": "C:/src"
+                },
+                {
+                    "name": "Ubuntu",
+                    "source": "Windows.Terminal.Wsl",
+                    "colorScheme": { "light": "One Half Light", "dark": "One Half Dark" },
+                    "startingDirectory": "~"
+                },
+                { "name": "Azure Cloud Shell", "source": "Windows.Terminal.Azure" },
+                { "name": "Old", "commandline": "old.exe", "hidden": true }, /* gone */
+            ]
+        },
+        "schemes": [
+            {
+                "name": "Night Owl",
+                "foreground": "#D6DEEB", "background": "#011627",
+                "black": "#011627", "red": "#EF5350", "green": "#22DA6E", "yellow": "#ADDB67",
+                "blue": "#82AAFF", "purple": "#C792EA", "cyan": "#21C7A8", "white": "#FFFFFF",
+                "brightBlack": "#575656", "brightRed": "#EF5350", "brightGreen": "#22DA6E",
+                "brightYellow": "#FFEB95", "brightBlue": "#82AAFF", "brightPurple": "#C792EA",
+                "brightCyan": "#7FDBCA", "brightWhite": "#FFFFFF"
+            }
+        ]
+    }
+    "##;
+
+    #[test]
+    fn settings_with_comments_and_trailing_commas_are_read() {
+        let settings = WindowsTerminalSettings::from_json(SETTINGS).unwrap();
+        assert_eq!(settings.profiles().len(), 5);
+        assert_eq!(settings.schemes.len(), 1);
+        assert_eq!(
+            strip_json_comments(r#"{"url": "https://example.com", "a": [1, 2, ], }"#),
+            r#"{"url": "https://example.com", "a": [1, 2 ] }"#
+        );
+    }
+
+    #[test]
+    fn import_is_a_valid_config_layout_and_themes() {
+        let settings = WindowsTerminalSettings::from_json(SETTINGS).unwrap();
+        let import = settings.import(Path::new("settings.json"));
+
+        let config = Config::from_kdl(&import.config, None).unwrap();
+        assert_eq!(config.options.theme, Some("Night Owl".to_owned()));
+        assert_eq!(
+            config.options.default_shell,
+            Some(PathBuf::from("pwsh.exe"))
+        );
+        assert_eq!(
+            config.options.default_layout,
+            Some(PathBuf::from(LAYOUT_NAME))
+        );
+        assert!(import.config.contains("\"Cascadia Code\" at 11pt"));
+
+        let themes = Config::from_kdl(&import.themes, None).unwrap().themes;
+        let mut theme_names: Vec<&String> = themes.inner().keys().collect();
+        theme_names.sort();
+        assert_eq!(theme_names, vec!["Campbell", "Night Owl", "One Half Dark"]);
+
+        let layout = Layout::from_kdl(import.layout.as_ref().unwrap(), None, None, None).unwrap();
+        let tab_names: Vec<Option<String>> = layout
+            .tabs
+            .iter()
+            .map(|(name, _, _)| name.clone())
+            .collect();
+        assert_eq!(
+            tab_names,
+            vec![
+                Some("PowerShell".to_owned()),
+                Some("Command Prompt".to_owned()),
+                Some("Ubuntu".to_owned()),
+            ]
+        );
+        assert_eq!(
+            import.skipped_profiles,
+            vec![(
+                "Azure Cloud Shell".to_owned(),
+                "the profiles of Windows.Terminal.Azure have no command line".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn command_lines_are_split_like_windows_does() {
+        assert_eq!(
+            split_command_line(r#""C:\Program Files\Git\bin\bash.exe" -i -l"#),
+            vec![r"C:\Program Files\Git\bin\bash.exe", "-i", "-l"]
+        );
+        assert_eq!(
+            split_command_line(r#"cmd.exe /k "echo \"hi\"" C:\dir\ "#),
+            vec!["cmd.exe", "/k", r#"echo "hi""#, r"C:\dir\"]
+        );
+        assert_eq!(split_command_line("  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unset_environment_variables_are_left_as_they_are() {
+        assert_eq!(
+            expand_environment_variables("100% %ZELLIJ_SURELY_UNSET_VARIABLE%\\x"),
+            "100% %ZELLIJ_SURELY_UNSET_VARIABLE%\\x"
+        );
+    }
+}